@@ -177,6 +177,7 @@ fn run_seed_once(seed: u64, shutdown_at_ms: i64) -> TestResult<(u64, DateTime<Ut
             min_compaction_sources: 2,
             max_compaction_sources: 999,
             include_size_threshold: 4.0,
+            ..Default::default()
         }
         .into(),
         ..CompactorOptions::default()
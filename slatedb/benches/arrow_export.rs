@@ -0,0 +1,90 @@
+// our microbenchmarks use pprof, but it doesn't work on windows
+#![cfg(not(windows))]
+
+//! Compares exporting a scan as Arrow record batches with copying its entries
+//! out one at a time.
+//!
+//! - 100k 16-byte keys with 100-byte values, held in the memtable so the
+//!   benchmark measures the export rather than SST reads.
+//! - `per_entry` copies each key, value and timestamp into vectors, the way a
+//!   caller without the adapter would before building columns.
+//! - `arrow_batches` exports the same scan with `ArrowBatchIterator` at a few
+//!   batch sizes.
+//!
+//! Run: cargo bench -p slatedb --bench arrow_export --features arrow
+
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use object_store::memory::InMemory;
+use pprof::criterion::{Output, PProfProfiler};
+use slatedb::arrow_export::ArrowBatchIterator;
+use slatedb::Db;
+use tokio::runtime::Runtime;
+
+const NUM_KEYS: usize = 100_000;
+const VALUE_LEN: usize = 100;
+const BATCH_SIZES: [usize; 3] = [256, 4096, 65536];
+
+async fn build_db() -> Db {
+    let db = Db::open("/bench/arrow_export", Arc::new(InMemory::new()))
+        .await
+        .expect("failed to open db");
+    let value = vec![b'v'; VALUE_LEN];
+    let mut batch = slatedb::WriteBatch::new();
+    for i in 0..NUM_KEYS {
+        batch.put(format!("key{:013}", i), &value);
+    }
+    db.write(batch).await.expect("write failed");
+    db
+}
+
+fn bench_arrow_export(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("failed to create runtime");
+    let db = runtime.block_on(build_db());
+
+    let mut group = c.benchmark_group("arrow_export");
+    group.sample_size(20);
+    group.throughput(Throughput::Elements(NUM_KEYS as u64));
+
+    group.bench_function("per_entry", |b| {
+        b.to_async(&runtime).iter(|| async {
+            let mut iter = db.scan::<&[u8], _>(..).await.expect("scan failed");
+            let mut keys = Vec::new();
+            let mut values = Vec::new();
+            let mut create_ts = Vec::new();
+            while let Some(kv) = iter.next().await.expect("iterator next failed") {
+                keys.push(kv.key.to_vec());
+                values.push(kv.value.to_vec());
+                create_ts.push(kv.create_ts);
+            }
+            assert_eq!(keys.len(), NUM_KEYS);
+        });
+    });
+
+    for batch_size in BATCH_SIZES {
+        group.bench_function(BenchmarkId::new("arrow_batches", batch_size), |b| {
+            b.to_async(&runtime).iter(|| async {
+                let iter = db.scan::<&[u8], _>(..).await.expect("scan failed");
+                let mut batches = ArrowBatchIterator::new(iter, batch_size);
+                let mut rows = 0usize;
+                while let Some(batch) = batches.next().await.expect("export failed") {
+                    rows += batch.num_rows();
+                }
+                assert_eq!(rows, NUM_KEYS);
+            });
+        });
+    }
+
+    group.finish();
+    runtime.block_on(async { db.close().await.expect("close failed") });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .with_profiler(PProfProfiler::new(100, Output::Protobuf));
+    targets = bench_arrow_export
+}
+
+criterion_main!(benches);
@@ -0,0 +1,50 @@
+// our microbenchmarks use pprof, but it doesn't work on windows
+#![cfg(not(windows))]
+
+// Run with: cargo bench --features bench-internal --bench memtable_front_coding
+// The `bench-internal` feature gates `slatedb::mem_table_benches`.
+// It measures a full scan over a memtable whose keys share a long prefix,
+// with the keys stored as-is and front-coded, and prints the number of bytes
+// holding the keys in each case.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use pprof::criterion::{Output, PProfProfiler};
+use slatedb::mem_table_benches::MemtableFrontCodingBenchConfig;
+
+const NUM_ENTRIES: usize = 100_000;
+
+// the key sizes are reported on stdout next to criterion's timings
+#[allow(clippy::redundant_closure, clippy::disallowed_macros)]
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memtable_front_coding_scan");
+    group.throughput(Throughput::Elements(NUM_ENTRIES as u64));
+    for prefix_len in [16, 64] {
+        for (mode, front_coded) in [("raw", false), ("front_coded", true)] {
+            let key_bytes = slatedb::mem_table_benches::memtable_front_coding_bench(
+                MemtableFrontCodingBenchConfig {
+                    num_entries: NUM_ENTRIES,
+                    prefix_len,
+                    front_coded,
+                },
+                |inner| {
+                    group.bench_function(format!("prefix_{prefix_len}/{mode}"), |b| {
+                        b.iter(|| inner());
+                    });
+                },
+            );
+            println!("prefix_{prefix_len}/{mode}: {key_bytes} bytes of keys");
+        }
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .sample_size(10)
+        // This only runs when `--profile-time <num_seconds>` is set
+        .with_profiler(PProfProfiler::new(100, Output::Protobuf));
+    targets = criterion_benchmark
+}
+
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+// our microbenchmarks use pprof, but it doesn't work on windows
+#![cfg(not(windows))]
+
+// Run with: cargo bench --features bench-internal --bench memtable_insert
+// The `bench-internal` feature gates `slatedb::mem_table_benches`.
+// It measures sequential insert throughput into a skip map memtable and an
+// append-only memtable.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use pprof::criterion::{Output, PProfProfiler};
+use slatedb::config::{MemtableType, OutOfOrderWritePolicy};
+use slatedb::mem_table_benches::MemtableInsertBenchConfig;
+
+const NUM_ENTRIES: usize = 100_000;
+
+#[allow(clippy::redundant_closure)]
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memtable_sequential_insert");
+    group.throughput(Throughput::Elements(NUM_ENTRIES as u64));
+    for (name, memtable_type) in [
+        ("skip_map", MemtableType::SkipMap),
+        (
+            "append_only",
+            MemtableType::AppendOnly {
+                on_out_of_order: OutOfOrderWritePolicy::Reject,
+            },
+        ),
+    ] {
+        slatedb::mem_table_benches::memtable_insert_bench(
+            MemtableInsertBenchConfig {
+                num_entries: NUM_ENTRIES,
+                value_size: 64,
+                memtable_type,
+            },
+            |inner| {
+                group.bench_function(name, |b| {
+                    b.iter(|| inner());
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .sample_size(10)
+        // This only runs when `--profile-time <num_seconds>` is set
+        .with_profiler(PProfProfiler::new(100, Output::Protobuf));
+    targets = criterion_benchmark
+}
+
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+// our microbenchmarks use pprof, but it doesn't work on windows
+#![cfg(not(windows))]
+
+// Run with: cargo bench --features bench-internal --bench memtable_scan
+// The `bench-internal` feature gates `slatedb::mem_table_benches`.
+// It measures an aggregate scan over a skip map memtable and an append-only
+// memtable, once cloning every entry out of an iterator and once borrowing
+// entries in place.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use pprof::criterion::{Output, PProfProfiler};
+use slatedb::config::{MemtableType, OutOfOrderWritePolicy};
+use slatedb::mem_table_benches::MemtableScanBenchConfig;
+
+const NUM_ENTRIES: usize = 100_000;
+
+#[allow(clippy::redundant_closure)]
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memtable_aggregate_scan");
+    group.throughput(Throughput::Elements(NUM_ENTRIES as u64));
+    for (type_name, memtable_type) in [
+        ("skip_map", MemtableType::SkipMap),
+        (
+            "append_only",
+            MemtableType::AppendOnly {
+                on_out_of_order: OutOfOrderWritePolicy::Reject,
+            },
+        ),
+    ] {
+        for (mode, borrowed) in [("owned", false), ("borrowed", true)] {
+            slatedb::mem_table_benches::memtable_scan_bench(
+                MemtableScanBenchConfig {
+                    num_entries: NUM_ENTRIES,
+                    value_size: 64,
+                    memtable_type,
+                    borrowed,
+                },
+                |inner| {
+                    group.bench_function(format!("{type_name}/{mode}"), |b| {
+                        b.iter(|| inner());
+                    });
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .sample_size(10)
+        // This only runs when `--profile-time <num_seconds>` is set
+        .with_profiler(PProfProfiler::new(100, Output::Protobuf));
+    targets = criterion_benchmark
+}
+
+criterion_main!(benches);
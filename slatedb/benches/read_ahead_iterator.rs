@@ -0,0 +1,51 @@
+// our microbenchmarks use pprof, but it doesn't work on windows
+#![cfg(not(windows))]
+
+// Run with: cargo bench --features bench-internal --bench read_ahead_iterator
+// The `bench-internal` feature gates `slatedb::read_ahead_iterator_benches`.
+// It simulates an SST iterator over a high-latency store and compares
+// draining it directly against draining it through a `ReadAheadIterator`.
+
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use pprof::criterion::{Output, PProfProfiler};
+use slatedb::read_ahead_iterator_benches::ReadAheadIteratorBenchConfig;
+
+#[allow(clippy::redundant_closure)]
+fn criterion_benchmark(c: &mut Criterion) {
+    // 16 blocks of 64 entries, 5ms per block fetch and 5ms of consumer work
+    // per block. Without read-ahead the two latencies add up; with a depth of
+    // at least one block they overlap.
+    for (name, depth) in [
+        ("read_ahead_iterator_naive", None),
+        ("read_ahead_iterator_depth_64", Some(64)),
+        ("read_ahead_iterator_depth_256", Some(256)),
+    ] {
+        slatedb::read_ahead_iterator_benches::read_ahead_iterator_bench(
+            ReadAheadIteratorBenchConfig {
+                num_entries: 1024,
+                entries_per_block: 64,
+                fetch_latency: Duration::from_millis(5),
+                consumer_latency: Duration::from_millis(5),
+                depth,
+            },
+            |inner| {
+                c.bench_function(name, |b| {
+                    b.iter(|| inner());
+                });
+            },
+        );
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .sample_size(10)
+        // This only runs when `--profile-time <num_seconds>` is set
+        .with_profiler(PProfProfiler::new(100, Output::Protobuf));
+    targets = criterion_benchmark
+}
+
+criterion_main!(benches);
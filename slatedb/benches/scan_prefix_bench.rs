@@ -139,6 +139,7 @@ async fn populate(db: &Db) {
     let write_opts = WriteOptions {
         await_durable: false,
         seqnum: 0,
+        tenant: None,
     };
     let put_opts = PutOptions::default();
     let mut next_version = [0u64; NUM_PREFIXES];
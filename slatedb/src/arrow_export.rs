@@ -0,0 +1,147 @@
+//! Exporting scan results as Arrow record batches.
+//!
+//! [`ArrowBatchIterator`] wraps a [`DbIterator`] and groups its entries into
+//! [`RecordBatch`]es with the columns of [`ArrowBatchIterator::schema`], for
+//! handing scans to columnar analytics engines. Deleted keys are not exported,
+//! since the wrapped scan doesn't return them.
+
+use std::sync::Arc;
+
+use arrow_array::builder::{BinaryBuilder, Int64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+
+use crate::db_iter::DbIterator;
+
+/// The name of the column holding the keys of the exported entries.
+pub const KEY_COLUMN: &str = "key";
+/// The name of the column holding the values of the exported entries.
+pub const VALUE_COLUMN: &str = "value";
+/// The name of the column holding the creation timestamps of the exported
+/// entries, as read from the db's system clock when they were written.
+pub const CREATE_TS_COLUMN: &str = "create_ts";
+
+/// Groups the entries of a [`DbIterator`] into [`RecordBatch`]es of up to a
+/// fixed number of rows, in the scan's order.
+pub struct ArrowBatchIterator {
+    iter: DbIterator,
+    batch_size: usize,
+    schema: SchemaRef,
+}
+
+impl ArrowBatchIterator {
+    /// Wraps `iter`, emitting batches of up to `batch_size` rows. A
+    /// `batch_size` of 0 is treated as 1.
+    pub fn new(iter: DbIterator, batch_size: usize) -> Self {
+        Self {
+            iter,
+            batch_size: batch_size.max(1),
+            schema: Self::schema(),
+        }
+    }
+
+    /// Returns the schema of the emitted batches: a binary `key` column, a
+    /// binary `value` column and an int64 `create_ts` column, none of which
+    /// hold nulls.
+    pub fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new(KEY_COLUMN, DataType::Binary, false),
+            Field::new(VALUE_COLUMN, DataType::Binary, false),
+            Field::new(CREATE_TS_COLUMN, DataType::Int64, false),
+        ]))
+    }
+
+    /// Returns the next batch, or `None` once the scan is exhausted. Every
+    /// batch but the last holds exactly `batch_size` rows, and the last one
+    /// holds the remaining rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error`] if the wrapped iterator fails. The rows read
+    /// for the batch being built when the error occurred are discarded.
+    pub async fn next(&mut self) -> Result<Option<RecordBatch>, crate::Error> {
+        let mut keys = BinaryBuilder::with_capacity(self.batch_size, 0);
+        let mut values = BinaryBuilder::with_capacity(self.batch_size, 0);
+        let mut create_ts = Int64Builder::with_capacity(self.batch_size);
+        let mut rows = 0;
+        while rows < self.batch_size {
+            let Some(kv) = self.iter.next().await? else {
+                break;
+            };
+            keys.append_value(&kv.key);
+            values.append_value(&kv.value);
+            create_ts.append_value(kv.create_ts);
+            rows += 1;
+        }
+        if rows == 0 {
+            return Ok(None);
+        }
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(keys.finish()),
+            Arc::new(values.finish()),
+            Arc::new(create_ts.finish()),
+        ];
+        RecordBatch::try_new(self.schema.clone(), columns)
+            .map(Some)
+            .map_err(|err| crate::Error::internal(format!("failed to build record batch: {err}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Db;
+    use arrow_array::{Array, BinaryArray, Int64Array};
+    use object_store::memory::InMemory;
+
+    fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> &'a T {
+        batch
+            .column_by_name(name)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<T>()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_emit_full_batches_then_the_remaining_rows() {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        for i in 0..7u8 {
+            db.put([b'k', i], [b'v', i]).await.unwrap();
+        }
+        db.delete([b'k', 3]).await.unwrap();
+
+        let iter = db.scan::<&[u8], _>(..).await.unwrap();
+        let mut batches = ArrowBatchIterator::new(iter, 4);
+        let first = batches.next().await.unwrap().unwrap();
+        let second = batches.next().await.unwrap().unwrap();
+        assert!(batches.next().await.unwrap().is_none());
+
+        assert_eq!(first.schema(), ArrowBatchIterator::schema());
+        assert_eq!(first.num_rows(), 4);
+        assert_eq!(second.num_rows(), 2);
+        let keys: Vec<_> = [&first, &second]
+            .iter()
+            .flat_map(|batch| column::<BinaryArray>(batch, KEY_COLUMN).iter())
+            .map(|key| key.unwrap()[1])
+            .collect();
+        assert_eq!(keys, vec![0, 1, 2, 4, 5, 6]);
+        let values = column::<BinaryArray>(&second, VALUE_COLUMN);
+        assert_eq!(values.value(0), [b'v', 5]);
+        assert_eq!(values.value(1), [b'v', 6]);
+        let create_ts = column::<Int64Array>(&first, CREATE_TS_COLUMN);
+        assert_eq!(create_ts.null_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_return_none_for_an_empty_scan() {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        let iter = db.scan::<&[u8], _>(..).await.unwrap();
+        let mut batches = ArrowBatchIterator::new(iter, 0);
+        assert!(batches.next().await.unwrap().is_none());
+    }
+}
@@ -4,18 +4,23 @@
 //! collection of write operations (puts and/or deletes) that are applied
 //! atomically to the database.
 
-use crate::config::{MergeOptions, PutOptions};
+use crate::config::{MergeOptions, PutOptions, Ttl};
+use crate::conflict_resolver::{ConflictResolver, ConflictWinner};
 use crate::error::SlateDBError;
 use crate::iter::{IterationOrder, RowEntryIterator};
 use crate::mem_table::{KVTableInternalKeyRange, SequencedKey};
 use crate::merge_operator::{MergeOperatorIterator, MergeOperatorType};
 use crate::prefix_extractor::{PrefixExtractor, PrefixTarget};
+use crate::rand::DbRand;
+use crate::read_repair::ReadRepairCheck;
 use crate::types::{RowEntry, ValueDeletable};
 use async_trait::async_trait;
 use bytes::Bytes;
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use rand::Rng;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::iter::Peekable;
 use std::ops::RangeBounds;
+use std::time::Duration;
 use uuid::Uuid;
 
 /// A batch of write operations (puts and/or deletes). All operations in the
@@ -58,6 +63,15 @@ pub struct WriteBatch {
     /// atomically by the oracle when the batch is committed).
     pub(crate) write_idx: u64,
     pub(crate) merge_op_count: usize,
+    /// Set on the batch of a read repair, which the write loop drops if the
+    /// key has a newer version than the one that was read.
+    pub(crate) read_repair: Option<ReadRepairCheck>,
+    /// The values put to a key earlier in this batch and replaced by its
+    /// current put, in the order they were put, for the [`ConflictResolver`]
+    /// to pick from when the batch is written. Only keys put more than once
+    /// in the batch have an entry, so a batch that puts each key once never
+    /// touches the map.
+    pub(crate) intra_batch_puts: HashMap<Bytes, Vec<(Bytes, PutOptions)>>,
 }
 
 impl Default for WriteBatch {
@@ -70,7 +84,8 @@ impl Default for WriteBatch {
 #[derive(PartialEq, Clone)]
 pub(crate) enum WriteOp {
     Put(Bytes, Bytes, PutOptions),
-    Delete(Bytes),
+    /// A delete, with the retention period of its tombstone, if any.
+    Delete(Bytes, Option<Duration>),
     Merge(Bytes, Bytes, MergeOptions),
 }
 
@@ -90,10 +105,14 @@ impl std::fmt::Debug for WriteOp {
                 let value = trunc(value);
                 write!(f, "Put({key}, {value}, {:?})", options)
             }
-            WriteOp::Delete(key) => {
+            WriteOp::Delete(key, None) => {
                 let key = trunc(key);
                 write!(f, "Delete({key})")
             }
+            WriteOp::Delete(key, Some(retention)) => {
+                let key = trunc(key);
+                write!(f, "Delete({key}, retention={retention:?})")
+            }
             WriteOp::Merge(key, value, options) => {
                 let key = trunc(key);
                 let value = trunc(value);
@@ -124,7 +143,7 @@ impl WriteOp {
                     expire_ts,
                 )
             }
-            WriteOp::Delete(key) => RowEntry::new(
+            WriteOp::Delete(key, _) => RowEntry::new(
                 key.clone(),
                 ValueDeletable::Tombstone,
                 seq,
@@ -149,28 +168,29 @@ impl WriteBatch {
             txn_id: None,
             write_idx: 0,
             merge_op_count: 0,
+            read_repair: None,
+            intra_batch_puts: HashMap::new(),
         }
     }
 
-    /// Remove all existing ops for the given user key from the batch.
-    fn remove_ops_by_key(&mut self, key: &Bytes) {
-        // Find the contiguous range of entries for this user key and delete them in place.
-        let start = SequencedKey::new(key.clone(), u64::MAX);
-        let end = SequencedKey::new(key.clone(), 0);
-
+    /// Remove all existing ops for the given user key from the batch, and
+    /// return them from the last one written.
+    fn remove_ops_by_key(&mut self, key: &Bytes) -> Vec<WriteOp> {
         // Collect keys to remove
-        let keys_to_remove: Vec<SequencedKey> = self
-            .ops
-            .range(start..=end)
-            .map(|(k, _)| k.clone())
-            .collect();
+        let keys_to_remove: Vec<SequencedKey> =
+            self.ops_by_key(key).map(|(k, _)| k.clone()).collect();
 
         // Remove them
+        let mut removed = Vec::with_capacity(keys_to_remove.len());
         for k in keys_to_remove {
-            if let Some(WriteOp::Merge(..)) = self.ops.remove(&k) {
-                self.merge_op_count -= 1
+            if let Some(op) = self.ops.remove(&k) {
+                if let WriteOp::Merge(..) = op {
+                    self.merge_op_count -= 1
+                }
+                removed.push(op);
             }
         }
+        removed
     }
 
     pub(crate) fn with_txn_id(self, txn_id: Uuid) -> Self {
@@ -179,6 +199,43 @@ impl WriteBatch {
             txn_id: Some(txn_id),
             write_idx: self.write_idx,
             merge_op_count: self.merge_op_count,
+            read_repair: self.read_repair,
+            intra_batch_puts: self.intra_batch_puts,
+        }
+    }
+
+    /// Returns the ops for the given user key, from the last one written.
+    fn ops_by_key(&self, key: &Bytes) -> impl Iterator<Item = (&SequencedKey, &WriteOp)> {
+        let start = SequencedKey::new(key.clone(), u64::MAX);
+        let end = SequencedKey::new(key.clone(), 0);
+        self.ops.range(start..=end)
+    }
+
+    /// Replaces the value of each key that this batch put more than once in a
+    /// row with the one `resolver` keeps. See [`ConflictResolver`].
+    pub(crate) fn resolve_intra_batch_puts(&mut self, resolver: &dyn ConflictResolver) {
+        for (key, replaced) in std::mem::take(&mut self.intra_batch_puts) {
+            // merges made after the puts are applied to the value kept
+            let Some((seq_key, WriteOp::Put(_, value, options))) = self
+                .ops_by_key(&key)
+                .find(|(_, op)| matches!(op, WriteOp::Put(..)))
+            else {
+                continue;
+            };
+            let seq_key = seq_key.clone();
+            let last = (value.clone(), options.clone());
+            let mut puts = replaced.into_iter().chain(std::iter::once(last));
+            let Some(first) = puts.next() else {
+                continue;
+            };
+            let (value, options) = puts.fold(first, |kept, put| {
+                match resolver.resolve(&key, &kept.0, &put.0) {
+                    ConflictWinner::Earlier => kept,
+                    ConflictWinner::Later => put,
+                }
+            });
+            self.ops
+                .insert(seq_key, WriteOp::Put(key.clone(), value, options));
         }
     }
 
@@ -260,7 +317,20 @@ impl WriteBatch {
 
         // put will overwrite the existing key so we can safely
         // remove all previous entries.
-        self.remove_ops_by_key(&key);
+        let removed = self.remove_ops_by_key(&key);
+        // keep the value this put replaces for the conflict resolver, if the
+        // key's only op was a put
+        match removed.as_slice() {
+            [] => {}
+            [WriteOp::Put(_, value, options)] => self
+                .intra_batch_puts
+                .entry(key.clone())
+                .or_default()
+                .push((value.clone(), options.clone())),
+            _ => {
+                self.intra_batch_puts.remove(&key);
+            }
+        }
         self.ops.insert(
             SequencedKey::new(key.clone(), self.write_idx),
             WriteOp::Put(key, value, options.clone()),
@@ -298,16 +368,35 @@ impl WriteBatch {
 
     /// Delete a key-value pair into the batch. Keys must not be empty.
     pub fn delete<K: AsRef<[u8]>>(&mut self, key: K) {
+        self.delete_op(key, None);
+    }
+
+    /// Delete a key-value pair into the batch, retaining the tombstone for at
+    /// least `retention`. Keys must not be empty.
+    ///
+    /// The tombstone is stamped with an expiry of its write time plus
+    /// `retention`. Compaction drops a tombstone once nothing older remains for
+    /// it to shadow, but not before its expiry, so the delete stays visible to
+    /// every reader (e.g. replicas reading an older checkpoint) for the whole
+    /// retention period. Once the tombstone expires it is collected like any
+    /// other. Reads treat it as a delete whether or not it has expired.
+    pub fn delete_with_retention<K: AsRef<[u8]>>(&mut self, key: K, retention: Duration) {
+        self.delete_op(key, Some(retention));
+    }
+
+    fn delete_op<K: AsRef<[u8]>>(&mut self, key: K, retention: Option<Duration>) {
         self.assert_kv(&key, &[]);
 
         let key = Bytes::copy_from_slice(key.as_ref());
 
         // delete will overwrite the existing key so we can safely
         // remove all previous entries.
-        self.remove_ops_by_key(&key);
+        if !self.remove_ops_by_key(&key).is_empty() {
+            self.intra_batch_puts.remove(&key);
+        }
         self.ops.insert(
             SequencedKey::new(key.clone(), self.write_idx),
-            WriteOp::Delete(key),
+            WriteOp::Delete(key, retention),
         );
 
         self.write_idx += 1;
@@ -341,6 +430,7 @@ impl WriteBatch {
         seq: u64,
         now: i64,
         default_ttl: Option<u64>,
+        ttl_jitter: Option<TtlJitter<'_>>,
         merger: Option<MergeOperatorType>,
         extractor: Option<&dyn PrefixExtractor>,
     ) -> Result<(Vec<RowEntry>, BTreeSet<Bytes>), SlateDBError> {
@@ -355,6 +445,7 @@ impl WriteBatch {
             seq,
             now,
             default_ttl,
+            ttl_jitter,
         ));
         if let Some(ref merge_operator) = merger {
             it = Box::new(MergeOperatorIterator::new(
@@ -387,6 +478,12 @@ impl WriteBatch {
 }
 
 /// Iterator over `WriteBatch` entries.
+///
+/// Entries are returned in key order for the requested [`IterationOrder`]. A
+/// key can hold several entries when merges were written to it, and those are
+/// always returned from the newest write to the oldest, whatever the key order.
+/// All entries of a batch share one sequence number, so consumers such as the
+/// merge operator iterator rely on this order to tell the writes apart.
 pub(crate) struct WriteBatchIterator {
     iter: Peekable<Box<dyn Iterator<Item = (SequencedKey, RowEntry)> + Send + Sync>>,
     ordering: IterationOrder,
@@ -406,7 +503,7 @@ impl WriteBatchIterator {
             .collect();
 
         if matches!(ordering, IterationOrder::Descending) {
-            entries.reverse();
+            reverse_key_order(&mut entries);
         }
 
         let iter: Box<dyn Iterator<Item = (SequencedKey, RowEntry)> + Send + Sync> =
@@ -425,23 +522,43 @@ impl WriteBatchIterator {
         seq: u64,
         now: i64,
         default_ttl: Option<u64>,
+        ttl_jitter: Option<TtlJitter<'_>>,
     ) -> Self {
         let range = KVTableInternalKeyRange::from(range);
         let mut entries: Vec<(SequencedKey, RowEntry)> = batch
             .ops
             .range(range)
             .map(|(k, v)| {
-                let expire_ts = match v {
-                    WriteOp::Put(_, _, opts) => opts.expire_ts_from(default_ttl, now),
-                    WriteOp::Merge(_, _, opts) => opts.expire_ts_from(default_ttl, now),
-                    WriteOp::Delete(_) => None,
+                let (ttl, expire_ts) = match v {
+                    WriteOp::Put(_, _, opts) => {
+                        (Some(&opts.ttl), opts.expire_ts_from(default_ttl, now))
+                    }
+                    WriteOp::Merge(_, _, opts) => {
+                        (Some(&opts.ttl), opts.expire_ts_from(default_ttl, now))
+                    }
+                    // a tombstone's retention is a guaranteed minimum, so it
+                    // isn't jittered
+                    WriteOp::Delete(_, retention) => (
+                        None,
+                        retention.map(|retention| {
+                            now.saturating_add(retention.as_millis().min(i64::MAX as u128) as i64)
+                        }),
+                    ),
+                };
+                let expire_ts = match (&ttl_jitter, ttl, expire_ts) {
+                    (Some(jitter), Some(ttl), Some(expire_ts))
+                        if !matches!(ttl, Ttl::ExpireAt(_)) =>
+                    {
+                        Some(jitter.apply(now, expire_ts))
+                    }
+                    _ => expire_ts,
                 };
                 (k.clone(), v.to_row_entry(seq, Some(now), expire_ts))
             })
             .collect();
 
         if matches!(ordering, IterationOrder::Descending) {
-            entries.reverse();
+            reverse_key_order(&mut entries);
         }
 
         let iter: Box<dyn Iterator<Item = (SequencedKey, RowEntry)> + Send + Sync> =
@@ -454,6 +571,41 @@ impl WriteBatchIterator {
     }
 }
 
+/// Spreads out the expiry of entries written with a relative TTL. See
+/// [`crate::config::Settings::ttl_jitter`].
+pub(crate) struct TtlJitter<'a> {
+    fraction: f64,
+    rand: &'a DbRand,
+}
+
+impl<'a> TtlJitter<'a> {
+    /// Returns `None` when `fraction` disables jitter.
+    pub(crate) fn new(fraction: f64, rand: &'a DbRand) -> Option<Self> {
+        (fraction > 0.0).then_some(Self { fraction, rand })
+    }
+
+    /// Moves `expire_ts` by a random offset of at most `fraction` of the TTL
+    /// it was computed from.
+    fn apply(&self, now: i64, expire_ts: i64) -> i64 {
+        let ttl = expire_ts.saturating_sub(now);
+        let max_offset = (ttl as f64 * self.fraction) as i64;
+        if max_offset <= 0 {
+            return expire_ts;
+        }
+        let offset = self.rand.rng().random_range(-max_offset..=max_offset);
+        expire_ts.saturating_add(offset)
+    }
+}
+
+/// Reverses the key order of `entries`, which are sorted by [`SequencedKey`],
+/// while keeping the entries of each key newest first.
+fn reverse_key_order(entries: &mut [(SequencedKey, RowEntry)]) {
+    entries.reverse();
+    for group in entries.chunk_by_mut(|(a, _), (b, _)| a.user_key == b.user_key) {
+        group.reverse();
+    }
+}
+
 #[async_trait]
 impl RowEntryIterator for WriteBatchIterator {
     async fn init(&mut self) -> Result<(), crate::error::SlateDBError> {
@@ -553,7 +705,7 @@ mod tests {
                 batch.delete(test_case.key.as_slice());
                 expected_ops.insert(
                     SequencedKey::new(Bytes::from(test_case.key.clone()), seq as u64),
-                    WriteOp::Delete(Bytes::from(test_case.key)),
+                    WriteOp::Delete(Bytes::from(test_case.key), None),
                 );
             }
         }
@@ -577,6 +729,54 @@ mod tests {
         }
     }
 
+    struct MaxValue;
+
+    impl ConflictResolver for MaxValue {
+        fn resolve(&self, _key: &[u8], earlier: &[u8], later: &[u8]) -> ConflictWinner {
+            if later >= earlier {
+                ConflictWinner::Later
+            } else {
+                ConflictWinner::Earlier
+            }
+        }
+    }
+
+    fn put_values(batch: &WriteBatch) -> Vec<(Bytes, Bytes)> {
+        batch
+            .ops
+            .values()
+            .filter_map(|op| match op {
+                WriteOp::Put(key, value, _) => Some((key.clone(), value.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_intra_batch_puts_keeps_resolver_winner() {
+        let mut batch = WriteBatch::new();
+        batch.put(b"key1", b"b");
+        batch.put(b"key1", b"c");
+        batch.put(b"key1", b"a");
+        // a delete ends the run of puts
+        batch.put(b"key2", b"z");
+        batch.delete(b"key2");
+        batch.put(b"key2", b"a");
+        batch.put(b"key3", b"only");
+
+        batch.resolve_intra_batch_puts(&MaxValue);
+
+        assert_eq!(
+            put_values(&batch),
+            vec![
+                (Bytes::from_static(b"key1"), Bytes::from_static(b"c")),
+                (Bytes::from_static(b"key2"), Bytes::from_static(b"a")),
+                (Bytes::from_static(b"key3"), Bytes::from_static(b"only")),
+            ]
+        );
+        assert!(batch.intra_batch_puts.is_empty());
+    }
+
     #[tokio::test]
     async fn test_writebatch_iterator_basic() {
         let mut batch = WriteBatch::new();
@@ -1017,7 +1217,7 @@ mod tests {
             .get(&SequencedKey::new(Bytes::from_static(b"key1"), 0))
             .unwrap();
         match delete_op {
-            WriteOp::Delete(key) => {
+            WriteOp::Delete(key, None) => {
                 assert_eq!(key.as_ref(), b"key1");
             }
             _ => panic!("Expected Delete operation"),
@@ -1079,7 +1279,7 @@ mod tests {
             .get(&SequencedKey::new(Bytes::from_static(b"key1"), 1))
             .unwrap();
         match op {
-            WriteOp::Delete(key) => {
+            WriteOp::Delete(key, None) => {
                 assert_eq!(key.as_ref(), b"key1");
             }
             _ => panic!("Expected Delete operation"),
@@ -1177,7 +1377,7 @@ mod tests {
         // When: creating a descending iterator
         let mut iter = WriteBatchIterator::new(&batch, .., IterationOrder::Descending);
 
-        // Then: the iterator should return operations in descending order
+        // Then: keys come in descending order, and each key's writes newest first
         let expected = vec![
             RowEntry::new(
                 Bytes::from_static(b"key2"),
@@ -1188,14 +1388,14 @@ mod tests {
             ),
             RowEntry::new(
                 Bytes::from_static(b"key1"),
-                ValueDeletable::Merge(Bytes::from_static(b"merge1")),
+                ValueDeletable::Merge(Bytes::from_static(b"merge3")),
                 u64::MAX,
                 None,
                 None,
             ),
             RowEntry::new(
                 Bytes::from_static(b"key1"),
-                ValueDeletable::Merge(Bytes::from_static(b"merge3")),
+                ValueDeletable::Merge(Bytes::from_static(b"merge1")),
                 u64::MAX,
                 None,
                 None,
@@ -1205,6 +1405,41 @@ mod tests {
         assert_iterator(&mut iter, expected).await;
     }
 
+    #[rstest]
+    #[case(IterationOrder::Ascending)]
+    #[case(IterationOrder::Descending)]
+    #[tokio::test]
+    async fn should_return_writes_to_a_key_newest_first(#[case] order: IterationOrder) {
+        let mut batch = WriteBatch::new();
+        batch.put(b"key1", b"base");
+        batch.merge(b"key2", b"other");
+        batch.merge(b"key1", b"merge1");
+        batch.merge(b"key1", b"merge2");
+
+        let mut iter = WriteBatchIterator::new(&batch, .., order);
+
+        let merge = |key: &'static [u8], value: &'static [u8]| {
+            RowEntry::new(
+                Bytes::from_static(key),
+                ValueDeletable::Merge(Bytes::from_static(value)),
+                u64::MAX,
+                None,
+                None,
+            )
+        };
+        let key1 = vec![
+            merge(b"key1", b"merge2"),
+            merge(b"key1", b"merge1"),
+            RowEntry::new_value(b"key1", b"base", u64::MAX),
+        ];
+        let key2 = vec![merge(b"key2", b"other")];
+        let expected = match order {
+            IterationOrder::Ascending => [key1, key2].concat(),
+            IterationOrder::Descending => [key2, key1].concat(),
+        };
+        assert_iterator(&mut iter, expected).await;
+    }
+
     #[tokio::test]
     async fn should_iterate_over_merges_in_range() {
         // Given: a WriteBatch with merges for different keys
@@ -1286,6 +1521,89 @@ mod tests {
         }
     }
 
+    async fn extract_expire_ts(batch: &WriteBatch, jitter: f64, seed: u64) -> Vec<Option<i64>> {
+        let rand = DbRand::new(seed);
+        let (entries, _) = batch
+            .extract_entries(
+                1,
+                1000,
+                Some(1000),
+                TtlJitter::new(jitter, &rand),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        entries.into_iter().map(|e| e.expire_ts).collect()
+    }
+
+    #[tokio::test]
+    async fn should_spread_expire_ts_of_same_ttl_writes_within_jitter() {
+        let mut batch = WriteBatch::new();
+        for i in 0..100u32 {
+            let ttl = if i % 2 == 0 {
+                Ttl::Default
+            } else {
+                Ttl::ExpireAfter(1000)
+            };
+            batch.put_with_options(i.to_be_bytes(), b"value", &PutOptions { ttl });
+        }
+
+        let expire_ts = extract_expire_ts(&batch, 0.1, 7).await;
+
+        // both relative TTLs expire 1000 after `now`, give or take 10%
+        assert!(expire_ts
+            .iter()
+            .all(|ts| ts.is_some_and(|ts| (1900..=2100).contains(&ts))));
+        let distinct: HashSet<_> = expire_ts.iter().collect();
+        assert!(distinct.len() > 50);
+        // the same seed reproduces the same expiries
+        assert_eq!(expire_ts, extract_expire_ts(&batch, 0.1, 7).await);
+        // no jitter leaves every entry at the exact TTL
+        assert!(extract_expire_ts(&batch, 0.0, 7)
+            .await
+            .iter()
+            .all(|ts| *ts == Some(2000)));
+    }
+
+    #[tokio::test]
+    async fn should_not_jitter_absolute_expiry() {
+        let mut batch = WriteBatch::new();
+        batch.put_with_options(
+            b"key1",
+            b"value",
+            &PutOptions {
+                ttl: Ttl::ExpireAt(5000),
+            },
+        );
+        batch.put_with_options(b"key2", b"value", &PutOptions { ttl: Ttl::NoExpiry });
+        batch.delete(b"key3");
+
+        let expire_ts = extract_expire_ts(&batch, 0.5, 7).await;
+
+        assert_eq!(expire_ts, vec![Some(5000), None, None]);
+    }
+
+    #[tokio::test]
+    async fn should_stamp_retained_tombstone_with_unjittered_expiry() {
+        let mut batch = WriteBatch::new();
+        batch.delete_with_retention(b"key1", Duration::from_secs(3));
+        batch.delete(b"key2");
+
+        let (entries, _) = batch
+            .extract_entries(1, 1000, None, None, None, None)
+            .await
+            .unwrap();
+        assert!(entries.iter().all(|entry| entry.value.is_tombstone()));
+        assert_eq!(entries[0].expire_ts, Some(4000));
+        assert_eq!(entries[1].expire_ts, None);
+        // jitter never shortens the retention period
+        assert_eq!(
+            extract_expire_ts(&batch, 0.5, 7).await,
+            vec![Some(4000), None]
+        );
+    }
+
     #[tokio::test]
     async fn should_extract_entries_no_merges() {
         // Given: a WriteBatch with no merge operations
@@ -1296,7 +1614,7 @@ mod tests {
 
         // When: extracting entries
         let (result, _) = batch
-            .extract_entries(100, 1000, None, None, None)
+            .extract_entries(100, 1000, None, None, None, None)
             .await
             .unwrap();
 
@@ -1326,7 +1644,7 @@ mod tests {
         batch.merge(b"key2", b"merge1");
 
         let err = batch
-            .extract_entries(100, 1000, None, None, None)
+            .extract_entries(100, 1000, None, None, None, None)
             .await
             .unwrap_err();
         assert!(matches!(err, SlateDBError::MergeOperatorMissing));
@@ -1344,7 +1662,7 @@ mod tests {
         let merge_operator = Some(std::sync::Arc::new(StringConcatMergeOperator)
             as crate::merge_operator::MergeOperatorType);
         let (result, _) = batch
-            .extract_entries(100, 1000, None, merge_operator, None)
+            .extract_entries(100, 1000, None, None, merge_operator, None)
             .await
             .unwrap();
 
@@ -1369,7 +1687,7 @@ mod tests {
         let merge_operator = Some(std::sync::Arc::new(StringConcatMergeOperator)
             as crate::merge_operator::MergeOperatorType);
         let (result, _) = batch
-            .extract_entries(100, 1000, None, merge_operator, None)
+            .extract_entries(100, 1000, None, None, merge_operator, None)
             .await
             .unwrap();
 
@@ -1395,7 +1713,7 @@ mod tests {
         let merge_operator = Some(std::sync::Arc::new(StringConcatMergeOperator)
             as crate::merge_operator::MergeOperatorType);
         let (result, _) = batch
-            .extract_entries(100, 1000, None, merge_operator, None)
+            .extract_entries(100, 1000, None, None, merge_operator, None)
             .await
             .unwrap();
 
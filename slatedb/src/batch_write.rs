@@ -30,6 +30,7 @@ use fail_parallel::fail_point;
 use futures::stream::BoxStream;
 use futures::StreamExt;
 use log::warn;
+use parking_lot::RwLockWriteGuard;
 use std::sync::Arc;
 use std::time::Duration;
 use tracing::instrument;
@@ -38,12 +39,14 @@ use std::collections::BTreeSet;
 
 use bytes::Bytes;
 
+use crate::batch::{TtlJitter, WriteBatch};
 use crate::config::WriteOptions;
 use crate::dispatcher::MessageHandler;
 use crate::mem_table::KVTable;
+use crate::read_repair::ReadRepairCheck;
 use crate::types::RowEntry;
 use crate::utils::WatchableOnceCellReader;
-use crate::{batch::WriteBatch, db::DbInner, db::WriteHandle, error::SlateDBError};
+use crate::{db::DbInner, db::WriteHandle, error::SlateDBError};
 use slatedb_common::clock::SystemClock;
 
 pub(crate) const WRITE_BATCH_TASK_NAME: &str = "writer";
@@ -127,29 +130,39 @@ impl MessageHandler<WriteBatchMessage> for WriteBatchEventHandler {
 impl DbInner {
     #[allow(clippy::panic)]
     #[instrument(level = "trace", skip_all, fields(batch_size = batch.ops.len()))]
-    async fn write_batch(&self, batch: WriteBatch, options: &WriteOptions) -> WriteBatchResult {
+    async fn write_batch(&self, mut batch: WriteBatch, options: &WriteOptions) -> WriteBatchResult {
         let _options = options;
         #[cfg(not(dst))]
         let now = self.mono_clock.now().await?;
         #[cfg(dst)]
         // Force the current timestamp for DST operations. See #719 for details.
         let now = options.now;
-        // If the user supplied a sequence number, validate that it's strictly greater
-        // than the current max and advance the oracle. No CAS loop is needed here because
-        // write_batch is always called from a single-writer event loop.
-        let commit_seq = if options.seqnum > 0 {
-            let current = self.oracle.last_seq();
-            if options.seqnum <= current {
-                return Err(SlateDBError::InvalidSequenceNumber {
-                    provided: options.seqnum,
-                    current,
-                });
+        // A read repair must not shadow a version of its key written after the
+        // scan read it, so it's dropped if there is one. Like a transaction that
+        // conflicts, it's reported as a conflict.
+        if let Some(check) = &batch.read_repair {
+            if !self.is_read_repair_current(&batch, check) {
+                return Err(SlateDBError::TransactionConflict);
             }
-            self.oracle.advance_last_seq(options.seqnum);
+        }
+
+        // Take the user supplied sequence number, or else the allocator's, validate that
+        // it's strictly greater than the current max unless importing, and advance the
+        // oracle. No CAS loop is needed here because write_batch is always called from a
+        // single-writer event loop.
+        let current = self.oracle.last_seq();
+        let commit_seq = if options.seqnum > 0 {
             options.seqnum
         } else {
-            self.oracle.next_seq()
+            self.sequence_allocator.next_seq(current)
         };
+        if commit_seq <= current && !self.sequence_import_mode {
+            return Err(SlateDBError::InvalidSequenceNumber {
+                provided: commit_seq,
+                current,
+            });
+        }
+        self.oracle.advance_last_seq(commit_seq);
 
         // Check for transaction conflicts before proceeding with the write batch
         // if this batch is part of a transaction.
@@ -159,6 +172,10 @@ impl DbInner {
             }
         }
 
+        if let Some(resolver) = &self.conflict_resolver {
+            batch.resolve_intra_batch_puts(resolver.as_ref());
+        }
+
         // Count batch-local merge folding on the flush path so DB-side merge
         // resolution uses one metric for both write batches and memtable flushes.
         let (entries, touched_segments) = batch
@@ -166,6 +183,7 @@ impl DbInner {
                 commit_seq,
                 now,
                 self.settings.default_ttl,
+                TtlJitter::new(self.settings.ttl_jitter, &self.rand),
                 self.flush_merge_operator.clone(),
                 self.segment_extractor.as_deref(),
             )
@@ -178,6 +196,21 @@ impl DbInner {
         // effects.
         self.validate_segment_antichain(&touched_segments)?;
 
+        // An append-only memtable configured to reject out-of-order keys must
+        // refuse the batch before it reaches the WAL, for the same reason.
+        self.state
+            .read()
+            .memtable()
+            .table()
+            .check_append_order(&entries)?;
+
+        // A newer writer may have fenced this one after the batch was queued.
+        // Fencing is detected by the WAL flush or the manifest poller, which
+        // close the db with `Fenced`, so check here that no batch reaches the
+        // WAL or the memtable once it's been detected.
+        self.check_closed()?;
+
+        let pinned_writes = self.read_cache.pinned_writes(&entries);
         let durable_watcher = if self.wal_enabled {
             // WAL entries must be appended to the wal buffer atomically. Otherwise,
             // the WAL buffer might flush the entries in the middle of the batch, which
@@ -197,7 +230,11 @@ impl DbInner {
 
         // update the last_applied_seq to wal buffer. if a chunk of WAL entries are applied to the memtable
         // and flushed to the remote storage, WAL buffer manager will recycle these WAL entries.
-        self.wal_buffer.track_last_applied_seq(commit_seq);
+        // an out-of-order import doesn't advance the applied sequence number, so it's left as is.
+        let advances_seq = commit_seq > current;
+        if advances_seq {
+            self.wal_buffer.track_last_applied_seq(commit_seq);
+        }
 
         // insert a fail point to make it easier to test the case where the last_committed_seq is not updated.
         // this is useful for testing the case where the reader is not able to see the writes.
@@ -208,16 +245,23 @@ impl DbInner {
         );
 
         // track the recent committed txn for conflict check. if txn_id is not supplied,
-        // we still consider this as an transaction commit.
+        // we still consider this as an transaction commit. a read repair rewrites the
+        // value a reader would already see, so it can't conflict with a transaction.
         if let Some(txn_id) = &batch.txn_id {
             self.txn_manager
                 .track_recent_committed_txn(txn_id, commit_seq);
-        } else {
+        } else if batch.read_repair.is_none() {
             let write_keys = batch.keys();
             self.txn_manager
                 .track_recent_committed_write_batch(&write_keys, commit_seq);
         }
 
+        // the writes are now visible to readers, so cached reads of these keys
+        // are stale.
+        self.read_cache
+            .invalidate(batch.ops.keys().map(|key| &key.user_key));
+        self.read_cache.update_pinned(pinned_writes);
+
         // insert a fail point to make it easier to test the case where the transaction is committed but
         // but remaining work hasn't been done. this is useful for testing that transaction commits and
         // commited seqnums get updated in lock-step. See #1301 for details.
@@ -227,8 +271,11 @@ impl DbInner {
             |_| { Err(SlateDBError::from(std::io::Error::other("oops"))) }
         );
 
-        // record the memtable sequence in the memtable's sequence tracker.
-        self.record_memtable_sequence(commit_seq);
+        // record the memtable sequence in the memtable's sequence tracker, which
+        // requires sequence numbers to increase.
+        if advances_seq {
+            self.record_memtable_sequence(commit_seq);
+        }
 
         // maybe freeze the memtable.
         self.maybe_freeze_current_memtable()?;
@@ -283,16 +330,47 @@ impl DbInner {
         Ok(())
     }
 
+    /// Returns whether the version a read repair copies is still the newest
+    /// version of its key: no memtable holds a newer one, and no memtable
+    /// has been flushed to L0 since the version was read, since a flushed
+    /// memtable may have held one.
+    fn is_read_repair_current(&self, batch: &WriteBatch, check: &ReadRepairCheck) -> bool {
+        let guard = self.state.read();
+        let cow = guard.state();
+        if cow.core().last_l0_seq != check.last_l0_seq {
+            return false;
+        }
+        let mut tables = vec![Arc::clone(guard.memtable().table())];
+        tables.extend(cow.imm_memtable.iter().map(|imm| imm.table()));
+        batch.keys().iter().all(|key| {
+            tables.iter().all(|table| {
+                table
+                    .get_raw(key)
+                    .iter()
+                    .all(|entry| entry.seq <= check.seq)
+            })
+        })
+    }
+
     /// Write entries to the currently active memtable and record
     /// the batch's touched-segment prefixes on it. Returns a durable
     /// watcher for the memtable. When no extractor is configured,
-    /// `touched_segments` is empty and recording is a no-op.
+    /// `touched_segments` is empty and recording is a no-op. An
+    /// append-only memtable that can't take the entries in order is
+    /// first replaced with a skip map, see
+    /// [`crate::mem_table::WritableKVTable::prepare_for`].
     fn write_entries_to_memtable(
         &self,
         entries: Vec<RowEntry>,
         touched_segments: BTreeSet<Bytes>,
     ) -> WatchableOnceCellReader<Result<(), SlateDBError>> {
-        let guard = self.state.read();
+        let mut guard = self.state.read();
+        if !guard.memtable().table().can_append(&entries) {
+            drop(guard);
+            let mut write_guard = self.state.write();
+            write_guard.prepare_memtable_for(&entries);
+            guard = RwLockWriteGuard::downgrade(write_guard);
+        }
         let memtable = guard.memtable();
         memtable.record_touched_segments(touched_segments);
         entries.into_iter().for_each(|entry| memtable.put(entry));
@@ -362,8 +440,11 @@ async fn monitor_first_write(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::PutOptions;
     use crate::object_store::memory::InMemory;
+    use crate::oracle::SequenceAllocator;
     use crate::Db;
+    use slatedb_common::clock::MockSystemClock;
 
     #[tokio::test]
     async fn test_is_first_write_set_false_after_first_write() {
@@ -491,6 +572,124 @@ mod tests {
         ));
     }
 
+    /// Hands out the sequence numbers it was created with, in order.
+    struct PinnedSequenceAllocator(parking_lot::Mutex<std::vec::IntoIter<u64>>);
+
+    impl PinnedSequenceAllocator {
+        fn new(seqs: Vec<u64>) -> Arc<Self> {
+            Arc::new(Self(parking_lot::Mutex::new(seqs.into_iter())))
+        }
+    }
+
+    impl SequenceAllocator for PinnedSequenceAllocator {
+        fn next_seq(&self, _last_seq: u64) -> u64 {
+            self.0
+                .lock()
+                .next()
+                .expect("no pinned sequence number left")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sequence_allocator_assigns_every_write() {
+        let db = Db::builder("/tmp/test_sequence_allocator", Arc::new(InMemory::new()))
+            .with_sequence_allocator(PinnedSequenceAllocator::new(vec![10, 20, 30]))
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(db.put(b"key1", b"value1").await.unwrap().seqnum(), 10);
+        assert_eq!(db.delete(b"key1").await.unwrap().seqnum(), 20);
+        // a user-defined seqnum takes precedence over the allocator
+        let options = WriteOptions {
+            seqnum: 25,
+            ..Default::default()
+        };
+        let mut batch = WriteBatch::new();
+        batch.put(b"key2", b"value2");
+        let handle = db.write_with_options(batch, &options).await.unwrap();
+        assert_eq!(handle.seqnum(), 25);
+        assert_eq!(db.put(b"key3", b"value3").await.unwrap().seqnum(), 30);
+        assert_eq!(db.current_sequence(), 30);
+    }
+
+    #[tokio::test]
+    async fn test_sequence_allocator_rejects_out_of_order_seq() {
+        let db = Db::builder(
+            "/tmp/test_sequence_allocator_rejects_out_of_order_seq",
+            Arc::new(InMemory::new()),
+        )
+        .with_sequence_allocator(PinnedSequenceAllocator::new(vec![10, 10, 5, 11]))
+        .build()
+        .await
+        .unwrap();
+
+        db.put(b"key1", b"value1").await.unwrap();
+        for _ in 0..2 {
+            let err = db.put(b"key2", b"value2").await.unwrap_err();
+            assert_eq!(err.kind(), crate::ErrorKind::Invalid);
+        }
+        assert_eq!(db.current_sequence(), 10);
+        assert_eq!(db.get(b"key2").await.unwrap(), None);
+        assert_eq!(db.put(b"key2", b"value2").await.unwrap().seqnum(), 11);
+    }
+
+    #[tokio::test]
+    async fn test_sequence_import_mode_accepts_out_of_order_seq() {
+        let clock = Arc::new(MockSystemClock::new());
+        let db = Db::builder(
+            "/tmp/test_sequence_import_mode_accepts_out_of_order_seq",
+            Arc::new(InMemory::new()),
+        )
+        .with_sequence_allocator(PinnedSequenceAllocator::new(vec![100, 50, 70]))
+        .with_sequence_import_mode(true)
+        .with_system_clock(clock.clone())
+        .build()
+        .await
+        .unwrap();
+        // the mock clock never ticks the WAL flush, so don't wait for it
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            db.put_with_options(b"key1", b"new", &PutOptions::default(), &write_options)
+                .await
+                .unwrap()
+                .seqnum(),
+            100
+        );
+        assert_eq!(
+            db.put_with_options(b"key1", b"old", &PutOptions::default(), &write_options)
+                .await
+                .unwrap()
+                .seqnum(),
+            50
+        );
+        assert_eq!(db.current_sequence(), 100);
+        // the write with the older sequence number is shadowed by the newer one
+        assert_eq!(
+            db.get(b"key1").await.unwrap(),
+            Some(Bytes::from_static(b"new"))
+        );
+        // an out-of-order write after the memtable's sequence tracker is due for
+        // its next sample doesn't break the tracker's ordering
+        clock.advance(Duration::from_secs(61)).await;
+        assert_eq!(
+            db.put_with_options(b"key2", b"old", &PutOptions::default(), &write_options)
+                .await
+                .unwrap()
+                .seqnum(),
+            70
+        );
+        assert_eq!(db.current_sequence(), 100);
+        assert_eq!(
+            db.get(b"key2").await.unwrap(),
+            Some(Bytes::from_static(b"old"))
+        );
+    }
+
     fn batch(prefixes: &[&[u8]]) -> BTreeSet<Bytes> {
         prefixes.iter().map(|p| Bytes::copy_from_slice(p)).collect()
     }
@@ -4,6 +4,7 @@ use std::ops::Bound::{Excluded, Included, Unbounded};
 use std::ops::{Bound, RangeBounds};
 
 use crate::comparable_range::{ComparableRange, EndBound, StartBound};
+use crate::error::SlateDBError;
 
 /// Concrete struct representing a range of Bytes. Gets around much of
 /// the cumbersome work associated with the generic trait RangeBounds<Bytes>
@@ -70,6 +71,29 @@ impl BytesRange {
         }
     }
 
+    /// Builds the range of a scan from the bounds a caller passed. A range
+    /// that holds no keys, e.g. `a..a`, is valid and scans nothing, but a
+    /// range whose start key is after its end key is rejected, since it is
+    /// most likely a bug in the caller.
+    pub(crate) fn try_from_scan_range<K, T>(range: &T) -> Result<Self, SlateDBError>
+    where
+        K: AsRef<[u8]>,
+        T: RangeBounds<K>,
+    {
+        let start = range
+            .start_bound()
+            .map(|b| Bytes::copy_from_slice(b.as_ref()));
+        let end = range
+            .end_bound()
+            .map(|b| Bytes::copy_from_slice(b.as_ref()));
+        if let (Included(s) | Excluded(s), Included(e) | Excluded(e)) = (&start, &end) {
+            if s > e {
+                return Err(SlateDBError::InvalidRange { start, end });
+            }
+        }
+        Ok(Self::try_new(start, end).unwrap_or_else(Self::new_empty))
+    }
+
     pub(crate) fn from<T: RangeBounds<Bytes>>(range: T) -> Self {
         Self::new(range.start_bound().cloned(), range.end_bound().cloned())
     }
@@ -138,6 +162,26 @@ impl BytesRange {
             .map(|inner| Self { inner })
     }
 
+    pub(crate) fn union(&self, other: &Self) -> Option<Self> {
+        self.inner.union(&other.inner).map(|inner| Self { inner })
+    }
+
+    /// Merges `ranges` into the fewest ranges that hold the same keys. The
+    /// returned ranges are sorted, and neither overlap nor are adjacent to
+    /// each other. Ranges that hold no keys are dropped.
+    pub(crate) fn coalesce(mut ranges: Vec<BytesRange>) -> Vec<BytesRange> {
+        ranges.retain(|range| range.inner.non_empty());
+        ranges.sort();
+        let mut coalesced: Vec<BytesRange> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match coalesced.last_mut().and_then(|last| last.union(&range)) {
+                Some(union) => *coalesced.last_mut().expect("union of last range") = union,
+                None => coalesced.push(range),
+            }
+        }
+        coalesced
+    }
+
     pub(crate) fn is_start_bound_included_or_unbounded(&self) -> bool {
         !matches!(self.start_bound(), Excluded(_))
     }
@@ -171,6 +215,7 @@ impl BytesRange {
 #[cfg(test)]
 pub(crate) mod tests {
     use crate::bytes_range::BytesRange;
+    use crate::error::SlateDBError;
     use crate::proptest_util::arbitrary;
     use crate::proptest_util::sample;
 
@@ -195,6 +240,25 @@ pub(crate) mod tests {
         });
     }
 
+    #[test]
+    fn test_try_from_scan_range_accepts_empty_and_rejects_inverted_ranges() {
+        let empty = BytesRange::try_from_scan_range(&("a".."a")).unwrap();
+        assert!(empty.empty());
+        let point = BytesRange::try_from_scan_range(&("a"..="a")).unwrap();
+        assert!(point.non_empty());
+
+        for inverted in [
+            BytesRange::try_from_scan_range(&("b".."a")),
+            BytesRange::try_from_scan_range(&("b"..="a")),
+            BytesRange::try_from_scan_range::<&[u8], _>(&(
+                Bound::Excluded(b"b".as_slice()),
+                Bound::Excluded(b"a".as_slice()),
+            )),
+        ] {
+            assert!(matches!(inverted, Err(SlateDBError::InvalidRange { .. })));
+        }
+    }
+
     #[test]
     fn test_from_prefix_builds_half_open_range() {
         let range = BytesRange::from_prefix(b"ab");
@@ -306,6 +370,26 @@ pub(crate) mod tests {
         });
     }
 
+    #[test]
+    fn test_coalesce_merges_overlapping_and_adjacent_ranges() {
+        let range = |start: &'static str, end: &'static str| {
+            BytesRange::from(Bytes::from(start)..Bytes::from(end))
+        };
+        let ranges = vec![
+            range("m", "p"),
+            range("a", "c"),
+            range("b", "d"),
+            range("d", "f"),
+            BytesRange::new_empty(),
+            range("k", "m"),
+        ];
+
+        assert_eq!(
+            BytesRange::coalesce(ranges),
+            vec![range("a", "f"), range("k", "p")]
+        );
+    }
+
     #[test]
     fn test_new_with_unbounded_range_is_valid() {
         BytesRange::new(Unbounded, Unbounded);
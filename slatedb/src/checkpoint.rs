@@ -1,8 +1,17 @@
 use crate::config::{CheckpointOptions, CheckpointScope};
 use crate::db::Db;
+use crate::db_reader::DbReader;
+use crate::filter_policy::FilterPolicy;
+use crate::format::sst::BlockTransformer;
+use crate::manifest::store::{ManifestStore, StoredManifest};
 use crate::memtable_flusher::FlushTarget;
+use crate::merge_operator::MergeOperatorType;
+use crate::object_stores::{ObjectStoreType, ObjectStores};
 use chrono::{DateTime, Utc};
+use object_store::path::Path;
 use serde::Serialize;
+use slatedb_common::clock::SystemClock;
+use std::sync::Arc;
 use uuid::Uuid;
 
 #[non_exhaustive]
@@ -24,6 +33,27 @@ pub struct CheckpointCreateResult {
     pub manifest_id: u64,
 }
 
+/// What [`Db::open_checkpoint`] needs to open a [`DbReader`] on the files of
+/// a db, and [`Db::release_checkpoint`] needs to update its manifest. Holds the
+/// object stores and components the db was built with.
+pub(crate) struct CheckpointReaderConfig {
+    pub(crate) path: Path,
+    pub(crate) object_stores: ObjectStores,
+    pub(crate) merge_operator: Option<MergeOperatorType>,
+    pub(crate) block_transformer: Option<Arc<dyn BlockTransformer>>,
+    pub(crate) filter_policies: Vec<Arc<dyn FilterPolicy>>,
+    pub(crate) system_clock: Arc<dyn SystemClock>,
+}
+
+impl CheckpointReaderConfig {
+    fn manifest_store(&self) -> Arc<ManifestStore> {
+        Arc::new(ManifestStore::new(
+            &self.path,
+            self.object_stores.store_of(ObjectStoreType::Main).clone(),
+        ))
+    }
+}
+
 impl Db {
     /// Creates a checkpoint of an opened db using the provided options. Returns the ID of the created
     /// checkpoint and the id of the referenced manifest.
@@ -31,6 +61,18 @@ impl Db {
         &self,
         scope: CheckpointScope,
         options: &CheckpointOptions,
+    ) -> Result<CheckpointCreateResult, crate::Error> {
+        self.checkpoint_in_scope(scope, options, false).await
+    }
+
+    /// Flushes what `scope` covers and creates a checkpoint. If `unique_name`
+    /// is set, the name is checked against the checkpoints in the manifest
+    /// that the checkpoint is written to.
+    async fn checkpoint_in_scope(
+        &self,
+        scope: CheckpointScope,
+        options: &CheckpointOptions,
+        unique_name: bool,
     ) -> Result<CheckpointCreateResult, crate::Error> {
         let target = match scope {
             CheckpointScope::All => {
@@ -45,7 +87,120 @@ impl Db {
 
         self.inner
             .memtable_flusher()
-            .create_checkpoint(target, options.clone())
+            .create_checkpoint(target, options.clone(), unique_name)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Flushes all writes and creates a checkpoint with the given name, e.g. as
+    /// a restore point for a backup.
+    ///
+    /// The checkpoint references the manifest written by the flush, so it
+    /// holds every write made before the call, up to the sequence number the
+    /// flush reached. It doesn't expire: the SSTs it references are kept, even
+    /// after compaction has replaced them, until it is released with
+    /// [`Self::release_checkpoint`].
+    ///
+    /// ## Arguments
+    /// - `name`: the name of the checkpoint, which must not be the name of
+    ///   another checkpoint of the db
+    ///
+    /// ## Returns
+    /// - `Ok(Uuid)`: the id of the checkpoint, for [`Self::open_checkpoint`] and
+    ///   [`Self::release_checkpoint`]
+    ///
+    /// ## Errors
+    /// - `Error`: with kind [`crate::ErrorKind::Invalid`] if a checkpoint with
+    ///   the name already exists. The name is checked when the manifest is
+    ///   written, so of two concurrent calls with the same name only one
+    ///   succeeds.
+    /// - `Error`: if flushing the db or writing the manifest fails
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key", b"value1").await?;
+    ///     let id = db.flush_and_checkpoint("backup").await?;
+    ///     db.put(b"key", b"value2").await?;
+    ///
+    ///     let reader = db.open_checkpoint(id).await?;
+    ///     assert_eq!(reader.get(b"key").await?, Some("value1".into()));
+    ///     reader.close().await?;
+    ///     db.release_checkpoint(id).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn flush_and_checkpoint(&self, name: &str) -> Result<Uuid, crate::Error> {
+        let options = CheckpointOptions {
+            name: Some(name.to_string()),
+            ..CheckpointOptions::default()
+        };
+        let result = self
+            .checkpoint_in_scope(CheckpointScope::All, &options, true)
+            .await?;
+        Ok(result.id)
+    }
+
+    /// Opens a read-only [`DbReader`] on a checkpoint of the db, e.g. one
+    /// created by [`Self::flush_and_checkpoint`]. The reader sees the db as it
+    /// was when the checkpoint was created, and is opened with the object
+    /// stores, merge operator, block transformer and filter policies of the
+    /// db.
+    ///
+    /// ## Arguments
+    /// - `id`: the id of the checkpoint
+    ///
+    /// ## Returns
+    /// - `Ok(DbReader)`: a reader of the checkpoint
+    ///
+    /// ## Errors
+    /// - `Error`: if the checkpoint doesn't exist, or the reader can't be opened
+    pub async fn open_checkpoint(&self, id: Uuid) -> Result<DbReader, crate::Error> {
+        let config = &self.checkpoint_readers;
+        let mut builder = DbReader::builder(
+            config.path.clone(),
+            config.object_stores.store_of(ObjectStoreType::Main).clone(),
+        )
+        .with_checkpoint_id(id)
+        .with_filter_policies(config.filter_policies.clone())
+        .with_system_clock(config.system_clock.clone());
+        if config.object_stores.has_wal_object_store() {
+            builder = builder
+                .with_wal_object_store(config.object_stores.store_of(ObjectStoreType::Wal).clone());
+        }
+        if let Some(merge_operator) = &config.merge_operator {
+            builder = builder.with_merge_operator(merge_operator.clone());
+        }
+        if let Some(block_transformer) = &config.block_transformer {
+            builder = builder.with_block_transformer(block_transformer.clone());
+        }
+        builder.build().await
+    }
+
+    /// Releases a checkpoint of the db, so that the SSTs only it references
+    /// can be garbage collected. Readers still open on the checkpoint must not
+    /// be used afterwards. Releasing a checkpoint that doesn't exist does
+    /// nothing.
+    ///
+    /// ## Arguments
+    /// - `id`: the id of the checkpoint
+    ///
+    /// ## Errors
+    /// - `Error`: if the manifest can't be updated
+    pub async fn release_checkpoint(&self, id: Uuid) -> Result<(), crate::Error> {
+        let config = &self.checkpoint_readers;
+        let mut stored_manifest =
+            StoredManifest::load(config.manifest_store(), config.system_clock.clone()).await?;
+        stored_manifest
+            .delete_checkpoint(id)
             .await
             .map_err(Into::into)
     }
@@ -149,6 +304,70 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_concurrent_named_checkpoints_with_same_name_create_one() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = Path::from("/tmp/test_concurrent_named_checkpoints");
+        let db = Db::open(path, object_store).await.unwrap();
+        db.put(b"key", b"value").await.unwrap();
+
+        let (first, second) = tokio::join!(
+            db.flush_and_checkpoint("backup"),
+            db.flush_and_checkpoint("backup")
+        );
+
+        let errors: Vec<crate::Error> = [first, second]
+            .into_iter()
+            .filter_map(Result::err)
+            .collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind(), crate::ErrorKind::Invalid);
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_named_checkpoint_keeps_data_after_later_writes() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = Path::from("/tmp/test_named_checkpoint");
+        let db = Db::open(path.clone(), object_store.clone()).await.unwrap();
+        db.put(b"updated", b"v1").await.unwrap();
+        db.put(b"deleted", b"v1").await.unwrap();
+
+        let id = db.flush_and_checkpoint("backup").await.unwrap();
+        let err = db.flush_and_checkpoint("backup").await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Invalid);
+
+        db.put(b"updated", b"v2").await.unwrap();
+        db.delete(b"deleted").await.unwrap();
+        db.put(b"added", b"v2").await.unwrap();
+        db.flush().await.unwrap();
+
+        let reader = db.open_checkpoint(id).await.unwrap();
+        assert_eq!(
+            reader.get(b"updated").await.unwrap(),
+            Some(Bytes::from("v1"))
+        );
+        assert_eq!(
+            reader.get(b"deleted").await.unwrap(),
+            Some(Bytes::from("v1"))
+        );
+        assert_eq!(reader.get(b"added").await.unwrap(), None);
+        reader.close().await.unwrap();
+        assert_eq!(db.get(b"updated").await.unwrap(), Some(Bytes::from("v2")));
+
+        db.release_checkpoint(id).await.unwrap();
+        let manifest_store = ManifestStore::new(&path, object_store.clone());
+        let manifest = manifest_store.read_latest_manifest().await.unwrap();
+        assert!(manifest
+            .manifest
+            .core
+            .checkpoints
+            .iter()
+            .all(|c| c.id != id));
+        assert!(db.open_checkpoint(id).await.is_err());
+        db.close().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_should_create_checkpoint_from_checkpoint() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
@@ -51,6 +51,11 @@ impl MonotonicClock {
         self.enforce_monotonic(tick)
     }
 
+    /// Returns the system clock this clock reads its ticks from.
+    pub(crate) fn system_clock(&self) -> Arc<dyn SystemClock> {
+        self.delegate.clone()
+    }
+
     pub(crate) fn fetch_max_last_durable_tick(&self, tick: i64) -> i64 {
         self.last_durable_tick.fetch_max(tick, Ordering::SeqCst)
     }
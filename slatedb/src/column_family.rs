@@ -0,0 +1,184 @@
+//! Column families: named, independent key spaces within a single [`crate::Db`].
+//!
+//! A column family stores its keys under a prefix made of the length of its
+//! name (one byte) followed by the name. Since the length comes first, no
+//! family's prefix is a prefix of another's, so the keys of two families never
+//! interleave and a scan of one family never returns keys of another. Keys
+//! are returned to callers with the prefix removed.
+//!
+//! All families share the database's WAL and memtables. To compact and retain
+//! each family independently, open the database with [`ColumnFamilyExtractor`]
+//! as its segment extractor (see [`crate::DbBuilder::with_segment_extractor`]),
+//! which gives every family its own segment. When it is configured, every
+//! write must go through a column family.
+
+use std::ops::{Bound, RangeBounds};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::bytes_range::BytesRange;
+use crate::error::SlateDBError;
+use crate::prefix_extractor::{PrefixExtractor, PrefixTarget};
+use crate::types::KeyValue;
+use crate::DbIterator;
+
+/// The longest allowed column family name, in bytes.
+pub const MAX_COLUMN_FAMILY_NAME_LEN: usize = u8::MAX as usize;
+
+/// Returns the prefix under which the keys of column family `cf` are stored.
+pub(crate) fn column_family_prefix(cf: &str) -> Result<Bytes, SlateDBError> {
+    if cf.is_empty() || cf.len() > MAX_COLUMN_FAMILY_NAME_LEN {
+        return Err(SlateDBError::InvalidColumnFamilyName {
+            name: cf.to_string(),
+        });
+    }
+    let mut prefix = BytesMut::with_capacity(1 + cf.len());
+    prefix.put_u8(cf.len() as u8);
+    prefix.put_slice(cf.as_bytes());
+    Ok(prefix.freeze())
+}
+
+pub(crate) fn column_family_key(prefix: &[u8], key: &[u8]) -> Bytes {
+    let mut encoded = BytesMut::with_capacity(prefix.len() + key.len());
+    encoded.put_slice(prefix);
+    encoded.put_slice(key);
+    encoded.freeze()
+}
+
+/// Maps a range of keys within a column family to the range of stored keys.
+/// Like [`BytesRange::try_from_scan_range`], an empty range scans nothing and
+/// a range whose start is after its end is rejected.
+pub(crate) fn column_family_range<K, T>(prefix: &[u8], range: T) -> Result<BytesRange, SlateDBError>
+where
+    K: AsRef<[u8]>,
+    T: RangeBounds<K>,
+{
+    let family = BytesRange::from_prefix(prefix);
+    let start = match range.start_bound() {
+        Bound::Unbounded => family.start_bound().cloned(),
+        bound => bound.map(|key| column_family_key(prefix, key.as_ref())),
+    };
+    let end = match range.end_bound() {
+        Bound::Unbounded => family.end_bound().cloned(),
+        bound => bound.map(|key| column_family_key(prefix, key.as_ref())),
+    };
+    BytesRange::try_from_scan_range(&(start, end))
+}
+
+/// A segment extractor that places each column family in its own segment.
+///
+/// Keys that were not written through a column family have no segment, so
+/// writes of such keys are rejected once this extractor is configured.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ColumnFamilyExtractor;
+
+impl ColumnFamilyExtractor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PrefixExtractor for ColumnFamilyExtractor {
+    fn name(&self) -> &str {
+        "slatedb.column_family"
+    }
+
+    fn prefix_len(&self, target: &PrefixTarget) -> Option<usize> {
+        // a scan prefix that covers the whole family prefix is as good as a key
+        let (PrefixTarget::Point(bytes) | PrefixTarget::Prefix(bytes)) = target;
+        let name_len = *bytes.first()? as usize;
+        let prefix_len = 1 + name_len;
+        (name_len > 0 && bytes.len() >= prefix_len).then_some(prefix_len)
+    }
+}
+
+/// An iterator over the keys of a single column family, created by
+/// [`crate::Db::scan_cf`]. Keys are returned without the column family
+/// prefix.
+pub struct ColumnFamilyIterator {
+    iter: DbIterator,
+    prefix: Bytes,
+}
+
+impl ColumnFamilyIterator {
+    pub(crate) fn new(iter: DbIterator, prefix: Bytes) -> Self {
+        Self { iter, prefix }
+    }
+
+    /// Get the next key-value pair of the column family.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error`] if the iterator has been invalidated due to an
+    /// underlying error.
+    pub async fn next(&mut self) -> Result<Option<KeyValue>, crate::Error> {
+        let Some(mut kv) = self.iter.next().await? else {
+            return Ok(None);
+        };
+        kv.key = kv.key.slice(self.prefix.len()..);
+        Ok(Some(kv))
+    }
+
+    /// Seek ahead to the next key of the column family. See
+    /// [`DbIterator::seek`] for the rules that `next_key` must follow.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error`] if `next_key` is out of range or before the
+    /// current position, or if the iterator has been invalidated.
+    pub async fn seek<K: AsRef<[u8]>>(&mut self, next_key: K) -> Result<(), crate::Error> {
+        self.iter
+            .seek(column_family_key(&self.prefix, next_key.as_ref()))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_length_prefix_column_family_names() {
+        assert_eq!(
+            column_family_prefix("users").unwrap(),
+            Bytes::from_static(b"\x05users")
+        );
+        assert!(matches!(
+            column_family_prefix(""),
+            Err(SlateDBError::InvalidColumnFamilyName { .. })
+        ));
+        assert!(matches!(
+            column_family_prefix(&"x".repeat(MAX_COLUMN_FAMILY_NAME_LEN + 1)),
+            Err(SlateDBError::InvalidColumnFamilyName { .. })
+        ));
+    }
+
+    #[test]
+    fn should_bound_unbounded_ranges_to_the_column_family() {
+        let prefix = column_family_prefix("a").unwrap();
+
+        let range = column_family_range::<&[u8], _>(&prefix, ..).unwrap();
+        assert_eq!(range, BytesRange::from_prefix(b"\x01a"));
+
+        let range = column_family_range(&prefix, b"k".as_slice()..).unwrap();
+        assert_eq!(
+            range,
+            BytesRange::from(Bytes::from_static(b"\x01ak")..Bytes::from_static(b"\x01b"))
+        );
+    }
+
+    #[test]
+    fn should_extract_column_family_segment() {
+        let extractor = ColumnFamilyExtractor::new();
+        let point = |key: &'static [u8]| PrefixTarget::Point(Bytes::from_static(key));
+
+        assert_eq!(extractor.prefix_len(&point(b"\x03abckey")), Some(4));
+        assert_eq!(extractor.prefix_len(&point(b"\x03abc")), Some(4));
+        assert_eq!(extractor.prefix_len(&point(b"\x03ab")), None);
+        assert_eq!(extractor.prefix_len(&point(b"\x00key")), None);
+        assert_eq!(
+            extractor.prefix_len(&PrefixTarget::Prefix(Bytes::from_static(b"\x03ab"))),
+            None
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Digests of the live data read and written by compaction jobs, reported to
+//! a [`CompactionObserver`].
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+
+use crate::error::SlateDBError;
+use crate::iter::{RowEntryIterator, TrackedRowEntryIterator};
+use crate::types::{is_expired, RowEntry, ValueDeletable};
+
+/// Receives digests of the live data read and written by each compaction job.
+///
+/// An observer registered with
+/// [`crate::CompactorBuilder::with_compaction_observer`] receives, for every
+/// compaction job, a digest of the live data the job read and a digest of the
+/// live data it wrote. Compaction drops shadowed versions, tombstones and
+/// expired entries, but it must never change which value a key holds, so the
+/// two digests of a healthy job are equal. A mismatch means the job lost or
+/// altered live data.
+///
+/// A key is live if its newest version is a value or a merge operand that has
+/// not expired as of the job's clock tick. For each live key, the following
+/// bytes are fed to a SHA-256 hasher, in key order:
+///
+/// ```text
+/// | kind (u8) | key_len (u32, big-endian) | key | value_len (u32, big-endian) | value |
+/// ```
+///
+/// where `kind` is 0 for a value and 1 for a merge operand. The input is
+/// digested after merge operands have been combined, so the digests check
+/// retention, compaction filters and the writing of output SSTs. A compaction
+/// filter that modifies or drops live entries makes the digests differ by
+/// design.
+///
+/// Computing the digests costs an extra hash of every live entry on both
+/// sides, so no digests are computed unless an observer is registered.
+pub trait CompactionObserver: Send + Sync {
+    /// Called once a compaction job has written all of its output, before the
+    /// output is added to the manifest. Jobs that fail, and jobs resumed after
+    /// a compactor restart (part of whose output was written by an earlier
+    /// run), are not reported.
+    ///
+    /// This is called on the compaction task, so it should return quickly.
+    fn on_compaction_job_finished(&self, digests: &CompactionDigests);
+}
+
+/// The digests of the live data read and written by one compaction job.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactionDigests {
+    /// The id of the sorted run the job wrote.
+    pub destination: u32,
+    /// The live data read from the job's input SSTs and sorted runs.
+    pub input: LiveDataDigest,
+    /// The live data written to the job's output SSTs.
+    pub output: LiveDataDigest,
+}
+
+impl CompactionDigests {
+    /// Returns true if the job wrote exactly the live data it read.
+    pub fn is_consistent(&self) -> bool {
+        self.input.digest == self.output.digest && self.input.live_keys == self.output.live_keys
+    }
+}
+
+/// A digest of the live keys in a stream of entries.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveDataDigest {
+    /// The SHA-256 digest of the live keys and their values.
+    pub digest: [u8; 32],
+    /// The number of live keys.
+    pub live_keys: u64,
+    /// The number of entries, including tombstones and older versions.
+    pub entries: u64,
+}
+
+/// Accumulates a [`LiveDataDigest`] over entries sorted by key, and by
+/// sequence number from newest to oldest within a key.
+pub(crate) struct LiveDataDigester {
+    hasher: Sha256,
+    now: i64,
+    last_key: Option<Bytes>,
+    live_keys: u64,
+    entries: u64,
+}
+
+impl LiveDataDigester {
+    pub(crate) fn new(now: i64) -> Self {
+        Self {
+            hasher: Sha256::new(),
+            now,
+            last_key: None,
+            live_keys: 0,
+            entries: 0,
+        }
+    }
+
+    pub(crate) fn update(&mut self, entry: &RowEntry) {
+        self.entries += 1;
+        // only the newest version of a key decides whether it's live
+        if self.last_key.as_ref() == Some(&entry.key) {
+            return;
+        }
+        self.last_key = Some(entry.key.clone());
+        if is_expired(entry.expire_ts, self.now) {
+            return;
+        }
+        let (kind, value) = match &entry.value {
+            ValueDeletable::Value(value) => (0u8, value),
+            ValueDeletable::Merge(value) => (1u8, value),
+            ValueDeletable::Tombstone => return,
+        };
+        self.live_keys += 1;
+        self.hasher.update([kind]);
+        self.hasher.update((entry.key.len() as u32).to_be_bytes());
+        self.hasher.update(&entry.key);
+        self.hasher.update((value.len() as u32).to_be_bytes());
+        self.hasher.update(value);
+    }
+
+    /// Returns the digest of the entries seen so far and resets the hasher.
+    pub(crate) fn finish(&mut self) -> LiveDataDigest {
+        LiveDataDigest {
+            digest: self.hasher.finalize_reset().into(),
+            live_keys: self.live_keys,
+            entries: self.entries,
+        }
+    }
+}
+
+/// Passes entries through unchanged while feeding them to a shared
+/// [`LiveDataDigester`], so that the entries read by a compaction job can be
+/// digested in the middle of its iterator stack.
+pub(crate) struct DigestingIterator<T: RowEntryIterator> {
+    iterator: T,
+    digester: Arc<Mutex<LiveDataDigester>>,
+}
+
+impl<T: RowEntryIterator> DigestingIterator<T> {
+    pub(crate) fn new(iterator: T, digester: Arc<Mutex<LiveDataDigester>>) -> Self {
+        Self { iterator, digester }
+    }
+}
+
+#[async_trait]
+impl<T: RowEntryIterator> RowEntryIterator for DigestingIterator<T> {
+    async fn init(&mut self) -> Result<(), SlateDBError> {
+        self.iterator.init().await
+    }
+
+    async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        let entry = self.iterator.next().await?;
+        if let Some(entry) = &entry {
+            self.digester.lock().update(entry);
+        }
+        Ok(entry)
+    }
+
+    async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
+        self.iterator.seek(next_key).await
+    }
+}
+
+impl<T: TrackedRowEntryIterator> TrackedRowEntryIterator for DigestingIterator<T> {
+    fn bytes_processed(&self) -> u64 {
+        self.iterator.bytes_processed()
+    }
+
+    fn entries_processed(&self) -> u64 {
+        self.iterator.entries_processed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(entries: &[RowEntry]) -> LiveDataDigest {
+        let mut digester = LiveDataDigester::new(100);
+        for entry in entries {
+            digester.update(entry);
+        }
+        digester.finish()
+    }
+
+    #[test]
+    fn should_digest_only_the_newest_live_version_of_each_key() {
+        let input = digest(&[
+            RowEntry::new_value(b"a", b"2", 5),
+            RowEntry::new_value(b"a", b"1", 1),
+            RowEntry::new_tombstone(b"b", 6),
+            RowEntry::new_value(b"b", b"1", 2),
+            RowEntry::new_value(b"c", b"1", 3).with_expire_ts(100),
+            RowEntry::new_merge(b"d", b"1", 4),
+        ]);
+        let output = digest(&[
+            RowEntry::new_value(b"a", b"2", 5),
+            RowEntry::new_merge(b"d", b"1", 4),
+        ]);
+
+        assert_eq!(input.digest, output.digest);
+        assert_eq!(input.live_keys, 2);
+        assert_eq!(input.entries, 6);
+        assert_eq!(output.entries, 2);
+    }
+
+    #[test]
+    fn should_tell_values_from_merge_operands() {
+        let value = digest(&[RowEntry::new_value(b"a", b"1", 1)]);
+        let merge = digest(&[RowEntry::new_merge(b"a", b"1", 1)]);
+
+        assert_ne!(value.digest, merge.digest);
+    }
+}
@@ -344,6 +344,7 @@ impl CompactionExecuteBench {
             merge_operator: None,
             #[cfg(feature = "compaction_filters")]
             compaction_filter_supplier: None,
+            compaction_observer: None,
         });
 
         let manifest = StoredManifest::load(manifest_store, self.system_clock.clone()).await?;
@@ -122,7 +122,7 @@ pub enum CompactionFilterDecision {
     ///   The `expire_ts` is preserved from the original entry.
     ///
     /// - `Modify(ValueDeletable::Tombstone)`: Converts the entry to a tombstone.
-    ///   The `expire_ts` is cleared (set to `None`) since tombstones should not expire.
+    ///   The `expire_ts` is cleared (set to `None`) since a converted tombstone has no retention period.
     ///   Use this instead of `Drop` when you need to shadow older versions of the
     ///   same key in older sorted runs.
     ///
@@ -154,6 +154,12 @@ pub enum CompactionFilterError {
 /// Each filter instance is created for a single compaction job and executes
 /// single-threaded on the compactor thread.
 ///
+/// The filter sees the entries in key order, after older versions of each key
+/// have been dropped by the compaction's retention rules, so it isn't called
+/// with versions that wouldn't have been written anyway. A decision can only
+/// replace an entry's value or drop the entry, never change its key, so the
+/// output stays in key order.
+///
 /// # Performance
 ///
 /// The `filter()` method is called for every entry during compaction. While it
@@ -74,6 +74,10 @@ impl<T: TrackedRowEntryIterator> TrackedRowEntryIterator for CompactionFilterIte
     fn bytes_processed(&self) -> u64 {
         self.inner.bytes_processed()
     }
+
+    fn entries_processed(&self) -> u64 {
+        self.inner.entries_processed()
+    }
 }
 
 #[cfg(test)]
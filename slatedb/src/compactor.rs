@@ -54,19 +54,22 @@
 //! represents a description (Spec), a durable decision (Compaction), or a running
 //! attempt (JobSpec).
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use futures::stream::BoxStream;
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
 use tokio::runtime::Handle;
+use tokio::sync::oneshot;
 use tracing::instrument;
 use ulid::Ulid;
 
+use crate::compaction_digest::CompactionObserver;
 #[cfg(feature = "compaction_filters")]
 use crate::compaction_filter::CompactionFilterSupplier;
 use crate::compactions_store::{CompactionsStore, StoredCompactions};
@@ -93,7 +96,7 @@ use slatedb_common::metrics::MetricsRecorderHelper;
 pub use crate::compactor_state::{
     Compaction, CompactionSpec, CompactionStatus, CompactorState, SourceId,
 };
-pub use crate::compactor_state_protocols::CompactorStateView;
+pub use crate::compactor_state_protocols::{CompactorStateView, LsmTreeStats, SortedRunStats};
 pub use crate::db::builder::CompactorBuilder;
 pub use crate::size_tiered_compaction::SizeTieredCompactionSchedulerSupplier;
 
@@ -122,6 +125,11 @@ pub trait CompactionSchedulerSupplier: Send + Sync {
 /// The compactor periodically invokes the scheduler with the latest [`CompactorState`].
 /// Implementations return one or more candidate compaction specs, which the compactor then
 /// validates and submits to the executor.
+///
+/// Most policies only need [`CompactorStateView::tree_stats`], which reports L0 SST counts,
+/// sizes and overlap, the sorted runs and the number of running compactions for every tree.
+/// The full manifest and compactions are available via [`CompactorStateView::manifest`] and
+/// [`CompactorStateView::compactions`] for policies that need more.
 pub trait CompactionScheduler: Send + Sync {
     /// Proposes compaction specs for the current state.
     ///
@@ -274,8 +282,8 @@ pub(crate) enum CompactorMessage {
     CompactionJobFinished {
         /// Job id (distinct from the canonical compaction id).
         id: Ulid,
-        /// Output SR on success, or the compaction error.
-        result: Result<SortedRun, SlateDBError>,
+        /// Output SR and entry counts on success, or the compaction error.
+        result: Result<CompactionJobOutput, SlateDBError>,
     },
     /// Periodic progress update from the [`CompactionExecutor`].
     CompactionJobProgress {
@@ -290,6 +298,93 @@ pub(crate) enum CompactorMessage {
     LogStats,
     /// Ticker-triggered message to refresh the manifest and schedule compactions.
     PollManifest,
+    /// Requests that all eligible compactions run now. The report is sent once the
+    /// compactions scheduled for the request have finished.
+    CompactNow {
+        sender: oneshot::Sender<Result<CompactionReport, SlateDBError>>,
+    },
+}
+
+/// The outcome of a successful compaction job.
+#[derive(Clone, Debug)]
+pub(crate) struct CompactionJobOutput {
+    /// The destination sorted run with all output SST handles.
+    pub(crate) sorted_run: SortedRun,
+    /// The number of source entries the job read. Entries before a resume point
+    /// are not counted.
+    pub(crate) entries_read: u64,
+    /// The number of entries the job wrote to the destination sorted run.
+    pub(crate) entries_written: u64,
+    /// The number of tombstones the job dropped.
+    pub(crate) tombstones_dropped: u64,
+    /// The number of expired entries the job dropped.
+    pub(crate) expired_dropped: u64,
+    /// The number of shadowed versions the job dropped.
+    pub(crate) duplicates_dropped: u64,
+}
+
+impl CompactionJobOutput {
+    #[cfg(test)]
+    pub(crate) fn new(sorted_run: SortedRun) -> Self {
+        Self {
+            sorted_run,
+            entries_read: 0,
+            entries_written: 0,
+            tombstones_dropped: 0,
+            expired_dropped: 0,
+            duplicates_dropped: 0,
+        }
+    }
+}
+
+/// Statistics about the compactions performed by [`crate::Db::compact_now`].
+#[non_exhaustive]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompactionReport {
+    /// The number of compactions that completed successfully.
+    pub compactions: usize,
+    /// The number of compactions that failed. Their sources are left in place.
+    pub failed_compactions: usize,
+    /// The estimated size of the sources of the completed compactions, in bytes.
+    pub bytes_read: u64,
+    /// The estimated size of the sorted runs written by the completed compactions,
+    /// in bytes.
+    pub bytes_written: u64,
+    /// The number of source entries read by the completed compactions.
+    pub entries_read: u64,
+    /// The number of entries written by the completed compactions.
+    pub entries_written: u64,
+    /// The number of tombstones the completed compactions dropped because no
+    /// older version of their key was left for them to shadow.
+    pub tombstones_dropped: u64,
+    /// The number of expired entries the completed compactions dropped.
+    pub expired_dropped: u64,
+    /// The number of versions of a key the completed compactions dropped because
+    /// a newer version shadowed them.
+    pub duplicates_dropped: u64,
+    /// The wall-clock time from the request until the compactions it scheduled
+    /// finished, or until the request was handled if it scheduled none.
+    pub duration: Duration,
+}
+
+impl CompactionReport {
+    /// Returns the number of entries the completed compactions dropped. Besides
+    /// [`Self::tombstones_dropped`], [`Self::expired_dropped`] and
+    /// [`Self::duplicates_dropped`], this counts merge operands combined into one
+    /// entry and entries removed by a compaction filter.
+    pub fn entries_dropped(&self) -> u64 {
+        self.entries_read.saturating_sub(self.entries_written)
+    }
+}
+
+/// A [`CompactorMessage::CompactNow`] request waiting for the compactions it
+/// scheduled to finish.
+struct PendingCompactionReport {
+    started_at: DateTime<Utc>,
+    /// The compactions scheduled for the request that haven't finished yet.
+    compaction_ids: HashSet<Ulid>,
+    report: CompactionReport,
+    sender: oneshot::Sender<Result<CompactionReport, SlateDBError>>,
 }
 
 /// The compactor is responsible for taking groups of sorted runs (this doc uses the term
@@ -332,10 +427,11 @@ pub struct Compactor {
     merge_operator: Option<MergeOperatorType>,
     #[cfg(feature = "compaction_filters")]
     compaction_filter_supplier: Option<Arc<dyn CompactionFilterSupplier>>,
+    compaction_observer: Option<Arc<dyn CompactionObserver>>,
 }
 
 impl Compactor {
-    #[cfg_attr(not(feature = "compaction_filters"), allow(clippy::too_many_arguments))]
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         manifest_store: Arc<ManifestStore>,
         compactions_store: Arc<CompactionsStore>,
@@ -351,6 +447,7 @@ impl Compactor {
         #[cfg(feature = "compaction_filters")] compaction_filter_supplier: Option<
             Arc<dyn CompactionFilterSupplier>,
         >,
+        compaction_observer: Option<Arc<dyn CompactionObserver>>,
     ) -> Self {
         let stats = Arc::new(CompactionStats::new(recorder));
         let task_executor = Arc::new(MessageHandlerExecutor::new(
@@ -371,6 +468,7 @@ impl Compactor {
             merge_operator,
             #[cfg(feature = "compaction_filters")]
             compaction_filter_supplier,
+            compaction_observer,
         }
     }
 
@@ -398,6 +496,7 @@ impl Compactor {
                 merge_operator: self.merge_operator.clone(),
                 #[cfg(feature = "compaction_filters")]
                 compaction_filter_supplier: self.compaction_filter_supplier.clone(),
+                compaction_observer: self.compaction_observer.clone(),
             },
         ));
         let handler = CompactorEventHandler::new(
@@ -426,6 +525,19 @@ impl Compactor {
             .map_err(|e| e.into())
     }
 
+    /// Returns when a running compaction last made progress, or `None` if no
+    /// compaction has run yet.
+    ///
+    /// Compactions report progress when they start, every
+    /// [`CompactorOptions::heartbeat_interval`] while they read entries, and
+    /// each time they finish writing an SST. A watchdog can treat a compaction
+    /// that is running, but hasn't made progress for several intervals, as
+    /// stalled, e.g. on a flaky object store. The same time is reported by the
+    /// `slatedb.compactor.last_progress_timestamp_sec` gauge.
+    pub fn last_progress_at(&self) -> Option<DateTime<Utc>> {
+        self.stats.last_progress_at()
+    }
+
     /// Gracefully stops the compactor task and waits for it to finish.
     ///
     /// ## Returns
@@ -485,6 +597,7 @@ pub(crate) struct CompactorEventHandler {
     rand: Arc<DbRand>,
     stats: Arc<CompactionStats>,
     system_clock: Arc<dyn SystemClock>,
+    pending_reports: Vec<PendingCompactionReport>,
 }
 
 #[async_trait]
@@ -508,15 +621,25 @@ impl MessageHandler<CompactorMessage> for CompactorEventHandler {
             CompactorMessage::PollManifest => self.handle_ticker().await?,
             CompactorMessage::CompactionJobFinished { id, result } => {
                 match result {
-                    Ok(sr) => self.finish_compaction(id, sr).await?,
+                    Ok(output) => {
+                        self.record_finished_compaction(id, &output);
+                        self.finish_compaction(id, output.sorted_run).await?
+                    }
                     Err(err) => {
                         error!("error executing compaction [error={:#?}]", err);
+                        for pending in &mut self.pending_reports {
+                            if pending.compaction_ids.remove(&id) {
+                                pending.report.failed_compactions += 1;
+                            }
+                        }
                         self.finish_failed_compaction(id).await?;
                     }
                 }
                 self.maybe_schedule_compactions().await?;
                 self.maybe_start_compactions().await?;
+                self.maybe_send_reports();
             }
+            CompactorMessage::CompactNow { sender } => self.handle_compact_now(sender).await?,
             CompactorMessage::CompactionJobProgress {
                 id,
                 bytes_processed,
@@ -581,6 +704,7 @@ impl CompactorEventHandler {
             rand,
             stats,
             system_clock,
+            pending_reports: Vec::new(),
         })
     }
 
@@ -700,6 +824,99 @@ impl CompactorEventHandler {
         Ok(())
     }
 
+    /// Handles a [`CompactorMessage::CompactNow`] request by refreshing the state and
+    /// scheduling every eligible compaction. The report is sent right away if nothing
+    /// was scheduled, or by [`CompactorEventHandler::maybe_send_reports`] once the
+    /// compactions scheduled here have finished.
+    async fn handle_compact_now(
+        &mut self,
+        sender: oneshot::Sender<Result<CompactionReport, SlateDBError>>,
+    ) -> Result<(), SlateDBError> {
+        if self.is_executor_stopped() {
+            // The receiver may be gone if the caller was cancelled.
+            let _ = sender.send(Err(SlateDBError::CompactionExecutorFailed));
+            return Ok(());
+        }
+        let started_at = self.system_clock.now();
+        let active_before = self.active_compaction_ids();
+        self.handle_ticker().await?;
+        let compaction_ids = self
+            .active_compaction_ids()
+            .difference(&active_before)
+            .copied()
+            .collect();
+        self.pending_reports.push(PendingCompactionReport {
+            started_at,
+            compaction_ids,
+            report: CompactionReport::default(),
+            sender,
+        });
+        self.maybe_send_reports();
+        Ok(())
+    }
+
+    fn active_compaction_ids(&self) -> HashSet<Ulid> {
+        self.state().active_compactions().map(|c| c.id()).collect()
+    }
+
+    /// Adds a successful compaction to the pending [`CompactionReport`]s that wait
+    /// for it. Must be called before the compaction's sources are removed from the
+    /// state.
+    fn record_finished_compaction(&mut self, id: Ulid, output: &CompactionJobOutput) {
+        if !self
+            .pending_reports
+            .iter()
+            .any(|pending| pending.compaction_ids.contains(&id))
+        {
+            return;
+        }
+        let Some(compaction) = self.state().compactions().value.get(&id) else {
+            return;
+        };
+        let bytes_read =
+            Self::calculate_estimated_source_bytes(compaction, self.state().db_state());
+        let bytes_written = output.sorted_run.estimate_size();
+        for pending in &mut self.pending_reports {
+            if !pending.compaction_ids.remove(&id) {
+                continue;
+            }
+            let report = &mut pending.report;
+            report.compactions += 1;
+            report.bytes_read += bytes_read;
+            report.bytes_written += bytes_written;
+            report.entries_read += output.entries_read;
+            report.entries_written += output.entries_written;
+            report.tombstones_dropped += output.tombstones_dropped;
+            report.expired_dropped += output.expired_dropped;
+            report.duplicates_dropped += output.duplicates_dropped;
+        }
+    }
+
+    /// Sends the pending [`CompactionReport`]s whose compactions have all finished.
+    /// A compaction that is no longer active without its job having finished was
+    /// rejected before it started, and is counted as failed.
+    fn maybe_send_reports(&mut self) {
+        if self.pending_reports.is_empty() {
+            return;
+        }
+        let active = self.active_compaction_ids();
+        let now = self.system_clock.now();
+        let mut still_pending = Vec::new();
+        for mut pending in self.pending_reports.drain(..) {
+            let waiting = pending.compaction_ids.len();
+            pending.compaction_ids.retain(|id| active.contains(id));
+            pending.report.failed_compactions += waiting - pending.compaction_ids.len();
+            if !pending.compaction_ids.is_empty() {
+                still_pending.push(pending);
+                continue;
+            }
+            pending.report.duration = (now - pending.started_at).to_std().unwrap_or_default();
+            // The receiver may be gone if the caller was cancelled.
+            let _ = pending.sender.send(Ok(pending.report));
+        }
+        self.pending_reports = still_pending;
+    }
+
     /// Stops the underlying compaction executor, aborting the executor and waiting for any
     /// in-flight tasks to stop gracefully.
     async fn stop_executor(&self) -> Result<(), SlateDBError> {
@@ -1183,6 +1400,8 @@ impl CompactorEventHandler {
 }
 
 pub mod stats {
+    use chrono::{DateTime, Utc};
+    use parking_lot::Mutex;
     use slatedb_common::metrics::{CounterFn, GaugeFn, MetricsRecorderHelper, UpDownCounterFn};
     use std::sync::Arc;
 
@@ -1201,6 +1420,7 @@ pub mod stats {
     pub const BYTES_COMPACTED: &str = compactor_stat_name!("bytes_compacted");
     pub const COMPACTOR_EPOCH: &str = compactor_stat_name!("epoch");
     pub const LAST_COMPACTION_TS_SEC: &str = compactor_stat_name!("last_compaction_timestamp_sec");
+    pub const LAST_PROGRESS_TS_SEC: &str = compactor_stat_name!("last_progress_timestamp_sec");
     pub const RUNNING_COMPACTIONS: &str = compactor_stat_name!("running_compactions");
     pub const TOTAL_BYTES_BEING_COMPACTED: &str =
         compactor_stat_name!("total_bytes_being_compacted");
@@ -1218,6 +1438,10 @@ pub mod stats {
     pub(crate) struct CompactionStats {
         pub(crate) compactor_epoch: Arc<dyn GaugeFn>,
         pub(crate) last_compaction_ts: Arc<dyn GaugeFn>,
+        pub(crate) last_progress_ts: Arc<dyn GaugeFn>,
+        /// When a running compaction last reported progress. See
+        /// [`crate::compactor::Compactor::last_progress_at`].
+        last_progress_at: Mutex<Option<DateTime<Utc>>>,
         pub(crate) running_compactions: Arc<dyn UpDownCounterFn>,
         pub(crate) bytes_compacted: Arc<dyn CounterFn>,
         pub(crate) total_bytes_being_compacted: Arc<dyn GaugeFn>,
@@ -1232,6 +1456,8 @@ pub mod stats {
             Self {
                 compactor_epoch: recorder.gauge(COMPACTOR_EPOCH).register(),
                 last_compaction_ts: recorder.gauge(LAST_COMPACTION_TS_SEC).register(),
+                last_progress_ts: recorder.gauge(LAST_PROGRESS_TS_SEC).register(),
+                last_progress_at: Mutex::new(None),
                 running_compactions: recorder.up_down_counter(RUNNING_COMPACTIONS).register(),
                 bytes_compacted: recorder.counter(BYTES_COMPACTED).register(),
                 total_bytes_being_compacted: recorder.gauge(TOTAL_BYTES_BEING_COMPACTED).register(),
@@ -1254,6 +1480,16 @@ pub mod stats {
             }
         }
 
+        /// Records that a running compaction made progress at `now`.
+        pub(crate) fn record_progress(&self, now: DateTime<Utc>) {
+            *self.last_progress_at.lock() = Some(now);
+            self.last_progress_ts.set(now.timestamp());
+        }
+
+        pub(crate) fn last_progress_at(&self) -> Option<DateTime<Utc>> {
+            *self.last_progress_at.lock()
+        }
+
         pub(crate) fn retention_metrics(&self) -> crate::retention_iterator::RetentionMetrics {
             crate::retention_iterator::RetentionMetrics {
                 expired_entries_purged_value: self.expired_entries_purged_value.clone(),
@@ -1433,6 +1669,51 @@ mod tests {
         }
     }
 
+    /// Test scheduler that compacts every L0 SST of the root tree into a new
+    /// sorted run once the L0 count reaches `min_l0_count`. It records the
+    /// root tree stats it was shown on each call.
+    #[derive(Clone)]
+    struct L0CountTestScheduler {
+        min_l0_count: usize,
+        observed: Arc<Mutex<Vec<LsmTreeStats>>>,
+    }
+
+    impl CompactionScheduler for L0CountTestScheduler {
+        fn propose(&self, state: &CompactorStateView) -> Vec<CompactionSpec> {
+            let root = state
+                .tree_stats()
+                .into_iter()
+                .next()
+                .expect("root tree stats are always present");
+            self.observed.lock().push(root.clone());
+            if root.active_compactions > 0 || root.l0_count < self.min_l0_count {
+                return vec![];
+            }
+            let sources = state
+                .manifest()
+                .l0()
+                .iter()
+                .map(|view| SourceId::SstView(view.id))
+                .collect();
+            let destination = root
+                .sorted_runs
+                .iter()
+                .map(|sr| sr.id + 1)
+                .max()
+                .unwrap_or(0);
+            vec![CompactionSpec::new(sources, destination)]
+        }
+    }
+
+    impl CompactionSchedulerSupplier for L0CountTestScheduler {
+        fn compaction_scheduler(
+            &self,
+            _options: &CompactorOptions,
+        ) -> Box<dyn CompactionScheduler + Send + Sync> {
+            Box::new(self.clone())
+        }
+    }
+
     struct StringConcatMergeOperator;
 
     impl MergeOperator for StringConcatMergeOperator {
@@ -1461,6 +1742,7 @@ mod tests {
             min_compaction_sources: 1,
             max_compaction_sources: 999,
             include_size_threshold: 4.0,
+            ..Default::default()
         }
         .into();
         options
@@ -1546,6 +1828,75 @@ mod tests {
         assert!(expected.is_empty());
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_compactor_runs_custom_scheduler_policy() {
+        let os = Arc::new(InMemory::new());
+        let system_clock = Arc::new(MockSystemClock::new());
+        let path = "/tmp/test_compactor_runs_custom_scheduler_policy";
+        let mut options = db_options(Some(compactor_options()));
+        options.l0_sst_size_bytes = 512;
+        options.flush_interval = None;
+        let scheduler = L0CountTestScheduler {
+            min_l0_count: 3,
+            observed: Arc::new(Mutex::new(Vec::new())),
+        };
+
+        let db = Db::builder(path, os.clone())
+            .with_settings(options)
+            .with_system_clock(system_clock.clone())
+            .with_compactor_builder(
+                CompactorBuilder::new(path, os.clone())
+                    .with_options(compactor_options())
+                    .with_scheduler_supplier(Arc::new(scheduler.clone())),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        put_and_flush_memtable(&db, b"key1", b"v1").await;
+        put_and_flush_memtable(&db, b"key2", b"v2").await;
+
+        // The scheduler sees both L0 SSTs but stays below its trigger
+        let observed = run_for(Duration::from_secs(10), || async {
+            system_clock
+                .as_ref()
+                .advance(Duration::from_millis(60000))
+                .await;
+            scheduler
+                .observed
+                .lock()
+                .iter()
+                .find(|stats| stats.l0_count == 2)
+                .cloned()
+        })
+        .await
+        .expect("scheduler never observed two L0 SSTs");
+        assert!(observed.l0_size_bytes > 0);
+        assert_eq!(observed.l0_max_overlap, 1);
+        assert!(observed.sorted_runs.is_empty());
+        assert_eq!(read_db_state_core(&db).tree.l0.len(), 2);
+
+        put_and_flush_memtable(&db, b"key3", b"v3").await;
+
+        let db_state = run_for(Duration::from_secs(10), || async {
+            system_clock
+                .as_ref()
+                .advance(Duration::from_millis(60000))
+                .await;
+            let core = read_db_state_core(&db);
+            (core.tree.l0.is_empty() && core.tree.compacted.len() == 1).then_some(core)
+        })
+        .await
+        .expect("custom scheduler did not trigger a compaction");
+        assert_eq!(db_state.tree.compacted[0].id, 0);
+        for (key, value) in [(b"key1", b"v1"), (b"key2", b"v2"), (b"key3", b"v3")] {
+            assert_eq!(
+                db.get(key).await.unwrap(),
+                Some(Bytes::copy_from_slice(value))
+            );
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_compactor_compacts_only_target_segment() {
         let os = Arc::new(InMemory::new());
@@ -1649,6 +2000,7 @@ mod tests {
             min_compaction_sources: 2,
             max_compaction_sources: 999,
             include_size_threshold: 4.0,
+            ..Default::default()
         }
         .into();
         let compactor_opts = options
@@ -1832,6 +2184,7 @@ mod tests {
             min_compaction_sources: 2,
             max_compaction_sources: 999,
             include_size_threshold: 4.0,
+            ..Default::default()
         }
         .into();
         let compactor_opts = options
@@ -3238,6 +3591,7 @@ mod tests {
             min_compaction_sources: 2,
             max_compaction_sources: 2,
             include_size_threshold: 4.0,
+            ..Default::default()
         }
         .into();
         let mut options = db_options(Some(compactor_options()));
@@ -3354,6 +3708,7 @@ mod tests {
             min_compaction_sources: 2,
             max_compaction_sources: 2,
             include_size_threshold: 4.0,
+            ..Default::default()
         }
         .into();
         let mut options = db_options(Some(compactor_options()));
@@ -4384,6 +4739,7 @@ mod tests {
                     merge_operator: None,
                     #[cfg(feature = "compaction_filters")]
                     compaction_filter_supplier: None,
+                    compaction_observer: None,
                 },
             ));
             let handler = CompactorEventHandler::new(
@@ -4970,7 +5326,7 @@ mod tests {
         };
         let msg = CompactorMessage::CompactionJobFinished {
             id: compaction_id,
-            result: Ok(output_sr),
+            result: Ok(CompactionJobOutput::new(output_sr)),
         };
         handler.handle(msg).await.unwrap();
 
@@ -5089,7 +5445,7 @@ mod tests {
         };
         let msg = CompactorMessage::CompactionJobFinished {
             id: job.id,
-            result: Ok(output_sr),
+            result: Ok(CompactionJobOutput::new(output_sr)),
         };
 
         // when:
@@ -5179,6 +5535,7 @@ mod tests {
             min_compaction_sources: 1,
             max_compaction_sources: 999,
             include_size_threshold: 4.0,
+            ..Default::default()
         }
         .into();
         let mut options = db_options(Some(compactor_options()));
@@ -9,12 +9,15 @@ use futures::future::{join, join_all};
 use parking_lot::Mutex;
 use tokio::task::JoinHandle;
 
+use crate::compaction_digest::{
+    CompactionDigests, CompactionObserver, DigestingIterator, LiveDataDigester,
+};
 #[cfg(feature = "compaction_filters")]
 use crate::compaction_filter::CompactionFilterSupplier;
 #[cfg(feature = "compaction_filters")]
 use crate::compaction_filter_iterator::CompactionFilterIterator;
-use crate::compactor::CompactorMessage;
 use crate::compactor::CompactorMessage::CompactionJobFinished;
+use crate::compactor::{CompactionJobOutput, CompactorMessage};
 use crate::config::CompactorOptions;
 use crate::db_state::{SortedRun, SsTableHandle, SsTableId, SsTableView};
 use crate::error::SlateDBError;
@@ -27,7 +30,7 @@ use crate::merge_operator::{
 };
 use crate::peeking_iterator::PeekingIterator;
 use crate::rand::DbRand;
-use crate::retention_iterator::RetentionIterator;
+use crate::retention_iterator::{DroppedEntries, RetentionIterator};
 use crate::sorted_run_iterator::SortedRunIterator;
 use crate::sst_iter::{SstIterator, SstIteratorOptions};
 use crate::tablestore::TableStore;
@@ -166,6 +169,10 @@ impl<T: TrackedRowEntryIterator> TrackedRowEntryIterator for ResumingIterator<T>
     fn bytes_processed(&self) -> u64 {
         self.iterator.bytes_processed()
     }
+
+    fn entries_processed(&self) -> u64 {
+        self.iterator.entries_processed()
+    }
 }
 
 /// Executes compaction jobs produced by the compactor.
@@ -193,6 +200,7 @@ pub(crate) struct TokioCompactionExecutorOptions {
     pub merge_operator: Option<MergeOperatorType>,
     #[cfg(feature = "compaction_filters")]
     pub compaction_filter_supplier: Option<Arc<dyn CompactionFilterSupplier>>,
+    pub compaction_observer: Option<Arc<dyn CompactionObserver>>,
 }
 
 pub(crate) struct TokioCompactionExecutor {
@@ -224,6 +232,7 @@ impl TokioCompactionExecutor {
                 merge_operator,
                 #[cfg(feature = "compaction_filters")]
                 compaction_filter_supplier: opts.compaction_filter_supplier,
+                compaction_observer: opts.compaction_observer,
             }),
         }
     }
@@ -244,7 +253,7 @@ impl CompactionExecutor for TokioCompactionExecutor {
 }
 
 struct TokioCompactionTask {
-    task: JoinHandle<Result<SortedRun, SlateDBError>>,
+    task: JoinHandle<Result<CompactionJobOutput, SlateDBError>>,
 }
 
 pub(crate) struct TokioCompactionExecutorInner {
@@ -261,14 +270,19 @@ pub(crate) struct TokioCompactionExecutorInner {
     merge_operator: Option<MergeOperatorType>,
     #[cfg(feature = "compaction_filters")]
     compaction_filter_supplier: Option<Arc<dyn CompactionFilterSupplier>>,
+    compaction_observer: Option<Arc<dyn CompactionObserver>>,
 }
 
 impl TokioCompactionExecutorInner {
     /// Builds input iterators for all sources (L0 and SR) and wraps them with optional
-    /// merge, retention, and compaction filter logic.
+    /// merge, retention, and compaction filter logic. When `input_digester` is set, it
+    /// is fed the merged input entries before retention is applied. When `dropped` is
+    /// set, it counts the entries that retention drops.
     async fn load_iterators<'a>(
         &self,
         job_args: &'a StartCompactionJobArgs,
+        input_digester: Option<Arc<Mutex<LiveDataDigester>>>,
+        dropped: Option<Arc<DroppedEntries>>,
     ) -> Result<ResumingIterator<Box<dyn TrackedRowEntryIterator + 'a>>, SlateDBError> {
         let resume_cursor = match job_args.output_ssts.last() {
             Some(output_sst) => {
@@ -322,6 +336,10 @@ impl TokioCompactionExecutorInner {
             } else {
                 Box::new(MergeOperatorRequiredIterator::new(merge_iter))
             };
+        let merge_iter: Box<dyn TrackedRowEntryIterator> = match input_digester {
+            Some(digester) => Box::new(DigestingIterator::new(merge_iter, digester)),
+            None => merge_iter,
+        };
 
         let stored_manifest =
             StoredManifest::load(self.manifest_store.clone(), self.clock.clone()).await?;
@@ -336,6 +354,9 @@ impl TokioCompactionExecutorInner {
             Some(self.stats.retention_metrics()),
         )
         .await?;
+        if let Some(dropped) = dropped {
+            retention_iter = retention_iter.with_dropped_entries(dropped);
+        }
         retention_iter.init().await?;
 
         // Apply compaction filter if configured
@@ -393,37 +414,73 @@ impl TokioCompactionExecutorInner {
     /// - Writes output SSTs up to `max_sst_size`, reporting periodic progress
     ///
     /// ## Returns
-    /// - The destination [`SortedRun`] with all output SST handles.
+    /// - A [`CompactionJobOutput`] holding the destination [`SortedRun`] with all output
+    ///   SST handles.
     #[instrument(level = "debug", skip_all, fields(id = %args.id))]
     async fn execute_compaction_job(
         &self,
         args: StartCompactionJobArgs,
-    ) -> Result<SortedRun, SlateDBError> {
+    ) -> Result<CompactionJobOutput, SlateDBError> {
         debug!("executing compaction [job_args={:?}]", args);
-        let mut all_iter = self.load_iterators(&args).await?;
+        // resumed jobs aren't digested, since part of their output was written
+        // by an earlier run
+        let observer = self
+            .compaction_observer
+            .as_ref()
+            .filter(|_| args.output_ssts.is_empty());
+        let input_digester = observer.map(|_| {
+            Arc::new(Mutex::new(LiveDataDigester::new(
+                args.compaction_clock_tick,
+            )))
+        });
+        let mut output_digester =
+            observer.map(|_| LiveDataDigester::new(args.compaction_clock_tick));
+        let dropped = Arc::new(DroppedEntries::default());
+        let mut all_iter = self
+            .load_iterators(&args, input_digester.clone(), Some(dropped.clone()))
+            .await?;
         let mut output_ssts = args.output_ssts.clone();
         let mut current_writer = self.table_store.table_writer(SsTableId::Compacted(
             self.rand.rng().gen_ulid(self.clock.as_ref()),
         ));
         let mut bytes_written = 0usize;
+        let mut entries_written = 0u64;
         let mut last_progress_report = self.clock.now();
+        let mut last_heartbeat = last_progress_report;
+        self.stats.record_progress(last_heartbeat);
+        let heartbeat_interval =
+            TimeDelta::from_std(self.options.heartbeat_interval).unwrap_or(TimeDelta::MAX);
         // Estimate bytes processed before the resume point, if any.
         let start_bytes_processed = all_iter.start().map_or(0, |(k, _s)| {
             estimate_bytes_before_key(args.sorted_runs.as_slice(), k)
         });
 
         while let Some(kv) = all_iter.next().await? {
-            let duration_since_last_report =
-                self.clock.now().signed_duration_since(last_progress_report);
+            let now = self.clock.now();
+            let duration_since_last_report = now.signed_duration_since(last_progress_report);
             if duration_since_last_report > TimeDelta::seconds(1) {
                 let total_bytes = start_bytes_processed + all_iter.bytes_processed();
                 self.send_compaction_progress(args.id, total_bytes, &output_ssts);
                 last_progress_report = self.clock.now();
             }
+            if now.signed_duration_since(last_heartbeat) >= heartbeat_interval {
+                debug!(
+                    "compaction heartbeat [id={}, entries_processed={}, current_key={:?}]",
+                    args.id,
+                    all_iter.entries_processed(),
+                    kv.key,
+                );
+                self.stats.record_progress(now);
+                last_heartbeat = now;
+            }
 
+            if let Some(digester) = &mut output_digester {
+                digester.update(&kv);
+            }
             if let Some(block_size) = current_writer.add(kv).await? {
                 bytes_written += block_size;
             }
+            entries_written += 1;
 
             if bytes_written > self.options.max_sst_size {
                 let finished_writer = mem::replace(
@@ -437,6 +494,7 @@ impl TokioCompactionExecutorInner {
                 self.stats.bytes_compacted.increment(sst.info.filter_offset);
                 output_ssts.push(sst);
                 bytes_written = 0;
+                self.stats.record_progress(self.clock.now());
                 let total_bytes = start_bytes_processed + all_iter.bytes_processed();
                 self.send_compaction_progress(args.id, total_bytes, &output_ssts);
                 last_progress_report = self.clock.now();
@@ -450,7 +508,17 @@ impl TokioCompactionExecutorInner {
             output_ssts.push(sst);
         }
 
-        Ok(SortedRun {
+        if let (Some(observer), Some(input), Some(mut output)) =
+            (observer, input_digester, output_digester)
+        {
+            observer.on_compaction_job_finished(&CompactionDigests {
+                destination: args.destination,
+                input: input.lock().finish(),
+                output: output.finish(),
+            });
+        }
+
+        let sorted_run = SortedRun {
             id: args.destination,
             sst_views: output_ssts
                 .into_iter()
@@ -459,6 +527,14 @@ impl TokioCompactionExecutorInner {
                     SsTableView::new(id, sst)
                 })
                 .collect(),
+        };
+        Ok(CompactionJobOutput {
+            sorted_run,
+            entries_read: all_iter.entries_processed(),
+            entries_written,
+            tombstones_dropped: dropped.tombstones(),
+            expired_dropped: dropped.expired(),
+            duplicates_dropped: dropped.duplicates(),
         })
     }
 
@@ -559,6 +635,7 @@ mod tests {
     use crate::types::{RowEntry, ValueDeletable};
     use crate::Db;
     use bytes::Bytes;
+    use chrono::{DateTime, Utc};
     use object_store::memory::InMemory;
     use object_store::path::Path;
     use object_store::ObjectStore;
@@ -567,9 +644,11 @@ mod tests {
     use proptest::test_runner::Config;
     use proptest::{prop_assume, prop_oneof, proptest};
     use rstest::rstest;
-    use slatedb_common::clock::DefaultSystemClock;
+    use slatedb_common::clock::{DefaultSystemClock, MockSystemClock, SystemClockTicker};
     use std::cmp::Ordering;
     use std::collections::HashSet;
+    use std::future::Future;
+    use std::pin::Pin;
     use std::time::Duration;
 
     async fn write_sst(
@@ -1031,6 +1110,7 @@ mod tests {
             merge_operator,
             #[cfg(feature = "compaction_filters")]
             compaction_filter_supplier: None,
+            compaction_observer: None,
         });
 
         // Materialize L0 SSTs from the provided entry sets. Use a huge max size so
@@ -1097,7 +1177,11 @@ mod tests {
 
         // Verify the resumed iterator yields all remaining rows, starting immediately
         // after the persisted prefix and continuing in sorted order.
-        let mut iter = executor.inner.load_iterators(&job_args).await.unwrap();
+        let mut iter = executor
+            .inner
+            .load_iterators(&job_args, None, None)
+            .await
+            .unwrap();
         let mut resumed_entries = Vec::new();
         while let Some(entry) = iter.next().await.unwrap() {
             resumed_entries.push(entry);
@@ -1239,6 +1323,7 @@ mod tests {
                     merge_operator,
                     #[cfg(feature = "compaction_filters")]
                     compaction_filter_supplier: None,
+                    compaction_observer: None,
                 });
 
                 let mut l0_ssts = Vec::new();
@@ -1266,7 +1351,7 @@ mod tests {
                     .unwrap();
 
                 let mut expected_entries = Vec::new();
-                for sst in &full_run.sst_views {
+                for sst in &full_run.sorted_run.sst_views {
                     let mut iter = SstIterator::new(
                         SstView::Borrowed(sst, BytesRange::from(..)),
                         table_store.clone(),
@@ -1311,7 +1396,7 @@ mod tests {
                         .unwrap();
 
                     let mut resumed_entries = Vec::new();
-                    for sst in &resumed_run.sst_views {
+                    for sst in &resumed_run.sorted_run.sst_views {
                         let mut iter = SstIterator::new(
                             SstView::Borrowed(sst, BytesRange::from(..)),
                             table_store.clone(),
@@ -1335,32 +1420,55 @@ mod tests {
     struct TestContext {
         executor: TokioCompactionExecutor,
         table_store: Arc<TableStore>,
+        stats: Arc<CompactionStats>,
         rx: async_channel::Receiver<CompactorMessage>,
     }
 
     /// Builder for creating test context with configurable options.
     struct TestContextBuilder {
         path: String,
+        options: CompactorOptions,
         merge_operator: Option<MergeOperatorType>,
         #[cfg(feature = "compaction_filters")]
         compaction_filter_supplier: Option<Arc<dyn CompactionFilterSupplier>>,
+        compaction_observer: Option<Arc<dyn CompactionObserver>>,
+        executor_clock: Option<Arc<dyn SystemClock>>,
     }
 
     impl TestContextBuilder {
         fn new(path: &str) -> Self {
             Self {
                 path: path.to_string(),
+                options: CompactorOptions::default(),
                 merge_operator: None,
                 #[cfg(feature = "compaction_filters")]
                 compaction_filter_supplier: None,
+                compaction_observer: None,
+                executor_clock: None,
             }
         }
 
+        fn with_options(mut self, options: CompactorOptions) -> Self {
+            self.options = options;
+            self
+        }
+
         fn with_merge_operator(mut self, merge_operator: MergeOperatorType) -> Self {
             self.merge_operator = Some(merge_operator);
             self
         }
 
+        fn with_compaction_observer(mut self, observer: Arc<dyn CompactionObserver>) -> Self {
+            self.compaction_observer = Some(observer);
+            self
+        }
+
+        /// Sets the clock of the executor only. The db keeps a real clock.
+        fn with_executor_clock(mut self, clock: Arc<dyn SystemClock>) -> Self {
+            self.executor_clock = Some(clock);
+            self
+        }
+
         #[cfg(feature = "compaction_filters")]
         fn with_compaction_filter_supplier(
             mut self,
@@ -1372,7 +1480,7 @@ mod tests {
 
         async fn build(self) -> TestContext {
             let handle = tokio::runtime::Handle::current();
-            let options = Arc::new(CompactorOptions::default());
+            let options = Arc::new(self.options);
             let (tx, rx) = async_channel::unbounded();
             let os = Arc::new(InMemory::new());
             let clock = Arc::new(DefaultSystemClock::new());
@@ -1387,26 +1495,29 @@ mod tests {
                 os.clone(),
             ));
 
+            let stats = {
+                let recorder = slatedb_common::metrics::MetricsRecorderHelper::noop();
+                Arc::new(CompactionStats::new(&recorder))
+            };
             let executor = TokioCompactionExecutor::new(TokioCompactionExecutorOptions {
                 handle,
                 options,
                 worker_tx: tx,
                 table_store: table_store.clone(),
                 rand: Arc::new(DbRand::new(100u64)),
-                stats: {
-                    let recorder = slatedb_common::metrics::MetricsRecorderHelper::noop();
-                    Arc::new(CompactionStats::new(&recorder))
-                },
-                clock,
+                stats: stats.clone(),
+                clock: self.executor_clock.unwrap_or(clock),
                 manifest_store,
                 merge_operator: self.merge_operator,
                 #[cfg(feature = "compaction_filters")]
                 compaction_filter_supplier: self.compaction_filter_supplier,
+                compaction_observer: self.compaction_observer,
             });
 
             TestContext {
                 executor,
                 table_store,
+                stats,
                 rx,
             }
         }
@@ -1437,7 +1548,7 @@ mod tests {
                 loop {
                     let msg = self.rx.recv().await.unwrap();
                     if let CompactorMessage::CompactionJobFinished { id: _, result } = msg {
-                        return result;
+                        return result.map(|output| output.sorted_run);
                     }
                 }
             })
@@ -1446,6 +1557,145 @@ mod tests {
         }
     }
 
+    #[derive(Default)]
+    struct RecordingObserver {
+        digests: Mutex<Vec<CompactionDigests>>,
+    }
+
+    impl CompactionObserver for RecordingObserver {
+        fn on_compaction_job_finished(&self, digests: &CompactionDigests) {
+            self.digests.lock().push(digests.clone());
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_compaction_job_reports_matching_live_data_digests() {
+        let observer = Arc::new(RecordingObserver::default());
+        let ctx = TestContextBuilder::new("testdb")
+            .with_compaction_observer(observer.clone())
+            .build()
+            .await;
+        let table_store = ctx.table_store.clone();
+
+        let entries = [
+            RowEntry::new_value(b"a", b"2", 3),
+            RowEntry::new_value(b"a", b"1", 1),
+            RowEntry::new_tombstone(b"b", 4),
+            RowEntry::new_value(b"b", b"1", 2),
+            RowEntry::new_value(b"c", b"1", 5),
+        ];
+        let mut sst_builder = table_store.table_builder();
+        for entry in entries.iter().cloned() {
+            sst_builder.add(entry).await.unwrap();
+        }
+        let encoded_sst = sst_builder.build().await.unwrap();
+        let id = SsTableId::Compacted(Ulid::new());
+        let l0 = table_store
+            .write_sst(&id, &encoded_sst, false)
+            .await
+            .unwrap();
+
+        ctx.run_compaction(vec![l0], true, None).await.unwrap();
+
+        let digests = observer.digests.lock().clone();
+        assert_eq!(digests.len(), 1);
+        let digests = &digests[0];
+        assert!(digests.is_consistent());
+        // only the newest versions of "a" and "c" are live, and the last run
+        // drops everything else
+        let mut expected = LiveDataDigester::new(0);
+        expected.update(&entries[0]);
+        expected.update(&entries[4]);
+        assert_eq!(digests.output, expected.finish());
+        assert_eq!(digests.input.live_keys, 2);
+        assert_eq!(digests.input.entries, 5);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_compaction_job_records_heartbeat_progress() {
+        // every read of the clock moves it forward a millisecond, so reading
+        // the 100 entries spans many heartbeat intervals
+        let heartbeat_interval = Duration::from_millis(10);
+        let clock = Arc::new(SteppingClock::new(1_000_000, 1));
+        let ctx = TestContextBuilder::new("testdb")
+            .with_options(CompactorOptions {
+                heartbeat_interval,
+                ..CompactorOptions::default()
+            })
+            .with_executor_clock(clock.clone())
+            .build()
+            .await;
+        let table_store = ctx.table_store.clone();
+        let stats = ctx.stats.clone();
+        assert_eq!(stats.last_progress_at(), None);
+
+        let mut sst_builder = table_store.table_builder();
+        for i in 0..100u64 {
+            let key = format!("key{}", i);
+            sst_builder
+                .add(RowEntry::new_value(key.as_bytes(), b"value", i + 1))
+                .await
+                .unwrap();
+        }
+        let encoded_sst = sst_builder.build().await.unwrap();
+        let id = SsTableId::Compacted(Ulid::new());
+        let l0 = table_store
+            .write_sst(&id, &encoded_sst, false)
+            .await
+            .unwrap();
+        let started_at = clock.now();
+
+        ctx.run_compaction(vec![l0], true, None).await.unwrap();
+
+        // progress recorded only when the job starts would stay within a few
+        // clock reads of `started_at`
+        let last_progress_at = stats.last_progress_at().expect("no progress recorded");
+        let interval = TimeDelta::from_std(heartbeat_interval).unwrap();
+        assert!(last_progress_at >= started_at + interval * 5);
+    }
+
+    /// A clock that moves forward by a fixed step each time it is read.
+    #[derive(Debug)]
+    struct SteppingClock {
+        inner: MockSystemClock,
+        step_millis: i64,
+    }
+
+    impl SteppingClock {
+        fn new(start_millis: i64, step_millis: i64) -> Self {
+            Self {
+                inner: MockSystemClock::with_time(start_millis),
+                step_millis,
+            }
+        }
+    }
+
+    impl SystemClock for SteppingClock {
+        fn now(&self) -> DateTime<Utc> {
+            let now = self.inner.now();
+            self.inner.set(now.timestamp_millis() + self.step_millis);
+            now
+        }
+
+        fn advance<'a>(
+            &'a self,
+            duration: Duration,
+        ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+            self.inner.advance(duration)
+        }
+
+        fn sleep<'a>(
+            &'a self,
+            duration: Duration,
+        ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+            self.inner.sleep(duration)
+        }
+
+        fn ticker<'a>(&'a self, duration: Duration) -> SystemClockTicker<'a> {
+            SystemClockTicker::new(self, duration)
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_compaction_job_should_retain_merges_newer_than_retention_min_seq_num() {
         let ctx = TestContextBuilder::new("testdb")
@@ -1685,6 +1935,85 @@ mod tests {
         assert!(iter.next().await.unwrap().is_none());
     }
 
+    #[cfg(feature = "compaction_filters")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_compaction_filter_sees_entries_after_dedup() {
+        use crate::compaction_filter::{
+            CompactionFilter, CompactionFilterDecision, CompactionFilterError,
+            CompactionFilterSupplier, CompactionJobContext,
+        };
+
+        /// Records the key and sequence number of each entry it's called with.
+        struct RecordingFilter(Arc<Mutex<Vec<(Bytes, u64)>>>);
+
+        #[async_trait::async_trait]
+        impl CompactionFilter for RecordingFilter {
+            async fn filter(
+                &mut self,
+                entry: &RowEntry,
+            ) -> Result<CompactionFilterDecision, CompactionFilterError> {
+                self.0.lock().push((entry.key.clone(), entry.seq));
+                Ok(CompactionFilterDecision::Keep)
+            }
+
+            async fn on_compaction_end(&mut self) -> Result<(), CompactionFilterError> {
+                Ok(())
+            }
+        }
+
+        struct RecordingFilterSupplier(Arc<Mutex<Vec<(Bytes, u64)>>>);
+
+        #[async_trait::async_trait]
+        impl CompactionFilterSupplier for RecordingFilterSupplier {
+            async fn create_compaction_filter(
+                &self,
+                _context: &CompactionJobContext,
+            ) -> Result<Box<dyn CompactionFilter>, CompactionFilterError> {
+                Ok(Box::new(RecordingFilter(self.0.clone())))
+            }
+        }
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let ctx = TestContextBuilder::new("testdb_filter_after_dedup")
+            .with_compaction_filter_supplier(Arc::new(RecordingFilterSupplier(seen.clone())))
+            .build()
+            .await;
+        let table_store = ctx.table_store.clone();
+
+        // the newer SST overwrites key1, which the older one also holds
+        let mut l0s = Vec::new();
+        for entries in [
+            vec![RowEntry::new_value(b"key1", b"new", 3)],
+            vec![
+                RowEntry::new_value(b"key1", b"old", 1),
+                RowEntry::new_value(b"key2", b"value", 2),
+            ],
+        ] {
+            let mut sst_builder = table_store.table_builder();
+            for entry in entries {
+                sst_builder.add(entry).await.unwrap();
+            }
+            let encoded_sst = sst_builder.build().await.unwrap();
+            let id = SsTableId::Compacted(Ulid::new());
+            l0s.push(
+                table_store
+                    .write_sst(&id, &encoded_sst, false)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        ctx.run_compaction(l0s, true, None).await.unwrap();
+
+        assert_eq!(
+            *seen.lock(),
+            vec![
+                (Bytes::from_static(b"key1"), 3),
+                (Bytes::from_static(b"key2"), 2)
+            ]
+        );
+    }
+
     #[cfg(feature = "compaction_filters")]
     #[tokio::test(flavor = "multi_thread")]
     async fn test_compaction_job_aborts_on_filter_creation_error() {
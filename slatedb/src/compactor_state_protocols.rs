@@ -12,11 +12,13 @@
 use std::sync::Arc;
 use std::time::Duration;
 
+use bytes::Bytes;
 use log::{debug, info};
 
 use crate::compactions_store::{CompactionsStore, FenceableCompactions, StoredCompactions};
 use crate::compactor_state::{CompactionStatus, CompactorState, VersionedCompactions};
 use crate::config::{CheckpointOptions, CompactorOptions};
+use crate::db_state::max_l0_overlap;
 use crate::error::SlateDBError;
 use crate::manifest::store::{FenceableManifest, ManifestStore, StoredManifest};
 use crate::manifest::VersionedManifest;
@@ -45,6 +47,72 @@ impl CompactorStateView {
     pub fn manifest(&self) -> &VersionedManifest {
         &self.manifest
     }
+
+    /// Summarizes every LSM tree in the manifest: the unsegmented root tree first,
+    /// followed by each named segment in prefix order.
+    ///
+    /// This is the input most [`crate::compactor::CompactionScheduler`] policies need
+    /// (file counts, sizes and L0 overlap) without walking the manifest directly.
+    pub fn tree_stats(&self) -> Vec<LsmTreeStats> {
+        let active_compactions = self
+            .compactions()
+            .into_iter()
+            .flat_map(|c| c.recent_compactions())
+            .filter(|c| c.active())
+            .collect::<Vec<_>>();
+        self.manifest
+            .core()
+            .trees_with_prefix()
+            .map(|(segment, tree)| LsmTreeStats {
+                l0_count: tree.l0.len(),
+                l0_size_bytes: tree.l0.iter().map(|view| view.estimate_size()).sum(),
+                l0_max_overlap: max_l0_overlap(&tree.l0),
+                sorted_runs: tree
+                    .compacted
+                    .iter()
+                    .map(|sr| SortedRunStats {
+                        id: sr.id,
+                        sst_count: sr.sst_views.len(),
+                        size_bytes: sr.estimate_size(),
+                    })
+                    .collect(),
+                active_compactions: active_compactions
+                    .iter()
+                    .filter(|c| c.spec().segment() == &segment)
+                    .count(),
+                segment,
+            })
+            .collect()
+    }
+}
+
+/// Size and shape of a single LSM tree, as seen by a compaction scheduler.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LsmTreeStats {
+    /// The segment prefix of the tree. Empty for the unsegmented root tree.
+    pub segment: Bytes,
+    /// Number of L0 SSTs.
+    pub l0_count: usize,
+    /// Estimated total size of the L0 SSTs in bytes.
+    pub l0_size_bytes: u64,
+    /// The largest number of L0 SSTs whose key ranges overlap at any single key.
+    /// This bounds how many L0 SSTs a point read may have to probe.
+    pub l0_max_overlap: usize,
+    /// The compacted sorted runs, in manifest order (newest first).
+    pub sorted_runs: Vec<SortedRunStats>,
+    /// Number of compactions currently running against this tree.
+    pub active_compactions: usize,
+}
+
+/// Size and shape of a single sorted run, as seen by a compaction scheduler.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortedRunStats {
+    /// The sorted run id.
+    pub id: u32,
+    /// Number of SSTs in the sorted run.
+    pub sst_count: usize,
+    /// Estimated size of the sorted run in bytes.
+    pub size_bytes: u64,
 }
 
 /// Converts a full [`CompactorState`] into a read-only view.
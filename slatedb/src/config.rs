@@ -186,6 +186,7 @@
 //!
 use crate::filter_policy::FilterContext;
 use crate::iter::IterationOrder;
+use chrono::{DateTime, Utc};
 use duration_str::{deserialize_duration, deserialize_option_duration};
 use figment::providers::{Env, Format, Json, Toml, Yaml};
 use figment::{Figment, Metadata, Provider};
@@ -209,6 +210,99 @@ pub enum PreloadLevel {
     AllSst,
 }
 
+/// The order in which queued immutable memtables are uploaded to L0.
+///
+/// This only decides which upload starts next. Memtables are still added to
+/// the manifest, and the WAL is still truncated, strictly in the order they
+/// were frozen: a memtable is released, and the WALs it covers become
+/// obsolete, only once it and every older memtable are durable in L0.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub enum FlushOrder {
+    /// Upload memtables in the order they were frozen.
+    #[default]
+    OldestFirst,
+    /// Upload the largest queued memtable first. Useful when uploads are slow
+    /// relative to writes, so the bulk of the queued bytes starts uploading
+    /// as early as possible.
+    LargestFirst,
+}
+
+/// Options for warming the block cache after the database opens. See
+/// [`Settings::block_cache_warmup`].
+///
+/// Warming runs in the background and doesn't delay opening the database.
+/// SSTs are warmed newest first: the L0 SSTs from the newest to the oldest,
+/// followed by the SSTs of the sorted runs from the newest run to the oldest.
+/// For each SST, the filters, the index and all data blocks are loaded.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct BlockCacheWarmupOptions {
+    /// Which SSTs may be warmed.
+    pub level: PreloadLevel,
+    /// Warming stops at the first SST that would bring the estimated size of
+    /// the SSTs warmed so far above this many bytes. Keep it below the block
+    /// cache capacity, or the warmup evicts the blocks it loaded first.
+    pub max_bytes: u64,
+}
+
+impl Default for BlockCacheWarmupOptions {
+    fn default() -> Self {
+        Self {
+            level: PreloadLevel::L0Sst,
+            max_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// The data structure backing the mutable memtable.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub enum MemtableType {
+    /// A concurrent skip list. Accepts writes in any key order.
+    #[default]
+    SkipMap,
+    /// An ordered log that writes are appended to, with binary search for
+    /// lookups. Suited to workloads whose keys are strictly increasing (for
+    /// example time-ordered or sequence-numbered keys), where it avoids the
+    /// per-insert cost of maintaining the skip list. `on_out_of_order`
+    /// decides what happens when a write's key is not greater than the
+    /// last key in the memtable.
+    AppendOnly {
+        on_out_of_order: OutOfOrderWritePolicy,
+    },
+}
+
+/// What an append-only memtable does with a write whose key is not greater
+/// than the last key it holds. See [`MemtableType::AppendOnly`].
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub enum OutOfOrderWritePolicy {
+    /// Convert the memtable to a skip list and accept the write. The next
+    /// memtable starts out append-only again.
+    #[default]
+    Fallback,
+    /// Reject the write batch with an invalid-argument error. Nothing from
+    /// the batch is written.
+    /// Entries replayed from the WAL on startup are never rejected; a
+    /// replayed memtable falls back instead.
+    Reject,
+}
+
+/// What WAL replay does when it can't read a WAL SST, for example because a
+/// block fails its checksum or a row doesn't decode. Only data errors are
+/// subject to the policy; a WAL SST that can't be fetched from object storage
+/// always fails replay. See [`Settings::wal_replay_policy`].
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub enum ReplayPolicy {
+    /// Fail replay, and with it [`crate::Db::open`], on the first unreadable WAL
+    /// SST.
+    #[default]
+    Strict,
+    /// Log and skip the unreadable WAL SST, none of whose writes are applied,
+    /// and continue replaying the WAL SSTs after it.
+    SkipBad,
+    /// Log and stop replay at the first unreadable WAL SST. Its writes and
+    /// those of every later WAL SST are treated as lost.
+    Truncate,
+}
+
 /// Enum representing valid SST block sizes
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Default)]
 pub enum SstBlockSize {
@@ -256,6 +350,14 @@ impl SstBlockSize {
 #[derive(Clone, Default, Debug, Copy, PartialEq)]
 pub enum DurabilityLevel {
     /// Includes only data currently stored durably in object storage.
+    ///
+    /// Reads at this level never observe a write that could be lost in a crash,
+    /// which makes it suitable for serving replicas. Writes still held in the
+    /// active or an immutable memtable are hidden until they are durable: with
+    /// the WAL enabled that is once their WAL SST is written, and with the WAL
+    /// disabled once their memtable is flushed to L0. Visibility is decided by
+    /// the last durable sequence number rather than per memtable, so a memtable
+    /// that is partially durable contributes only its durable writes.
     Remote,
 
     /// Includes data with level Remote and data currently only stored in-memory awaiting flush
@@ -280,6 +382,17 @@ pub struct ReadOptions {
     /// Optional context forwarded to custom filter policies; ignored by
     /// built-in filters. See [`FilterContext`].
     pub filter_context: Option<FilterContext>,
+    /// If set, `Db::get` may answer from a short-lived cache of recent point
+    /// reads, as long as the cached result is no older than this. Results read
+    /// this way are also added to the cache. A write to a key invalidates its
+    /// cached result, so a zero staleness still observes every write made
+    /// through this `Db`. Data that becomes visible without a write, such as a
+    /// WAL flush satisfying a `Remote` durability filter, may lag by up to the
+    /// staleness bound.
+    ///
+    /// Ignored by readers, snapshots and transactions. Defaults to `None`,
+    /// which always performs a full read.
+    pub max_cache_staleness: Option<Duration>,
 }
 
 impl Default for ReadOptions {
@@ -289,6 +402,7 @@ impl Default for ReadOptions {
             dirty: false,
             cache_blocks: true,
             filter_context: None,
+            max_cache_staleness: None,
         }
     }
 }
@@ -322,6 +436,13 @@ impl ReadOptions {
             ..self
         }
     }
+
+    pub fn with_max_cache_staleness(self, max_cache_staleness: Option<Duration>) -> Self {
+        Self {
+            max_cache_staleness,
+            ..self
+        }
+    }
 }
 #[derive(Clone, Debug)]
 pub struct ScanOptions {
@@ -349,6 +470,40 @@ pub struct ScanOptions {
     /// Only consulted for `scan_prefix` today. Plain range scans do not
     /// evaluate SST filters, so this field has no effect on `scan`.
     pub filter_context: Option<FilterContext>,
+    /// Optional deadline for the scan, compared against the database's
+    /// [`SystemClock`](slatedb_common::clock::SystemClock). Once it has
+    /// passed, the scan's iterator fails with an
+    /// [`ErrorKind::Unavailable`](crate::ErrorKind::Unavailable) error instead
+    /// of reading further, and keeps failing on every later call. To limit
+    /// clock reads, the deadline is checked before the first entry is read
+    /// and then once every few dozen entries, counting deleted entries the
+    /// scan skips over, so a scan may return a few entries after the deadline
+    /// has passed. Defaults to `None`, which never aborts the scan.
+    pub deadline: Option<DateTime<Utc>>,
+    /// Whether the scan re-inserts a sample of the entries it reads from SSTs
+    /// into the active memtable, so that later reads of their keys are served
+    /// from memory. See [`Settings::read_repair_sample_rate`].
+    ///
+    /// A repair is written like any other write, with a new sequence number,
+    /// the value the scan read, and the same expiry time, without waiting for
+    /// it to be applied. It's dropped if the key was written again after the
+    /// scan read it. Only used by [`crate::Db::scan_with_options`] and
+    /// [`crate::Db::scan_prefix_with_options`]. Defaults to `false`.
+    pub read_repair: bool,
+    /// The maximum number of entries a call to the scan's iterator reads
+    /// before it yields to the async runtime, counting deleted entries the
+    /// scan skips over. This bounds how long a call can hold its executor
+    /// thread when many entries in a row are skipped, such as a run of
+    /// tombstones. Reading resumes where it left off when the call is polled
+    /// again. Defaults to `None`, which never yields.
+    pub max_entries_before_yield: Option<usize>,
+    /// The number of entries read from the SSTs ahead of the consumer. When
+    /// set, the SSTs are read on a background task that keeps up to this many
+    /// entries buffered, so block fetches overlap with whatever the caller does
+    /// with the entries it already has. This is separate from
+    /// `read_ahead_bytes`, which sizes each fetch. Defaults to `None`, which
+    /// reads an SST entry only when the scan asks for it.
+    pub prefetch_depth: Option<usize>,
 }
 
 impl Default for ScanOptions {
@@ -362,6 +517,10 @@ impl Default for ScanOptions {
             max_fetch_tasks: 1,
             order: IterationOrder::Ascending,
             filter_context: None,
+            deadline: None,
+            read_repair: false,
+            max_entries_before_yield: None,
+            prefetch_depth: None,
         }
     }
 }
@@ -413,6 +572,31 @@ impl ScanOptions {
             ..self
         }
     }
+
+    pub fn with_deadline(self, deadline: Option<DateTime<Utc>>) -> Self {
+        Self { deadline, ..self }
+    }
+
+    pub fn with_read_repair(self, read_repair: bool) -> Self {
+        Self {
+            read_repair,
+            ..self
+        }
+    }
+
+    pub fn with_max_entries_before_yield(self, max_entries_before_yield: Option<usize>) -> Self {
+        Self {
+            max_entries_before_yield,
+            ..self
+        }
+    }
+
+    pub fn with_prefetch_depth(self, prefetch_depth: Option<usize>) -> Self {
+        Self {
+            prefetch_depth,
+            ..self
+        }
+    }
 }
 
 /// Enum representing the type of flush to perform.
@@ -457,6 +641,9 @@ pub struct WriteOptions {
     /// The value must be strictly greater than the current maximum sequence number
     /// or the write will fail with an `InvalidSequenceNumber` error.
     pub seqnum: u64,
+    /// The tenant the write is made for, whose write rate the database's
+    /// [`crate::RateLimiter`] limits. Writes without a tenant aren't limited.
+    pub tenant: Option<String>,
 }
 
 impl Default for WriteOptions {
@@ -467,6 +654,7 @@ impl Default for WriteOptions {
             #[cfg(dst)]
             now: 0,
             seqnum: 0,
+            tenant: None,
         }
     }
 }
@@ -588,6 +776,21 @@ pub struct CheckpointOptions {
     pub name: Option<String>,
 }
 
+/// Specify options to provide when exporting a range as an SST file. See
+/// [`crate::Db::export_sst_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct ExportSstOptions {
+    /// Whether to export the tombstones of deleted keys. Defaults to `false`.
+    pub include_tombstones: bool,
+
+    /// Whether to export entries whose TTL has expired but that compaction
+    /// hasn't removed yet. Defaults to `false`.
+    pub include_expired: bool,
+
+    /// The options used to scan the exported range.
+    pub scan_options: ScanOptions,
+}
+
 /// Settings represents the configuration options that a user can tweak to customize
 /// the database engine to their use case.
 ///
@@ -681,6 +884,12 @@ pub struct Settings {
     /// This also bounds the amount of WAL data that needs to be replayed on recovery: once
     /// this many WAL flushes have occurred since the last memtable freeze, the active
     /// memtable will be frozen even if it has not reached `l0_sst_size_bytes`.
+    ///
+    /// Each frozen memtable records the id of the last WAL SST it holds writes from, so
+    /// the WAL SSTs up to that id can be garbage collected as soon as it is flushed to L0.
+    /// Setting this aligns L0 flushes with a fixed number of WAL SSTs.
+    ///
+    /// Must be at least 4096.
     pub max_wal_flushes_before_l0_flush: u64,
 
     /// Defines the max total number of SSTs in L0 across the entire key space. Memtables
@@ -705,6 +914,12 @@ pub struct Settings {
     /// slot is treated conservatively as contributing to the peak at every point.
     pub l0_max_ssts_per_key: usize,
 
+    /// The order in which immutable memtables are uploaded to L0. See [`FlushOrder`].
+    ///
+    /// Default: [`FlushOrder::OldestFirst`]
+    #[serde(default)]
+    pub flush_order: FlushOrder,
+
     /// Number of parallel workers for flushing immutable memtables to L0 SSTs.
     /// Higher values increase L0 flush throughput at the cost of more concurrent
     /// object store uploads. Increasing parallelism may require a higher `l0_max_ssts`
@@ -719,6 +934,28 @@ pub struct Settings {
     /// to object storage.
     pub max_unflushed_bytes: usize,
 
+    /// Defines a hard cap on the number of immutable memtables waiting to be flushed to L0.
+    /// Unlike [`Self::max_unflushed_bytes`], which pauses writers until flushing catches up,
+    /// writes fail fast with an unavailable error while this many immutable memtables are
+    /// queued. This is a backstop for when flushing may never catch up, such as when the
+    /// object store is degraded. `None` disables the cap.
+    ///
+    /// Default: `None`
+    #[serde(default)]
+    pub max_immutable_memtables: Option<usize>,
+
+    /// Defines a global budget on the key/value pair bytes held by the active memtable and
+    /// all immutable memtables together. Unlike [`Self::l0_sst_size_bytes`], which bounds
+    /// each memtable on its own, this bounds their sum, which can grow large when many
+    /// small immutable memtables queue up behind a slow flush. Writes are paused while the
+    /// sum exceeds this value until an immutable memtable has been flushed to L0. If the
+    /// active memtable alone exceeds it, it's frozen so that it can be flushed. `None`
+    /// disables the budget.
+    ///
+    /// Default: `None`
+    #[serde(default)]
+    pub max_memtable_bytes: Option<usize>,
+
     /// Configuration options for the compactor.
     pub compactor_options: Option<CompactorOptions>,
 
@@ -734,9 +971,96 @@ pub struct Settings {
     /// The default time-to-live (TTL) for insertions (note that re-inserting a key
     /// with any value will update the TTL to use the default_ttl)
     ///
+    /// Expiry is read from the db's system clock. Reads treat an expired entry
+    /// like a deleted one from the moment it expires, whether or not it has
+    /// been flushed or compacted, and its space is reclaimed by compaction
+    /// without any further action: a compaction that starts after the entry's
+    /// expiry rewrites it as a tombstone, which is dropped once it reaches the
+    /// last sorted run and no snapshot still needs the older versions it
+    /// shadows.
+    ///
     /// Default: no TTL (insertions will remain until deleted)
     pub default_ttl: Option<u64>,
 
+    /// Randomly shifts the expiry of each entry written with a relative TTL
+    /// (the default TTL or [`Ttl::ExpireAfter`]) by up to this fraction of its
+    /// TTL, in either direction, so that keys written together with the same
+    /// TTL don't all expire at once. With a jitter of `0.1`, an entry written
+    /// with a TTL of 1000 expires between 900 and 1100 after its write.
+    ///
+    /// Jitter is drawn separately for every entry, from the database's random
+    /// number generator, so it is deterministic when the database is opened
+    /// with [`crate::DbBuilder::with_seed`]. Entries written with
+    /// [`Ttl::ExpireAt`] are never jittered. Must be at least 0 and less than 1.
+    ///
+    /// Default: `0.0` (no jitter)
+    #[serde(default)]
+    pub ttl_jitter: f64,
+
+    /// The data structure backing the mutable memtable. See [`MemtableType`].
+    ///
+    /// Default: [`MemtableType::SkipMap`]
+    #[serde(default)]
+    pub memtable_type: MemtableType,
+
+    /// The size, in bits, of the approximate key filter kept by each memtable.
+    /// A point lookup skips the memtables whose filter rules its key out
+    /// instead of searching them, which saves time when many immutable
+    /// memtables are waiting to be flushed. Every write to a memtable,
+    /// including a delete, adds its key to the filter, and keys are never
+    /// removed, so a deleted key is always searched for.
+    ///
+    /// The filter's size is fixed when the memtable is created, so its false
+    /// positive rate rises with the number of distinct keys the memtable
+    /// holds: about 1% at one key per 10 bits, and close to 100% at one key
+    /// per bit. A false positive only costs the search the filter would have
+    /// saved. Set to `0` to disable the filters.
+    ///
+    /// Default: `65536` (8 KiB per memtable)
+    #[serde(default = "default_memtable_filter_bits")]
+    pub memtable_filter_bits: usize,
+
+    /// Whether immutable memtables that wait to be flushed to L0, e.g. because
+    /// L0 is full, store their keys front-coded. Each key is then stored as
+    /// the length of the prefix it shares with the key before it plus the
+    /// rest of the key, which saves memory when keys share long prefixes.
+    /// Reads from a front-coded memtable decode keys as they go, so they are
+    /// slower. Memtables are re-encoded on a blocking thread, off both the
+    /// write path and the flusher's event loop.
+    ///
+    /// This doesn't relieve write backpressure: the memtable sizes counted
+    /// against [`Settings::max_unflushed_bytes`] are still estimated from the
+    /// raw entries, so writers stall at the same point with or without it.
+    ///
+    /// Default: `false`
+    #[serde(default)]
+    pub front_code_immutable_memtables: bool,
+
+    /// The fraction of entries, from 0 to 1, that a scan with
+    /// [`ScanOptions::read_repair`] set re-inserts into the active memtable
+    /// when it reads them from an SST. Sampling bounds the writes a scan can
+    /// cause, while the keys that are read most often still get repaired.
+    ///
+    /// Default: `0.01`
+    #[serde(default = "default_read_repair_sample_rate")]
+    pub read_repair_sample_rate: f64,
+
+    /// What replaying the WAL on open does with a WAL SST it can't read. See
+    /// [`ReplayPolicy`]. A skipped or truncated WAL SST is never replayed
+    /// again, so its writes are lost for good.
+    ///
+    /// Default: [`ReplayPolicy::Strict`]
+    #[serde(default)]
+    pub wal_replay_policy: ReplayPolicy,
+
+    /// Warms the block cache with the newest SSTs in the background after the
+    /// database opens, so that reads of recently written keys don't have to go
+    /// to object storage first. See [`BlockCacheWarmupOptions`].
+    ///
+    /// Default: `None` (no warmup)
+    #[serde(default)]
+    pub block_cache_warmup: Option<BlockCacheWarmupOptions>,
+
     /// The block format for SST files. This is only available in tests
     /// to verify backward compatibility between V1 and V2 formats.
     #[cfg(test)]
@@ -759,6 +1083,8 @@ impl std::fmt::Debug for Settings {
             .field("manifest_update_timeout", &self.manifest_update_timeout)
             .field("min_filter_keys", &self.min_filter_keys)
             .field("max_unflushed_bytes", &self.max_unflushed_bytes)
+            .field("max_immutable_memtables", &self.max_immutable_memtables)
+            .field("max_memtable_bytes", &self.max_memtable_bytes)
             .field("l0_sst_size_bytes", &self.l0_sst_size_bytes)
             .field(
                 "max_wal_flushes_before_l0_flush",
@@ -766,6 +1092,7 @@ impl std::fmt::Debug for Settings {
             )
             .field("l0_max_ssts", &self.l0_max_ssts)
             .field("l0_max_ssts_per_key", &self.l0_max_ssts_per_key)
+            .field("flush_order", &self.flush_order)
             .field("l0_flush_parallelism", &self.l0_flush_parallelism)
             .field("compactor_options", &self.compactor_options)
             .field("compression_codec", &self.compression_codec)
@@ -774,7 +1101,17 @@ impl std::fmt::Debug for Settings {
                 &self.object_store_cache_options,
             )
             .field("garbage_collector_options", &self.garbage_collector_options)
-            .field("default_ttl", &self.default_ttl);
+            .field("default_ttl", &self.default_ttl)
+            .field("ttl_jitter", &self.ttl_jitter)
+            .field("memtable_type", &self.memtable_type)
+            .field("memtable_filter_bits", &self.memtable_filter_bits)
+            .field(
+                "front_code_immutable_memtables",
+                &self.front_code_immutable_memtables,
+            )
+            .field("read_repair_sample_rate", &self.read_repair_sample_rate)
+            .field("wal_replay_policy", &self.wal_replay_policy)
+            .field("block_cache_warmup", &self.block_cache_warmup);
         data.finish()
     }
 }
@@ -951,6 +1288,20 @@ impl Provider for Settings {
     }
 }
 
+/// The default for [`Settings::memtable_filter_bits`].
+pub(crate) const DEFAULT_MEMTABLE_FILTER_BITS: usize = 65536;
+
+fn default_memtable_filter_bits() -> usize {
+    DEFAULT_MEMTABLE_FILTER_BITS
+}
+
+/// The default for [`Settings::read_repair_sample_rate`].
+pub(crate) const DEFAULT_READ_REPAIR_SAMPLE_RATE: f64 = 0.01;
+
+fn default_read_repair_sample_rate() -> f64 {
+    DEFAULT_READ_REPAIR_SAMPLE_RATE
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
@@ -961,16 +1312,26 @@ impl Default for Settings {
             manifest_update_timeout: Duration::from_secs(300),
             min_filter_keys: 1000,
             max_unflushed_bytes: 1_073_741_824,
+            max_immutable_memtables: None,
+            max_memtable_bytes: None,
             l0_sst_size_bytes: 64 * 1024 * 1024,
             max_wal_flushes_before_l0_flush: 4096,
             l0_max_ssts: 8,
             l0_max_ssts_per_key: 8,
+            flush_order: FlushOrder::default(),
             l0_flush_parallelism: 4,
             compactor_options: Some(CompactorOptions::default()),
             compression_codec: None,
             object_store_cache_options: ObjectStoreCacheOptions::default(),
             garbage_collector_options: Some(GarbageCollectorOptions::default()),
             default_ttl: None,
+            ttl_jitter: 0.0,
+            memtable_type: MemtableType::default(),
+            memtable_filter_bits: DEFAULT_MEMTABLE_FILTER_BITS,
+            front_code_immutable_memtables: false,
+            read_repair_sample_rate: DEFAULT_READ_REPAIR_SAMPLE_RATE,
+            wal_replay_policy: ReplayPolicy::default(),
+            block_cache_warmup: None,
             #[cfg(test)]
             block_format: None,
         }
@@ -983,6 +1344,9 @@ pub struct DbReaderOptions {
     /// file allows readers to detect newly compacted data. The reader will also look for
     /// new writes to the WAL at this poll interval. If the reader is using an explicit checkpoint,
     /// then the manifest and WAL will not be polled.
+    ///
+    /// Each poll advances [`crate::DbReader::applied_time`], so a polling reader
+    /// lags the database by roughly this interval.
     pub manifest_poll_interval: Duration,
 
     /// For readers that do not provide an explicit checkpoint, the client will
@@ -1092,6 +1456,15 @@ pub struct CompactorOptions {
     /// Scheduler-specific options expressed as string key/value pairs.
     #[serde(default)]
     pub scheduler_options: HashMap<String, String>,
+
+    /// The interval at which a running compaction logs a heartbeat with the
+    /// number of entries it has read and the key it has reached, and records
+    /// its progress for [`crate::compactor::Compactor::last_progress_at`]. A
+    /// compaction that stops making progress, e.g. because reads from the
+    /// object store hang, stops sending heartbeats. The default is 10 seconds.
+    #[serde(deserialize_with = "deserialize_duration")]
+    #[serde(serialize_with = "serialize_duration")]
+    pub heartbeat_interval: Duration,
 }
 
 /// Default options for the compactor. Currently, only a
@@ -1107,6 +1480,7 @@ impl Default for CompactorOptions {
             max_concurrent_compactions: 4,
             max_fetch_tasks: 4,
             scheduler_options: HashMap::new(),
+            heartbeat_interval: Duration::from_secs(10),
         }
     }
 }
@@ -1124,6 +1498,7 @@ impl std::fmt::Debug for CompactorOptions {
             )
             .field("max_fetch_tasks", &self.max_fetch_tasks)
             .field("scheduler_options", &self.scheduler_options)
+            .field("heartbeat_interval", &self.heartbeat_interval)
             .finish()
     }
 }
@@ -1141,6 +1516,10 @@ pub struct SizeTieredCompactionSchedulerOptions {
     /// be included in a given compaction. A sorted run S will be added to a compaction C if S's
     /// size is less than this value times the min size of the runs currently included in C.
     pub include_size_threshold: f32,
+
+    /// How the scheduler chooses between series of sorted runs that are all eligible
+    /// for compaction. Defaults to [`CompactionPriority::Newest`].
+    pub priority: CompactionPriority,
 }
 
 impl Default for SizeTieredCompactionSchedulerOptions {
@@ -1149,6 +1528,48 @@ impl Default for SizeTieredCompactionSchedulerOptions {
             min_compaction_sources: 4,
             max_compaction_sources: 8,
             include_size_threshold: 4.0,
+            priority: CompactionPriority::default(),
+        }
+    }
+}
+
+/// How the size-tiered compaction scheduler chooses which series of sorted runs to
+/// compact when several are eligible. L0 SSTs are always compacted first, since they
+/// hold up writes once there are too many of them.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompactionPriority {
+    /// Compact the eligible series holding the newest sorted runs first.
+    #[default]
+    Newest,
+    /// Compact the eligible series with the highest ratio of delete entries
+    /// (tombstones) to entries first, since compacting it reclaims the most space.
+    /// The ratio is computed from the per-SST counts recorded when the SSTs were
+    /// written. SSTs written before the counts were recorded count as having no
+    /// deletes.
+    DeleteDensity,
+}
+
+impl CompactionPriority {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CompactionPriority::Newest => "newest",
+            CompactionPriority::DeleteDensity => "delete_density",
+        }
+    }
+}
+
+impl FromStr for CompactionPriority {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "newest" => Ok(Self::Newest),
+            "delete_density" => Ok(Self::DeleteDensity),
+            _ => Err(crate::Error::invalid(format!(
+                "unknown compaction priority '{}'",
+                s
+            ))),
         }
     }
 }
@@ -1185,6 +1606,15 @@ impl From<&HashMap<String, String>> for SizeTieredCompactionSchedulerOptions {
                         );
                     }
                 },
+                "priority" => match value.parse::<CompactionPriority>() {
+                    Ok(parsed) => options.priority = parsed,
+                    Err(err) => {
+                        warn!(
+                            "invalid scheduler option value for priority: '{}': {}",
+                            value, err
+                        );
+                    }
+                },
                 _ => {
                     warn!("unknown scheduler option '{}'; ignoring", key);
                 }
@@ -1216,6 +1646,10 @@ impl From<SizeTieredCompactionSchedulerOptions> for HashMap<String, String> {
             "include_size_threshold".to_string(),
             options.include_size_threshold.to_string(),
         );
+        map.insert(
+            "priority".to_string(),
+            options.priority.as_str().to_string(),
+        );
         map
     }
 }
@@ -1485,6 +1919,17 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_settings_without_memtable_filter_bits_use_default() {
+        let mut json = serde_json::to_value(Settings::default()).expect("failed to serialize");
+        json.as_object_mut()
+            .expect("settings should serialize to an object")
+            .remove("memtable_filter_bits");
+
+        let options: Settings = serde_json::from_value(json).expect("failed to deserialize");
+        assert_eq!(DEFAULT_MEMTABLE_FILTER_BITS, options.memtable_filter_bits);
+    }
+
     #[test]
     fn test_db_options_env_with_default_respects_overrides() {
         figment::Jail::expect_with(|_jail| {
@@ -1608,6 +2053,7 @@ object_store_cache_options:
             min_compaction_sources: 3,
             max_compaction_sources: 9,
             include_size_threshold: 7.0,
+            priority: CompactionPriority::DeleteDensity,
         };
 
         let map: HashMap<String, String> = options.into();
@@ -1616,6 +2062,7 @@ object_store_cache_options:
         assert_eq!(roundtripped.min_compaction_sources, 3);
         assert_eq!(roundtripped.max_compaction_sources, 9);
         assert_eq!(roundtripped.include_size_threshold, 7.0);
+        assert_eq!(roundtripped.priority, CompactionPriority::DeleteDensity);
     }
 
     #[test]
@@ -0,0 +1,85 @@
+//! Resolving puts to the same key in one write batch.
+//!
+//! A [`crate::WriteBatch`] that puts a key more than once writes only one of
+//! the values. By default it's the last one put. A [`ConflictResolver`] set
+//! with [`crate::DbBuilder::with_conflict_resolver`] picks the value instead,
+//! when the batch is written.
+//!
+//! Resolution is intra-batch only. Puts made by separate writes, e.g. two
+//! [`crate::Db::put`] calls from different tasks, are separate batches with
+//! their own sequence numbers, and the one sequenced last wins as usual.
+//!
+//! ## Design note: no cross-writer resolution
+//!
+//! The writer task doesn't group concurrent writes into one commit: it takes
+//! the batches one at a time and gives each its own sequence number, so
+//! there's no commit holding puts from more than one writer to resolve
+//! across. Resolving against a value an earlier write already committed
+//! isn't done either. That value may have been read, or be durable, by the
+//! time the later put is written, and replacing it would rewrite history
+//! that readers of its sequence number saw. A resolver that has to converge
+//! across writers, like the `MaxValue` one below, needs the writes to go
+//! through one batch, or a merge operator
+//! ([`crate::DbBuilder::with_merge_operator`]) instead.
+
+/// Which of two values put to the same key a [`ConflictResolver`] keeps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictWinner {
+    /// The value put first.
+    Earlier,
+    /// The value put last.
+    Later,
+}
+
+/// Picks the value written for a key that a write batch puts more than once.
+/// Set with [`crate::DbBuilder::with_conflict_resolver`].
+///
+/// The resolver is consulted for the puts to a key with no other write to the
+/// key between them, in the order they were made: the value it keeps from the
+/// first two puts is compared with the third, and so on. The options of the
+/// kept put, such as its TTL, are written with its value. A delete or merge
+/// between two puts ends the run, and the last put wins as usual.
+///
+/// The batch is resolved when it's written, so a transaction that reads a key
+/// it put more than once sees the last value put.
+///
+/// ## Examples
+///
+/// ```
+/// use slatedb::{ConflictResolver, ConflictWinner, Db, Error, WriteBatch};
+/// use slatedb::object_store::{ObjectStore, memory::InMemory};
+/// use std::sync::Arc;
+///
+/// /// Keeps the larger value, so that concurrent puts converge.
+/// struct MaxValue;
+///
+/// impl ConflictResolver for MaxValue {
+///     fn resolve(&self, _key: &[u8], earlier: &[u8], later: &[u8]) -> ConflictWinner {
+///         if later >= earlier {
+///             ConflictWinner::Later
+///         } else {
+///             ConflictWinner::Earlier
+///         }
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+///     let db = Db::builder("test_db", object_store)
+///         .with_conflict_resolver(Arc::new(MaxValue))
+///         .build()
+///         .await?;
+///
+///     let mut batch = WriteBatch::new();
+///     batch.put(b"key", b"b");
+///     batch.put(b"key", b"a");
+///     db.write(batch).await?;
+///     assert_eq!(db.get(b"key").await?, Some("b".into()));
+///     Ok(())
+/// }
+/// ```
+pub trait ConflictResolver: Send + Sync {
+    /// Decides which of two values put to `key` is kept.
+    fn resolve(&self, key: &[u8], earlier: &[u8], later: &[u8]) -> ConflictWinner;
+}
@@ -22,8 +22,9 @@
 
 pub use crate::db_status::DbStatus;
 
-use crate::db_cache_manager::{self, CacheTarget};
-use std::ops::RangeBounds;
+use crate::db_cache_manager::{self, CacheTarget, BLOCK_CACHE_WARMUP_TASK_NAME};
+use std::collections::HashSet;
+use std::ops::{ControlFlow, RangeBounds};
 use std::sync::Arc;
 
 use bytes::Bytes;
@@ -32,46 +33,68 @@ use object_store::path::Path;
 use object_store::prefix::PrefixStore;
 use object_store::{parse_url_opts, ObjectStore};
 
-use crate::compactor::COMPACTOR_TASK_NAME;
+use crate::compactor::{CompactionReport, CompactorMessage, COMPACTOR_TASK_NAME};
 use crate::db_transaction::DbTransaction;
 use crate::dispatcher::MessageHandlerExecutor;
 use crate::garbage_collector::GC_TASK_NAME;
 use crate::transaction_manager::IsolationLevel;
 use crate::CloseReason;
-use log::{info, trace, warn};
+use log::{debug, info, trace, warn};
 use parking_lot::RwLock;
+use sha2::{Digest, Sha256};
 use std::time::Duration;
+use tokio::io::AsyncWrite;
 
 use crate::batch::WriteBatch;
 use crate::batch_write::{WriteBatchMessage, WRITE_BATCH_TASK_NAME};
 use crate::bytes_range::BytesRange;
 use crate::cached_object_store::CachedObjectStore;
+use crate::checkpoint::CheckpointReaderConfig;
 use crate::clock::MonotonicClock;
+use crate::column_family::{
+    column_family_key, column_family_prefix, column_family_range, ColumnFamilyIterator,
+};
 use crate::config::{
-    FlushOptions, FlushType, MergeOptions, PutOptions, ReadOptions, ScanOptions, Settings,
-    WriteOptions,
+    BlockCacheWarmupOptions, ExportSstOptions, FlushOptions, FlushType, MergeOptions, PutOptions,
+    ReadOptions, ScanOptions, Settings, WriteOptions,
 };
+use crate::conflict_resolver::ConflictResolver;
+use crate::db_diff::DbDiffIterator;
 use crate::db_iter::{DbIterator, DbRecencyIterator};
 use crate::db_snapshot::DbSnapshot;
 use crate::db_state::{DbState, SsTableId};
 use crate::db_stats::DbStats;
 use crate::error::SlateDBError;
+use crate::flush_policy::{DefaultFlushPolicy, FlushPolicy};
 use crate::iter::IterationOrder;
+use crate::key_encoding::{self, IntKey};
 use crate::manifest::store::FenceableManifest;
 use crate::manifest::{Manifest, VersionedManifest};
 use crate::memtable_flusher::{FlushResult, FlushTarget, MemtableFlusher};
-use crate::merge_operator::{instrument_merge_operator, MergeOperatorType};
-use crate::oracle::{DbOracle, Oracle};
+use crate::merge_operator::{
+    instrument_merge_operator, IntegerAddMergeOperator, MergeOperatorType,
+};
+use crate::merkle::{self, MerkleLeaves, MerkleNode};
+use crate::oracle::{DbOracle, MonotonicSequenceAllocator, Oracle, SequenceAllocator};
 use crate::paths::PathResolver;
 use crate::prefix_extractor::PrefixExtractor;
 use crate::rand::DbRand;
-use crate::reader::{Reader, ScanContext};
+use crate::rate_limiter::{RateLimitDecision, RateLimiter};
+use crate::read_cache::{PinnedLookup, ReadCache};
+use crate::read_repair::ReadRepairer;
+use crate::read_view::ReadView;
+use crate::reader::{DbStateReader, Reader, ScanContext};
+use crate::rewrite::{self, RewriteSummary};
+use crate::row_filter::RowFilter;
+use crate::shutdown::WriteGate;
 use crate::snapshot_manager::SnapshotManager;
+use crate::sst_export::{self, SstExportStats};
 use crate::sst_iter::SstIteratorOptions;
 use crate::tablestore::TableStore;
 use crate::transaction_manager::TransactionManager;
-use crate::types::KeyValue;
-use crate::utils::{format_bytes_si, SafeSender};
+use crate::types::{KeyValue, PutCondition, RowEntry};
+use crate::utils::{format_bytes_si, spawn_bg_task, SafeSender};
+use crate::virtual_source::{KeyValueIterator, SourcePriority};
 use crate::wal_buffer::{WalBufferManager, WAL_BUFFER_TASK_NAME};
 use crate::wal_replay::{WalReplayIterator, WalReplayOptions};
 use crate::{DbCacheManagerOps, DbMetadataOps, DbReadOps, DbWriteOps};
@@ -105,6 +128,19 @@ pub(crate) struct DbInner {
     pub(crate) system_clock: Arc<dyn SystemClock>,
     pub(crate) rand: Arc<DbRand>,
     pub(crate) oracle: Arc<DbOracle>,
+    /// Assigns the sequence numbers of write batches that don't supply one.
+    pub(crate) sequence_allocator: Arc<dyn SequenceAllocator>,
+    /// Whether writes may take sequence numbers at or below the last one, see
+    /// [`DbBuilder::with_sequence_import_mode`].
+    pub(crate) sequence_import_mode: bool,
+    /// Decides when to freeze the active memtable, see
+    /// [`DbBuilder::with_flush_policy`].
+    pub(crate) flush_policy: Arc<dyn FlushPolicy>,
+    /// Picks the value of a key put more than once in a write batch, see
+    /// [`DbBuilder::with_conflict_resolver`].
+    pub(crate) conflict_resolver: Option<Arc<dyn ConflictResolver>>,
+    /// Limits the writes that name a tenant, see [`DbBuilder::with_rate_limiter`].
+    pub(crate) rate_limiter: Option<Arc<dyn RateLimiter>>,
     pub(crate) flush_merge_operator: Option<MergeOperatorType>,
     pub(crate) reader: Reader,
     /// [`wal_buffer`] manages the in-memory WAL buffer, it manages the flushing
@@ -120,6 +156,12 @@ pub(crate) struct DbInner {
     /// L0 SSTs. When `None`, the database is the singleton `prefix=""`
     /// segment encoded in the manifest's top-level tree.
     pub(crate) segment_extractor: Option<Arc<dyn PrefixExtractor>>,
+    /// Recent point-read results, served to reads that accept some staleness
+    /// (see [`ReadOptions::max_cache_staleness`]).
+    pub(crate) read_cache: ReadCache,
+    /// Admits writes until [`Db::close`] starts, and lets it wait for the
+    /// writes admitted before.
+    pub(crate) write_gate: WriteGate,
 }
 
 impl DbInner {
@@ -152,7 +194,8 @@ impl DbInner {
         ));
 
         // state are mostly manifest, including IMM, L0, etc.
-        let db_state = DbState::new(manifest);
+        let db_state = DbState::new(manifest)
+            .with_memtable_type(settings.memtable_type, settings.memtable_filter_bits);
         let state = Arc::new(RwLock::new(db_state));
 
         let db_stats = DbStats::new(&recorder);
@@ -187,12 +230,19 @@ impl DbInner {
 
         let txn_manager = Arc::new(TransactionManager::new(oracle.clone(), rand.clone()));
         let snapshot_manager = Arc::new(SnapshotManager::new(oracle.clone(), rand.clone()));
+        let read_cache = ReadCache::new(system_clock.clone());
+        let flush_policy = Arc::new(DefaultFlushPolicy::new(&settings));
 
         let db_inner = Self {
             state,
             settings,
             memtable_flusher,
             oracle,
+            sequence_allocator: Arc::new(MonotonicSequenceAllocator),
+            sequence_import_mode: false,
+            flush_policy,
+            conflict_resolver: None,
+            rate_limiter: None,
             wal_enabled,
             table_store,
             wal_buffer,
@@ -209,10 +259,45 @@ impl DbInner {
             snapshot_manager,
             status_manager,
             segment_extractor,
+            read_cache,
+            write_gate: WriteGate::new(),
         };
         Ok(db_inner)
     }
 
+    /// Replaces the default [`MonotonicSequenceAllocator`] and sets whether the
+    /// database is in sequence import mode.
+    pub(crate) fn with_sequence_allocator(
+        mut self,
+        allocator: Arc<dyn SequenceAllocator>,
+        import_mode: bool,
+    ) -> Self {
+        self.sequence_allocator = allocator;
+        self.sequence_import_mode = import_mode;
+        self
+    }
+
+    /// Replaces the [`DefaultFlushPolicy`] if `flush_policy` is set.
+    pub(crate) fn with_flush_policy(mut self, flush_policy: Option<Arc<dyn FlushPolicy>>) -> Self {
+        if let Some(flush_policy) = flush_policy {
+            self.flush_policy = flush_policy;
+        }
+        self
+    }
+
+    pub(crate) fn with_conflict_resolver(
+        mut self,
+        conflict_resolver: Option<Arc<dyn ConflictResolver>>,
+    ) -> Self {
+        self.conflict_resolver = conflict_resolver;
+        self
+    }
+
+    pub(crate) fn with_rate_limiter(mut self, rate_limiter: Option<Arc<dyn RateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
     /// Get the value for a given key.
     pub(crate) async fn get_with_options<K: AsRef<[u8]>>(
         &self,
@@ -230,17 +315,206 @@ impl DbInner {
         key: K,
         options: &ReadOptions,
     ) -> Result<Option<KeyValue>, SlateDBError> {
+        self.check_closed()?;
+        let key = key.as_ref();
+        match self.read_cache.get_pinned(key, options) {
+            PinnedLookup::Hit(value) => return Ok(value),
+            PinnedLookup::Miss => {
+                let token = self.read_cache.begin_read(key);
+                let db_state = self.state.read().view();
+                let result = self
+                    .reader
+                    .get_key_value_with_options(key, options, &db_state, None, None)
+                    .await?;
+                self.read_cache.fill_pinned(token, result.clone());
+                return Ok(result);
+            }
+            PinnedLookup::NotPinned => {}
+        }
+        let Some(max_staleness) = options.max_cache_staleness else {
+            let db_state = self.state.read().view();
+            return self
+                .reader
+                .get_key_value_with_options(key, options, &db_state, None, None)
+                .await;
+        };
+        if let Some(cached) = self.read_cache.get(key, options, max_staleness) {
+            return Ok(cached);
+        }
+        let token = self.read_cache.begin_read(key);
+        let db_state = self.state.read().view();
+        let result = self
+            .reader
+            .get_key_value_with_options(key, options, &db_state, None, None)
+            .await?;
+        self.read_cache.insert(token, options, result.clone());
+        Ok(result)
+    }
+
+    pub(crate) async fn get_key_values_with_options(
+        &self,
+        keys: &[Bytes],
+        options: &ReadOptions,
+    ) -> Result<Vec<Option<KeyValue>>, SlateDBError> {
         self.check_closed()?;
         let db_state = self.state.read().view();
         self.reader
-            .get_key_value_with_options(key, options, &db_state, None, None)
+            .get_key_values_with_options(keys, options, &db_state)
+            .await
+    }
+
+    /// Pins `keys` and reads those that weren't already pinned into the
+    /// pinned tier of the read cache.
+    pub(crate) async fn pin_keys(&self, keys: &[Bytes]) -> Result<(), SlateDBError> {
+        self.check_closed()?;
+        self.read_cache.pin(keys);
+        let options = ReadOptions::default();
+        for key in keys {
+            self.get_key_value_with_options(key, &options).await?;
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn get_all_versions<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        seq_range: impl RangeBounds<u64>,
+    ) -> Result<Vec<RowEntry>, SlateDBError> {
+        self.check_closed()?;
+        let db_state = self.state.read().view();
+        self.reader
+            .get_all_versions(key.as_ref(), seq_range, &db_state)
             .await
     }
 
+    pub(crate) fn memtable_tombstones(
+        &self,
+        range: BytesRange,
+    ) -> Result<Vec<RowEntry>, SlateDBError> {
+        self.check_closed()?;
+        let db_state = self.state.read().view();
+        let tables = std::iter::once(Arc::clone(&db_state.memtable))
+            .chain(db_state.state.imm_memtable.iter().map(|imm| imm.table()));
+        let mut tombstones = Vec::new();
+        for table in tables {
+            table.visit_range(range.clone(), |entry| {
+                if entry.value.is_tombstone() {
+                    tombstones.push(entry.clone());
+                }
+                ControlFlow::Continue(())
+            });
+        }
+        tombstones.sort_by(|a, b| a.key.cmp(&b.key).then(b.seq.cmp(&a.seq)));
+        Ok(tombstones)
+    }
+
+    /// Returns the committed writes with a sequence number greater than
+    /// `seq`, in sequence number order. They're read from the memtables, which
+    /// hold every write made since the last one flushed to L0.
+    pub(crate) fn changes_since(&self, seq: u64) -> Result<Vec<RowEntry>, SlateDBError> {
+        self.check_closed()?;
+        let db_state = self.state.read().view();
+        // a flush to L0 drops the versions that no reader needs, so only the
+        // writes still in the memtables are retained
+        let oldest_retained = db_state.state.core().last_l0_seq;
+        if seq < oldest_retained {
+            return Err(SlateDBError::ChangesNotRetained {
+                since: seq,
+                oldest_retained,
+            });
+        }
+        let last_committed_seq = self.oracle.last_committed_seq();
+        let tables = std::iter::once(Arc::clone(&db_state.memtable))
+            .chain(db_state.state.imm_memtable.iter().map(|imm| imm.table()));
+        let mut changes = Vec::new();
+        for table in tables {
+            table.visit_range(.., |entry| {
+                if entry.seq > seq && entry.seq <= last_committed_seq {
+                    changes.push(entry.clone());
+                }
+                ControlFlow::Continue(())
+            });
+        }
+        changes.sort_by(|a, b| a.seq.cmp(&b.seq).then(a.key.cmp(&b.key)));
+        Ok(changes)
+    }
+
     pub(crate) async fn scan_with_options(
         &self,
         range: BytesRange,
         options: &ScanOptions,
+    ) -> Result<DbIterator, SlateDBError> {
+        self.check_closed()?;
+        let db_state = self.state.read().view();
+        let iter = self
+            .reader
+            .scan_with_options(
+                range,
+                options,
+                ScanContext {
+                    db_state: &db_state,
+                    write_batch_iter: None,
+                    max_seq: None,
+                    range_tracker: None,
+                    prefix: None,
+                    as_of_ts: None,
+                    include_expired: false,
+                },
+            )
+            .await?;
+        Ok(self.maybe_read_repair(iter, options, &db_state))
+    }
+
+    /// Adds read repair to `iter` if `options` asks for it. `db_state` must be
+    /// the state the scan read.
+    fn maybe_read_repair(
+        &self,
+        iter: DbIterator,
+        options: &ScanOptions,
+        db_state: &(dyn DbStateReader + Sync),
+    ) -> DbIterator {
+        if !options.read_repair {
+            return iter;
+        }
+        iter.with_read_repair(ReadRepairer::new(
+            self.write_notifier.clone(),
+            self.rand.clone(),
+            self.settings.read_repair_sample_rate,
+            db_state.core().last_l0_seq,
+        ))
+    }
+
+    pub(crate) async fn scan_as_of(
+        &self,
+        range: BytesRange,
+        as_of_ts: i64,
+        options: &ScanOptions,
+    ) -> Result<DbIterator, SlateDBError> {
+        self.check_closed()?;
+        let db_state = self.state.read().view();
+        self.reader
+            .scan_with_options(
+                range,
+                options,
+                ScanContext {
+                    db_state: &db_state,
+                    write_batch_iter: None,
+                    max_seq: None,
+                    range_tracker: None,
+                    prefix: None,
+                    as_of_ts: Some(as_of_ts),
+                    include_expired: false,
+                },
+            )
+            .await
+    }
+
+    /// Like [`Self::scan_with_options`], but returns the entries that have
+    /// expired as they're stored.
+    pub(crate) async fn scan_including_expired(
+        &self,
+        range: BytesRange,
+        options: &ScanOptions,
     ) -> Result<DbIterator, SlateDBError> {
         self.check_closed()?;
         let db_state = self.state.read().view();
@@ -254,6 +528,58 @@ impl DbInner {
                     max_seq: None,
                     range_tracker: None,
                     prefix: None,
+                    as_of_ts: None,
+                    include_expired: true,
+                },
+            )
+            .await
+    }
+
+    pub(crate) fn read_view(&self) -> Result<ReadView, SlateDBError> {
+        self.check_closed()?;
+        // Read the watermark before capturing the tables: every write at or
+        // below it is already in a memtable, so the captured tables hold it.
+        let seq = self.oracle.last_committed_seq();
+        Ok(ReadView::new(self.state.read().view(), seq))
+    }
+
+    pub(crate) async fn get_key_value_with_view<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        options: &ReadOptions,
+        view: Option<&ReadView>,
+    ) -> Result<Option<KeyValue>, SlateDBError> {
+        let Some(view) = view else {
+            return self.get_key_value_with_options(key, options).await;
+        };
+        self.check_closed()?;
+        self.reader
+            .get_key_value_with_options(key, options, &view.db_state, None, Some(view.seq))
+            .await
+    }
+
+    pub(crate) async fn scan_with_view(
+        &self,
+        range: BytesRange,
+        options: &ScanOptions,
+        view: Option<&ReadView>,
+    ) -> Result<DbIterator, SlateDBError> {
+        let Some(view) = view else {
+            return self.scan_with_options(range, options).await;
+        };
+        self.check_closed()?;
+        self.reader
+            .scan_with_options(
+                range,
+                options,
+                ScanContext {
+                    db_state: &view.db_state,
+                    write_batch_iter: None,
+                    max_seq: Some(view.seq),
+                    range_tracker: None,
+                    prefix: None,
+                    as_of_ts: None,
+                    include_expired: false,
                 },
             )
             .await
@@ -267,7 +593,8 @@ impl DbInner {
         self.check_closed()?;
         let range = BytesRange::from_prefix(prefix.as_ref());
         let db_state = self.state.read().view();
-        self.reader
+        let iter = self
+            .reader
             .scan_with_options(
                 range,
                 options,
@@ -277,9 +604,12 @@ impl DbInner {
                     max_seq: None,
                     range_tracker: None,
                     prefix: Some(prefix),
+                    as_of_ts: None,
+                    include_expired: false,
                 },
             )
-            .await
+            .await?;
+        Ok(self.maybe_read_repair(iter, options, &db_state))
     }
 
     pub(crate) async fn scan_prefix_by_recency_with_options(
@@ -340,12 +670,57 @@ impl DbInner {
         batch: WriteBatch,
         options: &WriteOptions,
     ) -> Result<WriteHandle, SlateDBError> {
+        match self
+            .submit_write(batch, options, Backpressure::Wait)
+            .await?
+        {
+            PutOutcome::Applied(write_handle) => Ok(write_handle),
+            PutOutcome::WouldBlock { .. } => {
+                unreachable!("writes that wait for backpressure are always applied")
+            }
+        }
+    }
+
+    /// Like [`Self::write_with_options`], but returns
+    /// [`PutOutcome::WouldBlock`] instead of waiting for backpressure.
+    pub(crate) async fn try_write_with_options(
+        &self,
+        batch: WriteBatch,
+        options: &WriteOptions,
+    ) -> Result<PutOutcome, SlateDBError> {
+        self.submit_write(batch, options, Backpressure::WouldBlock)
+            .await
+    }
+
+    /// Submits `batch` to the write loop, handling backpressure as
+    /// `backpressure` says.
+    async fn submit_write(
+        &self,
+        batch: WriteBatch,
+        options: &WriteOptions,
+        backpressure: Backpressure,
+    ) -> Result<PutOutcome, SlateDBError> {
+        self.check_closed()?;
+        if let Backpressure::WouldBlock = backpressure {
+            if let Some(outcome) = self.check_write_pressure()? {
+                return Ok(outcome);
+            }
+        }
         self.db_stats.write_batch_count.increment(1);
         self.db_stats.write_ops.increment(batch.ops.len() as u64);
-        self.check_closed()?;
         if batch.ops.is_empty() {
             return Err(SlateDBError::EmptyBatch);
         }
+        if let Some(tenant) = &options.tenant {
+            self.acquire_write_rate(tenant).await?;
+        }
+        if let Backpressure::Wait = backpressure {
+            self.check_immutable_memtable_cap()?;
+            self.maybe_apply_backpressure().await?;
+        }
+        // admitted only once the write is ready to be sent, so that a close
+        // doesn't wait for writes stalled by backpressure
+        let permit = self.write_gate.admit()?;
 
         let (tx, rx) = tokio::sync::oneshot::channel();
         let batch_msg = WriteBatchMessage {
@@ -353,45 +728,134 @@ impl DbInner {
             options: options.clone(),
             done: tx,
         };
-
-        self.maybe_apply_backpressure().await?;
         self.write_notifier.send(batch_msg)?;
 
         // TODO: this can be modified as awaiting the last_durable_seq watermark & fatal error.
 
         let (write_handle, mut durable_watcher) = rx.await??;
+        // the write is applied, so a closing db can go on to flush it
+        drop(permit);
 
         if options.await_durable {
-            durable_watcher.await_value().await?;
+            durable_watcher
+                .await_value_or(Err(SlateDBError::Closed))
+                .await?;
+        }
+
+        Ok(PutOutcome::Applied(write_handle))
+    }
+
+    /// Consults the [`RateLimiter`] about a write for `tenant`, and waits if it
+    /// holds the write back.
+    async fn acquire_write_rate(&self, tenant: &str) -> Result<(), SlateDBError> {
+        let Some(rate_limiter) = &self.rate_limiter else {
+            return Ok(());
+        };
+        match rate_limiter.acquire(tenant, self.system_clock.now()) {
+            RateLimitDecision::Admit => Ok(()),
+            RateLimitDecision::Wait(duration) => {
+                self.system_clock.sleep(duration).await;
+                Ok(())
+            }
+            RateLimitDecision::Reject => {
+                warn!("rejecting write, rate limit exceeded [tenant={}]", tenant);
+                Err(SlateDBError::RateLimited {
+                    tenant: tenant.to_string(),
+                })
+            }
+        }
+    }
+
+    /// Fails if the immutable memtable queue has reached
+    /// [`Settings::max_immutable_memtables`]. Unlike backpressure, this doesn't
+    /// wait for flushing to catch up.
+    fn check_immutable_memtable_cap(&self) -> Result<(), SlateDBError> {
+        let Some(max) = self.settings.max_immutable_memtables else {
+            return Ok(());
+        };
+        let count = self.state.read().state().imm_memtable.len();
+        if count >= max {
+            warn!(
+                "rejecting write, immutable memtable queue is full [count={}, max={}]",
+                count, max
+            );
+            return Err(SlateDBError::TooManyImmutableMemtables { count, max });
         }
+        Ok(())
+    }
+
+    /// Returns the estimated sizes of the unflushed WAL and of the immutable
+    /// memtables, which backpressure compares to
+    /// [`Settings::max_unflushed_bytes`].
+    fn unflushed_bytes(&self) -> Result<(usize, usize), SlateDBError> {
+        let wal_size_bytes = self.wal_buffer.estimated_bytes()?;
+        let imm_memtable_size_bytes = {
+            let guard = self.state.read();
+            // Exclude active memtable to avoid a write lock.
+            guard
+                .state()
+                .imm_memtable
+                .iter()
+                .map(|imm| {
+                    let metadata = imm.table().metadata();
+                    self.table_store.estimate_encoded_size_compacted(
+                        metadata.entry_num,
+                        metadata.entries_size_in_bytes,
+                    )
+                })
+                .sum::<usize>()
+        };
+        Ok((wal_size_bytes, imm_memtable_size_bytes))
+    }
 
-        Ok(write_handle)
+    /// Returns whether the key/value pair bytes of the active and immutable
+    /// memtables together exceed [`Settings::max_memtable_bytes`], along with
+    /// their sum.
+    fn exceeds_memtable_budget(&self) -> (bool, usize) {
+        let Some(max) = self.settings.max_memtable_bytes else {
+            return (false, 0);
+        };
+        let memtable_bytes = {
+            let guard = self.state.read();
+            let active_bytes = guard.memtable().metadata().entries_size_in_bytes;
+            let imm_bytes = guard
+                .state()
+                .imm_memtable
+                .iter()
+                .map(|imm| imm.table().metadata().entries_size_in_bytes)
+                .sum::<usize>();
+            active_bytes + imm_bytes
+        };
+        (memtable_bytes > max, memtable_bytes)
+    }
+
+    /// Returns [`PutOutcome::WouldBlock`] if a write would have to wait for
+    /// backpressure, or be rejected by
+    /// [`Settings::max_immutable_memtables`], rather than be applied now.
+    fn check_write_pressure(&self) -> Result<Option<PutOutcome>, SlateDBError> {
+        let queued_immutables = self.state.read().state().imm_memtable.len();
+        let (wal_size_bytes, imm_memtable_size_bytes) = self.unflushed_bytes()?;
+        let queued_bytes = wal_size_bytes + imm_memtable_size_bytes;
+        let queue_full = self
+            .settings
+            .max_immutable_memtables
+            .is_some_and(|max| queued_immutables >= max);
+        let (over_memtable_budget, _) = self.exceeds_memtable_budget();
+        if queue_full || over_memtable_budget || queued_bytes >= self.settings.max_unflushed_bytes {
+            self.db_stats.backpressure_count.increment(1);
+            return Ok(Some(PutOutcome::WouldBlock {
+                queued_immutables,
+                queued_bytes,
+            }));
+        }
+        Ok(None)
     }
 
     #[inline]
     pub(crate) async fn maybe_apply_backpressure(&self) -> Result<(), SlateDBError> {
         loop {
             self.check_closed()?;
-            let (wal_size_bytes, imm_memtable_size_bytes) = {
-                let wal_size_bytes = self.wal_buffer.estimated_bytes()?;
-                let imm_memtable_size_bytes = {
-                    let guard = self.state.read();
-                    // Exclude active memtable to avoid a write lock.
-                    guard
-                        .state()
-                        .imm_memtable
-                        .iter()
-                        .map(|imm| {
-                            let metadata = imm.table().metadata();
-                            self.table_store.estimate_encoded_size_compacted(
-                                metadata.entry_num,
-                                metadata.entries_size_in_bytes,
-                            )
-                        })
-                        .sum::<usize>()
-                };
-                (wal_size_bytes, imm_memtable_size_bytes)
-            };
+            let (wal_size_bytes, imm_memtable_size_bytes) = self.unflushed_bytes()?;
             let total_mem_size_bytes = wal_size_bytes + imm_memtable_size_bytes;
             self.db_stats
                 .total_mem_size_bytes
@@ -405,15 +869,30 @@ impl DbInner {
                 format_bytes_si(self.settings.max_unflushed_bytes as u64),
             );
 
-            if total_mem_size_bytes >= self.settings.max_unflushed_bytes {
+            let (over_memtable_budget, memtable_bytes) = self.exceeds_memtable_budget();
+            if total_mem_size_bytes >= self.settings.max_unflushed_bytes || over_memtable_budget {
                 self.db_stats.backpressure_count.increment(1);
-                warn!(
-                    "unflushed memtable size exceeds max_unflushed_bytes. applying backpressure. [total_mem_size_bytes={}, wal_size_bytes={}, imm_memtable_size_bytes={}, max_unflushed_bytes={}]",
-                    format_bytes_si(total_mem_size_bytes as u64),
-                    format_bytes_si(wal_size_bytes as u64),
-                    format_bytes_si(imm_memtable_size_bytes as u64),
-                    format_bytes_si(self.settings.max_unflushed_bytes as u64),
-                );
+                if over_memtable_budget {
+                    warn!(
+                        "memtable size exceeds max_memtable_bytes. applying backpressure. [memtable_bytes={}, max_memtable_bytes={}]",
+                        format_bytes_si(memtable_bytes as u64),
+                        format_bytes_si(self.settings.max_memtable_bytes.unwrap_or_default() as u64),
+                    );
+                    // The budget counts the active memtable, which only frees its
+                    // memory once it's frozen and flushed. Freeze it if there's no
+                    // immutable memtable whose flush would free memory instead.
+                    if self.state.read().state().imm_memtable.is_empty() {
+                        self.freeze_current_memtable()?;
+                    }
+                } else {
+                    warn!(
+                        "unflushed memtable size exceeds max_unflushed_bytes. applying backpressure. [total_mem_size_bytes={}, wal_size_bytes={}, imm_memtable_size_bytes={}, max_unflushed_bytes={}]",
+                        format_bytes_si(total_mem_size_bytes as u64),
+                        format_bytes_si(wal_size_bytes as u64),
+                        format_bytes_si(imm_memtable_size_bytes as u64),
+                        format_bytes_si(self.settings.max_unflushed_bytes as u64),
+                    );
+                }
 
                 let maybe_oldest_unflushed_memtable = {
                     let guard = self.state.read();
@@ -534,6 +1013,47 @@ impl DbInner {
         }
     }
 
+    /// Release memory held by the database. See [`Db::trim_memory`] for details.
+    pub(crate) async fn trim_memory(&self) -> Result<u64, SlateDBError> {
+        self.check_closed()?;
+        let memtable_bytes = {
+            let guard = self.state.read();
+            let imm_bytes = guard
+                .state()
+                .imm_memtable
+                .iter()
+                .map(|imm| imm.table().metadata().entries_size_in_bytes)
+                .sum::<usize>();
+            guard.memtable().metadata().entries_size_in_bytes + imm_bytes
+        };
+        if memtable_bytes > 0 {
+            self.flush(
+                FlushOptions {
+                    flush_type: FlushType::MemTable,
+                },
+                true,
+            )
+            .await?;
+        }
+
+        self.read_cache.clear();
+        let cache_bytes = match self.table_store.cache() {
+            Some(cache) => {
+                let before = cache.memory_usage();
+                cache.clear().await;
+                before.saturating_sub(cache.memory_usage())
+            }
+            None => 0,
+        };
+
+        debug!(
+            "trimmed memory [memtable_bytes={}, cache_bytes={}]",
+            format_bytes_si(memtable_bytes as u64),
+            format_bytes_si(cache_bytes),
+        );
+        Ok(memtable_bytes as u64 + cache_bytes)
+    }
+
     async fn replay_wal(&self) -> Result<(), SlateDBError> {
         let sst_iter_options = SstIteratorOptions {
             max_fetch_tasks: 1,
@@ -550,6 +1070,9 @@ impl DbInner {
             max_memtable_bytes: self.settings.l0_sst_size_bytes,
             sst_iter_options,
             min_seq: None,
+            memtable_type: self.settings.memtable_type,
+            memtable_filter_bits: self.settings.memtable_filter_bits,
+            policy: self.settings.wal_replay_policy,
         };
 
         let db_state = self.state.read().state().core().clone();
@@ -558,6 +1081,12 @@ impl DbInner {
                 .await?;
 
         while let Some(replayed_table) = replay_iter.next().await? {
+            for (wal_id, err) in &replayed_table.bad_wals {
+                warn!(
+                    "dropped unreadable WAL SST during replay [wal_id={}, policy={:?}, error={:?}]",
+                    wal_id, self.settings.wal_replay_policy, err
+                );
+            }
             // RFC-0024: re-extract each replayed entry's prefix to
             // populate the memtable's touched-segment set. Per the
             // validation model, durable WAL entries were validated when
@@ -617,6 +1146,53 @@ impl DbInner {
         .await
     }
 
+    /// Starts warming the block cache in the background if
+    /// [`Settings::block_cache_warmup`] is set. A failed warmup is logged and
+    /// doesn't affect the database.
+    pub(crate) fn spawn_block_cache_warmup(self: &Arc<Self>) {
+        let Some(options) = self.settings.block_cache_warmup.clone() else {
+            return;
+        };
+        if self.table_store.cache().is_none() {
+            warn!("block cache warmup configured on a Db without a block cache");
+            return;
+        }
+        let this = Arc::clone(self);
+        spawn_bg_task(
+            BLOCK_CACHE_WARMUP_TASK_NAME.to_string(),
+            &tokio::runtime::Handle::current(),
+            |_: &Result<(), SlateDBError>| {},
+            async move {
+                if let Err(e) = this.warm_block_cache(&options).await {
+                    warn!("failed to warm block cache [error={:?}]", e);
+                }
+                Ok(())
+            },
+        );
+    }
+
+    async fn warm_block_cache(
+        &self,
+        options: &BlockCacheWarmupOptions,
+    ) -> Result<(), crate::Error> {
+        let views = {
+            let state = self.state.read().state();
+            db_cache_manager::plan_block_cache_warmup(state.core(), options)
+        };
+        let targets = [
+            CacheTarget::Filters,
+            CacheTarget::Index,
+            CacheTarget::data::<&[u8], _>(..),
+        ];
+        for view in &views {
+            // stop as soon as the db is closed
+            self.check_closed()?;
+            db_cache_manager::warm_sst_view(&self.table_store, view, &targets).await?;
+        }
+        debug!("warmed block cache [ssts={}]", views.len());
+        Ok(())
+    }
+
     /// Returns the latest database status snapshot.
     pub(crate) fn status(&self) -> DbStatus {
         self.status_manager.status()
@@ -649,6 +1225,10 @@ impl DbInner {
 pub struct Db {
     pub(crate) inner: Arc<DbInner>,
     task_executor: Arc<MessageHandlerExecutor>,
+    /// Sends requests to the compactor run by this db, if any.
+    compactor_tx: Option<SafeSender<CompactorMessage>>,
+    /// Opens readers on, and releases, the db's checkpoints.
+    pub(crate) checkpoint_readers: Arc<CheckpointReaderConfig>,
 }
 
 impl Db {
@@ -748,7 +1328,10 @@ impl Db {
             None => true,
         };
 
-        // Mark the database as closed before flushing.
+        // Reject new writes, and let the writes admitted before finish being
+        // applied, so that the flush below makes them durable. Then mark the
+        // database as closed before flushing.
+        self.inner.write_gate.close().await;
         self.inner.status_manager.write_result(Ok(()));
 
         let result = if should_flush {
@@ -841,6 +1424,71 @@ impl Db {
         Ok(snapshot)
     }
 
+    /// Compare two snapshots of the database key by key.
+    ///
+    /// The returned iterator yields every key whose value differs between
+    /// `from` and `to`, in ascending key order, with the change that turns the
+    /// value in `from` into the value in `to`: [`crate::Change::Put`] for a key that
+    /// was added or modified, and [`crate::Change::Delete`] for a key that was
+    /// deleted. Keys whose value is the same in both snapshots, including keys
+    /// that were rewritten with an identical value, are skipped. Applying the
+    /// changes in order to a copy of `from` produces `to`, which makes this
+    /// suitable for incremental backups.
+    ///
+    /// Both snapshots are scanned once, side by side, so neither is
+    /// materialized in memory. The iterator holds on to both snapshots until
+    /// it is dropped. Use [`DbDiffIterator::into_stream`] to consume it as a
+    /// [`futures::Stream`].
+    ///
+    /// ## Arguments
+    /// - `from`: the older snapshot
+    /// - `to`: the newer snapshot
+    ///
+    /// ## Returns
+    /// - `DbDiffIterator`: an iterator over the keys that changed
+    ///
+    /// ## Errors
+    /// - `Error` with [`crate::ErrorKind::Invalid`]: if either snapshot was
+    ///   taken of a different database.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Change, Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key1", b"value1").await?;
+    ///     let from = db.snapshot().await?;
+    ///     db.put(b"key2", b"value2").await?;
+    ///     let to = db.snapshot().await?;
+    ///
+    ///     let mut diff = db.diff(from, to).await?;
+    ///     assert_eq!(
+    ///         diff.next().await?,
+    ///         Some(("key2".into(), Change::Put("value2".into())))
+    ///     );
+    ///     assert_eq!(diff.next().await?, None);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn diff(
+        &self,
+        from: Arc<DbSnapshot>,
+        to: Arc<DbSnapshot>,
+    ) -> Result<DbDiffIterator, crate::Error> {
+        if !from.is_snapshot_of(&self.inner) || !to.is_snapshot_of(&self.inner) {
+            return Err(SlateDBError::ForeignSnapshot.into());
+        }
+        let from_iter = from.scan::<&[u8], _>(..).await?;
+        let to_iter = to.scan::<&[u8], _>(..).await?;
+        Ok(DbDiffIterator::new(from_iter, to_iter, (from, to)))
+    }
+
     /// Get a value from the database with default read options.
     ///
     /// The `Bytes` object returned contains a slice of an entire
@@ -981,7 +1629,317 @@ impl Db {
         Ok(kv)
     }
 
-    /// Scan a range of keys using the default scan options.
+    /// Get the values of several keys with default read options.
+    ///
+    /// See [`Db::get_multi_with_options`] for the exact semantics.
+    ///
+    /// ## Arguments
+    /// - `keys`: the keys to look up
+    ///
+    /// ## Returns
+    /// - `Result<Vec<Option<Bytes>>, Error>`: the value of each key, in the order
+    ///   of `keys`, or `None` for keys that don't exist or are deleted/expired
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error reading from the database
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"a", b"1").await?;
+    ///     db.put(b"c", b"3").await?;
+    ///     let values = db.get_multi(&[b"c", b"b", b"a"]).await?;
+    ///     assert_eq!(values, vec![Some("3".into()), None, Some("1".into())]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_multi<K: AsRef<[u8]> + Send + Sync>(
+        &self,
+        keys: &[K],
+    ) -> Result<Vec<Option<Bytes>>, crate::Error> {
+        self.get_multi_with_options(keys, &ReadOptions::default())
+            .await
+    }
+
+    /// Get the values of several keys with custom read options.
+    ///
+    /// The keys are read together from one view of the database. The
+    /// memtables are searched first, then the SSTs from newest to oldest, and
+    /// a key is no longer searched for once its value is found. Each SST is
+    /// read once for all of the keys it may hold: the keys are checked against
+    /// its filters, and the blocks that may hold the remaining keys are
+    /// fetched together, so keys that fall in the same block share one fetch.
+    ///
+    /// Unlike [`Db::get`], the read cache is not consulted, so
+    /// [`ReadOptions::max_cache_staleness`] is ignored and keys pinned with
+    /// [`Db::pin_keys`] are read like any other key.
+    ///
+    /// ## Arguments
+    /// - `keys`: the keys to look up
+    /// - `options`: the read options to use
+    ///
+    /// ## Returns
+    /// - `Result<Vec<Option<Bytes>>, Error>`: the value of each key, in the order
+    ///   of `keys`, or `None` for keys that don't exist or are deleted/expired
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error reading from the database
+    pub async fn get_multi_with_options<K: AsRef<[u8]> + Send + Sync>(
+        &self,
+        keys: &[K],
+        options: &ReadOptions,
+    ) -> Result<Vec<Option<Bytes>>, crate::Error> {
+        let mut sorted = keys
+            .iter()
+            .map(|key| Bytes::copy_from_slice(key.as_ref()))
+            .collect::<Vec<_>>();
+        sorted.sort();
+        sorted.dedup();
+        let values = self
+            .inner
+            .get_key_values_with_options(&sorted, options)
+            .await?;
+        Ok(keys
+            .iter()
+            .map(|key| {
+                let i = sorted
+                    .binary_search_by(|sorted_key| sorted_key.as_ref().cmp(key.as_ref()))
+                    .expect("every key is in the sorted keys");
+                values[i].as_ref().map(|kv| kv.value.clone())
+            })
+            .collect())
+    }
+
+    /// Pin keys in memory, so that reads of them are always served from memory.
+    ///
+    /// Pinned keys are held in a tier of the read cache that is separate from
+    /// the short-lived cache used by [`ReadOptions::max_cache_staleness`] and
+    /// is never evicted. Every write to a pinned key made through this `Db`
+    /// updates its pinned entry in place, so reads always see the latest
+    /// committed value, and a delete makes the key read as not found. A merge
+    /// into a pinned key is the exception: the merged value is read again on
+    /// the next read of the key.
+    ///
+    /// Pinned entries are served to reads at
+    /// [`DurabilityLevel::Memory`](crate::config::DurabilityLevel::Memory) that
+    /// don't set [`ReadOptions::dirty`], which includes [`Db::get`]. Other
+    /// reads, and reads from snapshots, transactions and readers, are
+    /// unaffected. Pins are not persisted, and only last until the `Db` is
+    /// closed or the keys are released with [`Db::unpin_keys`]. Pinning a key
+    /// that is already pinned has no effect.
+    ///
+    /// ## Arguments
+    /// - `keys`: the keys to pin
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error reading the keys into memory
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"config", b"v1").await?;
+    ///     db.pin_keys(&[Bytes::from_static(b"config")]).await?;
+    ///     db.put(b"config", b"v2").await?;
+    ///     assert_eq!(db.get(b"config").await?, Some("v2".into()));
+    ///     db.unpin_keys(&[Bytes::from_static(b"config")]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn pin_keys(&self, keys: &[Bytes]) -> Result<(), crate::Error> {
+        self.inner.pin_keys(keys).await.map_err(Into::into)
+    }
+
+    /// Release keys pinned with [`Db::pin_keys`]. Keys that aren't pinned are
+    /// ignored.
+    ///
+    /// ## Arguments
+    /// - `keys`: the keys to release
+    pub fn unpin_keys(&self, keys: &[Bytes]) {
+        self.inner.read_cache.unpin(keys);
+    }
+
+    /// Get every stored version of a key whose sequence number is within a
+    /// range. This is meant for debugging a key's history, e.g. to find out
+    /// which write produced an unexpected value.
+    ///
+    /// Versions are read from the memtables and from SSTs, and are returned as
+    /// stored: tombstones and merge operands are included, expired values are
+    /// not filtered out, and merge operands are not applied. Older versions
+    /// that a memtable flush or compaction has already dropped cannot be
+    /// returned.
+    ///
+    /// ## Arguments
+    /// - `key`: the key whose versions to return
+    /// - `seq_range`: the range of sequence numbers to return versions for
+    ///
+    /// ## Returns
+    /// - `Ok(Vec<RowEntry>)`: the matching versions, ordered from the highest
+    ///   sequence number to the lowest. Empty if there are none.
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error reading from the database
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key", b"value1").await?;
+    ///     db.put(b"key", b"value2").await?;
+    ///     db.delete(b"key").await?;
+    ///
+    ///     let versions = db.get_all_versions(b"key", ..).await?;
+    ///     assert_eq!(versions.len(), 3);
+    ///     assert!(versions[0].value.is_tombstone());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_all_versions<K, R>(
+        &self,
+        key: K,
+        seq_range: R,
+    ) -> Result<Vec<RowEntry>, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        R: RangeBounds<u64> + Send,
+    {
+        self.inner
+            .get_all_versions(key, seq_range)
+            .await
+            .map_err(crate::Error::from)
+    }
+
+    /// Get the writes made since a sequence number, for keeping an external
+    /// index up to date. Every put, delete and merge with a sequence number
+    /// greater than `seq` is returned, in sequence number order, including
+    /// tombstones. The writes of a batch share a sequence number and are
+    /// returned in key order.
+    ///
+    /// To consume the changes incrementally, pass the sequence number of the
+    /// last change seen to the next call. Only committed writes are returned.
+    ///
+    /// Changes are retained until the memtable holding them is flushed to L0,
+    /// since flushing drops the versions of a key that no reader needs. If
+    /// `seq` is older than that, the changes since it can't be returned and
+    /// the index must be rebuilt from a full scan.
+    ///
+    /// The memtables hold their writes in key order, so the changes are
+    /// collected and sorted before they're returned. The result can be as
+    /// large as the unflushed writes, which
+    /// [`crate::config::Settings::max_unflushed_bytes`] bounds.
+    ///
+    /// ## Arguments
+    /// - `seq`: the sequence number of the last change already seen, or the
+    ///   sequence number the index was built at
+    ///
+    /// ## Returns
+    /// - `Ok(Vec<RowEntry>)`: the changes made since `seq`
+    ///
+    /// ## Errors
+    /// - `Error`: with [`crate::ErrorKind::Invalid`] if changes since `seq`
+    ///   are no longer retained, or an error if the database is closed
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     let indexed_at = db.current_sequence();
+    ///     db.put(b"key", b"value").await?;
+    ///     db.delete(b"key").await?;
+    ///
+    ///     let changes = db.changes_since(indexed_at).await?;
+    ///     assert_eq!(changes.len(), 2);
+    ///     assert!(changes[1].value.is_tombstone());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn changes_since(&self, seq: u64) -> Result<Vec<RowEntry>, crate::Error> {
+        Ok(self.inner.changes_since(seq)?)
+    }
+
+    /// Get the tombstones held in the memtables for a range of keys. This is
+    /// meant for auditing delete debt: tombstones take up space until a
+    /// compaction into the last sorted run drops them along with the values
+    /// they shadow.
+    ///
+    /// Both the mutable memtable and the immutable memtables waiting to be
+    /// flushed are read, and every version is returned, so a key deleted twice
+    /// appears twice. Tombstones that were already flushed to SSTs are not
+    /// returned.
+    ///
+    /// ## Arguments
+    /// - `range`: the range of keys to return tombstones for
+    ///
+    /// ## Returns
+    /// - `Ok(Vec<RowEntry>)`: the tombstones, ordered by key and then from the
+    ///   highest sequence number to the lowest.
+    ///
+    /// ## Errors
+    /// - `Error`: with kind [`crate::ErrorKind::Invalid`] if the range's start
+    ///   is after its end, or if the database is closed
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"a", b"value").await?;
+    ///     db.delete(b"b").await?;
+    ///
+    ///     let tombstones = db.memtable_tombstones::<&[u8], _>(..)?;
+    ///     assert_eq!(tombstones.len(), 1);
+    ///     assert_eq!(tombstones[0].key.as_ref(), b"b");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn memtable_tombstones<K, T>(&self, range: T) -> Result<Vec<RowEntry>, crate::Error>
+    where
+        K: AsRef<[u8]>,
+        T: RangeBounds<K>,
+    {
+        let range = BytesRange::try_from_scan_range(&range)?;
+        self.inner
+            .memtable_tombstones(range)
+            .map_err(crate::Error::from)
+    }
+
+    /// Scan a range of keys using the default scan options.
     ///
     /// returns a `DbIterator`
     ///
@@ -1062,254 +2020,276 @@ impl Db {
         K: AsRef<[u8]> + Send,
         T: RangeBounds<K> + Send,
     {
-        let start = range
-            .start_bound()
-            .map(|b| Bytes::copy_from_slice(b.as_ref()));
-        let end = range
-            .end_bound()
-            .map(|b| Bytes::copy_from_slice(b.as_ref()));
-        let range = (start, end);
+        let range = BytesRange::try_from_scan_range(&range)?;
         self.inner
-            .scan_with_options(BytesRange::from(range), options)
+            .scan_with_options(range, options)
             .await
             .map_err(Into::into)
     }
 
-    /// Scan all keys that share the provided prefix using the default scan options.
+    /// Scan a range of keys, returning only the rows that `filter` matches.
+    ///
+    /// The filter is evaluated inside the scan as each key is resolved, so
+    /// rejected rows are skipped without being returned, and without being
+    /// projected if a [`crate::Projector`] is added with
+    /// [`DbIterator::with_projector`]. See [`RowFilter`].
     ///
     /// ## Arguments
-    /// - `prefix`: the key prefix to scan
+    /// - `range`: the range of keys to scan
+    /// - `options`: the scan options to use
+    /// - `filter`: the filter that selects the rows to return
     ///
     /// ## Returns
-    /// - `Result<DbIterator, Error>`: An iterator with the results of the scan
+    /// - `Result<DbIterator, Error>`: an iterator over the matching rows
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error scanning the range of keys
     ///
     /// ## Examples
     ///
     /// ```
-    /// use slatedb::{Db, Error};
+    /// use slatedb::{Db, Error, RowEntry, RowFilter, ValueDeletable};
+    /// use slatedb::config::ScanOptions;
     /// use slatedb::object_store::{ObjectStore, memory::InMemory};
     /// use std::sync::Arc;
     ///
+    /// struct Active;
+    ///
+    /// impl RowFilter for Active {
+    ///     fn matches(&self, entry: &RowEntry) -> bool {
+    ///         matches!(&entry.value, ValueDeletable::Value(v) if v.as_ref() == b"active")
+    ///     }
+    /// }
+    ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Error> {
     ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
     ///     let db = Db::open("test_db", object_store).await?;
-    ///     db.put(b"ab", b"v0").await?;
-    ///     db.put(b"aba", b"v1").await?;
-    ///     db.put(b"b", b"v2").await?;
+    ///     db.put(b"user/1", b"active").await?;
+    ///     db.put(b"user/2", b"inactive").await?;
     ///
-    ///     let mut iter = db.scan_prefix(b"ab").await?;
-    ///     let kv = iter.next().await?.unwrap();
-    ///     assert_eq!(kv.key.as_ref(), b"ab");
-    ///     assert_eq!(kv.value.as_ref(), b"v0");
-    ///     let kv = iter.next().await?.unwrap();
-    ///     assert_eq!(kv.key.as_ref(), b"aba");
-    ///     assert_eq!(kv.value.as_ref(), b"v1");
+    ///     let mut iter = db
+    ///         .scan_with_row_filter("user/".."user0", &ScanOptions::default(), Arc::new(Active))
+    ///         .await?;
+    ///     assert_eq!(iter.next().await?.unwrap().key.as_ref(), b"user/1");
     ///     assert_eq!(None, iter.next().await?);
     ///     Ok(())
     /// }
     /// ```
-    pub async fn scan_prefix<P>(&self, prefix: P) -> Result<DbIterator, crate::Error>
+    pub async fn scan_with_row_filter<K, T>(
+        &self,
+        range: T,
+        options: &ScanOptions,
+        filter: Arc<dyn RowFilter>,
+    ) -> Result<DbIterator, crate::Error>
     where
-        P: AsRef<[u8]> + Send,
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
     {
-        self.scan_prefix_with_options(prefix, &ScanOptions::default())
-            .await
+        let iter = self.scan_with_options(range, options).await?;
+        Ok(iter.with_row_filter(filter))
     }
 
-    /// Scan all keys that share the provided prefix with custom options.
+    /// Scan a range of keys with a user-provided source of key-value pairs
+    /// merged in, as if its entries were stored in the database.
+    ///
+    /// The source is read lazily as the scan advances, and must return its
+    /// keys in the scan's order (see [`KeyValueIterator`]). When the source
+    /// and the database both hold a key, `priority` decides which one the scan
+    /// returns. With [`SourcePriority::Highest`] the source's value shadows
+    /// the database, including a deleted key. With [`SourcePriority::Lowest`]
+    /// the source only fills in keys the database has never written, or whose
+    /// deletion has been compacted away.
     ///
     /// ## Arguments
-    /// - `prefix`: the key prefix to scan
+    /// - `range`: the range of keys to scan
     /// - `options`: the scan options to use
+    /// - `source`: the source of key-value pairs to merge into the scan
+    /// - `priority`: whether the source's entries shadow the database's
     ///
     /// ## Returns
-    /// - `Result<DbIterator, Error>`: An iterator with the results of the scan
+    /// - `Result<DbIterator, Error>`: an iterator over the merged keys
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error scanning the range of keys. The
+    ///   iterator returns any error the source returns, and an invalid error if
+    ///   the source returns its keys out of order.
     ///
     /// ## Examples
     ///
     /// ```
-    /// use slatedb::{Db, Error};
+    /// use async_trait::async_trait;
+    /// use slatedb::{Db, Error, KeyValueIterator, SourcePriority};
+    /// use slatedb::bytes::Bytes;
     /// use slatedb::config::ScanOptions;
     /// use slatedb::object_store::{ObjectStore, memory::InMemory};
     /// use std::sync::Arc;
     ///
+    /// struct Defaults(std::vec::IntoIter<(Bytes, Bytes)>);
+    ///
+    /// #[async_trait]
+    /// impl KeyValueIterator for Defaults {
+    ///     async fn next(&mut self) -> Result<Option<(Bytes, Bytes)>, Error> {
+    ///         Ok(self.0.next())
+    ///     }
+    /// }
+    ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Error> {
     ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
     ///     let db = Db::open("test_db", object_store).await?;
-    ///     db.put(b"x1", b"v1").await?;
-    ///     db.put(b"x2", b"v2").await?;
-    ///     db.put(b"y", b"v3").await?;
-    ///
-    ///     let options = ScanOptions {
-    ///         cache_blocks: false,
-    ///         ..ScanOptions::default()
-    ///     };
-    ///     let mut iter = db.scan_prefix_with_options(b"x", &options).await?;
-    ///     let kv = iter.next().await?.unwrap();
-    ///     assert_eq!(kv.key.as_ref(), b"x1");
-    ///     assert_eq!(kv.value.as_ref(), b"v1");
-    ///     let kv = iter.next().await?.unwrap();
-    ///     assert_eq!(kv.key.as_ref(), b"x2");
-    ///     assert_eq!(kv.value.as_ref(), b"v2");
-    ///     assert_eq!(None, iter.next().await?);
+    ///     db.put(b"b", b"stored").await?;
+    ///
+    ///     let defaults = Defaults(
+    ///         vec![
+    ///             (Bytes::from("a"), Bytes::from("default")),
+    ///             (Bytes::from("b"), Bytes::from("default")),
+    ///         ]
+    ///         .into_iter(),
+    ///     );
+    ///     let mut iter = db
+    ///         .scan_with_source::<&[u8], _, _>(
+    ///             ..,
+    ///             &ScanOptions::default(),
+    ///             defaults,
+    ///             SourcePriority::Lowest,
+    ///         )
+    ///         .await?;
+    ///     assert_eq!(iter.next().await?.map(|kv| kv.value), Some("default".into()));
+    ///     assert_eq!(iter.next().await?.map(|kv| kv.value), Some("stored".into()));
+    ///     assert_eq!(iter.next().await?, None);
     ///     Ok(())
     /// }
     /// ```
-    pub async fn scan_prefix_with_options<P>(
+    pub async fn scan_with_source<K, T, S>(
         &self,
-        prefix: P,
+        range: T,
         options: &ScanOptions,
+        source: S,
+        priority: SourcePriority,
     ) -> Result<DbIterator, crate::Error>
     where
-        P: AsRef<[u8]> + Send,
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
+        S: KeyValueIterator + 'static,
     {
-        let prefix = Bytes::copy_from_slice(prefix.as_ref());
-        self.inner
-            .scan_prefix_with_options(prefix, options)
-            .await
-            .map_err(Into::into)
+        let range = BytesRange::try_from_scan_range(&range)?;
+        let iter = self.inner.scan_with_options(range, options).await?;
+        Ok(iter.with_virtual_source(Box::new(source), priority, options.order))
     }
 
-    /// Scan keys that share `prefix`, walking sources newest-first.
+    /// Scan the union of several ranges of keys, using the default scan
+    /// options.
     ///
-    /// **Warning:** this is a low-level, unopinionated iterator. It does
-    /// **no** merging, **no** deduping, and **no** interpretation of
-    /// entries across sources, unlike [`Self::scan_prefix`]. The API
-    /// makes no assumptions about what duplicates, tombstones, or merge
-    /// operands should mean; every such decision is left to the caller.
-    ///
-    /// Within each source, entries are emitted in the order requested by
-    /// `options.order`: ascending (the default) or descending. Across
-    /// sources, the walk is always newest-first, independent of
-    /// `options.order`. Each source restarts its own scan at its own
-    /// first matching key for that order, so the global emit sequence is
-    /// not a single sorted key stream and the within-source key order
-    /// resets at every source boundary. The same user key can appear
-    /// multiple times, both across sources (once per source that holds
-    /// it, newest source first) and within a single source (one entry
-    /// per stored sequence number, newest seq first within the key
-    /// group): nothing collapses versions. Tombstones and merge operands
-    /// are surfaced as raw [`crate::types::RowEntry`] values. The caller
-    /// is responsible for any dedup, delete handling, or merge
-    /// resolution. Callers that need a totally ordered, fully merged
-    /// view should use [`Self::scan_prefix`] instead; use this only when
-    /// you want freshest-first results with the option to early-stop and
-    /// are willing to interpret raw entries.
-    ///
-    /// Sources are walked in this order: active memtable, immutable
-    /// memtables, then within the single matching segment that segment's
-    /// L0 SSTs newest-first followed by its sorted runs newest-first. Each
-    /// source is fully drained before moving to the next. Sources are
-    /// lazily initialized: the filter check, index load, and first data
-    /// block fetch only happen when the recency walk reaches that source.
-    /// A prefix read whose data lives in the active memtable therefore
-    /// performs zero I/O. When the walk does have to descend to SST
-    /// sources, configuring prefix bloom filters lets the scan skip
-    /// non-matching SSTs without a data-block fetch, which keeps I/O
-    /// proportional to how recent the data is rather than to the size of
-    /// the LSM.
-    ///
-    /// **Multi-segment prefixes are rejected.** If the prefix overlaps
-    /// more than one segment, this returns an error with
-    /// [`crate::ErrorKind::Invalid`]. The recency guarantee is only
-    /// well-defined within a single segment: walking one segment's oldest
-    /// data before touching another segment's newest data would violate
-    /// freshest-first ordering. Callers that need cross-segment scans
-    /// should use [`Self::scan_prefix`] instead.
+    /// The ranges may be given in any order, and may overlap. Each key in
+    /// their union is returned once, in key order, from a single iterator.
     ///
     /// ## Arguments
-    /// - `prefix`: the key prefix to scan
+    /// - `ranges`: the ranges of keys to scan
     ///
     /// ## Returns
-    /// - `Result<RecencyIterator, Error>`: an iterator that yields raw
-    ///   `RowEntry` values newest-first. Use
-    ///   [`RecencyIterator::next_entry`] to pull the next entry, and
-    ///   inspect `entry.value` for the [`crate::types::ValueDeletable`]
-    ///   variant (`Value`, `Merge`, or `Tombstone`).
+    /// - `Result<DbIterator, Error>`: an iterator over the keys in the ranges
     ///
-    /// ## Examples
+    /// ## Errors
+    /// - `Error`: if any of the ranges is invalid, or if there was an error
+    ///   scanning them
     ///
-    /// Pull entries from the freshest source under the prefix and stop.
-    /// Because sources are walked newest-first and lazily initialized, an
-    /// early-return like this only touches the source that holds the
-    /// freshest data. Note that *within* a source the order is set by
-    /// `options.order` (ascending by default), so the first yielded entry
-    /// is the smallest key in the freshest source, not necessarily the
-    /// most recently written key. Across sources the same key may appear
-    /// more than once, so callers that want only the freshest value per
-    /// key should dedupe.
+    /// ## Examples
     ///
     /// ```
     /// use slatedb::{Db, Error};
     /// use slatedb::object_store::{ObjectStore, memory::InMemory};
-    /// use slatedb::ValueDeletable;
     /// use std::sync::Arc;
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Error> {
     ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
     ///     let db = Db::open("test_db", object_store).await?;
-    ///     db.put(b"user:42", b"alice").await?;
-    ///     db.put(b"user:42", b"alice2").await?;
-    ///     db.put(b"user:99", b"bob").await?;
-    ///
-    ///     let mut iter = db.scan_prefix_by_recency(b"user:").await?;
-    ///     let entry = iter.next_entry().await?.unwrap();
-    ///     // All three writes live in the active memtable (the freshest
-    ///     // source). With ascending within-source order, "user:42" comes
-    ///     // out first because it sorts before "user:99". Both writes to
-    ///     // "user:42" are stored as separate sequence-numbered entries;
-    ///     // within the "user:42" key group the newest seq is yielded
-    ///     // first, so the first entry carries the latest write
-    ///     // ("alice2"). A second pull would yield the older "user:42"
-    ///     // entry ("alice") before advancing to "user:99".
-    ///     assert_eq!(entry.key.as_ref(), b"user:42");
-    ///     match entry.value {
-    ///         ValueDeletable::Value(v) => assert_eq!(v.as_ref(), b"alice2"),
-    ///         _ => panic!("expected a regular value"),
+    ///     for key in ["a", "b", "c", "d"] {
+    ///         db.put(key, b"value").await?;
     ///     }
+    ///
+    ///     let mut iter = db.scan_ranges(vec!["c".."e", "a".."b"]).await?;
+    ///     assert_eq!(iter.next().await?.unwrap().key.as_ref(), b"a");
+    ///     assert_eq!(iter.next().await?.unwrap().key.as_ref(), b"c");
+    ///     assert_eq!(iter.next().await?.unwrap().key.as_ref(), b"d");
+    ///     assert_eq!(None, iter.next().await?);
     ///     Ok(())
     /// }
     /// ```
-    pub async fn scan_prefix_by_recency<P>(
-        &self,
-        prefix: P,
-    ) -> Result<DbRecencyIterator, crate::Error>
+    pub async fn scan_ranges<K, T>(&self, ranges: Vec<T>) -> Result<DbIterator, crate::Error>
     where
-        P: AsRef<[u8]> + Send,
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
     {
-        self.scan_prefix_by_recency_with_options(prefix, &ScanOptions::default())
+        self.scan_ranges_with_options(ranges, &ScanOptions::default())
             .await
     }
 
-    /// Recency-ordered prefix scan with custom options.
+    /// Scan the union of several ranges of keys with the provided options.
+    /// See [`Self::scan_ranges`].
     ///
-    /// Same contract as [`Self::scan_prefix_by_recency`] (raw entries
-    /// emitted newest-source-first; caller handles dedupe, tombstones, and
-    /// merge operands).
+    /// Overlapping and adjacent ranges are merged first. The scan reads
+    /// from the start of the first range to the end of the last, and in
+    /// ascending order seeks over the gaps between them.
     ///
     /// ## Arguments
-    /// - `prefix`: the key prefix to scan
+    /// - `ranges`: the ranges of keys to scan
     /// - `options`: the scan options to use
     ///
     /// ## Returns
-    /// - `Result<RecencyIterator, Error>`: an iterator that yields raw
-    ///   `RowEntry` values newest-first.
+    /// - `Result<DbIterator, Error>`: an iterator over the keys in the ranges
     ///
-    /// ## Examples
+    /// ## Errors
+    /// - `Error`: if any of the ranges is invalid, or if there was an error
+    ///   scanning them
+    pub async fn scan_ranges_with_options<K, T>(
+        &self,
+        ranges: Vec<T>,
+        options: &ScanOptions,
+    ) -> Result<DbIterator, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
+    {
+        let ranges = ranges
+            .iter()
+            .map(BytesRange::try_from_scan_range)
+            .collect::<Result<Vec<_>, _>>()?;
+        let ranges = BytesRange::coalesce(ranges);
+        let range = match (ranges.first(), ranges.last()) {
+            (Some(first), Some(last)) => {
+                BytesRange::new(first.start_bound().cloned(), last.end_bound().cloned())
+            }
+            _ => BytesRange::new_empty(),
+        };
+        let iter = self.inner.scan_with_options(range, options).await?;
+        Ok(iter.with_sub_ranges(ranges, options.order))
+    }
+
+    /// Scan the keys in a range of integers, using the default scan options.
     ///
-    /// Use `cache_blocks: false` to scan recent data without polluting
-    /// the block cache, and stop after pulling enough entries from the
-    /// freshest source. Combined with the recency walk's early-stop, this
-    /// is a cheap way to ask "is there a recent entry under this prefix?"
-    /// without warming the cache for cold blocks the answer doesn't depend
-    /// on.
+    /// The keys must have been written with [`IntKey::encode`], which
+    /// preserves the integers' order, so that e.g. `-3..2` returns the keys of
+    /// `-3`, `-2`, `-1`, `0` and `1` in that order. Decode the returned keys
+    /// with [`IntKey::decode`]. Any other keys that sort between the encoded
+    /// bounds are returned too.
+    ///
+    /// ## Arguments
+    /// - `range`: the range of integers to scan
+    ///
+    /// ## Returns
+    /// - `Result<DbIterator, Error>`: an iterator over the keys in the range
+    ///
+    /// ## Errors
+    /// - `Error` with [`crate::ErrorKind::Invalid`]: if the range starts after it ends
+    /// - `Error`: if there was an error scanning the range of keys
+    ///
+    /// ## Examples
     ///
     /// ```
     /// use slatedb::{Db, Error};
-    /// use slatedb::config::ScanOptions;
+    /// use slatedb::key_encoding::IntKey;
     /// use slatedb::object_store::{ObjectStore, memory::InMemory};
     /// use std::sync::Arc;
     ///
@@ -1317,48 +2297,56 @@ impl Db {
     /// async fn main() -> Result<(), Error> {
     ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
     ///     let db = Db::open("test_db", object_store).await?;
-    ///     db.put(b"event:001", b"a").await?;
-    ///     db.put(b"event:002", b"b").await?;
+    ///     for i in [-2i64, -1, 0, 1] {
+    ///         db.put(i.encode(), b"value").await?;
+    ///     }
     ///
-    ///     let options = ScanOptions {
-    ///         cache_blocks: false,
-    ///         ..ScanOptions::default()
-    ///     };
-    ///     let mut iter = db
-    ///         .scan_prefix_by_recency_with_options(b"event:", &options)
-    ///         .await?;
-    ///     let first = iter.next_entry().await?.unwrap();
-    ///     // Within-source order is ascending by default, so "event:001"
-    ///     // is yielded before "event:002" even though both live in the
-    ///     // same (freshest) source. Stop here: we only needed to see
-    ///     // that something fresh exists under the prefix.
-    ///     assert_eq!(first.key.as_ref(), b"event:001");
+    ///     let mut iter = db.scan_int_range(-1i64..=0).await?;
+    ///     assert_eq!(iter.next().await?.and_then(|kv| i64::decode(&kv.key)), Some(-1));
+    ///     assert_eq!(iter.next().await?.and_then(|kv| i64::decode(&kv.key)), Some(0));
+    ///     assert_eq!(iter.next().await?, None);
     ///     Ok(())
     /// }
     /// ```
-    pub async fn scan_prefix_by_recency_with_options<P>(
-        &self,
-        prefix: P,
-        options: &ScanOptions,
-    ) -> Result<DbRecencyIterator, crate::Error>
+    pub async fn scan_int_range<I, R>(&self, range: R) -> Result<DbIterator, crate::Error>
     where
-        P: AsRef<[u8]> + Send,
+        I: IntKey,
+        R: RangeBounds<I> + Send,
     {
-        let prefix = Bytes::copy_from_slice(prefix.as_ref());
+        let range = key_encoding::encode_range(range)?;
         self.inner
-            .scan_prefix_by_recency_with_options(prefix, options)
+            .scan_with_options(range, &ScanOptions::default())
             .await
             .map_err(Into::into)
     }
 
-    /// Write a value into the database with default `WriteOptions`.
+    /// Scan a range of keys as it stood at a point in time, using the default
+    /// scan options.
+    ///
+    /// Each key's value is the newest version whose `create_ts` is at or
+    /// before `ts`, and keys whose newest such version is a tombstone are
+    /// skipped. Timestamps come from the database's [`SystemClock`], in
+    /// milliseconds since the Unix epoch, and are recorded when a write is
+    /// applied. Versions that don't record a `create_ts` are treated as always
+    /// visible, as if they were written before any timestamp.
+    ///
+    /// Unlike a [`DbSnapshot`], which pins a sequence number, this doesn't keep
+    /// old versions around: flushes and compactions keep only the newest
+    /// version of a key unless an older one is still needed by a snapshot or
+    /// transaction. A
+    /// time-travel scan only sees versions that have been retained, so reads
+    /// further back than the oldest retained version return the newer value
+    /// or nothing at all. Expiry is still evaluated at the current time.
     ///
     /// ## Arguments
-    /// - `key`: the key to write
-    /// - `value`: the value to write
+    /// - `range`: the range of keys to scan
+    /// - `ts`: the point in time to read at, in milliseconds since the Unix epoch
+    ///
+    /// ## Returns
+    /// - `Result<DbIterator, Error>`: an iterator over the keys as of `ts`
     ///
     /// ## Errors
-    /// - `Error`: if there was an error writing the value.
+    /// - `Error`: if there was an error scanning the range of keys
     ///
     /// ## Examples
     ///
@@ -1371,35 +2359,41 @@ impl Db {
     /// async fn main() -> Result<(), Error> {
     ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
     ///     let db = Db::open("test_db", object_store).await?;
-    ///     let handle = db.put(b"key", b"value").await?;
+    ///     db.put(b"key", b"value").await?;
+    ///
+    ///     // nothing had been written yet at the start of the epoch
+    ///     let mut iter = db.scan_as_of::<&[u8], _>(.., 0).await?;
+    ///     assert_eq!(None, iter.next().await?);
     ///     Ok(())
     /// }
     /// ```
-    pub async fn put<K, V>(&self, key: K, value: V) -> Result<WriteHandle, crate::Error>
+    pub async fn scan_as_of<K, T>(&self, range: T, ts: i64) -> Result<DbIterator, crate::Error>
     where
-        K: AsRef<[u8]>,
-        V: AsRef<[u8]>,
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
     {
-        let mut batch = WriteBatch::new();
-        batch.put(key, value);
-        self.write(batch).await
+        let range = BytesRange::try_from_scan_range(&range)?;
+        self.inner
+            .scan_as_of(range, ts, &ScanOptions::default())
+            .await
+            .map_err(Into::into)
     }
 
-    /// Write a value into the database with custom `PutOptions` and `WriteOptions`.
+    /// Create a [`ReadView`] that pins the current memtables, manifest state
+    /// and committed sequence number, for use with
+    /// [`get_with_view`](Self::get_with_view) and
+    /// [`scan_with_view`](Self::scan_with_view).
     ///
-    /// ## Arguments
-    /// - `key`: the key to write
-    /// - `value`: the value to write
-    /// - `put_opts`: the put options to use
-    /// - `write_opts`: the write options to use
+    /// ## Returns
+    /// - `Ok(ReadView)`: a view of the database as of this call
     ///
     /// ## Errors
-    /// - `Error`: if there was an error writing the value.
+    /// - `Error`: if the database is closed
     ///
     /// ## Examples
     ///
     /// ```
-    /// use slatedb::{Db, config::{PutOptions, WriteOptions}, Error};
+    /// use slatedb::{Db, config::ReadOptions, Error};
     /// use slatedb::object_store::{ObjectStore, memory::InMemory};
     /// use std::sync::Arc;
     ///
@@ -1407,58 +2401,95 @@ impl Db {
     /// async fn main() -> Result<(), Error> {
     ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
     ///     let db = Db::open("test_db", object_store).await?;
-    ///     let handle = db.put_with_options(b"key", b"value", &PutOptions::default(), &WriteOptions::default()).await?;
+    ///     db.put(b"key", b"value1").await?;
+    ///     let view = db.read_view()?;
+    ///     db.put(b"key", b"value2").await?;
+    ///
+    ///     let options = ReadOptions::default();
+    ///     assert_eq!(db.get_with_view(b"key", &options, Some(&view)).await?, Some("value1".into()));
+    ///     assert_eq!(db.get_with_view(b"key", &options, None).await?, Some("value2".into()));
     ///     Ok(())
     /// }
     /// ```
-    pub async fn put_with_options<K, V>(
-        &self,
-        key: K,
-        value: V,
-        put_opts: &PutOptions,
-        write_opts: &WriteOptions,
-    ) -> Result<WriteHandle, crate::Error>
-    where
-        K: AsRef<[u8]>,
-        V: AsRef<[u8]>,
-    {
-        let mut batch = WriteBatch::new();
-        batch.put_with_options(key, value, put_opts);
-        self.write_with_options(batch, write_opts).await
+    pub fn read_view(&self) -> Result<ReadView, crate::Error> {
+        self.inner.read_view().map_err(Into::into)
     }
 
-    /// Write a value into the database using owned [`Bytes`], avoiding the
-    /// copies that [`Db::put`] performs via `Bytes::copy_from_slice`. Prefer
-    /// this form when the caller already holds the data as [`Bytes`] (e.g.
-    /// from a prior read, a zero-copy buffer pool, or a client that produces
-    /// [`Bytes`] directly).
-    pub async fn put_bytes(&self, key: Bytes, value: Bytes) -> Result<WriteHandle, crate::Error> {
-        self.put_bytes_with_options(key, value, &PutOptions::default(), &WriteOptions::default())
+    /// Get a value through a [`ReadView`], or from the latest state of the
+    /// database if no view is given.
+    ///
+    /// ## Arguments
+    /// - `key`: the key to get
+    /// - `options`: the read options to use. With a view, reads never see
+    ///   writes above [`ReadView::seq`], whatever the options allow.
+    /// - `view`: the view to read through, or `None` to read the latest state
+    ///   like [`get_with_options`](Self::get_with_options)
+    ///
+    /// ## Returns
+    /// - `Result<Option<Bytes>, Error>`:
+    ///   - `Some(Bytes)`: the value if it exists in the view
+    ///   - `None`: if the value does not exist in the view
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error getting the value
+    pub async fn get_with_view<K: AsRef<[u8]> + Send>(
+        &self,
+        key: K,
+        options: &ReadOptions,
+        view: Option<&ReadView>,
+    ) -> Result<Option<Bytes>, crate::Error> {
+        self.inner
+            .get_key_value_with_view(key, options, view)
             .await
+            .map(|kv| kv.map(|kv| kv.value))
+            .map_err(Into::into)
     }
 
-    /// Write a value into the database using owned [`Bytes`] with custom
-    /// `PutOptions` and `WriteOptions`. See [`Db::put_bytes`] for why this
-    /// form exists.
-    pub async fn put_bytes_with_options(
+    /// Scan a range of keys through a [`ReadView`], or over the latest state of
+    /// the database if no view is given.
+    ///
+    /// ## Arguments
+    /// - `range`: the range of keys to scan
+    /// - `options`: the scan options to use. With a view, the scan never sees
+    ///   writes above [`ReadView::seq`], whatever the options allow.
+    /// - `view`: the view to scan through, or `None` to scan the latest state
+    ///   like [`scan_with_options`](Self::scan_with_options)
+    ///
+    /// ## Returns
+    /// - `Result<DbIterator, Error>`: An iterator with the results of the scan
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error scanning the range of keys
+    pub async fn scan_with_view<K, T>(
         &self,
-        key: Bytes,
-        value: Bytes,
-        put_opts: &PutOptions,
-        write_opts: &WriteOptions,
-    ) -> Result<WriteHandle, crate::Error> {
-        let mut batch = WriteBatch::new();
-        batch.put_bytes_with_options(key, value, put_opts);
-        self.write_with_options(batch, write_opts).await
+        range: T,
+        options: &ScanOptions,
+        view: Option<&ReadView>,
+    ) -> Result<DbIterator, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
+    {
+        let range = BytesRange::try_from_scan_range(&range)?;
+        self.inner
+            .scan_with_view(range, options, view)
+            .await
+            .map_err(Into::into)
     }
 
-    /// Delete a key from the database with default `WriteOptions`.
+    /// Compute a SHA-256 digest over the live entries in a range using the
+    /// default scan options.
+    ///
+    /// See [`Db::range_digest_with_options`] for the byte layout that is hashed.
     ///
     /// ## Arguments
-    /// - `key`: the key to delete
+    /// - `range`: the range of keys to digest
+    ///
+    /// ## Returns
+    /// - `Result<[u8; 32], Error>`: the SHA-256 digest of the range
     ///
     /// ## Errors
-    /// - `Error`: if there was an error deleting the key.
+    /// - `Error`: if there was an error reading the range
     ///
     /// ## Examples
     ///
@@ -1469,31 +2500,119 @@ impl Db {
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Error> {
-    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let object_store = Arc::new(InMemory::new());
     ///     let db = Db::open("test_db", object_store).await?;
-    ///     let handle = db.delete(b"key").await?;
+    ///     db.put(b"a", b"a_value").await?;
+    ///     db.put(b"b", b"b_value").await?;
+    ///     let digest = db.range_digest::<&[u8], _>(..).await?;
+    ///     assert_eq!(digest, db.range_digest::<&[u8], _>(..).await?);
     ///     Ok(())
     /// }
     /// ```
-    pub async fn delete<K: AsRef<[u8]>>(&self, key: K) -> Result<WriteHandle, crate::Error> {
-        let mut batch = WriteBatch::new();
-        batch.delete(key.as_ref());
-        self.write(batch).await
+    pub async fn range_digest<K, T>(&self, range: T) -> Result<[u8; 32], crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
+    {
+        self.range_digest_with_options(range, &ScanOptions::default())
+            .await
     }
 
-    /// Delete a key from the database with custom `WriteOptions`.
+    /// Compute a SHA-256 digest over the live entries in a range.
+    ///
+    /// Entries are visited in ascending key order regardless of `options.order`.
+    /// Tombstones and entries that have expired
+    /// as of the start of the call are not included, whether or not compaction
+    /// has removed them yet. For each entry the following bytes are fed to the
+    /// hasher:
+    ///
+    /// ```text
+    /// | key_len (u32, big-endian) | key | value_len (u32, big-endian) | value |
+    /// ```
+    ///
+    /// Sequence numbers, timestamps and expiry times are not part of the digest,
+    /// so two databases (or the same database before and after a flush or
+    /// compaction) holding the same visible key/value pairs produce the same
+    /// digest.
     ///
     /// ## Arguments
-    /// - `key`: the key to delete
-    /// - `options`: the write options to use
+    /// - `range`: the range of keys to digest
+    /// - `options`: the scan options to use when reading the range
+    ///
+    /// ## Returns
+    /// - `Result<[u8; 32], Error>`: the SHA-256 digest of the range
     ///
     /// ## Errors
-    /// - `Error`: if there was an error deleting the key.
+    /// - `Error`: if there was an error reading the range
+    pub async fn range_digest_with_options<K, T>(
+        &self,
+        range: T,
+        options: &ScanOptions,
+    ) -> Result<[u8; 32], crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
+    {
+        let options = ScanOptions {
+            order: IterationOrder::Ascending,
+            ..options.clone()
+        };
+        let mut iter = self.scan_with_options(range, &options).await?;
+        let mut hasher = Sha256::new();
+        while let Some(kv) = iter.next().await? {
+            hasher.update((kv.key.len() as u32).to_be_bytes());
+            hasher.update(&kv.key);
+            hasher.update((kv.value.len() as u32).to_be_bytes());
+            hasher.update(&kv.value);
+        }
+        Ok(hasher.finalize().into())
+    }
+
+    /// Export a range of keys as a standalone SST file using the default
+    /// export options. See [`Db::export_sst_with_options`].
+    pub async fn export_sst<K, T, W>(
+        &self,
+        range: T,
+        writer: &mut W,
+    ) -> Result<SstExportStats, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        self.export_sst_with_options(range, writer, &ExportSstOptions::default())
+            .await
+    }
+
+    /// Export a range of keys as a standalone SST file.
+    ///
+    /// The newest version of each key in the range is written to `writer`,
+    /// in ascending key order regardless of `options.scan_options.order`. The
+    /// file has the same format as the SSTs SlateDB writes itself, encoded
+    /// with this database's compression codec and block transformer, so it
+    /// can only be read by an instance configured with the same ones. Entries
+    /// keep their sequence numbers, creation times and expiry times.
+    ///
+    /// Tombstones and entries that have expired as of the start of the call
+    /// are skipped unless `options` asks for them. The writer is flushed but
+    /// not shut down.
+    ///
+    /// ## Arguments
+    /// - `range`: the range of keys to export
+    /// - `writer`: where to write the SST file
+    /// - `options`: the options to use when exporting the range
+    ///
+    /// ## Returns
+    /// - `Result<SstExportStats, Error>`: the number of entries exported and
+    ///   the size of the file
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error reading the range or writing the file
     ///
     /// ## Examples
     ///
     /// ```
-    /// use slatedb::{Db, config::WriteOptions, Error};
+    /// use slatedb::{Db, Error};
     /// use slatedb::object_store::{ObjectStore, memory::InMemory};
     /// use std::sync::Arc;
     ///
@@ -1501,159 +2620,158 @@ impl Db {
     /// async fn main() -> Result<(), Error> {
     ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
     ///     let db = Db::open("test_db", object_store).await?;
-    ///     let handle = db.delete_with_options(b"key", &WriteOptions::default()).await?;
+    ///     db.put(b"key", b"value").await?;
+    ///     let mut file = Vec::new();
+    ///     let stats = db.export_sst(b"a".as_slice().., &mut file).await?;
+    ///     assert_eq!(stats.num_entries, 1);
+    ///     assert_eq!(stats.size, file.len() as u64);
     ///     Ok(())
     /// }
     /// ```
-    pub async fn delete_with_options<K: AsRef<[u8]>>(
+    pub async fn export_sst_with_options<K, T, W>(
         &self,
-        key: K,
-        options: &WriteOptions,
-    ) -> Result<WriteHandle, crate::Error> {
-        let mut batch = WriteBatch::new();
-        batch.delete(key);
-        self.write_with_options(batch, options).await
+        range: T,
+        writer: &mut W,
+        options: &ExportSstOptions,
+    ) -> Result<SstExportStats, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
+        W: AsyncWrite + Unpin + Send,
+    {
+        let range = BytesRange::try_from_scan_range(&range)?;
+        let scan_options = ScanOptions {
+            order: IterationOrder::Ascending,
+            ..options.scan_options.clone()
+        };
+        let mut iter = if options.include_expired {
+            self.inner
+                .scan_including_expired(range, &scan_options)
+                .await?
+        } else {
+            self.inner.scan_with_options(range, &scan_options).await?
+        };
+        let builder = self.inner.table_store.table_builder();
+        Ok(sst_export::write_sst(&mut iter, builder, writer, options).await?)
     }
 
-    /// Merge a value into the database with default `MergeOptions` and `WriteOptions`.
+    /// Returns the root of a merkle tree over the database's live data.
     ///
-    /// Merge operations allow applications to bypass the traditional read/modify/write cycle
-    /// by expressing partial updates using an associative operator. The merge operator must
-    /// be configured when opening the database.
+    /// Two databases holding the same live key/value pairs have the same root
+    /// hash, whatever their history or layout in memtables and SSTs. If the
+    /// roots of two replicas differ, [`Db::merkle_children`] descends the tree
+    /// to the leaves whose key ranges hold the differences, so that only those
+    /// ranges need to be compared or repaired.
     ///
-    /// ## Arguments
-    /// - `key`: the key to merge into
-    /// - `value`: the merge operand to apply
+    /// The tree partitions the key space by the leading bytes of each key.
+    /// Every internal node has 256 children, one for each value of the next
+    /// key byte, and the leaves are identified by the first two bytes of a
+    /// key, with shorter keys padded with zero bytes. A leaf's hash is the
+    /// [`Db::range_digest`] of its key range and an internal node's hash is the
+    /// SHA-256 digest of its children's hashes, in order. Keys that share
+    /// their first two bytes share a leaf, so a leaf's range can hold many
+    /// keys when keys have a common prefix.
+    ///
+    /// Each call reads the live data under the node, so hashes computed while
+    /// the database is being written may not be consistent with each other.
+    ///
+    /// ## Returns
+    /// - `Result<MerkleNode, Error>`: the root of the tree
     ///
     /// ## Errors
-    /// - `Error`: if there was an error merging the value, or if no merge operator is configured.
+    /// - `Error`: if there was an error reading the database
     ///
     /// ## Examples
     ///
     /// ```
-    /// use slatedb::{Db, Error, MergeOperator, MergeOperatorError};
+    /// use slatedb::{Db, Error};
     /// use slatedb::object_store::{ObjectStore, memory::InMemory};
     /// use std::sync::Arc;
-    /// use bytes::Bytes;
-    ///
-    /// struct StringConcatMergeOperator;
-    ///
-    /// impl MergeOperator for StringConcatMergeOperator {
-    ///     fn merge(&self, _key: &Bytes, existing_value: Option<Bytes>, value: Bytes) -> Result<Bytes, MergeOperatorError> {
-    ///         let mut result = existing_value.unwrap_or_default().as_ref().to_vec();
-    ///         result.extend_from_slice(&value);
-    ///         Ok(Bytes::from(result))
-    ///     }
-    /// }
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Error> {
     ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-    ///     let db = Db::builder("test_db", object_store)
-    ///         .with_merge_operator(Arc::new(StringConcatMergeOperator))
-    ///         .build()
-    ///         .await?;
-    ///     let handle = db.merge(b"key", b"value").await?;
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key", b"value").await?;
+    ///     let root = db.merkle_root().await?;
+    ///     assert_eq!(root.hash, db.merkle_root().await?.hash);
     ///     Ok(())
     /// }
     /// ```
-    pub async fn merge<K, V>(&self, key: K, value: V) -> Result<WriteHandle, crate::Error>
-    where
-        K: AsRef<[u8]>,
-        V: AsRef<[u8]>,
-    {
-        self.merge_with_options(
-            key,
-            value,
-            &MergeOptions::default(),
-            &WriteOptions::default(),
-        )
-        .await
+    pub async fn merkle_root(&self) -> Result<MerkleNode, crate::Error> {
+        let mut leaves = self.merkle_leaves(&[]).await?;
+        Ok(leaves.node(&[]))
     }
 
-    /// Merge a value into the database with custom `MergeOptions` and `WriteOptions`.
+    /// Returns the children of a node of the merkle tree returned by
+    /// [`Db::merkle_root`], in key order, or no nodes if the node is a leaf.
     ///
-    /// Merge operations allow applications to bypass the traditional read/modify/write cycle
-    /// by expressing partial updates using an associative operator. The merge operator must
-    /// be configured when opening the database.
+    /// Every internal node has 256 children, including those whose key ranges
+    /// are empty, so the children of the same node on two replicas can be
+    /// compared pairwise.
     ///
     /// ## Arguments
-    /// - `key`: the key to merge into
-    /// - `value`: the merge operand to apply
-    /// - `merge_opts`: the merge options to use
-    /// - `write_opts`: the write options to use
+    /// - `node`: the node to descend into
+    ///
+    /// ## Returns
+    /// - `Result<Vec<MerkleNode>, Error>`: the node's children
     ///
     /// ## Errors
-    /// - `Error`: if there was an error merging the value, or if no merge operator is configured.
+    /// - `Error`: if there was an error reading the database
     ///
     /// ## Examples
     ///
     /// ```
-    /// use slatedb::{Db, Error, MergeOperator, MergeOperatorError, config::{MergeOptions, WriteOptions}};
+    /// use slatedb::{Db, Error};
     /// use slatedb::object_store::{ObjectStore, memory::InMemory};
     /// use std::sync::Arc;
-    /// use bytes::Bytes;
-    ///
-    /// struct StringConcatMergeOperator;
-    ///
-    /// impl MergeOperator for StringConcatMergeOperator {
-    ///     fn merge(&self, _key: &Bytes, existing_value: Option<Bytes>, value: Bytes) -> Result<Bytes, MergeOperatorError> {
-    ///         let mut result = existing_value.unwrap_or_default().as_ref().to_vec();
-    ///         result.extend_from_slice(&value);
-    ///         Ok(Bytes::from(result))
-    ///     }
-    /// }
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Error> {
     ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-    ///     let db = Db::builder("test_db", object_store)
-    ///         .with_merge_operator(Arc::new(StringConcatMergeOperator))
-    ///         .build()
-    ///         .await?;
-    ///     let handle = db.merge_with_options(
-    ///         b"key",
-    ///         b"value",
-    ///         &MergeOptions::default(),
-    ///         &WriteOptions::default()
-    ///     ).await?;
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key", b"value").await?;
+    ///     let root = db.merkle_root().await?;
+    ///     let children = db.merkle_children(&root).await?;
+    ///     assert_eq!(children.len(), 256);
     ///     Ok(())
     /// }
     /// ```
-    pub async fn merge_with_options<K, V>(
+    pub async fn merkle_children(
         &self,
-        key: K,
-        value: V,
-        merge_opts: &MergeOptions,
-        write_opts: &WriteOptions,
-    ) -> Result<WriteHandle, crate::Error>
-    where
-        K: AsRef<[u8]>,
-        V: AsRef<[u8]>,
-    {
-        if self.inner.flush_merge_operator.is_none() {
-            return Err(SlateDBError::MergeOperatorMissing.into());
+        node: &MerkleNode,
+    ) -> Result<Vec<MerkleNode>, crate::Error> {
+        if node.is_leaf() {
+            return Ok(Vec::new());
         }
-
-        let mut batch = WriteBatch::new();
-        batch.merge_with_options(key, value, merge_opts);
-        self.write_with_options(batch, write_opts).await
+        let mut leaves = self.merkle_leaves(&node.prefix).await?;
+        Ok(leaves.children(&node.prefix))
+    }
+
+    /// Reads the leaf hashes of the merkle tree node with the given prefix.
+    async fn merkle_leaves(&self, prefix: &[u8]) -> Result<MerkleLeaves, crate::Error> {
+        let mut iter = self
+            .scan_with_options(merkle::key_range(prefix), &ScanOptions::default())
+            .await?;
+        let mut leaves = MerkleLeaves::new();
+        while let Some(kv) = iter.next().await? {
+            leaves.update(&kv);
+        }
+        Ok(leaves)
     }
 
-    /// Write a batch of put/delete operations atomically to the database. Batch writes
-    /// block other gets and writes until the batch is written to the WAL (or memtable if
-    /// WAL is disabled).
+    /// Scan all keys that share the provided prefix using the default scan options.
     ///
     /// ## Arguments
-    /// - `batch`: the batch of put/delete operations to write
+    /// - `prefix`: the key prefix to scan
     ///
-    /// ## Errors
-    /// - `Error`: if there was an error writing the batch.
+    /// ## Returns
+    /// - `Result<DbIterator, Error>`: An iterator with the results of the scan
     ///
     /// ## Examples
     ///
     /// ```
-    /// use slatedb::{WriteBatch, Db, Error};
+    /// use slatedb::{Db, Error};
     /// use slatedb::object_store::{ObjectStore, memory::InMemory};
     /// use std::sync::Arc;
     ///
@@ -1661,36 +2779,43 @@ impl Db {
     /// async fn main() -> Result<(), Error> {
     ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
     ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"ab", b"v0").await?;
+    ///     db.put(b"aba", b"v1").await?;
+    ///     db.put(b"b", b"v2").await?;
     ///
-    ///     let mut batch = WriteBatch::new();
-    ///     batch.put(b"key1", b"value1");
-    ///     batch.put(b"key2", b"value2");
-    ///     batch.delete(b"key1");
-    ///     let handle = db.write(batch).await?;
-    ///
+    ///     let mut iter = db.scan_prefix(b"ab").await?;
+    ///     let kv = iter.next().await?.unwrap();
+    ///     assert_eq!(kv.key.as_ref(), b"ab");
+    ///     assert_eq!(kv.value.as_ref(), b"v0");
+    ///     let kv = iter.next().await?.unwrap();
+    ///     assert_eq!(kv.key.as_ref(), b"aba");
+    ///     assert_eq!(kv.value.as_ref(), b"v1");
+    ///     assert_eq!(None, iter.next().await?);
     ///     Ok(())
     /// }
     /// ```
-    pub async fn write(&self, batch: WriteBatch) -> Result<WriteHandle, crate::Error> {
-        self.write_with_options(batch, &WriteOptions::default())
+    pub async fn scan_prefix<P>(&self, prefix: P) -> Result<DbIterator, crate::Error>
+    where
+        P: AsRef<[u8]> + Send,
+    {
+        self.scan_prefix_with_options(prefix, &ScanOptions::default())
             .await
     }
 
-    /// Write a batch of put/delete operations atomically to the database. Batch writes
-    /// block other gets and writes until the batch is written to the WAL (or memtable if
-    /// WAL is disabled).
+    /// Scan all keys that share the provided prefix with custom options.
     ///
     /// ## Arguments
-    /// - `batch`: the batch of put/delete operations to write
-    /// - `options`: the write options to use
+    /// - `prefix`: the key prefix to scan
+    /// - `options`: the scan options to use
     ///
-    /// ## Errors
-    /// - `Error`: if there was an error writing the batch.
+    /// ## Returns
+    /// - `Result<DbIterator, Error>`: An iterator with the results of the scan
     ///
     /// ## Examples
     ///
     /// ```
-    /// use slatedb::{WriteBatch, Db, config::WriteOptions, Error};
+    /// use slatedb::{Db, Error};
+    /// use slatedb::config::ScanOptions;
     /// use slatedb::object_store::{ObjectStore, memory::InMemory};
     /// use std::sync::Arc;
     ///
@@ -1698,87 +2823,178 @@ impl Db {
     /// async fn main() -> Result<(), Error> {
     ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
     ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"x1", b"v1").await?;
+    ///     db.put(b"x2", b"v2").await?;
+    ///     db.put(b"y", b"v3").await?;
     ///
-    ///     let mut batch = WriteBatch::new();
-    ///     batch.put(b"key1", b"value1");
-    ///     batch.put(b"key2", b"value2");
-    ///     batch.delete(b"key1");
-    ///     let handle = db.write_with_options(batch, &WriteOptions::default()).await?;
-    ///
+    ///     let options = ScanOptions {
+    ///         cache_blocks: false,
+    ///         ..ScanOptions::default()
+    ///     };
+    ///     let mut iter = db.scan_prefix_with_options(b"x", &options).await?;
+    ///     let kv = iter.next().await?.unwrap();
+    ///     assert_eq!(kv.key.as_ref(), b"x1");
+    ///     assert_eq!(kv.value.as_ref(), b"v1");
+    ///     let kv = iter.next().await?.unwrap();
+    ///     assert_eq!(kv.key.as_ref(), b"x2");
+    ///     assert_eq!(kv.value.as_ref(), b"v2");
+    ///     assert_eq!(None, iter.next().await?);
     ///     Ok(())
     /// }
     /// ```
-    pub async fn write_with_options(
+    pub async fn scan_prefix_with_options<P>(
         &self,
-        batch: WriteBatch,
-        options: &WriteOptions,
-    ) -> Result<WriteHandle, crate::Error> {
+        prefix: P,
+        options: &ScanOptions,
+    ) -> Result<DbIterator, crate::Error>
+    where
+        P: AsRef<[u8]> + Send,
+    {
+        let prefix = Bytes::copy_from_slice(prefix.as_ref());
         self.inner
-            .write_with_options(batch, options)
+            .scan_prefix_with_options(prefix, options)
             .await
             .map_err(Into::into)
     }
 
-    /// Flush in-memory writes to disk. This function blocks until the in-memory
-    /// data has been durably written to object storage.
+    /// Scan keys that share `prefix`, walking sources newest-first.
     ///
-    /// If WAL is enabled, this method is equivalent to:
-    /// `flush_with_options(FlushOptions { flush_type: FlushType::Wal })`
+    /// **Warning:** this is a low-level, unopinionated iterator. It does
+    /// **no** merging, **no** deduping, and **no** interpretation of
+    /// entries across sources, unlike [`Self::scan_prefix`]. The API
+    /// makes no assumptions about what duplicates, tombstones, or merge
+    /// operands should mean; every such decision is left to the caller.
     ///
-    /// If WAL is disabled, this method is equivalent to:
-    /// `flush_with_options(FlushOptions { flush_type: FlushType::Memtable })`.
+    /// Within each source, entries are emitted in the order requested by
+    /// `options.order`: ascending (the default) or descending. Across
+    /// sources, the walk is always newest-first, independent of
+    /// `options.order`. Each source restarts its own scan at its own
+    /// first matching key for that order, so the global emit sequence is
+    /// not a single sorted key stream and the within-source key order
+    /// resets at every source boundary. The same user key can appear
+    /// multiple times, both across sources (once per source that holds
+    /// it, newest source first) and within a single source (one entry
+    /// per stored sequence number, newest seq first within the key
+    /// group): nothing collapses versions. Tombstones and merge operands
+    /// are surfaced as raw [`crate::types::RowEntry`] values. The caller
+    /// is responsible for any dedup, delete handling, or merge
+    /// resolution. Callers that need a totally ordered, fully merged
+    /// view should use [`Self::scan_prefix`] instead; use this only when
+    /// you want freshest-first results with the option to early-stop and
+    /// are willing to interpret raw entries.
     ///
-    /// ## Errors
-    /// - `Error`: if there was an error flushing the database
+    /// Sources are walked in this order: active memtable, immutable
+    /// memtables, then within the single matching segment that segment's
+    /// L0 SSTs newest-first followed by its sorted runs newest-first. Each
+    /// source is fully drained before moving to the next. Sources are
+    /// lazily initialized: the filter check, index load, and first data
+    /// block fetch only happen when the recency walk reaches that source.
+    /// A prefix read whose data lives in the active memtable therefore
+    /// performs zero I/O. When the walk does have to descend to SST
+    /// sources, configuring prefix bloom filters lets the scan skip
+    /// non-matching SSTs without a data-block fetch, which keeps I/O
+    /// proportional to how recent the data is rather than to the size of
+    /// the LSM.
+    ///
+    /// **Multi-segment prefixes are rejected.** If the prefix overlaps
+    /// more than one segment, this returns an error with
+    /// [`crate::ErrorKind::Invalid`]. The recency guarantee is only
+    /// well-defined within a single segment: walking one segment's oldest
+    /// data before touching another segment's newest data would violate
+    /// freshest-first ordering. Callers that need cross-segment scans
+    /// should use [`Self::scan_prefix`] instead.
+    ///
+    /// ## Arguments
+    /// - `prefix`: the key prefix to scan
+    ///
+    /// ## Returns
+    /// - `Result<RecencyIterator, Error>`: an iterator that yields raw
+    ///   `RowEntry` values newest-first. Use
+    ///   [`RecencyIterator::next_entry`] to pull the next entry, and
+    ///   inspect `entry.value` for the [`crate::types::ValueDeletable`]
+    ///   variant (`Value`, `Merge`, or `Tombstone`).
     ///
     /// ## Examples
     ///
+    /// Pull entries from the freshest source under the prefix and stop.
+    /// Because sources are walked newest-first and lazily initialized, an
+    /// early-return like this only touches the source that holds the
+    /// freshest data. Note that *within* a source the order is set by
+    /// `options.order` (ascending by default), so the first yielded entry
+    /// is the smallest key in the freshest source, not necessarily the
+    /// most recently written key. Across sources the same key may appear
+    /// more than once, so callers that want only the freshest value per
+    /// key should dedupe.
+    ///
     /// ```
     /// use slatedb::{Db, Error};
     /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use slatedb::ValueDeletable;
     /// use std::sync::Arc;
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Error> {
     ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
     ///     let db = Db::open("test_db", object_store).await?;
-    ///     db.flush().await?;
+    ///     db.put(b"user:42", b"alice").await?;
+    ///     db.put(b"user:42", b"alice2").await?;
+    ///     db.put(b"user:99", b"bob").await?;
+    ///
+    ///     let mut iter = db.scan_prefix_by_recency(b"user:").await?;
+    ///     let entry = iter.next_entry().await?.unwrap();
+    ///     // All three writes live in the active memtable (the freshest
+    ///     // source). With ascending within-source order, "user:42" comes
+    ///     // out first because it sorts before "user:99". Both writes to
+    ///     // "user:42" are stored as separate sequence-numbered entries;
+    ///     // within the "user:42" key group the newest seq is yielded
+    ///     // first, so the first entry carries the latest write
+    ///     // ("alice2"). A second pull would yield the older "user:42"
+    ///     // entry ("alice") before advancing to "user:99".
+    ///     assert_eq!(entry.key.as_ref(), b"user:42");
+    ///     match entry.value {
+    ///         ValueDeletable::Value(v) => assert_eq!(v.as_ref(), b"alice2"),
+    ///         _ => panic!("expected a regular value"),
+    ///     }
     ///     Ok(())
     /// }
     /// ```
-    pub async fn flush(&self) -> Result<(), crate::Error> {
-        let flush_type = if self.inner.wal_enabled {
-            FlushType::Wal
-        } else {
-            FlushType::MemTable
-        };
-        self.inner
-            .flush(FlushOptions { flush_type }, true)
+    pub async fn scan_prefix_by_recency<P>(
+        &self,
+        prefix: P,
+    ) -> Result<DbRecencyIterator, crate::Error>
+    where
+        P: AsRef<[u8]> + Send,
+    {
+        self.scan_prefix_by_recency_with_options(prefix, &ScanOptions::default())
             .await
-            .map_err(Into::into)
     }
 
-    /// Flush in-memory writes to disk with custom options.
-    ///
-    /// An error will be returned if `options.flush_type` is `FlushType::Wal` and the WAL
-    /// is disabled.
+    /// Recency-ordered prefix scan with custom options.
     ///
-    /// `FlushType::Memtable` is allowed even if WAL is enabled.
+    /// Same contract as [`Self::scan_prefix_by_recency`] (raw entries
+    /// emitted newest-source-first; caller handles dedupe, tombstones, and
+    /// merge operands).
     ///
     /// ## Arguments
-    /// - `options`: the flush options
+    /// - `prefix`: the key prefix to scan
+    /// - `options`: the scan options to use
     ///
     /// ## Returns
-    /// - `Result<(), crate::Error>`: the result of the flush operation.
-    ///
-    /// ## Errors
-    /// - `Error`: if there was an error flushing the database
+    /// - `Result<RecencyIterator, Error>`: an iterator that yields raw
+    ///   `RowEntry` values newest-first.
     ///
     /// ## Examples
     ///
+    /// Use `cache_blocks: false` to scan recent data without polluting
+    /// the block cache, and stop after pulling enough entries from the
+    /// freshest source. Combined with the recency walk's early-stop, this
+    /// is a cheap way to ask "is there a recent entry under this prefix?"
+    /// without warming the cache for cold blocks the answer doesn't depend
+    /// on.
+    ///
     /// ```
     /// use slatedb::{Db, Error};
-    /// use slatedb::config::{FlushOptions, FlushType};
+    /// use slatedb::config::ScanOptions;
     /// use slatedb::object_store::{ObjectStore, memory::InMemory};
     /// use std::sync::Arc;
     ///
@@ -1786,2673 +3002,6411 @@ impl Db {
     /// async fn main() -> Result<(), Error> {
     ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
     ///     let db = Db::open("test_db", object_store).await?;
-    ///     db.flush_with_options(FlushOptions {
-    ///         flush_type: FlushType::Wal,
-    ///     })
-    ///     .await?;
+    ///     db.put(b"event:001", b"a").await?;
+    ///     db.put(b"event:002", b"b").await?;
+    ///
+    ///     let options = ScanOptions {
+    ///         cache_blocks: false,
+    ///         ..ScanOptions::default()
+    ///     };
+    ///     let mut iter = db
+    ///         .scan_prefix_by_recency_with_options(b"event:", &options)
+    ///         .await?;
+    ///     let first = iter.next_entry().await?.unwrap();
+    ///     // Within-source order is ascending by default, so "event:001"
+    ///     // is yielded before "event:002" even though both live in the
+    ///     // same (freshest) source. Stop here: we only needed to see
+    ///     // that something fresh exists under the prefix.
+    ///     assert_eq!(first.key.as_ref(), b"event:001");
     ///     Ok(())
     /// }
     /// ```
-    pub async fn flush_with_options(&self, options: FlushOptions) -> Result<(), crate::Error> {
-        self.inner.flush(options, true).await.map_err(Into::into)
-    }
-
-    /// Refresh the manifest immediately and wait for it to complete.
-    ///
-    /// The database normally refreshes its manifest on a background timer
-    /// controlled by [`Settings::manifest_poll_interval`]. This method
-    /// bypasses that timer, triggering an immediate refresh and waiting
-    /// for it to finish.
-    ///
-    /// Use this when you know the manifest has changed externally and
-    /// want to ensure the database has observed the update before
-    /// proceeding — for example, after a compaction completes and you
-    /// need to confirm that a compaction filter has been applied.
-    ///
-    /// ## Errors
-    /// - Returns [`Error`] if the database is closed before the refresh
-    ///   completes.
-    pub async fn refresh_manifest(&self) -> Result<(), crate::Error> {
-        self.inner.check_closed()?;
+    pub async fn scan_prefix_by_recency_with_options<P>(
+        &self,
+        prefix: P,
+        options: &ScanOptions,
+    ) -> Result<DbRecencyIterator, crate::Error>
+    where
+        P: AsRef<[u8]> + Send,
+    {
+        let prefix = Bytes::copy_from_slice(prefix.as_ref());
         self.inner
-            .memtable_flusher
-            .refresh_manifest()
+            .scan_prefix_by_recency_with_options(prefix, options)
             .await
             .map_err(Into::into)
     }
 
-    /// Begin a new transaction with the specified isolation level.
+    /// Write a value into the database with default `WriteOptions`.
     ///
     /// ## Arguments
-    /// - `isolation_level`: the isolation level for the transaction
+    /// - `key`: the key to write
+    /// - `value`: the value to write
     ///
-    /// ## Returns
-    /// - `Result<DbTransaction, crate::Error>`: the transaction handle
+    /// ## Errors
+    /// - `Error`: if there was an error writing the value.
     ///
     /// ## Examples
     ///
-    /// ```rust
-    /// use slatedb::{Db, IsolationLevel};
-    /// use slatedb::object_store::memory::InMemory;
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
     /// use std::sync::Arc;
     ///
     /// #[tokio::main]
-    /// async fn main() -> Result<(), slatedb::Error> {
-    ///     let object_store = Arc::new(InMemory::new());
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
     ///     let db = Db::open("test_db", object_store).await?;
-    ///     let txn = db.begin(IsolationLevel::SerializableSnapshot).await?;
+    ///     let handle = db.put(b"key", b"value").await?;
     ///     Ok(())
     /// }
     /// ```
-    pub async fn begin(
-        &self,
-        isolation_level: IsolationLevel,
-    ) -> Result<DbTransaction, crate::Error> {
-        self.inner.check_closed()?;
-        let txn = DbTransaction::new(
-            self.inner.clone(),
-            self.inner.txn_manager.clone(),
-            isolation_level,
-        );
-        Ok(txn)
+    pub async fn put<K, V>(&self, key: K, value: V) -> Result<WriteHandle, crate::Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let mut batch = WriteBatch::new();
+        batch.put(key, value);
+        self.write(batch).await
     }
 
-    /// Resolve an object store from a URL.
+    /// Write a value into the database, unless the write would have to wait for
+    /// backpressure.
+    ///
+    /// Where [`put`](Self::put) stalls until flushing catches up with the
+    /// writes, this returns [`PutOutcome::WouldBlock`] without writing
+    /// anything, so that a latency-sensitive caller can shed load instead. A
+    /// write would block if the unflushed WAL and immutable memtables have
+    /// reached [`Settings::max_unflushed_bytes`], if the memtables have reached
+    /// [`Settings::max_memtable_bytes`], or if the immutable memtable queue has
+    /// reached [`Settings::max_immutable_memtables`].
+    ///
+    /// The write is not awaited to be durable, as that would wait for the WAL
+    /// to be flushed to object storage. It is readable by the time this
+    /// returns, and is made durable by the next WAL flush; call
+    /// [`flush`](Self::flush) to wait for it.
     ///
     /// ## Arguments
-    /// - `url`: the URL to resolve, for example `s3://my-bucket/my-prefix`.
+    /// - `key`: the key to write
+    /// - `value`: the value to write
     ///
     /// ## Returns
-    /// - `Result<Arc<dyn ObjectStore>, crate::Error>`: the resolved object store
-    pub fn resolve_object_store(url: &str) -> Result<Arc<dyn ObjectStore>, crate::Error> {
-        let url = url
-            .try_into()
-            .map_err(|e| SlateDBError::InvalidObjectStoreURL(url.to_string(), e))?;
-        // Lowercase env keys because parse_url_opts only recognizes lower case option keys.
-        let env_vars = std::env::vars().map(|(key, value)| (key.to_ascii_lowercase(), value));
-        let (object_store, path) = parse_url_opts(&url, env_vars).map_err(SlateDBError::from)?;
-        let object_store: Arc<dyn ObjectStore> = if path.as_ref().is_empty() {
-            Arc::from(object_store)
-        } else {
-            let object_store = Arc::from(object_store);
-            Arc::new(PrefixStore::new(object_store, path))
-        };
-        Ok(object_store)
-    }
-}
-
-#[async_trait::async_trait]
-impl DbReadOps for Db {
-    async fn get_with_options<K: AsRef<[u8]> + Send>(
-        &self,
-        key: K,
-        options: &ReadOptions,
-    ) -> Result<Option<Bytes>, crate::Error> {
-        Db::get_with_options(self, key, options).await
-    }
-
-    async fn get_key_value_with_options<K: AsRef<[u8]> + Send>(
-        &self,
-        key: K,
-        options: &ReadOptions,
-    ) -> Result<Option<KeyValue>, crate::Error> {
-        Db::get_key_value_with_options(self, key, options).await
-    }
-
-    async fn scan_with_options<K, T>(
-        &self,
-        range: T,
-        options: &ScanOptions,
-    ) -> Result<DbIterator, crate::Error>
+    /// - `Ok(PutOutcome::Applied)`: the value was written
+    /// - `Ok(PutOutcome::WouldBlock)`: the value was not written, along with
+    ///   the state of the queue of unflushed writes
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error writing the value.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error, PutOutcome};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     match db.try_put(b"key", b"value").await? {
+    ///         PutOutcome::Applied(handle) => println!("written at {}", handle.seqnum()),
+    ///         PutOutcome::WouldBlock { queued_bytes, .. } => {
+    ///             println!("dropped write, {queued_bytes} bytes are waiting to flush")
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn try_put<K, V>(&self, key: K, value: V) -> Result<PutOutcome, crate::Error>
     where
-        K: AsRef<[u8]> + Send,
-        T: RangeBounds<K> + Send,
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
     {
-        Db::scan_with_options(self, range, options).await
+        let mut batch = WriteBatch::new();
+        batch.put(key, value);
+        let write_opts = WriteOptions {
+            await_durable: false,
+            ..WriteOptions::default()
+        };
+        self.inner
+            .try_write_with_options(batch, &write_opts)
+            .await
+            .map_err(Into::into)
     }
 
-    async fn scan_prefix_with_options<P>(
+    /// Write a value into the database with custom `PutOptions` and `WriteOptions`.
+    ///
+    /// ## Arguments
+    /// - `key`: the key to write
+    /// - `value`: the value to write
+    /// - `put_opts`: the put options to use
+    /// - `write_opts`: the write options to use
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error writing the value.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, config::{PutOptions, WriteOptions}, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     let handle = db.put_with_options(b"key", b"value", &PutOptions::default(), &WriteOptions::default()).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn put_with_options<K, V>(
         &self,
-        prefix: P,
-        options: &ScanOptions,
-    ) -> Result<DbIterator, crate::Error>
+        key: K,
+        value: V,
+        put_opts: &PutOptions,
+        write_opts: &WriteOptions,
+    ) -> Result<WriteHandle, crate::Error>
     where
-        P: AsRef<[u8]> + Send,
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
     {
-        Db::scan_prefix_with_options(self, prefix, options).await
+        let mut batch = WriteBatch::new();
+        batch.put_with_options(key, value, put_opts);
+        self.write_with_options(batch, write_opts).await
     }
-}
 
-impl DbMetadataOps for Db {
-    fn manifest(&self) -> VersionedManifest {
-        self.inner.manifest()
+    /// Write a value into the database using owned [`Bytes`], avoiding the
+    /// copies that [`Db::put`] performs via `Bytes::copy_from_slice`. Prefer
+    /// this form when the caller already holds the data as [`Bytes`] (e.g.
+    /// from a prior read, a zero-copy buffer pool, or a client that produces
+    /// [`Bytes`] directly).
+    pub async fn put_bytes(&self, key: Bytes, value: Bytes) -> Result<WriteHandle, crate::Error> {
+        self.put_bytes_with_options(key, value, &PutOptions::default(), &WriteOptions::default())
+            .await
     }
 
-    fn subscribe(&self) -> tokio::sync::watch::Receiver<DbStatus> {
-        self.inner.status_manager.subscribe()
+    /// Write a value into the database using owned [`Bytes`] with custom
+    /// `PutOptions` and `WriteOptions`. See [`Db::put_bytes`] for why this
+    /// form exists.
+    pub async fn put_bytes_with_options(
+        &self,
+        key: Bytes,
+        value: Bytes,
+        put_opts: &PutOptions,
+        write_opts: &WriteOptions,
+    ) -> Result<WriteHandle, crate::Error> {
+        let mut batch = WriteBatch::new();
+        batch.put_bytes_with_options(key, value, put_opts);
+        self.write_with_options(batch, write_opts).await
     }
 
-    fn status(&self) -> DbStatus {
-        self.inner.status()
+    /// Delete a key from the database with default `WriteOptions`.
+    ///
+    /// ## Arguments
+    /// - `key`: the key to delete
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error deleting the key.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     let handle = db.delete(b"key").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn delete<K: AsRef<[u8]>>(&self, key: K) -> Result<WriteHandle, crate::Error> {
+        let mut batch = WriteBatch::new();
+        batch.delete(key.as_ref());
+        self.write(batch).await
     }
-}
-
-#[async_trait::async_trait]
-impl DbWriteOps for Db {
-    type Transaction = DbTransaction;
 
-    async fn put_with_options<K, V>(
+    /// Delete a key from the database with custom `WriteOptions`.
+    ///
+    /// ## Arguments
+    /// - `key`: the key to delete
+    /// - `options`: the write options to use
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error deleting the key.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, config::WriteOptions, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     let handle = db.delete_with_options(b"key", &WriteOptions::default()).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn delete_with_options<K: AsRef<[u8]>>(
         &self,
         key: K,
-        value: V,
-        put_opts: &PutOptions,
-        write_opts: &WriteOptions,
-    ) -> Result<WriteHandle, crate::Error>
-    where
-        K: AsRef<[u8]> + Send,
-        V: AsRef<[u8]> + Send,
-    {
-        Db::put_with_options(self, key, value, put_opts, write_opts).await
+        options: &WriteOptions,
+    ) -> Result<WriteHandle, crate::Error> {
+        let mut batch = WriteBatch::new();
+        batch.delete(key);
+        self.write_with_options(batch, options).await
     }
 
-    async fn delete_with_options<K: AsRef<[u8]> + Send>(
+    /// Delete a key from the database, retaining its tombstone for at least
+    /// `retention`.
+    ///
+    /// A plain delete's tombstone may be dropped by the first compaction into
+    /// the last sorted run. This one is stamped with an expiry of the write
+    /// time plus `retention`, and compaction only drops it once that time has
+    /// passed, so the delete stays visible for the whole period to anything
+    /// that reads the database's SSTs, such as readers on older checkpoints or
+    /// replicas. Reads treat the key as deleted whether or not the tombstone
+    /// has expired. See [`WriteBatch::delete_with_retention`].
+    ///
+    /// ## Arguments
+    /// - `key`: the key to delete
+    /// - `retention`: the minimum time to keep the tombstone
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error deleting the key.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key", b"value").await?;
+    ///     db.delete_with_retention(b"key", Duration::from_secs(30 * 24 * 3600))
+    ///         .await?;
+    ///     assert_eq!(db.get(b"key").await?, None);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn delete_with_retention<K: AsRef<[u8]>>(
         &self,
         key: K,
-        options: &WriteOptions,
+        retention: Duration,
     ) -> Result<WriteHandle, crate::Error> {
-        Db::delete_with_options(self, key, options).await
+        let mut batch = WriteBatch::new();
+        batch.delete_with_retention(key, retention);
+        self.write(batch).await
     }
 
-    async fn merge_with_options<K, V>(
+    /// Rewrite the values of every live key in a range, e.g. to migrate them
+    /// to a new schema.
+    ///
+    /// The range is scanned in key order and `f` is called with each key and
+    /// its value. If it returns a new value, the key is updated to it, keeping
+    /// the expiry time of the old value. If it returns `None`, the key is
+    /// deleted. Returning the same value leaves the key untouched.
+    ///
+    /// The range is streamed in chunks of a few hundred keys, each read and
+    /// written back by its own [`IsolationLevel::Snapshot`] transaction, so
+    /// the rewrite never holds the whole range in memory. Each chunk is
+    /// applied atomically and durably, but the rewrite as a whole is not: a
+    /// failed rewrite leaves the chunks committed before the failure in place,
+    /// and readers can observe a partially rewritten range.
+    ///
+    /// Concurrent writes are preserved. If another writer changes a key while
+    /// its chunk is being rewritten, the chunk's commit fails with a conflict
+    /// and the chunk is read and rewritten again, so `f` is applied to the
+    /// concurrently written value rather than overwriting it. `f` can
+    /// therefore be called more than once for the same key, and should not
+    /// have side effects. Keys written after their chunk has been committed
+    /// are not rewritten.
+    ///
+    /// ## Arguments
+    /// - `range`: the range of keys to rewrite
+    /// - `f`: returns the new value of a key given the key and its current
+    ///   value, or `None` to delete the key
+    ///
+    /// ## Returns
+    /// - `Result<RewriteSummary, Error>`: the number of keys that were
+    ///   rewritten and deleted
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error reading or writing the range
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"user:1", b"v1:alice").await?;
+    ///     db.put(b"user:2", b"obsolete").await?;
+    ///     let summary = db
+    ///         .rewrite_range(b"user:".as_slice()..b"user;".as_slice(), |_, value| {
+    ///             let name = value.strip_prefix(b"v1:")?;
+    ///             Some(Bytes::from([b"v2:".as_slice(), name].concat()))
+    ///         })
+    ///         .await?;
+    ///     assert_eq!((summary.rewritten, summary.deleted), (1, 1));
+    ///     assert_eq!(db.get(b"user:1").await?, Some("v2:alice".into()));
+    ///     assert_eq!(db.get(b"user:2").await?, None);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn rewrite_range<K, T, F>(
         &self,
-        key: K,
-        value: V,
-        merge_opts: &MergeOptions,
-        write_opts: &WriteOptions,
-    ) -> Result<WriteHandle, crate::Error>
+        range: T,
+        f: F,
+    ) -> Result<RewriteSummary, crate::Error>
     where
         K: AsRef<[u8]> + Send,
-        V: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
+        F: Fn(&[u8], &[u8]) -> Option<Bytes>,
     {
-        Db::merge_with_options(self, key, value, merge_opts, write_opts).await
+        let start = range
+            .start_bound()
+            .map(|b| Bytes::copy_from_slice(b.as_ref()));
+        let end = range
+            .end_bound()
+            .map(|b| Bytes::copy_from_slice(b.as_ref()));
+        rewrite::rewrite_range(self, (start, end), f).await
     }
 
-    async fn write_with_options(
-        &self,
-        batch: WriteBatch,
-        options: &WriteOptions,
-    ) -> Result<WriteHandle, crate::Error> {
-        Db::write_with_options(self, batch, options).await
+    /// Put several key-value pairs into the database with default `WriteOptions`.
+    ///
+    /// This is a shorthand for building a [`WriteBatch`] of puts and passing it
+    /// to [`Db::write`]. The entries are applied atomically: they are written
+    /// to the same WAL SST under a single sequence number, so either all of
+    /// them are applied or, if the write fails, none are. Durability is awaited
+    /// once, for the whole batch, rather than per entry. If the same key
+    /// appears more than once, the last value wins, unless a
+    /// [`ConflictResolver`] is set with
+    /// [`DbBuilder::with_conflict_resolver`], in which case it picks the value.
+    ///
+    /// ## Arguments
+    /// - `entries`: the key-value pairs to put
+    ///
+    /// ## Returns
+    /// - `WriteHandle`: the handle of the write, whose sequence number is shared
+    ///   by every entry
+    ///
+    /// ## Errors
+    /// - `Error` with [`crate::ErrorKind::Invalid`]: if `entries` is empty
+    /// - `Error`: if there was an error writing the entries. No entry is applied.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put_all([(b"key1", b"value1"), (b"key2", b"value2")]).await?;
+    ///     assert_eq!(db.get(b"key2").await?, Some("value2".into()));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn put_all<K, V, I>(&self, entries: I) -> Result<WriteHandle, crate::Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut batch = WriteBatch::new();
+        for (key, value) in entries {
+            batch.put(key, value);
+        }
+        self.write(batch).await
     }
 
-    async fn flush(&self) -> Result<(), crate::Error> {
-        Db::flush(self).await
+    /// Delete several keys from the database with default `WriteOptions`.
+    ///
+    /// Like [`Db::put_all`], the deletes are written as a single
+    /// [`WriteBatch`], so they are applied atomically and durability is
+    /// awaited once for the whole batch.
+    ///
+    /// ## Arguments
+    /// - `keys`: the keys to delete
+    ///
+    /// ## Returns
+    /// - `WriteHandle`: the handle of the write, whose sequence number is shared
+    ///   by every delete
+    ///
+    /// ## Errors
+    /// - `Error` with [`crate::ErrorKind::Invalid`]: if `keys` is empty
+    /// - `Error`: if there was an error writing the deletes. No key is deleted.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put_all([(b"key1", b"value1"), (b"key2", b"value2")]).await?;
+    ///     db.delete_all([b"key1", b"key2"]).await?;
+    ///     assert_eq!(db.get(b"key1").await?, None);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn delete_all<K, I>(&self, keys: I) -> Result<WriteHandle, crate::Error>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        let mut batch = WriteBatch::new();
+        for key in keys {
+            batch.delete(key);
+        }
+        self.write(batch).await
     }
 
-    async fn flush_with_options(&self, options: FlushOptions) -> Result<(), crate::Error> {
-        Db::flush_with_options(self, options).await
+    /// Get a value from a column family with default read options.
+    ///
+    /// Column families are independent key spaces within the database. See
+    /// [`crate::ColumnFamilyExtractor`] for how they are stored.
+    ///
+    /// ## Arguments
+    /// - `cf`: the name of the column family, between 1 and 255 bytes long
+    /// - `key`: the key to get
+    ///
+    /// ## Returns
+    /// - `Result<Option<Bytes>, Error>`:
+    ///     - `Some(Bytes)`: the value if the key exists in the column family
+    ///     - `None`: if the key does not exist in the column family
+    ///
+    /// ## Errors
+    /// - `Error` with [`crate::ErrorKind::Invalid`]: if `cf` is not a valid name
+    /// - `Error`: if there was an error getting the value
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put_cf("users", b"key", b"value").await?;
+    ///     assert_eq!(db.get_cf("users", b"key").await?, Some("value".into()));
+    ///     assert_eq!(db.get_cf("orders", b"key").await?, None);
+    ///     assert_eq!(db.get(b"key").await?, None);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_cf<K: AsRef<[u8]> + Send>(
+        &self,
+        cf: &str,
+        key: K,
+    ) -> Result<Option<Bytes>, crate::Error> {
+        let prefix = column_family_prefix(cf)?;
+        self.get(column_family_key(&prefix, key.as_ref())).await
     }
 
-    async fn begin(&self, isolation_level: IsolationLevel) -> Result<DbTransaction, crate::Error> {
-        Db::begin(self, isolation_level).await
+    /// Write a value into a column family with default write options.
+    ///
+    /// ## Arguments
+    /// - `cf`: the name of the column family, between 1 and 255 bytes long
+    /// - `key`: the key to write
+    /// - `value`: the value to write
+    ///
+    /// ## Errors
+    /// - `Error` with [`crate::ErrorKind::Invalid`]: if `cf` is not a valid name
+    /// - `Error`: if there was an error writing the value
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put_cf("users", b"key", b"value").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn put_cf<K, V>(
+        &self,
+        cf: &str,
+        key: K,
+        value: V,
+    ) -> Result<WriteHandle, crate::Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let prefix = column_family_prefix(cf)?;
+        self.put(column_family_key(&prefix, key.as_ref()), value)
+            .await
     }
-}
 
-impl Db {
-    /// See [`DbMetadataOps::manifest`].
-    pub fn manifest(&self) -> VersionedManifest {
-        <Self as DbMetadataOps>::manifest(self)
+    /// Delete a key from a column family with default write options.
+    ///
+    /// ## Arguments
+    /// - `cf`: the name of the column family, between 1 and 255 bytes long
+    /// - `key`: the key to delete
+    ///
+    /// ## Errors
+    /// - `Error` with [`crate::ErrorKind::Invalid`]: if `cf` is not a valid name
+    /// - `Error`: if there was an error deleting the key
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put_cf("users", b"key", b"value").await?;
+    ///     db.delete_cf("users", b"key").await?;
+    ///     assert_eq!(db.get_cf("users", b"key").await?, None);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn delete_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &str,
+        key: K,
+    ) -> Result<WriteHandle, crate::Error> {
+        let prefix = column_family_prefix(cf)?;
+        self.delete(column_family_key(&prefix, key.as_ref())).await
     }
 
-    /// See [`DbMetadataOps::subscribe`].
-    pub fn subscribe(&self) -> tokio::sync::watch::Receiver<DbStatus> {
-        <Self as DbMetadataOps>::subscribe(self)
+    /// Scan a range of keys of a column family with default scan options.
+    ///
+    /// The scan never returns keys of other column families, even when `range`
+    /// is unbounded, and the returned keys don't include the column family
+    /// prefix.
+    ///
+    /// ## Arguments
+    /// - `cf`: the name of the column family, between 1 and 255 bytes long
+    /// - `range`: the range of keys within the column family to scan
+    ///
+    /// ## Returns
+    /// - `Result<ColumnFamilyIterator, Error>`: an iterator over the keys in range
+    ///
+    /// ## Errors
+    /// - `Error` with [`crate::ErrorKind::Invalid`]: if `cf` is not a valid name
+    ///   or the range starts after it ends
+    /// - `Error`: if there was an error scanning the range of keys
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put_cf("a", b"key", b"value").await?;
+    ///     db.put_cf("b", b"key", b"other").await?;
+    ///
+    ///     let mut iter = db.scan_cf::<&[u8], _>("a", ..).await?;
+    ///     let kv = iter.next().await?.unwrap();
+    ///     assert_eq!(kv.key.as_ref(), b"key");
+    ///     assert_eq!(kv.value.as_ref(), b"value");
+    ///     assert_eq!(None, iter.next().await?);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn scan_cf<K, T>(
+        &self,
+        cf: &str,
+        range: T,
+    ) -> Result<ColumnFamilyIterator, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
+    {
+        let prefix = column_family_prefix(cf)?;
+        let range = column_family_range(&prefix, range)?;
+        let iter = self
+            .inner
+            .scan_with_options(range, &ScanOptions::default())
+            .await?;
+        Ok(ColumnFamilyIterator::new(iter, prefix))
     }
 
-    /// See [`DbMetadataOps::status`].
-    pub fn status(&self) -> DbStatus {
-        <Self as DbMetadataOps>::status(self)
+    /// Put a value only if the key is absent, using the default `PutOptions` and
+    /// `WriteOptions`.
+    ///
+    /// See [`Db::put_if_absent_with_options`] for the exact semantics.
+    ///
+    /// ## Arguments
+    /// - `key`: the key to write
+    /// - `value`: the value to write if the key is absent
+    ///
+    /// ## Returns
+    /// - `Result<bool, Error>`: whether the value was written
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error reading or writing the key.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     assert!(db.put_if_absent(b"key", b"default").await?);
+    ///     assert!(!db.put_if_absent(b"key", b"other").await?);
+    ///     assert_eq!(db.get(b"key").await?, Some(Bytes::from_static(b"default")));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn put_if_absent<K, V>(&self, key: K, value: V) -> Result<bool, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        V: AsRef<[u8]> + Send,
+    {
+        self.put_if(key, value, PutCondition::Absent).await
     }
-}
 
-#[async_trait::async_trait]
-impl DbCacheManagerOps for Db {
-    async fn warm_sst(
+    /// Put a value only if the key is absent.
+    ///
+    /// The current value is read and the new one written within a
+    /// [`IsolationLevel::SerializableSnapshot`] transaction, so of several
+    /// concurrent calls for the same key exactly one writes its value. The others
+    /// are retried, find the written value, and return `false`.
+    ///
+    /// Keys that were deleted or whose TTL has expired are treated as absent, so
+    /// the value is written over them.
+    ///
+    /// ## Arguments
+    /// - `key`: the key to write
+    /// - `value`: the value to write if the key is absent
+    /// - `put_opts`: the put options to use for the write
+    /// - `write_opts`: the write options to use for the write
+    ///
+    /// ## Returns
+    /// - `Result<bool, Error>`: `true` if the key was absent and the value was
+    ///   written, `false` if the key holds a value and nothing was written
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error reading or writing the key.
+    pub async fn put_if_absent_with_options<K, V>(
         &self,
-        sst_id: SsTableId,
-        targets: &[CacheTarget],
-    ) -> Result<(), crate::Error> {
-        self.inner.check_closed()?;
-        let manifest = self.manifest();
-        db_cache_manager::warm_sst_impl(&self.inner.table_store, &manifest, sst_id, targets).await
+        key: K,
+        value: V,
+        put_opts: &PutOptions,
+        write_opts: &WriteOptions,
+    ) -> Result<bool, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        V: AsRef<[u8]> + Send,
+    {
+        self.put_if_with_options(key, value, PutCondition::Absent, put_opts, write_opts)
+            .await
     }
 
-    async fn evict_cached_sst(&self, sst_id: SsTableId) -> Result<(), crate::Error> {
-        self.inner.check_closed()?;
-        db_cache_manager::evict_cached_sst_impl(&self.inner.table_store, sst_id).await
+    /// Put a value only if the key's current value meets `condition`, using the
+    /// default `PutOptions` and `WriteOptions`.
+    ///
+    /// See [`Db::put_if_with_options`] for the exact semantics.
+    ///
+    /// ## Arguments
+    /// - `key`: the key to write
+    /// - `value`: the value to write if the condition is met
+    /// - `condition`: what the key's current value must be
+    ///
+    /// ## Returns
+    /// - `Result<bool, Error>`: whether the value was written
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error reading or writing the key.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use slatedb::{Db, Error, PutCondition};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key", b"v1").await?;
+    ///     let seq = db.get_key_value(b"key").await?.unwrap().seq;
+    ///     assert!(db.put_if(b"key", b"v2", PutCondition::VersionEquals(seq)).await?);
+    ///     // the key was written since `seq` was read
+    ///     assert!(!db.put_if(b"key", b"v3", PutCondition::VersionEquals(seq)).await?);
+    ///     assert_eq!(db.get(b"key").await?, Some(Bytes::from_static(b"v2")));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn put_if<K, V>(
+        &self,
+        key: K,
+        value: V,
+        condition: PutCondition,
+    ) -> Result<bool, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        V: AsRef<[u8]> + Send,
+    {
+        self.put_if_with_options(
+            key,
+            value,
+            condition,
+            &PutOptions::default(),
+            &WriteOptions::default(),
+        )
+        .await
     }
-}
 
-/// Handle returned from write operations, containing metadata about the write.
-/// This structure is designed to be extensible for future enhancements.
-#[derive(Debug, Clone)]
-pub struct WriteHandle {
-    pub(crate) seq: u64,
-    pub(crate) create_ts: i64,
-}
+    /// Put a value only if the key's current value meets `condition`.
+    ///
+    /// The current value is read and the new one written within a
+    /// [`IsolationLevel::SerializableSnapshot`] transaction, so the value is
+    /// only written if no other write to the key committed in between. If one
+    /// did, the call is retried against the value it wrote.
+    ///
+    /// Keys that were deleted or whose TTL has expired have no current value:
+    /// they only meet [`PutCondition::Absent`].
+    ///
+    /// ## Arguments
+    /// - `key`: the key to write
+    /// - `value`: the value to write if the condition is met
+    /// - `condition`: what the key's current value must be
+    /// - `put_opts`: the put options to use for the write
+    /// - `write_opts`: the write options to use for the write
+    ///
+    /// ## Returns
+    /// - `Result<bool, Error>`: `true` if the condition was met and the value
+    ///   was written, `false` if it wasn't and nothing was written
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error reading or writing the key.
+    pub async fn put_if_with_options<K, V>(
+        &self,
+        key: K,
+        value: V,
+        condition: PutCondition,
+        put_opts: &PutOptions,
+        write_opts: &WriteOptions,
+    ) -> Result<bool, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        V: AsRef<[u8]> + Send,
+    {
+        let key = key.as_ref();
+        let value = value.as_ref();
+        self.write_if(
+            key,
+            |current| condition.is_met_by(current),
+            |txn| txn.put_with_options(key, value, put_opts),
+            write_opts,
+        )
+        .await
+    }
 
-impl WriteHandle {
-    pub fn new(seq: u64, create_ts: i64) -> Self {
-        Self { seq, create_ts }
+    /// Reads `key` and, if `condition` accepts its current value, commits the
+    /// write that `write` adds, within a
+    /// [`IsolationLevel::SerializableSnapshot`] transaction. Returns whether
+    /// the write was committed. A commit that conflicts with another write to
+    /// the key is retried against the value that write left.
+    async fn write_if<C, W>(
+        &self,
+        key: &[u8],
+        condition: C,
+        write: W,
+        write_opts: &WriteOptions,
+    ) -> Result<bool, crate::Error>
+    where
+        C: Fn(Option<&KeyValue>) -> bool + Sync,
+        W: Fn(&DbTransaction) -> Result<(), crate::Error> + Sync,
+    {
+        loop {
+            let txn = self.begin(IsolationLevel::SerializableSnapshot).await?;
+            let current = txn.get_key_value(key).await?;
+            if !condition(current.as_ref()) {
+                return Ok(false);
+            }
+            write(&txn)?;
+            match txn.commit_with_options(write_opts).await {
+                Ok(_) => return Ok(true),
+                Err(e) if e.kind() == crate::ErrorKind::Transaction => {
+                    debug!("retrying conditional write after conflict [key={:?}]", key);
+                }
+                Err(e) => return Err(e),
+            }
+        }
     }
 
-    /// Returns the sequence number assigned to this write operation.
-    pub fn seqnum(&self) -> u64 {
-        self.seq
+    /// Delete a key only if its current value matches `expected`, using the default
+    /// `WriteOptions`.
+    ///
+    /// See [`Db::delete_if_with_options`] for the exact semantics.
+    ///
+    /// ## Arguments
+    /// - `key`: the key to delete
+    /// - `expected`: the value the key must currently hold, or `None` if it must be absent
+    ///
+    /// ## Returns
+    /// - `Result<bool, Error>`: whether the current value matched `expected`
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error reading or deleting the key.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key", b"value").await?;
+    ///     assert!(!db.delete_if(b"key", Some(Bytes::from_static(b"other"))).await?);
+    ///     assert!(db.delete_if(b"key", Some(Bytes::from_static(b"value"))).await?);
+    ///     assert_eq!(db.get(b"key").await?, None);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn delete_if<K: AsRef<[u8]> + Send>(
+        &self,
+        key: K,
+        expected: Option<Bytes>,
+    ) -> Result<bool, crate::Error> {
+        self.delete_if_with_options(key, expected, &WriteOptions::default())
+            .await
     }
 
-    /// Returns the creation timestamp assigned to this write operation.
-    pub fn create_ts(&self) -> i64 {
-        self.create_ts
+    /// Delete a key only if its current value matches `expected`.
+    ///
+    /// The current value is read and the tombstone written within a
+    /// [`IsolationLevel::SerializableSnapshot`] transaction, so a concurrent write
+    /// to the key between the two causes the check to be retried against the new
+    /// value rather than deleting it.
+    ///
+    /// Keys that were deleted or whose TTL has expired are treated as absent:
+    /// - `expected` is `Some(value)`: the key is deleted and `true` is returned if its
+    ///   current value equals `value`. Otherwise nothing is written and `false` is
+    ///   returned, including when the key is absent.
+    /// - `expected` is `None`: nothing is ever written, since an absent key has
+    ///   nothing to delete. `true` is returned if the key is absent and `false` if it
+    ///   currently holds a value.
+    ///
+    /// ## Arguments
+    /// - `key`: the key to delete
+    /// - `expected`: the value the key must currently hold, or `None` if it must be absent
+    /// - `options`: the write options to use for the delete
+    ///
+    /// ## Returns
+    /// - `Result<bool, Error>`: whether the current value matched `expected`
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error reading or deleting the key.
+    pub async fn delete_if_with_options<K: AsRef<[u8]> + Send>(
+        &self,
+        key: K,
+        expected: Option<Bytes>,
+        options: &WriteOptions,
+    ) -> Result<bool, crate::Error> {
+        let key = key.as_ref();
+        let Some(expected) = expected else {
+            // an absent key has nothing to delete
+            return Ok(self.get(key).await?.is_none());
+        };
+        self.write_if(
+            key,
+            |current| current.is_some_and(|current| current.value == expected),
+            |txn| txn.delete(key),
+            options,
+        )
+        .await
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::cached_object_store::stats::{PART_ACCESS_COUNT, PART_HIT_COUNT};
-    use crate::cached_object_store::{CachedObjectStore, FsCacheStorage};
-    use crate::cached_object_store_stats::CachedObjectStoreStats;
-    use crate::config::DurabilityLevel::{Memory, Remote};
-    use crate::config::{
-        CheckpointOptions, CompactorOptions, GarbageCollectorDirectoryOptions,
-        GarbageCollectorOptions, ObjectStoreCacheOptions, PutOptions, ScanOptions, Settings, Ttl,
-        WriteOptions,
-    };
-    use crate::db::builder::GarbageCollectorBuilder;
-    use crate::db_stats::IMMUTABLE_MEMTABLE_FLUSHES;
-    use crate::format::sst::SsTableFormat;
-    use crate::instrumented_object_store::stats::{
-        REQUEST_COUNT as OBJECT_STORE_REQUEST_COUNT,
-        REQUEST_DURATION_SECONDS as OBJECT_STORE_REQUEST_DURATION_SECONDS,
-    };
-    use crate::iter::RowEntryIterator;
-    use crate::manifest::store::{ManifestStore, StoredManifest};
-    use crate::manifest::{ManifestCore, VersionedManifest};
-    use crate::merge_operator::{
-        MERGE_OPERATOR_COMPACT_PATH, MERGE_OPERATOR_FLUSH_PATH, MERGE_OPERATOR_READ_PATH,
-    };
-    use crate::object_stores::ObjectStores;
-    use crate::proptest_util::arbitrary;
-    use crate::proptest_util::sample;
-    use crate::rand::DbRand;
-    use crate::seq_tracker::FindOption;
-    use crate::sst_iter::{SstIterator, SstIteratorOptions};
-    use crate::test_utils::{
-        assert_iterator, lookup_merge_operator_operands, OnDemandCompactionSchedulerSupplier,
-        StringConcatMergeOperator,
-    };
-    use crate::types::RowEntry;
-    use crate::wal_reader::WalReader;
-    use crate::{proptest_util, test_utils, CloseReason, CompactorBuilder, KeyValue};
-    use async_trait::async_trait;
-    use chrono::{TimeZone, Utc};
-    use fail_parallel::FailPointRegistry;
-    use futures::{future, future::join_all, StreamExt};
-    use object_store::memory::InMemory;
-    use object_store::ObjectStore;
-    use proptest::test_runner::{TestRng, TestRunner};
-    use slatedb_common::clock::DefaultSystemClock;
-    use slatedb_common::clock::MockSystemClock;
-    use slatedb_common::metrics::{
-        lookup_metric, lookup_metric_with_labels, DefaultMetricsRecorder, MetricValue,
-        MetricsRecorderHelper,
-    };
-    use std::collections::BTreeMap;
-    use std::collections::Bound::Included;
-    use std::sync::atomic::{AtomicBool, Ordering};
-    use std::time::Duration;
-    use tokio::runtime::Runtime;
-    use tracing::info;
+    /// Merge a value into the database with default `MergeOptions` and `WriteOptions`.
+    ///
+    /// Merge operations allow applications to bypass the traditional read/modify/write cycle
+    /// by expressing partial updates using an associative operator. The merge operator must
+    /// be configured when opening the database.
+    ///
+    /// ## Arguments
+    /// - `key`: the key to merge into
+    /// - `value`: the merge operand to apply
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error merging the value, or if no merge operator is configured.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error, MergeOperator, MergeOperatorError};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    /// use bytes::Bytes;
+    ///
+    /// struct StringConcatMergeOperator;
+    ///
+    /// impl MergeOperator for StringConcatMergeOperator {
+    ///     fn merge(&self, _key: &Bytes, existing_value: Option<Bytes>, value: Bytes) -> Result<Bytes, MergeOperatorError> {
+    ///         let mut result = existing_value.unwrap_or_default().as_ref().to_vec();
+    ///         result.extend_from_slice(&value);
+    ///         Ok(Bytes::from(result))
+    ///     }
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::builder("test_db", object_store)
+    ///         .with_merge_operator(Arc::new(StringConcatMergeOperator))
+    ///         .build()
+    ///         .await?;
+    ///     let handle = db.merge(b"key", b"value").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn merge<K, V>(&self, key: K, value: V) -> Result<WriteHandle, crate::Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.merge_with_options(
+            key,
+            value,
+            &MergeOptions::default(),
+            &WriteOptions::default(),
+        )
+        .await
+    }
+
+    /// Merge a value into the database with custom `MergeOptions` and `WriteOptions`.
+    ///
+    /// Merge operations allow applications to bypass the traditional read/modify/write cycle
+    /// by expressing partial updates using an associative operator. The merge operator must
+    /// be configured when opening the database.
+    ///
+    /// ## Arguments
+    /// - `key`: the key to merge into
+    /// - `value`: the merge operand to apply
+    /// - `merge_opts`: the merge options to use
+    /// - `write_opts`: the write options to use
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error merging the value, or if no merge operator is configured.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error, MergeOperator, MergeOperatorError, config::{MergeOptions, WriteOptions}};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    /// use bytes::Bytes;
+    ///
+    /// struct StringConcatMergeOperator;
+    ///
+    /// impl MergeOperator for StringConcatMergeOperator {
+    ///     fn merge(&self, _key: &Bytes, existing_value: Option<Bytes>, value: Bytes) -> Result<Bytes, MergeOperatorError> {
+    ///         let mut result = existing_value.unwrap_or_default().as_ref().to_vec();
+    ///         result.extend_from_slice(&value);
+    ///         Ok(Bytes::from(result))
+    ///     }
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::builder("test_db", object_store)
+    ///         .with_merge_operator(Arc::new(StringConcatMergeOperator))
+    ///         .build()
+    ///         .await?;
+    ///     let handle = db.merge_with_options(
+    ///         b"key",
+    ///         b"value",
+    ///         &MergeOptions::default(),
+    ///         &WriteOptions::default()
+    ///     ).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn merge_with_options<K, V>(
+        &self,
+        key: K,
+        value: V,
+        merge_opts: &MergeOptions,
+        write_opts: &WriteOptions,
+    ) -> Result<WriteHandle, crate::Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        if self.inner.flush_merge_operator.is_none() {
+            return Err(SlateDBError::MergeOperatorMissing.into());
+        }
+
+        let mut batch = WriteBatch::new();
+        batch.merge_with_options(key, value, merge_opts);
+        self.write_with_options(batch, write_opts).await
+    }
+
+    /// Atomically adds `delta` to the counter stored at `key` and returns its new
+    /// value.
+    ///
+    /// The increment is written as a merge operand, so concurrent increments of the
+    /// same key don't race the way a client-side read-modify-write would. The
+    /// database must be opened with [`crate::IntegerAddMergeOperator`] as its merge
+    /// operator, which resolves the operands on reads and compactions. A key
+    /// without a value counts from 0, and so does a key holding a value that isn't
+    /// a counter: the increment overwrites it. Use
+    /// [`crate::IntegerAddMergeOperator::decode`] to read a counter with
+    /// [`Db::get`].
+    ///
+    /// The returned value includes every increment written before this one and
+    /// none written after it, even if they're written concurrently. The call
+    /// waits for the increment to be durable, like [`Db::merge`].
+    ///
+    /// ## Arguments
+    /// - `key`: the key of the counter
+    /// - `delta`: the amount to add, which may be negative
+    ///
+    /// ## Returns
+    /// - `Ok(i64)`: the value of the counter after the increment
+    ///
+    /// ## Errors
+    /// - `Error` with [`crate::ErrorKind::Invalid`]: if no merge operator is
+    ///   configured, or if the configured merge operator didn't produce a counter
+    /// - `Error`: if there was an error writing or reading the counter
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error, IntegerAddMergeOperator};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::builder("test_db", object_store)
+    ///         .with_merge_operator(Arc::new(IntegerAddMergeOperator))
+    ///         .build()
+    ///         .await?;
+    ///     assert_eq!(db.increment(b"visits", 1).await?, 1);
+    ///     assert_eq!(db.increment(b"visits", 5).await?, 6);
+    ///     let visits = db.get(b"visits").await?.unwrap();
+    ///     assert_eq!(IntegerAddMergeOperator::decode(&visits), Some(6));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn increment<K>(&self, key: K, delta: i64) -> Result<i64, crate::Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        // Flushes don't merge operands written after an active snapshot, so
+        // holding one across the write keeps this increment's operand apart from
+        // later ones until the counter is read back as of the increment below.
+        let _barrier = DbSnapshot::new(self.inner.clone(), None);
+        let handle = self
+            .merge_with_options(
+                key,
+                IntegerAddMergeOperator::encode(delta),
+                &MergeOptions::default(),
+                &WriteOptions::default(),
+            )
+            .await?;
+        let snapshot = DbSnapshot::new(self.inner.clone(), Some(handle.seqnum()));
+        let value = snapshot.get(key).await?.unwrap_or_default();
+        IntegerAddMergeOperator::decode(&value).ok_or_else(|| {
+            crate::Error::invalid(format!(
+                "merge operator produced a {} byte value for counter {:?}, expected 8 bytes",
+                value.len(),
+                Bytes::copy_from_slice(key)
+            ))
+        })
+    }
+
+    /// Write a batch of put/delete operations atomically to the database. Batch writes
+    /// block other gets and writes until the batch is written to the WAL (or memtable if
+    /// WAL is disabled).
+    ///
+    /// ## Arguments
+    /// - `batch`: the batch of put/delete operations to write
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error writing the batch.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{WriteBatch, Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///
+    ///     let mut batch = WriteBatch::new();
+    ///     batch.put(b"key1", b"value1");
+    ///     batch.put(b"key2", b"value2");
+    ///     batch.delete(b"key1");
+    ///     let handle = db.write(batch).await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn write(&self, batch: WriteBatch) -> Result<WriteHandle, crate::Error> {
+        self.write_with_options(batch, &WriteOptions::default())
+            .await
+    }
+
+    /// Write a batch of put/delete operations atomically to the database. Batch writes
+    /// block other gets and writes until the batch is written to the WAL (or memtable if
+    /// WAL is disabled).
+    ///
+    /// ## Arguments
+    /// - `batch`: the batch of put/delete operations to write
+    /// - `options`: the write options to use
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error writing the batch.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{WriteBatch, Db, config::WriteOptions, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///
+    ///     let mut batch = WriteBatch::new();
+    ///     batch.put(b"key1", b"value1");
+    ///     batch.put(b"key2", b"value2");
+    ///     batch.delete(b"key1");
+    ///     let handle = db.write_with_options(batch, &WriteOptions::default()).await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn write_with_options(
+        &self,
+        batch: WriteBatch,
+        options: &WriteOptions,
+    ) -> Result<WriteHandle, crate::Error> {
+        self.inner
+            .write_with_options(batch, options)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Atomically replaces every key in a range with a new set of entries, e.g.
+    /// to swap in the recomputed contents of a range. Readers see either the
+    /// old contents of the range or the new ones, never a mix of both.
+    ///
+    /// The keys currently in the range are read in a serializable transaction,
+    /// which then deletes the keys that aren't among the new entries and puts
+    /// the new entries, all in one atomic write. If another write to the range
+    /// commits while the replacement runs, the replacement fails rather than
+    /// leaving that write's keys in place, and can be retried.
+    ///
+    /// ## Arguments
+    /// - `range`: the range of keys to replace
+    /// - `new_entries`: the keys and values the range holds afterwards, which
+    ///   must all be in `range`
+    ///
+    /// ## Errors
+    /// - `Error`: with kind [`crate::ErrorKind::Invalid`] if a new entry's key is
+    ///   outside of `range` or the range's start is after its end, with kind
+    ///   [`crate::ErrorKind::Transaction`] if a concurrent write to the range
+    ///   conflicted with the replacement, or if there was an error reading or
+    ///   writing the range
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"report/a", b"old").await?;
+    ///     db.put(b"report/b", b"old").await?;
+    ///
+    ///     db.replace_range(
+    ///         b"report/".as_slice()..b"report0".as_slice(),
+    ///         [(b"report/b", b"new"), (b"report/c", b"new")],
+    ///     )
+    ///     .await?;
+    ///     assert_eq!(db.get(b"report/a").await?, None);
+    ///     assert_eq!(db.get(b"report/c").await?, Some("new".into()));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn replace_range<K, T, I, NK, NV>(
+        &self,
+        range: T,
+        new_entries: I,
+    ) -> Result<(), crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
+        I: IntoIterator<Item = (NK, NV)>,
+        NK: AsRef<[u8]>,
+        NV: AsRef<[u8]>,
+    {
+        let range = BytesRange::try_from_scan_range(&range)?;
+        let txn = self.begin(IsolationLevel::SerializableSnapshot).await?;
+        let mut new_keys = HashSet::new();
+        for (key, value) in new_entries {
+            let key = Bytes::copy_from_slice(key.as_ref());
+            if !range.contains(&key) {
+                return Err(crate::Error::invalid(format!(
+                    "key {:?} is outside of the replaced range {:?}",
+                    key, range
+                )));
+            }
+            txn.put(&key, value)?;
+            new_keys.insert(key);
+        }
+        let mut iter = txn
+            .scan_with_options(range, &ScanOptions::default())
+            .await?;
+        let mut old_keys = Vec::new();
+        while let Some(kv) = iter.next().await? {
+            old_keys.push(kv.key);
+        }
+        for key in old_keys.iter().filter(|key| !new_keys.contains(*key)) {
+            txn.delete(key)?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Flush in-memory writes to disk. This function blocks until the in-memory
+    /// data has been durably written to object storage.
+    ///
+    /// If WAL is enabled, this method is equivalent to:
+    /// `flush_with_options(FlushOptions { flush_type: FlushType::Wal })`
+    ///
+    /// If WAL is disabled, this method is equivalent to:
+    /// `flush_with_options(FlushOptions { flush_type: FlushType::Memtable })`.
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error flushing the database
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.flush().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn flush(&self) -> Result<(), crate::Error> {
+        let flush_type = if self.inner.wal_enabled {
+            FlushType::Wal
+        } else {
+            FlushType::MemTable
+        };
+        self.inner
+            .flush(FlushOptions { flush_type }, true)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Flush in-memory writes to disk with custom options.
+    ///
+    /// An error will be returned if `options.flush_type` is `FlushType::Wal` and the WAL
+    /// is disabled.
+    ///
+    /// `FlushType::Memtable` is allowed even if WAL is enabled.
+    ///
+    /// ## Arguments
+    /// - `options`: the flush options
+    ///
+    /// ## Returns
+    /// - `Result<(), crate::Error>`: the result of the flush operation.
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error flushing the database
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::config::{FlushOptions, FlushType};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.flush_with_options(FlushOptions {
+    ///         flush_type: FlushType::Wal,
+    ///     })
+    ///     .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn flush_with_options(&self, options: FlushOptions) -> Result<(), crate::Error> {
+        self.inner.flush(options, true).await.map_err(Into::into)
+    }
+
+    /// Waits until every write committed before the call is durable, and
+    /// returns the highest durable sequence number.
+    ///
+    /// Pending writes are flushed as [`Db::flush`] would flush them. If every
+    /// committed write is already durable, the current durable sequence number
+    /// is returned right away, without flushing.
+    ///
+    /// The returned watermark can be recorded by an external system, e.g.
+    /// alongside a message queue offset, to know which writes survive a crash.
+    /// Every write whose [`WriteHandle::seqnum`] is at most the watermark is
+    /// durable.
+    ///
+    /// ## Returns
+    /// - `Ok(u64)`: the highest durable sequence number, which is at least the
+    ///   sequence number of every write committed before the call
+    ///
+    /// ## Errors
+    /// - `Error`: if the db is closed, or there was an error flushing it
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::config::{PutOptions, WriteOptions};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     let write_options = WriteOptions {
+    ///         await_durable: false,
+    ///         ..WriteOptions::default()
+    ///     };
+    ///     let handle = db
+    ///         .put_with_options(b"key", b"value", &PutOptions::default(), &write_options)
+    ///         .await?;
+    ///     let watermark = db.sync().await?;
+    ///     assert!(watermark >= handle.seqnum());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn sync(&self) -> Result<u64, crate::Error> {
+        self.inner.check_closed()?;
+        let last_committed_seq = self.inner.oracle.last_committed_seq();
+        if self.inner.oracle.last_remote_persisted_seq() < last_committed_seq {
+            self.flush().await?;
+        }
+        Ok(self.inner.oracle.last_remote_persisted_seq())
+    }
+
+    /// Run every eligible compaction now and wait for those compactions to finish.
+    ///
+    /// The compactor run by this db refreshes its view of the manifest and
+    /// schedules every compaction its scheduler proposes, as it would on its next
+    /// poll, up to its concurrency limit. The call returns once the compactions
+    /// it scheduled have finished. Compactions that were already running, and
+    /// ones scheduled later (e.g. as the scheduled ones finish), are neither
+    /// waited for nor included in the report. Memtables are not flushed first;
+    /// call [`Db::flush_with_options`] with [`FlushType::MemTable`] to compact
+    /// data that is still in memory.
+    ///
+    /// Background compaction keeps running while the call is in progress, and
+    /// several calls may wait at the same time. A call made while the
+    /// compactions of an earlier call are still running can't schedule
+    /// compactions of the same sources, so it returns an empty report right away
+    /// rather than waiting for the earlier call's compactions.
+    ///
+    /// ## Returns
+    /// - `Result<CompactionReport, crate::Error>`: statistics about the
+    ///   compactions the call scheduled.
+    ///
+    /// ## Errors
+    /// - `Error`: if this db doesn't run a compactor, or the compactor stopped
+    ///   before the scheduled compactions finished
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::config::{FlushOptions, FlushType};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key", b"value").await?;
+    ///     db.flush_with_options(FlushOptions {
+    ///         flush_type: FlushType::MemTable,
+    ///     })
+    ///     .await?;
+    ///     let report = db.compact_now().await?;
+    ///     assert_eq!(report.failed_compactions, 0);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn compact_now(&self) -> Result<CompactionReport, crate::Error> {
+        let Some(compactor_tx) = &self.compactor_tx else {
+            return Err(SlateDBError::CompactorNotRunning.into());
+        };
+        self.inner.check_closed()?;
+        let (sender, rx) = tokio::sync::oneshot::channel();
+        compactor_tx.send(CompactorMessage::CompactNow { sender })?;
+        Ok(rx.await.map_err(SlateDBError::from)??)
+    }
+
+    /// Release as much memory as possible, for use under memory pressure.
+    ///
+    /// Rotates the active memtable and flushes it to L0 along with any
+    /// immutable memtables, even if they're below `l0_sst_size_bytes`, and
+    /// drops all entries held in memory by the block cache and the cache of
+    /// recent point reads. Intended to be called from an external
+    /// memory-pressure monitor (e.g. cgroup memory notifications).
+    ///
+    /// The call is cheap when there's nothing to release, so it's safe to call
+    /// frequently. Calling it again right after it returned is a no-op that
+    /// returns `0`, unless new writes or reads arrived in between.
+    ///
+    /// ## Returns
+    /// - `Result<u64, crate::Error>`: an estimate of the number of bytes
+    ///   released, summing the memtable entries that were flushed and the
+    ///   cache entries that were dropped.
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error flushing the memtables
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key", b"value").await?;
+    ///     let released = db.trim_memory().await?;
+    ///     assert!(released > 0);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn trim_memory(&self) -> Result<u64, crate::Error> {
+        self.inner.trim_memory().await.map_err(Into::into)
+    }
+
+    /// Refresh the manifest immediately and wait for it to complete.
+    ///
+    /// The database normally refreshes its manifest on a background timer
+    /// controlled by [`Settings::manifest_poll_interval`]. This method
+    /// bypasses that timer, triggering an immediate refresh and waiting
+    /// for it to finish.
+    ///
+    /// Use this when you know the manifest has changed externally and
+    /// want to ensure the database has observed the update before
+    /// proceeding — for example, after a compaction completes and you
+    /// need to confirm that a compaction filter has been applied.
+    ///
+    /// ## Errors
+    /// - Returns [`Error`] if the database is closed before the refresh
+    ///   completes.
+    pub async fn refresh_manifest(&self) -> Result<(), crate::Error> {
+        self.inner.check_closed()?;
+        self.inner
+            .memtable_flusher
+            .refresh_manifest()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Begin a new transaction with the specified isolation level.
+    ///
+    /// ## Arguments
+    /// - `isolation_level`: the isolation level for the transaction
+    ///
+    /// ## Returns
+    /// - `Result<DbTransaction, crate::Error>`: the transaction handle
+    ///
+    /// ## Examples
+    ///
+    /// ```rust
+    /// use slatedb::{Db, IsolationLevel};
+    /// use slatedb::object_store::memory::InMemory;
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), slatedb::Error> {
+    ///     let object_store = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     let txn = db.begin(IsolationLevel::SerializableSnapshot).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn begin(
+        &self,
+        isolation_level: IsolationLevel,
+    ) -> Result<DbTransaction, crate::Error> {
+        self.inner.check_closed()?;
+        let txn = DbTransaction::new(
+            self.inner.clone(),
+            self.inner.txn_manager.clone(),
+            isolation_level,
+        );
+        Ok(txn)
+    }
+
+    /// Resolve an object store from a URL.
+    ///
+    /// ## Arguments
+    /// - `url`: the URL to resolve, for example `s3://my-bucket/my-prefix`.
+    ///
+    /// ## Returns
+    /// - `Result<Arc<dyn ObjectStore>, crate::Error>`: the resolved object store
+    pub fn resolve_object_store(url: &str) -> Result<Arc<dyn ObjectStore>, crate::Error> {
+        let url = url
+            .try_into()
+            .map_err(|e| SlateDBError::InvalidObjectStoreURL(url.to_string(), e))?;
+        // Lowercase env keys because parse_url_opts only recognizes lower case option keys.
+        let env_vars = std::env::vars().map(|(key, value)| (key.to_ascii_lowercase(), value));
+        let (object_store, path) = parse_url_opts(&url, env_vars).map_err(SlateDBError::from)?;
+        let object_store: Arc<dyn ObjectStore> = if path.as_ref().is_empty() {
+            Arc::from(object_store)
+        } else {
+            let object_store = Arc::from(object_store);
+            Arc::new(PrefixStore::new(object_store, path))
+        };
+        Ok(object_store)
+    }
+}
+
+#[async_trait::async_trait]
+impl DbReadOps for Db {
+    async fn get_with_options<K: AsRef<[u8]> + Send>(
+        &self,
+        key: K,
+        options: &ReadOptions,
+    ) -> Result<Option<Bytes>, crate::Error> {
+        Db::get_with_options(self, key, options).await
+    }
+
+    async fn get_key_value_with_options<K: AsRef<[u8]> + Send>(
+        &self,
+        key: K,
+        options: &ReadOptions,
+    ) -> Result<Option<KeyValue>, crate::Error> {
+        Db::get_key_value_with_options(self, key, options).await
+    }
+
+    async fn scan_with_options<K, T>(
+        &self,
+        range: T,
+        options: &ScanOptions,
+    ) -> Result<DbIterator, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
+    {
+        Db::scan_with_options(self, range, options).await
+    }
+
+    async fn scan_prefix_with_options<P>(
+        &self,
+        prefix: P,
+        options: &ScanOptions,
+    ) -> Result<DbIterator, crate::Error>
+    where
+        P: AsRef<[u8]> + Send,
+    {
+        Db::scan_prefix_with_options(self, prefix, options).await
+    }
+}
+
+impl DbMetadataOps for Db {
+    fn manifest(&self) -> VersionedManifest {
+        self.inner.manifest()
+    }
+
+    fn subscribe(&self) -> tokio::sync::watch::Receiver<DbStatus> {
+        self.inner.status_manager.subscribe()
+    }
+
+    fn status(&self) -> DbStatus {
+        self.inner.status()
+    }
+}
+
+#[async_trait::async_trait]
+impl DbWriteOps for Db {
+    type Transaction = DbTransaction;
+
+    async fn put_with_options<K, V>(
+        &self,
+        key: K,
+        value: V,
+        put_opts: &PutOptions,
+        write_opts: &WriteOptions,
+    ) -> Result<WriteHandle, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        V: AsRef<[u8]> + Send,
+    {
+        Db::put_with_options(self, key, value, put_opts, write_opts).await
+    }
+
+    async fn delete_with_options<K: AsRef<[u8]> + Send>(
+        &self,
+        key: K,
+        options: &WriteOptions,
+    ) -> Result<WriteHandle, crate::Error> {
+        Db::delete_with_options(self, key, options).await
+    }
+
+    async fn merge_with_options<K, V>(
+        &self,
+        key: K,
+        value: V,
+        merge_opts: &MergeOptions,
+        write_opts: &WriteOptions,
+    ) -> Result<WriteHandle, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        V: AsRef<[u8]> + Send,
+    {
+        Db::merge_with_options(self, key, value, merge_opts, write_opts).await
+    }
+
+    async fn write_with_options(
+        &self,
+        batch: WriteBatch,
+        options: &WriteOptions,
+    ) -> Result<WriteHandle, crate::Error> {
+        Db::write_with_options(self, batch, options).await
+    }
+
+    async fn flush(&self) -> Result<(), crate::Error> {
+        Db::flush(self).await
+    }
+
+    async fn flush_with_options(&self, options: FlushOptions) -> Result<(), crate::Error> {
+        Db::flush_with_options(self, options).await
+    }
+
+    async fn begin(&self, isolation_level: IsolationLevel) -> Result<DbTransaction, crate::Error> {
+        Db::begin(self, isolation_level).await
+    }
+}
+
+impl Db {
+    /// See [`DbMetadataOps::manifest`].
+    pub fn manifest(&self) -> VersionedManifest {
+        <Self as DbMetadataOps>::manifest(self)
+    }
+
+    /// See [`DbMetadataOps::subscribe`].
+    pub fn subscribe(&self) -> tokio::sync::watch::Receiver<DbStatus> {
+        <Self as DbMetadataOps>::subscribe(self)
+    }
+
+    /// See [`DbMetadataOps::status`].
+    pub fn status(&self) -> DbStatus {
+        <Self as DbMetadataOps>::status(self)
+    }
+
+    /// Returns the highest sequence number assigned to a write so far,
+    /// including writes that haven't been committed or made durable yet. See
+    /// [`crate::SequenceAllocator`].
+    pub fn current_sequence(&self) -> u64 {
+        self.inner.oracle.last_seq()
+    }
+
+    /// Returns a snapshot of the sizes of this database's memtables and the
+    /// shape of its LSM tree, e.g. to serve from a status endpoint. Taking
+    /// the snapshot doesn't wait for writes in progress.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::memory::InMemory;
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key", b"value").await?;
+    ///     let stats = db.stats();
+    ///     assert_eq!(stats.active_memtable.entries, 1);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn stats(&self) -> crate::DbStats {
+        let view = self.inner.state.read().view();
+        let durable_seq = self.inner.oracle.last_remote_persisted_seq();
+        crate::DbStats::new(&view, durable_seq)
+    }
+
+    /// Estimates the number of entries in a range of keys and their size,
+    /// e.g. to decide where to split a range into shards of similar size.
+    ///
+    /// Entries in memory are counted exactly. Entries in SSTs are estimated
+    /// from the blocks of each SST that cover the range, so the estimate may
+    /// be off by up to a block at each end of the range in every SST. Every
+    /// version of a key and every tombstone that hasn't been compacted away is
+    /// counted.
+    ///
+    /// ## Arguments
+    /// - `range`: the range of keys to estimate
+    ///
+    /// ## Errors
+    /// - `Error`: with kind [`crate::ErrorKind::Invalid`] if the range's start is
+    ///   after its end, or if there was an error reading the SSTs' indexes
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::memory::InMemory;
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"a", b"1").await?;
+    ///     db.put(b"b", b"2").await?;
+    ///     db.put(b"c", b"3").await?;
+    ///     let estimate = db.estimate_range(b"a".as_slice()..b"c".as_slice()).await?;
+    ///     assert_eq!(estimate.keys, 2);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn estimate_range<K, T>(&self, range: T) -> Result<crate::RangeEstimate, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
+    {
+        self.inner.check_closed()?;
+        let range = BytesRange::try_from_scan_range(&range)?;
+        self.inner.estimate_range(range).await.map_err(Into::into)
+    }
+
+    /// Returns an exporter that renders this database's memtable gauges in the
+    /// Prometheus text format on every call to
+    /// [`MemtableMetricsExporter::render`](crate::MemtableMetricsExporter::render).
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::memory::InMemory;
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     let exporter = db.memtable_metrics_exporter();
+    ///     db.put(b"key", b"value").await?;
+    ///     assert!(exporter
+    ///         .render()
+    ///         .contains("slatedb_memtable_entries{state=\"mutable\",position=\"0\"} 1"));
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "prometheus")]
+    pub fn memtable_metrics_exporter(&self) -> crate::MemtableMetricsExporter {
+        crate::MemtableMetricsExporter::new(self)
+    }
+}
+
+#[async_trait::async_trait]
+impl DbCacheManagerOps for Db {
+    async fn warm_sst(
+        &self,
+        sst_id: SsTableId,
+        targets: &[CacheTarget],
+    ) -> Result<(), crate::Error> {
+        self.inner.check_closed()?;
+        let manifest = self.manifest();
+        db_cache_manager::warm_sst_impl(&self.inner.table_store, &manifest, sst_id, targets).await
+    }
+
+    async fn evict_cached_sst(&self, sst_id: SsTableId) -> Result<(), crate::Error> {
+        self.inner.check_closed()?;
+        db_cache_manager::evict_cached_sst_impl(&self.inner.table_store, sst_id).await
+    }
+}
+
+/// What a write does when flushing hasn't caught up with writes.
+#[derive(Clone, Copy)]
+enum Backpressure {
+    /// Wait for flushing to catch up. Fails if the immutable memtable queue
+    /// is full.
+    Wait,
+    /// Return [`PutOutcome::WouldBlock`] instead of waiting.
+    WouldBlock,
+}
+
+/// The result of [`Db::try_put`].
+#[derive(Debug, Clone)]
+pub enum PutOutcome {
+    /// The write was applied.
+    Applied(WriteHandle),
+    /// The write was not applied because it would have had to wait for
+    /// flushing to catch up.
+    WouldBlock {
+        /// The number of immutable memtables waiting to be flushed.
+        queued_immutables: usize,
+        /// The estimated size of the unflushed WAL and immutable memtables.
+        queued_bytes: usize,
+    },
+}
+
+/// Handle returned from write operations, containing metadata about the write.
+/// This structure is designed to be extensible for future enhancements.
+#[derive(Debug, Clone)]
+pub struct WriteHandle {
+    pub(crate) seq: u64,
+    pub(crate) create_ts: i64,
+}
+
+impl WriteHandle {
+    pub fn new(seq: u64, create_ts: i64) -> Self {
+        Self { seq, create_ts }
+    }
+
+    /// Returns the sequence number assigned to this write operation.
+    pub fn seqnum(&self) -> u64 {
+        self.seq
+    }
+
+    /// Returns the creation timestamp assigned to this write operation.
+    pub fn create_ts(&self) -> i64 {
+        self.create_ts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cached_object_store::stats::{PART_ACCESS_COUNT, PART_HIT_COUNT};
+    use crate::cached_object_store::{CachedObjectStore, FsCacheStorage};
+    use crate::cached_object_store_stats::CachedObjectStoreStats;
+    use crate::config::DurabilityLevel::{Memory, Remote};
+    use crate::config::{
+        CheckpointOptions, CompactorOptions, GarbageCollectorDirectoryOptions,
+        GarbageCollectorOptions, ObjectStoreCacheOptions, PutOptions, ScanOptions, Settings, Ttl,
+        WriteOptions,
+    };
+    use crate::conflict_resolver::ConflictWinner;
+    use crate::db::builder::GarbageCollectorBuilder;
+    use crate::db_state::SsTableView;
+    use crate::db_stats::IMMUTABLE_MEMTABLE_FLUSHES;
+    use crate::flush_policy::FlushSignals;
+    use crate::format::sst::SsTableFormat;
+    use crate::instrumented_object_store::stats::{
+        REQUEST_COUNT as OBJECT_STORE_REQUEST_COUNT,
+        REQUEST_DURATION_SECONDS as OBJECT_STORE_REQUEST_DURATION_SECONDS,
+    };
+    use crate::iter::RowEntryIterator;
+    use crate::manifest::store::{ManifestStore, StoredManifest};
+    use crate::manifest::{ManifestCore, VersionedManifest};
+    use crate::merge_operator::{
+        MERGE_OPERATOR_COMPACT_PATH, MERGE_OPERATOR_FLUSH_PATH, MERGE_OPERATOR_READ_PATH,
+    };
+    use crate::object_stores::ObjectStores;
+    use crate::proptest_util::arbitrary;
+    use crate::proptest_util::sample;
+    use crate::rand::DbRand;
+    use crate::rate_limiter::{RateLimitMode, TenantRateLimit, TokenBucketRateLimiter};
+    use crate::read_cache::PinnedLookup;
+    use crate::read_repair::ReadRepairCheck;
+    use crate::seq_tracker::FindOption;
+    use crate::shutdown::ShutdownPhase;
+    use crate::sst_iter::{SstIterator, SstIteratorOptions};
+    use crate::test_utils::{
+        assert_iterator, lookup_merge_operator_operands, OnDemandCompactionSchedulerSupplier,
+        StringConcatMergeOperator, VecSource,
+    };
+    use crate::types::{RowEntry, ValueDeletable};
+    use crate::wal_reader::WalReader;
+    use crate::{proptest_util, test_utils, CloseReason, CompactorBuilder, ErrorKind, KeyValue};
+    use async_trait::async_trait;
+    use chrono::{TimeZone, Utc};
+    use fail_parallel::FailPointRegistry;
+    use futures::{future, future::join_all, StreamExt};
+    use object_store::memory::InMemory;
+    use object_store::ObjectStore;
+    use proptest::test_runner::{TestRng, TestRunner};
+    use slatedb_common::clock::DefaultSystemClock;
+    use slatedb_common::clock::MockSystemClock;
+    use slatedb_common::metrics::{
+        lookup_metric, lookup_metric_with_labels, DefaultMetricsRecorder, MetricValue,
+        MetricsRecorderHelper,
+    };
+    use std::collections::BTreeMap;
+    use std::collections::Bound::Included;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+    use tokio::runtime::Runtime;
+    use tracing::info;
+
+    fn object_store_labels(
+        component: &'static str,
+        store_type: &'static str,
+        op: &'static str,
+        api: &'static str,
+    ) -> [(&'static str, &'static str); 4] {
+        [
+            ("component", component),
+            ("store_type", store_type),
+            ("op", op),
+            ("api", api),
+        ]
+    }
+
+    fn lookup_object_store_histogram_count(
+        recorder: &DefaultMetricsRecorder,
+        labels: &[(&str, &str)],
+    ) -> Option<u64> {
+        recorder
+            .snapshot()
+            .by_name_and_labels(OBJECT_STORE_REQUEST_DURATION_SECONDS, labels)
+            .map(|metric| match &metric.value {
+                MetricValue::Histogram { count, .. } => *count,
+                other => panic!("expected histogram metric, got {other:?}"),
+            })
+    }
+
+    fn lookup_object_store_op_request_count(
+        recorder: &DefaultMetricsRecorder,
+        component: &'static str,
+        store_type: &'static str,
+        op: &'static str,
+    ) -> i64 {
+        let apis = match op {
+            "get" => &["get", "get_range", "get_ranges", "head"][..],
+            "put" => &[
+                "put",
+                "multipart_init",
+                "multipart_part",
+                "multipart_complete",
+            ][..],
+            "delete" => &["delete"][..],
+            _ => panic!("unexpected op {op}"),
+        };
+
+        apis.iter()
+            .map(|api| {
+                lookup_metric_with_labels(
+                    recorder,
+                    OBJECT_STORE_REQUEST_COUNT,
+                    &object_store_labels(component, store_type, op, api),
+                )
+                .unwrap_or(0)
+            })
+            .sum()
+    }
+
+    fn lookup_object_store_op_histogram_count(
+        recorder: &DefaultMetricsRecorder,
+        component: &'static str,
+        store_type: &'static str,
+        op: &'static str,
+    ) -> u64 {
+        let apis = match op {
+            "get" => &["get", "get_range", "get_ranges", "head"][..],
+            "put" => &[
+                "put",
+                "multipart_init",
+                "multipart_part",
+                "multipart_complete",
+            ][..],
+            "delete" => &["delete"][..],
+            _ => panic!("unexpected op {op}"),
+        };
+
+        apis.iter()
+            .map(|api| {
+                lookup_object_store_histogram_count(
+                    recorder,
+                    &object_store_labels(component, store_type, op, api),
+                )
+                .unwrap_or(0)
+            })
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn test_put_get_delete() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let kv_store = Db::builder("/tmp/test_kv_store", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        let key = b"test_key";
+        let value = b"test_value";
+        kv_store.put(key, value).await.unwrap();
+        kv_store.flush().await.unwrap();
+
+        assert_eq!(
+            kv_store.get(key).await.unwrap(),
+            Some(Bytes::from_static(value))
+        );
+        kv_store.delete(key).await.unwrap();
+        assert_eq!(None, kv_store.get(key).await.unwrap());
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_delete_if() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let kv_store = Db::builder("/tmp/test_delete_if", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+        let value = Some(Bytes::from_static(b"value"));
+
+        // Absent key: only a `None` expectation matches, and nothing is written
+        assert!(kv_store.delete_if(b"key", None).await.unwrap());
+        assert!(!kv_store.delete_if(b"key", value.clone()).await.unwrap());
+
+        // Mismatched value leaves the key in place, from the memtable or an SST
+        kv_store.put(b"key", b"value").await.unwrap();
+        assert!(!kv_store.delete_if(b"key", None).await.unwrap());
+        kv_store.flush().await.unwrap();
+        assert!(!kv_store
+            .delete_if(b"key", Some(Bytes::from_static(b"other")))
+            .await
+            .unwrap());
+        assert_eq!(kv_store.get(b"key").await.unwrap(), value);
+
+        // Matching value deletes the key
+        assert!(kv_store.delete_if(b"key", value.clone()).await.unwrap());
+        assert_eq!(kv_store.get(b"key").await.unwrap(), None);
+
+        // An existing tombstone counts as absent
+        assert!(!kv_store.delete_if(b"key", value).await.unwrap());
+        assert!(kv_store.delete_if(b"key", None).await.unwrap());
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_delete_if_treats_expired_value_as_absent() {
+        let clock = Arc::new(MockSystemClock::new());
+        let mut options = test_db_options(0, 1024, None);
+        options.flush_interval = None;
+        let kv_store = Db::builder("/tmp/test_delete_if_expired", Arc::new(InMemory::new()))
+            .with_settings(options)
+            .with_system_clock(clock.clone())
+            .build()
+            .await
+            .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        kv_store
+            .put_with_options(
+                b"key",
+                b"value",
+                &PutOptions {
+                    ttl: Ttl::ExpireAfter(10),
+                },
+                &write_options,
+            )
+            .await
+            .unwrap();
+        clock.set(10);
+
+        let value = Some(Bytes::from_static(b"value"));
+        assert!(!kv_store
+            .delete_if_with_options(b"key", value, &write_options)
+            .await
+            .unwrap());
+        assert!(kv_store
+            .delete_if_with_options(b"key", None, &write_options)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_put_if_absent() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let kv_store = Db::builder("/tmp/test_put_if_absent", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        assert!(kv_store.put_if_absent(b"key", b"first").await.unwrap());
+        assert!(!kv_store.put_if_absent(b"key", b"second").await.unwrap());
+
+        // a value in an SST is present too
+        kv_store.flush().await.unwrap();
+        assert!(!kv_store.put_if_absent(b"key", b"third").await.unwrap());
+        assert_eq!(
+            kv_store.get(b"key").await.unwrap(),
+            Some(Bytes::from_static(b"first"))
+        );
+
+        // a tombstone counts as absent
+        kv_store.delete(b"key").await.unwrap();
+        assert!(kv_store.put_if_absent(b"key", b"fourth").await.unwrap());
+        assert_eq!(
+            kv_store.get(b"key").await.unwrap(),
+            Some(Bytes::from_static(b"fourth"))
+        );
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_put_if_absent_writes_over_expired_value() {
+        let clock = Arc::new(MockSystemClock::new());
+        let mut options = test_db_options(0, 1024, None);
+        options.flush_interval = None;
+        let kv_store = Db::builder("/tmp/test_put_if_absent_expired", Arc::new(InMemory::new()))
+            .with_settings(options)
+            .with_system_clock(clock.clone())
+            .build()
+            .await
+            .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        let ttl = PutOptions {
+            ttl: Ttl::ExpireAfter(10),
+        };
+        assert!(kv_store
+            .put_if_absent_with_options(b"key", b"first", &ttl, &write_options)
+            .await
+            .unwrap());
+        clock.set(9);
+        assert!(!kv_store
+            .put_if_absent_with_options(b"key", b"second", &ttl, &write_options)
+            .await
+            .unwrap());
+
+        clock.set(10);
+        assert!(kv_store
+            .put_if_absent_with_options(b"key", b"third", &ttl, &write_options)
+            .await
+            .unwrap());
+        assert_eq!(
+            kv_store.get(b"key").await.unwrap(),
+            Some(Bytes::from_static(b"third"))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_put_if_absent_writes_once() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let kv_store = Arc::new(
+            Db::builder("/tmp/test_concurrent_put_if_absent", object_store)
+                .with_settings(test_db_options(0, 1024, None))
+                .build()
+                .await
+                .unwrap(),
+        );
+
+        let tasks: Vec<_> = (0..8u8)
+            .map(|i| {
+                let kv_store = kv_store.clone();
+                tokio::spawn(async move { kv_store.put_if_absent(b"key", [i]).await })
+            })
+            .collect();
+        let mut written = Vec::new();
+        for (i, task) in tasks.into_iter().enumerate() {
+            if task.await.unwrap().unwrap() {
+                written.push(i as u8);
+            }
+        }
+
+        assert_eq!(written.len(), 1);
+        assert_eq!(
+            kv_store.get(b"key").await.unwrap(),
+            Some(Bytes::copy_from_slice(&written))
+        );
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_multi_reads_keys_from_memtable_and_ssts() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let kv_store = Db::builder("/tmp/test_get_multi", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+        for key in [b"a", b"b", b"c", b"d"] {
+            kv_store.put(key, key).await.unwrap();
+        }
+        kv_store
+            .flush_with_options(FlushOptions {
+                flush_type: FlushType::MemTable,
+            })
+            .await
+            .unwrap();
+        kv_store.put(b"b", b"new b").await.unwrap();
+        kv_store.delete(b"c").await.unwrap();
+
+        let values = kv_store
+            .get_multi(&[b"d".as_slice(), b"c", b"missing", b"b", b"a", b"d", b"0"])
+            .await
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                Some(Bytes::from_static(b"d")),
+                None,
+                None,
+                Some(Bytes::from_static(b"new b")),
+                Some(Bytes::from_static(b"a")),
+                Some(Bytes::from_static(b"d")),
+                None,
+            ]
+        );
+        assert!(kv_store.get_multi::<&[u8]>(&[]).await.unwrap().is_empty());
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_put_if_checks_value_and_version() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let kv_store = Db::builder("/tmp/test_put_if", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        // a missing key only meets `Absent`
+        let equals_v1 = PutCondition::ValueEquals(Bytes::from_static(b"v1"));
+        assert!(!kv_store
+            .put_if(b"key", b"v1", equals_v1.clone())
+            .await
+            .unwrap());
+        assert!(!kv_store
+            .put_if(b"key", b"v1", PutCondition::VersionEquals(0))
+            .await
+            .unwrap());
+        assert!(kv_store
+            .put_if(b"key", b"v1", PutCondition::Absent)
+            .await
+            .unwrap());
+
+        assert!(!kv_store
+            .put_if(
+                b"key",
+                b"v2",
+                PutCondition::ValueEquals(Bytes::from_static(b"v0"))
+            )
+            .await
+            .unwrap());
+        assert!(kv_store.put_if(b"key", b"v2", equals_v1).await.unwrap());
+
+        let seq = kv_store.get_key_value(b"key").await.unwrap().unwrap().seq;
+        assert!(!kv_store
+            .put_if(b"key", b"v3", PutCondition::VersionEquals(seq - 1))
+            .await
+            .unwrap());
+        assert!(kv_store
+            .put_if(b"key", b"v3", PutCondition::VersionEquals(seq))
+            .await
+            .unwrap());
+        assert!(!kv_store
+            .put_if(b"key", b"v4", PutCondition::VersionEquals(seq))
+            .await
+            .unwrap());
+        assert_eq!(
+            kv_store.get(b"key").await.unwrap(),
+            Some(Bytes::from_static(b"v3"))
+        );
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reads_hide_expired_values() {
+        let clock = Arc::new(MockSystemClock::new());
+        let mut options = test_db_options(0, 1024 * 1024, None);
+        options.flush_interval = None;
+        let db = Db::builder("/tmp/test_reads_hide_expired", Arc::new(InMemory::new()))
+            .with_settings(options)
+            .with_system_clock(clock.clone())
+            .build()
+            .await
+            .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        let ttl = PutOptions {
+            ttl: Ttl::ExpireAfter(10),
+        };
+        db.put_with_options(b"a", b"a1", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        db.put_with_options(b"a", b"a2", &ttl, &write_options)
+            .await
+            .unwrap();
+        db.put_with_options(b"b", b"b1", &ttl, &write_options)
+            .await
+            .unwrap();
+        db.put_with_options(b"c", b"c1", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+
+        clock.set(9);
+        assert_eq!(db.get(b"a").await.unwrap(), Some(Bytes::from_static(b"a2")));
+        assert_eq!(db.get(b"b").await.unwrap(), Some(Bytes::from_static(b"b1")));
+
+        // a value expires at its expiry time, and hides the older versions of
+        // its key like a delete would
+        clock.set(10);
+        assert_eq!(db.get(b"a").await.unwrap(), None);
+        assert_eq!(db.get(b"b").await.unwrap(), None);
+        assert_eq!(
+            db.get_multi(&[b"a", b"b", b"c"]).await.unwrap(),
+            vec![None, None, Some(Bytes::from_static(b"c1"))]
+        );
+        let iter = db.scan::<&[u8], _>(..).await.unwrap();
+        assert_eq!(
+            collect_scan(iter).await,
+            vec![(Bytes::from_static(b"c"), Bytes::from_static(b"c1"))]
+        );
+        let snapshot = db.snapshot().await.unwrap();
+        assert_eq!(snapshot.get(b"a").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_default_ttl_expires_values_for_reads() {
+        let clock = Arc::new(MockSystemClock::new());
+        let mut options = test_db_options_with_ttl(0, 1024 * 1024, None, Some(50));
+        options.flush_interval = None;
+        let db = Db::builder("/tmp/test_default_ttl_reads", Arc::new(InMemory::new()))
+            .with_settings(options)
+            .with_system_clock(clock.clone())
+            .build()
+            .await
+            .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        db.put_with_options(b"a", b"a1", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        db.put_with_options(
+            b"b",
+            b"b1",
+            &PutOptions { ttl: Ttl::NoExpiry },
+            &write_options,
+        )
+        .await
+        .unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+
+        clock.set(49);
+        assert_eq!(db.get(b"a").await.unwrap(), Some(Bytes::from_static(b"a1")));
+        clock.set(50);
+        assert_eq!(db.get(b"a").await.unwrap(), None);
+        assert_eq!(db.get(b"b").await.unwrap(), Some(Bytes::from_static(b"b1")));
+    }
+
+    #[tokio::test]
+    async fn test_get_with_max_cache_staleness_sees_fresh_writes() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let clock = Arc::new(MockSystemClock::new());
+        let mut options = test_db_options(0, 1024, None);
+        options.flush_interval = None;
+        let kv_store = Db::builder("/tmp/test_get_with_max_cache_staleness", object_store)
+            .with_settings(options)
+            .with_system_clock(clock.clone())
+            .build()
+            .await
+            .unwrap();
+        let cached = ReadOptions::default().with_max_cache_staleness(Some(Duration::from_secs(60)));
+        let fresh = ReadOptions::default().with_max_cache_staleness(Some(Duration::ZERO));
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            kv_store.get_with_options(b"key", &cached).await.unwrap(),
+            None
+        );
+        kv_store
+            .put_with_options(b"key", b"value1", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        assert_eq!(
+            kv_store.get_with_options(b"key", &fresh).await.unwrap(),
+            Some(Bytes::from_static(b"value1"))
+        );
+        assert_eq!(
+            kv_store.get_with_options(b"key", &cached).await.unwrap(),
+            Some(Bytes::from_static(b"value1"))
+        );
+
+        // a write invalidates the cached read, whatever staleness is accepted
+        kv_store
+            .put_with_options(b"key", b"value2", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        assert_eq!(
+            kv_store.get_with_options(b"key", &cached).await.unwrap(),
+            Some(Bytes::from_static(b"value2"))
+        );
+        kv_store
+            .delete_with_options(b"key", &write_options)
+            .await
+            .unwrap();
+        clock.set(1);
+        assert_eq!(
+            kv_store.get_with_options(b"key", &fresh).await.unwrap(),
+            None
+        );
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pinned_keys_follow_writes_and_deletes() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut options = test_db_options(0, 1024, None);
+        options.flush_interval = None;
+        let kv_store = Db::builder("/tmp/test_pinned_keys", object_store)
+            .with_settings(options)
+            .with_system_clock(Arc::new(MockSystemClock::new()))
+            .build()
+            .await
+            .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        let key = Bytes::from_static(b"config");
+        let pinned = |kv_store: &Db| match kv_store
+            .inner
+            .read_cache
+            .get_pinned(&key, &ReadOptions::default())
+        {
+            PinnedLookup::Hit(value) => Some(value.map(|kv| kv.value)),
+            PinnedLookup::Miss | PinnedLookup::NotPinned => None,
+        };
+
+        kv_store
+            .put_with_options(&key, b"v1", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        kv_store.pin_keys(std::slice::from_ref(&key)).await.unwrap();
+        assert_eq!(pinned(&kv_store), Some(Some(Bytes::from_static(b"v1"))));
+
+        // writes update the pinned entry in place
+        kv_store
+            .put_with_options(&key, b"v2", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        assert_eq!(pinned(&kv_store), Some(Some(Bytes::from_static(b"v2"))));
+        assert_eq!(
+            kv_store.get(&key).await.unwrap(),
+            Some(Bytes::from_static(b"v2"))
+        );
+
+        // a delete makes the pinned key read as not found
+        kv_store
+            .delete_with_options(&key, &write_options)
+            .await
+            .unwrap();
+        assert_eq!(pinned(&kv_store), Some(None));
+        assert_eq!(kv_store.get(&key).await.unwrap(), None);
+
+        kv_store.unpin_keys(std::slice::from_ref(&key));
+        assert_eq!(pinned(&kv_store), None);
+        kv_store
+            .put_with_options(&key, b"v3", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        assert_eq!(
+            kv_store.get(&key).await.unwrap(),
+            Some(Bytes::from_static(b"v3"))
+        );
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_append_only_memtable_rejects_out_of_order_writes() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut options = test_db_options(0, 1024, None);
+        options.memtable_type = crate::config::MemtableType::AppendOnly {
+            on_out_of_order: crate::config::OutOfOrderWritePolicy::Reject,
+        };
+        let kv_store = Db::builder("/tmp/test_append_only_memtable", object_store)
+            .with_settings(options)
+            .build()
+            .await
+            .unwrap();
+
+        kv_store.put(b"key2", b"value2").await.unwrap();
+        let err = kv_store.put(b"key1", b"value1").await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Invalid);
+        let err = kv_store.put(b"key2", b"value2b").await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Invalid);
+        kv_store.put(b"key3", b"value3").await.unwrap();
+
+        assert_eq!(kv_store.get(b"key1").await.unwrap(), None);
+        assert_eq!(
+            kv_store.get(b"key2").await.unwrap(),
+            Some(Bytes::from_static(b"value2"))
+        );
+        let mut iter = kv_store.scan::<&[u8], _>(..).await.unwrap();
+        let mut keys = Vec::new();
+        while let Some(kv) = iter.next().await.unwrap() {
+            keys.push(kv.key);
+        }
+        assert_eq!(
+            keys,
+            vec![Bytes::from_static(b"key2"), b"key3".as_ref().into()]
+        );
+
+        // A new memtable starts empty, so keys may restart from the beginning
+        kv_store
+            .flush_with_options(FlushOptions {
+                flush_type: FlushType::MemTable,
+            })
+            .await
+            .unwrap();
+        kv_store.put(b"key1", b"value1").await.unwrap();
+        assert_eq!(
+            kv_store.get(b"key1").await.unwrap(),
+            Some(Bytes::from_static(b"value1"))
+        );
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_append_only_memtable_falls_back_to_skip_map_on_out_of_order_writes() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut options = test_db_options(0, 1024, None);
+        options.memtable_type = crate::config::MemtableType::AppendOnly {
+            on_out_of_order: crate::config::OutOfOrderWritePolicy::Fallback,
+        };
+        let kv_store = Db::builder("/tmp/test_append_only_memtable_fallback", object_store)
+            .with_settings(options)
+            .build()
+            .await
+            .unwrap();
+        let takes_key0 = |kv_store: &Db| {
+            kv_store
+                .inner
+                .state
+                .read()
+                .memtable()
+                .table()
+                .can_append(&[RowEntry::new_value(b"key0", b"value0", u64::MAX)])
+        };
+
+        kv_store.put(b"key2", b"value2").await.unwrap();
+        assert!(!takes_key0(&kv_store));
+        kv_store.put(b"key1", b"value1").await.unwrap();
+        kv_store.put(b"key2", b"value2b").await.unwrap();
+        assert!(takes_key0(&kv_store));
+        assert_eq!(
+            kv_store.get(b"key1").await.unwrap(),
+            Some(Bytes::from_static(b"value1"))
+        );
+        assert_eq!(
+            kv_store.get(b"key2").await.unwrap(),
+            Some(Bytes::from_static(b"value2b"))
+        );
+
+        // the next memtable is append-only again
+        kv_store
+            .flush_with_options(FlushOptions {
+                flush_type: FlushType::MemTable,
+            })
+            .await
+            .unwrap();
+        kv_store.put(b"key3", b"value3").await.unwrap();
+        assert!(!takes_key0(&kv_store));
+        assert_eq!(
+            kv_store.get(b"key2").await.unwrap(),
+            Some(Bytes::from_static(b"value2b"))
+        );
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_manifest_returns_current_versioned_manifest() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_manifest_accessor", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        db.put(b"test_key", b"test_value").await.unwrap();
+
+        let manifest = db.manifest();
+        let expected: VersionedManifest = db.inner.state.read().state().manifest.clone().into();
+        assert_eq!(manifest, expected);
+
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_coarse_size_estimation_via_manifest() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = "/tmp/test_coarse_size_estimation";
+        let should_compact = Arc::new(AtomicBool::new(false));
+        let should_compact_clone = should_compact.clone();
+        let compaction_scheduler = Arc::new(OnDemandCompactionSchedulerSupplier::new(Arc::new(
+            move |_state| should_compact_clone.swap(false, Ordering::SeqCst),
+        )));
+        let db = Db::builder(path, object_store.clone())
+            .with_settings(test_db_options(0, 1024, None))
+            .with_compactor_builder(
+                CompactorBuilder::new(path, object_store.clone())
+                    .with_scheduler_supplier(compaction_scheduler),
+            )
+            .build()
+            .await
+            .unwrap();
+        let db = Arc::new(db);
+
+        // Write keys in the range k0000..k0099 and flush to L0
+        for i in 0..100u32 {
+            let key = format!("k{:04}", i);
+            db.put(key.as_bytes(), &[0u8; 64]).await.unwrap();
+        }
+        db.flush().await.unwrap();
+
+        // estimate_size on L0 views should return non-zero
+        let manifest = db.manifest();
+        assert!(!manifest.manifest.core.tree.l0.is_empty());
+        for view in &manifest.manifest.core.tree.l0 {
+            assert!(view.estimate_size() > 0);
+        }
+
+        // Trigger compaction and wait for sorted runs
+        should_compact.store(true, Ordering::SeqCst);
+        let db_poll = db.clone();
+        tokio::time::timeout(Duration::from_secs(10), async move {
+            loop {
+                {
+                    let state = db_poll.inner.state.read();
+                    if !state.state().core().tree.compacted.is_empty() {
+                        return;
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let manifest = db.manifest();
+        assert!(!manifest.manifest.core.tree.compacted.is_empty());
+
+        for sr in &manifest.manifest.core.tree.compacted {
+            // A range covering all keys returns results
+            let covering = sr
+                .tables_covering_range(Bytes::from_static(b"k0000")..Bytes::from_static(b"k0100"));
+            assert!(!covering.is_empty());
+            for view in &covering {
+                assert!(view.estimate_size() > 0);
+            }
+
+            // A range before all keys returns nothing
+            let outside = sr
+                .tables_covering_range(Bytes::from_static(b"a0000")..Bytes::from_static(b"a9999"));
+            assert!(outside.is_empty());
+        }
+
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix_returns_matching_keys() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let kv_store = Db::builder("/tmp/test_scan_prefix", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        kv_store.put(b"ab", b"v0").await.unwrap();
+        kv_store.put(b"aba", b"v1").await.unwrap();
+        kv_store.put(b"abb", b"v2").await.unwrap();
+        kv_store.put(b"ac", b"v3").await.unwrap();
+
+        let mut iter = kv_store.scan_prefix(b"ab").await.unwrap();
+        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"ab");
+        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"aba");
+        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"abb");
+        assert_eq!(iter.next().await.unwrap(), None);
+
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_descending_returns_records_in_reverse_order() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let kv_store = Db::builder("/tmp/test_scan_descending", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        kv_store.put(b"a", b"v0").await.unwrap();
+        kv_store.put(b"b", b"v1").await.unwrap();
+        kv_store.flush().await.unwrap();
+        kv_store.put(b"c", b"v2").await.unwrap();
+        kv_store.put(b"d", b"v3").await.unwrap();
+
+        let scan_options = ScanOptions::default().with_order(IterationOrder::Descending);
+        let mut iter = kv_store
+            .scan_with_options::<Vec<u8>, _>(.., &scan_options)
+            .await
+            .unwrap();
+        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"d");
+        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"c");
+        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"b");
+        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"a");
+        assert_eq!(iter.next().await.unwrap(), None);
+
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_descending_bounded_range() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let kv_store = Db::builder("/tmp/test_scan_descending_bounded", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        kv_store.put(b"a", b"v0").await.unwrap();
+        kv_store.put(b"b", b"v1").await.unwrap();
+        kv_store.put(b"c", b"v2").await.unwrap();
+        kv_store.put(b"d", b"v3").await.unwrap();
+        kv_store.put(b"e", b"v4").await.unwrap();
+
+        let scan_options = ScanOptions::default().with_order(IterationOrder::Descending);
+        let mut iter = kv_store
+            .scan_with_options::<Vec<u8>, _>(b"b".to_vec()..b"d".to_vec(), &scan_options)
+            .await
+            .unwrap();
+        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"c");
+        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"b");
+        assert_eq!(iter.next().await.unwrap(), None);
+
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_descending_skips_deleted_keys() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let kv_store = Db::builder("/tmp/test_scan_descending_deletes", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        kv_store.put(b"a", b"v0").await.unwrap();
+        kv_store.put(b"b", b"v1").await.unwrap();
+        kv_store.put(b"c", b"v2").await.unwrap();
+        kv_store.put(b"d", b"v3").await.unwrap();
+        kv_store.delete(b"b").await.unwrap();
+        kv_store.delete(b"d").await.unwrap();
+
+        let scan_options = ScanOptions::default().with_order(IterationOrder::Descending);
+        let mut iter = kv_store
+            .scan_with_options::<Vec<u8>, _>(.., &scan_options)
+            .await
+            .unwrap();
+        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"c");
+        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"a");
+        assert_eq!(iter.next().await.unwrap(), None);
+
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix_descending() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let kv_store = Db::builder("/tmp/test_scan_prefix_descending", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        kv_store.put(b"prefix/a", b"v0").await.unwrap();
+        kv_store.put(b"prefix/b", b"v1").await.unwrap();
+        kv_store.put(b"prefix/c", b"v2").await.unwrap();
+        kv_store.put(b"other/a", b"v3").await.unwrap();
+
+        let scan_options = ScanOptions::default().with_order(IterationOrder::Descending);
+        let mut iter = kv_store
+            .scan_prefix_with_options(b"prefix/", &scan_options)
+            .await
+            .unwrap();
+        assert_eq!(
+            iter.next().await.unwrap().unwrap().key.as_ref(),
+            b"prefix/c"
+        );
+        assert_eq!(
+            iter.next().await.unwrap().unwrap().key.as_ref(),
+            b"prefix/b"
+        );
+        assert_eq!(
+            iter.next().await.unwrap().unwrap().key.as_ref(),
+            b"prefix/a"
+        );
+        assert_eq!(iter.next().await.unwrap(), None);
+
+        kv_store.close().await.unwrap();
+    }
+
+    async fn scan_ranges_keys(
+        db: &Db,
+        ranges: Vec<std::ops::Range<&str>>,
+        order: IterationOrder,
+    ) -> Vec<Bytes> {
+        let options = ScanOptions::default().with_order(order);
+        let mut iter = db.scan_ranges_with_options(ranges, &options).await.unwrap();
+        let mut keys = Vec::new();
+        while let Some(kv) = iter.next().await.unwrap() {
+            keys.push(kv.key);
+        }
+        keys
+    }
+
+    #[tokio::test]
+    async fn test_scan_ranges_returns_sorted_union_of_ranges() {
+        let db = Db::builder("/tmp/test_scan_ranges", Arc::new(InMemory::new()))
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .build()
+            .await
+            .unwrap();
+        let keys: Vec<String> = (0..20).map(|i| format!("k{:02}", i)).collect();
+        // even keys are flushed to L0, odd keys stay in the memtable
+        for key in keys.iter().step_by(2) {
+            db.put(key, b"v").await.unwrap();
+        }
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        for key in keys.iter().skip(1).step_by(2) {
+            db.put(key, b"v").await.unwrap();
+        }
+
+        let ranges = vec![
+            "k12".."k15",
+            "k02".."k05",
+            "k04".."k07",
+            "k06".."k09",
+            "k15".."k16",
+            "k13".."k13",
+        ];
+        let mut expected: Vec<Bytes> = keys
+            .iter()
+            .filter(|key| ranges.iter().any(|range| range.contains(&key.as_str())))
+            .map(|key| Bytes::from(key.clone()))
+            .collect();
+        assert_eq!(
+            expected,
+            ["k02", "k03", "k04", "k05", "k06", "k07", "k08", "k12", "k13", "k14", "k15"]
+                .map(Bytes::from)
+        );
+        assert_eq!(
+            scan_ranges_keys(&db, ranges.clone(), IterationOrder::Ascending).await,
+            expected
+        );
+        expected.reverse();
+        assert_eq!(
+            scan_ranges_keys(&db, ranges, IterationOrder::Descending).await,
+            expected
+        );
+        assert!(scan_ranges_keys(&db, vec![], IterationOrder::Ascending)
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix_with_options_handles_unbounded_end() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let kv_store = Db::builder("/tmp/test_scan_prefix_unbounded", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        kv_store.put(&[0xff, 0xff], b"v0").await.unwrap();
+        kv_store.put(&[0xff, 0xff, 0x00], b"v1").await.unwrap();
+        kv_store.put(&[0xff, 0xff, 0x10], b"v2").await.unwrap();
+        kv_store.put(&[0xff, 0xff, 0xff], b"v4").await.unwrap();
+        kv_store.put(&[0xff, 0xfe], b"v3").await.unwrap();
+
+        let scan_options = ScanOptions {
+            cache_blocks: false,
+            ..ScanOptions::default()
+        };
+        let mut iter = kv_store
+            .scan_prefix_with_options(&[0xff, 0xff], &scan_options)
+            .await
+            .unwrap();
+        assert_eq!(
+            iter.next().await.unwrap().unwrap().key.as_ref(),
+            &[0xff, 0xff]
+        );
+        assert_eq!(
+            iter.next().await.unwrap().unwrap().key.as_ref(),
+            &[0xff, 0xff, 0x00]
+        );
+        assert_eq!(
+            iter.next().await.unwrap().unwrap().key.as_ref(),
+            &[0xff, 0xff, 0x10]
+        );
+        assert_eq!(
+            iter.next().await.unwrap().unwrap().key.as_ref(),
+            &[0xff, 0xff, 0xff]
+        );
+        assert_eq!(iter.next().await.unwrap(), None);
+
+        kv_store.close().await.unwrap();
+    }
+
+    fn assert_value(entry: &crate::types::RowEntry, expected: &[u8]) {
+        match &entry.value {
+            crate::types::ValueDeletable::Value(v) => assert_eq!(v.as_ref(), expected),
+            other => panic!("expected Value({expected:?}), got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix_by_recency_returns_matching_keys_from_memtable() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_recency_memtable", object_store)
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        db.put(b"px:a", b"v0").await.unwrap();
+        db.put(b"px:b", b"v1").await.unwrap();
+        db.put(b"px:c", b"v2").await.unwrap();
+        db.put(b"qq:x", b"vx").await.unwrap();
+
+        let mut iter = db.scan_prefix_by_recency(b"px:").await.unwrap();
+        let e1 = iter.next_entry().await.unwrap().unwrap();
+        let e2 = iter.next_entry().await.unwrap().unwrap();
+        let e3 = iter.next_entry().await.unwrap().unwrap();
+        assert_eq!(e1.key.as_ref(), b"px:a");
+        assert_eq!(e2.key.as_ref(), b"px:b");
+        assert_eq!(e3.key.as_ref(), b"px:c");
+        assert!(iter.next_entry().await.unwrap().is_none());
+
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix_by_recency_no_match_returns_none() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_recency_no_match", object_store)
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        db.put(b"aa", b"v0").await.unwrap();
+        db.put(b"ab", b"v1").await.unwrap();
+
+        let mut iter = db.scan_prefix_by_recency(b"zz").await.unwrap();
+        assert!(iter.next_entry().await.unwrap().is_none());
+
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_view_ignores_later_writes_and_tables() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_read_view", object_store)
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .build()
+            .await
+            .unwrap();
+        let read_options = ReadOptions::default();
+        let scan_options = ScanOptions::default();
+
+        db.put(b"a", b"a1").await.unwrap();
+        db.put(b"b", b"b1").await.unwrap();
+        let view = db.read_view().unwrap();
+
+        // overwrite, delete and add keys, then move them into a new L0 SST
+        // and a fresh memtable, none of which the view captured
+        db.put(b"a", b"a2").await.unwrap();
+        db.delete(b"b").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        db.put(b"c", b"c1").await.unwrap();
+
+        for (key, view, expected) in [
+            (b"a", Some(&view), Some(Bytes::from_static(b"a1"))),
+            (b"b", Some(&view), Some(Bytes::from_static(b"b1"))),
+            (b"c", Some(&view), None),
+            (b"a", None, Some(Bytes::from_static(b"a2"))),
+            (b"b", None, None),
+        ] {
+            assert_eq!(
+                db.get_with_view(key, &read_options, view).await.unwrap(),
+                expected
+            );
+        }
+
+        let mut iter = db
+            .scan_with_view::<&[u8], _>(.., &scan_options, Some(&view))
+            .await
+            .unwrap();
+        let mut keys = Vec::new();
+        while let Some(kv) = iter.next().await.unwrap() {
+            keys.push((kv.key, kv.value));
+        }
+        assert_eq!(
+            keys,
+            vec![
+                (Bytes::from_static(b"a"), Bytes::from_static(b"a1")),
+                (Bytes::from_static(b"b"), Bytes::from_static(b"b1")),
+            ]
+        );
+
+        let mut iter = db
+            .scan_with_view::<&[u8], _>(.., &scan_options, None)
+            .await
+            .unwrap();
+        let mut keys = Vec::new();
+        while let Some(kv) = iter.next().await.unwrap() {
+            keys.push(kv.key);
+        }
+        assert_eq!(
+            keys,
+            vec![Bytes::from_static(b"a"), Bytes::from_static(b"c")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_all_versions_across_memtable_and_l0() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_get_all_versions", object_store)
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        // v1 lands in L0, the remaining versions stay in the memtable.
+        let seq1 = db.put(b"key", b"v1").await.unwrap().seqnum();
+        db.put(b"other", b"x").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        let seq2 = db.put(b"key", b"v2").await.unwrap().seqnum();
+        let seq3 = db.delete(b"key").await.unwrap().seqnum();
+        let seq4 = db.put(b"key", b"v3").await.unwrap().seqnum();
+
+        let versions = db.get_all_versions(b"key", ..).await.unwrap();
+        let summary: Vec<(u64, Option<Bytes>)> = versions
+            .iter()
+            .map(|entry| (entry.seq, entry.value.as_bytes()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (seq4, Some(Bytes::from_static(b"v3"))),
+                (seq3, None),
+                (seq2, Some(Bytes::from_static(b"v2"))),
+                (seq1, Some(Bytes::from_static(b"v1"))),
+            ]
+        );
+        assert!(versions.iter().all(|entry| entry.key.as_ref() == b"key"));
+
+        let seqs: Vec<u64> = db
+            .get_all_versions(b"key", seq2..=seq3)
+            .await
+            .unwrap()
+            .iter()
+            .map(|entry| entry.seq)
+            .collect();
+        assert_eq!(seqs, vec![seq3, seq2]);
+        assert!(db
+            .get_all_versions(b"missing", ..)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_memtable_tombstones_spans_mutable_and_immutable_memtables() {
+        let fp_registry = Arc::new(FailPointRegistry::new());
+        // block L0 uploads so that the frozen memtable stays in memory
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "pause").unwrap();
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_memtable_tombstones", object_store)
+            .with_settings(test_db_options(0, 128, None))
+            .with_fp_registry(fp_registry.clone())
+            .build()
+            .await
+            .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+
+        let seq1 = db
+            .delete_with_options(b"b", &write_options)
+            .await
+            .unwrap()
+            .seqnum();
+        // larger than l0_sst_size_bytes, so it freezes the memtable
+        db.put_with_options(b"big", [b'v'; 256], &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        let seq2 = db
+            .delete_with_options(b"a", &write_options)
+            .await
+            .unwrap()
+            .seqnum();
+        let seq3 = db
+            .delete_with_options(b"b", &write_options)
+            .await
+            .unwrap()
+            .seqnum();
+        db.put_with_options(b"c", b"value", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        let imm_memtables = db.inner.state.read().state().imm_memtable.len();
+        let all = db.memtable_tombstones::<&[u8], _>(..).unwrap();
+        let from_b = db.memtable_tombstones(b"b".as_slice()..).unwrap();
+        let empty_range = db
+            .memtable_tombstones(b"b".as_slice()..b"b".as_slice())
+            .unwrap();
+        let reversed_range = db.memtable_tombstones(b"b".as_slice()..b"a".as_slice());
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "off").unwrap();
+
+        assert!(imm_memtables > 0);
+        assert_eq!(
+            reversed_range.unwrap_err().kind(),
+            crate::ErrorKind::Invalid
+        );
+        let summary = |tombstones: Vec<RowEntry>| -> Vec<(Bytes, u64)> {
+            assert!(tombstones.iter().all(|entry| entry.value.is_tombstone()));
+            tombstones
+                .into_iter()
+                .map(|entry| (entry.key, entry.seq))
+                .collect()
+        };
+        assert_eq!(
+            summary(all),
+            vec![
+                (Bytes::from_static(b"a"), seq2),
+                (Bytes::from_static(b"b"), seq3),
+                (Bytes::from_static(b"b"), seq1),
+            ]
+        );
+        assert_eq!(
+            summary(from_b),
+            vec![
+                (Bytes::from_static(b"b"), seq3),
+                (Bytes::from_static(b"b"), seq1),
+            ]
+        );
+        assert!(empty_range.is_empty());
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix_by_recency_emits_both_versions_across_sources() {
+        // No dedup: a key present in both memtable (newer) and L0 (older)
+        // appears twice, newer first.
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_recency_no_dedup", object_store)
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        db.put(b"px:a", b"old").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+
+        db.put(b"px:a", b"new").await.unwrap();
+
+        let mut iter = db.scan_prefix_by_recency(b"px:").await.unwrap();
+        let e1 = iter.next_entry().await.unwrap().unwrap();
+        assert_eq!(e1.key.as_ref(), b"px:a");
+        assert_value(&e1, b"new");
+        let e2 = iter.next_entry().await.unwrap().unwrap();
+        assert_eq!(e2.key.as_ref(), b"px:a");
+        assert_value(&e2, b"old");
+        assert!(e1.seq > e2.seq);
+        assert!(iter.next_entry().await.unwrap().is_none());
+
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix_by_recency_emits_tombstones() {
+        // Tombstones are surfaced as raw entries; the caller decides what
+        // they mean.
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_recency_tombstones", object_store)
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        db.put(b"px:a", b"va").await.unwrap();
+        db.put(b"px:b", b"vb").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+
+        db.delete(b"px:a").await.unwrap();
+
+        let mut iter = db.scan_prefix_by_recency(b"px:").await.unwrap();
+        // Memtable first: tombstone for px:a.
+        let e1 = iter.next_entry().await.unwrap().unwrap();
+        assert_eq!(e1.key.as_ref(), b"px:a");
+        assert!(e1.value.is_tombstone());
+        // L0 second: original values, ascending.
+        let e2 = iter.next_entry().await.unwrap().unwrap();
+        assert_eq!(e2.key.as_ref(), b"px:a");
+        assert_value(&e2, b"va");
+        let e3 = iter.next_entry().await.unwrap().unwrap();
+        assert_eq!(e3.key.as_ref(), b"px:b");
+        assert_value(&e3, b"vb");
+        // Tombstone is from the freshest source so its seq dominates the
+        // earlier put of px:a.
+        assert!(e1.seq > e2.seq);
+        assert!(iter.next_entry().await.unwrap().is_none());
+
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix_by_recency_walks_memtable_then_l0() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_recency_multi_source", object_store)
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        // Older L0 SST: px:b (older value), px:c.
+        db.put(b"px:b", b"old_b").await.unwrap();
+        db.put(b"px:c", b"vc").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+
+        // Active memtable: px:a, px:b (newer value).
+        db.put(b"px:a", b"va").await.unwrap();
+        db.put(b"px:b", b"new_b").await.unwrap();
+
+        let mut iter = db.scan_prefix_by_recency(b"px:").await.unwrap();
+        // Memtable drained first, ascending within source.
+        let e1 = iter.next_entry().await.unwrap().unwrap();
+        assert_eq!(e1.key.as_ref(), b"px:a");
+        assert_value(&e1, b"va");
+        let e2 = iter.next_entry().await.unwrap().unwrap();
+        assert_eq!(e2.key.as_ref(), b"px:b");
+        assert_value(&e2, b"new_b");
+        // L0 next: px:b (older) then px:c. No dedup.
+        let e3 = iter.next_entry().await.unwrap().unwrap();
+        assert_eq!(e3.key.as_ref(), b"px:b");
+        assert_value(&e3, b"old_b");
+        let e4 = iter.next_entry().await.unwrap().unwrap();
+        assert_eq!(e4.key.as_ref(), b"px:c");
+        assert_value(&e4, b"vc");
+        assert!(iter.next_entry().await.unwrap().is_none());
+
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix_by_recency_active_memtable_avoids_main_object_store_gets() {
+        // When the prefix is satisfied entirely by the active memtable, the
+        // recency scan should not issue any GETs against the main object
+        // store (it shouldn't even open an L0/SR iterator).
+        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_recency_no_main_gets", object_store)
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .with_metrics_recorder(metrics_recorder.clone())
+            .build()
+            .await
+            .unwrap();
+
+        // Older data in L0. Different prefix; we want them to be present
+        // but never visited.
+        db.put(b"qq:a", b"vqa").await.unwrap();
+        db.put(b"qq:b", b"vqb").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+
+        // Active-memtable-only prefix.
+        db.put(b"px:a", b"va").await.unwrap();
+        db.put(b"px:b", b"vb").await.unwrap();
+
+        let gets_before =
+            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "get");
+
+        let mut iter = db.scan_prefix_by_recency(b"px:").await.unwrap();
+        let e1 = iter.next_entry().await.unwrap().unwrap();
+        let e2 = iter.next_entry().await.unwrap().unwrap();
+        assert_eq!(e1.key.as_ref(), b"px:a");
+        assert_eq!(e2.key.as_ref(), b"px:b");
+        // Caller stops here without driving the iterator into older sources.
+
+        let gets_after =
+            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "get");
+        assert_eq!(
+            gets_before, gets_after,
+            "scan_prefix_by_recency should not touch the main object store \
+             when the prefix is fully covered by the active memtable"
+        );
+
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix_by_recency_durability_remote_filters_unflushed() {
+        // With DurabilityLevel::Remote and the put issued without awaiting
+        // durability, only L0/SR-resident entries should be visible. The
+        // not-yet-flushed memtable write is filtered out by max_seq.
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_recency_remote_durability", object_store)
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        db.put(b"px:a", b"va_durable").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+
+        // Skip the WAL await. Without a follow-up flush, px:b lives only
+        // in the in-memory memtable.
+        db.put_with_options(
+            b"px:b",
+            b"vb_dirty",
+            &PutOptions::default(),
+            &WriteOptions {
+                await_durable: false,
+                seqnum: 0,
+                tenant: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let opts = ScanOptions {
+            durability_filter: Remote,
+            ..ScanOptions::default()
+        };
+        let mut iter = db
+            .scan_prefix_by_recency_with_options(b"px:", &opts)
+            .await
+            .unwrap();
+        let e = iter.next_entry().await.unwrap().unwrap();
+        assert_eq!(e.key.as_ref(), b"px:a");
+        assert_value(&e, b"va_durable");
+        assert!(iter.next_entry().await.unwrap().is_none());
+
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix_by_recency_walks_memtable_then_compacted_run() {
+        // Walks memtable -> L0 -> compacted sorted run, verifying that the
+        // sorted-run path is constructed and drained correctly when the
+        // recency walk reaches it. Same-key versions surface from each
+        // source in newest-first order with no dedup.
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = "/tmp/test_recency_compacted";
+        let should_compact = Arc::new(AtomicBool::new(false));
+        let should_compact_clone = should_compact.clone();
+        let scheduler = Arc::new(OnDemandCompactionSchedulerSupplier::new(Arc::new(
+            move |_state| should_compact_clone.swap(false, Ordering::SeqCst),
+        )));
+        let db = Db::builder(path, object_store.clone())
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .with_compactor_builder(
+                CompactorBuilder::new(path, object_store.clone())
+                    .with_scheduler_supplier(scheduler),
+            )
+            .build()
+            .await
+            .unwrap();
+        let db = Arc::new(db);
+
+        // Oldest write goes to a sorted run after compaction.
+        db.put(b"px:a", b"v_oldest").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+
+        // Trigger compaction and wait for it to land.
+        should_compact.store(true, Ordering::SeqCst);
+        let db_poll = db.clone();
+        tokio::time::timeout(Duration::from_secs(10), async move {
+            loop {
+                {
+                    let state = db_poll.inner.state.read();
+                    if !state.state().core().tree.compacted.is_empty() {
+                        return;
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // L0 layer (newer than the sorted run, older than the memtable).
+        db.put(b"px:a", b"v_l0").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+
+        // Active memtable (freshest).
+        db.put(b"px:a", b"v_memtable").await.unwrap();
+
+        let mut iter = db.scan_prefix_by_recency(b"px:").await.unwrap();
+        let e1 = iter.next_entry().await.unwrap().unwrap();
+        let e2 = iter.next_entry().await.unwrap().unwrap();
+        let e3 = iter.next_entry().await.unwrap().unwrap();
+        assert_eq!(e1.key.as_ref(), b"px:a");
+        assert_value(&e1, b"v_memtable");
+        assert_eq!(e2.key.as_ref(), b"px:a");
+        assert_value(&e2, b"v_l0");
+        assert_eq!(e3.key.as_ref(), b"px:a");
+        assert_value(&e3, b"v_oldest");
+        assert!(e1.seq > e2.seq);
+        assert!(e2.seq > e3.seq);
+        assert!(iter.next_entry().await.unwrap().is_none());
+
+        Arc::into_inner(db)
+            .expect("db Arc should be uniquely held")
+            .close()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix_by_recency_errors_on_multi_segment_prefix() {
+        use crate::manifest::{LsmTreeState, Segment};
+        use std::collections::VecDeque;
+
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_recency_multi_segment", object_store)
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        // Inject two non-nesting segments. Both prefixes share "hour=1"
+        // but neither is a prefix of the other, so the antichain holds.
+        // A scan with prefix b"hour=" overlaps both segments' intervals,
+        // which is the case we want to reject.
+        db.inner.state.write().modify(|m| {
+            let core = &mut m.state.manifest.value.core;
+            core.segment_extractor_name = Some("hour".into());
+            core.segments = vec![
+                Segment {
+                    prefix: Bytes::from_static(b"hour=12/"),
+                    tree: LsmTreeState {
+                        last_compacted_l0_sst_view_id: None,
+                        last_compacted_l0_sst_id: None,
+                        l0: VecDeque::new(),
+                        compacted: vec![],
+                    },
+                },
+                Segment {
+                    prefix: Bytes::from_static(b"hour=13/"),
+                    tree: LsmTreeState {
+                        last_compacted_l0_sst_view_id: None,
+                        last_compacted_l0_sst_id: None,
+                        l0: VecDeque::new(),
+                        compacted: vec![],
+                    },
+                },
+            ];
+        });
 
-    fn object_store_labels(
-        component: &'static str,
-        store_type: &'static str,
-        op: &'static str,
-        api: &'static str,
-    ) -> [(&'static str, &'static str); 4] {
-        [
-            ("component", component),
-            ("store_type", store_type),
-            ("op", op),
-            ("api", api),
-        ]
-    }
+        match db.scan_prefix_by_recency(b"hour=").await {
+            Err(e) => assert_eq!(e.kind(), crate::ErrorKind::Invalid),
+            Ok(_) => panic!("expected multi-segment error"),
+        }
 
-    fn lookup_object_store_histogram_count(
-        recorder: &DefaultMetricsRecorder,
-        labels: &[(&str, &str)],
-    ) -> Option<u64> {
-        recorder
-            .snapshot()
-            .by_name_and_labels(OBJECT_STORE_REQUEST_DURATION_SECONDS, labels)
-            .map(|metric| match &metric.value {
-                MetricValue::Histogram { count, .. } => *count,
-                other => panic!("expected histogram metric, got {other:?}"),
-            })
+        // Single-segment prefixes still work.
+        let mut iter = db.scan_prefix_by_recency(b"hour=12/").await.unwrap();
+        assert!(iter.next_entry().await.unwrap().is_none());
+
+        db.close().await.unwrap();
     }
 
-    fn lookup_object_store_op_request_count(
-        recorder: &DefaultMetricsRecorder,
-        component: &'static str,
-        store_type: &'static str,
-        op: &'static str,
-    ) -> i64 {
-        let apis = match op {
-            "get" => &["get", "get_range", "get_ranges", "head"][..],
-            "put" => &[
-                "put",
-                "multipart_init",
-                "multipart_part",
-                "multipart_complete",
-            ][..],
-            "delete" => &["delete"][..],
-            _ => panic!("unexpected op {op}"),
-        };
+    #[tokio::test]
+    async fn test_resolve_object_store_local_prefix_store_writes_to_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let prefix_path = temp_dir.path().join("prefix-store");
+        let url = format!("file://{}", prefix_path.display());
 
-        apis.iter()
-            .map(|api| {
-                lookup_metric_with_labels(
-                    recorder,
-                    OBJECT_STORE_REQUEST_COUNT,
-                    &object_store_labels(component, store_type, op, api),
-                )
-                .unwrap_or(0)
-            })
-            .sum()
+        let object_store = Db::resolve_object_store(&url).unwrap();
+        let location = object_store::path::Path::from("nested/file.txt");
+        let payload = Bytes::from_static(b"local prefix payload");
+
+        object_store
+            .put(&location, payload.clone().into())
+            .await
+            .unwrap();
+
+        let expected_path = prefix_path.join("nested").join("file.txt");
+        let stored = tokio::fs::read(&expected_path).await.unwrap();
+        assert_eq!(stored, payload.to_vec());
     }
 
-    fn lookup_object_store_op_histogram_count(
-        recorder: &DefaultMetricsRecorder,
-        component: &'static str,
-        store_type: &'static str,
-        op: &'static str,
-    ) -> u64 {
-        let apis = match op {
-            "get" => &["get", "get_range", "get_ranges", "head"][..],
-            "put" => &[
-                "put",
-                "multipart_init",
-                "multipart_part",
-                "multipart_complete",
-            ][..],
-            "delete" => &["delete"][..],
-            _ => panic!("unexpected op {op}"),
-        };
+    #[test]
+    fn test_get_after_put() {
+        let mut runner = new_proptest_runner(None);
+        let runtime = Runtime::new().unwrap();
 
-        apis.iter()
-            .map(|api| {
-                lookup_object_store_histogram_count(
-                    recorder,
-                    &object_store_labels(component, store_type, op, api),
-                )
-                .unwrap_or(0)
-            })
-            .sum()
+        let table = sample::table(runner.rng(), 1000, 10);
+        let db_options = test_db_options(0, 1024, None);
+        let db = runtime.block_on(build_database_from_table(&table, db_options, true));
+
+        runner
+            .run(
+                &(arbitrary::bytes(100), arbitrary::bytes(100)),
+                |(key, value)| {
+                    runtime.block_on(async {
+                        if !key.is_empty() {
+                            db.put_with_options(
+                                &key,
+                                &value,
+                                &PutOptions::default(),
+                                &WriteOptions {
+                                    await_durable: false,
+                                    ..Default::default()
+                                },
+                            )
+                            .await
+                            .unwrap();
+                            assert_eq!(
+                                Some(value),
+                                db.get_with_options(
+                                    &key,
+                                    &ReadOptions {
+                                        durability_filter: Memory,
+                                        dirty: false,
+                                        cache_blocks: true,
+                                        filter_context: None,
+                                        max_cache_staleness: None,
+                                    }
+                                )
+                                .await
+                                .unwrap()
+                            );
+                        }
+                    });
+                    Ok(())
+                },
+            )
+            .unwrap();
     }
 
     #[tokio::test]
-    async fn test_put_get_delete() {
+    async fn test_no_flush_interval() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db_options_no_flush_interval = {
+            let mut db_options = test_db_options(0, 1024, None);
+            db_options.flush_interval = None;
+            db_options
+        };
         let kv_store = Db::builder("/tmp/test_kv_store", object_store)
-            .with_settings(test_db_options(0, 1024, None))
+            .with_settings(db_options_no_flush_interval)
             .build()
             .await
             .unwrap();
-
         let key = b"test_key";
         let value = b"test_value";
-        kv_store.put(key, value).await.unwrap();
+
+        kv_store
+            .put_with_options(
+                key,
+                value,
+                &PutOptions::default(),
+                &WriteOptions {
+                    await_durable: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // a sanity check: the wal contains the most recent write
+        assert_ne!(kv_store.inner.wal_buffer.estimated_bytes().unwrap(), 0);
+
+        // and a flush() should clear it
         kv_store.flush().await.unwrap();
+        assert_eq!(kv_store.inner.wal_buffer.estimated_bytes().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_close_triggers_flush_when_open() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db_options_no_flush_interval = {
+            let mut db_options = test_db_options(0, 1024, None);
+            db_options.flush_interval = None;
+            db_options
+        };
+        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
+        let kv_store = Db::builder("/tmp/test_close_triggers_flush", object_store)
+            .with_settings(db_options_no_flush_interval)
+            .with_metrics_recorder(metrics_recorder.clone())
+            .build()
+            .await
+            .unwrap();
+
+        kv_store
+            .put_with_options(
+                b"test_key",
+                b"test_value",
+                &PutOptions::default(),
+                &WriteOptions {
+                    await_durable: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
 
+        // Sanity check: WAL has buffered entries before close.
+        assert_eq!(kv_store.inner.wal_buffer.buffered_wal_entries_count(), 1);
         assert_eq!(
-            kv_store.get(key).await.unwrap(),
-            Some(Bytes::from_static(value))
+            lookup_metric(&metrics_recorder, crate::db_stats::WAL_BUFFER_FLUSHES).unwrap(),
+            0
         );
-        kv_store.delete(key).await.unwrap();
-        assert_eq!(None, kv_store.get(key).await.unwrap());
+
         kv_store.close().await.unwrap();
+
+        // close() should trigger a flush when the db is open.
+        assert_eq!(kv_store.inner.wal_buffer.buffered_wal_entries_count(), 0);
+        assert_eq!(
+            lookup_metric(&metrics_recorder, crate::db_stats::WAL_BUFFER_FLUSHES).unwrap(),
+            1
+        );
     }
 
     #[tokio::test]
-    async fn test_manifest_returns_current_versioned_manifest() {
+    async fn test_close_twice_returns_closed_clean() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db = Db::builder("/tmp/test_manifest_accessor", object_store)
+        let db = Db::builder("/tmp/test_close_twice_returns_closed_clean", object_store)
             .with_settings(test_db_options(0, 1024, None))
             .build()
             .await
-            .unwrap();
-
-        db.put(b"test_key", b"test_value").await.unwrap();
-
-        let manifest = db.manifest();
-        let expected: VersionedManifest = db.inner.state.read().state().manifest.clone().into();
-        assert_eq!(manifest, expected);
+            .unwrap();
 
         db.close().await.unwrap();
+        let err = db.close().await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Closed(CloseReason::Clean));
     }
 
     #[tokio::test]
-    async fn test_coarse_size_estimation_via_manifest() {
+    async fn test_close_failed_state_does_not_flush() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let path = "/tmp/test_coarse_size_estimation";
-        let should_compact = Arc::new(AtomicBool::new(false));
-        let should_compact_clone = should_compact.clone();
-        let compaction_scheduler = Arc::new(OnDemandCompactionSchedulerSupplier::new(Arc::new(
-            move |_state| should_compact_clone.swap(false, Ordering::SeqCst),
-        )));
-        let db = Db::builder(path, object_store.clone())
-            .with_settings(test_db_options(0, 1024, None))
-            .with_compactor_builder(
-                CompactorBuilder::new(path, object_store.clone())
-                    .with_scheduler_supplier(compaction_scheduler),
-            )
+        let db_options_no_flush_interval = {
+            let mut db_options = test_db_options(0, 1024, None);
+            db_options.flush_interval = None;
+            db_options
+        };
+        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
+        let db = Db::builder("/tmp/test_close_failed_state_no_flush", object_store)
+            .with_settings(db_options_no_flush_interval)
+            .with_metrics_recorder(metrics_recorder.clone())
             .build()
             .await
             .unwrap();
-        let db = Arc::new(db);
-
-        // Write keys in the range k0000..k0099 and flush to L0
-        for i in 0..100u32 {
-            let key = format!("k{:04}", i);
-            db.put(key.as_bytes(), &[0u8; 64]).await.unwrap();
-        }
-        db.flush().await.unwrap();
-
-        // estimate_size on L0 views should return non-zero
-        let manifest = db.manifest();
-        assert!(!manifest.manifest.core.tree.l0.is_empty());
-        for view in &manifest.manifest.core.tree.l0 {
-            assert!(view.estimate_size() > 0);
-        }
 
-        // Trigger compaction and wait for sorted runs
-        should_compact.store(true, Ordering::SeqCst);
-        let db_poll = db.clone();
-        tokio::time::timeout(Duration::from_secs(10), async move {
-            loop {
-                {
-                    let state = db_poll.inner.state.read();
-                    if !state.state().core().tree.compacted.is_empty() {
-                        return;
-                    }
-                }
-                tokio::time::sleep(Duration::from_millis(10)).await;
-            }
-        })
+        db.put_with_options(
+            b"test_key",
+            b"test_value",
+            &PutOptions::default(),
+            &WriteOptions {
+                await_durable: false,
+                ..Default::default()
+            },
+        )
         .await
         .unwrap();
 
-        let manifest = db.manifest();
-        assert!(!manifest.manifest.core.tree.compacted.is_empty());
-
-        for sr in &manifest.manifest.core.tree.compacted {
-            // A range covering all keys returns results
-            let covering = sr
-                .tables_covering_range(Bytes::from_static(b"k0000")..Bytes::from_static(b"k0100"));
-            assert!(!covering.is_empty());
-            for view in &covering {
-                assert!(view.estimate_size() > 0);
-            }
+        // Sanity check: WAL has buffered entries before close.
+        assert_eq!(db.inner.wal_buffer.buffered_wal_entries_count(), 1);
+        assert_eq!(
+            lookup_metric(&metrics_recorder, crate::db_stats::WAL_BUFFER_FLUSHES).unwrap(),
+            0
+        );
 
-            // A range before all keys returns nothing
-            let outside = sr
-                .tables_covering_range(Bytes::from_static(b"a0000")..Bytes::from_static(b"a9999"));
-            assert!(outside.is_empty());
-        }
+        // Simulate a failed state (e.g. fenced).
+        db.inner
+            .status_manager
+            .write_result(Err(crate::error::SlateDBError::Fenced));
 
+        // close() should succeed but not flush when failed.
         db.close().await.unwrap();
+
+        assert_eq!(db.inner.wal_buffer.buffered_wal_entries_count(), 1);
+        assert_eq!(
+            lookup_metric(&metrics_recorder, crate::db_stats::WAL_BUFFER_FLUSHES).unwrap(),
+            0
+        );
+        let status = db.status();
+        assert_eq!(status.close_reason, Some(CloseReason::Fenced));
     }
 
     #[tokio::test]
-    async fn test_scan_prefix_returns_matching_keys() {
+    async fn test_clean_close_flushes_pending_wal() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let kv_store = Db::builder("/tmp/test_scan_prefix", object_store)
-            .with_settings(test_db_options(0, 1024, None))
+        let db_options_no_flush_interval = {
+            let mut db_options = test_db_options(0, 1024, None);
+            db_options.flush_interval = None;
+            db_options
+        };
+        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
+        let db = Db::builder("/tmp/test_clean_close_flushes_pending_wal", object_store)
+            .with_settings(db_options_no_flush_interval)
+            .with_metrics_recorder(metrics_recorder.clone())
             .build()
             .await
             .unwrap();
 
-        kv_store.put(b"ab", b"v0").await.unwrap();
-        kv_store.put(b"aba", b"v1").await.unwrap();
-        kv_store.put(b"abb", b"v2").await.unwrap();
-        kv_store.put(b"ac", b"v3").await.unwrap();
+        db.put_with_options(
+            b"test_key",
+            b"test_value",
+            &PutOptions::default(),
+            &WriteOptions {
+                await_durable: false,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
 
-        let mut iter = kv_store.scan_prefix(b"ab").await.unwrap();
-        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"ab");
-        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"aba");
-        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"abb");
-        assert_eq!(iter.next().await.unwrap(), None);
+        assert_eq!(db.inner.wal_buffer.buffered_wal_entries_count(), 1);
+        assert_eq!(
+            lookup_metric(&metrics_recorder, crate::db_stats::WAL_BUFFER_FLUSHES).unwrap(),
+            0
+        );
 
-        kv_store.close().await.unwrap();
+        db.close().await.unwrap();
+
+        assert_eq!(db.inner.wal_buffer.buffered_wal_entries_count(), 0);
+        assert_eq!(
+            lookup_metric(&metrics_recorder, crate::db_stats::WAL_BUFFER_FLUSHES).unwrap(),
+            1
+        );
+        let status = db.status();
+        assert_eq!(status.close_reason, Some(CloseReason::Clean));
     }
 
     #[tokio::test]
-    async fn test_scan_descending_returns_records_in_reverse_order() {
+    async fn test_close_flushes_memtables_to_l0() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let kv_store = Db::builder("/tmp/test_scan_descending", object_store)
-            .with_settings(test_db_options(0, 1024, None))
+        let db_options = {
+            let mut db_options = test_db_options(0, 1024, None);
+            db_options.flush_interval = None;
+            db_options
+        };
+        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
+        let db = Db::builder("/tmp/test_close_flushes_memtables_to_l0", object_store)
+            .with_settings(db_options)
+            .with_metrics_recorder(metrics_recorder.clone())
             .build()
             .await
             .unwrap();
 
-        kv_store.put(b"a", b"v0").await.unwrap();
-        kv_store.put(b"b", b"v1").await.unwrap();
-        kv_store.flush().await.unwrap();
-        kv_store.put(b"c", b"v2").await.unwrap();
-        kv_store.put(b"d", b"v3").await.unwrap();
+        db.put_with_options(
+            b"test_key",
+            b"test_value",
+            &PutOptions::default(),
+            &WriteOptions {
+                await_durable: false,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
 
-        let scan_options = ScanOptions::default().with_order(IterationOrder::Descending);
-        let mut iter = kv_store
-            .scan_with_options::<Vec<u8>, _>(.., &scan_options)
-            .await
-            .unwrap();
-        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"d");
-        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"c");
-        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"b");
-        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"a");
-        assert_eq!(iter.next().await.unwrap(), None);
+        // No L0 flushes should have happened yet.
+        assert_eq!(
+            lookup_metric(&metrics_recorder, crate::db_stats::L0_FLUSH_BYTES).unwrap_or(0),
+            0
+        );
 
-        kv_store.close().await.unwrap();
+        db.close().await.unwrap();
+
+        // close() should have flushed memtables to L0.
+        assert!(
+            lookup_metric(&metrics_recorder, crate::db_stats::L0_FLUSH_BYTES).unwrap() > 0,
+            "expected L0 flush during close"
+        );
     }
 
     #[tokio::test]
-    async fn test_scan_descending_bounded_range() {
+    async fn test_close_makes_in_flight_writes_durable() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let kv_store = Db::builder("/tmp/test_scan_descending_bounded", object_store)
-            .with_settings(test_db_options(0, 1024, None))
+        let path = "/tmp/test_close_makes_in_flight_writes_durable";
+        let db_options = {
+            let mut db_options = test_db_options(0, 1024, None);
+            db_options.flush_interval = None;
+            db_options
+        };
+        let db = Db::builder(path, object_store.clone())
+            .with_settings(db_options)
             .build()
             .await
             .unwrap();
 
-        kv_store.put(b"a", b"v0").await.unwrap();
-        kv_store.put(b"b", b"v1").await.unwrap();
-        kv_store.put(b"c", b"v2").await.unwrap();
-        kv_store.put(b"d", b"v3").await.unwrap();
-        kv_store.put(b"e", b"v4").await.unwrap();
+        // without a flush interval, the write waits for durability until
+        // close flushes it
+        let writer = db.clone();
+        let write = tokio::spawn(async move { writer.put(b"key", b"value").await });
+        while db.inner.oracle.last_committed_seq() == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert!(!write.is_finished());
 
-        let scan_options = ScanOptions::default().with_order(IterationOrder::Descending);
-        let mut iter = kv_store
-            .scan_with_options::<Vec<u8>, _>(b"b".to_vec()..b"d".to_vec(), &scan_options)
-            .await
-            .unwrap();
-        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"c");
-        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"b");
-        assert_eq!(iter.next().await.unwrap(), None);
+        db.close().await.unwrap();
+        write.await.unwrap().unwrap();
 
-        kv_store.close().await.unwrap();
+        let db = Db::open(path, object_store).await.unwrap();
+        assert_eq!(
+            db.get(b"key").await.unwrap(),
+            Some(Bytes::from_static(b"value"))
+        );
     }
 
     #[tokio::test]
-    async fn test_scan_descending_skips_deleted_keys() {
+    async fn test_writes_rejected_while_closing_and_after_close() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let kv_store = Db::builder("/tmp/test_scan_descending_deletes", object_store)
-            .with_settings(test_db_options(0, 1024, None))
-            .build()
-            .await
-            .unwrap();
-
-        kv_store.put(b"a", b"v0").await.unwrap();
-        kv_store.put(b"b", b"v1").await.unwrap();
-        kv_store.put(b"c", b"v2").await.unwrap();
-        kv_store.put(b"d", b"v3").await.unwrap();
-        kv_store.delete(b"b").await.unwrap();
-        kv_store.delete(b"d").await.unwrap();
+        let db = Db::builder(
+            "/tmp/test_writes_rejected_while_closing_and_after_close",
+            object_store,
+        )
+        .with_settings(test_db_options(0, 1024, None))
+        .build()
+        .await
+        .unwrap();
 
-        let scan_options = ScanOptions::default().with_order(IterationOrder::Descending);
-        let mut iter = kv_store
-            .scan_with_options::<Vec<u8>, _>(.., &scan_options)
-            .await
-            .unwrap();
-        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"c");
-        assert_eq!(iter.next().await.unwrap().unwrap().key.as_ref(), b"a");
-        assert_eq!(iter.next().await.unwrap(), None);
+        // stands in for a write that was admitted but isn't applied yet
+        let permit = db.inner.write_gate.admit().unwrap();
+        let closer = db.clone();
+        let close = tokio::spawn(async move { closer.close().await });
+        while db.inner.write_gate.phase() != ShutdownPhase::Closing {
+            tokio::task::yield_now().await;
+        }
 
-        kv_store.close().await.unwrap();
+        let mut batch = WriteBatch::new();
+        batch.put(b"key", b"value");
+        let result = db
+            .inner
+            .write_with_options(batch.clone(), &WriteOptions::default())
+            .await;
+        assert!(matches!(result, Err(SlateDBError::Closing)));
+        let err = db.put(b"key", b"value").await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Closed(CloseReason::Clean));
+        assert!(!close.is_finished());
+
+        drop(permit);
+        close.await.unwrap().unwrap();
+        let result = db
+            .inner
+            .write_with_options(batch, &WriteOptions::default())
+            .await;
+        assert!(matches!(result, Err(SlateDBError::Closed)));
     }
 
     #[tokio::test]
-    async fn test_scan_prefix_descending() {
+    #[cfg(feature = "wal_disable")]
+    async fn test_get_with_durability_level_when_wal_disabled() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let kv_store = Db::builder("/tmp/test_scan_prefix_descending", object_store)
-            .with_settings(test_db_options(0, 1024, None))
+        let mut options = test_db_options(0, 1024 * 1024, None);
+        options.wal_enabled = false;
+        let db = Db::builder("/tmp/test_kv_store", object_store)
+            .with_settings(options)
             .build()
             .await
             .unwrap();
+        let put_options = PutOptions::default();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        let get_memory_options = ReadOptions::new().with_durability_filter(Memory);
+        let get_remote_options = ReadOptions::new().with_durability_filter(Remote);
 
-        kv_store.put(b"prefix/a", b"v0").await.unwrap();
-        kv_store.put(b"prefix/b", b"v1").await.unwrap();
-        kv_store.put(b"prefix/c", b"v2").await.unwrap();
-        kv_store.put(b"other/a", b"v3").await.unwrap();
-
-        let scan_options = ScanOptions::default().with_order(IterationOrder::Descending);
-        let mut iter = kv_store
-            .scan_prefix_with_options(b"prefix/", &scan_options)
+        db.put_with_options(b"foo", b"bar", &put_options, &write_options)
             .await
             .unwrap();
+        let val_bytes = Bytes::copy_from_slice(b"bar");
         assert_eq!(
-            iter.next().await.unwrap().unwrap().key.as_ref(),
-            b"prefix/c"
+            None,
+            db.get_with_options(b"foo", &get_remote_options)
+                .await
+                .unwrap()
         );
         assert_eq!(
-            iter.next().await.unwrap().unwrap().key.as_ref(),
-            b"prefix/b"
+            Some(val_bytes.clone()),
+            db.get_with_options(b"foo", &get_memory_options)
+                .await
+                .unwrap()
         );
+        db.flush().await.unwrap();
         assert_eq!(
-            iter.next().await.unwrap().unwrap().key.as_ref(),
-            b"prefix/a"
+            Some(val_bytes.clone()),
+            db.get_with_options(b"foo", &get_remote_options)
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            Some(val_bytes.clone()),
+            db.get_with_options(b"foo", &get_memory_options)
+                .await
+                .unwrap()
         );
-        assert_eq!(iter.next().await.unwrap(), None);
-
-        kv_store.close().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_scan_prefix_with_options_handles_unbounded_end() {
+    #[cfg(feature = "wal_disable")]
+    async fn test_find_with_multiple_repeated_keys() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let kv_store = Db::builder("/tmp/test_scan_prefix_unbounded", object_store)
-            .with_settings(test_db_options(0, 1024, None))
+        let mut options = test_db_options(0, 1024 * 1024, None);
+        options.wal_enabled = false;
+        let db = Db::builder("/tmp/test_kv_store", object_store)
+            .with_settings(options)
             .build()
             .await
             .unwrap();
 
-        kv_store.put(&[0xff, 0xff], b"v0").await.unwrap();
-        kv_store.put(&[0xff, 0xff, 0x00], b"v1").await.unwrap();
-        kv_store.put(&[0xff, 0xff, 0x10], b"v2").await.unwrap();
-        kv_store.put(&[0xff, 0xff, 0xff], b"v4").await.unwrap();
-        kv_store.put(&[0xff, 0xfe], b"v3").await.unwrap();
-
-        let scan_options = ScanOptions {
-            cache_blocks: false,
-            ..ScanOptions::default()
-        };
-        let mut iter = kv_store
-            .scan_prefix_with_options(&[0xff, 0xff], &scan_options)
+        // write enough rows with the same key that we yield an L0 SST with multiple blocks
+        let mut last_val: String = "foo".to_string();
+        for x in 0..4096 {
+            let val = format!("val{}", x);
+            db.put_with_options(
+                b"key",
+                val.as_bytes(),
+                &PutOptions {
+                    ttl: Default::default(),
+                },
+                &WriteOptions {
+                    await_durable: false,
+                    ..Default::default()
+                },
+            )
             .await
             .unwrap();
+            last_val = val;
+            if db
+                .inner
+                .state
+                .write()
+                .memtable()
+                .metadata()
+                .entries_size_in_bytes
+                > (SsTableFormat::default().block_size * 3)
+            {
+                break;
+            }
+        }
         assert_eq!(
-            iter.next().await.unwrap().unwrap().key.as_ref(),
-            &[0xff, 0xff]
-        );
-        assert_eq!(
-            iter.next().await.unwrap().unwrap().key.as_ref(),
-            &[0xff, 0xff, 0x00]
-        );
-        assert_eq!(
-            iter.next().await.unwrap().unwrap().key.as_ref(),
-            &[0xff, 0xff, 0x10]
+            Some(Bytes::copy_from_slice(last_val.as_bytes())),
+            db.get_with_options(b"key", &ReadOptions::new().with_durability_filter(Memory))
+                .await
+                .unwrap()
         );
+        db.flush().await.unwrap();
+
+        let state = db.inner.state.read().view();
+        assert_eq!(1, state.state.manifest.value.core.tree.l0.len());
+        let view = state.state.manifest.value.core.tree.l0.front().unwrap();
+        let index = db
+            .inner
+            .table_store
+            .read_index(&view.sst, true)
+            .await
+            .unwrap();
+        assert!(!index.borrow().block_meta().is_empty());
         assert_eq!(
-            iter.next().await.unwrap().unwrap().key.as_ref(),
-            &[0xff, 0xff, 0xff]
+            Some(Bytes::copy_from_slice(last_val.as_bytes())),
+            db.get(b"key").await.unwrap()
         );
-        assert_eq!(iter.next().await.unwrap(), None);
-
-        kv_store.close().await.unwrap();
-    }
-
-    fn assert_value(entry: &crate::types::RowEntry, expected: &[u8]) {
-        match &entry.value {
-            crate::types::ValueDeletable::Value(v) => assert_eq!(v.as_ref(), expected),
-            other => panic!("expected Value({expected:?}), got {other:?}"),
-        }
+        db.close().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_scan_prefix_by_recency_returns_matching_keys_from_memtable() {
+    async fn test_get_with_object_store_cache_metrics() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db = Db::builder("/tmp/test_recency_memtable", object_store)
-            .with_settings(test_db_options(0, 64 * 1024, None))
-            .build()
-            .await
+        let mut opts = test_db_options(0, 1024, None);
+        let temp_dir = tempfile::Builder::new()
+            .prefix("objstore_cache_test_")
+            .tempdir()
             .unwrap();
 
-        db.put(b"px:a", b"v0").await.unwrap();
-        db.put(b"px:b", b"v1").await.unwrap();
-        db.put(b"px:c", b"v2").await.unwrap();
-        db.put(b"qq:x", b"vx").await.unwrap();
+        opts.object_store_cache_options.root_folder = Some(temp_dir.keep());
+        opts.object_store_cache_options.part_size_bytes = 1024;
+        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
+        let kv_store = Db::builder(
+            "/tmp/test_kv_store_with_cache_metrics",
+            object_store.clone(),
+        )
+        .with_settings(opts)
+        .with_metrics_recorder(metrics_recorder.clone())
+        .build()
+        .await
+        .unwrap();
 
-        let mut iter = db.scan_prefix_by_recency(b"px:").await.unwrap();
-        let e1 = iter.next_entry().await.unwrap().unwrap();
-        let e2 = iter.next_entry().await.unwrap().unwrap();
-        let e3 = iter.next_entry().await.unwrap().unwrap();
-        assert_eq!(e1.key.as_ref(), b"px:a");
-        assert_eq!(e2.key.as_ref(), b"px:b");
-        assert_eq!(e3.key.as_ref(), b"px:c");
-        assert!(iter.next_entry().await.unwrap().is_none());
+        let access_count0 = lookup_metric(&metrics_recorder, PART_ACCESS_COUNT).unwrap();
+        let key = b"test_key";
+        let value = b"test_value";
+        kv_store.put(key, value).await.unwrap();
+        kv_store.flush().await.unwrap();
 
-        db.close().await.unwrap();
+        let got = kv_store.get(key).await.unwrap();
+        let access_count1 = lookup_metric(&metrics_recorder, PART_ACCESS_COUNT).unwrap();
+        assert_eq!(got, Some(Bytes::from_static(value)));
+        assert!(access_count1 > 0);
+        assert!(access_count1 >= access_count0);
+        assert!(lookup_metric(&metrics_recorder, PART_HIT_COUNT).unwrap() >= 1);
     }
 
     #[tokio::test]
-    async fn test_scan_prefix_by_recency_no_match_returns_none() {
+    async fn test_db_records_remote_object_store_reads_but_not_cache_hits() {
+        // given:
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db = Db::builder("/tmp/test_recency_no_match", object_store)
-            .with_settings(test_db_options(0, 64 * 1024, None))
+        let mut opts = test_db_options(0, 1024, None);
+        let temp_dir = tempfile::Builder::new()
+            .prefix("objstore_metrics_test_")
+            .tempdir()
+            .unwrap();
+
+        opts.object_store_cache_options.root_folder = Some(temp_dir.keep());
+        opts.object_store_cache_options.part_size_bytes = 1024;
+        let path = "/tmp/test_db_records_remote_object_store_reads_but_not_cache_hits";
+        let kv_store = Db::builder(path, object_store.clone())
+            .with_settings(opts)
             .build()
             .await
             .unwrap();
 
-        db.put(b"aa", b"v0").await.unwrap();
-        db.put(b"ab", b"v1").await.unwrap();
-
-        let mut iter = db.scan_prefix_by_recency(b"zz").await.unwrap();
-        assert!(iter.next_entry().await.unwrap().is_none());
-
-        db.close().await.unwrap();
-    }
+        kv_store.put(b"test_key", b"test_value").await.unwrap();
+        kv_store.flush().await.unwrap();
+        kv_store
+            .flush_with_options(FlushOptions {
+                flush_type: FlushType::MemTable,
+            })
+            .await
+            .unwrap();
+        kv_store.close().await.unwrap();
 
-    #[tokio::test]
-    async fn test_scan_prefix_by_recency_emits_both_versions_across_sources() {
-        // No dedup: a key present in both memtable (newer) and L0 (older)
-        // appears twice, newer first.
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db = Db::builder("/tmp/test_recency_no_dedup", object_store)
-            .with_settings(test_db_options(0, 64 * 1024, None))
+        let mut reopen_opts = test_db_options(0, 1024, None);
+        let reopen_temp_dir = tempfile::Builder::new()
+            .prefix("objstore_metrics_reopen_")
+            .tempdir()
+            .unwrap();
+        reopen_opts.object_store_cache_options.root_folder = Some(reopen_temp_dir.keep());
+        reopen_opts.object_store_cache_options.part_size_bytes = 1024;
+        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
+        let reopened = Db::builder(path, object_store)
+            .with_settings(reopen_opts)
+            .with_metrics_recorder(metrics_recorder.clone())
             .build()
             .await
             .unwrap();
 
-        db.put(b"px:a", b"old").await.unwrap();
-        db.flush_with_options(FlushOptions {
-            flush_type: FlushType::MemTable,
-        })
-        .await
-        .unwrap();
-
-        db.put(b"px:a", b"new").await.unwrap();
+        let requests_before =
+            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "get");
 
-        let mut iter = db.scan_prefix_by_recency(b"px:").await.unwrap();
-        let e1 = iter.next_entry().await.unwrap().unwrap();
-        assert_eq!(e1.key.as_ref(), b"px:a");
-        assert_value(&e1, b"new");
-        let e2 = iter.next_entry().await.unwrap().unwrap();
-        assert_eq!(e2.key.as_ref(), b"px:a");
-        assert_value(&e2, b"old");
-        assert!(e1.seq > e2.seq);
-        assert!(iter.next_entry().await.unwrap().is_none());
+        // when:
+        let _got = reopened.get(b"test_key").await.unwrap();
+        let requests_after_first =
+            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "get");
+        let got = reopened.get(b"test_key").await.unwrap();
+        let requests_after_second =
+            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "get");
 
-        db.close().await.unwrap();
+        // then:
+        assert!(requests_after_first > requests_before);
+        assert_eq!(got, Some(Bytes::from_static(b"test_value")));
+        assert_eq!(requests_after_second, requests_after_first);
+        assert_eq!(
+            lookup_object_store_op_histogram_count(&metrics_recorder, "db", "main", "get"),
+            requests_after_first as u64
+        );
+        reopened.close().await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_scan_prefix_by_recency_emits_tombstones() {
-        // Tombstones are surfaced as raw entries; the caller decides what
-        // they mean.
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db = Db::builder("/tmp/test_recency_tombstones", object_store)
-            .with_settings(test_db_options(0, 64 * 1024, None))
+    async fn test_db_records_main_and_wal_object_store_requests_separately() {
+        // given:
+        let main_object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let wal_object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
+        let path = "/tmp/test_db_records_main_and_wal_object_store_requests_separately";
+        let db = Db::builder(path, main_object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .with_wal_object_store(wal_object_store)
+            .with_metrics_recorder(metrics_recorder.clone())
             .build()
             .await
             .unwrap();
 
-        db.put(b"px:a", b"va").await.unwrap();
-        db.put(b"px:b", b"vb").await.unwrap();
+        let wal_before =
+            lookup_object_store_op_request_count(&metrics_recorder, "db", "wal", "put");
+        let main_before =
+            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "put");
+        let wal_hist_before =
+            lookup_object_store_op_histogram_count(&metrics_recorder, "db", "wal", "put");
+        let main_hist_before =
+            lookup_object_store_op_histogram_count(&metrics_recorder, "db", "main", "put");
+
+        // when:
+        db.put(b"key", b"value").await.unwrap();
+        db.flush().await.unwrap();
         db.flush_with_options(FlushOptions {
             flush_type: FlushType::MemTable,
         })
         .await
         .unwrap();
 
-        db.delete(b"px:a").await.unwrap();
-
-        let mut iter = db.scan_prefix_by_recency(b"px:").await.unwrap();
-        // Memtable first: tombstone for px:a.
-        let e1 = iter.next_entry().await.unwrap().unwrap();
-        assert_eq!(e1.key.as_ref(), b"px:a");
-        assert!(e1.value.is_tombstone());
-        // L0 second: original values, ascending.
-        let e2 = iter.next_entry().await.unwrap().unwrap();
-        assert_eq!(e2.key.as_ref(), b"px:a");
-        assert_value(&e2, b"va");
-        let e3 = iter.next_entry().await.unwrap().unwrap();
-        assert_eq!(e3.key.as_ref(), b"px:b");
-        assert_value(&e3, b"vb");
-        // Tombstone is from the freshest source so its seq dominates the
-        // earlier put of px:a.
-        assert!(e1.seq > e2.seq);
-        assert!(iter.next_entry().await.unwrap().is_none());
+        let wal_after = lookup_object_store_op_request_count(&metrics_recorder, "db", "wal", "put");
+        let main_after =
+            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "put");
+        let wal_hist_after =
+            lookup_object_store_op_histogram_count(&metrics_recorder, "db", "wal", "put");
+        let main_hist_after =
+            lookup_object_store_op_histogram_count(&metrics_recorder, "db", "main", "put");
 
+        // then:
+        assert!(wal_after > wal_before);
+        assert!(main_after > main_before);
+        assert!(wal_hist_after > wal_hist_before);
+        assert!(main_hist_after > main_hist_before);
         db.close().await.unwrap();
     }
 
-    #[tokio::test]
-    async fn test_scan_prefix_by_recency_walks_memtable_then_l0() {
+    async fn test_object_store_cache_helper(
+        cache_puts_enabled: bool,
+        db_path: &str,
+        expected_cache_parts: Vec<(&str, usize)>,
+    ) -> (Arc<CachedObjectStore>, Db) {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db = Db::builder("/tmp/test_recency_multi_source", object_store)
-            .with_settings(test_db_options(0, 64 * 1024, None))
-            .build()
-            .await
+        let mut opts = test_db_options(0, 1024, None);
+        // disable manifest polling to avoid caching manifests non-deterministically
+        opts.manifest_poll_interval = Duration::from_millis(u64::MAX);
+        let temp_dir = tempfile::Builder::new()
+            .prefix("objstore_cache_test_")
+            .tempdir()
             .unwrap();
 
-        // Older L0 SST: px:b (older value), px:c.
-        db.put(b"px:b", b"old_b").await.unwrap();
-        db.put(b"px:c", b"vc").await.unwrap();
-        db.flush_with_options(FlushOptions {
-            flush_type: FlushType::MemTable,
-        })
-        .await
-        .unwrap();
-
-        // Active memtable: px:a, px:b (newer value).
-        db.put(b"px:a", b"va").await.unwrap();
-        db.put(b"px:b", b"new_b").await.unwrap();
+        let recorder = MetricsRecorderHelper::noop();
+        let cache_stats = Arc::new(CachedObjectStoreStats::new(&recorder));
+        let part_size = 1024;
+        info!("temp_dir: {:?}", temp_dir.path());
 
-        let mut iter = db.scan_prefix_by_recency(b"px:").await.unwrap();
-        // Memtable drained first, ascending within source.
-        let e1 = iter.next_entry().await.unwrap().unwrap();
-        assert_eq!(e1.key.as_ref(), b"px:a");
-        assert_value(&e1, b"va");
-        let e2 = iter.next_entry().await.unwrap().unwrap();
-        assert_eq!(e2.key.as_ref(), b"px:b");
-        assert_value(&e2, b"new_b");
-        // L0 next: px:b (older) then px:c. No dedup.
-        let e3 = iter.next_entry().await.unwrap().unwrap();
-        assert_eq!(e3.key.as_ref(), b"px:b");
-        assert_value(&e3, b"old_b");
-        let e4 = iter.next_entry().await.unwrap().unwrap();
-        assert_eq!(e4.key.as_ref(), b"px:c");
-        assert_value(&e4, b"vc");
-        assert!(iter.next_entry().await.unwrap().is_none());
+        let cache_storage = Arc::new(FsCacheStorage::new(
+            temp_dir.keep(),
+            None,
+            None,
+            cache_stats.clone(),
+            Arc::new(DefaultSystemClock::new()),
+            Arc::new(DbRand::default()),
+            1000,
+        ));
 
-        db.close().await.unwrap();
-    }
+        let cached_object_store = CachedObjectStore::new(
+            object_store.clone(),
+            cache_storage,
+            part_size,
+            cache_puts_enabled,
+            cache_stats.clone(),
+        )
+        .unwrap();
 
-    #[tokio::test]
-    async fn test_scan_prefix_by_recency_active_memtable_avoids_main_object_store_gets() {
-        // When the prefix is satisfied entirely by the active memtable, the
-        // recency scan should not issue any GETs against the main object
-        // store (it shouldn't even open an L0/SR iterator).
-        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db = Db::builder("/tmp/test_recency_no_main_gets", object_store)
-            .with_settings(test_db_options(0, 64 * 1024, None))
-            .with_metrics_recorder(metrics_recorder.clone())
+        let kv_store = Db::builder(db_path, cached_object_store.clone())
+            .with_settings(opts)
             .build()
             .await
             .unwrap();
+        // `cache_puts_enabled` won't take effect until after the first
+        // `resolve_root`. Call head to force the root to be resolved.
+        // Use the first WAL entry since it's guaranteed to exist because
+        // it's the fencing write.
+        cached_object_store
+            .head(&object_store::path::Path::from(format!(
+                "{}/wal/00000000000000000001.sst",
+                db_path
+            )))
+            .await
+            .unwrap();
+        let key = b"test_key";
+        let value = b"test_value";
+        kv_store.put(key, value).await.unwrap();
+        kv_store.flush().await.unwrap();
 
-        // Older data in L0. Different prefix; we want them to be present
-        // but never visited.
-        db.put(b"qq:a", b"vqa").await.unwrap();
-        db.put(b"qq:b", b"vqb").await.unwrap();
-        db.flush_with_options(FlushOptions {
-            flush_type: FlushType::MemTable,
-        })
-        .await
-        .unwrap();
+        // Verify cache behavior
+        for (path, expected_parts) in expected_cache_parts {
+            let entry = cached_object_store
+                .cache_storage
+                .entry(&object_store::path::Path::from(path), part_size);
+            assert_eq!(
+                entry.cached_parts().await.unwrap().len(),
+                expected_parts,
+                "Path: {}",
+                path
+            );
+        }
 
-        // Active-memtable-only prefix.
-        db.put(b"px:a", b"va").await.unwrap();
-        db.put(b"px:b", b"vb").await.unwrap();
+        (cached_object_store, kv_store)
+    }
 
-        let gets_before =
-            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "get");
+    #[tokio::test]
+    async fn test_get_with_object_store_cache_stored_files() {
+        let expected_cache_parts =
+            vec![
+            ("tmp/test_kv_store_with_cache_stored_files/manifest/00000000000000000001.manifest", 0),
+            ("tmp/test_kv_store_with_cache_stored_files/manifest/00000000000000000002.manifest", 0),
+            // 1 part is cached because of wal_replay after fencing (which reads the SST, thereby caching it)
+            ("tmp/test_kv_store_with_cache_stored_files/wal/00000000000000000001.sst", 1),
+            ("tmp/test_kv_store_with_cache_stored_files/wal/00000000000000000002.sst", 0),
+        ];
 
-        let mut iter = db.scan_prefix_by_recency(b"px:").await.unwrap();
-        let e1 = iter.next_entry().await.unwrap().unwrap();
-        let e2 = iter.next_entry().await.unwrap().unwrap();
-        assert_eq!(e1.key.as_ref(), b"px:a");
-        assert_eq!(e2.key.as_ref(), b"px:b");
-        // Caller stops here without driving the iterator into older sources.
+        let (_cached_object_store, kv_store) = test_object_store_cache_helper(
+            false, // cache_puts disabled
+            "/tmp/test_kv_store_with_cache_stored_files",
+            expected_cache_parts,
+        )
+        .await;
 
-        let gets_after =
-            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "get");
-        assert_eq!(
-            gets_before, gets_after,
-            "scan_prefix_by_recency should not touch the main object store \
-             when the prefix is fully covered by the active memtable"
-        );
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_with_object_store_cache_put_caching_enabled() {
+        let expected_cache_parts =
+            vec![
+            // not cached because manifests are put before the root is resolved, which is when
+            // `cache_puts_enabled` starts taking effect.
+            ("tmp/test_kv_store_with_put_cache_enabled/manifest/00000000000000000001.manifest", 0),
+            ("tmp/test_kv_store_with_put_cache_enabled/manifest/00000000000000000002.manifest", 0),
+            // 1 part is cached because of wal_replay after fencing (which reads the SST, thereby caching it)
+            ("tmp/test_kv_store_with_put_cache_enabled/wal/00000000000000000001.sst", 1),
+            // 1 part is cached because the put with cache_puts enabled should cache the test_key put
+            ("tmp/test_kv_store_with_put_cache_enabled/wal/00000000000000000002.sst", 1),
+        ];
 
-        db.close().await.unwrap();
+        let (_cached_object_store, kv_store) = test_object_store_cache_helper(
+            true, // cache_puts enabled
+            "/tmp/test_kv_store_with_put_cache_enabled",
+            expected_cache_parts,
+        )
+        .await;
+
+        kv_store.close().await.unwrap();
     }
 
-    #[tokio::test]
-    async fn test_scan_prefix_by_recency_durability_remote_filters_unflushed() {
-        // With DurabilityLevel::Remote and the put issued without awaiting
-        // durability, only L0/SR-resident entries should be visible. The
-        // not-yet-flushed memtable write is filtered out by max_seq.
+    async fn build_database_from_table(
+        table: &BTreeMap<Bytes, Bytes>,
+        db_options: Settings,
+        await_durable: bool,
+    ) -> Db {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db = Db::builder("/tmp/test_recency_remote_durability", object_store)
-            .with_settings(test_db_options(0, 64 * 1024, None))
+        let db = Db::builder("/tmp/test_kv_store", object_store)
+            .with_settings(db_options)
             .build()
             .await
             .unwrap();
 
-        db.put(b"px:a", b"va_durable").await.unwrap();
-        db.flush_with_options(FlushOptions {
-            flush_type: FlushType::MemTable,
-        })
-        .await
-        .unwrap();
-
-        // Skip the WAL await. Without a follow-up flush, px:b lives only
-        // in the in-memory memtable.
-        db.put_with_options(
-            b"px:b",
-            b"vb_dirty",
-            &PutOptions::default(),
-            &WriteOptions {
-                await_durable: false,
-                seqnum: 0,
-            },
-        )
-        .await
-        .unwrap();
+        test_utils::seed_database(&db, table, false).await.unwrap();
 
-        let opts = ScanOptions {
-            durability_filter: Remote,
-            ..ScanOptions::default()
-        };
-        let mut iter = db
-            .scan_prefix_by_recency_with_options(b"px:", &opts)
-            .await
-            .unwrap();
-        let e = iter.next_entry().await.unwrap().unwrap();
-        assert_eq!(e.key.as_ref(), b"px:a");
-        assert_value(&e, b"va_durable");
-        assert!(iter.next_entry().await.unwrap().is_none());
+        if await_durable {
+            db.flush().await.unwrap();
+        }
 
-        db.close().await.unwrap();
+        db
     }
 
     #[tokio::test]
-    async fn test_scan_prefix_by_recency_walks_memtable_then_compacted_run() {
-        // Walks memtable -> L0 -> compacted sorted run, verifying that the
-        // sorted-run path is constructed and drained correctly when the
-        // recency walk reaches it. Same-key versions surface from each
-        // source in newest-first order with no dedup.
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let path = "/tmp/test_recency_compacted";
-        let should_compact = Arc::new(AtomicBool::new(false));
-        let should_compact_clone = should_compact.clone();
-        let scheduler = Arc::new(OnDemandCompactionSchedulerSupplier::new(Arc::new(
-            move |_state| should_compact_clone.swap(false, Ordering::SeqCst),
-        )));
-        let db = Db::builder(path, object_store.clone())
-            .with_settings(test_db_options(0, 64 * 1024, None))
-            .with_compactor_builder(
-                CompactorBuilder::new(path, object_store.clone())
-                    .with_scheduler_supplier(scheduler),
-            )
-            .build()
-            .await
-            .unwrap();
-        let db = Arc::new(db);
+    async fn test_should_allow_iterating_behind_box_dyn() {
+        #[async_trait]
+        trait IteratorSupplier {
+            async fn iterator(&self) -> Box<dyn IteratorTrait>;
+        }
 
-        // Oldest write goes to a sorted run after compaction.
-        db.put(b"px:a", b"v_oldest").await.unwrap();
-        db.flush_with_options(FlushOptions {
-            flush_type: FlushType::MemTable,
-        })
-        .await
-        .unwrap();
+        struct DbHolder {
+            db: Db,
+        }
 
-        // Trigger compaction and wait for it to land.
-        should_compact.store(true, Ordering::SeqCst);
-        let db_poll = db.clone();
-        tokio::time::timeout(Duration::from_secs(10), async move {
-            loop {
-                {
-                    let state = db_poll.inner.state.read();
-                    if !state.state().core().tree.compacted.is_empty() {
-                        return;
-                    }
-                }
-                tokio::time::sleep(Duration::from_millis(10)).await;
+        #[async_trait]
+        impl IteratorSupplier for DbHolder {
+            async fn iterator(&self) -> Box<dyn IteratorTrait> {
+                let range = BytesRange::new_empty();
+                let iter = self
+                    .db
+                    .inner
+                    .scan_with_options(range, &ScanOptions::default())
+                    .await
+                    .unwrap();
+                Box::new(iter)
             }
-        })
-        .await
-        .unwrap();
-
-        // L0 layer (newer than the sorted run, older than the memtable).
-        db.put(b"px:a", b"v_l0").await.unwrap();
-        db.flush_with_options(FlushOptions {
-            flush_type: FlushType::MemTable,
-        })
-        .await
-        .unwrap();
+        }
 
-        // Active memtable (freshest).
-        db.put(b"px:a", b"v_memtable").await.unwrap();
+        #[async_trait]
+        trait IteratorTrait {
+            async fn next(&mut self) -> Result<Option<KeyValue>, crate::Error>;
+        }
 
-        let mut iter = db.scan_prefix_by_recency(b"px:").await.unwrap();
-        let e1 = iter.next_entry().await.unwrap().unwrap();
-        let e2 = iter.next_entry().await.unwrap().unwrap();
-        let e3 = iter.next_entry().await.unwrap().unwrap();
-        assert_eq!(e1.key.as_ref(), b"px:a");
-        assert_value(&e1, b"v_memtable");
-        assert_eq!(e2.key.as_ref(), b"px:a");
-        assert_value(&e2, b"v_l0");
-        assert_eq!(e3.key.as_ref(), b"px:a");
-        assert_value(&e3, b"v_oldest");
-        assert!(e1.seq > e2.seq);
-        assert!(e2.seq > e3.seq);
-        assert!(iter.next_entry().await.unwrap().is_none());
+        #[async_trait]
+        impl IteratorTrait for DbIterator {
+            async fn next(&mut self) -> Result<Option<KeyValue>, crate::Error> {
+                DbIterator::next(self).await
+            }
+        }
 
-        Arc::into_inner(db)
-            .expect("db Arc should be uniquely held")
-            .close()
+        let db_options = test_db_options(0, 1024, None);
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_kv_store", object_store)
+            .with_settings(db_options)
+            .build()
             .await
             .unwrap();
+        let db_holder = DbHolder { db };
+        let mut boxed = db_holder.iterator().await;
+        let next = boxed.next().await;
+        assert_eq!(next.unwrap(), None);
     }
 
-    #[tokio::test]
-    async fn test_scan_prefix_by_recency_errors_on_multi_segment_prefix() {
-        use crate::manifest::{LsmTreeState, Segment};
-        use std::collections::VecDeque;
-
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db = Db::builder("/tmp/test_recency_multi_segment", object_store)
-            .with_settings(test_db_options(0, 64 * 1024, None))
-            .build()
+    async fn assert_records_in_range(
+        table: &BTreeMap<Bytes, Bytes>,
+        db: &Db,
+        scan_options: &ScanOptions,
+        range: BytesRange,
+    ) {
+        let mut iter = db
+            .inner
+            .scan_with_options(range.clone(), scan_options)
             .await
             .unwrap();
-
-        // Inject two non-nesting segments. Both prefixes share "hour=1"
-        // but neither is a prefix of the other, so the antichain holds.
-        // A scan with prefix b"hour=" overlaps both segments' intervals,
-        // which is the case we want to reject.
-        db.inner.state.write().modify(|m| {
-            let core = &mut m.state.manifest.value.core;
-            core.segment_extractor_name = Some("hour".into());
-            core.segments = vec![
-                Segment {
-                    prefix: Bytes::from_static(b"hour=12/"),
-                    tree: LsmTreeState {
-                        last_compacted_l0_sst_view_id: None,
-                        last_compacted_l0_sst_id: None,
-                        l0: VecDeque::new(),
-                        compacted: vec![],
-                    },
-                },
-                Segment {
-                    prefix: Bytes::from_static(b"hour=13/"),
-                    tree: LsmTreeState {
-                        last_compacted_l0_sst_view_id: None,
-                        last_compacted_l0_sst_id: None,
-                        l0: VecDeque::new(),
-                        compacted: vec![],
-                    },
-                },
-            ];
-        });
-
-        match db.scan_prefix_by_recency(b"hour=").await {
-            Err(e) => assert_eq!(e.kind(), crate::ErrorKind::Invalid),
-            Ok(_) => panic!("expected multi-segment error"),
-        }
-
-        // Single-segment prefixes still work.
-        let mut iter = db.scan_prefix_by_recency(b"hour=12/").await.unwrap();
-        assert!(iter.next_entry().await.unwrap().is_none());
-
-        db.close().await.unwrap();
+        test_utils::assert_ranged_db_scan(table, range, IterationOrder::Ascending, &mut iter).await;
     }
 
-    #[tokio::test]
-    async fn test_resolve_object_store_local_prefix_store_writes_to_path() {
-        let temp_dir = tempfile::tempdir().unwrap();
-        let prefix_path = temp_dir.path().join("prefix-store");
-        let url = format!("file://{}", prefix_path.display());
+    #[test]
+    fn test_scan_returns_records_in_range() {
+        let mut runner = new_proptest_runner(None);
+        let table = sample::table(runner.rng(), 1000, 5);
 
-        let object_store = Db::resolve_object_store(&url).unwrap();
-        let location = object_store::path::Path::from("nested/file.txt");
-        let payload = Bytes::from_static(b"local prefix payload");
+        let runtime = Runtime::new().unwrap();
+        let db_options = test_db_options(0, 1024, None);
+        let db = runtime.block_on(build_database_from_table(&table, db_options, true));
 
-        object_store
-            .put(&location, payload.clone().into())
-            .await
+        runner
+            .run(&arbitrary::nonempty_range(10), |range| {
+                runtime.block_on(assert_records_in_range(
+                    &table,
+                    &db,
+                    &ScanOptions::default(),
+                    range,
+                ));
+                Ok(())
+            })
             .unwrap();
+    }
 
-        let expected_path = prefix_path.join("nested").join("file.txt");
-        let stored = tokio::fs::read(&expected_path).await.unwrap();
-        assert_eq!(stored, payload.to_vec());
+    fn new_proptest_runner(rng_seed: Option<[u8; 32]>) -> TestRunner {
+        proptest_util::runner::new(file!(), rng_seed)
     }
 
     #[test]
-    fn test_get_after_put() {
+    fn test_scan_returns_uncommitted_records_if_read_level_uncommitted() {
         let mut runner = new_proptest_runner(None);
+        let table = sample::table(runner.rng(), 1000, 5);
+
         let runtime = Runtime::new().unwrap();
+        let mut db_options = test_db_options(0, 1024, None);
+        db_options.flush_interval = Some(Duration::from_secs(5));
+        let db = runtime.block_on(build_database_from_table(&table, db_options, false));
+
+        runner
+            .run(&arbitrary::nonempty_range(10), |range| {
+                let scan_options = ScanOptions {
+                    durability_filter: Memory,
+                    ..ScanOptions::default()
+                };
+                runtime.block_on(assert_records_in_range(&table, &db, &scan_options, range));
+                Ok(())
+            })
+            .unwrap();
+    }
 
+    #[test]
+    fn test_seek_outside_of_range_returns_invalid_argument() {
+        let mut runner = new_proptest_runner(None);
         let table = sample::table(runner.rng(), 1000, 10);
+
+        let runtime = Runtime::new().unwrap();
         let db_options = test_db_options(0, 1024, None);
         let db = runtime.block_on(build_database_from_table(&table, db_options, true));
 
         runner
             .run(
-                &(arbitrary::bytes(100), arbitrary::bytes(100)),
-                |(key, value)| {
-                    runtime.block_on(async {
-                        if !key.is_empty() {
-                            db.put_with_options(
-                                &key,
-                                &value,
-                                &PutOptions::default(),
-                                &WriteOptions {
-                                    await_durable: false,
-                                    ..Default::default()
-                                },
-                            )
-                            .await
-                            .unwrap();
-                            assert_eq!(
-                                Some(value),
-                                db.get_with_options(
-                                    &key,
-                                    &ReadOptions {
-                                        durability_filter: Memory,
-                                        dirty: false,
-                                        cache_blocks: true,
-                                        filter_context: None,
-                                    }
-                                )
-                                .await
-                                .unwrap()
-                            );
-                        }
-                    });
+                &(arbitrary::nonempty_bytes(10), arbitrary::rng()),
+                |(arbitrary_key, mut rng)| {
+                    runtime.block_on(assert_out_of_bound_seek_returns_invalid_argument(
+                        &db,
+                        &mut rng,
+                        arbitrary_key,
+                    ));
                     Ok(())
                 },
             )
             .unwrap();
-    }
 
-    #[tokio::test]
-    async fn test_no_flush_interval() {
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db_options_no_flush_interval = {
-            let mut db_options = test_db_options(0, 1024, None);
-            db_options.flush_interval = None;
-            db_options
-        };
-        let kv_store = Db::builder("/tmp/test_kv_store", object_store)
-            .with_settings(db_options_no_flush_interval)
-            .build()
-            .await
-            .unwrap();
-        let key = b"test_key";
-        let value = b"test_value";
+        async fn assert_out_of_bound_seek_returns_invalid_argument(
+            db: &Db,
+            rng: &mut TestRng,
+            arbitrary_key: Bytes,
+        ) {
+            let mut iter = db
+                .scan_with_options(..arbitrary_key.clone(), &ScanOptions::default())
+                .await
+                .unwrap();
 
-        kv_store
-            .put_with_options(
-                key,
-                value,
-                &PutOptions::default(),
-                &WriteOptions {
-                    await_durable: false,
-                    ..Default::default()
-                },
-            )
-            .await
-            .unwrap();
+            let lower_bounded_range = BytesRange::from(arbitrary_key.clone()..);
+            let value = sample::bytes_in_range(rng, &lower_bounded_range);
+            let err = iter.seek(value.clone()).await.unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("cannot seek to a key outside the iterator range"),
+                "{}",
+                err
+            );
 
-        // a sanity check: the wal contains the most recent write
-        assert_ne!(kv_store.inner.wal_buffer.estimated_bytes().unwrap(), 0);
+            let mut iter = db
+                .scan_with_options(arbitrary_key.clone().., &ScanOptions::default())
+                .await
+                .unwrap();
 
-        // and a flush() should clear it
-        kv_store.flush().await.unwrap();
-        assert_eq!(kv_store.inner.wal_buffer.estimated_bytes().unwrap(), 0);
+            let upper_bounded_range = BytesRange::from(..arbitrary_key.clone());
+            let value = sample::bytes_in_range(rng, &upper_bounded_range);
+            let err = iter.seek(value.clone()).await.unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("cannot seek to a key outside the iterator range"),
+                "{}",
+                err
+            );
+        }
     }
 
-    #[tokio::test]
-    async fn test_close_triggers_flush_when_open() {
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db_options_no_flush_interval = {
-            let mut db_options = test_db_options(0, 1024, None);
-            db_options.flush_interval = None;
-            db_options
-        };
-        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
-        let kv_store = Db::builder("/tmp/test_close_triggers_flush", object_store)
-            .with_settings(db_options_no_flush_interval)
-            .with_metrics_recorder(metrics_recorder.clone())
-            .build()
-            .await
-            .unwrap();
+    #[test]
+    fn test_seek_fast_forwards_iterator() {
+        let mut runner = new_proptest_runner(None);
+        let table = sample::table(runner.rng(), 1000, 10);
 
-        kv_store
-            .put_with_options(
-                b"test_key",
-                b"test_value",
-                &PutOptions::default(),
-                &WriteOptions {
-                    await_durable: false,
-                    ..Default::default()
+        let runtime = Runtime::new().unwrap();
+        let db_options = test_db_options(0, 1024, None);
+        let db = runtime.block_on(build_database_from_table(&table, db_options, true));
+
+        runner
+            .run(
+                &(arbitrary::nonempty_range(5), arbitrary::rng()),
+                |(range, mut rng)| {
+                    runtime.block_on(assert_seek_fast_forwards_iterator(
+                        &table, &db, &range, &mut rng,
+                    ));
+                    Ok(())
                 },
             )
-            .await
-            .unwrap();
-
-        // Sanity check: WAL has buffered entries before close.
-        assert_eq!(kv_store.inner.wal_buffer.buffered_wal_entries_count(), 1);
-        assert_eq!(
-            lookup_metric(&metrics_recorder, crate::db_stats::WAL_BUFFER_FLUSHES).unwrap(),
-            0
-        );
-
-        kv_store.close().await.unwrap();
-
-        // close() should trigger a flush when the db is open.
-        assert_eq!(kv_store.inner.wal_buffer.buffered_wal_entries_count(), 0);
-        assert_eq!(
-            lookup_metric(&metrics_recorder, crate::db_stats::WAL_BUFFER_FLUSHES).unwrap(),
-            1
-        );
-    }
-
-    #[tokio::test]
-    async fn test_close_twice_returns_closed_clean() {
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db = Db::builder("/tmp/test_close_twice_returns_closed_clean", object_store)
-            .with_settings(test_db_options(0, 1024, None))
-            .build()
-            .await
-            .unwrap();
-
-        db.close().await.unwrap();
-        let err = db.close().await.unwrap_err();
-        assert_eq!(err.kind(), crate::ErrorKind::Closed(CloseReason::Clean));
-    }
-
-    #[tokio::test]
-    async fn test_close_failed_state_does_not_flush() {
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db_options_no_flush_interval = {
-            let mut db_options = test_db_options(0, 1024, None);
-            db_options.flush_interval = None;
-            db_options
-        };
-        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
-        let db = Db::builder("/tmp/test_close_failed_state_no_flush", object_store)
-            .with_settings(db_options_no_flush_interval)
-            .with_metrics_recorder(metrics_recorder.clone())
-            .build()
-            .await
             .unwrap();
 
-        db.put_with_options(
-            b"test_key",
-            b"test_value",
-            &PutOptions::default(),
-            &WriteOptions {
-                await_durable: false,
-                ..Default::default()
-            },
-        )
-        .await
-        .unwrap();
-
-        // Sanity check: WAL has buffered entries before close.
-        assert_eq!(db.inner.wal_buffer.buffered_wal_entries_count(), 1);
-        assert_eq!(
-            lookup_metric(&metrics_recorder, crate::db_stats::WAL_BUFFER_FLUSHES).unwrap(),
-            0
-        );
-
-        // Simulate a failed state (e.g. fenced).
-        db.inner
-            .status_manager
-            .write_result(Err(crate::error::SlateDBError::Fenced));
+        async fn assert_seek_fast_forwards_iterator(
+            table: &BTreeMap<Bytes, Bytes>,
+            db: &Db,
+            scan_range: &BytesRange,
+            rng: &mut TestRng,
+        ) {
+            let mut iter = db
+                .inner
+                .scan_with_options(scan_range.clone(), &ScanOptions::default())
+                .await
+                .unwrap();
 
-        // close() should succeed but not flush when failed.
-        db.close().await.unwrap();
+            let seek_key = sample::bytes_in_range(rng, scan_range);
+            iter.seek(seek_key.clone()).await.unwrap();
 
-        assert_eq!(db.inner.wal_buffer.buffered_wal_entries_count(), 1);
-        assert_eq!(
-            lookup_metric(&metrics_recorder, crate::db_stats::WAL_BUFFER_FLUSHES).unwrap(),
-            0
-        );
-        let status = db.status();
-        assert_eq!(status.close_reason, Some(CloseReason::Fenced));
+            let seek_range = BytesRange::new(Included(seek_key), scan_range.end_bound().cloned());
+            test_utils::assert_ranged_db_scan(
+                table,
+                seek_range,
+                IterationOrder::Ascending,
+                &mut iter,
+            )
+            .await;
+        }
     }
 
     #[tokio::test]
-    async fn test_clean_close_flushes_pending_wal() {
+    async fn test_write_batch() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db_options_no_flush_interval = {
-            let mut db_options = test_db_options(0, 1024, None);
-            db_options.flush_interval = None;
-            db_options
-        };
-        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
-        let db = Db::builder("/tmp/test_clean_close_flushes_pending_wal", object_store)
-            .with_settings(db_options_no_flush_interval)
-            .with_metrics_recorder(metrics_recorder.clone())
+        let kv_store = Db::builder("/tmp/test_kv_store", object_store)
+            .with_settings(test_db_options(0, 1024, None))
             .build()
             .await
             .unwrap();
 
-        db.put_with_options(
-            b"test_key",
-            b"test_value",
-            &PutOptions::default(),
-            &WriteOptions {
-                await_durable: false,
-                ..Default::default()
-            },
-        )
-        .await
-        .unwrap();
-
-        assert_eq!(db.inner.wal_buffer.buffered_wal_entries_count(), 1);
-        assert_eq!(
-            lookup_metric(&metrics_recorder, crate::db_stats::WAL_BUFFER_FLUSHES).unwrap(),
-            0
-        );
+        // Create a new WriteBatch
+        let mut batch = WriteBatch::new();
+        batch.put(b"key1", b"value1");
+        batch.put(b"key2", b"value2");
+        batch.delete(b"key1");
 
-        db.close().await.unwrap();
+        // Write the batch
+        kv_store.write(batch).await.expect("write batch failed");
 
-        assert_eq!(db.inner.wal_buffer.buffered_wal_entries_count(), 0);
+        // Read back keys
+        assert_eq!(kv_store.get(b"key1").await.unwrap(), None);
         assert_eq!(
-            lookup_metric(&metrics_recorder, crate::db_stats::WAL_BUFFER_FLUSHES).unwrap(),
-            1
+            kv_store.get(b"key2").await.unwrap(),
+            Some(Bytes::from_static(b"value2"))
         );
-        let status = db.status();
-        assert_eq!(status.close_reason, Some(CloseReason::Clean));
+
+        kv_store.close().await.unwrap();
     }
 
+    #[cfg(feature = "wal_disable")]
     #[tokio::test]
-    async fn test_close_flushes_memtables_to_l0() {
+    async fn test_write_batch_without_wal() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db_options = {
-            let mut db_options = test_db_options(0, 1024, None);
-            db_options.flush_interval = None;
-            db_options
-        };
-        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
-        let db = Db::builder("/tmp/test_close_flushes_memtables_to_l0", object_store)
-            .with_settings(db_options)
-            .with_metrics_recorder(metrics_recorder.clone())
+        // Use a very small l0 size to force flushes so await is notified
+        let mut options = test_db_options(0, 8, None);
+
+        // Disable WAL
+        options.wal_enabled = false;
+
+        let kv_store = Db::builder("/tmp/test_kv_store_without_wal", object_store.clone())
+            .with_settings(options)
             .build()
             .await
             .unwrap();
 
-        db.put_with_options(
-            b"test_key",
-            b"test_value",
-            &PutOptions::default(),
-            &WriteOptions {
-                await_durable: false,
-                ..Default::default()
-            },
-        )
-        .await
-        .unwrap();
+        // Create a new WriteBatch
+        let mut batch = WriteBatch::new();
+        batch.put(b"key1", b"value1");
+        batch.put(b"key2", b"value2");
+        batch.delete(b"key1");
 
-        // No L0 flushes should have happened yet.
-        assert_eq!(
-            lookup_metric(&metrics_recorder, crate::db_stats::L0_FLUSH_BYTES).unwrap_or(0),
-            0
-        );
+        // Write the batch
+        kv_store.write(batch).await.expect("write batch failed");
 
-        db.close().await.unwrap();
+        // Read back keys
+        assert_eq!(kv_store.get(b"key1").await.unwrap(), None);
+        assert_eq!(kv_store.get(b"key2").await.unwrap(), Some("value2".into()));
 
-        // close() should have flushed memtables to L0.
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_write_batch_with_empty_key() {
+        let mut batch = WriteBatch::new();
+        let result = std::panic::catch_unwind(move || {
+            batch.put(b"", b"value");
+        });
         assert!(
-            lookup_metric(&metrics_recorder, crate::db_stats::L0_FLUSH_BYTES).unwrap() > 0,
-            "expected L0 flush during close"
+            result.is_err(),
+            "Expected panic when using empty key in put operation"
+        );
+
+        let mut batch = WriteBatch::new();
+        let result = std::panic::catch_unwind(move || {
+            batch.delete(b"");
+        });
+        assert!(
+            result.is_err(),
+            "Expected panic when using empty key in delete operation"
         );
     }
 
+    /// Test that batch writes are atomic. Test does the following:
+    ///
+    /// - A set of, say 100 keys, 1-100
+    /// - Two tasks writing, one writing value to be same key, and other
+    ///   writing it to be key*2.
+    /// - We wait for both to complete.
+    /// - Assert that either all values are same as key, or all values key*2.
+    /// - Repeat above loop few times.
+    ///
+    /// _Note: This test is non-deterministic because it depends on the async
+    /// runtime to schedule the tasks in a way that the writes are concurrent._
     #[tokio::test]
-    #[cfg(feature = "wal_disable")]
-    async fn test_get_with_durability_level_when_wal_disabled() {
+    async fn test_concurrent_batch_writes_consistency() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let mut options = test_db_options(0, 1024 * 1024, None);
-        options.wal_enabled = false;
-        let db = Db::builder("/tmp/test_kv_store", object_store)
-            .with_settings(options)
-            .build()
-            .await
-            .unwrap();
-        let put_options = PutOptions::default();
-        let write_options = WriteOptions {
-            await_durable: false,
-            ..Default::default()
-        };
-        let get_memory_options = ReadOptions::new().with_durability_filter(Memory);
-        let get_remote_options = ReadOptions::new().with_durability_filter(Remote);
-
-        db.put_with_options(b"foo", b"bar", &put_options, &write_options)
-            .await
-            .unwrap();
-        let val_bytes = Bytes::copy_from_slice(b"bar");
-        assert_eq!(
-            None,
-            db.get_with_options(b"foo", &get_remote_options)
-                .await
-                .unwrap()
-        );
-        assert_eq!(
-            Some(val_bytes.clone()),
-            db.get_with_options(b"foo", &get_memory_options)
-                .await
-                .unwrap()
-        );
-        db.flush().await.unwrap();
-        assert_eq!(
-            Some(val_bytes.clone()),
-            db.get_with_options(b"foo", &get_remote_options)
-                .await
-                .unwrap()
-        );
-        assert_eq!(
-            Some(val_bytes.clone()),
-            db.get_with_options(b"foo", &get_memory_options)
+        let kv_store = Arc::new(
+            Db::builder("/tmp/test_concurrent_kv_store", object_store)
+                .with_settings(test_db_options(
+                    0,
+                    1024,
+                    // Enable compactor to prevent l0 from filling up and
+                    // applying backpressure indefinitely.
+                    Some(CompactorOptions {
+                        poll_interval: Duration::from_millis(100),
+                        max_sst_size: 256,
+                        max_concurrent_compactions: 1,
+                        manifest_update_timeout: Duration::from_secs(300),
+                        ..Default::default()
+                    }),
+                ))
+                .build()
                 .await
-                .unwrap()
+                .unwrap(),
         );
+
+        const NUM_KEYS: usize = 100;
+        const NUM_ROUNDS: usize = 20;
+
+        for _ in 0..NUM_ROUNDS {
+            // Write two tasks that write to the same keys
+            let task1 = {
+                let store = kv_store.clone();
+                tokio::spawn(async move {
+                    let mut batch = WriteBatch::new();
+                    for key in 1..=NUM_KEYS {
+                        batch.put(key.to_be_bytes(), key.to_be_bytes());
+                    }
+                    store.write(batch).await.expect("write batch failed");
+                })
+            };
+
+            let task2 = {
+                let store = kv_store.clone();
+                tokio::spawn(async move {
+                    let mut batch = WriteBatch::new();
+                    for key in 1..=NUM_KEYS {
+                        let value = (key * 2).to_be_bytes();
+                        batch.put(key.to_be_bytes(), value);
+                    }
+                    store.write(batch).await.expect("write batch failed");
+                })
+            };
+
+            // Wait for both tasks to complete
+            join_all(vec![task1, task2]).await;
+
+            // Ensure consistency: all values must be either key or key * 2
+            let mut all_key = true;
+            let mut all_key2 = true;
+
+            for key in 1..=NUM_KEYS {
+                let value = kv_store.get(key.to_be_bytes()).await.unwrap();
+                let value = value.expect("Value should exist");
+
+                if value.as_ref() != key.to_be_bytes() {
+                    all_key = false;
+                }
+                if value.as_ref() != (key * 2).to_be_bytes() {
+                    all_key2 = false;
+                }
+            }
+
+            // Assert that the result is consistent: either all key or all key * 2
+            assert!(
+                all_key || all_key2,
+                "Inconsistent state: not all values match either key or key * 2"
+            );
+        }
+
+        kv_store.close().await.unwrap();
     }
 
     #[tokio::test]
     #[cfg(feature = "wal_disable")]
-    async fn test_find_with_multiple_repeated_keys() {
+    async fn test_disable_wal_after_wal_enabled() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let mut options = test_db_options(0, 1024 * 1024, None);
-        options.wal_enabled = false;
-        let db = Db::builder("/tmp/test_kv_store", object_store)
+        let path = "/tmp/test_kv_store";
+        // open a db and write a wal entry
+        let options = test_db_options(0, 32, None);
+        let db = Db::builder(path, object_store.clone())
             .with_settings(options)
             .build()
             .await
             .unwrap();
+        db.put(&[b'a'; 4], &[b'j'; 4]).await.unwrap();
+        db.put(&[b'b'; 4], &[b'k'; 4]).await.unwrap();
+        db.close().await.unwrap();
 
-        // write enough rows with the same key that we yield an L0 SST with multiple blocks
-        let mut last_val: String = "foo".to_string();
-        for x in 0..4096 {
-            let val = format!("val{}", x);
-            db.put_with_options(
-                b"key",
-                val.as_bytes(),
-                &PutOptions {
-                    ttl: Default::default(),
-                },
-                &WriteOptions {
-                    await_durable: false,
-                    ..Default::default()
-                },
-            )
+        // open a db with wal disabled and write a memtable
+        let mut options = test_db_options(0, 32, None);
+        options.wal_enabled = false;
+        let db = Db::builder(path, object_store.clone())
+            .with_settings(options.clone())
+            .build()
             .await
             .unwrap();
-            last_val = val;
-            if db
-                .inner
-                .state
-                .write()
-                .memtable()
-                .metadata()
-                .entries_size_in_bytes
-                > (SsTableFormat::default().block_size * 3)
-            {
-                break;
-            }
-        }
-        assert_eq!(
-            Some(Bytes::copy_from_slice(last_val.as_bytes())),
-            db.get_with_options(b"key", &ReadOptions::new().with_durability_filter(Memory))
-                .await
-                .unwrap()
-        );
-        db.flush().await.unwrap();
+        db.delete_with_options(
+            &[b'b'; 4],
+            &WriteOptions {
+                await_durable: false,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        db.put(&[b'a'; 4], &[b'z'; 64]).await.unwrap();
+        db.close().await.unwrap();
 
-        let state = db.inner.state.read().view();
-        assert_eq!(1, state.state.manifest.value.core.tree.l0.len());
-        let view = state.state.manifest.value.core.tree.l0.front().unwrap();
-        let index = db
-            .inner
-            .table_store
-            .read_index(&view.sst, true)
+        // ensure we don't overwrite the values we just put on a reload
+        let db = Db::builder(path, object_store.clone())
+            .with_settings(options.clone())
+            .build()
             .await
             .unwrap();
-        assert!(!index.borrow().block_meta().is_empty());
-        assert_eq!(
-            Some(Bytes::copy_from_slice(last_val.as_bytes())),
-            db.get(b"key").await.unwrap()
-        );
-        db.close().await.unwrap();
+        let val = db.get(&[b'a'; 4]).await.unwrap();
+        assert_eq!(val.unwrap(), Bytes::copy_from_slice(&[b'z'; 64]));
+        let val = db.get(&[b'b'; 4]).await.unwrap();
+        assert!(val.is_none());
     }
 
+    #[cfg(feature = "wal_disable")]
     #[tokio::test]
-    async fn test_get_with_object_store_cache_metrics() {
+    async fn test_wal_disabled() {
+        use crate::{test_utils::assert_iterator, types::RowEntry};
+
+        let clock = Arc::new(MockSystemClock::new());
+        let mut options = test_db_options(0, 300, None);
+        options.wal_enabled = false;
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let mut opts = test_db_options(0, 1024, None);
-        let temp_dir = tempfile::Builder::new()
-            .prefix("objstore_cache_test_")
-            .tempdir()
+        let path = Path::from("/tmp/test_kv_store");
+        let sst_format = SsTableFormat::default();
+        let table_store = Arc::new(TableStore::new(
+            ObjectStores::new(object_store.clone(), None),
+            sst_format,
+            path.clone(),
+            None,
+        ));
+        let db = Db::builder(path.clone(), object_store.clone())
+            .with_settings(options)
+            .with_system_clock(clock.clone())
+            .build()
+            .await
+            .unwrap();
+        let manifest_store = Arc::new(ManifestStore::new(&path, object_store.clone()));
+        let mut stored_manifest =
+            StoredManifest::load(manifest_store.clone(), Arc::new(DefaultSystemClock::new()))
+                .await
+                .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+
+        db.put_with_options(
+            &[b'a'; 32],
+            &[b'j'; 32],
+            &PutOptions::default(),
+            &write_options,
+        )
+        .await
+        .unwrap();
+        db.delete_with_options(&[b'b'; 31], &write_options)
+            .await
             .unwrap();
 
-        opts.object_store_cache_options.root_folder = Some(temp_dir.keep());
-        opts.object_store_cache_options.part_size_bytes = 1024;
-        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
-        let kv_store = Db::builder(
-            "/tmp/test_kv_store_with_cache_metrics",
-            object_store.clone(),
+        // ensure the memtable's size is greater than l0_sst_size_bytes, or
+        // the memtable will not be flushed to l0, and the test will hang
+        // at this put_with_options call.
+        let write_options = WriteOptions {
+            await_durable: true,
+            ..Default::default()
+        };
+        clock.set(10);
+        db.put_with_options(
+            &[b'c'; 32],
+            &[b'l'; 32],
+            &PutOptions::default(),
+            &write_options,
         )
-        .with_settings(opts)
-        .with_metrics_recorder(metrics_recorder.clone())
-        .build()
         .await
         .unwrap();
 
-        let access_count0 = lookup_metric(&metrics_recorder, PART_ACCESS_COUNT).unwrap();
-        let key = b"test_key";
-        let value = b"test_value";
-        kv_store.put(key, value).await.unwrap();
-        kv_store.flush().await.unwrap();
+        let state = wait_for_manifest_condition(
+            &mut stored_manifest,
+            |s| !s.tree.l0.is_empty(),
+            Duration::from_secs(30),
+        )
+        .await;
+        assert_eq!(state.tree.l0.len(), 1);
 
-        let got = kv_store.get(key).await.unwrap();
-        let access_count1 = lookup_metric(&metrics_recorder, PART_ACCESS_COUNT).unwrap();
-        assert_eq!(got, Some(Bytes::from_static(value)));
-        assert!(access_count1 > 0);
-        assert!(access_count1 >= access_count0);
-        assert!(lookup_metric(&metrics_recorder, PART_HIT_COUNT).unwrap() >= 1);
+        let l0 = state.tree.l0.front().unwrap();
+        let mut iter = SstIterator::new_borrowed_initialized(
+            ..,
+            l0,
+            table_store.clone(),
+            SstIteratorOptions::default(),
+        )
+        .await
+        .unwrap()
+        .expect("Expected Some(iter) but got None");
+        assert_iterator(
+            &mut iter,
+            vec![
+                RowEntry::new_value(&[b'a'; 32], &[b'j'; 32], 1).with_create_ts(0),
+                RowEntry::new_tombstone(&[b'b'; 31], 2).with_create_ts(0),
+                RowEntry::new_value(&[b'c'; 32], &[b'l'; 32], 3).with_create_ts(10),
+            ],
+        )
+        .await;
     }
 
     #[tokio::test]
-    async fn test_db_records_remote_object_store_reads_but_not_cache_hits() {
-        // given:
+    async fn test_put_flushes_memtable() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let mut opts = test_db_options(0, 1024, None);
-        let temp_dir = tempfile::Builder::new()
-            .prefix("objstore_metrics_test_")
-            .tempdir()
-            .unwrap();
-
-        opts.object_store_cache_options.root_folder = Some(temp_dir.keep());
-        opts.object_store_cache_options.part_size_bytes = 1024;
-        let path = "/tmp/test_db_records_remote_object_store_reads_but_not_cache_hits";
-        let kv_store = Db::builder(path, object_store.clone())
-            .with_settings(opts)
-            .build()
-            .await
-            .unwrap();
-
-        kv_store.put(b"test_key", b"test_value").await.unwrap();
-        kv_store.flush().await.unwrap();
-        kv_store
-            .flush_with_options(FlushOptions {
-                flush_type: FlushType::MemTable,
-            })
-            .await
-            .unwrap();
-        kv_store.close().await.unwrap();
-
-        let mut reopen_opts = test_db_options(0, 1024, None);
-        let reopen_temp_dir = tempfile::Builder::new()
-            .prefix("objstore_metrics_reopen_")
-            .tempdir()
-            .unwrap();
-        reopen_opts.object_store_cache_options.root_folder = Some(reopen_temp_dir.keep());
-        reopen_opts.object_store_cache_options.part_size_bytes = 1024;
+        let path = "/tmp/test_kv_store";
         let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
-        let reopened = Db::builder(path, object_store)
-            .with_settings(reopen_opts)
+        let kv_store = Db::builder(path, object_store.clone())
+            .with_settings(test_db_options(0, 256, None))
             .with_metrics_recorder(metrics_recorder.clone())
             .build()
             .await
             .unwrap();
+        let manifest_store = Arc::new(ManifestStore::new(&Path::from(path), object_store.clone()));
+        let mut stored_manifest =
+            StoredManifest::load(manifest_store.clone(), Arc::new(DefaultSystemClock::new()))
+                .await
+                .unwrap();
+        let sst_format = SsTableFormat {
+            min_filter_keys: 10,
+            ..SsTableFormat::default()
+        };
+        let table_store = Arc::new(TableStore::new(
+            ObjectStores::new(object_store.clone(), None),
+            sst_format,
+            path,
+            None,
+        ));
 
-        let requests_before =
-            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "get");
+        // Write data a few times such that each loop results in a memtable flush
+        let mut last_wal_id = 0;
+        for i in 0..3 {
+            let key = [b'a' + i; 16];
+            let value = [b'b' + i; 50];
+            kv_store.put(&key, &value).await.unwrap();
+            let key = [b'j' + i; 16];
+            let value = [b'k' + i; 50];
+            kv_store.put(&key, &value).await.unwrap();
+            let db_state = wait_for_manifest_condition(
+                &mut stored_manifest,
+                |s| s.replay_after_wal_id > last_wal_id,
+                Duration::from_secs(30),
+            )
+            .await;
 
-        // when:
-        let _got = reopened.get(b"test_key").await.unwrap();
-        let requests_after_first =
-            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "get");
-        let got = reopened.get(b"test_key").await.unwrap();
-        let requests_after_second =
-            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "get");
+            // 2 wal per iteration.
+            assert_eq!(db_state.replay_after_wal_id, (i as u64) * 2 + 2);
+            last_wal_id = db_state.replay_after_wal_id
+        }
 
-        // then:
-        assert!(requests_after_first > requests_before);
-        assert_eq!(got, Some(Bytes::from_static(b"test_value")));
-        assert_eq!(requests_after_second, requests_after_first);
-        assert_eq!(
-            lookup_object_store_op_histogram_count(&metrics_recorder, "db", "main", "get"),
-            requests_after_first as u64
-        );
-        reopened.close().await.unwrap();
+        let manifest = stored_manifest.refresh().await.unwrap();
+        let l0 = &manifest.core.tree.l0;
+        assert_eq!(l0.len(), 3);
+        let sst_iter_options = SstIteratorOptions::default();
+
+        for i in 0u8..3u8 {
+            let sst1 = l0.get(2 - i as usize).unwrap();
+            let mut iter = SstIterator::new_borrowed_initialized(
+                ..,
+                sst1,
+                table_store.clone(),
+                sst_iter_options.clone(),
+            )
+            .await
+            .unwrap()
+            .expect("Expected Some(iter) but got None");
+            let kv: KeyValue = iter.next().await.unwrap().unwrap().into();
+            assert_eq!(kv.key.as_ref(), [b'a' + i; 16]);
+            assert_eq!(kv.value.as_ref(), [b'b' + i; 50]);
+            let kv: KeyValue = iter.next().await.unwrap().unwrap().into();
+            assert_eq!(kv.key.as_ref(), [b'j' + i; 16]);
+            assert_eq!(kv.value.as_ref(), [b'k' + i; 50]);
+            let kv = iter.next().await.unwrap().map(KeyValue::from);
+            assert!(kv.is_none());
+        }
+        assert!(lookup_metric(&metrics_recorder, IMMUTABLE_MEMTABLE_FLUSHES).is_some_and(|v| v > 0));
     }
 
     #[tokio::test]
-    async fn test_db_records_main_and_wal_object_store_requests_separately() {
-        // given:
-        let main_object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let wal_object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
-        let path = "/tmp/test_db_records_main_and_wal_object_store_requests_separately";
-        let db = Db::builder(path, main_object_store)
-            .with_settings(test_db_options(0, 1024, None))
-            .with_wal_object_store(wal_object_store)
-            .with_metrics_recorder(metrics_recorder.clone())
+    async fn test_flush_records_entry_and_delete_counts_in_manifest() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = "/tmp/test_flush_records_delete_counts";
+        let db = Db::builder(path, object_store.clone())
+            .with_settings(test_db_options(0, 1024 * 1024, None))
             .build()
             .await
             .unwrap();
-
-        let wal_before =
-            lookup_object_store_op_request_count(&metrics_recorder, "db", "wal", "put");
-        let main_before =
-            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "put");
-        let wal_hist_before =
-            lookup_object_store_op_histogram_count(&metrics_recorder, "db", "wal", "put");
-        let main_hist_before =
-            lookup_object_store_op_histogram_count(&metrics_recorder, "db", "main", "put");
-
-        // when:
-        db.put(b"key", b"value").await.unwrap();
-        db.flush().await.unwrap();
+        db.put(b"a", b"1").await.unwrap();
+        db.put(b"b", b"2").await.unwrap();
+        db.delete(b"c").await.unwrap();
         db.flush_with_options(FlushOptions {
             flush_type: FlushType::MemTable,
         })
         .await
         .unwrap();
 
-        let wal_after = lookup_object_store_op_request_count(&metrics_recorder, "db", "wal", "put");
-        let main_after =
-            lookup_object_store_op_request_count(&metrics_recorder, "db", "main", "put");
-        let wal_hist_after =
-            lookup_object_store_op_histogram_count(&metrics_recorder, "db", "wal", "put");
-        let main_hist_after =
-            lookup_object_store_op_histogram_count(&metrics_recorder, "db", "main", "put");
-
-        // then:
-        assert!(wal_after > wal_before);
-        assert!(main_after > main_before);
-        assert!(wal_hist_after > wal_hist_before);
-        assert!(main_hist_after > main_hist_before);
-        db.close().await.unwrap();
+        let manifest_store = Arc::new(ManifestStore::new(&Path::from(path), object_store));
+        let stored_manifest =
+            StoredManifest::load(manifest_store, Arc::new(DefaultSystemClock::new()))
+                .await
+                .unwrap();
+        let l0 = &stored_manifest.db_state().tree.l0;
+        assert_eq!(l0.len(), 1);
+        assert_eq!(l0[0].sst.info.num_entries, 3);
+        assert_eq!(l0[0].sst.info.num_deletes, 1);
     }
 
-    async fn test_object_store_cache_helper(
-        cache_puts_enabled: bool,
-        db_path: &str,
-        expected_cache_parts: Vec<(&str, usize)>,
-    ) -> (Arc<CachedObjectStore>, Db) {
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let mut opts = test_db_options(0, 1024, None);
-        // disable manifest polling to avoid caching manifests non-deterministically
-        opts.manifest_poll_interval = Duration::from_millis(u64::MAX);
-        let temp_dir = tempfile::Builder::new()
-            .prefix("objstore_cache_test_")
-            .tempdir()
-            .unwrap();
-
-        let recorder = MetricsRecorderHelper::noop();
-        let cache_stats = Arc::new(CachedObjectStoreStats::new(&recorder));
-        let part_size = 1024;
-        info!("temp_dir: {:?}", temp_dir.path());
+    #[tokio::test]
+    async fn test_put_flushes_memtable_after_max_wal_flushes() {
+        const MAX_WAL_FLUSHES_BEFORE_L0_FLUSH: u64 = 4096;
 
-        let cache_storage = Arc::new(FsCacheStorage::new(
-            temp_dir.keep(),
-            None,
-            None,
-            cache_stats.clone(),
-            Arc::new(DefaultSystemClock::new()),
-            Arc::new(DbRand::default()),
-            1000,
-        ));
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = "/tmp/test_flush_memtable_max_wal_flushes";
 
-        let cached_object_store = CachedObjectStore::new(
-            object_store.clone(),
-            cache_storage,
-            part_size,
-            cache_puts_enabled,
-            cache_stats.clone(),
-        )
-        .unwrap();
+        let mut settings = test_db_options(0, usize::MAX, None);
+        settings.flush_interval = None; // Disable flushing
+        settings.max_wal_flushes_before_l0_flush = MAX_WAL_FLUSHES_BEFORE_L0_FLUSH;
 
-        let kv_store = Db::builder(db_path, cached_object_store.clone())
-            .with_settings(opts)
+        let kv_store = Db::builder(path, object_store.clone())
+            .with_settings(settings)
             .build()
             .await
             .unwrap();
-        // `cache_puts_enabled` won't take effect until after the first
-        // `resolve_root`. Call head to force the root to be resolved.
-        // Use the first WAL entry since it's guaranteed to exist because
-        // it's the fencing write.
-        cached_object_store
-            .head(&object_store::path::Path::from(format!(
-                "{}/wal/00000000000000000001.sst",
-                db_path
-            )))
-            .await
-            .unwrap();
-        let key = b"test_key";
-        let value = b"test_value";
-        kv_store.put(key, value).await.unwrap();
-        kv_store.flush().await.unwrap();
 
-        // Verify cache behavior
-        for (path, expected_parts) in expected_cache_parts {
-            let entry = cached_object_store
-                .cache_storage
-                .entry(&object_store::path::Path::from(path), part_size);
-            assert_eq!(
-                entry.cached_parts().await.unwrap().len(),
-                expected_parts,
-                "Path: {}",
-                path
-            );
+        let manifest_store = Arc::new(ManifestStore::new(&Path::from(path), object_store.clone()));
+        let mut stored_manifest =
+            StoredManifest::load(manifest_store.clone(), Arc::new(DefaultSystemClock::new()))
+                .await
+                .unwrap();
+
+        let write_options: WriteOptions = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        let put_options = PutOptions::default();
+
+        for i in 0..(MAX_WAL_FLUSHES_BEFORE_L0_FLUSH - 1) {
+            let key = format!("key{:08}", i);
+            kv_store
+                .put_with_options(key.as_bytes(), b"v", &put_options, &write_options)
+                .await
+                .unwrap();
+            kv_store.flush().await.unwrap();
         }
 
-        (cached_object_store, kv_store)
-    }
+        // Verify WALs flushes.
+        let wal_id = kv_store.inner.wal_buffer.recent_flushed_wal_id();
+        assert_eq!(wal_id, MAX_WAL_FLUSHES_BEFORE_L0_FLUSH); // account for the empty WAL written for fencing
 
-    #[tokio::test]
-    async fn test_get_with_object_store_cache_stored_files() {
-        let expected_cache_parts =
-            vec![
-            ("tmp/test_kv_store_with_cache_stored_files/manifest/00000000000000000001.manifest", 0),
-            ("tmp/test_kv_store_with_cache_stored_files/manifest/00000000000000000002.manifest", 0),
-            // 1 part is cached because of wal_replay after fencing (which reads the SST, thereby caching it)
-            ("tmp/test_kv_store_with_cache_stored_files/wal/00000000000000000001.sst", 1),
-            ("tmp/test_kv_store_with_cache_stored_files/wal/00000000000000000002.sst", 0),
-        ];
+        // Verify no memtable was frozen or L0 flush happened.
+        {
+            let guard = kv_store.inner.state.read();
+            assert!(guard.state().imm_memtable.is_empty());
+            assert_eq!(guard.state().core().tree.l0.len(), 0);
+        }
 
-        let (_cached_object_store, kv_store) = test_object_store_cache_helper(
-            false, // cache_puts disabled
-            "/tmp/test_kv_store_with_cache_stored_files",
-            expected_cache_parts,
+        // This put() triggers a freeze.
+        let key = format!("key{:08}", MAX_WAL_FLUSHES_BEFORE_L0_FLUSH - 1);
+        kv_store
+            .put_with_options(key.as_bytes(), b"v", &put_options, &write_options)
+            .await
+            .unwrap();
+        // Flush the WAL so the manifest writer can proceed (flush_interval is
+        // disabled in this test, so there is no periodic WAL flush).
+        kv_store.flush().await.unwrap();
+
+        // Verify that the WAL count threshold triggered a memtable freeze and L0 flush.
+        // replay_after_wal_id should have advanced to the threshold, and there should
+        // be exactly one L0 SST.
+        let db_state = wait_for_manifest_condition(
+            &mut stored_manifest,
+            |s| s.replay_after_wal_id == MAX_WAL_FLUSHES_BEFORE_L0_FLUSH,
+            Duration::from_secs(30),
         )
         .await;
+        assert_eq!(db_state.tree.l0.len(), 1);
 
-        kv_store.close().await.unwrap();
-    }
+        // Run MAX_WAL_FLUSHES_BEFORE_L0_FLUSH more put()/flush() cycles
+        // and see if the threshold triggers again.
+        for i in 0..(MAX_WAL_FLUSHES_BEFORE_L0_FLUSH - 1) {
+            let key = format!("key{:08}", i);
+            kv_store
+                .put_with_options(key.as_bytes(), b"v", &put_options, &write_options)
+                .await
+                .unwrap();
+            kv_store.flush().await.unwrap();
+        }
+
+        // Verify no more memtables were frozen or L0 flush happened.
+        {
+            let guard = kv_store.inner.state.read();
+            assert_eq!(guard.state().core().tree.l0.len(), 1);
+        }
 
-    #[tokio::test]
-    async fn test_get_with_object_store_cache_put_caching_enabled() {
-        let expected_cache_parts =
-            vec![
-            // not cached because manifests are put before the root is resolved, which is when
-            // `cache_puts_enabled` starts taking effect.
-            ("tmp/test_kv_store_with_put_cache_enabled/manifest/00000000000000000001.manifest", 0),
-            ("tmp/test_kv_store_with_put_cache_enabled/manifest/00000000000000000002.manifest", 0),
-            // 1 part is cached because of wal_replay after fencing (which reads the SST, thereby caching it)
-            ("tmp/test_kv_store_with_put_cache_enabled/wal/00000000000000000001.sst", 1),
-            // 1 part is cached because the put with cache_puts enabled should cache the test_key put
-            ("tmp/test_kv_store_with_put_cache_enabled/wal/00000000000000000002.sst", 1),
-        ];
+        // This put() triggers a freeze.
+        let key = format!("key{:08}", MAX_WAL_FLUSHES_BEFORE_L0_FLUSH);
+        kv_store
+            .put_with_options(key.as_bytes(), b"v", &put_options, &write_options)
+            .await
+            .unwrap();
+        kv_store.flush().await.unwrap();
 
-        let (_cached_object_store, kv_store) = test_object_store_cache_helper(
-            true, // cache_puts enabled
-            "/tmp/test_kv_store_with_put_cache_enabled",
-            expected_cache_parts,
+        // Wait for the flush to happen.
+        let db_state = wait_for_manifest_condition(
+            &mut stored_manifest,
+            |s| s.replay_after_wal_id == MAX_WAL_FLUSHES_BEFORE_L0_FLUSH * 2,
+            Duration::from_secs(30),
         )
         .await;
+        assert_eq!(db_state.tree.l0.len(), 2); // We should have two L0 flushes.
 
         kv_store.close().await.unwrap();
     }
 
-    async fn build_database_from_table(
-        table: &BTreeMap<Bytes, Bytes>,
-        db_options: Settings,
-        await_durable: bool,
-    ) -> Db {
+    #[tokio::test]
+    async fn test_flush_memtable_with_wal_enabled() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db = Db::builder("/tmp/test_kv_store", object_store)
-            .with_settings(db_options)
+        let path = "/tmp/test_flush_with_options";
+        let mut options = test_db_options(0, 256, None);
+        options.flush_interval = Some(Duration::from_secs(u64::MAX));
+        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
+        let kv_store = Db::builder(path, object_store.clone())
+            .with_settings(options)
+            .with_metrics_recorder(metrics_recorder.clone())
             .build()
             .await
             .unwrap();
 
-        test_utils::seed_database(&db, table, false).await.unwrap();
-
-        if await_durable {
-            db.flush().await.unwrap();
-        }
-
-        db
-    }
-
-    #[tokio::test]
-    async fn test_should_allow_iterating_behind_box_dyn() {
-        #[async_trait]
-        trait IteratorSupplier {
-            async fn iterator(&self) -> Box<dyn IteratorTrait>;
-        }
-
-        struct DbHolder {
-            db: Db,
-        }
-
-        #[async_trait]
-        impl IteratorSupplier for DbHolder {
-            async fn iterator(&self) -> Box<dyn IteratorTrait> {
-                let range = BytesRange::new_empty();
-                let iter = self
-                    .db
-                    .inner
-                    .scan_with_options(range, &ScanOptions::default())
-                    .await
-                    .unwrap();
-                Box::new(iter)
-            }
-        }
-
-        #[async_trait]
-        trait IteratorTrait {
-            async fn next(&mut self) -> Result<Option<KeyValue>, crate::Error>;
-        }
-
-        #[async_trait]
-        impl IteratorTrait for DbIterator {
-            async fn next(&mut self) -> Result<Option<KeyValue>, crate::Error> {
-                DbIterator::next(self).await
-            }
-        }
+        let manifest_store = Arc::new(ManifestStore::new(&Path::from(path), object_store.clone()));
+        let mut stored_manifest =
+            StoredManifest::load(manifest_store.clone(), Arc::new(DefaultSystemClock::new()))
+                .await
+                .unwrap();
+        let sst_format = SsTableFormat {
+            min_filter_keys: 10,
+            ..SsTableFormat::default()
+        };
+        let table_store = Arc::new(TableStore::new(
+            ObjectStores::new(object_store.clone(), None),
+            sst_format,
+            path,
+            None,
+        ));
 
-        let db_options = test_db_options(0, 1024, None);
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let db = Db::builder("/tmp/test_kv_store", object_store)
-            .with_settings(db_options)
-            .build()
+        // Write some data to populate the memtable
+        let key1 = b"test_key_1";
+        let value1 = b"test_value_1";
+        kv_store
+            .put_with_options(
+                key1,
+                value1,
+                &PutOptions::default(),
+                &WriteOptions {
+                    await_durable: false,
+                    ..Default::default()
+                },
+            )
             .await
             .unwrap();
-        let db_holder = DbHolder { db };
-        let mut boxed = db_holder.iterator().await;
-        let next = boxed.next().await;
-        assert_eq!(next.unwrap(), None);
-    }
 
-    async fn assert_records_in_range(
-        table: &BTreeMap<Bytes, Bytes>,
-        db: &Db,
-        scan_options: &ScanOptions,
-        range: BytesRange,
-    ) {
-        let mut iter = db
-            .inner
-            .scan_with_options(range.clone(), scan_options)
+        let key2 = b"test_key_2";
+        let value2 = b"test_value_2";
+        kv_store
+            .put_with_options(
+                key2,
+                value2,
+                &PutOptions::default(),
+                &WriteOptions {
+                    await_durable: false,
+                    ..Default::default()
+                },
+            )
             .await
             .unwrap();
-        test_utils::assert_ranged_db_scan(table, range, IterationOrder::Ascending, &mut iter).await;
-    }
 
-    #[test]
-    fn test_scan_returns_records_in_range() {
-        let mut runner = new_proptest_runner(None);
-        let table = sample::table(runner.rng(), 1000, 5);
+        // Get initial state
+        let initial_manifest = stored_manifest.refresh().await.unwrap();
+        let initial_l0_count = initial_manifest.core.tree.l0.len();
 
-        let runtime = Runtime::new().unwrap();
-        let db_options = test_db_options(0, 1024, None);
-        let db = runtime.block_on(build_database_from_table(&table, db_options, true));
+        let initial_flush_count =
+            lookup_metric(&metrics_recorder, IMMUTABLE_MEMTABLE_FLUSHES).unwrap();
 
-        runner
-            .run(&arbitrary::nonempty_range(10), |range| {
-                runtime.block_on(assert_records_in_range(
-                    &table,
-                    &db,
-                    &ScanOptions::default(),
-                    range,
-                ));
-                Ok(())
+        // Flush memtable using flush_with_options
+        kv_store
+            .flush_with_options(FlushOptions {
+                flush_type: FlushType::MemTable,
             })
+            .await
             .unwrap();
-    }
 
-    fn new_proptest_runner(rng_seed: Option<[u8; 32]>) -> TestRunner {
-        proptest_util::runner::new(file!(), rng_seed)
-    }
+        // Wait for the flush to complete and manifest to be updated
+        let db_state = wait_for_manifest_condition(
+            &mut stored_manifest,
+            |s| s.tree.l0.len() > initial_l0_count,
+            Duration::from_secs(30),
+        )
+        .await;
 
-    #[test]
-    fn test_scan_returns_uncommitted_records_if_read_level_uncommitted() {
-        let mut runner = new_proptest_runner(None);
-        let table = sample::table(runner.rng(), 1000, 5);
+        // Verify that a new SST was created in L0
+        assert_eq!(db_state.tree.l0.len(), initial_l0_count + 1);
 
-        let runtime = Runtime::new().unwrap();
-        let mut db_options = test_db_options(0, 1024, None);
-        db_options.flush_interval = Some(Duration::from_secs(5));
-        let db = runtime.block_on(build_database_from_table(&table, db_options, false));
+        // Verify that the flush metrics were updated
+        let final_flush_count =
+            lookup_metric(&metrics_recorder, IMMUTABLE_MEMTABLE_FLUSHES).unwrap();
+        assert!(final_flush_count > initial_flush_count);
 
-        runner
-            .run(&arbitrary::nonempty_range(10), |range| {
-                let scan_options = ScanOptions {
-                    durability_filter: Memory,
-                    ..ScanOptions::default()
-                };
-                runtime.block_on(assert_records_in_range(&table, &db, &scan_options, range));
-                Ok(())
-            })
+        // Verify that the WAL was also flushed since we guarantee
+        // memtable data is persisted in the WAL prior to L0 flush.
+        let recent_flushed_wal_id = kv_store.inner.wal_buffer.recent_flushed_wal_id();
+        assert_eq!(recent_flushed_wal_id, 2);
+
+        // Verify that the data is still accessible after flush
+        let retrieved_value1 = kv_store.get(key1).await.unwrap().unwrap();
+        assert_eq!(retrieved_value1.as_ref(), value1);
+
+        let retrieved_value2 = kv_store.get(key2).await.unwrap().unwrap();
+        assert_eq!(retrieved_value2.as_ref(), value2);
+
+        // Verify the data exists in the newly created SST
+        let latest_sst = db_state.tree.l0.back().unwrap();
+        let sst_iter_options = SstIteratorOptions::default();
+        let mut iter = SstIterator::new_borrowed_initialized(
+            ..,
+            latest_sst,
+            table_store.clone(),
+            sst_iter_options,
+        )
+        .await
+        .unwrap()
+        .expect("Expected Some(iter) but got None");
+
+        // Collect all key-value pairs from the SST
+        let mut found_keys = std::collections::HashSet::new();
+        while let Some(kv) = iter.next().await.unwrap().map(KeyValue::from) {
+            found_keys.insert(kv.key.to_vec());
+        }
+
+        // Verify our keys are in the SST
+        assert!(found_keys.contains(key1.as_slice()));
+        assert!(found_keys.contains(key2.as_slice()));
+    }
+
+    #[tokio::test]
+    async fn test_trim_memory_flushes_memtables_and_is_idempotent() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = "/tmp/test_trim_memory";
+        let mut options = test_db_options(0, 1024 * 1024, None);
+        options.flush_interval = Some(Duration::from_secs(u64::MAX));
+        let kv_store = Db::builder(path, object_store.clone())
+            .with_settings(options)
+            .build()
+            .await
             .unwrap();
-    }
-
-    #[test]
-    fn test_seek_outside_of_range_returns_invalid_argument() {
-        let mut runner = new_proptest_runner(None);
-        let table = sample::table(runner.rng(), 1000, 10);
 
-        let runtime = Runtime::new().unwrap();
-        let db_options = test_db_options(0, 1024, None);
-        let db = runtime.block_on(build_database_from_table(&table, db_options, true));
+        // Nothing to release on a fresh database
+        assert_eq!(kv_store.trim_memory().await.unwrap(), 0);
 
-        runner
-            .run(
-                &(arbitrary::nonempty_bytes(10), arbitrary::rng()),
-                |(arbitrary_key, mut rng)| {
-                    runtime.block_on(assert_out_of_bound_seek_returns_invalid_argument(
-                        &db,
-                        &mut rng,
-                        arbitrary_key,
-                    ));
-                    Ok(())
+        kv_store
+            .put_with_options(
+                b"key1",
+                b"value1",
+                &PutOptions::default(),
+                &WriteOptions {
+                    await_durable: false,
+                    ..Default::default()
                 },
             )
+            .await
             .unwrap();
 
-        async fn assert_out_of_bound_seek_returns_invalid_argument(
-            db: &Db,
-            rng: &mut TestRng,
-            arbitrary_key: Bytes,
-        ) {
-            let mut iter = db
-                .scan_with_options(..arbitrary_key.clone(), &ScanOptions::default())
-                .await
-                .unwrap();
-
-            let lower_bounded_range = BytesRange::from(arbitrary_key.clone()..);
-            let value = sample::bytes_in_range(rng, &lower_bounded_range);
-            let err = iter.seek(value.clone()).await.unwrap_err();
-            assert!(
-                err.to_string()
-                    .contains("cannot seek to a key outside the iterator range"),
-                "{}",
-                err
-            );
-
-            let mut iter = db
-                .scan_with_options(arbitrary_key.clone().., &ScanOptions::default())
-                .await
-                .unwrap();
-
-            let upper_bounded_range = BytesRange::from(..arbitrary_key.clone());
-            let value = sample::bytes_in_range(rng, &upper_bounded_range);
-            let err = iter.seek(value.clone()).await.unwrap_err();
-            assert!(
-                err.to_string()
-                    .contains("cannot seek to a key outside the iterator range"),
-                "{}",
-                err
-            );
+        // The memtable is well below l0_sst_size_bytes, but is flushed anyway
+        let released = kv_store.trim_memory().await.unwrap();
+        assert!(released > 0);
+        {
+            let guard = kv_store.inner.state.read();
+            assert!(guard.memtable().is_empty());
+            assert!(guard.state().imm_memtable.is_empty());
+            assert_eq!(guard.state().core().tree.l0.len(), 1);
         }
-    }
 
-    #[test]
-    fn test_seek_fast_forwards_iterator() {
-        let mut runner = new_proptest_runner(None);
-        let table = sample::table(runner.rng(), 1000, 10);
+        // A second call has nothing left to release
+        assert_eq!(kv_store.trim_memory().await.unwrap(), 0);
+        assert_eq!(
+            kv_store.get(b"key1").await.unwrap(),
+            Some(Bytes::from_static(b"value1"))
+        );
 
-        let runtime = Runtime::new().unwrap();
-        let db_options = test_db_options(0, 1024, None);
-        let db = runtime.block_on(build_database_from_table(&table, db_options, true));
+        kv_store.close().await.unwrap();
+    }
 
-        runner
-            .run(
-                &(arbitrary::nonempty_range(5), arbitrary::rng()),
-                |(range, mut rng)| {
-                    runtime.block_on(assert_seek_fast_forwards_iterator(
-                        &table, &db, &range, &mut rng,
-                    ));
-                    Ok(())
-                },
-            )
+    #[tokio::test]
+    async fn test_scan_as_of_returns_newest_version_created_by_ts() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let clock = Arc::new(MockSystemClock::new());
+        let mut options = test_db_options(0, 1024 * 1024, None);
+        options.flush_interval = None;
+        let db = Db::builder("/tmp/test_scan_as_of", object_store)
+            .with_settings(options)
+            .with_system_clock(clock.clone())
+            .build()
+            .await
             .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        let put_options = PutOptions::default();
+        let put = |key: &'static [u8], value: &'static [u8]| {
+            db.put_with_options(key, value, &put_options, &write_options)
+        };
 
-        async fn assert_seek_fast_forwards_iterator(
-            table: &BTreeMap<Bytes, Bytes>,
-            db: &Db,
-            scan_range: &BytesRange,
-            rng: &mut TestRng,
-        ) {
-            let mut iter = db
-                .inner
-                .scan_with_options(scan_range.clone(), &ScanOptions::default())
-                .await
-                .unwrap();
+        clock.set(1);
+        put(b"a", b"a1").await.unwrap();
+        put(b"b", b"b1").await.unwrap();
+        clock.set(5);
+        put(b"a", b"a5").await.unwrap();
+        db.delete_with_options(b"b", &write_options).await.unwrap();
+        // keeps the versions written by ts=5 from being dropped by the flush below
+        let _snapshot = db.snapshot().await.unwrap();
+        clock.set(10);
+        put(b"a", b"a10").await.unwrap();
+        put(b"b", b"b10").await.unwrap();
+        put(b"c", b"c10").await.unwrap();
+
+        async fn collect(db: &Db, ts: i64) -> Vec<(Bytes, Bytes)> {
+            let mut iter = db.scan_as_of::<&[u8], _>(.., ts).await.unwrap();
+            let mut kvs = Vec::new();
+            while let Some(kv) = iter.next().await.unwrap() {
+                kvs.push((kv.key, kv.value));
+            }
+            kvs
+        }
+        let kv =
+            |k: &'static [u8], v: &'static [u8]| (Bytes::from_static(k), Bytes::from_static(v));
 
-            let seek_key = sample::bytes_in_range(rng, scan_range);
-            iter.seek(seek_key.clone()).await.unwrap();
+        // "b" was deleted at 5 and "c" didn't exist yet
+        assert_eq!(collect(&db, 7).await, vec![kv(b"a", b"a5")]);
+        assert_eq!(
+            collect(&db, 1).await,
+            vec![kv(b"a", b"a1"), kv(b"b", b"b1")]
+        );
+        assert_eq!(
+            collect(&db, 10).await,
+            vec![kv(b"a", b"a10"), kv(b"b", b"b10"), kv(b"c", b"c10")]
+        );
 
-            let seek_range = BytesRange::new(Included(seek_key), scan_range.end_bound().cloned());
-            test_utils::assert_ranged_db_scan(
-                table,
-                seek_range,
-                IterationOrder::Ascending,
-                &mut iter,
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        assert_eq!(collect(&db, 7).await, vec![kv(b"a", b"a5")]);
+    }
+
+    #[tokio::test]
+    async fn test_scan_aborts_once_deadline_passes() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let clock = Arc::new(MockSystemClock::new());
+        let mut options = test_db_options(0, 1024 * 1024, None);
+        options.flush_interval = None;
+        let db = Db::builder("/tmp/test_scan_deadline", object_store)
+            .with_settings(options)
+            .with_system_clock(clock.clone())
+            .build()
+            .await
+            .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        for i in 0..1000u32 {
+            db.put_with_options(
+                format!("key{:04}", i),
+                b"value",
+                &PutOptions::default(),
+                &write_options,
             )
-            .await;
+            .await
+            .unwrap();
+        }
+        clock.set(1_000);
+
+        // a deadline that has already passed aborts before any entry is read
+        let past = ScanOptions::default().with_deadline(Some(clock.now()));
+        let mut iter = db.scan_with_options::<&[u8], _>(.., &past).await.unwrap();
+        let err = iter.next().await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Unavailable);
+        // the iterator stays aborted
+        assert!(iter.next().await.is_err());
+
+        // a deadline passing mid-scan aborts the scan within a check interval
+        let future = ScanOptions::default()
+            .with_deadline(Some(clock.now() + chrono::Duration::milliseconds(10)));
+        let mut iter = db.scan_with_options::<&[u8], _>(.., &future).await.unwrap();
+        for _ in 0..100 {
+            iter.next().await.unwrap().unwrap();
         }
+        clock.set(1_010);
+        let mut returned_after_deadline = 0;
+        let err = loop {
+            match iter.next().await {
+                Ok(kv) => {
+                    assert!(kv.is_some(), "scan read every entry past its deadline");
+                    returned_after_deadline += 1;
+                }
+                Err(err) => break err,
+            }
+        };
+        assert_eq!(err.kind(), crate::ErrorKind::Unavailable);
+        assert!(returned_after_deadline < 64);
     }
 
     #[tokio::test]
-    async fn test_write_batch() {
+    async fn test_stats_reports_memtable_sizes_and_lsm_shape() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let kv_store = Db::builder("/tmp/test_kv_store", object_store)
-            .with_settings(test_db_options(0, 1024, None))
+        let db = Db::builder("/tmp/test_stats", object_store)
             .build()
             .await
             .unwrap();
+        for i in 0..10u32 {
+            db.put(format!("key{i}"), b"value").await.unwrap();
+        }
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        for i in 0..3u32 {
+            db.put(format!("key{i}"), b"new value").await.unwrap();
+        }
 
-        // Create a new WriteBatch
-        let mut batch = WriteBatch::new();
-        batch.put(b"key1", b"value1");
-        batch.put(b"key2", b"value2");
-        batch.delete(b"key1");
-
-        // Write the batch
-        kv_store.write(batch).await.expect("write batch failed");
+        let stats = db.stats();
 
-        // Read back keys
-        assert_eq!(kv_store.get(b"key1").await.unwrap(), None);
+        let memtable = db.inner.state.read().memtable().metadata();
         assert_eq!(
-            kv_store.get(b"key2").await.unwrap(),
-            Some(Bytes::from_static(b"value2"))
+            stats.active_memtable.size_bytes,
+            memtable.entries_size_in_bytes
         );
+        assert_eq!(stats.active_memtable.entries, 3);
+        assert!(stats.immutable_memtables.is_empty());
+        assert_eq!(stats.compaction.l0_ssts, 1);
+        assert_eq!(stats.estimated_keys, 13);
+        assert_eq!(stats.durable_seq, 13);
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["compaction"]["l0_ssts"], 1);
+    }
 
-        kv_store.close().await.unwrap();
+    #[tokio::test]
+    async fn test_estimate_range_counts_memtable_entries_and_sst_blocks() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_estimate_range", object_store)
+            .with_sst_block_size(crate::SstBlockSize::Other(64))
+            .build()
+            .await
+            .unwrap();
+        for i in 0..100u32 {
+            db.put(format!("key{i:03}"), [b'x'; 32]).await.unwrap();
+        }
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        for i in 0..10u32 {
+            db.put(format!("new{i:03}"), b"value").await.unwrap();
+        }
+
+        let estimate = db.estimate_range::<&[u8], _>(..).await.unwrap();
+        assert_eq!(estimate.keys, 110);
+
+        let memtable = db
+            .estimate_range(b"new".as_slice()..b"new005".as_slice())
+            .await
+            .unwrap();
+        assert_eq!(memtable.keys, 5);
+        // each entry's key, value, sequence number and create timestamp
+        assert_eq!(memtable.size_bytes, 5 * (6 + 5 + 8 + 8));
+
+        // the SST is estimated from the blocks covering the range
+        let half = db
+            .estimate_range(b"key000".as_slice()..b"key050".as_slice())
+            .await
+            .unwrap();
+        assert!((45..=55).contains(&half.keys), "{half:?}");
+        let total = db.manifest().l0()[0].estimate_size();
+        assert!(half.size_bytes > total * 2 / 5 && half.size_bytes < total * 3 / 5);
     }
 
-    #[cfg(feature = "wal_disable")]
     #[tokio::test]
-    async fn test_write_batch_without_wal() {
+    async fn test_scan_yields_after_max_entries_when_skipping_tombstones() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        // Use a very small l0 size to force flushes so await is notified
-        let mut options = test_db_options(0, 8, None);
+        let db = Db::builder("/tmp/test_scan_yield", object_store)
+            .build()
+            .await
+            .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        for i in 0..200u32 {
+            db.delete_with_options(format!("key{:04}", i), &write_options)
+                .await
+                .unwrap();
+        }
+        db.put_with_options(b"key9999", b"value", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
 
-        // Disable WAL
-        options.wal_enabled = false;
+        // without a budget, the tombstones are skipped in a single poll
+        let mut iter = db.scan::<&[u8], _>(..).await.unwrap();
+        let mut next = std::pin::pin!(iter.next());
+        assert!(futures::poll!(&mut next).is_ready());
 
-        let kv_store = Db::builder("/tmp/test_kv_store_without_wal", object_store.clone())
-            .with_settings(options)
+        let options = ScanOptions::default().with_max_entries_before_yield(Some(10));
+        let mut iter = db
+            .scan_with_options::<&[u8], _>(.., &options)
+            .await
+            .unwrap();
+        let mut next = std::pin::pin!(iter.next());
+        let mut pending_polls = 0;
+        let kv = loop {
+            match futures::poll!(&mut next) {
+                std::task::Poll::Ready(kv) => break kv.unwrap().unwrap(),
+                std::task::Poll::Pending => pending_polls += 1,
+            }
+        };
+        assert_eq!(kv.key, Bytes::from_static(b"key9999"));
+        assert_eq!(pending_polls, 20);
+    }
+
+    #[tokio::test]
+    async fn test_scan_with_prefetch_depth_reads_flushed_entries() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_scan_prefetch", object_store)
             .build()
             .await
             .unwrap();
+        let expected: Vec<_> = (0..100u32)
+            .map(|i| {
+                (
+                    Bytes::from(format!("key{i:04}")),
+                    Bytes::from(format!("value{i}")),
+                )
+            })
+            .collect();
+        for (key, value) in expected.iter() {
+            db.put(key, value).await.unwrap();
+        }
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        // a newer version in the memtable shadows the prefetched one
+        db.put(b"key0050", b"newer").await.unwrap();
+        let mut expected = expected;
+        expected[50].1 = Bytes::from_static(b"newer");
+
+        let options = ScanOptions::default().with_prefetch_depth(Some(8));
+        let iter = db
+            .scan_with_options::<&[u8], _>(.., &options)
+            .await
+            .unwrap();
+        assert_eq!(collect_scan(iter).await, expected);
+
+        let options = options.with_order(IterationOrder::Descending);
+        let iter = db
+            .scan_with_options::<&[u8], _>(.., &options)
+            .await
+            .unwrap();
+        let mut descending = expected.clone();
+        descending.reverse();
+        assert_eq!(collect_scan(iter).await, descending);
+
+        let options = options.with_order(IterationOrder::Ascending);
+        let mut iter = db
+            .scan_with_options::<&[u8], _>(.., &options)
+            .await
+            .unwrap();
+        iter.next().await.unwrap().unwrap();
+        iter.seek(b"key0090").await.unwrap();
+        assert_eq!(collect_scan(iter).await, expected[90..].to_vec());
 
-        // Create a new WriteBatch
-        let mut batch = WriteBatch::new();
-        batch.put(b"key1", b"value1");
-        batch.put(b"key2", b"value2");
-        batch.delete(b"key1");
-
-        // Write the batch
-        kv_store.write(batch).await.expect("write batch failed");
+        let options = options.with_order(IterationOrder::Descending);
+        let mut iter = db
+            .scan_with_options::<&[u8], _>(.., &options)
+            .await
+            .unwrap();
+        iter.next().await.unwrap().unwrap();
+        iter.seek(b"key0090").await.unwrap();
+        assert_eq!(collect_scan(iter).await, descending[9..].to_vec());
+        db.close().await.unwrap();
+    }
 
-        // Read back keys
-        assert_eq!(kv_store.get(b"key1").await.unwrap(), None);
-        assert_eq!(kv_store.get(b"key2").await.unwrap(), Some("value2".into()));
+    async fn collect_scan(mut iter: DbIterator) -> Vec<(Bytes, Bytes)> {
+        let mut entries = Vec::new();
+        while let Some(kv) = iter.next().await.unwrap() {
+            entries.push((kv.key, kv.value));
+        }
+        entries
+    }
 
-        kv_store.close().await.unwrap();
+    fn kvs(entries: &[(&'static str, &'static str)]) -> Vec<(Bytes, Bytes)> {
+        entries
+            .iter()
+            .map(|(key, value)| (Bytes::from(*key), Bytes::from(*value)))
+            .collect()
     }
 
+    #[rstest::rstest]
+    #[case::highest(
+        crate::SourcePriority::Highest,
+        IterationOrder::Ascending,
+        &[("a", "virtual"), ("b", "virtual"), ("c", "virtual"), ("d", "memtable")]
+    )]
+    #[case::lowest(
+        crate::SourcePriority::Lowest,
+        IterationOrder::Ascending,
+        &[("a", "virtual"), ("b", "memtable"), ("d", "memtable")]
+    )]
+    #[case::lowest_descending(
+        crate::SourcePriority::Lowest,
+        IterationOrder::Descending,
+        &[("d", "memtable"), ("b", "memtable"), ("a", "virtual")]
+    )]
     #[tokio::test]
-    async fn test_write_batch_with_empty_key() {
+    async fn test_scan_with_source_merges_by_key_and_priority(
+        #[case] priority: crate::SourcePriority,
+        #[case] order: IterationOrder,
+        #[case] expected: &[(&'static str, &'static str)],
+    ) {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::open("/tmp/test_scan_with_source", object_store)
+            .await
+            .unwrap();
         let mut batch = WriteBatch::new();
-        let result = std::panic::catch_unwind(move || {
-            batch.put(b"", b"value");
-        });
-        assert!(
-            result.is_err(),
-            "Expected panic when using empty key in put operation"
-        );
+        batch.put(b"b", b"memtable");
+        batch.put(b"c", b"memtable");
+        batch.put(b"d", b"memtable");
+        db.write(batch).await.unwrap();
+        db.delete(b"c").await.unwrap();
 
-        let mut batch = WriteBatch::new();
-        let result = std::panic::catch_unwind(move || {
-            batch.delete(b"");
-        });
-        assert!(
-            result.is_err(),
-            "Expected panic when using empty key in delete operation"
-        );
+        let mut source = [
+            ("a", "virtual"),
+            ("b", "virtual"),
+            ("c", "virtual"),
+            ("e", "outside the range"),
+        ];
+        if matches!(order, IterationOrder::Descending) {
+            source.reverse();
+        }
+        let options = ScanOptions::default().with_order(order);
+        let iter = db
+            .scan_with_source(
+                b"a".as_slice()..b"e".as_slice(),
+                &options,
+                VecSource::new(&source),
+                priority,
+            )
+            .await
+            .unwrap();
+        assert_eq!(collect_scan(iter).await, kvs(expected));
     }
 
-    /// Test that batch writes are atomic. Test does the following:
-    ///
-    /// - A set of, say 100 keys, 1-100
-    /// - Two tasks writing, one writing value to be same key, and other
-    ///   writing it to be key*2.
-    /// - We wait for both to complete.
-    /// - Assert that either all values are same as key, or all values key*2.
-    /// - Repeat above loop few times.
-    ///
-    /// _Note: This test is non-deterministic because it depends on the async
-    /// runtime to schedule the tasks in a way that the writes are concurrent._
     #[tokio::test]
-    async fn test_concurrent_batch_writes_consistency() {
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let kv_store = Arc::new(
-            Db::builder("/tmp/test_concurrent_kv_store", object_store)
-                .with_settings(test_db_options(
-                    0,
-                    1024,
-                    // Enable compactor to prevent l0 from filling up and
-                    // applying backpressure indefinitely.
-                    Some(CompactorOptions {
-                        poll_interval: Duration::from_millis(100),
-                        max_sst_size: 256,
-                        max_concurrent_compactions: 1,
-                        manifest_update_timeout: Duration::from_secs(300),
-                        ..Default::default()
-                    }),
-                ))
-                .build()
-                .await
-                .unwrap(),
-        );
-
-        const NUM_KEYS: usize = 100;
-        const NUM_ROUNDS: usize = 20;
-
-        for _ in 0..NUM_ROUNDS {
-            // Write two tasks that write to the same keys
-            let task1 = {
-                let store = kv_store.clone();
-                tokio::spawn(async move {
-                    let mut batch = WriteBatch::new();
-                    for key in 1..=NUM_KEYS {
-                        batch.put(key.to_be_bytes(), key.to_be_bytes());
-                    }
-                    store.write(batch).await.expect("write batch failed");
-                })
-            };
+    async fn test_replace_range_is_atomic_for_concurrent_scans() {
+        let db = Db::open("/tmp/test_replace_range", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        db.put(b"other", b"kept").await.unwrap();
+        let generation = |n: u8| -> Vec<(Vec<u8>, Vec<u8>)> {
+            // each generation holds a different set of keys
+            (0..10u8)
+                .map(|i| (vec![b'r', b'/', i * 2 + n % 2], vec![n]))
+                .collect()
+        };
+        let range = b"r/".to_vec()..b"r0".to_vec();
+        db.replace_range(range.clone(), generation(0))
+            .await
+            .unwrap();
 
-            let task2 = {
-                let store = kv_store.clone();
-                tokio::spawn(async move {
-                    let mut batch = WriteBatch::new();
-                    for key in 1..=NUM_KEYS {
-                        let value = (key * 2).to_be_bytes();
-                        batch.put(key.to_be_bytes(), value);
+        let reader = {
+            let db = db.clone();
+            let range = range.clone();
+            tokio::spawn(async move {
+                for _ in 0..50 {
+                    let mut iter = db.scan(range.clone()).await.unwrap();
+                    let mut entries = Vec::new();
+                    while let Some(kv) = iter.next().await.unwrap() {
+                        entries.push((kv.key.to_vec(), kv.value.to_vec()));
                     }
-                    store.write(batch).await.expect("write batch failed");
-                })
-            };
-
-            // Wait for both tasks to complete
-            join_all(vec![task1, task2]).await;
-
-            // Ensure consistency: all values must be either key or key * 2
-            let mut all_key = true;
-            let mut all_key2 = true;
-
-            for key in 1..=NUM_KEYS {
-                let value = kv_store.get(key.to_be_bytes()).await.unwrap();
-                let value = value.expect("Value should exist");
-
-                if value.as_ref() != key.to_be_bytes() {
-                    all_key = false;
-                }
-                if value.as_ref() != (key * 2).to_be_bytes() {
-                    all_key2 = false;
+                    let n = entries[0].1[0];
+                    assert_eq!(entries, generation(n));
+                    tokio::task::yield_now().await;
                 }
-            }
+            })
+        };
+        for n in 1..20 {
+            db.replace_range(range.clone(), generation(n))
+                .await
+                .unwrap();
+            tokio::task::yield_now().await;
+        }
+        reader.await.unwrap();
 
-            // Assert that the result is consistent: either all key or all key * 2
-            assert!(
-                all_key || all_key2,
-                "Inconsistent state: not all values match either key or key * 2"
-            );
+        let mut iter = db.scan(range.clone()).await.unwrap();
+        let mut count = 0;
+        while let Some(kv) = iter.next().await.unwrap() {
+            assert_eq!(kv.value, Bytes::from(vec![19]));
+            count += 1;
         }
+        assert_eq!(count, 10);
+        assert_eq!(db.get(b"other").await.unwrap(), Some(Bytes::from("kept")));
 
-        kv_store.close().await.unwrap();
+        let result = db
+            .replace_range(range.clone(), [(b"other".to_vec(), b"x".to_vec())])
+            .await;
+        assert!(matches!(result, Err(err) if err.kind() == crate::ErrorKind::Invalid));
     }
 
     #[tokio::test]
-    #[cfg(feature = "wal_disable")]
-    async fn test_disable_wal_after_wal_enabled() {
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let path = "/tmp/test_kv_store";
-        // open a db and write a wal entry
-        let options = test_db_options(0, 32, None);
-        let db = Db::builder(path, object_store.clone())
-            .with_settings(options)
-            .build()
+    async fn test_scan_returns_nothing_for_empty_range_and_rejects_inverted_range() {
+        let db = Db::open("/tmp/test_scan_empty_range", Arc::new(InMemory::new()))
             .await
             .unwrap();
-        db.put(&[b'a'; 4], &[b'j'; 4]).await.unwrap();
-        db.put(&[b'b'; 4], &[b'k'; 4]).await.unwrap();
-        db.close().await.unwrap();
+        db.put(b"a", b"1").await.unwrap();
+        db.put(b"b", b"2").await.unwrap();
 
-        // open a db with wal disabled and write a memtable
-        let mut options = test_db_options(0, 32, None);
-        options.wal_enabled = false;
-        let db = Db::builder(path, object_store.clone())
-            .with_settings(options.clone())
+        let mut iter = db.scan(b"a".as_slice()..b"a".as_slice()).await.unwrap();
+        assert_eq!(iter.next().await.unwrap(), None);
+
+        let result = db.scan(b"b".as_slice()..b"a".as_slice()).await;
+        assert!(matches!(result, Err(err) if err.kind() == crate::ErrorKind::Invalid));
+        let snapshot = db.snapshot().await.unwrap();
+        let result = snapshot.scan(b"b".as_slice()..=b"a".as_slice()).await;
+        assert!(matches!(result, Err(err) if err.kind() == crate::ErrorKind::Invalid));
+    }
+
+    #[tokio::test]
+    async fn test_scan_with_read_repair_copies_sst_entries_to_memtable() {
+        let db = Db::builder("/tmp/test_scan_with_read_repair", Arc::new(InMemory::new()))
+            .with_settings(Settings {
+                read_repair_sample_rate: 1.0,
+                ..test_db_options(0, 64 * 1024, None)
+            })
             .build()
             .await
             .unwrap();
-        db.delete_with_options(
-            &[b'b'; 4],
-            &WriteOptions {
-                await_durable: false,
-                ..Default::default()
+        db.put_with_options(
+            b"a",
+            b"a1",
+            &PutOptions {
+                ttl: Ttl::ExpireAfter(1_000_000),
             },
+            &WriteOptions::default(),
         )
         .await
         .unwrap();
-        db.put(&[b'a'; 4], &[b'z'; 64]).await.unwrap();
-        db.close().await.unwrap();
+        db.put(b"b", b"b1").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        db.put(b"c", b"c1").await.unwrap();
+        let a = db.get_key_value(b"a").await.unwrap().unwrap();
+        let b = db.get_key_value(b"b").await.unwrap().unwrap();
+        let c = db.get_key_value(b"c").await.unwrap().unwrap();
 
-        // ensure we don't overwrite the values we just put on a reload
-        let db = Db::builder(path, object_store.clone())
-            .with_settings(options.clone())
-            .build()
+        let mut iter = db
+            .scan_with_options::<&[u8], _>(.., &ScanOptions::default().with_read_repair(true))
             .await
             .unwrap();
-        let val = db.get(&[b'a'; 4]).await.unwrap();
-        assert_eq!(val.unwrap(), Bytes::copy_from_slice(&[b'z'; 64]));
-        let val = db.get(&[b'b'; 4]).await.unwrap();
-        assert!(val.is_none());
+        let mut values = Vec::new();
+        while let Some(kv) = iter.next().await.unwrap() {
+            values.push(kv.value);
+        }
+        assert_eq!(values, vec![a.value.clone(), b.value.clone(), c.value]);
+        // the write loop applies writes in order, so the repairs are in the
+        // memtable once a later write is
+        db.put(b"d", b"d1").await.unwrap();
+
+        let memtable = db.inner.state.read().memtable().table().clone();
+        for kv in [&a, &b] {
+            let entries = memtable.get_raw(&kv.key);
+            assert_eq!(entries.len(), 1);
+            assert!(entries[0].seq > kv.seq);
+            assert_eq!(entries[0].value, ValueDeletable::Value(kv.value.clone()));
+            assert_eq!(entries[0].expire_ts, kv.expire_ts);
+        }
+        let entries = memtable.get_raw(b"c");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].seq, c.seq);
+        assert_eq!(db.get(b"a").await.unwrap(), Some(a.value));
     }
 
-    #[cfg(feature = "wal_disable")]
     #[tokio::test]
-    async fn test_wal_disabled() {
-        use crate::{test_utils::assert_iterator, types::RowEntry};
+    async fn test_read_repair_copies_values_before_projection() {
+        struct FirstByte;
 
-        let clock = Arc::new(MockSystemClock::new());
-        let mut options = test_db_options(0, 300, None);
-        options.wal_enabled = false;
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let path = Path::from("/tmp/test_kv_store");
-        let sst_format = SsTableFormat::default();
-        let table_store = Arc::new(TableStore::new(
-            ObjectStores::new(object_store.clone(), None),
-            sst_format,
-            path.clone(),
-            None,
-        ));
-        let db = Db::builder(path.clone(), object_store.clone())
-            .with_settings(options)
-            .with_system_clock(clock.clone())
-            .build()
+        impl crate::Projector for FirstByte {
+            fn project(&self, value: &[u8]) -> Bytes {
+                Bytes::copy_from_slice(&value[..1])
+            }
+        }
+
+        let db = Db::builder(
+            "/tmp/test_read_repair_before_projection",
+            Arc::new(InMemory::new()),
+        )
+        .with_settings(Settings {
+            read_repair_sample_rate: 1.0,
+            ..test_db_options(0, 64 * 1024, None)
+        })
+        .build()
+        .await
+        .unwrap();
+        db.put(b"a", b"a1;a2").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+
+        let iter = db
+            .scan_with_options::<&[u8], _>(.., &ScanOptions::default().with_read_repair(true))
             .await
-            .unwrap();
-        let manifest_store = Arc::new(ManifestStore::new(&path, object_store.clone()));
-        let mut stored_manifest =
-            StoredManifest::load(manifest_store.clone(), Arc::new(DefaultSystemClock::new()))
-                .await
-                .unwrap();
-        let write_options = WriteOptions {
-            await_durable: false,
-            ..Default::default()
-        };
+            .unwrap()
+            .with_projector(Arc::new(FirstByte));
+        assert_eq!(
+            collect_scan(iter).await,
+            vec![(Bytes::from_static(b"a"), Bytes::from_static(b"a"))]
+        );
+        db.put(b"d", b"d1").await.unwrap();
 
-        db.put_with_options(
-            &[b'a'; 32],
-            &[b'j'; 32],
-            &PutOptions::default(),
-            &write_options,
+        let memtable = db.inner.state.read().memtable().table().clone();
+        let entries = memtable.get_raw(b"a");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].value,
+            ValueDeletable::Value(Bytes::from_static(b"a1;a2"))
+        );
+        assert_eq!(
+            db.get(b"a").await.unwrap(),
+            Some(Bytes::from_static(b"a1;a2"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_repair_skips_virtual_source_rows() {
+        let db = Db::builder(
+            "/tmp/test_read_repair_skips_virtual_source",
+            Arc::new(InMemory::new()),
         )
+        .with_settings(Settings {
+            read_repair_sample_rate: 1.0,
+            ..test_db_options(0, 64 * 1024, None)
+        })
+        .build()
+        .await
+        .unwrap();
+        db.put(b"b", b"b1").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
         .await
         .unwrap();
-        db.delete_with_options(&[b'b'; 31], &write_options)
+
+        let iter = db
+            .scan_with_source::<&[u8], _, _>(
+                ..,
+                &ScanOptions::default().with_read_repair(true),
+                VecSource::new(&[("a", "virtual"), ("c", "virtual")]),
+                crate::SourcePriority::Lowest,
+            )
             .await
             .unwrap();
+        assert_eq!(
+            collect_scan(iter).await,
+            kvs(&[("a", "virtual"), ("b", "b1"), ("c", "virtual")])
+        );
+        db.put(b"d", b"d1").await.unwrap();
 
-        // ensure the memtable's size is greater than l0_sst_size_bytes, or
-        // the memtable will not be flushed to l0, and the test will hang
-        // at this put_with_options call.
-        let write_options = WriteOptions {
-            await_durable: true,
-            ..Default::default()
-        };
-        clock.set(10);
-        db.put_with_options(
-            &[b'c'; 32],
-            &[b'l'; 32],
-            &PutOptions::default(),
-            &write_options,
+        let memtable = db.inner.state.read().memtable().table().clone();
+        assert_eq!(memtable.get_raw(b"b").len(), 1);
+        assert!(memtable.get_raw(b"a").is_empty());
+        assert!(memtable.get_raw(b"c").is_empty());
+        assert_eq!(db.get(b"a").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_scan_without_read_repair_leaves_memtable_unchanged() {
+        let db = Db::builder(
+            "/tmp/test_scan_without_read_repair",
+            Arc::new(InMemory::new()),
         )
+        .with_settings(test_db_options(0, 64 * 1024, None))
+        .build()
+        .await
+        .unwrap();
+        db.put(b"a", b"a1").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
         .await
         .unwrap();
 
-        let state = wait_for_manifest_condition(
-            &mut stored_manifest,
-            |s| !s.tree.l0.is_empty(),
-            Duration::from_secs(30),
-        )
-        .await;
-        assert_eq!(state.tree.l0.len(), 1);
+        let mut iter = db.scan::<&[u8], _>(..).await.unwrap();
+        while iter.next().await.unwrap().is_some() {}
+        db.put(b"d", b"d1").await.unwrap();
 
-        let l0 = state.tree.l0.front().unwrap();
-        let mut iter = SstIterator::new_borrowed_initialized(
-            ..,
-            l0,
-            table_store.clone(),
-            SstIteratorOptions::default(),
-        )
-        .await
-        .unwrap()
-        .expect("Expected Some(iter) but got None");
-        assert_iterator(
-            &mut iter,
-            vec![
-                RowEntry::new_value(&[b'a'; 32], &[b'j'; 32], 1).with_create_ts(0),
-                RowEntry::new_tombstone(&[b'b'; 31], 2).with_create_ts(0),
-                RowEntry::new_value(&[b'c'; 32], &[b'l'; 32], 3).with_create_ts(10),
-            ],
-        )
-        .await;
+        let memtable = db.inner.state.read().memtable().table().clone();
+        assert!(memtable.get_raw(b"a").is_empty());
     }
 
     #[tokio::test]
-    async fn test_put_flushes_memtable() {
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let path = "/tmp/test_kv_store";
-        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
-        let kv_store = Db::builder(path, object_store.clone())
-            .with_settings(test_db_options(0, 256, None))
-            .with_metrics_recorder(metrics_recorder.clone())
+    async fn test_read_repair_rejected_if_key_written_since_read() {
+        let db = Db::builder("/tmp/test_read_repair_rejected", Arc::new(InMemory::new()))
+            .with_settings(test_db_options(0, 64 * 1024, None))
             .build()
             .await
             .unwrap();
-        let manifest_store = Arc::new(ManifestStore::new(&Path::from(path), object_store.clone()));
-        let mut stored_manifest =
-            StoredManifest::load(manifest_store.clone(), Arc::new(DefaultSystemClock::new()))
-                .await
-                .unwrap();
-        let sst_format = SsTableFormat {
-            min_filter_keys: 10,
-            ..SsTableFormat::default()
+        db.put(b"a", b"a1").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        let a = db.get_key_value(b"a").await.unwrap().unwrap();
+        let last_l0_seq = db.inner.state.read().state().core().last_l0_seq;
+        let repair = || {
+            let mut batch = WriteBatch::new();
+            batch.put(b"a", b"a1");
+            batch.read_repair = Some(ReadRepairCheck {
+                seq: a.seq,
+                last_l0_seq,
+            });
+            batch
         };
-        let table_store = Arc::new(TableStore::new(
-            ObjectStores::new(object_store.clone(), None),
-            sst_format,
-            path,
-            None,
-        ));
 
-        // Write data a few times such that each loop results in a memtable flush
-        let mut last_wal_id = 0;
-        for i in 0..3 {
-            let key = [b'a' + i; 16];
-            let value = [b'b' + i; 50];
-            kv_store.put(&key, &value).await.unwrap();
-            let key = [b'j' + i; 16];
-            let value = [b'k' + i; 50];
-            kv_store.put(&key, &value).await.unwrap();
-            let db_state = wait_for_manifest_condition(
-                &mut stored_manifest,
-                |s| s.replay_after_wal_id > last_wal_id,
-                Duration::from_secs(30),
-            )
+        // a newer version in the memtable
+        db.put(b"a", b"a2").await.unwrap();
+        let result = db
+            .inner
+            .write_with_options(repair(), &WriteOptions::default())
             .await;
+        assert!(matches!(result, Err(SlateDBError::TransactionConflict)));
+        assert_eq!(db.get(b"a").await.unwrap(), Some(Bytes::from_static(b"a2")));
 
-            // 2 wal per iteration.
-            assert_eq!(db_state.replay_after_wal_id, (i as u64) * 2 + 2);
-            last_wal_id = db_state.replay_after_wal_id
-        }
-
-        let manifest = stored_manifest.refresh().await.unwrap();
-        let l0 = &manifest.core.tree.l0;
-        assert_eq!(l0.len(), 3);
-        let sst_iter_options = SstIteratorOptions::default();
-
-        for i in 0u8..3u8 {
-            let sst1 = l0.get(2 - i as usize).unwrap();
-            let mut iter = SstIterator::new_borrowed_initialized(
-                ..,
-                sst1,
-                table_store.clone(),
-                sst_iter_options.clone(),
-            )
-            .await
-            .unwrap()
-            .expect("Expected Some(iter) but got None");
-            let kv: KeyValue = iter.next().await.unwrap().unwrap().into();
-            assert_eq!(kv.key.as_ref(), [b'a' + i; 16]);
-            assert_eq!(kv.value.as_ref(), [b'b' + i; 50]);
-            let kv: KeyValue = iter.next().await.unwrap().unwrap().into();
-            assert_eq!(kv.key.as_ref(), [b'j' + i; 16]);
-            assert_eq!(kv.value.as_ref(), [b'k' + i; 50]);
-            let kv = iter.next().await.unwrap().map(KeyValue::from);
-            assert!(kv.is_none());
-        }
-        assert!(lookup_metric(&metrics_recorder, IMMUTABLE_MEMTABLE_FLUSHES).is_some_and(|v| v > 0));
+        // a newer version flushed to L0
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        let result = db
+            .inner
+            .write_with_options(repair(), &WriteOptions::default())
+            .await;
+        assert!(matches!(result, Err(SlateDBError::TransactionConflict)));
+        assert_eq!(db.get(b"a").await.unwrap(), Some(Bytes::from_static(b"a2")));
     }
 
     #[tokio::test]
-    async fn test_put_flushes_memtable_after_max_wal_flushes() {
-        const MAX_WAL_FLUSHES_BEFORE_L0_FLUSH: u64 = 4096;
-
+    async fn test_scan_with_source_seeks_and_rejects_out_of_order_keys() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let path = "/tmp/test_flush_memtable_max_wal_flushes";
-
-        let mut settings = test_db_options(0, usize::MAX, None);
-        settings.flush_interval = None; // Disable flushing
-        settings.max_wal_flushes_before_l0_flush = MAX_WAL_FLUSHES_BEFORE_L0_FLUSH;
+        let db = Db::open("/tmp/test_scan_with_source_seek", object_store)
+            .await
+            .unwrap();
+        let mut batch = WriteBatch::new();
+        batch.put(b"a", b"memtable");
+        batch.put(b"c", b"memtable");
+        db.write(batch).await.unwrap();
 
-        let kv_store = Db::builder(path, object_store.clone())
-            .with_settings(settings)
-            .build()
+        let source = VecSource::new(&[("b", "virtual"), ("d", "virtual"), ("e", "virtual")]);
+        let mut iter = db
+            .scan_with_source::<&[u8], _, _>(
+                ..,
+                &ScanOptions::default(),
+                source,
+                crate::SourcePriority::Highest,
+            )
             .await
             .unwrap();
+        assert_eq!(iter.next().await.unwrap().unwrap().key, Bytes::from("a"));
+        iter.seek(b"d").await.unwrap();
+        assert_eq!(
+            collect_scan(iter).await,
+            kvs(&[("d", "virtual"), ("e", "virtual")])
+        );
 
-        let manifest_store = Arc::new(ManifestStore::new(&Path::from(path), object_store.clone()));
-        let mut stored_manifest =
-            StoredManifest::load(manifest_store.clone(), Arc::new(DefaultSystemClock::new()))
-                .await
-                .unwrap();
+        let source = VecSource::new(&[("b", "virtual"), ("a", "virtual")]);
+        let mut iter = db
+            .scan_with_source::<&[u8], _, _>(
+                ..,
+                &ScanOptions::default(),
+                source,
+                crate::SourcePriority::Lowest,
+            )
+            .await
+            .unwrap();
+        let err = loop {
+            match iter.next().await {
+                Ok(kv) => assert!(kv.is_some(), "out of order key was not detected"),
+                Err(err) => break err,
+            }
+        };
+        assert_eq!(err.kind(), crate::ErrorKind::Invalid);
+    }
 
-        let write_options: WriteOptions = WriteOptions {
+    #[tokio::test]
+    async fn test_merkle_tree_localizes_differences_to_one_leaf() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let clock = Arc::new(MockSystemClock::new());
+        let mut options = test_db_options(0, 1024 * 1024, None);
+        options.flush_interval = None;
+        let build = |path: &'static str| {
+            Db::builder(path, object_store.clone())
+                .with_settings(options.clone())
+                .with_system_clock(clock.clone())
+                .build()
+        };
+        let db1 = build("/tmp/test_merkle_1").await.unwrap();
+        let db2 = build("/tmp/test_merkle_2").await.unwrap();
+        let write_options = WriteOptions {
             await_durable: false,
             ..Default::default()
         };
-        let put_options = PutOptions::default();
-
-        for i in 0..(MAX_WAL_FLUSHES_BEFORE_L0_FLUSH - 1) {
-            let key = format!("key{:08}", i);
-            kv_store
-                .put_with_options(key.as_bytes(), b"v", &put_options, &write_options)
-                .await
-                .unwrap();
-            kv_store.flush().await.unwrap();
-        }
-
-        // Verify WALs flushes.
-        let wal_id = kv_store.inner.wal_buffer.recent_flushed_wal_id();
-        assert_eq!(wal_id, MAX_WAL_FLUSHES_BEFORE_L0_FLUSH); // account for the empty WAL written for fencing
-
-        // Verify no memtable was frozen or L0 flush happened.
-        {
-            let guard = kv_store.inner.state.read();
-            assert!(guard.state().imm_memtable.is_empty());
-            assert_eq!(guard.state().core().tree.l0.len(), 0);
+        for i in 0..200u32 {
+            let key = format!("{:03}-key", i);
+            for db in [&db1, &db2] {
+                db.put_with_options(&key, b"value", &PutOptions::default(), &write_options)
+                    .await
+                    .unwrap();
+            }
         }
-
-        // This put() triggers a freeze.
-        let key = format!("key{:08}", MAX_WAL_FLUSHES_BEFORE_L0_FLUSH - 1);
-        kv_store
-            .put_with_options(key.as_bytes(), b"v", &put_options, &write_options)
+        // a deleted key, an expired key and a flush to L0 don't change the tree
+        db2.put_with_options(b"zzz", b"value", &PutOptions::default(), &write_options)
             .await
             .unwrap();
-        // Flush the WAL so the manifest writer can proceed (flush_interval is
-        // disabled in this test, so there is no periodic WAL flush).
-        kv_store.flush().await.unwrap();
-
-        // Verify that the WAL count threshold triggered a memtable freeze and L0 flush.
-        // replay_after_wal_id should have advanced to the threshold, and there should
-        // be exactly one L0 SST.
-        let db_state = wait_for_manifest_condition(
-            &mut stored_manifest,
-            |s| s.replay_after_wal_id == MAX_WAL_FLUSHES_BEFORE_L0_FLUSH,
-            Duration::from_secs(30),
-        )
-        .await;
-        assert_eq!(db_state.tree.l0.len(), 1);
-
-        // Run MAX_WAL_FLUSHES_BEFORE_L0_FLUSH more put()/flush() cycles
-        // and see if the threshold triggers again.
-        for i in 0..(MAX_WAL_FLUSHES_BEFORE_L0_FLUSH - 1) {
-            let key = format!("key{:08}", i);
-            kv_store
-                .put_with_options(key.as_bytes(), b"v", &put_options, &write_options)
-                .await
-                .unwrap();
-            kv_store.flush().await.unwrap();
-        }
-
-        // Verify no more memtables were frozen or L0 flush happened.
-        {
-            let guard = kv_store.inner.state.read();
-            assert_eq!(guard.state().core().tree.l0.len(), 1);
-        }
-
-        // This put() triggers a freeze.
-        let key = format!("key{:08}", MAX_WAL_FLUSHES_BEFORE_L0_FLUSH);
-        kv_store
-            .put_with_options(key.as_bytes(), b"v", &put_options, &write_options)
+        db2.delete_with_options(b"zzz", &write_options)
             .await
             .unwrap();
-        kv_store.flush().await.unwrap();
-
-        // Wait for the flush to happen.
-        let db_state = wait_for_manifest_condition(
-            &mut stored_manifest,
-            |s| s.replay_after_wal_id == MAX_WAL_FLUSHES_BEFORE_L0_FLUSH * 2,
-            Duration::from_secs(30),
+        db2.put_with_options(
+            b"zzy",
+            b"value",
+            &PutOptions {
+                ttl: Ttl::ExpireAfter(10),
+            },
+            &write_options,
         )
-        .await;
-        assert_eq!(db_state.tree.l0.len(), 2); // We should have two L0 flushes.
+        .await
+        .unwrap();
+        db2.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        clock.set(100);
+        let root = db1.merkle_root().await.unwrap();
+        assert_eq!(root, db2.merkle_root().await.unwrap());
 
-        kv_store.close().await.unwrap();
+        db2.put_with_options(
+            b"150-key",
+            b"changed",
+            &PutOptions::default(),
+            &write_options,
+        )
+        .await
+        .unwrap();
+        let mut node1 = db1.merkle_root().await.unwrap();
+        let mut node2 = db2.merkle_root().await.unwrap();
+        assert_ne!(node1.hash, node2.hash);
+        while !node1.is_leaf() {
+            let children1 = db1.merkle_children(&node1).await.unwrap();
+            let children2 = db2.merkle_children(&node2).await.unwrap();
+            assert_eq!(children1.len(), 256);
+            let divergent: Vec<_> = children1
+                .into_iter()
+                .zip(children2)
+                .filter(|(child1, child2)| child1.hash != child2.hash)
+                .collect();
+            assert_eq!(divergent.len(), 1);
+            (node1, node2) = divergent.into_iter().next().unwrap();
+        }
+        assert_eq!(node1.prefix, Bytes::from_static(b"15"));
+        assert!(node1.key_range().contains(&Bytes::from_static(b"150-key")));
+        assert_eq!(
+            node2.hash,
+            db2.range_digest(node2.key_range()).await.unwrap()
+        );
+        assert!(db2.merkle_children(&node2).await.unwrap().is_empty());
     }
 
     #[tokio::test]
-    async fn test_flush_memtable_with_wal_enabled() {
+    async fn test_range_digest_depends_only_on_live_entries() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let path = "/tmp/test_flush_with_options";
-        let mut options = test_db_options(0, 256, None);
-        options.flush_interval = Some(Duration::from_secs(u64::MAX));
-        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
-        let kv_store = Db::builder(path, object_store.clone())
-            .with_settings(options)
-            .with_metrics_recorder(metrics_recorder.clone())
-            .build()
-            .await
-            .unwrap();
-
-        let manifest_store = Arc::new(ManifestStore::new(&Path::from(path), object_store.clone()));
-        let mut stored_manifest =
-            StoredManifest::load(manifest_store.clone(), Arc::new(DefaultSystemClock::new()))
-                .await
-                .unwrap();
-        let sst_format = SsTableFormat {
-            min_filter_keys: 10,
-            ..SsTableFormat::default()
+        let clock = Arc::new(MockSystemClock::new());
+        let mut options = test_db_options(0, 1024 * 1024, None);
+        options.flush_interval = None;
+        let build = |path: &'static str| {
+            Db::builder(path, object_store.clone())
+                .with_settings(options.clone())
+                .with_system_clock(clock.clone())
+                .build()
+        };
+        let db1 = build("/tmp/test_range_digest_1").await.unwrap();
+        let db2 = build("/tmp/test_range_digest_2").await.unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
         };
-        let table_store = Arc::new(TableStore::new(
-            ObjectStores::new(object_store.clone(), None),
-            sst_format,
-            path,
-            None,
-        ));
 
-        // Write some data to populate the memtable
-        let key1 = b"test_key_1";
-        let value1 = b"test_value_1";
-        kv_store
-            .put_with_options(
-                key1,
-                value1,
-                &PutOptions::default(),
-                &WriteOptions {
-                    await_durable: false,
-                    ..Default::default()
-                },
-            )
+        db1.put_with_options(b"a", b"1", &PutOptions::default(), &write_options)
             .await
             .unwrap();
-
-        let key2 = b"test_key_2";
-        let value2 = b"test_value_2";
-        kv_store
-            .put_with_options(
-                key2,
-                value2,
-                &PutOptions::default(),
-                &WriteOptions {
-                    await_durable: false,
-                    ..Default::default()
-                },
-            )
+        db1.put_with_options(b"b", b"2", &PutOptions::default(), &write_options)
             .await
             .unwrap();
 
-        // Get initial state
-        let initial_manifest = stored_manifest.refresh().await.unwrap();
-        let initial_l0_count = initial_manifest.core.tree.l0.len();
-
-        let initial_flush_count =
-            lookup_metric(&metrics_recorder, IMMUTABLE_MEMTABLE_FLUSHES).unwrap();
-
-        // Flush memtable using flush_with_options
-        kv_store
-            .flush_with_options(FlushOptions {
-                flush_type: FlushType::MemTable,
-            })
+        // Reach the same live contents through overwrites, deletes and an expired entry
+        db2.put_with_options(b"a", b"stale", &PutOptions::default(), &write_options)
             .await
             .unwrap();
-
-        // Wait for the flush to complete and manifest to be updated
-        let db_state = wait_for_manifest_condition(
-            &mut stored_manifest,
-            |s| s.tree.l0.len() > initial_l0_count,
-            Duration::from_secs(30),
+        db2.put_with_options(b"a", b"1", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        db2.put_with_options(b"b", b"2", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        db2.put_with_options(b"c", b"deleted", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        db2.delete_with_options(b"c", &write_options).await.unwrap();
+        db2.put_with_options(
+            b"d",
+            b"expired",
+            &PutOptions {
+                ttl: Ttl::ExpireAfter(10),
+            },
+            &write_options,
         )
-        .await;
-
-        // Verify that a new SST was created in L0
-        assert_eq!(db_state.tree.l0.len(), initial_l0_count + 1);
+        .await
+        .unwrap();
+        clock.set(100);
 
-        // Verify that the flush metrics were updated
-        let final_flush_count =
-            lookup_metric(&metrics_recorder, IMMUTABLE_MEMTABLE_FLUSHES).unwrap();
-        assert!(final_flush_count > initial_flush_count);
+        let digest = db1.range_digest::<&[u8], _>(..).await.unwrap();
+        assert_eq!(digest, db2.range_digest::<&[u8], _>(..).await.unwrap());
 
-        // Verify that the WAL was also flushed since we guarantee
-        // memtable data is persisted in the WAL prior to L0 flush.
-        let recent_flushed_wal_id = kv_store.inner.wal_buffer.recent_flushed_wal_id();
-        assert_eq!(recent_flushed_wal_id, 2);
+        // Flushing to L0 doesn't change the digest
+        db2.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        assert_eq!(digest, db2.range_digest::<&[u8], _>(..).await.unwrap());
 
-        // Verify that the data is still accessible after flush
-        let retrieved_value1 = kv_store.get(key1).await.unwrap().unwrap();
-        assert_eq!(retrieved_value1.as_ref(), value1);
+        // Sub-ranges and changed values produce different digests
+        assert_ne!(
+            digest,
+            db2.range_digest(b"a".as_slice()..b"b".as_slice())
+                .await
+                .unwrap()
+        );
+        db2.put_with_options(b"b", b"3", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        assert_ne!(digest, db2.range_digest::<&[u8], _>(..).await.unwrap());
 
-        let retrieved_value2 = kv_store.get(key2).await.unwrap().unwrap();
-        assert_eq!(retrieved_value2.as_ref(), value2);
+        db1.close().await.unwrap();
+        db2.close().await.unwrap();
+    }
 
-        // Verify the data exists in the newly created SST
-        let latest_sst = db_state.tree.l0.back().unwrap();
-        let sst_iter_options = SstIteratorOptions::default();
-        let mut iter = SstIterator::new_borrowed_initialized(
+    /// Reads back the entries of an SST file written by [`Db::export_sst`].
+    async fn read_exported_sst(file: Vec<u8>) -> Vec<RowEntry> {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let ulid = ulid::Ulid::new();
+        object_store
+            .put(
+                &Path::from(format!("/tmp/test_exported_sst/compacted/{ulid}.sst")),
+                file.into(),
+            )
+            .await
+            .unwrap();
+        let table_store = Arc::new(TableStore::new(
+            ObjectStores::new(object_store, None),
+            SsTableFormat::default(),
+            Path::from("/tmp/test_exported_sst"),
+            None,
+        ));
+        let handle = table_store
+            .open_sst(&SsTableId::Compacted(ulid))
+            .await
+            .unwrap();
+        let mut iter = SstIterator::new_owned_initialized(
             ..,
-            latest_sst,
-            table_store.clone(),
-            sst_iter_options,
+            SsTableView::identity(handle),
+            table_store,
+            SstIteratorOptions::default(),
         )
         .await
         .unwrap()
         .expect("Expected Some(iter) but got None");
+        let mut entries = Vec::new();
+        while let Some(entry) = iter.next().await.unwrap() {
+            entries.push(entry);
+        }
+        entries
+    }
 
-        // Collect all key-value pairs from the SST
-        let mut found_keys = std::collections::HashSet::new();
-        while let Some(kv) = iter.next().await.unwrap().map(KeyValue::from) {
-            found_keys.insert(kv.key.to_vec());
+    #[tokio::test]
+    async fn test_export_sst_round_trips_range_into_fresh_db() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_export_sst", object_store.clone())
+            .with_settings(test_db_options(0, 1024 * 1024, None))
+            .build()
+            .await
+            .unwrap();
+        for key in [b"a", b"b", b"c"] {
+            db.put(key, b"old").await.unwrap();
+        }
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        db.put(b"b", b"new").await.unwrap();
+        db.delete(b"c").await.unwrap();
+        db.put(b"d", b"new").await.unwrap();
+        db.put(b"e", b"new").await.unwrap();
+
+        let range = b"b".as_slice()..=b"d".as_slice();
+        let mut file = Vec::new();
+        let stats = db.export_sst(range.clone(), &mut file).await.unwrap();
+        assert_eq!(stats.num_entries, 2);
+        assert_eq!(stats.size, file.len() as u64);
+
+        // import the file into a fresh db
+        let imported = Db::builder("/tmp/test_export_sst_import", object_store)
+            .with_settings(test_db_options(0, 1024 * 1024, None))
+            .build()
+            .await
+            .unwrap();
+        let mut batch = WriteBatch::new();
+        for entry in read_exported_sst(file).await {
+            batch.put(&entry.key, entry.value.as_bytes().unwrap());
         }
+        imported.write(batch).await.unwrap();
 
-        // Verify our keys are in the SST
-        assert!(found_keys.contains(key1.as_slice()));
-        assert!(found_keys.contains(key2.as_slice()));
+        let mut expected = db.scan(range.clone()).await.unwrap();
+        let mut actual = imported.scan::<&[u8], _>(..).await.unwrap();
+        loop {
+            let (expected, actual) = (expected.next().await.unwrap(), actual.next().await.unwrap());
+            assert_eq!(
+                expected.map(|kv| (kv.key, kv.value)),
+                actual.as_ref().map(|kv| (kv.key.clone(), kv.value.clone()))
+            );
+            if actual.is_none() {
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_sst_includes_tombstones_and_expired_entries_if_asked() {
+        let clock = Arc::new(MockSystemClock::new());
+        let mut options = test_db_options(0, 1024 * 1024, None);
+        options.flush_interval = None;
+        let db = Db::builder("/tmp/test_export_sst_options", Arc::new(InMemory::new()))
+            .with_settings(options)
+            .with_system_clock(clock.clone())
+            .build()
+            .await
+            .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        db.put_with_options(b"a", b"live", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        db.put_with_options(b"b", b"deleted", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        db.delete_with_options(b"b", &write_options).await.unwrap();
+        db.put_with_options(
+            b"c",
+            b"expired",
+            &PutOptions {
+                ttl: Ttl::ExpireAfter(10),
+            },
+            &write_options,
+        )
+        .await
+        .unwrap();
+        clock.set(100);
+
+        let mut file = Vec::new();
+        let stats = db.export_sst::<&[u8], _, _>(.., &mut file).await.unwrap();
+        assert_eq!(stats.num_entries, 1);
+        let keys: Vec<_> = read_exported_sst(file)
+            .await
+            .into_iter()
+            .map(|entry| entry.key)
+            .collect();
+        assert_eq!(keys, vec![Bytes::from_static(b"a")]);
+
+        let mut file = Vec::new();
+        let options = ExportSstOptions {
+            include_tombstones: true,
+            include_expired: true,
+            ..ExportSstOptions::default()
+        };
+        let stats = db
+            .export_sst_with_options::<&[u8], _, _>(.., &mut file, &options)
+            .await
+            .unwrap();
+        assert_eq!(stats.num_entries, 3);
+        let entries = read_exported_sst(file).await;
+        assert_eq!(entries[0].value, ValueDeletable::Value(Bytes::from("live")));
+        assert_eq!(entries[1].value, ValueDeletable::Tombstone);
+        assert_eq!(
+            entries[2].value,
+            ValueDeletable::Value(Bytes::from("expired"))
+        );
+        assert_eq!(entries[2].expire_ts, Some(10));
     }
 
     #[tokio::test]
@@ -4997,6 +9951,61 @@ mod tests {
         let _ = join_handle.await;
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_close_does_not_wait_for_write_stalled_by_backpressure() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut options = test_db_options(0, 1024 * 1024, None);
+        options.flush_interval = None;
+        options.max_unflushed_bytes = 1;
+        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
+        let db = Db::builder(
+            "/tmp/test_close_does_not_wait_for_write_stalled_by_backpressure",
+            object_store,
+        )
+        .with_settings(options)
+        .with_metrics_recorder(metrics_recorder.clone())
+        .build()
+        .await
+        .unwrap();
+        let write_opts = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+
+        // leave bytes buffered in the WAL, so that the next write stalls.
+        let large_value = vec![b'x'; 8 * 1024];
+        db.put_with_options(b"key1", &large_value, &PutOptions::default(), &write_opts)
+            .await
+            .unwrap();
+        let writer = db.clone();
+        let this_write_opts = write_opts.clone();
+        let stalled = tokio::spawn(async move {
+            writer
+                .put_with_options(b"key2", b"value2", &PutOptions::default(), &this_write_opts)
+                .await
+        });
+        tokio::time::timeout(Duration::from_secs(60), async {
+            while lookup_metric(&metrics_recorder, crate::db_stats::BACKPRESSURE_COUNT)
+                .is_none_or(|v| v == 0)
+            {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for backpressure to be applied");
+
+        tokio::time::timeout(Duration::from_secs(5), db.close())
+            .await
+            .expect("close waited for the stalled write")
+            .unwrap();
+        let err = tokio::time::timeout(Duration::from_secs(5), stalled)
+            .await
+            .expect("the stalled write didn't exit")
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Closed(CloseReason::Clean));
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_backpressure_waiter_exits_when_db_is_fenced() {
         // Build a DB whose WAL will not flush on a timer and whose backpressure
@@ -5404,362 +10413,964 @@ mod tests {
             .await
             .unwrap();
 
-        // Write some data to memtable
-        db.put(b"key1", b"value1").await.unwrap();
+        // Write some data to memtable
+        db.put(b"key1", b"value1").await.unwrap();
+
+        let val = db.get(b"key1").await.unwrap();
+        assert_eq!(val, Some(Bytes::from_static(b"value1")));
+
+        let state = db.inner.state.read();
+        let memtable = state.memtable();
+        assert_eq!(memtable.table().last_seq(), Some(1));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_should_read_uncommitted_data_if_read_level_uncommitted() {
+        let fp_registry = Arc::new(FailPointRegistry::new());
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = "/tmp/test_kv_store";
+        let kv_store = Db::builder(path, object_store.clone())
+            .with_settings(test_db_options(0, 1024, None))
+            .with_fp_registry(fp_registry.clone())
+            .build()
+            .await
+            .unwrap();
+
+        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "pause").unwrap();
+        kv_store
+            .put_with_options(
+                "foo".as_bytes(),
+                "bar".as_bytes(),
+                &PutOptions::default(),
+                &WriteOptions {
+                    await_durable: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // Validate uncommitted read
+        let val = kv_store
+            .get_with_options(
+                "foo".as_bytes(),
+                &ReadOptions::new().with_durability_filter(Memory),
+            )
+            .await
+            .unwrap();
+        assert_eq!(val, Some("bar".into()));
+
+        // Validate committed read should still return None
+        let val = kv_store
+            .get_with_options(
+                "foo".as_bytes(),
+                &ReadOptions::new().with_durability_filter(Remote),
+            )
+            .await
+            .unwrap();
+        assert_eq!(val, None);
+
+        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "off").unwrap();
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_should_read_only_committed_data() {
+        let fp_registry = Arc::new(FailPointRegistry::new());
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = "/tmp/test_kv_store";
+        let kv_store = Db::builder(path, object_store.clone())
+            .with_settings(test_db_options(0, 1024, None))
+            .with_fp_registry(fp_registry.clone())
+            .build()
+            .await
+            .unwrap();
+
+        kv_store
+            .put("foo".as_bytes(), "bar".as_bytes())
+            .await
+            .unwrap();
+        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "pause").unwrap();
+        kv_store
+            .put_with_options(
+                "foo".as_bytes(),
+                "bla".as_bytes(),
+                &PutOptions::default(),
+                &WriteOptions {
+                    await_durable: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let val = kv_store
+            .get_with_options(
+                "foo".as_bytes(),
+                &ReadOptions::new().with_durability_filter(Remote),
+            )
+            .await
+            .unwrap();
+        assert_eq!(val, Some("bar".into()));
+        let val = kv_store
+            .get_with_options(
+                "foo".as_bytes(),
+                &ReadOptions::new().with_durability_filter(Memory),
+            )
+            .await
+            .unwrap();
+        assert_eq!(val, Some("bla".into()));
+
+        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "off").unwrap();
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_should_delete_without_awaiting_flush() {
+        let fp_registry = Arc::new(FailPointRegistry::new());
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = "/tmp/test_kv_store";
+        let kv_store = Db::builder(path, object_store.clone())
+            .with_settings(test_db_options(0, 1024, None))
+            .with_fp_registry(fp_registry.clone())
+            .build()
+            .await
+            .unwrap();
+
+        kv_store
+            .put("foo".as_bytes(), "bar".as_bytes())
+            .await
+            .unwrap();
+        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "pause").unwrap();
+        kv_store
+            .delete_with_options(
+                "foo".as_bytes(),
+                &WriteOptions {
+                    await_durable: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let val = kv_store
+            .get_with_options(
+                "foo".as_bytes(),
+                &ReadOptions::new().with_durability_filter(Remote),
+            )
+            .await
+            .unwrap();
+        assert_eq!(val, Some("bar".into()));
+        let val = kv_store
+            .get_with_options(
+                "foo".as_bytes(),
+                &ReadOptions::new().with_durability_filter(Memory),
+            )
+            .await
+            .unwrap();
+        assert_eq!(val, None);
+
+        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "off").unwrap();
+        kv_store.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_should_read_only_committed_data() {
+        let fp_registry = Arc::new(FailPointRegistry::new());
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = "/tmp/test_kv_store";
+        let kv_store = Db::builder(path, object_store.clone())
+            .with_settings(test_db_options(0, 1024, None))
+            .with_fp_registry(fp_registry.clone())
+            .build()
+            .await
+            .unwrap();
+
+        // Write and commit some initial data
+        kv_store
+            .put("key1".as_bytes(), "committed1".as_bytes())
+            .await
+            .unwrap();
+        kv_store
+            .put("key2".as_bytes(), "committed2".as_bytes())
+            .await
+            .unwrap();
+        kv_store
+            .put("key3".as_bytes(), "committed3".as_bytes())
+            .await
+            .unwrap();
+
+        // Pause WAL writes to prevent new writes from being committed
+        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "pause").unwrap();
+
+        // Write uncommitted data
+        kv_store
+            .put_with_options(
+                "key2".as_bytes(),
+                "uncommitted2".as_bytes(),
+                &PutOptions::default(),
+                &WriteOptions {
+                    await_durable: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        kv_store
+            .put_with_options(
+                "key4".as_bytes(),
+                "uncommitted4".as_bytes(),
+                &PutOptions::default(),
+                &WriteOptions {
+                    await_durable: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // Scan with Remote filter should only see committed data
+        let mut iter = kv_store
+            .scan_with_options(
+                "key1".as_bytes().."key5".as_bytes(),
+                &ScanOptions::new().with_durability_filter(Remote),
+            )
+            .await
+            .unwrap();
+
+        let kv = iter.next().await.unwrap().unwrap();
+        assert_eq!(kv.key.as_ref(), b"key1");
+        assert_eq!(kv.value.as_ref(), b"committed1");
+
+        let kv = iter.next().await.unwrap().unwrap();
+        assert_eq!(kv.key.as_ref(), b"key2");
+        assert_eq!(kv.value.as_ref(), b"committed2"); // Old committed value
+
+        let kv = iter.next().await.unwrap().unwrap();
+        assert_eq!(kv.key.as_ref(), b"key3");
+        assert_eq!(kv.value.as_ref(), b"committed3");
+
+        // key4 should not be visible with Remote filter
+        assert_eq!(iter.next().await.unwrap(), None);
+
+        // Scan with Memory filter should see uncommitted data
+        let mut iter = kv_store
+            .scan_with_options(
+                "key1".as_bytes().."key5".as_bytes(),
+                &ScanOptions::new().with_durability_filter(Memory),
+            )
+            .await
+            .unwrap();
+
+        let kv = iter.next().await.unwrap().unwrap();
+        assert_eq!(kv.key.as_ref(), b"key1");
+        assert_eq!(kv.value.as_ref(), b"committed1");
 
-        let val = db.get(b"key1").await.unwrap();
-        assert_eq!(val, Some(Bytes::from_static(b"value1")));
+        let kv = iter.next().await.unwrap().unwrap();
+        assert_eq!(kv.key.as_ref(), b"key2");
+        assert_eq!(kv.value.as_ref(), b"uncommitted2"); // New uncommitted value
 
-        let state = db.inner.state.read();
-        let memtable = state.memtable();
-        assert_eq!(memtable.table().last_seq(), Some(1));
+        let kv = iter.next().await.unwrap().unwrap();
+        assert_eq!(kv.key.as_ref(), b"key3");
+        assert_eq!(kv.value.as_ref(), b"committed3");
+
+        let kv = iter.next().await.unwrap().unwrap();
+        assert_eq!(kv.key.as_ref(), b"key4");
+        assert_eq!(kv.value.as_ref(), b"uncommitted4"); // Uncommitted key visible
+
+        assert_eq!(iter.next().await.unwrap(), None);
+
+        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "off").unwrap();
+        kv_store.close().await.unwrap();
     }
 
-    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
-    async fn test_should_read_uncommitted_data_if_read_level_uncommitted() {
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_should_recover_imm_from_wal() {
         let fp_registry = Arc::new(FailPointRegistry::new());
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "pause").unwrap();
+
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
         let path = "/tmp/test_kv_store";
-        let kv_store = Db::builder(path, object_store.clone())
-            .with_settings(test_db_options(0, 1024, None))
+        let mut next_wal_id = 1;
+        let db = Db::builder(path, object_store.clone())
+            .with_settings(test_db_options(0, 128, None))
             .with_fp_registry(fp_registry.clone())
             .build()
             .await
             .unwrap();
+        next_wal_id += 1;
 
-        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "pause").unwrap();
-        kv_store
-            .put_with_options(
-                "foo".as_bytes(),
-                "bar".as_bytes(),
-                &PutOptions::default(),
-                &WriteOptions {
-                    await_durable: false,
-                    ..Default::default()
-                },
-            )
-            .await
-            .unwrap();
+        // subscribe to status manager to get notified when db is closed
+        let mut rx = db.inner.status_manager.subscribe();
 
-        // Validate uncommitted read
-        let val = kv_store
-            .get_with_options(
-                "foo".as_bytes(),
-                &ReadOptions::new().with_durability_filter(Memory),
-            )
+        // write a few keys that will result in memtable flushes
+        let key1 = [b'a'; 32];
+        let value1 = [b'b'; 96];
+        db.put(key1, value1).await.unwrap();
+        next_wal_id += 1;
+        let key2 = [b'c'; 32];
+        let value2 = [b'd'; 96];
+        db.put(key2, value2).await.unwrap();
+        next_wal_id += 1;
+
+        let reader = Db::builder(path, object_store.clone())
+            .with_settings(test_db_options(0, 128, None))
+            .with_fp_registry(fp_registry.clone())
+            .build()
             .await
             .unwrap();
-        assert_eq!(val, Some("bar".into()));
 
-        // Validate committed read should still return None
-        let val = kv_store
-            .get_with_options(
-                "foo".as_bytes(),
-                &ReadOptions::new().with_durability_filter(Remote),
-            )
+        // increment wal id for the empty wal
+        next_wal_id += 1;
+
+        // verify that we reload imm
+        let db_state = reader.inner.state.read().view();
+        assert_eq!(db_state.state.imm_memtable.len(), 2);
+
+        // one empty wal and two wals for the puts
+        assert_eq!(
+            db_state
+                .state
+                .imm_memtable
+                .front()
+                .unwrap()
+                .recent_flushed_wal_id(),
+            1 + 2
+        );
+        assert_eq!(
+            db_state
+                .state
+                .imm_memtable
+                .get(1)
+                .unwrap()
+                .recent_flushed_wal_id(),
+            2
+        );
+        assert_eq!(db_state.state.core().next_wal_sst_id, next_wal_id);
+        assert_eq!(
+            reader.get(key1).await.unwrap(),
+            Some(Bytes::copy_from_slice(&value1))
+        );
+        assert_eq!(
+            reader.get(key2).await.unwrap(),
+            Some(Bytes::copy_from_slice(&value2))
+        );
+
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "off").unwrap();
+
+        // wait for the background task to report the Fenced error
+        rx.wait_for(|status| status.close_reason.is_some())
             .await
             .unwrap();
-        assert_eq!(val, None);
+        assert_eq!(
+            db.inner.status_manager.status().close_reason,
+            Some(crate::error::CloseReason::Fenced)
+        );
 
-        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "off").unwrap();
-        kv_store.close().await.unwrap();
+        db.close().await.unwrap();
+        reader.close().await.unwrap();
     }
 
-    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
-    async fn test_should_read_only_committed_data() {
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_writes_fail_at_max_immutable_memtables() {
         let fp_registry = Arc::new(FailPointRegistry::new());
+        // block L0 uploads so that frozen memtables pile up
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "pause").unwrap();
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let path = "/tmp/test_kv_store";
-        let kv_store = Db::builder(path, object_store.clone())
-            .with_settings(test_db_options(0, 1024, None))
+        let mut options = test_db_options(0, 128, None);
+        options.max_immutable_memtables = Some(2);
+        let db = Db::builder("/tmp/test_max_immutable_memtables", object_store)
+            .with_settings(options)
             .with_fp_registry(fp_registry.clone())
             .build()
             .await
             .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        let value = [b'v'; 256];
 
-        kv_store
-            .put("foo".as_bytes(), "bar".as_bytes())
+        // each write is larger than l0_sst_size_bytes, so it freezes the memtable
+        for key in [b"key1", b"key2"] {
+            db.put_with_options(key, value, &PutOptions::default(), &write_options)
+                .await
+                .unwrap();
+        }
+        assert_eq!(db.inner.state.read().state().imm_memtable.len(), 2);
+
+        let err = db
+            .put_with_options(b"key3", value, &PutOptions::default(), &write_options)
             .await
-            .unwrap();
-        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "pause").unwrap();
-        kv_store
-            .put_with_options(
-                "foo".as_bytes(),
-                "bla".as_bytes(),
-                &PutOptions::default(),
-                &WriteOptions {
-                    await_durable: false,
-                    ..Default::default()
-                },
-            )
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Unavailable);
+        assert_eq!(db.inner.state.read().state().imm_memtable.len(), 2);
+        assert_eq!(db.get(b"key3").await.unwrap(), None);
+
+        // once flushing resumes, writes are accepted again
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "off").unwrap();
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            db.flush_with_options(FlushOptions {
+                flush_type: FlushType::MemTable,
+            }),
+        )
+        .await
+        .expect("timed out flushing memtables")
+        .unwrap();
+        db.put_with_options(b"key3", value, &PutOptions::default(), &write_options)
             .await
             .unwrap();
+        db.close().await.unwrap();
+    }
 
-        let val = kv_store
-            .get_with_options(
-                "foo".as_bytes(),
-                &ReadOptions::new().with_durability_filter(Remote),
-            )
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_max_memtable_bytes_throttles_across_small_memtables() {
+        let fp_registry = Arc::new(FailPointRegistry::new());
+        // block L0 uploads so that frozen memtables pile up
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "pause").unwrap();
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut options = test_db_options(0, 128, None);
+        options.max_memtable_bytes = Some(700);
+        let metrics_recorder = Arc::new(DefaultMetricsRecorder::new());
+        let db = Db::builder("/tmp/test_max_memtable_bytes", object_store)
+            .with_settings(options)
+            .with_fp_registry(fp_registry.clone())
+            .with_metrics_recorder(metrics_recorder.clone())
+            .build()
             .await
             .unwrap();
-        assert_eq!(val, Some("bar".into()));
-        let val = kv_store
-            .get_with_options(
-                "foo".as_bytes(),
-                &ReadOptions::new().with_durability_filter(Memory),
-            )
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        let value = [b'v'; 256];
+
+        // each write is larger than l0_sst_size_bytes, so it freezes the memtable,
+        // and three of them exceed the budget together
+        for key in [b"key1", b"key2", b"key3"] {
+            db.put_with_options(key, value, &PutOptions::default(), &write_options)
+                .await
+                .unwrap();
+        }
+        {
+            let guard = db.inner.state.read();
+            let imms = &guard.state().imm_memtable;
+            assert_eq!(imms.len(), 3);
+            assert!(imms
+                .iter()
+                .all(|imm| imm.table().metadata().entries_size_in_bytes < 700));
+        }
+
+        let db = Arc::new(db);
+        let blocked_db = db.clone();
+        let mut blocked_write = tokio::spawn(async move {
+            blocked_db
+                .put_with_options(b"key4", value, &PutOptions::default(), &write_options)
+                .await
+        });
+        assert!(
+            tokio::time::timeout(Duration::from_millis(500), &mut blocked_write)
+                .await
+                .is_err(),
+            "write should wait for memtables to flush"
+        );
+        assert!(
+            lookup_metric(&metrics_recorder, crate::db_stats::BACKPRESSURE_COUNT)
+                .is_some_and(|count| count > 0)
+        );
+        assert!(matches!(
+            db.try_put(b"key5", value).await.unwrap(),
+            PutOutcome::WouldBlock { .. }
+        ));
+
+        // once flushing resumes, the blocked write is applied
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "off").unwrap();
+        tokio::time::timeout(Duration::from_secs(10), blocked_write)
             .await
+            .expect("timed out waiting for the blocked write")
+            .unwrap()
             .unwrap();
-        assert_eq!(val, Some("bla".into()));
-
-        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "off").unwrap();
-        kv_store.close().await.unwrap();
+        assert_eq!(
+            db.get(b"key4").await.unwrap(),
+            Some(Bytes::copy_from_slice(&value))
+        );
+        db.close().await.unwrap();
     }
 
-    #[tokio::test]
-    async fn test_should_delete_without_awaiting_flush() {
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_try_put_would_block_on_saturated_immutable_queue() {
         let fp_registry = Arc::new(FailPointRegistry::new());
+        // block L0 uploads so that frozen memtables pile up
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "pause").unwrap();
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let path = "/tmp/test_kv_store";
-        let kv_store = Db::builder(path, object_store.clone())
-            .with_settings(test_db_options(0, 1024, None))
+        let mut options = test_db_options(0, 128, None);
+        options.max_immutable_memtables = Some(2);
+        let db = Db::builder("/tmp/test_try_put_would_block", object_store)
+            .with_settings(options)
             .with_fp_registry(fp_registry.clone())
             .build()
             .await
             .unwrap();
+        let value = [b'v'; 256];
+
+        // each write is larger than l0_sst_size_bytes, so it freezes the memtable
+        for key in [b"key1", b"key2"] {
+            let outcome = db.try_put(key, value).await.unwrap();
+            assert!(matches!(outcome, PutOutcome::Applied(_)));
+        }
+        assert_eq!(db.inner.state.read().state().imm_memtable.len(), 2);
+
+        let outcome = db.try_put(b"key3", value).await.unwrap();
+        let PutOutcome::WouldBlock {
+            queued_immutables,
+            queued_bytes,
+        } = outcome
+        else {
+            panic!("expected the write to be shed, got {:?}", outcome);
+        };
+        assert_eq!(queued_immutables, 2);
+        assert!(queued_bytes > 0);
+        assert_eq!(db.get(b"key3").await.unwrap(), None);
+
+        // once flushing resumes, writes are applied again
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "off").unwrap();
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            db.flush_with_options(FlushOptions {
+                flush_type: FlushType::MemTable,
+            }),
+        )
+        .await
+        .expect("timed out flushing memtables")
+        .unwrap();
+        let outcome = db.try_put(b"key3", value).await.unwrap();
+        assert!(matches!(outcome, PutOutcome::Applied(_)));
+        assert_eq!(
+            db.get(b"key3").await.unwrap(),
+            Some(Bytes::copy_from_slice(&value))
+        );
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_try_put_does_not_wait_for_durability() {
+        // without a flush interval the WAL is only flushed on request
+        let mut options = test_db_options(0, 1024 * 1024, None);
+        options.flush_interval = None;
+        let db = Db::builder("/tmp/test_try_put_not_durable", Arc::new(InMemory::new()))
+            .with_settings(options)
+            .with_system_clock(Arc::new(MockSystemClock::new()))
+            .build()
+            .await
+            .unwrap();
 
-        kv_store
-            .put("foo".as_bytes(), "bar".as_bytes())
-            .await
-            .unwrap();
-        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "pause").unwrap();
-        kv_store
-            .delete_with_options(
-                "foo".as_bytes(),
-                &WriteOptions {
-                    await_durable: false,
-                    ..Default::default()
-                },
-            )
+        let outcome = tokio::time::timeout(Duration::from_secs(5), db.try_put(b"key", b"value"))
             .await
+            .expect("try_put waited for the WAL to be flushed")
             .unwrap();
 
-        let val = kv_store
-            .get_with_options(
-                "foo".as_bytes(),
-                &ReadOptions::new().with_durability_filter(Remote),
-            )
-            .await
-            .unwrap();
-        assert_eq!(val, Some("bar".into()));
-        let val = kv_store
-            .get_with_options(
-                "foo".as_bytes(),
-                &ReadOptions::new().with_durability_filter(Memory),
-            )
-            .await
-            .unwrap();
-        assert_eq!(val, None);
+        assert!(matches!(outcome, PutOutcome::Applied(_)));
+        assert_eq!(
+            db.get(b"key").await.unwrap(),
+            Some(Bytes::from_static(b"value"))
+        );
+        db.close().await.unwrap();
+    }
 
-        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "off").unwrap();
-        kv_store.close().await.unwrap();
+    fn tenant_write_options(tenant: &str) -> WriteOptions {
+        WriteOptions {
+            await_durable: false,
+            tenant: Some(tenant.to_string()),
+            ..WriteOptions::default()
+        }
     }
 
     #[tokio::test]
-    async fn test_scan_should_read_only_committed_data() {
-        let fp_registry = Arc::new(FailPointRegistry::new());
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let path = "/tmp/test_kv_store";
-        let kv_store = Db::builder(path, object_store.clone())
-            .with_settings(test_db_options(0, 1024, None))
-            .with_fp_registry(fp_registry.clone())
+    async fn test_rate_limited_tenant_does_not_block_other_tenants() {
+        let limiter = TokenBucketRateLimiter::new(RateLimitMode::Reject)
+            .with_tenant_limit(
+                "a",
+                TenantRateLimit {
+                    rate: 0.001,
+                    burst: 1,
+                },
+            )
+            .unwrap()
+            .with_tenant_limit(
+                "b",
+                TenantRateLimit {
+                    rate: 0.001,
+                    burst: 3,
+                },
+            )
+            .unwrap();
+        let db = Db::builder("/tmp/test_rate_limited_tenant", Arc::new(InMemory::new()))
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .with_rate_limiter(Arc::new(limiter))
             .build()
             .await
             .unwrap();
+        let put = |key: &'static [u8], tenant: &str| {
+            let options = tenant_write_options(tenant);
+            let db = &db;
+            async move {
+                db.put_with_options(key, b"value", &PutOptions::default(), &options)
+                    .await
+            }
+        };
 
-        // Write and commit some initial data
-        kv_store
-            .put("key1".as_bytes(), "committed1".as_bytes())
-            .await
+        put(b"a1", "a").await.unwrap();
+        let err = put(b"a2", "a").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unavailable);
+        for key in [b"b1", b"b2", b"b3"] {
+            put(key, "b").await.unwrap();
+        }
+        put(b"c1", "c").await.unwrap();
+        db.put(b"untagged", b"value").await.unwrap();
+
+        assert_eq!(db.get(b"a2").await.unwrap(), None);
+        assert!(db.get(b"b3").await.unwrap().is_some());
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_waiting_tenant_does_not_block_other_tenants() {
+        let clock = Arc::new(MockSystemClock::new());
+        let limiter = TokenBucketRateLimiter::new(RateLimitMode::Wait)
+            .with_tenant_limit(
+                "a",
+                TenantRateLimit {
+                    rate: 1.0,
+                    burst: 1,
+                },
+            )
             .unwrap();
-        kv_store
-            .put("key2".as_bytes(), "committed2".as_bytes())
+        let mut options = test_db_options(0, 64 * 1024, None);
+        options.flush_interval = None;
+        let db = Arc::new(
+            Db::builder("/tmp/test_waiting_tenant", Arc::new(InMemory::new()))
+                .with_settings(options)
+                .with_system_clock(clock.clone())
+                .with_rate_limiter(Arc::new(limiter))
+                .build()
+                .await
+                .unwrap(),
+        );
+        let put_options = PutOptions::default();
+        db.put_with_options(b"a1", b"value", &put_options, &tenant_write_options("a"))
             .await
             .unwrap();
-        kv_store
-            .put("key3".as_bytes(), "committed3".as_bytes())
+
+        let waiting = tokio::spawn({
+            let db = db.clone();
+            async move {
+                db.put_with_options(
+                    b"a2",
+                    b"value",
+                    &PutOptions::default(),
+                    &tenant_write_options("a"),
+                )
+                .await
+            }
+        });
+        db.put_with_options(b"b1", b"value", &put_options, &tenant_write_options("b"))
             .await
             .unwrap();
+        assert!(!waiting.is_finished());
+        assert_eq!(db.get(b"a2").await.unwrap(), None);
 
-        // Pause WAL writes to prevent new writes from being committed
-        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "pause").unwrap();
-
-        // Write uncommitted data
-        kv_store
-            .put_with_options(
-                "key2".as_bytes(),
-                "uncommitted2".as_bytes(),
-                &PutOptions::default(),
-                &WriteOptions {
-                    await_durable: false,
-                    ..Default::default()
-                },
-            )
+        clock.advance(Duration::from_secs(1)).await;
+        tokio::time::timeout(Duration::from_secs(10), waiting)
             .await
+            .expect("timed out waiting for the held back write")
+            .unwrap()
             .unwrap();
-        kv_store
-            .put_with_options(
-                "key4".as_bytes(),
-                "uncommitted4".as_bytes(),
-                &PutOptions::default(),
-                &WriteOptions {
-                    await_durable: false,
-                    ..Default::default()
-                },
-            )
+        assert!(db.get(b"a2").await.unwrap().is_some());
+    }
+
+    async fn collect_changes(db: &Db, seq: u64) -> Vec<(Bytes, Option<Bytes>, u64)> {
+        db.changes_since(seq)
             .await
-            .unwrap();
+            .unwrap()
+            .into_iter()
+            .map(|entry| {
+                let value = match entry.value {
+                    ValueDeletable::Value(value) => Some(value),
+                    _ => None,
+                };
+                (entry.key, value, entry.seq)
+            })
+            .collect()
+    }
 
-        // Scan with Remote filter should only see committed data
-        let mut iter = kv_store
-            .scan_with_options(
-                "key1".as_bytes().."key5".as_bytes(),
-                &ScanOptions::new().with_durability_filter(Remote),
-            )
+    #[tokio::test]
+    async fn test_changes_since_can_be_consumed_incrementally() {
+        let db = Db::builder("/tmp/test_changes_since", Arc::new(InMemory::new()))
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .build()
             .await
             .unwrap();
+        let start = db.current_sequence();
+        db.put(b"b", b"v1").await.unwrap();
+        db.put(b"a", b"v1").await.unwrap();
+        db.delete(b"b").await.unwrap();
 
-        let kv = iter.next().await.unwrap().unwrap();
-        assert_eq!(kv.key.as_ref(), b"key1");
-        assert_eq!(kv.value.as_ref(), b"committed1");
+        let first = collect_changes(&db, start).await;
+        assert_eq!(
+            first,
+            vec![
+                (
+                    Bytes::from_static(b"b"),
+                    Some(Bytes::from_static(b"v1")),
+                    start + 1
+                ),
+                (
+                    Bytes::from_static(b"a"),
+                    Some(Bytes::from_static(b"v1")),
+                    start + 2
+                ),
+                (Bytes::from_static(b"b"), None, start + 3),
+            ]
+        );
 
-        let kv = iter.next().await.unwrap().unwrap();
-        assert_eq!(kv.key.as_ref(), b"key2");
-        assert_eq!(kv.value.as_ref(), b"committed2"); // Old committed value
+        let last_seen = first.last().unwrap().2;
+        let mut batch = WriteBatch::new();
+        batch.put(b"c", b"v1");
+        batch.put(b"a", b"v2");
+        db.write(batch).await.unwrap();
+        let second = collect_changes(&db, last_seen).await;
+        assert_eq!(
+            second,
+            vec![
+                (
+                    Bytes::from_static(b"a"),
+                    Some(Bytes::from_static(b"v2")),
+                    start + 4
+                ),
+                (
+                    Bytes::from_static(b"c"),
+                    Some(Bytes::from_static(b"v1")),
+                    start + 4
+                ),
+            ]
+        );
+        assert!(collect_changes(&db, start + 4).await.is_empty());
 
-        let kv = iter.next().await.unwrap().unwrap();
-        assert_eq!(kv.key.as_ref(), b"key3");
-        assert_eq!(kv.value.as_ref(), b"committed3");
+        // flushing to L0 drops the changes from retention
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        let Err(err) = db.changes_since(last_seen).await else {
+            panic!("expected changes flushed to L0 not to be retained");
+        };
+        assert_eq!(err.kind(), ErrorKind::Invalid);
+        assert!(collect_changes(&db, start + 4).await.is_empty());
+        db.close().await.unwrap();
+    }
 
-        // key4 should not be visible with Remote filter
-        assert_eq!(iter.next().await.unwrap(), None);
+    /// Keeps the larger of two values put to the same key.
+    struct MaxValueResolver;
 
-        // Scan with Memory filter should see uncommitted data
-        let mut iter = kv_store
-            .scan_with_options(
-                "key1".as_bytes().."key5".as_bytes(),
-                &ScanOptions::new().with_durability_filter(Memory),
-            )
+    impl ConflictResolver for MaxValueResolver {
+        fn resolve(&self, _key: &[u8], earlier: &[u8], later: &[u8]) -> ConflictWinner {
+            if later >= earlier {
+                ConflictWinner::Later
+            } else {
+                ConflictWinner::Earlier
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conflict_resolver_picks_value_of_key_put_twice_in_batch() {
+        let db = Db::builder("/tmp/test_conflict_resolver", Arc::new(InMemory::new()))
+            .with_settings(test_db_options(0, 64 * 1024, None))
+            .with_conflict_resolver(Arc::new(MaxValueResolver))
+            .build()
             .await
             .unwrap();
 
-        let kv = iter.next().await.unwrap().unwrap();
-        assert_eq!(kv.key.as_ref(), b"key1");
-        assert_eq!(kv.value.as_ref(), b"committed1");
-
-        let kv = iter.next().await.unwrap().unwrap();
-        assert_eq!(kv.key.as_ref(), b"key2");
-        assert_eq!(kv.value.as_ref(), b"uncommitted2"); // New uncommitted value
+        let mut batch = WriteBatch::new();
+        batch.put(b"key", b"larger");
+        batch.put(b"key", b"large");
+        db.write(batch).await.unwrap();
 
-        let kv = iter.next().await.unwrap().unwrap();
-        assert_eq!(kv.key.as_ref(), b"key3");
-        assert_eq!(kv.value.as_ref(), b"committed3");
+        assert_eq!(
+            db.get(b"key").await.unwrap(),
+            Some(Bytes::from_static(b"larger"))
+        );
 
-        let kv = iter.next().await.unwrap().unwrap();
-        assert_eq!(kv.key.as_ref(), b"key4");
-        assert_eq!(kv.value.as_ref(), b"uncommitted4"); // Uncommitted key visible
+        db.put_all([(b"key2", &b"larger"[..]), (b"key2", &b"large"[..])])
+            .await
+            .unwrap();
+        assert_eq!(
+            db.get(b"key2").await.unwrap(),
+            Some(Bytes::from_static(b"larger"))
+        );
+        db.close().await.unwrap();
+    }
 
-        assert_eq!(iter.next().await.unwrap(), None);
+    /// Freezes memtables that are big enough once they stop receiving writes.
+    struct FlushWhenIdle;
 
-        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "off").unwrap();
-        kv_store.close().await.unwrap();
+    impl FlushPolicy for FlushWhenIdle {
+        fn should_freeze(&self, signals: &FlushSignals) -> bool {
+            signals.estimated_sst_size >= 1024
+                && signals.since_last_write >= Duration::from_secs(60)
+        }
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
-    async fn test_should_recover_imm_from_wal() {
-        let fp_registry = Arc::new(FailPointRegistry::new());
-        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "pause").unwrap();
-
-        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
-        let path = "/tmp/test_kv_store";
-        let mut next_wal_id = 1;
-        let db = Db::builder(path, object_store.clone())
-            .with_settings(test_db_options(0, 128, None))
-            .with_fp_registry(fp_registry.clone())
+    async fn test_flush_policy_freezes_idle_memtable_without_a_write() {
+        let clock = Arc::new(MockSystemClock::new());
+        let mut options = test_db_options(0, 64, None);
+        options.flush_interval = None;
+        let db = Db::builder("/tmp/test_flush_policy", Arc::new(InMemory::new()))
+            .with_settings(options)
+            .with_system_clock(clock.clone())
+            .with_flush_policy(Arc::new(FlushWhenIdle))
             .build()
             .await
             .unwrap();
-        next_wal_id += 1;
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..WriteOptions::default()
+        };
+        let put = |key: u8| {
+            let db = &db;
+            let write_options = &write_options;
+            async move {
+                db.put_with_options([key; 16], [key; 256], &PutOptions::default(), write_options)
+                    .await
+                    .unwrap();
+            }
+        };
+        let memtable_is_empty = || db.inner.state.read().memtable().is_empty();
 
-        // subscribe to status manager to get notified when db is closed
-        let mut rx = db.inner.status_manager.subscribe();
+        // idle, but too small to freeze
+        put(1).await;
+        clock.advance(Duration::from_secs(60)).await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!memtable_is_empty());
 
-        // write a few keys that will result in memtable flushes
-        let key1 = [b'a'; 32];
-        let value1 = [b'b'; 96];
-        db.put(key1, value1).await.unwrap();
-        next_wal_id += 1;
-        let key2 = [b'c'; 32];
-        let value2 = [b'd'; 96];
-        db.put(key2, value2).await.unwrap();
-        next_wal_id += 1;
+        // big enough, but not idle yet
+        for key in 2..8 {
+            put(key).await;
+        }
+        assert!(!memtable_is_empty());
 
-        let reader = Db::builder(path, object_store.clone())
-            .with_settings(test_db_options(0, 128, None))
-            .with_fp_registry(fp_registry.clone())
+        clock.advance(Duration::from_secs(60)).await;
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while !memtable_is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("timed out waiting for the idle memtable to be frozen");
+        for key in 1..8 {
+            assert!(db.get([key; 16]).await.unwrap().is_some());
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_compact_now_reports_compactions() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = "/tmp/test_compact_now";
+        // only compact once the test asks for it
+        let enabled = Arc::new(AtomicBool::new(false));
+        let this_enabled = enabled.clone();
+        let scheduler = Arc::new(OnDemandCompactionSchedulerSupplier::new(Arc::new(
+            move |state| {
+                this_enabled.load(Ordering::SeqCst) && !state.manifest().core().tree.l0.is_empty()
+            },
+        )));
+        let db = Db::builder(path, object_store.clone())
+            .with_settings(test_db_options(0, 1024, None))
+            .with_compactor_builder(
+                CompactorBuilder::new(path, object_store.clone())
+                    .with_scheduler_supplier(scheduler)
+                    .with_options(CompactorOptions {
+                        poll_interval: Duration::from_millis(100),
+                        max_concurrent_compactions: 1,
+                        ..Default::default()
+                    }),
+            )
             .build()
             .await
             .unwrap();
-
-        // increment wal id for the empty wal
-        next_wal_id += 1;
-
-        // verify that we reload imm
-        let db_state = reader.inner.state.read().view();
-        assert_eq!(db_state.state.imm_memtable.len(), 2);
-
-        // one empty wal and two wals for the puts
-        assert_eq!(
-            db_state
-                .state
-                .imm_memtable
-                .front()
-                .unwrap()
-                .recent_flushed_wal_id(),
-            1 + 2
-        );
-        assert_eq!(
-            db_state
-                .state
-                .imm_memtable
-                .get(1)
-                .unwrap()
-                .recent_flushed_wal_id(),
-            2
-        );
-        assert_eq!(db_state.state.core().next_wal_sst_id, next_wal_id);
+        let flush_memtable = FlushOptions {
+            flush_type: FlushType::MemTable,
+        };
+        db.put(b"key1", b"value1").await.unwrap();
+        db.put(b"key2", b"value2").await.unwrap();
+        db.flush_with_options(flush_memtable.clone()).await.unwrap();
+        db.put(b"key1", b"value1b").await.unwrap();
+        db.delete(b"key2").await.unwrap();
+        db.flush_with_options(flush_memtable).await.unwrap();
+        assert_eq!(db.manifest().manifest.core.tree.l0.len(), 2);
+
+        // the second request arrives while the compaction of the first one runs,
+        // so it schedules nothing and doesn't wait for the first one's
+        enabled.store(true, Ordering::SeqCst);
+        let (first, second) = tokio::time::timeout(Duration::from_secs(10), async {
+            tokio::join!(db.compact_now(), db.compact_now())
+        })
+        .await
+        .expect("timed out waiting for compaction");
+        let report = first.unwrap();
+        let second = second.unwrap();
         assert_eq!(
-            reader.get(key1).await.unwrap(),
-            Some(Bytes::copy_from_slice(&value1))
+            second,
+            CompactionReport {
+                duration: second.duration,
+                ..CompactionReport::default()
+            }
         );
+        assert!(second.duration <= report.duration);
+        assert_eq!(report.compactions, 1);
+        assert_eq!(report.failed_compactions, 0);
+        assert_eq!(report.entries_read, 4);
+        // the older versions of key1 and key2 and key2's tombstone are dropped
+        assert_eq!(report.entries_written, 1);
+        assert_eq!(report.entries_dropped(), 3);
+        assert_eq!(report.duplicates_dropped, 2);
+        assert_eq!(report.tombstones_dropped, 1);
+        assert_eq!(report.expired_dropped, 0);
+        assert!(report.bytes_read > 0 && report.bytes_written > 0);
+
+        let ms = Arc::new(ManifestStore::new(&Path::from(path), object_store.clone()));
+        let sm = StoredManifest::load(ms, Arc::new(DefaultSystemClock::new()))
+            .await
+            .unwrap();
+        assert!(sm.db_state().tree.l0.is_empty());
+        assert_eq!(sm.db_state().tree.compacted.len(), 1);
         assert_eq!(
-            reader.get(key2).await.unwrap(),
-            Some(Bytes::copy_from_slice(&value2))
+            db.get(b"key1").await.unwrap(),
+            Some(Bytes::from_static(b"value1b"))
         );
 
-        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "off").unwrap();
+        // nothing is left to compact
+        let report = db.compact_now().await.unwrap();
+        assert_eq!(report.compactions, 0);
+        db.close().await.unwrap();
+    }
 
-        // wait for the background task to report the Fenced error
-        rx.wait_for(|status| status.close_reason.is_some())
+    #[tokio::test]
+    async fn test_compact_now_requires_compactor() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_compact_now_no_compactor", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
             .await
             .unwrap();
-        assert_eq!(
-            db.inner.status_manager.status().close_reason,
-            Some(crate::error::CloseReason::Fenced)
-        );
 
+        let err = db.compact_now().await.unwrap_err();
+
+        assert_eq!(err.kind(), crate::ErrorKind::Invalid);
         db.close().await.unwrap();
-        reader.close().await.unwrap();
     }
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -6142,6 +11753,104 @@ mod tests {
         assert_eq!(db2.inner.state.read().state().core().next_wal_sst_id, 5);
     }
 
+    #[tokio::test]
+    async fn test_fenced_writer_rejects_non_durable_writes() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = "/tmp/test_kv_store";
+        let non_durable = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+
+        let db1 = Db::builder(path, object_store.clone())
+            .with_settings(test_db_options(0, 128, None))
+            .build()
+            .await
+            .unwrap();
+        db1.put_with_options(b"1", b"1", &PutOptions::default(), &non_durable)
+            .await
+            .unwrap();
+
+        // opening db2 bumps the writer epoch and fences db1, which detects it
+        // when it next flushes its WAL.
+        let db2 = Db::builder(path, object_store.clone())
+            .with_settings(test_db_options(0, 128, None))
+            .build()
+            .await
+            .unwrap();
+        let err = db1.flush().await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Closed(CloseReason::Fenced));
+
+        // db1 rejects writes that don't wait for the WAL, too.
+        let err = db1
+            .put_with_options(b"2", b"2", &PutOptions::default(), &non_durable)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Closed(CloseReason::Fenced));
+        let err = db1.delete(b"1").await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Closed(CloseReason::Fenced));
+
+        assert_eq!(db2.get(b"1").await.unwrap(), None);
+        db2.put(b"2", b"2").await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_fenced_writer_rejects_batch_queued_before_fencing() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let fp_registry = Arc::new(FailPointRegistry::new());
+        let path = "/tmp/test_kv_store";
+        let non_durable = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+
+        let db = Arc::new(
+            Db::builder(path, object_store)
+                .with_settings(test_db_options(0, 128, None))
+                .with_fp_registry(fp_registry.clone())
+                .build()
+                .await
+                .unwrap(),
+        );
+
+        // hold the writer on a first batch, so that a second one is queued
+        // behind it.
+        fail_parallel::cfg(fp_registry.clone(), "write-batch-pre-commit", "pause").unwrap();
+        let this_db = db.clone();
+        let this_options = non_durable.clone();
+        let first = tokio::spawn(async move {
+            this_db
+                .put_with_options(b"1", b"1", &PutOptions::default(), &this_options)
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let this_db = db.clone();
+        let this_options = non_durable.clone();
+        let queued = tokio::spawn(async move {
+            this_db
+                .put_with_options(b"2", b"2", &PutOptions::default(), &this_options)
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        // simulate the db detecting that it's been fenced while the second
+        // batch is queued.
+        db.inner
+            .status_manager
+            .write_result(Err(SlateDBError::Fenced));
+
+        fail_parallel::cfg(fp_registry.clone(), "write-batch-pre-commit", "off").unwrap();
+        let _ = tokio::time::timeout(Duration::from_secs(10), first)
+            .await
+            .expect("the first batch was not applied");
+        let err = tokio::time::timeout(Duration::from_secs(10), queued)
+            .await
+            .expect("the queued batch was not rejected")
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Closed(CloseReason::Fenced));
+    }
+
     #[tokio::test]
     async fn test_invalid_clock_progression() {
         // Given:
@@ -6646,8 +12355,11 @@ mod tests {
             manifest_poll_interval: Duration::from_millis(100),
             manifest_update_timeout: Duration::from_secs(300),
             max_unflushed_bytes: 134_217_728,
+            max_immutable_memtables: None,
+            max_memtable_bytes: None,
             l0_max_ssts: 8,
             l0_max_ssts_per_key: 8,
+            flush_order: Default::default(),
             l0_flush_parallelism: 1,
             min_filter_keys,
             l0_sst_size_bytes,
@@ -6657,6 +12369,13 @@ mod tests {
             object_store_cache_options: ObjectStoreCacheOptions::default(),
             garbage_collector_options: None,
             default_ttl: ttl,
+            memtable_type: Default::default(),
+            memtable_filter_bits: crate::config::DEFAULT_MEMTABLE_FILTER_BITS,
+            front_code_immutable_memtables: false,
+            read_repair_sample_rate: crate::config::DEFAULT_READ_REPAIR_SAMPLE_RATE,
+            ttl_jitter: 0.0,
+            wal_replay_policy: Default::default(),
+            block_cache_warmup: None,
             block_format: None,
         }
     }
@@ -6981,6 +12700,40 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_sync_returns_durable_watermark_without_empty_flushes() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut opts = test_db_options(0, 1024, None);
+        opts.flush_interval = Some(Duration::MAX);
+        let db = Db::builder("/tmp/test_sync", object_store)
+            .with_settings(opts)
+            .build()
+            .await
+            .unwrap();
+        let write_opts = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        let mut last_seq = 0;
+        for i in 0..3u8 {
+            let handle = db
+                .put_with_options([i], b"value", &PutOptions::default(), &write_opts)
+                .await
+                .unwrap();
+            last_seq = handle.seqnum();
+        }
+        assert!(db.inner.oracle.last_remote_persisted_seq() < last_seq);
+
+        let watermark = db.sync().await.unwrap();
+        assert_eq!(watermark, last_seq);
+        assert_eq!(db.inner.oracle.last_remote_persisted_seq(), last_seq);
+
+        // nothing is pending, so no WAL SST is written
+        let wal_id = db.inner.wal_buffer.recent_flushed_wal_id();
+        assert_eq!(db.sync().await.unwrap(), watermark);
+        assert_eq!(db.inner.wal_buffer.recent_flushed_wal_id(), wal_id);
+    }
+
     #[tokio::test]
     async fn test_memtable_flush_updates_last_remote_persisted_seq() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
@@ -7115,6 +12868,75 @@ mod tests {
         assert_eq!(err.kind(), crate::ErrorKind::Invalid);
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn should_sum_concurrent_increments() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Arc::new(
+            Db::builder("/tmp/test_increment_concurrent", object_store)
+                .with_settings(test_db_options(0, 1024, None))
+                .with_merge_operator(Arc::new(IntegerAddMergeOperator))
+                .build()
+                .await
+                .unwrap(),
+        );
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let db = db.clone();
+                tokio::spawn(async move {
+                    let mut returned = Vec::new();
+                    for _ in 0..10 {
+                        returned.push(db.increment(b"counter", 1).await.unwrap());
+                    }
+                    returned
+                })
+            })
+            .collect();
+        let mut returned = Vec::new();
+        for task in tasks {
+            returned.extend(task.await.unwrap());
+        }
+
+        // every increment saw exactly the increments written before it
+        returned.sort();
+        assert_eq!(returned, (1..=80).collect::<Vec<i64>>());
+        let value = db.get(b"counter").await.unwrap().unwrap();
+        assert_eq!(IntegerAddMergeOperator::decode(&value), Some(80));
+    }
+
+    #[tokio::test]
+    async fn should_increment_missing_and_non_counter_values_from_zero() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_increment_from_zero", object_store.clone())
+            .with_settings(test_db_options(0, 1024, None))
+            .with_merge_operator(Arc::new(IntegerAddMergeOperator))
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(db.increment(b"missing", 3).await.unwrap(), 3);
+        db.put(b"text", b"not a counter").await.unwrap();
+        assert_eq!(db.increment(b"text", 5).await.unwrap(), 5);
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        assert_eq!(db.increment(b"text", -7).await.unwrap(), -2);
+        assert_eq!(
+            db.increment(b"missing", i64::MIN).await.unwrap(),
+            i64::MIN + 3
+        );
+
+        let db = Db::builder("/tmp/test_increment_no_operator", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+        let err = db.increment(b"counter", 1).await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Invalid);
+    }
+
     #[tokio::test]
     async fn should_error_when_writing_merge_without_merge_operator() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
@@ -7515,6 +13337,36 @@ mod tests {
         assert_eq!(handle.create_ts(), 300);
     }
 
+    #[tokio::test]
+    async fn test_put_all_and_delete_all_write_a_single_durable_batch() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder("/tmp/test_put_all_and_delete_all", object_store)
+            .with_settings(test_db_options(0, 1024, None))
+            .build()
+            .await
+            .unwrap();
+
+        let handle = db
+            .put_all([(b"a", b"1"), (b"b", b"2"), (b"c", b"3")])
+            .await
+            .unwrap();
+        // every entry shares the batch's seq, and the batch is durable on return
+        assert_eq!(handle.seqnum(), 1);
+        assert!(db.inner.oracle.last_remote_persisted_seq() >= handle.seqnum());
+        assert_eq!(db.get(b"b").await.unwrap(), Some(Bytes::from_static(b"2")));
+
+        let handle = db.delete_all([b"a", b"c"]).await.unwrap();
+        assert_eq!(handle.seqnum(), 2);
+        assert!(db.inner.oracle.last_remote_persisted_seq() >= handle.seqnum());
+        assert_eq!(db.get(b"a").await.unwrap(), None);
+        assert_eq!(db.get(b"b").await.unwrap(), Some(Bytes::from_static(b"2")));
+        assert_eq!(db.get(b"c").await.unwrap(), None);
+
+        let err = db.put_all(Vec::<(&[u8], &[u8])>::new()).await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Invalid);
+        db.close().await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_write_with_options_empty_batch_returns_empty_batch_error() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
@@ -8899,6 +14751,114 @@ mod tests {
         db.close().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_column_families_are_isolated_segments() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::builder(
+            "/tmp/test_column_families_are_isolated_segments",
+            object_store,
+        )
+        .with_settings(test_db_options(0, 1024, None))
+        .with_segment_extractor(Arc::new(crate::ColumnFamilyExtractor::new()))
+        .build()
+        .await
+        .unwrap();
+
+        db.put_cf("a", b"k1", b"a1").await.unwrap();
+        db.put_cf("a", b"k2", b"a2").await.unwrap();
+        // "ab" extends the name "a" but must not show up in scans of "a"
+        db.put_cf("ab", b"k1", b"ab1").await.unwrap();
+        db.put_cf("b", b"k1", b"b1").await.unwrap();
+        db.delete_cf("b", b"k1").await.unwrap();
+        let err = db.put(b"k1", b"raw").await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Invalid);
+        let err = db.put_cf("", b"k1", b"v").await.unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Invalid);
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+
+        let prefixes: Vec<Bytes> = db
+            .inner
+            .state
+            .read()
+            .state()
+            .core()
+            .segments
+            .iter()
+            .map(|segment| segment.prefix.clone())
+            .collect();
+        assert_eq!(
+            prefixes,
+            vec![
+                Bytes::from_static(b"\x01a"),
+                Bytes::from_static(b"\x01b"),
+                Bytes::from_static(b"\x02ab"),
+            ]
+        );
+
+        let scan = async |cf: &str, range: BytesRange| {
+            let mut iter = db.scan_cf(cf, range).await.unwrap();
+            let mut entries = Vec::new();
+            while let Some(kv) = iter.next().await.unwrap() {
+                entries.push((kv.key, kv.value));
+            }
+            entries
+        };
+        let kv = |key: &'static [u8], value: &'static [u8]| {
+            (Bytes::from_static(key), Bytes::from_static(value))
+        };
+        assert_eq!(
+            scan("a", BytesRange::from(..)).await,
+            vec![kv(b"k1", b"a1"), kv(b"k2", b"a2")]
+        );
+        assert_eq!(
+            scan("a", BytesRange::from(Bytes::from_static(b"k2")..)).await,
+            vec![kv(b"k2", b"a2")]
+        );
+        assert_eq!(
+            scan("ab", BytesRange::from(..)).await,
+            vec![kv(b"k1", b"ab1")]
+        );
+        assert_eq!(scan("b", BytesRange::from(..)).await, vec![]);
+        assert_eq!(
+            db.get_cf("ab", b"k1").await.unwrap(),
+            Some(Bytes::from_static(b"ab1"))
+        );
+        assert_eq!(db.get_cf("b", b"k1").await.unwrap(), None);
+
+        let mut iter = db.scan_cf::<&[u8], _>("a", ..).await.unwrap();
+        iter.seek(b"k2").await.unwrap();
+        assert_eq!(
+            iter.next().await.unwrap().map(|kv| kv.key),
+            Some(Bytes::from_static(b"k2"))
+        );
+        db.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_cf_should_reject_inverted_ranges_and_scan_nothing_for_empty_ones() {
+        let db = Db::open("/tmp/test_scan_cf_ranges", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        db.put_cf("a", b"5", b"v").await.unwrap();
+
+        let mut iter = db
+            .scan_cf("a", b"5".as_slice()..b"5".as_slice())
+            .await
+            .unwrap();
+        assert!(iter.next().await.unwrap().is_none());
+        let err = db
+            .scan_cf("a", b"9".as_slice()..b"3".as_slice())
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), crate::ErrorKind::Invalid);
+        db.close().await.unwrap();
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_wal_replay_l0_boundary_does_not_skip_unflushed_replay_batches() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
@@ -118,6 +118,8 @@ use crate::admin::Admin;
 use crate::batch_write::WriteBatchEventHandler;
 use crate::batch_write::WRITE_BATCH_TASK_NAME;
 use crate::cached_object_store::CachedObjectStore;
+use crate::checkpoint::CheckpointReaderConfig;
+use crate::compaction_digest::CompactionObserver;
 #[cfg(feature = "compaction_filters")]
 use crate::compaction_filter::CompactionFilterSupplier;
 use crate::compactions_store::CompactionsStore;
@@ -132,6 +134,7 @@ use crate::config::CompactorOptions;
 use crate::config::DbReaderOptions;
 use crate::config::GarbageCollectorOptions;
 use crate::config::{Settings, SstBlockSize};
+use crate::conflict_resolver::ConflictResolver;
 use crate::db::Db;
 use crate::db::DbInner;
 use crate::db_cache::SplitCache;
@@ -141,6 +144,7 @@ use crate::db_status::{ClosedResultWriter, DbStatusManager};
 use crate::dispatcher::MessageHandlerExecutor;
 use crate::error::SlateDBError;
 use crate::filter_policy::{BloomFilterPolicy, FilterPolicy};
+use crate::flush_policy::FlushPolicy;
 use crate::format::sst::{BlockTransformer, SsTableFormat};
 use crate::garbage_collector::GarbageCollector;
 use crate::garbage_collector::GC_TASK_NAME;
@@ -151,8 +155,10 @@ use crate::memtable_flusher::MemtableFlusher;
 use crate::merge_operator::MergeOperatorType;
 use crate::object_stores::ObjectStoreType;
 use crate::object_stores::ObjectStores;
+use crate::oracle::{MonotonicSequenceAllocator, SequenceAllocator};
 use crate::paths::PathResolver;
 use crate::rand::DbRand;
+use crate::rate_limiter::RateLimiter;
 use crate::retrying_object_store::RetryingObjectStore;
 use crate::store_provider::DefaultStoreProvider;
 use crate::tablestore::TableStore;
@@ -188,6 +194,11 @@ pub struct DbBuilder<P: Into<Path>> {
     filter_policies: Vec<Arc<dyn FilterPolicy>>,
     metrics_recorder: Arc<dyn MetricsRecorder>,
     segment_extractor: Option<Arc<dyn crate::prefix_extractor::PrefixExtractor>>,
+    sequence_allocator: Option<Arc<dyn SequenceAllocator>>,
+    sequence_import_mode: bool,
+    rate_limiter: Option<Arc<dyn RateLimiter>>,
+    flush_policy: Option<Arc<dyn FlushPolicy>>,
+    conflict_resolver: Option<Arc<dyn ConflictResolver>>,
 }
 
 impl<P: Into<Path>> DbBuilder<P> {
@@ -211,6 +222,11 @@ impl<P: Into<Path>> DbBuilder<P> {
             filter_policies: default_filter_policies(),
             metrics_recorder: Arc::new(NoopMetricsRecorder::new()),
             segment_extractor: None,
+            sequence_allocator: None,
+            sequence_import_mode: false,
+            rate_limiter: None,
+            flush_policy: None,
+            conflict_resolver: None,
         }
     }
 
@@ -240,6 +256,25 @@ impl<P: Into<Path>> DbBuilder<P> {
 
     /// Sets the separate object store dedicated specifically for WAL.
     ///
+    /// This is the extension point for keeping the WAL on a different backend
+    /// than the SSTs, e.g. a local disk through
+    /// [`object_store::local::LocalFileSystem`] for lower write latency, or a
+    /// custom append-only log wrapped in an [`ObjectStore`] implementation. The
+    /// WAL writer and recovery only use the following operations, so a custom
+    /// backend needs to support just these:
+    ///
+    /// - `put_opts` with [`object_store::PutMode::Create`] to write each WAL SST
+    ///   under a new, increasing id. The write must fail with
+    ///   [`object_store::Error::AlreadyExists`] if the id is taken, which is how
+    ///   a newer writer fences an older one.
+    /// - `list` to find the WAL SSTs to replay on open, and `head` and
+    ///   `get_range` to read them.
+    /// - `delete` for the garbage collector to remove WAL SSTs whose writes
+    ///   have been flushed to L0.
+    ///
+    /// Once a db has been opened with a WAL object store it must always be
+    /// opened with one.
+    ///
     /// NOTE: WAL durability and availability properties depend on the properties
     /// of the underlying object store. Make sure the configured object store is
     /// durable and available enough for your use case.
@@ -394,6 +429,62 @@ impl<P: Into<Path>> DbBuilder<P> {
         self
     }
 
+    /// Sets the allocator that assigns the sequence numbers of writes that
+    /// don't supply one in [`crate::config::WriteOptions::seqnum`]. See
+    /// [`SequenceAllocator`].
+    ///
+    /// Defaults to [`crate::MonotonicSequenceAllocator`], which numbers writes
+    /// consecutively from the last sequence number recovered on open.
+    pub fn with_sequence_allocator(mut self, allocator: Arc<dyn SequenceAllocator>) -> Self {
+        self.sequence_allocator = Some(allocator);
+        self
+    }
+
+    /// Sets whether writes may take a sequence number at or below the last one,
+    /// from the [`SequenceAllocator`] or [`crate::config::WriteOptions::seqnum`].
+    /// Otherwise such a write fails with an invalid sequence number error.
+    ///
+    /// This is meant for importing data with its original sequence numbers. A
+    /// write with an older sequence number than a version of the same key
+    /// already written is shadowed by it, and snapshots and transactions taken
+    /// while importing may see the imported writes out of order.
+    ///
+    /// Defaults to `false`.
+    pub fn with_sequence_import_mode(mut self, enabled: bool) -> Self {
+        self.sequence_import_mode = enabled;
+        self
+    }
+
+    /// Sets the policy that decides when to freeze the active memtable, so
+    /// that it's flushed to L0. See [`FlushPolicy`].
+    ///
+    /// Defaults to [`crate::DefaultFlushPolicy`], which freezes it once it
+    /// reaches [`Settings::l0_sst_size_bytes`] or after
+    /// [`Settings::max_wal_flushes_before_l0_flush`] WAL SSTs.
+    pub fn with_flush_policy(mut self, flush_policy: Arc<dyn FlushPolicy>) -> Self {
+        self.flush_policy = Some(flush_policy);
+        self
+    }
+
+    /// Sets the resolver that picks the value written for a key that a write
+    /// batch puts more than once. See [`ConflictResolver`].
+    ///
+    /// By default, the last value put is written.
+    pub fn with_conflict_resolver(mut self, conflict_resolver: Arc<dyn ConflictResolver>) -> Self {
+        self.conflict_resolver = Some(conflict_resolver);
+        self
+    }
+
+    /// Sets the limiter that decides whether the writes that name a tenant in
+    /// [`crate::config::WriteOptions::tenant`] may go ahead. See
+    /// [`RateLimiter`].
+    ///
+    /// Defaults to no limit.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<dyn RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
     /// Builds and opens the database.
     pub async fn build(self) -> Result<Db, crate::Error> {
         if self.settings.l0_flush_parallelism == 0 {
@@ -407,6 +498,16 @@ impl<P: Into<Path>> DbBuilder<P> {
                     .into(),
             ));
         }
+        if !(0.0..1.0).contains(&self.settings.ttl_jitter) {
+            return Err(crate::Error::invalid(
+                "invalid configuration: ttl_jitter must be at least 0 and less than 1".into(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.settings.read_repair_sample_rate) {
+            return Err(crate::Error::invalid(
+                "invalid configuration: read_repair_sample_rate must be between 0 and 1".into(),
+            ));
+        }
 
         let path = self.path.into();
         // TODO: proper URI generation, for now it works just as a flag
@@ -418,6 +519,17 @@ impl<P: Into<Path>> DbBuilder<P> {
             .unwrap_or_else(|| Arc::new(DefaultSystemClock::new()));
 
         let recorder = MetricsRecorderHelper::new(self.metrics_recorder, MetricLevel::default());
+        let checkpoint_readers = Arc::new(CheckpointReaderConfig {
+            path: path.clone(),
+            object_stores: ObjectStores::new(
+                self.main_object_store.clone(),
+                self.wal_object_store.clone(),
+            ),
+            merge_operator: self.merge_operator.clone(),
+            block_transformer: self.block_transformer.clone(),
+            filter_policies: self.filter_policies.clone(),
+            system_clock: system_clock.clone(),
+        });
         let retrying_main_object_store = instrumented_retrying_object_store(
             self.main_object_store,
             &recorder,
@@ -602,7 +714,16 @@ impl<P: Into<Path>> DbBuilder<P> {
                 status_manager.clone(),
                 self.segment_extractor.clone(),
             )
-            .await?,
+            .await?
+            .with_sequence_allocator(
+                self.sequence_allocator
+                    .clone()
+                    .unwrap_or_else(|| Arc::new(MonotonicSequenceAllocator)),
+                self.sequence_import_mode,
+            )
+            .with_rate_limiter(self.rate_limiter.clone())
+            .with_flush_policy(self.flush_policy.clone())
+            .with_conflict_resolver(self.conflict_resolver.clone()),
         );
 
         // Fence writers if WAL is enabled
@@ -644,6 +765,7 @@ impl<P: Into<Path>> DbBuilder<P> {
             })
         });
 
+        let mut compactor_tx = None;
         if let Some(compactor_builder) = compactor_builder {
             let mut builder = compactor_builder
                 .with_system_clock(system_clock.clone())
@@ -654,13 +776,16 @@ impl<P: Into<Path>> DbBuilder<P> {
                 builder = builder.with_merge_operator(operator);
             }
 
-            let (handler, rx) = builder
+            let (tx, rx) = async_channel::unbounded();
+            let handler = builder
                 .build_handler(
                     uncached_table_store.clone(),
                     manifest_store.clone(),
                     compactions_store.clone(),
+                    tx.clone(),
                 )
                 .await?;
+            compactor_tx = Some(SafeSender::new(tx, inner.status_manager.result_reader()));
             task_executor.add_handler(
                 COMPACTOR_TASK_NAME.to_string(),
                 Box::new(handler),
@@ -718,11 +843,14 @@ impl<P: Into<Path>> DbBuilder<P> {
                 .preload_cache(&cached_obj_store, &path_resolver)
                 .await?;
         }
+        inner.spawn_block_cache_warmup();
 
         // Create and return the Db instance
         Ok(Db {
             inner,
             task_executor,
+            compactor_tx,
+            checkpoint_readers,
         })
     }
 }
@@ -965,6 +1093,7 @@ pub struct CompactorBuilder<P: Into<Path>> {
     filter_policies: Vec<Arc<dyn FilterPolicy>>,
     #[cfg(feature = "compaction_filters")]
     compaction_filter_supplier: Option<Arc<dyn CompactionFilterSupplier>>,
+    compaction_observer: Option<Arc<dyn CompactionObserver>>,
 }
 
 #[allow(unused)]
@@ -985,6 +1114,7 @@ impl<P: Into<Path>> CompactorBuilder<P> {
             filter_policies: default_filter_policies(),
             #[cfg(feature = "compaction_filters")]
             compaction_filter_supplier: None,
+            compaction_observer: None,
         }
     }
 
@@ -1004,6 +1134,7 @@ impl<P: Into<Path>> CompactorBuilder<P> {
             filter_policies: self.filter_policies,
             #[cfg(feature = "compaction_filters")]
             compaction_filter_supplier: self.compaction_filter_supplier,
+            compaction_observer: self.compaction_observer,
         }
     }
 
@@ -1104,6 +1235,15 @@ impl<P: Into<Path>> CompactorBuilder<P> {
         self
     }
 
+    /// Sets an observer that receives digests of the live data read and
+    /// written by each compaction job, to check that compaction doesn't lose
+    /// or alter live data. See [`CompactionObserver`] for how the digests are
+    /// computed. Digests are only computed when an observer is set.
+    pub fn with_compaction_observer(mut self, observer: Arc<dyn CompactionObserver>) -> Self {
+        self.compaction_observer = Some(observer);
+        self
+    }
+
     /// Builds and returns a Compactor instance.
     pub fn build(self) -> Compactor {
         let path: Path = self.path.into();
@@ -1153,39 +1293,34 @@ impl<P: Into<Path>> CompactorBuilder<P> {
             self.merge_operator,
             #[cfg(feature = "compaction_filters")]
             self.compaction_filter_supplier,
+            self.compaction_observer,
         )
     }
 
     /// Build a CompactorEventHandler from this builder's configuration.
     ///
     /// Constructs the compaction scheduler, executor, and stats, then
-    /// returns the handler and its message receiver, which are needed
-    /// to register the compactor with the task executor in DbBuilder::build
+    /// returns the handler, which DbBuilder::build registers with the task
+    /// executor together with the receiver of `worker_tx`'s channel.
     pub(crate) async fn build_handler(
         self,
         table_store: Arc<TableStore>,
         manifest_store: Arc<ManifestStore>,
         compactions_store: Arc<CompactionsStore>,
-    ) -> Result<
-        (
-            CompactorEventHandler,
-            async_channel::Receiver<CompactorMessage>,
-        ),
-        SlateDBError,
-    > {
+        worker_tx: async_channel::Sender<CompactorMessage>,
+    ) -> Result<CompactorEventHandler, SlateDBError> {
         let options = Arc::new(self.options);
         let handle = self.compaction_runtime;
         let scheduler_supplier = self
             .scheduler_supplier
             .unwrap_or(Arc::new(SizeTieredCompactionSchedulerSupplier));
-        let (tx, rx) = async_channel::unbounded();
         let scheduler = Arc::from(scheduler_supplier.compaction_scheduler(&options));
         let stats = Arc::new(CompactionStats::new(&self.recorder));
         let executor = Arc::new(TokioCompactionExecutor::new(
             TokioCompactionExecutorOptions {
                 handle,
                 options: options.clone(),
-                worker_tx: tx,
+                worker_tx,
                 table_store,
                 rand: self.rand.clone(),
                 stats: stats.clone(),
@@ -1194,9 +1329,10 @@ impl<P: Into<Path>> CompactorBuilder<P> {
                 merge_operator: self.merge_operator,
                 #[cfg(feature = "compaction_filters")]
                 compaction_filter_supplier: self.compaction_filter_supplier,
+                compaction_observer: self.compaction_observer,
             },
         ));
-        let handler = CompactorEventHandler::new(
+        CompactorEventHandler::new(
             manifest_store,
             compactions_store,
             options,
@@ -1206,8 +1342,7 @@ impl<P: Into<Path>> CompactorBuilder<P> {
             stats,
             self.system_clock,
         )
-        .await?;
-        Ok((handler, rx))
+        .await
     }
 }
 
@@ -1817,6 +1952,64 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_db_builder_rejects_ttl_jitter_out_of_range() {
+        for ttl_jitter in [-0.1, 1.0, f64::NAN] {
+            let result = crate::Db::builder(
+                "test_db_builder_rejects_ttl_jitter_out_of_range",
+                Arc::new(InMemory::new()),
+            )
+            .with_settings(Settings {
+                ttl_jitter,
+                ..Settings::default()
+            })
+            .build()
+            .await;
+
+            let err = match result {
+                Ok(_) => panic!("expected ttl_jitter {ttl_jitter} to fail"),
+                Err(err) => err,
+            };
+
+            assert!(matches!(err.kind(), ErrorKind::Invalid));
+            assert!(
+                err.to_string()
+                    .contains("ttl_jitter must be at least 0 and less than 1"),
+                "unexpected error: {err}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_db_builder_rejects_read_repair_sample_rate_out_of_range() {
+        for read_repair_sample_rate in [-0.1, 1.1, f64::NAN] {
+            let result = crate::Db::builder(
+                "test_db_builder_rejects_read_repair_sample_rate_out_of_range",
+                Arc::new(InMemory::new()),
+            )
+            .with_settings(Settings {
+                read_repair_sample_rate,
+                ..Settings::default()
+            })
+            .build()
+            .await;
+
+            let err = match result {
+                Ok(_) => {
+                    panic!("expected read_repair_sample_rate {read_repair_sample_rate} to fail")
+                }
+                Err(err) => err,
+            };
+
+            assert!(matches!(err.kind(), ErrorKind::Invalid));
+            assert!(
+                err.to_string()
+                    .contains("read_repair_sample_rate must be between 0 and 1"),
+                "unexpected error: {err}"
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_shared_recorder_registers_object_store_metrics_for_db_gc_and_compactor() {
         // given:
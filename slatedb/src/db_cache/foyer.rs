@@ -124,4 +124,12 @@ impl DbCache for FoyerCache {
         // foyer cache doesn't support an entry count estimate
         0
     }
+
+    fn memory_usage(&self) -> u64 {
+        self.inner.usage() as u64
+    }
+
+    async fn clear(&self) {
+        self.inner.clear();
+    }
 }
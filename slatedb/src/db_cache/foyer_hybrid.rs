@@ -128,6 +128,16 @@ impl DbCache for FoyerHybridCache {
         0
     }
 
+    fn memory_usage(&self) -> u64 {
+        self.inner.memory().usage() as u64
+    }
+
+    async fn clear(&self) {
+        // Only drop the memory tier. The disk tier doesn't count against
+        // process memory and is expensive to rebuild.
+        self.inner.memory().clear();
+    }
+
     async fn close(&self) -> Result<(), crate::Error> {
         let memory_bytes = self.inner.memory().usage();
         info!(
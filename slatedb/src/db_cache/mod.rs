@@ -157,6 +157,21 @@ pub trait DbCache: Send + Sync {
     async fn close(&self) -> Result<(), crate::Error> {
         Ok(())
     }
+
+    /// Returns an estimate of the bytes the cache currently holds in memory.
+    ///
+    /// Used to report how much memory [`Db::trim_memory`](crate::Db::trim_memory)
+    /// released. The default implementation returns `0`.
+    fn memory_usage(&self) -> u64 {
+        0
+    }
+
+    /// Drop every entry the cache holds in memory.
+    ///
+    /// Called by [`Db::trim_memory`](crate::Db::trim_memory) to release memory
+    /// under pressure. Hybrid caches should leave their disk tier intact. The
+    /// default implementation is a no-op.
+    async fn clear(&self) {}
 }
 
 /// A key used to identify a cached entry.
@@ -459,6 +474,20 @@ impl DbCache for SplitCache {
         }
         Ok(())
     }
+
+    fn memory_usage(&self) -> u64 {
+        self.block_cache.as_ref().map_or(0, |c| c.memory_usage())
+            + self.meta_cache.as_ref().map_or(0, |c| c.memory_usage())
+    }
+
+    async fn clear(&self) {
+        if let Some(ref cache) = self.block_cache {
+            cache.clear().await;
+        }
+        if let Some(ref cache) = self.meta_cache {
+            cache.clear().await;
+        }
+    }
 }
 
 /// Wraps a [`DbCache`] to add statistics, error logging, and cache scoping.
@@ -623,6 +652,16 @@ impl DbCache for DbCacheWrapper {
     async fn close(&self) -> Result<(), crate::Error> {
         self.cache.close().await
     }
+
+    fn memory_usage(&self) -> u64 {
+        self.cache.memory_usage()
+    }
+
+    // Clears the wrapped cache as a whole, including entries of other scopes
+    // sharing it. Memory pressure is a process-wide concern.
+    async fn clear(&self) {
+        self.cache.clear().await
+    }
 }
 
 pub mod stats {
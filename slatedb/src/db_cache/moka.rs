@@ -133,4 +133,13 @@ impl DbCache for MokaCache {
     fn entry_count(&self) -> u64 {
         self.inner.entry_count()
     }
+
+    fn memory_usage(&self) -> u64 {
+        self.inner.weighted_size()
+    }
+
+    async fn clear(&self) {
+        self.inner.invalidate_all();
+        self.inner.run_pending_tasks().await;
+    }
 }
@@ -1,3 +1,4 @@
+use std::cmp::Reverse;
 use std::ops::{Bound, RangeBounds};
 use std::sync::Arc;
 
@@ -6,13 +7,16 @@ use log::{debug, warn};
 use tokio::sync::OnceCell;
 
 use crate::bytes_range::BytesRange;
-use crate::db_state::{SsTableHandle, SsTableId};
+use crate::config::{BlockCacheWarmupOptions, PreloadLevel};
+use crate::db_state::{SortedRun, SsTableHandle, SsTableId, SsTableView};
 use crate::error::SlateDBError;
 use crate::flatbuffer_types::SsTableIndexOwned;
-use crate::manifest::VersionedManifest;
+use crate::manifest::{ManifestCore, VersionedManifest};
 use crate::partitioned_keyspace::partitions_covering_range;
 use crate::tablestore::TableStore;
 
+pub(crate) const BLOCK_CACHE_WARMUP_TASK_NAME: &str = "block_cache_warmup";
+
 /// Cache content that [`DbCacheManagerOps::warm_sst`](crate::DbCacheManagerOps::warm_sst) should populate.
 #[derive(Clone, Debug)]
 pub enum CacheTarget {
@@ -77,6 +81,49 @@ pub(crate) async fn warm_sst_impl(
         );
         return Ok(());
     };
+    warm_sst_view(table_store, view, targets).await
+}
+
+/// Returns the SSTs that a block cache warmup with `options` loads, newest
+/// first across every tree: L0 SSTs by flush time, then the SSTs of sorted
+/// runs. See [`BlockCacheWarmupOptions`].
+pub(crate) fn plan_block_cache_warmup(
+    core: &ManifestCore,
+    options: &BlockCacheWarmupOptions,
+) -> Vec<SsTableView> {
+    // Each tree's L0 is already newest first. L0 SST ids are ULIDs minted at
+    // flush time, so a stable sort on their timestamp merges the trees while
+    // keeping that order for SSTs flushed in the same millisecond.
+    let mut l0: Vec<&SsTableView> = core.trees().flat_map(|tree| tree.l0.iter()).collect();
+    l0.sort_by_key(|view| Reverse(view.sst.id.unwrap_compacted_id().timestamp_ms()));
+    let mut sorted_runs: Vec<&SortedRun> = match options.level {
+        PreloadLevel::AllSst => core
+            .trees()
+            .flat_map(|tree| tree.compacted.iter())
+            .collect(),
+        PreloadLevel::L0Sst => Vec::new(),
+    };
+    // Sorted run ids grow as compactions write newer runs.
+    sorted_runs.sort_by_key(|sr| Reverse(sr.id));
+    let compacted = sorted_runs.into_iter().flat_map(|sr| sr.sst_views.iter());
+    let mut planned_bytes = 0;
+    l0.into_iter()
+        .chain(compacted)
+        .take_while(|view| {
+            planned_bytes += view.estimate_size();
+            planned_bytes <= options.max_bytes
+        })
+        .cloned()
+        .collect()
+}
+
+/// Loads `targets` of the SST that `view` refers to into the block cache.
+pub(crate) async fn warm_sst_view(
+    table_store: &Arc<TableStore>,
+    view: &SsTableView,
+    targets: &[CacheTarget],
+) -> Result<(), crate::Error> {
+    let sst_id = view.sst.id;
     let handle = view.sst.clone();
     let visible_ranges: Vec<BytesRange> = view
         .calculate_view_range(BytesRange::unbounded())
@@ -227,9 +274,11 @@ mod tests {
     use crate::config::{FlushOptions, FlushType, PutOptions, Settings, WriteOptions};
     use crate::db::Db;
     use crate::db_cache::{CachedKey, DbCache};
+    use crate::test_utils::FixedThreeBytePrefixExtractor;
     use crate::DbCacheManagerOps;
     use object_store::memory::InMemory;
     use object_store::ObjectStore;
+    use slatedb_common::clock::MockSystemClock;
 
     const PATH: &str = "/cache_manager_test";
 
@@ -817,4 +866,148 @@ mod tests {
 
         db.close().await.expect("close");
     }
+
+    async fn open_db_with_two_l0_ssts(object_store: Arc<dyn ObjectStore>) -> Vec<SsTableView> {
+        let db = open_db_single_sst(object_store).await;
+        write_keys(&db, 64).await;
+        flush_to_l0(&db).await;
+        write_keys(&db, 32).await;
+        flush_to_l0(&db).await;
+        let l0: Vec<SsTableView> = db.manifest().l0().iter().cloned().collect();
+        assert_eq!(l0.len(), 2);
+        db.close().await.expect("close");
+        l0
+    }
+
+    #[tokio::test]
+    async fn should_plan_warmup_newest_first_within_budget() {
+        let os: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let l0 = open_db_with_two_l0_ssts(os.clone()).await;
+        let db = open_db_single_sst(os).await;
+        let state = db.inner.state.read().state();
+        let plan = |max_bytes: u64| -> Vec<SsTableId> {
+            let options = BlockCacheWarmupOptions {
+                level: PreloadLevel::L0Sst,
+                max_bytes,
+            };
+            plan_block_cache_warmup(state.core(), &options)
+                .iter()
+                .map(|view| view.sst.id)
+                .collect()
+        };
+        let newest_size = l0[0].estimate_size();
+        let total_size = newest_size + l0[1].estimate_size();
+
+        assert_eq!(plan(newest_size - 1), vec![]);
+        assert_eq!(plan(newest_size), vec![l0[0].sst.id]);
+        assert_eq!(plan(total_size), vec![l0[0].sst.id, l0[1].sst.id]);
+
+        db.close().await.expect("close");
+    }
+
+    #[tokio::test]
+    async fn should_plan_warmup_newest_first_across_segments() {
+        // given: segment "aaa" holds the oldest and the newest L0 SSTs, and
+        // segment "bbb" holds one flushed in between
+        let os: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let clock = Arc::new(MockSystemClock::new());
+        let db = Db::builder(PATH, os)
+            .with_settings(Settings {
+                flush_interval: None,
+                ..Default::default()
+            })
+            .with_system_clock(clock.clone())
+            .with_segment_extractor(Arc::new(FixedThreeBytePrefixExtractor))
+            .build()
+            .await
+            .expect("failed to open db");
+        let mut flushed = Vec::new();
+        for (millis, key) in [(1_000, b"aaa-1"), (2_000, b"bbb-1"), (3_000, b"aaa-2")] {
+            clock.set(millis);
+            db.put_with_options(
+                key,
+                b"value",
+                &PutOptions::default(),
+                &WriteOptions {
+                    await_durable: false,
+                    ..Default::default()
+                },
+            )
+            .await
+            .expect("put failed");
+            flush_to_l0(&db).await;
+            let state = db.inner.state.read().state();
+            let tree = state
+                .core()
+                .tree_for_segment(&key[..3])
+                .expect("segment exists");
+            flushed.push(tree.l0.front().expect("flushed to L0").sst.id);
+        }
+
+        // when
+        let state = db.inner.state.read().state();
+        let options = BlockCacheWarmupOptions {
+            level: PreloadLevel::L0Sst,
+            max_bytes: u64::MAX,
+        };
+        let plan: Vec<SsTableId> = plan_block_cache_warmup(state.core(), &options)
+            .iter()
+            .map(|view| view.sst.id)
+            .collect();
+
+        // then
+        assert_eq!(plan, vec![flushed[2], flushed[1], flushed[0]]);
+
+        db.close().await.expect("close");
+    }
+
+    #[tokio::test]
+    async fn should_warm_newest_ssts_in_background_after_open() {
+        // given: a DB whose newest L0 SST fits the warmup budget but whose
+        // oldest does not
+        let os: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let l0 = open_db_with_two_l0_ssts(os.clone()).await;
+
+        // when
+        let db = Db::builder(PATH, os)
+            .with_settings(Settings {
+                flush_interval: None,
+                block_cache_warmup: Some(BlockCacheWarmupOptions {
+                    level: PreloadLevel::L0Sst,
+                    max_bytes: l0[0].estimate_size(),
+                }),
+                ..Default::default()
+            })
+            .build()
+            .await
+            .expect("failed to open db");
+
+        // then: the newest SST's blocks show up in the cache, the oldest's don't
+        let table_store = &db.inner.table_store;
+        for _ in 0..100 {
+            if cached_block_mask(table_store, l0[0].sst.id)
+                .await
+                .iter()
+                .all(|&b| b)
+            {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let newest = cached_block_mask(table_store, l0[0].sst.id).await;
+        assert!(!newest.is_empty(), "expected SST to have data blocks");
+        assert!(
+            newest.iter().all(|&b| b),
+            "expected all blocks of the newest SST cached, got {:?}",
+            newest,
+        );
+        let oldest = cached_block_mask(table_store, l0[1].sst.id).await;
+        assert!(
+            oldest.iter().all(|&b| !b),
+            "expected no blocks of the oldest SST cached, got {:?}",
+            oldest,
+        );
+
+        db.close().await.expect("close");
+    }
 }
@@ -1,15 +1,22 @@
+use std::time::Duration;
+
 use parking_lot::RwLockWriteGuard;
 
 use crate::db::DbInner;
 use crate::db_state::DbState;
 use crate::error::SlateDBError;
+use crate::flush_policy::FlushSignals;
 use crate::oracle::Oracle;
 use crate::wal_replay::ReplayedMemtable;
 
 impl DbInner {
+    /// Freezes the active memtable if the [`FlushPolicy`] decides to.
     pub(crate) fn maybe_freeze_current_memtable(&self) -> Result<(), SlateDBError> {
         let wal_id = self.wal_buffer.recent_flushed_wal_id();
         let mut guard = self.state.write();
+        if guard.memtable().is_empty() {
+            return Ok(());
+        }
         let meta = guard.memtable().metadata();
 
         let last_freeze_wal_id = guard
@@ -27,12 +34,26 @@ impl DbInner {
             .checked_sub(last_freeze_wal_id)
             .ok_or_else(|| SlateDBError::InvalidDBState)?;
 
-        if wal_id_gap < self.settings.max_wal_flushes_before_l0_flush
-            && l0_sst_size_est < self.settings.l0_sst_size_bytes
-        {
-            Ok(())
-        } else {
+        let imm_bytes: usize = guard
+            .state()
+            .imm_memtable
+            .iter()
+            .map(|imm| imm.table().metadata().entries_size_in_bytes)
+            .sum();
+        let now = self.system_clock.now().timestamp_millis();
+        let since = |tick: i64| Duration::from_millis(now.saturating_sub(tick).max(0) as u64);
+        let signals = FlushSignals {
+            estimated_sst_size: l0_sst_size_est,
+            since_first_write: since(meta.first_tick),
+            since_last_write: since(meta.last_tick),
+            wal_flushes_since_freeze: wal_id_gap,
+            memtable_bytes: meta.entries_size_in_bytes + imm_bytes,
+            max_memtable_bytes: self.settings.max_memtable_bytes,
+        };
+        if self.flush_policy.should_freeze(&signals) {
             self.freeze_memtable(&mut guard, wal_id)
+        } else {
+            Ok(())
         }
     }
 
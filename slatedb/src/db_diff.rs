@@ -0,0 +1,183 @@
+//! Key-by-key differences between two snapshots of a database. See
+//! [`crate::Db::diff`].
+
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::Stream;
+
+use crate::db_iter::DbIterator;
+use crate::db_snapshot::DbSnapshot;
+use crate::types::KeyValue;
+
+/// How a key changed between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// The key was added, or its value was modified, and now holds this value.
+    Put(Bytes),
+    /// The key was deleted.
+    Delete,
+}
+
+/// Iterates over the keys whose values differ between two snapshots, in
+/// ascending key order. See [`crate::Db::diff`] for the full contract.
+pub struct DbDiffIterator {
+    from: DbIterator,
+    to: DbIterator,
+    /// The next entry of each side, read ahead to compare keys. `None` once the
+    /// side is exhausted.
+    from_next: Option<KeyValue>,
+    to_next: Option<KeyValue>,
+    /// Whether `from_next` and `to_next` have been read yet.
+    started: bool,
+    /// The snapshots being compared, kept alive so that compaction retains the
+    /// versions the iterators read.
+    _snapshots: (Arc<DbSnapshot>, Arc<DbSnapshot>),
+}
+
+impl DbDiffIterator {
+    pub(crate) fn new(
+        from: DbIterator,
+        to: DbIterator,
+        snapshots: (Arc<DbSnapshot>, Arc<DbSnapshot>),
+    ) -> Self {
+        Self {
+            from,
+            to,
+            from_next: None,
+            to_next: None,
+            started: false,
+            _snapshots: snapshots,
+        }
+    }
+
+    /// Returns the next key that differs between the two snapshots and how it
+    /// changed, or `None` once both snapshots have been read to the end.
+    pub async fn next(&mut self) -> Result<Option<(Bytes, Change)>, crate::Error> {
+        if !self.started {
+            self.from_next = self.from.next().await?;
+            self.to_next = self.to.next().await?;
+            self.started = true;
+        }
+        loop {
+            let order = match (&self.from_next, &self.to_next) {
+                (None, None) => return Ok(None),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(from), Some(to)) => from.key.cmp(&to.key),
+            };
+            match order {
+                Ordering::Less => {
+                    let from = self.advance_from().await?;
+                    return Ok(Some((from.key, Change::Delete)));
+                }
+                Ordering::Greater => {
+                    let to = self.advance_to().await?;
+                    return Ok(Some((to.key, Change::Put(to.value))));
+                }
+                Ordering::Equal => {
+                    let from = self.advance_from().await?;
+                    let to = self.advance_to().await?;
+                    // the same version is the same value, so only compare the
+                    // values of keys that were rewritten
+                    if from.seq != to.seq && from.value != to.value {
+                        return Ok(Some((to.key, Change::Put(to.value))));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Converts this iterator into a [`Stream`] of the same differences.
+    pub fn into_stream(self) -> impl Stream<Item = Result<(Bytes, Change), crate::Error>> {
+        futures::stream::try_unfold(self, |mut iter| async move {
+            Ok(iter.next().await?.map(|change| (change, iter)))
+        })
+    }
+
+    async fn advance_from(&mut self) -> Result<KeyValue, crate::Error> {
+        let next = self.from.next().await?;
+        let current = std::mem::replace(&mut self.from_next, next);
+        Ok(current.expect("advanced an exhausted side of the diff"))
+    }
+
+    async fn advance_to(&mut self) -> Result<KeyValue, crate::Error> {
+        let next = self.to.next().await?;
+        let current = std::mem::replace(&mut self.to_next, next);
+        Ok(current.expect("advanced an exhausted side of the diff"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Db, ErrorKind};
+    use futures::TryStreamExt;
+    use object_store::memory::InMemory;
+    use object_store::ObjectStore;
+
+    #[tokio::test]
+    async fn should_yield_added_modified_and_deleted_keys_in_key_order() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let db = Db::open("test_db", object_store).await.unwrap();
+        db.put(b"deleted", b"v1").await.unwrap();
+        db.put(b"modified", b"v1").await.unwrap();
+        db.put(b"rewritten", b"v1").await.unwrap();
+        db.put(b"unchanged", b"v1").await.unwrap();
+        let from = db.snapshot().await.unwrap();
+
+        db.delete(b"deleted").await.unwrap();
+        db.put(b"modified", b"v2").await.unwrap();
+        db.put(b"rewritten", b"v1").await.unwrap();
+        db.put(b"added", b"v1").await.unwrap();
+        db.put(b"zz_added", b"v1").await.unwrap();
+        let to = db.snapshot().await.unwrap();
+        // writes after `to` are not part of the diff
+        db.put(b"later", b"v1").await.unwrap();
+
+        let diff = db.diff(from.clone(), to.clone()).await.unwrap();
+        let changes: Vec<_> = diff.into_stream().try_collect().await.unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                (Bytes::from("added"), Change::Put(Bytes::from("v1"))),
+                (Bytes::from("deleted"), Change::Delete),
+                (Bytes::from("modified"), Change::Put(Bytes::from("v2"))),
+                (Bytes::from("zz_added"), Change::Put(Bytes::from("v1"))),
+            ]
+        );
+
+        // the reverse diff undoes the changes
+        let mut diff = db.diff(to, from.clone()).await.unwrap();
+        let mut changes = Vec::new();
+        while let Some(change) = diff.next().await.unwrap() {
+            changes.push(change);
+        }
+        assert_eq!(
+            changes,
+            vec![
+                (Bytes::from("added"), Change::Delete),
+                (Bytes::from("deleted"), Change::Put(Bytes::from("v1"))),
+                (Bytes::from("modified"), Change::Put(Bytes::from("v1"))),
+                (Bytes::from("zz_added"), Change::Delete),
+            ]
+        );
+
+        let mut diff = db.diff(from.clone(), from).await.unwrap();
+        assert_eq!(diff.next().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn should_reject_snapshot_of_another_db() {
+        let db = Db::open("db1", Arc::new(InMemory::new())).await.unwrap();
+        let other = Db::open("db2", Arc::new(InMemory::new())).await.unwrap();
+        let snapshot = db.snapshot().await.unwrap();
+        let foreign = other.snapshot().await.unwrap();
+
+        let Err(err) = db.diff(snapshot, foreign).await else {
+            panic!("expected diff of a foreign snapshot to fail");
+        };
+        assert_eq!(err.kind(), ErrorKind::Invalid);
+    }
+}
@@ -8,14 +8,22 @@ use crate::merge_iterator::MergeIterator;
 use crate::merge_operator::{
     MergeOperatorIterator, MergeOperatorRequiredIterator, MergeOperatorType,
 };
+use crate::projection::{ProjectingIterator, Projector};
+use crate::read_repair::{ReadRepairIterator, ReadRepairer};
+use crate::row_filter::{RowFilter, RowFilterIterator};
 use crate::segment_iterator::{build_l0_point_iters, build_sr_point_iters, SegmentScanContext};
 use crate::types::{KeyValue, RowEntry, ValueDeletable};
+use crate::virtual_source::{
+    KeyValueIterator, PrioritizedMergeIterator, SourcePriority, VirtualSourceIterator,
+};
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use parking_lot::Mutex;
+use slatedb_common::clock::SystemClock;
 use std::collections::VecDeque;
-use std::ops::RangeBounds;
+use std::ops::{Bound, RangeBounds};
 use std::sync::Arc;
 
 /// [`DbIteratorRangeTracker'] is used to track the range of keys accessed by a [`DbIterator`].  For
@@ -225,12 +233,102 @@ impl RowEntryIterator for ScanIterator {
     }
 }
 
+/// The number of entries a scan reads between checks of its deadline.
+const DEADLINE_CHECK_INTERVAL: u32 = 64;
+
+/// The deadline of a scan set by [`crate::config::ScanOptions::deadline`].
+struct ScanDeadline {
+    deadline: DateTime<Utc>,
+    clock: Arc<dyn SystemClock>,
+    /// The number of entries to read before the clock is checked again.
+    entries_until_check: u32,
+}
+
+impl ScanDeadline {
+    /// Called before each entry is read. Reads the clock only every
+    /// [`DEADLINE_CHECK_INTERVAL`] entries, starting with the first.
+    fn check(&mut self) -> Result<(), SlateDBError> {
+        if self.entries_until_check > 0 {
+            self.entries_until_check -= 1;
+            return Ok(());
+        }
+        self.entries_until_check = DEADLINE_CHECK_INTERVAL - 1;
+        if self.clock.now() >= self.deadline {
+            return Err(SlateDBError::ScanDeadlineExceeded);
+        }
+        Ok(())
+    }
+}
+
+/// The disjoint ranges that a scan of several ranges returns keys from. See
+/// [`crate::Db::scan_ranges`].
+struct SubRanges {
+    /// The ranges the iterator hasn't moved past yet, in iteration order.
+    ranges: VecDeque<BytesRange>,
+    order: IterationOrder,
+}
+
+/// Where a key read by the iterator falls relative to its [`SubRanges`].
+enum SubRangePosition {
+    /// The key is in the current range.
+    Within,
+    /// The key is in the gap before the current range.
+    Before,
+    /// The key is past the last range.
+    Done,
+}
+
+impl SubRanges {
+    /// Drops the ranges the iterator has moved past on reading `key`, and
+    /// returns where `key` falls relative to the ranges that are left.
+    fn position(&mut self, key: &Bytes) -> SubRangePosition {
+        while let Some(range) = self.ranges.front() {
+            if range.contains(key) {
+                return SubRangePosition::Within;
+            }
+            let passed = match self.order {
+                IterationOrder::Ascending => match range.end_bound() {
+                    Bound::Included(end) => key > end,
+                    Bound::Excluded(end) => key >= end,
+                    Bound::Unbounded => false,
+                },
+                IterationOrder::Descending => match range.start_bound() {
+                    Bound::Included(start) => key < start,
+                    Bound::Excluded(start) => key <= start,
+                    Bound::Unbounded => false,
+                },
+            };
+            if !passed {
+                return SubRangePosition::Before;
+            }
+            self.ranges.pop_front();
+        }
+        SubRangePosition::Done
+    }
+
+    /// The key to seek to from the gap before the current range, if the
+    /// iterator can seek over it. Only ascending iterators can.
+    fn seek_key(&self) -> Option<Bytes> {
+        if !matches!(self.order, IterationOrder::Ascending) {
+            return None;
+        }
+        match self.ranges.front()?.start_bound() {
+            Bound::Included(start) | Bound::Excluded(start) => Some(start.clone()),
+            Bound::Unbounded => None,
+        }
+    }
+}
+
 pub struct DbIterator {
     range: BytesRange,
     iter: Box<dyn RowEntryIterator + 'static>,
     invalidated_error: Option<SlateDBError>,
     last_key: Option<Bytes>,
     range_tracker: Option<Arc<DbIteratorRangeTracker>>,
+    deadline: Option<ScanDeadline>,
+    sub_ranges: Option<SubRanges>,
+    max_entries_before_yield: Option<usize>,
+    order: IterationOrder,
 }
 
 impl DbIterator {
@@ -303,9 +401,136 @@ impl DbIterator {
             invalidated_error: None,
             last_key: None,
             range_tracker,
+            deadline: None,
+            sub_ranges: None,
+            max_entries_before_yield: None,
+            order,
         })
     }
 
+    /// Makes the iterator fail with [`SlateDBError::ScanDeadlineExceeded`]
+    /// once `clock` reaches `deadline`.
+    pub(crate) fn with_deadline(
+        mut self,
+        deadline: DateTime<Utc>,
+        clock: Arc<dyn SystemClock>,
+    ) -> Self {
+        self.deadline = Some(ScanDeadline {
+            deadline,
+            clock,
+            entries_until_check: 0,
+        });
+        self
+    }
+
+    /// Makes each call to the iterator yield to the async runtime after
+    /// reading `max_entries` entries, counting the entries it skips. See
+    /// [`crate::config::ScanOptions::max_entries_before_yield`].
+    pub(crate) fn with_max_entries_before_yield(mut self, max_entries: usize) -> Self {
+        self.max_entries_before_yield = Some(max_entries.max(1));
+        self
+    }
+
+    /// Lets `repairer` re-insert the entries the iterator reads from SSTs into
+    /// the active memtable. See [`crate::config::ScanOptions::read_repair`].
+    ///
+    /// Must be called before [`Self::with_projector`], [`Self::with_row_filter`]
+    /// and [`Self::with_virtual_source`], so that `repairer` sees the entries
+    /// as they were read rather than as those adapters return them.
+    pub(crate) fn with_read_repair(mut self, repairer: ReadRepairer) -> Self {
+        let iter = std::mem::replace(&mut self.iter, Box::new(EmptyIterator::new()));
+        self.iter = Box::new(ReadRepairIterator::new(iter, repairer));
+        self
+    }
+
+    /// Limits the iterator to the keys in `ranges`, which must be sorted,
+    /// disjoint and within the range the iterator was created with. In
+    /// ascending order the iterator seeks over the gaps between the ranges,
+    /// and in descending order it reads through them.
+    pub(crate) fn with_sub_ranges(
+        mut self,
+        ranges: Vec<BytesRange>,
+        order: IterationOrder,
+    ) -> Self {
+        let ranges = match order {
+            IterationOrder::Ascending => ranges.into_iter().collect(),
+            IterationOrder::Descending => ranges.into_iter().rev().collect(),
+        };
+        self.sub_ranges = Some(SubRanges { ranges, order });
+        self
+    }
+
+    /// Applies `projector` to each value the iterator returns from now on,
+    /// e.g. to keep only the fields of structured values that the caller
+    /// reads. Keys, their order, and deleted keys are unaffected.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error, Projector};
+    /// use slatedb::bytes::Bytes;
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// struct FirstField;
+    ///
+    /// impl Projector for FirstField {
+    ///     fn project(&self, value: &[u8]) -> Bytes {
+    ///         let end = value.iter().position(|b| *b == b',').unwrap_or(value.len());
+    ///         Bytes::copy_from_slice(&value[..end])
+    ///     }
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"user/1", b"ada,1815,mathematician").await?;
+    ///     let mut iter = db.scan_prefix(b"user/").await?.with_projector(Arc::new(FirstField));
+    ///     assert_eq!(iter.next().await?.map(|kv| kv.value), Some("ada".into()));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_projector(mut self, projector: Arc<dyn Projector>) -> Self {
+        let iter = std::mem::replace(&mut self.iter, Box::new(EmptyIterator::new()));
+        self.iter = Box::new(ProjectingIterator::new(iter, projector));
+        self
+    }
+
+    /// Drops the rows that `filter` rejects from now on. Deleted keys are
+    /// unaffected.
+    pub(crate) fn with_row_filter(mut self, filter: Arc<dyn RowFilter>) -> Self {
+        let iter = std::mem::replace(&mut self.iter, Box::new(EmptyIterator::new()));
+        self.iter = Box::new(RowFilterIterator::new(iter, filter));
+        self
+    }
+
+    /// Merges the entries of `source` into the iterator, with `priority`
+    /// deciding which entry is returned for a key both hold.
+    pub(crate) fn with_virtual_source(
+        mut self,
+        source: Box<dyn KeyValueIterator>,
+        priority: SourcePriority,
+        order: IterationOrder,
+    ) -> Self {
+        let db_iter = std::mem::replace(&mut self.iter, Box::new(EmptyIterator::new()));
+        let source_iter = Box::new(VirtualSourceIterator::new(
+            source,
+            self.range.clone(),
+            order,
+            priority,
+        ));
+        self.iter = match priority {
+            SourcePriority::Highest => {
+                Box::new(PrioritizedMergeIterator::new(source_iter, db_iter, order))
+            }
+            SourcePriority::Lowest => {
+                Box::new(PrioritizedMergeIterator::new(db_iter, source_iter, order))
+            }
+        };
+        self
+    }
+
     /// Get the next key-value pair.
     ///
     /// This method filters out tombstones and returns the user-facing [`KeyValue`] struct,
@@ -330,13 +555,56 @@ impl DbIterator {
     }
 
     pub(crate) async fn next_entry(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        self.next_row(false).await
+    }
+
+    /// Like [`Self::next_entry`], but also returns the tombstones of keys that
+    /// are deleted. Tombstones don't count as returned keys for
+    /// [`Self::seek`] or the range tracker.
+    pub(crate) async fn next_entry_with_tombstones(
+        &mut self,
+    ) -> Result<Option<RowEntry>, SlateDBError> {
+        self.next_row(true).await
+    }
+
+    async fn next_row(&mut self, with_tombstones: bool) -> Result<Option<RowEntry>, SlateDBError> {
         if let Some(error) = self.invalidated_error.clone() {
             Err(error)
         } else {
+            let mut entries_read = 0;
             let result = loop {
-                match self.iter.next().await {
+                // checked per entry read rather than per entry returned, so
+                // that a run of deleted keys can't hold the scan up
+                if let Some(deadline) = &mut self.deadline {
+                    if let Err(e) = deadline.check() {
+                        break Err(e);
+                    }
+                }
+                if let Some(max_entries) = self.max_entries_before_yield {
+                    if entries_read == max_entries {
+                        entries_read = 0;
+                        tokio::task::yield_now().await;
+                    }
+                    entries_read += 1;
+                }
+                let next = self.iter.next().await;
+                if let (Ok(Some(entry)), Some(sub_ranges)) = (&next, &mut self.sub_ranges) {
+                    match sub_ranges.position(&entry.key) {
+                        SubRangePosition::Within => {}
+                        SubRangePosition::Before => {
+                            if let Some(start) = sub_ranges.seek_key() {
+                                if let Err(e) = self.iter.seek(&start).await {
+                                    break Err(e);
+                                }
+                            }
+                            continue;
+                        }
+                        SubRangePosition::Done => break Ok(None),
+                    }
+                }
+                match next {
                     Ok(Some(entry)) => match entry.value {
-                        ValueDeletable::Tombstone => continue,
+                        ValueDeletable::Tombstone if !with_tombstones => continue,
                         _ => break Ok(Some(entry)),
                     },
                     Ok(None) => break Ok(None),
@@ -345,10 +613,12 @@ impl DbIterator {
             };
             let result = self.maybe_invalidate(result);
             if let Ok(Some(ref entry)) = result {
-                self.last_key = Some(entry.key.clone());
-                // Track the key in range tracker if present
-                if let Some(tracker) = &self.range_tracker {
-                    tracker.track_key(&entry.key);
+                if !entry.value.is_tombstone() {
+                    self.last_key = Some(entry.key.clone());
+                    // Track the key in range tracker if present
+                    if let Some(tracker) = &self.range_tracker {
+                        tracker.track_key(&entry.key);
+                    }
                 }
             }
             result
@@ -365,19 +635,20 @@ impl DbIterator {
         result
     }
 
-    /// Seek ahead to the next key. The next key must be larger than the
-    /// last key returned by the iterator and less than the end bound specified
-    /// in the `scan` arguments.
+    /// Seek ahead to the next key. The next key must come after the last key
+    /// returned by the iterator in the scan's order, and be within the range
+    /// specified in the `scan` arguments.
     ///
-    /// After a successful seek, the iterator will return the next record
-    /// with a key greater than or equal to `next_key`.
+    /// After a successful seek, an ascending iterator will return the next
+    /// record with a key greater than or equal to `next_key`, and a descending
+    /// one the next record with a key less than or equal to `next_key`.
     ///
     /// # Errors
     ///
     /// Returns an invalid argument error in the following cases:
     ///
     /// - if `next_key` comes before the current iterator position
-    /// - if `next_key` is beyond the upper bound specified in the original
+    /// - if `next_key` is outside the range specified in the original
     ///   [`crate::db::Db::scan`] parameters
     ///
     /// Returns [`Error`] if the iterator has been invalidated in order to reclaim resources.
@@ -394,7 +665,10 @@ impl DbIterator {
         } else if self
             .last_key
             .clone()
-            .is_some_and(|last_key| next_key <= last_key)
+            .is_some_and(|last_key| match self.order {
+                IterationOrder::Ascending => next_key <= last_key,
+                IterationOrder::Descending => next_key >= last_key,
+            })
         {
             Err(SlateDBError::SeekKeyLessThanLastReturnedKey.into())
         } else {
@@ -404,6 +678,16 @@ impl DbIterator {
     }
 }
 
+/// Lets a scan be one side of a [`crate::MergeJoinIterator`] or the source of
+/// [`crate::Db::scan_with_source`].
+#[async_trait]
+impl KeyValueIterator for DbIterator {
+    async fn next(&mut self) -> Result<Option<(Bytes, Bytes)>, crate::Error> {
+        let next = DbIterator::next(self).await?;
+        Ok(next.map(|kv| (kv.key, kv.value)))
+    }
+}
+
 pub(crate) fn apply_filters<T>(
     iters: impl IntoIterator<Item = T>,
     max_seq: Option<u64>,
@@ -598,13 +882,13 @@ mod tests {
         let err = iter.seek(b"key1").await.unwrap_err();
         assert_eq!(
             err.to_string(),
-            "Invalid error: cannot seek to a key less than the last returned key"
+            "Invalid error: cannot seek to a key at or before the last returned key"
         );
 
         let err = iter.seek(b"key0").await.unwrap_err();
         assert_eq!(
             err.to_string(),
-            "Invalid error: cannot seek to a key less than the last returned key"
+            "Invalid error: cannot seek to a key at or before the last returned key"
         );
 
         // Seeking forward succeeds and allows reading the next key
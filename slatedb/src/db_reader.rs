@@ -27,6 +27,7 @@ use crate::{Checkpoint, DbIterator};
 use crate::{DbCacheManagerOps, DbMetadataOps, DbReadOps};
 use async_trait::async_trait;
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use futures::stream::BoxStream;
 use log::{info, warn};
 use object_store::path::Path;
@@ -39,10 +40,25 @@ use std::ops::{RangeBounds, Sub};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::runtime::Handle;
+use tokio::sync::watch;
 use uuid::Uuid;
 
 pub(crate) const DB_READER_TASK_NAME: &str = "manifest_poller";
 
+/// Whether a read from a [`DbReader`] reflects the database as of a requested
+/// time. See [`DbReader::get_with_freshness`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// The reader was current as of the requested time.
+    UpToDate,
+    /// The reader was behind the requested time, so writes made in the
+    /// meantime may be missing from the read.
+    Stale {
+        /// How far the reader's applied time was behind the requested time.
+        behind_by: Duration,
+    },
+}
+
 /// Read-only interface for accessing a database from either
 /// the latest persistent state or from an arbitrary checkpoint.
 pub struct DbReader {
@@ -55,6 +71,9 @@ struct DbReaderInner {
     table_store: Arc<TableStore>,
     options: DbReaderOptions,
     state: RwLock<Arc<CheckpointState>>,
+    /// The time as of which `state` reflects the database in object storage.
+    /// Advanced by each successful manifest poll.
+    applied_time: watch::Sender<DateTime<Utc>>,
     system_clock: Arc<dyn SystemClock>,
     user_checkpoint_id: Option<Uuid>,
     oracle: Arc<DbReaderOracle>,
@@ -116,10 +135,18 @@ impl DbReaderInner {
         rand: Arc<DbRand>,
         recorder: slatedb_common::metrics::MetricsRecorderHelper,
         mut manifest: StoredManifest,
+        loaded_at: DateTime<Utc>,
     ) -> Result<Self, SlateDBError> {
         let checkpoint =
             Self::get_or_create_checkpoint(&mut manifest, checkpoint_id, &options, rand.clone())
                 .await?;
+        // a user-provided checkpoint is never refreshed, so the reader's view is
+        // only as recent as the checkpoint itself
+        let applied_time = if checkpoint_id.is_some() {
+            checkpoint.create_time.min(loaded_at)
+        } else {
+            loaded_at
+        };
 
         let replay_new_wals = checkpoint_id.is_none() && !options.skip_wal_replay;
         let initial_state = Arc::new(
@@ -166,6 +193,7 @@ impl DbReaderInner {
             table_store,
             options,
             state,
+            applied_time: watch::Sender::new(applied_time),
             system_clock,
             user_checkpoint_id: checkpoint_id,
             oracle,
@@ -239,6 +267,8 @@ impl DbReaderInner {
                     max_seq: None,
                     range_tracker: None,
                     prefix,
+                    as_of_ts: None,
+                    include_expired: false,
                 },
             )
             .await
@@ -258,6 +288,18 @@ impl DbReaderInner {
             || latest.segments != current_state.segments
     }
 
+    /// Records that the reader's view reflects object storage as of `time`.
+    fn advance_applied_time(&self, time: DateTime<Utc>) {
+        self.applied_time.send_if_modified(|applied_time| {
+            if time > *applied_time {
+                *applied_time = time;
+                true
+            } else {
+                false
+            }
+        });
+    }
+
     async fn replace_checkpoint(
         &self,
         stored_manifest: &mut StoredManifest,
@@ -488,6 +530,7 @@ impl DbReaderInner {
             sst_iter_options,
             // Skip entries that we already have in `imm_memtable` (that might be above last_l0_seq).
             min_seq: Some(last_committed_seq),
+            ..WalReplayOptions::default()
         };
 
         let mut replay_iter = WalReplayIterator::range(
@@ -568,6 +611,8 @@ impl MessageHandler<DbReaderMessage> for ManifestPoller {
 
     async fn handle(&mut self, message: DbReaderMessage) -> Result<(), SlateDBError> {
         assert!(matches!(message, DbReaderMessage::PollManifest));
+        // anything made durable before the manifest is loaded is applied by this poll
+        let poll_time = self.inner.system_clock.now();
         let mut manifest = StoredManifest::load(
             Arc::clone(&self.inner.manifest_store),
             self.inner.system_clock.clone(),
@@ -584,6 +629,7 @@ impl MessageHandler<DbReaderMessage> for ManifestPoller {
         } else {
             self.inner.maybe_replay_new_wals().await?;
         }
+        self.inner.advance_applied_time(poll_time);
 
         self.inner.maybe_refresh_checkpoint(&mut manifest).await
     }
@@ -722,6 +768,7 @@ impl DbReader {
         let manifest_store = store_provider.manifest_store();
         let table_store = store_provider.table_store();
 
+        let loaded_at = system_clock.now();
         let manifest =
             StoredManifest::load(Arc::clone(&manifest_store), system_clock.clone()).await?;
         if !manifest.db_state().initialized {
@@ -746,6 +793,7 @@ impl DbReader {
                 rand,
                 recorder,
                 manifest,
+                loaded_at,
             )
             .await?,
         );
@@ -887,6 +935,75 @@ impl DbReader {
         Ok(kv)
     }
 
+    /// Get a value from the reader along with whether it may be stale.
+    ///
+    /// The value is read as with [`DbReader::get`], and is at least as fresh
+    /// as the reader's [applied time](DbReader::applied_time) when the read
+    /// started. The read is [`Freshness::UpToDate`] if that time is at or past
+    /// `fresh_as_of`, and otherwise [`Freshness::Stale`] by the difference, so
+    /// writes made to the database between the applied time and `fresh_as_of`
+    /// may be missing from the result.
+    ///
+    /// ## Arguments
+    /// - `key`: the key to get
+    /// - `fresh_as_of`: the time as of which the caller needs the reader to be
+    ///   current, e.g. the current time, or the current time minus a tolerated
+    ///   lag
+    ///
+    /// ## Returns
+    /// - `Result<(Option<Bytes>, Freshness), Error>`: the value, if it exists,
+    ///   and the freshness of the read
+    ///
+    /// ## Errors
+    /// - `Error`: if there was an error getting the value
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, DbReader, Freshness, config::DbReaderOptions, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", Arc::clone(&object_store)).await?;
+    ///     db.put(b"key", b"value").await?;
+    ///     db.flush().await?;
+    ///
+    ///     let reader = DbReader::open(
+    ///       "test_db",
+    ///       Arc::clone(&object_store),
+    ///       None,
+    ///       DbReaderOptions::default(),
+    ///     ).await?;
+    ///     let opened_at = reader.applied_time();
+    ///     let (value, freshness) = reader.get_with_freshness(b"key", opened_at).await?;
+    ///     assert_eq!(value, Some("value".into()));
+    ///     assert_eq!(freshness, Freshness::UpToDate);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_with_freshness<K: AsRef<[u8]> + Send>(
+        &self,
+        key: K,
+        fresh_as_of: DateTime<Utc>,
+    ) -> Result<(Option<Bytes>, Freshness), crate::Error> {
+        // sampled before the read, which sees at least everything applied by then
+        let applied_time = self.applied_time();
+        let value = self.get(key).await?;
+        let freshness = if applied_time >= fresh_as_of {
+            Freshness::UpToDate
+        } else {
+            Freshness::Stale {
+                behind_by: (fresh_as_of - applied_time)
+                    .to_std()
+                    .unwrap_or(Duration::ZERO),
+            }
+        };
+        Ok((value, freshness))
+    }
+
     /// Scan a range of keys using the default scan options.
     ///
     /// returns a `DbIterator`
@@ -992,13 +1109,7 @@ impl DbReader {
         K: AsRef<[u8]> + Send,
         T: RangeBounds<K> + Send,
     {
-        let start = range
-            .start_bound()
-            .map(|b| Bytes::copy_from_slice(b.as_ref()));
-        let end = range
-            .end_bound()
-            .map(|b| Bytes::copy_from_slice(b.as_ref()));
-        let range = BytesRange::from((start, end));
+        let range = BytesRange::try_from_scan_range(&range)?;
         self.inner
             .scan_with_options(range, options, None)
             .await
@@ -1153,6 +1264,24 @@ impl DbReader {
     pub fn status(&self) -> DbStatus {
         <Self as DbMetadataOps>::status(self)
     }
+
+    /// Returns the time as of which this reader's view reflects the database.
+    ///
+    /// Every write that was durable in object storage at the returned time is
+    /// visible to reads, so `now - applied_time()` bounds the reader's
+    /// staleness. The reader advances this time each time it polls the
+    /// manifest (see [`DbReaderOptions::manifest_poll_interval`]), whether or not
+    /// the poll found new data. With [`DbReaderOptions::skip_wal_replay`] set,
+    /// only writes that have been flushed to L0 count as applied.
+    ///
+    /// A reader opened from an explicit checkpoint never polls, so its applied
+    /// time stays at the checkpoint's creation time.
+    ///
+    /// ## Returns
+    /// - `DateTime<Utc>`: the time as of which the reader's view is current.
+    pub fn applied_time(&self) -> DateTime<Utc> {
+        *self.inner.applied_time.borrow()
+    }
 }
 
 #[async_trait]
@@ -1199,7 +1328,7 @@ mod tests {
         CheckpointOptions, CheckpointScope, FlushOptions, FlushType, MergeOptions, Settings,
         WriteOptions,
     };
-    use crate::db_reader::{DbReader, DbReaderInner, DbReaderOptions};
+    use crate::db_reader::{DbReader, DbReaderInner, DbReaderOptions, Freshness};
     use crate::db_state::SsTableId;
     use crate::db_stats::DbStats;
     use crate::db_status::DbStatusManager;
@@ -1463,6 +1592,93 @@ mod tests {
         );
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn should_report_stale_reads_while_held_behind() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = Path::from("/tmp/test_kv_store");
+        let test_provider = TestProvider::new(path.clone(), Arc::clone(&object_store));
+
+        let db = test_provider.new_db(Settings::default()).await.unwrap();
+        db.put(b"key", b"old").await.unwrap();
+        // a reader opened from a checkpoint never advances past it
+        let checkpoint = db
+            .create_checkpoint(CheckpointScope::All, &CheckpointOptions::default())
+            .await
+            .unwrap();
+        let reader = test_provider
+            .new_db_reader(DbReaderOptions::default(), Some(checkpoint.id), None)
+            .await
+            .unwrap();
+        let applied_time = reader.applied_time();
+
+        tokio::time::advance(Duration::from_secs(5)).await;
+        db.put(b"key", b"new").await.unwrap();
+        db.flush().await.unwrap();
+
+        let now = test_provider.system_clock.now();
+        let (value, freshness) = reader.get_with_freshness(b"key", now).await.unwrap();
+        assert_eq!(value, Some(Bytes::from_static(b"old")));
+        let Freshness::Stale { behind_by } = freshness else {
+            panic!("expected a stale read, got {:?}", freshness);
+        };
+        assert!(behind_by >= Duration::from_secs(5));
+        assert!(behind_by < Duration::from_secs(6));
+
+        // a tolerated lag that covers the applied time is up to date
+        let (_, freshness) = reader
+            .get_with_freshness(b"key", applied_time)
+            .await
+            .unwrap();
+        assert_eq!(freshness, Freshness::UpToDate);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_apply_new_l0_sst_within_poll_interval() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = Path::from("/tmp/test_kv_store");
+        let test_provider = TestProvider::new(path.clone(), Arc::clone(&object_store));
+
+        let db = test_provider.new_db(Settings::default()).await.unwrap();
+        let poll_interval = Duration::from_millis(100);
+        let reader_options = DbReaderOptions {
+            manifest_poll_interval: poll_interval,
+            skip_wal_replay: true,
+            ..DbReaderOptions::default()
+        };
+        let reader = test_provider
+            .new_db_reader(reader_options, None, None)
+            .await
+            .unwrap();
+        let opened_applied_time = reader.applied_time();
+
+        db.put(b"key", b"value").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        let flushed_at = test_provider.system_clock.now();
+        assert_eq!(reader.get(b"key").await.unwrap(), None);
+
+        let mut waited = Duration::ZERO;
+        while reader.get(b"key").await.unwrap().is_none() {
+            assert!(
+                waited <= poll_interval * 2,
+                "new L0 SST not visible after {:?}",
+                waited
+            );
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            waited += Duration::from_millis(10);
+        }
+
+        assert_eq!(
+            reader.get(b"key").await.unwrap(),
+            Some(Bytes::from_static(b"value"))
+        );
+        assert!(reader.applied_time() > opened_applied_time);
+        assert!(reader.applied_time() >= flushed_at);
+    }
+
     #[tokio::test(start_paused = true)]
     async fn should_refresh_reader_checkpoint() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
@@ -2266,6 +2482,7 @@ mod tests {
                 ..DbReaderOptions::default()
             },
             state: parking_lot::RwLock::new(Arc::new(prior_state)),
+            applied_time: tokio::sync::watch::Sender::new(test_provider.system_clock.now()),
             system_clock: test_provider.system_clock.clone(),
             user_checkpoint_id: None,
             oracle,
@@ -2347,6 +2564,7 @@ mod tests {
             table_store,
             options: DbReaderOptions::default(),
             state: parking_lot::RwLock::new(Arc::new(prior_state)),
+            applied_time: tokio::sync::watch::Sender::new(test_provider.system_clock.now()),
             system_clock: test_provider.system_clock.clone(),
             user_checkpoint_id: None,
             oracle,
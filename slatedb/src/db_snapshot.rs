@@ -35,6 +35,11 @@ impl DbSnapshot {
         self.started_seq
     }
 
+    /// Returns true if this snapshot was taken of the database `db_inner`.
+    pub(crate) fn is_snapshot_of(&self, db_inner: &Arc<DbInner>) -> bool {
+        Arc::ptr_eq(&self.db_inner, db_inner)
+    }
+
     /// Get a value from the snapshot with default read options.
     ///
     /// ## Arguments
@@ -122,16 +127,8 @@ impl DbSnapshot {
         K: AsRef<[u8]> + Send,
         T: RangeBounds<K> + Send,
     {
-        // TODO: this range conversion logic can be extract to an util
-        let start = range
-            .start_bound()
-            .map(|b| Bytes::copy_from_slice(b.as_ref()));
-        let end = range
-            .end_bound()
-            .map(|b| Bytes::copy_from_slice(b.as_ref()));
-        let range = (start, end);
-        self.scan_inner(BytesRange::from(range), options, None)
-            .await
+        let range = BytesRange::try_from_scan_range(&range)?;
+        self.scan_inner(range, options, None).await
     }
 
     /// Scan all keys that share the provided prefix using the default scan options.
@@ -189,6 +186,8 @@ impl DbSnapshot {
                     max_seq: Some(self.started_seq),
                     range_tracker: None,
                     prefix,
+                    as_of_ts: None,
+                    include_expired: false,
                 },
             )
             .await
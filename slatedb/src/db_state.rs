@@ -1,9 +1,10 @@
 use crate::bytes_range::BytesRange;
-use crate::config::CompressionCodec;
+use crate::config::{CompressionCodec, MemtableType, DEFAULT_MEMTABLE_FILTER_BITS};
 use crate::error::SlateDBError;
 use crate::manifest::{Manifest, ManifestCore};
 use crate::mem_table::{ImmutableMemtable, KVTable, WritableKVTable};
 use crate::reader::DbStateReader;
+use crate::types::RowEntry;
 use crate::wal_id::WalIdStore;
 use bytes::Bytes;
 use serde::Serialize;
@@ -430,6 +431,12 @@ pub struct SsTableInfo {
     pub stats_len: u64,
     /// Filter block format.
     pub filter_format: FilterFormat,
+    /// The number of entries in the SSTable. Zero for SSTables written
+    /// before it was tracked.
+    pub num_entries: u64,
+    /// The number of delete entries (tombstones) in the SSTable. Zero for
+    /// SSTables written before it was tracked.
+    pub num_deletes: u64,
 }
 
 pub(crate) trait SsTableInfoCodec: Send + Sync {
@@ -621,6 +628,8 @@ impl SortedRun {
 
 pub(crate) struct DbState {
     memtable: WritableKVTable,
+    memtable_type: MemtableType,
+    memtable_filter_bits: usize,
     state: Arc<COWDbState>,
 }
 
@@ -662,6 +671,8 @@ impl DbState {
     pub(crate) fn new(manifest: DirtyObject<Manifest>) -> Self {
         Self {
             memtable: WritableKVTable::new(),
+            memtable_type: MemtableType::default(),
+            memtable_filter_bits: DEFAULT_MEMTABLE_FILTER_BITS,
             state: Arc::new(COWDbState {
                 imm_memtable: VecDeque::new(),
                 manifest,
@@ -669,6 +680,21 @@ impl DbState {
         }
     }
 
+    /// Sets the type and key filter size of memtables created for new writes.
+    /// The current memtable, which must be empty, is replaced with one of that
+    /// type.
+    pub(crate) fn with_memtable_type(
+        mut self,
+        memtable_type: MemtableType,
+        memtable_filter_bits: usize,
+    ) -> Self {
+        assert!(self.memtable.is_empty());
+        self.memtable_type = memtable_type;
+        self.memtable_filter_bits = memtable_filter_bits;
+        self.memtable = WritableKVTable::new_with_type(memtable_type, memtable_filter_bits);
+        self
+    }
+
     pub(crate) fn state(&self) -> Arc<COWDbState> {
         self.state.clone()
     }
@@ -685,7 +711,10 @@ impl DbState {
     }
 
     pub(crate) fn freeze_memtable(&mut self, recent_flushed_wal_id: u64) {
-        let old_memtable = std::mem::replace(&mut self.memtable, WritableKVTable::new());
+        let old_memtable = std::mem::replace(
+            &mut self.memtable,
+            WritableKVTable::new_with_type(self.memtable_type, self.memtable_filter_bits),
+        );
         self.modify(|modifier| {
             modifier
                 .state
@@ -697,6 +726,34 @@ impl DbState {
         });
     }
 
+    /// Makes sure the current memtable can take `entries`. See
+    /// [`WritableKVTable::prepare_for`].
+    pub(crate) fn prepare_memtable_for(&mut self, entries: &[RowEntry]) {
+        self.memtable.prepare_for(entries);
+    }
+
+    /// Replaces the immutable memtable `imm_memtable` with `replacement`, which
+    /// must hold the same entries. Does nothing if `imm_memtable` has been
+    /// flushed already. Returns whether it was replaced.
+    pub(crate) fn replace_imm_memtable(
+        &mut self,
+        imm_memtable: &Arc<ImmutableMemtable>,
+        replacement: Arc<ImmutableMemtable>,
+    ) -> bool {
+        self.modify(|modifier| {
+            let Some(slot) = modifier
+                .state
+                .imm_memtable
+                .iter_mut()
+                .find(|imm| Arc::ptr_eq(imm, imm_memtable))
+            else {
+                return false;
+            };
+            *slot = replacement;
+            true
+        })
+    }
+
     pub(crate) fn replace_memtable(&mut self, memtable: WritableKVTable) {
         assert!(self.memtable.is_empty());
         let _ = std::mem::replace(&mut self.memtable, memtable);
@@ -216,16 +216,8 @@ impl DbTransaction {
         K: AsRef<[u8]> + Send,
         T: RangeBounds<K> + Send,
     {
-        // TODO: this range conversion logic can be extract to an util
-        let start = range
-            .start_bound()
-            .map(|b| Bytes::copy_from_slice(b.as_ref()));
-        let end = range
-            .end_bound()
-            .map(|b| Bytes::copy_from_slice(b.as_ref()));
-        let range = (start, end);
-        self.scan_inner(BytesRange::from(range), options, None)
-            .await
+        let range = BytesRange::try_from_scan_range(&range)?;
+        self.scan_inner(range, options, None).await
     }
 
     /// Scan all keys that share the provided prefix using the default scan options.
@@ -307,6 +299,8 @@ impl DbTransaction {
                     max_seq: Some(self.started_seq),
                     range_tracker,
                     prefix,
+                    as_of_ts: None,
+                    include_expired: false,
                 },
             )
             .await
@@ -508,6 +502,11 @@ impl DbTransaction {
     /// immediately without any database interaction. Since it's impossible to have read-write
     /// conflict, neither write-write conflict for an empty write batch.
     ///
+    /// Buffered operations are never written to the WAL before commit, and the commit writes
+    /// them as a single batch, which always lands in one WAL SST. A crash therefore either
+    /// loses the whole transaction or recovers all of it; recovery never observes a partially
+    /// applied transaction.
+    ///
     /// ## Returns
     /// - `Ok(Some(WriteHandle))` if the commit is successful and there are writes in the batch.
     /// - `Ok(None)` if the commit is successful but the write batch is empty (no-op).
@@ -994,6 +993,61 @@ mod tests {
         db.close().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_txn_recovery_is_all_or_nothing() {
+        use crate::config::WriteOptions;
+        use fail_parallel::FailPointRegistry;
+
+        let fp_registry = Arc::new(FailPointRegistry::new());
+        let object_store: Arc<dyn object_store::ObjectStore> = Arc::new(InMemory::new());
+        let path = "/tmp/test_txn_recovery_is_all_or_nothing";
+        let db = crate::Db::builder(path, object_store.clone())
+            .with_fp_registry(fp_registry.clone())
+            .build()
+            .await
+            .unwrap();
+
+        // a durably committed transaction is recovered in full
+        let committed = db.begin(IsolationLevel::Snapshot).await.unwrap();
+        committed.put(b"a1", b"v1").unwrap();
+        committed.put(b"a2", b"v2").unwrap();
+        committed.commit().await.unwrap();
+
+        // a transaction that is still staging its writes when the db goes down
+        let staged = db.begin(IsolationLevel::Snapshot).await.unwrap();
+        staged.put(b"b1", b"v1").unwrap();
+        staged.delete(b"a1").unwrap();
+
+        // a transaction whose commit never reaches the WAL
+        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "return").unwrap();
+        let unflushed = db.begin(IsolationLevel::Snapshot).await.unwrap();
+        unflushed.put(b"c1", b"v1").unwrap();
+        unflushed.delete(b"a2").unwrap();
+        unflushed
+            .commit_with_options(&WriteOptions {
+                await_durable: false,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        drop(staged);
+        db.close()
+            .await
+            .expect_err("close should fail to flush the WAL");
+        fail_parallel::cfg(fp_registry.clone(), "write-wal-sst-io-error", "off").unwrap();
+
+        let db = crate::Db::open(path, object_store).await.unwrap();
+        for (key, expected) in [
+            (b"a1", Some(Bytes::from_static(b"v1"))),
+            (b"a2", Some(Bytes::from_static(b"v2"))),
+            (b"b1", None),
+            (b"c1", None),
+        ] {
+            assert_eq!(db.get(key).await.unwrap(), expected);
+        }
+        db.close().await.unwrap();
+    }
+
     // Transaction test structures for table-driven tests
     #[derive(Debug, Clone)]
     struct TransactionTestCase {
@@ -1788,6 +1842,39 @@ mod tests {
         assert_eq!(total, EXPECTED);
     }
 
+    #[rstest]
+    #[case(IterationOrder::Ascending)]
+    #[case(IterationOrder::Descending)]
+    #[tokio::test]
+    async fn test_txn_scan_merges_uncommitted_writes_in_either_order(
+        #[case] order: IterationOrder,
+    ) {
+        let object_store: Arc<dyn object_store::ObjectStore> = Arc::new(InMemory::new());
+        let db = crate::Db::builder("test_txn_scan_merges_uncommitted_writes", object_store)
+            .with_merge_operator(Arc::new(CounterMergeOperator))
+            .build()
+            .await
+            .unwrap();
+
+        let txn = db.begin(IsolationLevel::Snapshot).await.unwrap();
+        txn.put(b"counter", 1u64.to_le_bytes()).unwrap();
+        txn.merge(b"counter", 2u64.to_le_bytes()).unwrap();
+        txn.merge(b"counter", 3u64.to_le_bytes()).unwrap();
+
+        let options = ScanOptions {
+            order,
+            ..ScanOptions::default()
+        };
+        let mut iter = txn
+            .scan_with_options::<&[u8], _>(.., &options)
+            .await
+            .unwrap();
+        let kv = iter.next().await.unwrap().unwrap();
+        assert_eq!(kv.key, Bytes::from_static(b"counter"));
+        assert_eq!(kv.value, Bytes::copy_from_slice(&6u64.to_le_bytes()));
+        assert_eq!(iter.next().await.unwrap(), None);
+    }
+
     #[tokio::test]
     async fn test_txn_merge_requires_merge_operator() {
         let object_store: Arc<dyn object_store::ObjectStore> = Arc::new(InMemory::new());
@@ -1817,8 +1904,11 @@ mod tests {
             manifest_poll_interval: std::time::Duration::from_secs(3600),
             manifest_update_timeout: std::time::Duration::from_secs(300),
             max_unflushed_bytes: 134_217_728,
+            max_immutable_memtables: None,
+            max_memtable_bytes: None,
             l0_max_ssts: 8,
             l0_max_ssts_per_key: 8,
+            flush_order: Default::default(),
             l0_flush_parallelism: 1,
             min_filter_keys,
             l0_sst_size_bytes,
@@ -1828,6 +1918,13 @@ mod tests {
             object_store_cache_options: crate::config::ObjectStoreCacheOptions::default(),
             garbage_collector_options: None,
             default_ttl: None,
+            memtable_type: Default::default(),
+            memtable_filter_bits: crate::config::DEFAULT_MEMTABLE_FILTER_BITS,
+            front_code_immutable_memtables: false,
+            read_repair_sample_rate: crate::config::DEFAULT_READ_REPAIR_SAMPLE_RATE,
+            ttl_jitter: 0.0,
+            wal_replay_policy: Default::default(),
+            block_cache_warmup: None,
             block_format: None,
         }
     }
@@ -0,0 +1,474 @@
+//! Diagnostic reads of a db's internal state. See
+//! [`Db::raw_memtable_entries`], [`Db::memtable_range_bytes`] and
+//! [`Db::scan_with_provenance`].
+
+use std::ops::RangeBounds;
+
+use crate::bytes_range::BytesRange;
+use crate::db::Db;
+use crate::db_state::SsTableId;
+use crate::filter_iterator::FilterIterator;
+use crate::iter::{IterationOrder, RowEntryIterator};
+use crate::merge_iterator::MergeIterator;
+use crate::oracle::Oracle;
+use crate::sst_iter::{SstIterator, SstIteratorOptions};
+use crate::types::RowEntry;
+
+/// The memtable that holds a [`RawMemtableEntries`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemtableSource {
+    /// The active memtable, which receives new writes.
+    Active,
+    /// An immutable memtable waiting to be flushed to L0.
+    Immutable {
+        /// The position of the memtable among the immutable memtables, from 0
+        /// for the most recently frozen one.
+        index: usize,
+    },
+}
+
+/// The raw versions of a key held by one memtable. See
+/// [`Db::raw_memtable_entries`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawMemtableEntries {
+    /// The memtable holding the entries.
+    pub source: MemtableSource,
+    /// The versions of the key, from newest to oldest.
+    pub entries: Vec<RowEntry>,
+}
+
+/// Where the entry returned by [`Db::scan_with_provenance`] is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provenance {
+    /// The active memtable, which receives new writes.
+    ActiveMemtable,
+    /// An immutable memtable waiting to be flushed to L0.
+    ImmutableMemtable {
+        /// The id of the last WAL SST flushed when the memtable was frozen.
+        last_wal_id: u64,
+    },
+    /// An L0 SST.
+    L0 {
+        /// The id of the SST.
+        sst_id: SsTableId,
+    },
+    /// An SST of a compacted sorted run.
+    SortedRun {
+        /// The id of the sorted run.
+        id: u32,
+        /// The id of the SST within the sorted run.
+        sst_id: SsTableId,
+    },
+}
+
+impl Db {
+    /// Returns the versions of `key` held by each memtable, as they were
+    /// written: values, tombstones and merge operands, before they are
+    /// resolved into the value a read returns.
+    ///
+    /// This is a diagnostic API for finding out why a read returns an
+    /// unexpected result. Only memtables are inspected, so versions that have
+    /// been flushed to L0 or compacted aren't returned.
+    ///
+    /// ## Arguments
+    /// - `key`: the key to look up
+    ///
+    /// ## Returns
+    /// - `Ok(Vec<RawMemtableEntries>)`: the versions of the key in each
+    ///   memtable that holds it, from the active memtable to the oldest
+    ///   immutable one
+    ///
+    /// ## Errors
+    /// - `Error`: if the db is closed
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error, MemtableSource, ValueDeletable};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key", b"value").await?;
+    ///     db.delete(b"key").await?;
+    ///
+    ///     let tables = db.raw_memtable_entries(b"key")?;
+    ///     assert_eq!(tables[0].source, MemtableSource::Active);
+    ///     assert_eq!(tables[0].entries[0].value, ValueDeletable::Tombstone);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn raw_memtable_entries<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+    ) -> Result<Vec<RawMemtableEntries>, crate::Error> {
+        self.inner.check_closed()?;
+        let key = key.as_ref();
+        let guard = self.inner.state.read();
+        let state = guard.state();
+        let active = (MemtableSource::Active, guard.memtable().table().clone());
+        let immutables = state
+            .imm_memtable
+            .iter()
+            .enumerate()
+            .map(|(index, imm)| (MemtableSource::Immutable { index }, imm.table()));
+        Ok(std::iter::once(active)
+            .chain(immutables)
+            .map(|(source, table)| RawMemtableEntries {
+                source,
+                entries: table.get_raw(key),
+            })
+            .filter(|table| !table.entries.is_empty())
+            .collect())
+    }
+
+    /// Returns the estimated number of bytes the memtables hold for the keys
+    /// in `range`, counting every version of a key.
+    ///
+    /// This is a diagnostic API for finding hot key ranges. Only memtables are
+    /// inspected, so the data flushed to L0 or compacted isn't counted. The
+    /// memtables' entries in the range are walked to sum their sizes.
+    ///
+    /// ## Arguments
+    /// - `range`: the range of keys to measure
+    ///
+    /// ## Returns
+    /// - `Ok(usize)`: the estimated size of the entries in the range, summed
+    ///   over the active and immutable memtables
+    ///
+    /// ## Errors
+    /// - `Error`: if the db is closed, or the range's start is after its end
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"hot/1", b"value").await?;
+    ///     db.put(b"cold/1", b"value").await?;
+    ///
+    ///     assert!(db.memtable_range_bytes("hot/".."hot0")? > 0);
+    ///     assert_eq!(db.memtable_range_bytes("warm/".."warm0")?, 0);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn memtable_range_bytes<K, T>(&self, range: T) -> Result<usize, crate::Error>
+    where
+        K: AsRef<[u8]>,
+        T: RangeBounds<K>,
+    {
+        self.inner.check_closed()?;
+        let range = BytesRange::try_from_scan_range(&range)?;
+        let tables: Vec<_> = {
+            let guard = self.inner.state.read();
+            std::iter::once(guard.memtable().table().clone())
+                .chain(guard.state().imm_memtable.iter().map(|imm| imm.table()))
+                .collect()
+        };
+        Ok(tables
+            .iter()
+            .map(|table| table.approx_range_bytes(range.clone()))
+            .sum())
+    }
+
+    /// Returns the newest version of each key in `range`, together with
+    /// where it is stored.
+    ///
+    /// This is a diagnostic API for finding out why reads of some keys are
+    /// slow: a key whose newest version is deep in the tree takes more reads
+    /// to find. Versions are returned as they were written, so tombstones and
+    /// expired values are included, and a merge operand is returned without
+    /// the older versions it applies to. Writes that aren't committed yet
+    /// aren't returned.
+    ///
+    /// ## Arguments
+    /// - `range`: the range of keys to scan
+    ///
+    /// ## Returns
+    /// - `Ok(Vec<(RowEntry, Provenance)>)`: the newest version of each key,
+    ///   in ascending key order
+    ///
+    /// ## Errors
+    /// - `Error`: if the db is closed, the range's start is after its end, or
+    ///   an SST can't be read
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error, Provenance};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key", b"value").await?;
+    ///
+    ///     let entries = db.scan_with_provenance::<&[u8], _>(..).await?;
+    ///     assert_eq!(entries[0].1, Provenance::ActiveMemtable);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn scan_with_provenance<K, T>(
+        &self,
+        range: T,
+    ) -> Result<Vec<(RowEntry, Provenance)>, crate::Error>
+    where
+        K: AsRef<[u8]>,
+        T: RangeBounds<K>,
+    {
+        self.inner.check_closed()?;
+        let range = BytesRange::try_from_scan_range(&range)?;
+        let (memtables, segments) = {
+            let guard = self.inner.state.read();
+            let state = guard.state();
+            let active = (Provenance::ActiveMemtable, guard.memtable().table().clone());
+            let immutables = state.imm_memtable.iter().map(|imm| {
+                let last_wal_id = imm.recent_flushed_wal_id();
+                (Provenance::ImmutableMemtable { last_wal_id }, imm.table())
+            });
+            let memtables: Vec<_> = std::iter::once(active).chain(immutables).collect();
+            (memtables, state.core().select_segments(&range))
+        };
+
+        // the sources are listed newest first, so that the merge returns the
+        // newest version of a key
+        let max_seq = Some(self.inner.oracle.last_committed_seq());
+        let mut provenances = Vec::new();
+        let mut iters: Vec<Box<dyn RowEntryIterator>> = Vec::new();
+        for (provenance, table) in memtables {
+            let iter = table.range(range.clone(), IterationOrder::Ascending);
+            provenances.push(provenance);
+            iters.push(Box::new(FilterIterator::new_with_max_seq(iter, max_seq)));
+        }
+        let table_store = &self.inner.table_store;
+        let mut push_sst = |provenance, view| -> Result<(), crate::Error> {
+            let iter = SstIterator::new_owned(
+                range.clone(),
+                view,
+                table_store.clone(),
+                SstIteratorOptions::default(),
+            )?;
+            if let Some(iter) = iter {
+                provenances.push(provenance);
+                iters.push(Box::new(iter));
+            }
+            Ok(())
+        };
+        for segment in segments {
+            for view in segment.tree.l0 {
+                push_sst(
+                    Provenance::L0 {
+                        sst_id: view.sst.id,
+                    },
+                    view,
+                )?;
+            }
+            for sorted_run in segment.tree.compacted {
+                for view in sorted_run.sst_views {
+                    let provenance = Provenance::SortedRun {
+                        id: sorted_run.id,
+                        sst_id: view.sst.id,
+                    };
+                    push_sst(provenance, view)?;
+                }
+            }
+        }
+
+        let mut iter = MergeIterator::new(iters)?;
+        iter.init().await?;
+        let mut entries: Vec<(RowEntry, Provenance)> = Vec::new();
+        while let Some(source) = iter.current_source() {
+            let Some(entry) = iter.next().await? else {
+                break;
+            };
+            // the merge keeps the older merge operands of a key
+            if entries
+                .last()
+                .is_some_and(|(last, _)| last.key == entry.key)
+            {
+                continue;
+            }
+            entries.push((entry, provenances[source]));
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{FlushOptions, FlushType, PutOptions, WriteOptions};
+    use crate::test_utils::StringConcatMergeOperator;
+    use crate::types::ValueDeletable;
+    use bytes::Bytes;
+    use fail_parallel::FailPointRegistry;
+    use object_store::memory::InMemory;
+    use std::sync::Arc;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn should_return_unresolved_versions_per_memtable() {
+        let fp_registry = Arc::new(FailPointRegistry::new());
+        // block L0 uploads so that the frozen memtable stays in memory
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "pause").unwrap();
+        let db = Db::builder("test_db", Arc::new(InMemory::new()))
+            .with_merge_operator(Arc::new(StringConcatMergeOperator))
+            .with_fp_registry(fp_registry.clone())
+            .build()
+            .await
+            .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        db.put_with_options(b"key", b"old", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        db.inner.freeze_current_memtable().unwrap();
+        db.merge_with_options(b"key", b"operand", &Default::default(), &write_options)
+            .await
+            .unwrap();
+        db.delete_with_options(b"key", &write_options)
+            .await
+            .unwrap();
+        db.put_with_options(b"other", b"value", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+
+        let tables = db.raw_memtable_entries(b"key").unwrap();
+        let missing = db.raw_memtable_entries(b"missing").unwrap();
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "off").unwrap();
+
+        let tables: Vec<_> = tables
+            .iter()
+            .map(|table| {
+                let values: Vec<_> = table.entries.iter().map(|e| e.value.clone()).collect();
+                (table.source, values)
+            })
+            .collect();
+        assert_eq!(
+            tables,
+            vec![
+                (
+                    MemtableSource::Active,
+                    vec![
+                        ValueDeletable::Tombstone,
+                        ValueDeletable::Merge(Bytes::from_static(b"operand")),
+                    ]
+                ),
+                (
+                    MemtableSource::Immutable { index: 0 },
+                    vec![ValueDeletable::Value(Bytes::from_static(b"old"))]
+                ),
+            ]
+        );
+        assert!(missing.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn should_sum_range_bytes_over_all_memtables() {
+        let fp_registry = Arc::new(FailPointRegistry::new());
+        // block L0 uploads so that the frozen memtable stays in memory
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "pause").unwrap();
+        let db = Db::builder("test_db", Arc::new(InMemory::new()))
+            .with_fp_registry(fp_registry.clone())
+            .build()
+            .await
+            .unwrap();
+        let write_options = WriteOptions {
+            await_durable: false,
+            ..Default::default()
+        };
+        db.put_with_options(b"a1", b"old", &PutOptions::default(), &write_options)
+            .await
+            .unwrap();
+        db.inner.freeze_current_memtable().unwrap();
+        for key in [b"a1", b"a2", b"b1"] {
+            db.put_with_options(key, b"new", &PutOptions::default(), &write_options)
+                .await
+                .unwrap();
+        }
+
+        let in_a = db.memtable_range_bytes("a".."b").unwrap();
+        let all = db.memtable_range_bytes::<&[u8], _>(..).unwrap();
+        let empty = db.memtable_range_bytes("b2".."b2").unwrap();
+        let (active, immutable) = {
+            let guard = db.inner.state.read();
+            (
+                guard.memtable().table().clone(),
+                guard.state().imm_memtable[0].table(),
+            )
+        };
+        fail_parallel::cfg(fp_registry.clone(), "write-compacted-sst-io-error", "off").unwrap();
+
+        let a_entries: usize = active
+            .get_raw(b"a1")
+            .iter()
+            .chain(&active.get_raw(b"a2"))
+            .chain(&immutable.get_raw(b"a1"))
+            .map(RowEntry::estimated_size)
+            .sum();
+        assert_eq!(in_a, a_entries);
+        assert_eq!(
+            all,
+            active.metadata().entries_size_in_bytes + immutable.metadata().entries_size_in_bytes
+        );
+        assert_eq!(empty, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn should_tag_newest_versions_with_their_source() {
+        let db = Db::builder("test_db", Arc::new(InMemory::new()))
+            .build()
+            .await
+            .unwrap();
+        db.put(b"k1", b"old").await.unwrap();
+        db.put(b"k3", b"flushed").await.unwrap();
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+        db.put(b"k1", b"new").await.unwrap();
+        db.put(b"k2", b"active").await.unwrap();
+
+        let entries = db.scan_with_provenance::<&[u8], _>(..).await.unwrap();
+
+        let l0_sst_id = db.inner.state.read().state().core().tree.l0[0].sst.id;
+        let entries: Vec<_> = entries
+            .into_iter()
+            .map(|(entry, provenance)| (entry.key, entry.value, provenance))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    Bytes::from_static(b"k1"),
+                    ValueDeletable::Value(Bytes::from_static(b"new")),
+                    Provenance::ActiveMemtable
+                ),
+                (
+                    Bytes::from_static(b"k2"),
+                    ValueDeletable::Value(Bytes::from_static(b"active")),
+                    Provenance::ActiveMemtable
+                ),
+                (
+                    Bytes::from_static(b"k3"),
+                    ValueDeletable::Value(Bytes::from_static(b"flushed")),
+                    Provenance::L0 { sst_id: l0_sst_id }
+                ),
+            ]
+        );
+    }
+}
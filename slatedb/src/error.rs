@@ -61,6 +61,12 @@ pub(crate) enum SlateDBError {
     #[error("transactional object (e.g. manifest) op timeout after {timeout:?}")]
     TransactionalObjectTimeout { timeout: Duration },
 
+    #[error("too many immutable memtables waiting to be flushed (count: {count}, max: {max})")]
+    TooManyImmutableMemtables { count: usize, max: usize },
+
+    #[error("write rate limit exceeded for tenant {tenant:?}")]
+    RateLimited { tenant: String },
+
     #[error("transactional object (e.g. manifest) is in an invalid state")]
     InvalidTransactionalObjectState,
 
@@ -82,6 +88,11 @@ pub(crate) enum SlateDBError {
     #[error("segment prefix {prefix:?} would nest with existing segment {conflict:?}")]
     InvalidSegmentPrefix { prefix: Bytes, conflict: Bytes },
 
+    #[error(
+        "key {key:?} is not greater than the last key {last_key:?} in the append-only memtable"
+    )]
+    OutOfOrderAppend { key: Bytes, last_key: Bytes },
+
     #[error("recency scan prefix spans multiple segments, which is unsupported")]
     RecencyScanPrefixSpansMultipleSegments,
 
@@ -102,9 +113,24 @@ pub(crate) enum SlateDBError {
     #[error("segment extractor produced an empty prefix for key {key:?}")]
     EmptySegmentPrefix { key: Bytes },
 
+    #[error("invalid column family name {name:?}, must be between 1 and 255 bytes long")]
+    InvalidColumnFamilyName { name: String },
+
+    #[error("snapshot belongs to a different database")]
+    ForeignSnapshot,
+
+    #[error("changes since sequence number {since} are no longer retained, the oldest retained change is after {oldest_retained}, rebuild from a full scan")]
+    ChangesNotRetained { since: u64, oldest_retained: u64 },
+
+    #[error("scan deadline exceeded")]
+    ScanDeadlineExceeded,
+
     #[error("compaction executor failed")]
     CompactionExecutorFailed,
 
+    #[error("no compactor is running in this db")]
+    CompactorNotRunning,
+
     #[error(
         "invalid clock tick, must be monotonic. last_tick=`{last_tick}`, next_tick=`{next_tick}`"
     )]
@@ -160,6 +186,9 @@ pub(crate) enum SlateDBError {
     #[error("db is closed")]
     Closed,
 
+    #[error("db is closing")]
+    Closing,
+
     #[error("merge operator error")]
     MergeOperatorError(#[from] MergeOperatorError),
 
@@ -169,6 +198,9 @@ pub(crate) enum SlateDBError {
     #[error("checkpoint missing. checkpoint_id=`{0}`")]
     CheckpointMissing(Uuid),
 
+    #[error("checkpoint name already exists. name=`{0}`")]
+    CheckpointNameExists(String),
+
     #[error(
         "unsupported {format_name} format version. supported_versions=`{supported_versions:?}`, actual_version=`{actual_version}`"
     )]
@@ -185,9 +217,25 @@ pub(crate) enum SlateDBError {
     #[error("cannot seek to a key outside the iterator range. key=`{key:?}`, range=`{range:?}`")]
     SeekKeyOutOfRange { key: Vec<u8>, range: BytesRange },
 
-    #[error("cannot seek to a key less than the last returned key")]
+    #[error("cannot seek to a key at or before the last returned key")]
     SeekKeyLessThanLastReturnedKey,
 
+    #[error("range start is after range end. start=`{start:?}`, end=`{end:?}`")]
+    InvalidRange {
+        start: Bound<Bytes>,
+        end: Bound<Bytes>,
+    },
+
+    #[error("invalid page token. reason=`{reason}`")]
+    InvalidPageToken { reason: String },
+
+    #[error("page token watermark is no longer available. watermark=`{watermark}`, min_watermark=`{min_watermark}`, max_watermark=`{max_watermark}`")]
+    PageTokenExpired {
+        watermark: u64,
+        min_watermark: u64,
+        max_watermark: u64,
+    },
+
     #[error(
         "parent path must be different from the clone's path. parent_path=`{0}`, clone_path=`{0}`"
     )]
@@ -261,6 +309,9 @@ pub(crate) enum SlateDBError {
     #[error("compaction filter error: {0}")]
     CompactionFilterError(Arc<crate::compaction_filter::CompactionFilterError>),
 
+    #[error("virtual source error: {0}")]
+    VirtualSourceError(Arc<crate::Error>),
+
     #[error("invalid sequence number ordering during merge. expected sequence numbers in descending order, but found {current_seq} followed by {next_seq}")]
     InvalidSequenceOrder { current_seq: u64, next_seq: u64 },
 
@@ -521,6 +572,26 @@ impl Error {
         self
     }
 
+    /// Prefixes the error message with `context`, e.g. to record what the
+    /// application was doing when the error occurred. The kind and source of
+    /// the error are kept, so callers can still match on [`Error::kind`].
+    ///
+    /// ```
+    /// use slatedb::{Error, ErrorKind};
+    ///
+    /// let err = Error::unavailable("object store timed out".to_string())
+    ///     .with_context("loading user profiles");
+    /// assert_eq!(err.kind(), ErrorKind::Unavailable);
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "Unavailable error: loading user profiles: object store timed out"
+    /// );
+    /// ```
+    pub fn with_context(mut self, context: impl std::fmt::Display) -> Self {
+        self.msg = format!("{}: {}", context, self.msg);
+        self
+    }
+
     /// Returns the error kind.
     pub fn kind(&self) -> ErrorKind {
         self.kind
@@ -535,7 +606,7 @@ impl From<SlateDBError> for Error {
             SlateDBError::TransactionConflict => Error::transaction(msg),
 
             // Closed
-            SlateDBError::Closed => Error::closed(msg, CloseReason::Clean),
+            SlateDBError::Closed | SlateDBError::Closing => Error::closed(msg, CloseReason::Clean),
             SlateDBError::Fenced => Error::closed(msg, CloseReason::Fenced),
             SlateDBError::BackgroundTaskPanic(_) => Error::closed(msg, CloseReason::Panic),
 
@@ -552,6 +623,9 @@ impl From<SlateDBError> for Error {
             #[cfg(feature = "foyer")]
             SlateDBError::FoyerError(err) => Error::unavailable(msg).with_source(Box::new(err)),
             SlateDBError::TransactionalObjectTimeout { .. } => Error::unavailable(msg),
+            SlateDBError::ScanDeadlineExceeded => Error::unavailable(msg),
+            SlateDBError::TooManyImmutableMemtables { .. } => Error::unavailable(msg),
+            SlateDBError::RateLimited { .. } => Error::unavailable(msg),
 
             // Invalid errors
             SlateDBError::InvalidCachePartSize => Error::invalid(msg),
@@ -570,14 +644,23 @@ impl From<SlateDBError> for Error {
             SlateDBError::CheckpointLifetimeTooShort { .. } => Error::invalid(msg),
             SlateDBError::SeekKeyOutOfRange { .. } => Error::invalid(msg),
             SlateDBError::SeekKeyLessThanLastReturnedKey => Error::invalid(msg),
+            SlateDBError::InvalidRange { .. } => Error::invalid(msg),
+            SlateDBError::CheckpointNameExists(_) => Error::invalid(msg),
+            SlateDBError::InvalidPageToken { .. } => Error::invalid(msg),
+            SlateDBError::PageTokenExpired { .. } => Error::invalid(msg),
             SlateDBError::IdenticalClonePaths { .. } => Error::invalid(msg),
             SlateDBError::WalDisabled => Error::invalid(msg),
             SlateDBError::InvalidCompaction => Error::invalid(msg),
+            SlateDBError::CompactorNotRunning => Error::invalid(msg),
             SlateDBError::InvalidSegmentPrefix { .. } => Error::invalid(msg),
+            SlateDBError::OutOfOrderAppend { .. } => Error::invalid(msg),
             SlateDBError::RecencyScanPrefixSpansMultipleSegments => Error::invalid(msg),
             SlateDBError::SegmentExtractorMismatch { .. } => Error::invalid(msg),
             SlateDBError::SegmentPrefixNotRecognized { .. } => Error::invalid(msg),
             SlateDBError::EmptySegmentPrefix { .. } => Error::invalid(msg),
+            SlateDBError::InvalidColumnFamilyName { .. } => Error::invalid(msg),
+            SlateDBError::ForeignSnapshot => Error::invalid(msg),
+            SlateDBError::ChangesNotRetained { .. } => Error::invalid(msg),
             SlateDBError::InvalidClockTick { .. } => Error::invalid(msg),
             SlateDBError::InvalidDeletion => Error::invalid(msg),
             SlateDBError::MergeOperatorError(err) => Error::invalid(msg).with_source(Box::new(err)),
@@ -622,7 +705,15 @@ impl From<SlateDBError> for Error {
             // Internal errors
             SlateDBError::CompactionExecutorFailed => Error::internal(msg),
             #[cfg(feature = "compaction_filters")]
-            SlateDBError::CompactionFilterError(_) => Error::internal(msg),
+            SlateDBError::CompactionFilterError(err) => {
+                Error::internal(msg).with_source(Box::new(err))
+            }
+            // keep the kind of the error the source returned
+            SlateDBError::VirtualSourceError(err) => Error {
+                msg,
+                kind: err.kind(),
+                source: Some(Box::new(err)),
+            },
             SlateDBError::SeekKeyOutOfKeyRange { .. } => Error::internal(msg),
             SlateDBError::ReadChannelError(err) => Error::internal(msg).with_source(Box::new(err)),
             SlateDBError::BackgroundTaskExists(_) => Error::internal(msg),
@@ -662,4 +753,58 @@ mod tests {
 
         assert_eq!(public_err.kind(), ErrorKind::Unavailable);
     }
+
+    #[test]
+    fn should_map_errors_to_kinds_independent_of_message() {
+        let cases = [
+            (SlateDBError::Fenced, ErrorKind::Closed(CloseReason::Fenced)),
+            (SlateDBError::Closed, ErrorKind::Closed(CloseReason::Clean)),
+            (SlateDBError::Closing, ErrorKind::Closed(CloseReason::Clean)),
+            (
+                SlateDBError::TransactionalObjectTimeout {
+                    timeout: Duration::from_secs(1),
+                },
+                ErrorKind::Unavailable,
+            ),
+            (
+                SlateDBError::ChecksumMismatch { path: None },
+                ErrorKind::Data,
+            ),
+            (SlateDBError::TransactionConflict, ErrorKind::Transaction),
+            (SlateDBError::EmptyBatch, ErrorKind::Invalid),
+            (SlateDBError::CompactionExecutorFailed, ErrorKind::Internal),
+        ];
+        for (err, kind) in cases {
+            let public_err = Error::from(err).with_context("handling request 42");
+            assert_eq!(public_err.kind(), kind, "{public_err}");
+        }
+    }
+
+    #[test]
+    fn should_chain_sources_through_context() {
+        let err = SlateDBError::from(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "read timed out",
+        ));
+        let public_err = Error::from(err).with_context("reading key k1");
+
+        assert_eq!(public_err.kind(), ErrorKind::Unavailable);
+        assert!(public_err.to_string().contains("reading key k1: "));
+        let source = std::error::Error::source(&public_err).unwrap();
+        let io_err = source.downcast_ref::<Arc<std::io::Error>>().unwrap();
+        assert_eq!(io_err.kind(), std::io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn should_keep_virtual_source_error_as_source() {
+        let err = SlateDBError::VirtualSourceError(Arc::new(Error::unavailable(
+            "source is down".to_string(),
+        )));
+        let public_err = Error::from(err);
+
+        assert_eq!(public_err.kind(), ErrorKind::Unavailable);
+        let source = std::error::Error::source(&public_err).unwrap();
+        let source_err = source.downcast_ref::<Arc<Error>>().unwrap();
+        assert_eq!(source_err.kind(), ErrorKind::Unavailable);
+    }
 }
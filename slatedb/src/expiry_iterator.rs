@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+
+use crate::error::SlateDBError;
+use crate::iter::RowEntryIterator;
+use crate::types::{is_expired, RowEntry, ValueDeletable};
+
+/// An iterator adapter that hides the entries that expired at or before `now`
+/// from reads.
+///
+/// Expired entries stay in memtables and SSTs until a flush or compaction
+/// drops them (see [`crate::retention_iterator::RetentionIterator`]), so reads
+/// apply the same rules: an expired value is returned as a tombstone with its
+/// sequence number, which hides the older versions of its key, and an expired
+/// merge operand is skipped, as older operands may still be live. Tombstones
+/// are returned as they are.
+pub(crate) struct ExpiryIterator<T: RowEntryIterator> {
+    iterator: T,
+    now: i64,
+}
+
+impl<T: RowEntryIterator> ExpiryIterator<T> {
+    pub(crate) fn new(iterator: T, now: i64) -> Self {
+        Self { iterator, now }
+    }
+}
+
+#[async_trait]
+impl<T: RowEntryIterator> RowEntryIterator for ExpiryIterator<T> {
+    async fn init(&mut self) -> Result<(), SlateDBError> {
+        self.iterator.init().await
+    }
+
+    async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        while let Some(entry) = self.iterator.next().await? {
+            if !is_expired(entry.expire_ts, self.now) {
+                return Ok(Some(entry));
+            }
+            match entry.value {
+                ValueDeletable::Value(_) => {
+                    return Ok(Some(RowEntry {
+                        value: ValueDeletable::Tombstone,
+                        expire_ts: None,
+                        ..entry
+                    }))
+                }
+                ValueDeletable::Merge(_) => continue,
+                ValueDeletable::Tombstone => return Ok(Some(entry)),
+            }
+        }
+        Ok(None)
+    }
+
+    async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
+        self.iterator.seek(next_key).await
+    }
+}
+
+/// Wraps each of `iters` in an [`ExpiryIterator`] with the given `now`.
+pub(crate) fn apply_expiry<T>(
+    iters: impl IntoIterator<Item = T>,
+    now: i64,
+) -> Vec<Box<dyn RowEntryIterator + 'static>>
+where
+    T: RowEntryIterator + 'static,
+{
+    iters
+        .into_iter()
+        .map(|iter| Box::new(ExpiryIterator::new(iter, now)) as Box<dyn RowEntryIterator + 'static>)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{assert_iterator, TestIterator};
+
+    #[tokio::test]
+    async fn should_hide_entries_expired_at_or_before_now() {
+        let iter = TestIterator::new()
+            .with_row_entry(RowEntry::new_value(b"a", b"1", 3).with_expire_ts(99))
+            .with_row_entry(RowEntry::new_value(b"a", b"0", 2))
+            .with_row_entry(RowEntry::new_value(b"b", b"1", 3).with_expire_ts(100))
+            .with_row_entry(RowEntry::new_value(b"c", b"1", 3).with_expire_ts(101))
+            .with_row_entry(RowEntry::new_merge(b"d", b"2", 3).with_expire_ts(50))
+            .with_row_entry(RowEntry::new_merge(b"d", b"1", 2))
+            .with_row_entry(RowEntry::new_tombstone(b"e", 3).with_expire_ts(50));
+
+        let mut iter = ExpiryIterator::new(iter, 100);
+
+        assert_iterator(
+            &mut iter,
+            vec![
+                RowEntry::new_tombstone(b"a", 3),
+                RowEntry::new_value(b"a", b"0", 2),
+                RowEntry::new_tombstone(b"b", 3),
+                RowEntry::new_value(b"c", b"1", 3).with_expire_ts(101),
+                RowEntry::new_merge(b"d", b"1", 2),
+                RowEntry::new_tombstone(b"e", 3).with_expire_ts(50),
+            ],
+        )
+        .await;
+    }
+}
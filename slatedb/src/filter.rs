@@ -1,3 +1,4 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use crate::filter_policy::{Filter, FilterBuilder, FilterQuery};
@@ -199,20 +200,90 @@ fn filter_hash(key: &[u8]) -> u64 {
 }
 
 fn probes_for_key(key_hash: u64, num_probes: u16, filter_bits: u32) -> Vec<u32> {
+    probe_iter(key_hash, num_probes, filter_bits).collect()
+}
+
+fn probe_iter(key_hash: u64, num_probes: u16, filter_bits: u32) -> impl Iterator<Item = u32> {
     // implements enhanced double hashing from:
     // https://www.khoury.northeastern.edu/~pete/pub/bloom-filters-verification.pdf
     // as suggested by the author P. Dillinger for RocksDB's legacy filters here:
     // https://github.com/facebook/rocksdb/issues/4120
-    let mut probes = vec![0u32; num_probes as usize];
     let filter_bits = filter_bits as u64;
     let mut h = ((key_hash << 32) >> 32) % filter_bits; // lower 32 bits of hash
     let mut delta = (key_hash >> 32) % filter_bits; // higher 32 bits of hash
-    for i in 0..num_probes {
+    (0..num_probes).map(move |i| {
         delta = (delta + i as u64) % filter_bits;
-        probes[i as usize] = h as u32;
+        let probe = h as u32;
         h = (h + delta) % filter_bits;
+        probe
+    })
+}
+
+/// The number of bits set for each key added to a [`MemtableFilter`].
+const MEMTABLE_FILTER_PROBES: u16 = 3;
+
+/// An approximate set of the keys written to a memtable, used to skip
+/// memtables that can't hold a key on point lookups.
+///
+/// Unlike a [`BloomFilter`], which is built once from the sorted keys of an
+/// SST, keys are added to this filter concurrently as they are written, so its
+/// size is fixed up front rather than derived from the number of keys. The
+/// false positive rate grows as the memtable fills up. Keys can't be removed:
+/// a deleted key still tests positive, which is harmless since the memtable
+/// holds its tombstone.
+pub(crate) struct MemtableFilter {
+    words: Box<[AtomicU64]>,
+}
+
+impl Clone for MemtableFilter {
+    fn clone(&self) -> Self {
+        Self {
+            words: self
+                .words
+                .iter()
+                .map(|word| AtomicU64::new(word.load(Ordering::Relaxed)))
+                .collect(),
+        }
+    }
+}
+
+impl MemtableFilter {
+    /// Creates a filter of about `bits` bits, rounded up to a multiple of 64.
+    /// A filter of 0 bits is disabled and reports every key as possibly
+    /// present.
+    pub(crate) fn new(bits: usize) -> Self {
+        // keep every bit addressable by a u32 probe
+        let words = bits.min(1 << 31).div_ceil(64);
+        Self {
+            words: (0..words).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    /// Returns the number of bits in the filter.
+    pub(crate) fn bits(&self) -> usize {
+        self.words.len() * 64
+    }
+
+    pub(crate) fn add(&self, key: &[u8]) {
+        if self.words.is_empty() {
+            return;
+        }
+        for probe in probe_iter(filter_hash(key), MEMTABLE_FILTER_PROBES, self.bits() as u32) {
+            let probe = probe as usize;
+            self.words[probe / 64].fetch_or(1 << (probe % 64), Ordering::Relaxed);
+        }
+    }
+
+    /// Returns false only if `key` was definitely never added.
+    pub(crate) fn might_contain(&self, key: &[u8]) -> bool {
+        if self.words.is_empty() {
+            return true;
+        }
+        probe_iter(filter_hash(key), MEMTABLE_FILTER_PROBES, self.bits() as u32).all(|probe| {
+            let probe = probe as usize;
+            self.words[probe / 64].load(Ordering::Relaxed) & (1 << (probe % 64)) != 0
+        })
     }
-    probes
 }
 
 fn check_bit(bit: usize, buf: &[u8]) -> bool {
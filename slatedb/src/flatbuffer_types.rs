@@ -150,6 +150,8 @@ impl FlatBufferSsTableInfoCodec {
             stats_offset: info.stats_offset(),
             stats_len: info.stats_len(),
             filter_format: info.filter_format().into(),
+            num_entries: info.num_entries(),
+            num_deletes: info.num_deletes(),
         }
     }
 
@@ -734,6 +736,8 @@ impl<'b> DbFlatBufferBuilder<'b> {
                 stats_offset: info.stats_offset,
                 stats_len: info.stats_len,
                 filter_format: info.filter_format.into(),
+                num_entries: info.num_entries,
+                num_deletes: info.num_deletes,
             },
         )
     }
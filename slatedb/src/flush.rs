@@ -4,7 +4,7 @@ use crate::db_state::SsTableHandle;
 use crate::error::SlateDBError;
 use crate::format::sst::EncodedSsTable;
 use crate::iter::RowEntryIterator;
-use crate::mem_table::KVTable;
+use crate::mem_table::{KVTable, MemtableCompactionInput};
 use crate::merge_operator::{MergeOperatorIterator, MergeOperatorRequiredIterator};
 use crate::oracle::Oracle;
 use crate::reader::DbStateReader;
@@ -238,16 +238,16 @@ impl DbInner {
         .flatten()
         .min();
 
+        let input = MemtableCompactionInput::new(imm_table.clone());
         let merge_iter = if let Some(merge_operator) = self.flush_merge_operator.clone() {
             Box::new(MergeOperatorIterator::new(
                 merge_operator,
-                imm_table.iter(),
+                input.iter(),
                 false,
                 min_retention_seq,
             ))
         } else {
-            Box::new(MergeOperatorRequiredIterator::new(imm_table.iter()))
-                as Box<dyn RowEntryIterator>
+            Box::new(MergeOperatorRequiredIterator::new(input.iter())) as Box<dyn RowEntryIterator>
         };
         let mut iter = RetentionIterator::new(
             merge_iter,
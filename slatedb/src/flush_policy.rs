@@ -0,0 +1,125 @@
+//! Deciding when to freeze the active memtable, so that it's flushed to L0.
+//!
+//! The [`FlushPolicy`] set with [`crate::DbBuilder::with_flush_policy`] is
+//! consulted after each write, and on every
+//! [`crate::Settings::manifest_poll_interval`] so that a policy can freeze a
+//! memtable that no longer receives writes. Freezing the active memtable when
+//! the memtables exceed [`crate::Settings::max_memtable_bytes`] is part of
+//! write backpressure, and happens whatever the policy decides.
+
+use std::time::Duration;
+
+use crate::config::Settings;
+
+/// The state of the active memtable that a [`FlushPolicy`] decides on.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FlushSignals {
+    /// The estimated size of the L0 SST the active memtable would be flushed
+    /// to.
+    pub estimated_sst_size: usize,
+    /// The time since the first write to the active memtable.
+    pub since_first_write: Duration,
+    /// The time since the last write to the active memtable.
+    pub since_last_write: Duration,
+    /// The number of WAL SSTs written since the last memtable was frozen.
+    pub wal_flushes_since_freeze: u64,
+    /// The key/value bytes held by the active and immutable memtables
+    /// together.
+    pub memtable_bytes: usize,
+    /// The budget on [`Self::memtable_bytes`], see
+    /// [`crate::Settings::max_memtable_bytes`].
+    pub max_memtable_bytes: Option<usize>,
+}
+
+/// Decides when to freeze the active memtable. Set with
+/// [`crate::DbBuilder::with_flush_policy`].
+///
+/// The policy is only consulted while the active memtable holds writes.
+///
+/// ## Examples
+///
+/// ```
+/// use slatedb::{Db, Error, FlushPolicy, FlushSignals};
+/// use slatedb::object_store::{ObjectStore, memory::InMemory};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// /// Flushes memtables that haven't been written to for a minute.
+/// struct FlushWhenIdle;
+///
+/// impl FlushPolicy for FlushWhenIdle {
+///     fn should_freeze(&self, signals: &FlushSignals) -> bool {
+///         signals.since_last_write >= Duration::from_secs(60)
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+///     let db = Db::builder("test_db", object_store)
+///         .with_flush_policy(Arc::new(FlushWhenIdle))
+///         .build()
+///         .await?;
+///     db.put(b"key", b"value").await?;
+///     Ok(())
+/// }
+/// ```
+pub trait FlushPolicy: Send + Sync {
+    /// Returns whether to freeze the active memtable now.
+    fn should_freeze(&self, signals: &FlushSignals) -> bool;
+}
+
+/// The default [`FlushPolicy`], which freezes the active memtable once it
+/// would be flushed to an SST of [`crate::Settings::l0_sst_size_bytes`], or
+/// after [`crate::Settings::max_wal_flushes_before_l0_flush`] WAL SSTs.
+#[derive(Clone, Copy, Debug)]
+pub struct DefaultFlushPolicy {
+    l0_sst_size_bytes: usize,
+    max_wal_flushes_before_l0_flush: u64,
+}
+
+impl DefaultFlushPolicy {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            l0_sst_size_bytes: settings.l0_sst_size_bytes,
+            max_wal_flushes_before_l0_flush: settings.max_wal_flushes_before_l0_flush,
+        }
+    }
+}
+
+impl FlushPolicy for DefaultFlushPolicy {
+    fn should_freeze(&self, signals: &FlushSignals) -> bool {
+        signals.estimated_sst_size >= self.l0_sst_size_bytes
+            || signals.wal_flushes_since_freeze >= self.max_wal_flushes_before_l0_flush
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(1023, 3, false)]
+    #[case(1024, 0, true)]
+    #[case(0, 4, true)]
+    fn test_default_policy_freezes_on_size_or_wal_flushes(
+        #[case] estimated_sst_size: usize,
+        #[case] wal_flushes_since_freeze: u64,
+        #[case] expected: bool,
+    ) {
+        let policy = DefaultFlushPolicy::new(&Settings {
+            l0_sst_size_bytes: 1024,
+            max_wal_flushes_before_l0_flush: 4,
+            ..Settings::default()
+        });
+        let signals = FlushSignals {
+            estimated_sst_size,
+            wal_flushes_since_freeze,
+            ..FlushSignals::default()
+        };
+
+        assert_eq!(policy.should_freeze(&signals), expected);
+    }
+}
@@ -461,6 +461,8 @@ impl<'a, 'b> EncodedSsTableFooterBuilder<'a, 'b> {
             stats_offset,
             stats_len,
             filter_format,
+            num_entries: maybe_stats.as_ref().map_or(0, |stats| stats.num_rows()),
+            num_deletes: maybe_stats.as_ref().map_or(0, |stats| stats.num_deletes),
         };
         SsTableInfo::encode(&info, &mut buf, self.sst_info_codec);
 
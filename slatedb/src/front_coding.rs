@@ -0,0 +1,400 @@
+//! A read-only, front-coded representation of a memtable's entries. See
+//! [`FrontCodedLog`].
+
+use std::ops::{Bound, ControlFlow, RangeBounds};
+use std::sync::Arc;
+
+use bytes::Bytes;
+
+use crate::iter::IterationOrder;
+use crate::types::{RowEntry, ValueDeletable};
+use crate::utils::{decode_varint, encode_varint};
+
+/// The number of keys between restart points. A restart key is stored in
+/// full, and every other key only stores what differs from the key before it.
+const RESTART_INTERVAL: usize = 16;
+
+/// The part of an entry other than its key.
+struct FrontCodedRow {
+    value: ValueDeletable,
+    seq: u64,
+    create_ts: Option<i64>,
+    expire_ts: Option<i64>,
+}
+
+/// The entries of a frozen memtable in [`SequencedKey`] order, with their keys
+/// front-coded: each key is stored as the length of the prefix it shares with
+/// the key before it, followed by the rest of the key. Keys with long common
+/// prefixes, and the versions of a key, take little more space than the bytes
+/// that tell them apart.
+///
+/// Every [`RESTART_INTERVAL`]-th key is stored in full, so that a key can be
+/// decoded from the restart point before it, and a key can be looked up by
+/// binary searching the restart points. Keys are decoded into new [`Bytes`]
+/// whenever an entry is read, so the log trades some read cost for memory,
+/// which suits memtables that are waiting to be flushed.
+///
+/// [`SequencedKey`]: crate::mem_table::SequencedKey
+pub(crate) struct FrontCodedLog {
+    /// The front-coded keys: for each entry, the varint length of the shared
+    /// prefix, the varint length of the suffix, and the suffix.
+    keys: Vec<u8>,
+    /// The offsets in `keys` of the restart keys.
+    restarts: Vec<usize>,
+    rows: Vec<FrontCodedRow>,
+}
+
+impl FrontCodedLog {
+    /// Builds a log from `visit`, which must lend entries in [`SequencedKey`]
+    /// order.
+    ///
+    /// [`SequencedKey`]: crate::mem_table::SequencedKey
+    pub(crate) fn build(visit: impl FnOnce(&mut dyn FnMut(&RowEntry))) -> Self {
+        let mut log = Self {
+            keys: Vec::new(),
+            restarts: Vec::new(),
+            rows: Vec::new(),
+        };
+        // keys are reference counted, so holding on to the last one is cheap
+        let mut last_key = Bytes::new();
+        visit(&mut |entry| {
+            let shared = if log.rows.len().is_multiple_of(RESTART_INTERVAL) {
+                log.restarts.push(log.keys.len());
+                0
+            } else {
+                last_key
+                    .iter()
+                    .zip(entry.key.iter())
+                    .take_while(|(a, b)| a == b)
+                    .count()
+            };
+            encode_varint(&mut log.keys, shared as u32);
+            encode_varint(&mut log.keys, (entry.key.len() - shared) as u32);
+            log.keys.extend_from_slice(&entry.key[shared..]);
+            log.rows.push(FrontCodedRow {
+                value: entry.value.clone(),
+                seq: entry.seq,
+                create_ts: entry.create_ts,
+                expire_ts: entry.expire_ts,
+            });
+            last_key = entry.key.clone();
+        });
+        log.keys.shrink_to_fit();
+        log.restarts.shrink_to_fit();
+        log.rows.shrink_to_fit();
+        log
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns the number of bytes holding the keys, including the restart
+    /// points.
+    pub(crate) fn key_bytes(&self) -> usize {
+        self.keys.capacity() + self.restarts.capacity() * std::mem::size_of::<usize>()
+    }
+
+    /// Decodes the keys from the restart point before `index`, calling `f`
+    /// with the index and key of each entry from `index` on until it breaks.
+    fn visit_keys(&self, index: usize, mut f: impl FnMut(usize, &[u8]) -> ControlFlow<()>) {
+        let restart = index / RESTART_INTERVAL;
+        let Some(&offset) = self.restarts.get(restart) else {
+            return;
+        };
+        let mut buf = &self.keys[offset..];
+        let mut key = Vec::new();
+        for current in restart * RESTART_INTERVAL..self.rows.len() {
+            let shared = decode_varint(&mut buf) as usize;
+            let suffix_len = decode_varint(&mut buf) as usize;
+            key.truncate(shared);
+            key.extend_from_slice(&buf[..suffix_len]);
+            buf = &buf[suffix_len..];
+            if current >= index && f(current, &key).is_break() {
+                return;
+            }
+        }
+    }
+
+    fn key(&self, index: usize) -> Option<Bytes> {
+        let mut key = None;
+        self.visit_keys(index, |_, k| {
+            key = Some(Bytes::copy_from_slice(k));
+            ControlFlow::Break(())
+        });
+        key
+    }
+
+    fn entry(&self, index: usize, key: &[u8]) -> RowEntry {
+        let row = &self.rows[index];
+        RowEntry::new(
+            Bytes::copy_from_slice(key),
+            row.value.clone(),
+            row.seq,
+            row.create_ts,
+            row.expire_ts,
+        )
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<RowEntry> {
+        let mut entry = None;
+        self.visit_keys(index, |index, key| {
+            entry = Some(self.entry(index, key));
+            ControlFlow::Break(())
+        });
+        entry
+    }
+
+    /// Returns the index of the first entry whose key doesn't satisfy `pred`,
+    /// which must hold for a prefix of the entries.
+    fn partition_point(&self, pred: impl Fn(&[u8]) -> bool) -> usize {
+        // a restart key is stored in full, right after its two varints
+        let restart_key = |offset: usize| {
+            let mut buf = &self.keys[offset..];
+            decode_varint(&mut buf);
+            let len = decode_varint(&mut buf) as usize;
+            &buf[..len]
+        };
+        let block = self
+            .restarts
+            .partition_point(|&offset| pred(restart_key(offset)));
+        if block == 0 {
+            return 0;
+        }
+        let mut point = (block - 1) * RESTART_INTERVAL;
+        self.visit_keys(point, |index, key| {
+            if pred(key) {
+                point = index + 1;
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        });
+        point
+    }
+
+    /// Returns the range of indexes of the entries whose keys fall in `range`.
+    pub(crate) fn index_range<T: RangeBounds<Bytes>>(&self, range: &T) -> std::ops::Range<usize> {
+        let start = self.partition_point(|key| match range.start_bound() {
+            Bound::Included(start) => key < start.as_ref(),
+            Bound::Excluded(start) => key <= start.as_ref(),
+            Bound::Unbounded => false,
+        });
+        let end = self.partition_point(|key| match range.end_bound() {
+            Bound::Included(end) => key <= end.as_ref(),
+            Bound::Excluded(end) => key < end.as_ref(),
+            Bound::Unbounded => true,
+        });
+        start..end.max(start)
+    }
+
+    /// Calls `f` with the entries at `indexes` until it breaks.
+    pub(crate) fn visit(
+        &self,
+        indexes: std::ops::Range<usize>,
+        mut f: impl FnMut(&RowEntry) -> ControlFlow<()>,
+    ) {
+        if indexes.is_empty() {
+            return;
+        }
+        self.visit_keys(indexes.start, |index, key| {
+            if index >= indexes.end {
+                return ControlFlow::Break(());
+            }
+            f(&self.entry(index, key))
+        });
+    }
+}
+
+/// Iterator over a range of a [`FrontCodedLog`].
+pub(crate) struct FrontCodedIterator {
+    log: Arc<FrontCodedLog>,
+    ordering: IterationOrder,
+    /// Indexes of the entries that have not been returned yet, other than
+    /// those in `group`.
+    remaining: std::ops::Range<usize>,
+    /// In descending order, the indexes of the versions of the current key
+    /// that have not been returned yet. The log holds them from newest to
+    /// oldest, which is the order they are returned in.
+    group: std::ops::Range<usize>,
+}
+
+impl FrontCodedIterator {
+    pub(crate) fn new<T: RangeBounds<Bytes>>(
+        log: Arc<FrontCodedLog>,
+        range: &T,
+        ordering: IterationOrder,
+    ) -> Self {
+        let remaining = log.index_range(range);
+        Self {
+            log,
+            ordering,
+            remaining,
+            group: 0..0,
+        }
+    }
+
+    pub(crate) fn next_sync(&mut self) -> Option<RowEntry> {
+        let index = match self.ordering {
+            IterationOrder::Ascending => self.remaining.next()?,
+            IterationOrder::Descending => {
+                if self.group.is_empty() {
+                    let last = self.remaining.next_back()?;
+                    let key = self.log.key(last)?;
+                    let first = self
+                        .log
+                        .partition_point(|k| k < key.as_ref())
+                        .max(self.remaining.start);
+                    self.group = first..last + 1;
+                    self.remaining.end = first;
+                }
+                self.group.next()?
+            }
+        };
+        self.log.get(index)
+    }
+
+    pub(crate) fn seek_sync(&mut self, next_key: &[u8]) {
+        match self.ordering {
+            IterationOrder::Ascending => {
+                let point = self.log.partition_point(|key| key < next_key);
+                self.remaining.start = point.clamp(self.remaining.start, self.remaining.end);
+            }
+            IterationOrder::Descending => {
+                // The versions of the current key are kept unless the key sorts
+                // after `next_key`, in which case all of them are skipped.
+                let group_is_after = !self.group.is_empty()
+                    && self
+                        .log
+                        .key(self.group.start)
+                        .is_some_and(|key| key.as_ref() > next_key);
+                if group_is_after {
+                    self.group = 0..0;
+                }
+                if self.group.is_empty() {
+                    let point = self.log.partition_point(|key| key <= next_key);
+                    self.remaining.end = point.clamp(self.remaining.start, self.remaining.end);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<RowEntry> {
+        let mut entries = Vec::new();
+        for i in 0..100u64 {
+            let key = format!("tenant/0001/user/{i:05}");
+            entries.push(RowEntry::new_value(key.as_bytes(), b"new", 1000 + i));
+            if i % 3 == 0 {
+                entries.push(RowEntry::new_tombstone(key.as_bytes(), i));
+            }
+        }
+        entries
+    }
+
+    fn build(entries: &[RowEntry]) -> Arc<FrontCodedLog> {
+        Arc::new(FrontCodedLog::build(|push| entries.iter().for_each(push)))
+    }
+
+    fn collect(mut iter: FrontCodedIterator) -> Vec<RowEntry> {
+        std::iter::from_fn(|| iter.next_sync()).collect()
+    }
+
+    #[test]
+    fn should_decode_every_entry() {
+        let entries = entries();
+        let log = build(&entries);
+        assert_eq!(log.len(), entries.len());
+        for (index, entry) in entries.iter().enumerate() {
+            assert_eq!(log.get(index).as_ref(), Some(entry));
+        }
+        assert_eq!(log.get(entries.len()), None);
+
+        let mut visited = Vec::new();
+        log.visit(5..40, |entry| {
+            visited.push(entry.clone());
+            ControlFlow::Continue(())
+        });
+        assert_eq!(visited, entries[5..40]);
+    }
+
+    #[test]
+    fn should_iterate_ranges_in_both_orders() {
+        let entries = entries();
+        let log = build(&entries);
+        let start = Bytes::from_static(b"tenant/0001/user/00010");
+        let end = Bytes::from_static(b"tenant/0001/user/00042");
+        let expected: Vec<_> = entries
+            .iter()
+            .filter(|entry| entry.key >= start && entry.key < end)
+            .cloned()
+            .collect();
+
+        let ascending = FrontCodedIterator::new(
+            log.clone(),
+            &(start.clone()..end.clone()),
+            IterationOrder::Ascending,
+        );
+        assert_eq!(collect(ascending), expected);
+
+        // keys come out in descending order, each key's versions newest first
+        let descending =
+            FrontCodedIterator::new(log.clone(), &(start..end), IterationOrder::Descending);
+        let mut expected_descending = expected;
+        expected_descending.sort_by(|a, b| b.key.cmp(&a.key).then(b.seq.cmp(&a.seq)));
+        assert_eq!(collect(descending), expected_descending);
+    }
+
+    #[test]
+    fn should_seek_forward() {
+        let entries = entries();
+        let log = build(&entries);
+        let mut iter = FrontCodedIterator::new(log.clone(), &(..), IterationOrder::Ascending);
+        iter.seek_sync(b"tenant/0001/user/00050");
+        assert_eq!(
+            iter.next_sync().map(|entry| entry.key),
+            Some(Bytes::from_static(b"tenant/0001/user/00050"))
+        );
+        // seeking backwards doesn't rewind
+        iter.seek_sync(b"tenant/0001/user/00001");
+        assert_eq!(
+            iter.next_sync().map(|entry| entry.key),
+            Some(Bytes::from_static(b"tenant/0001/user/00051"))
+        );
+
+        // descending seeks skip the keys after the seek key
+        let mut iter = FrontCodedIterator::new(log, &(..), IterationOrder::Descending);
+        iter.seek_sync(b"tenant/0001/user/00050");
+        assert_eq!(
+            iter.next_sync().map(|entry| entry.key),
+            Some(Bytes::from_static(b"tenant/0001/user/00050"))
+        );
+        iter.seek_sync(b"tenant/0001/user/00099");
+        assert_eq!(
+            iter.next_sync().map(|entry| entry.key),
+            Some(Bytes::from_static(b"tenant/0001/user/00049"))
+        );
+        iter.seek_sync(b"tenant/0000");
+        assert_eq!(iter.next_sync(), None);
+    }
+
+    #[test]
+    fn should_store_keys_with_shared_prefixes_compactly() {
+        let prefix = "a/long/shared/prefix/for/every/key/in/the/table/";
+        let entries: Vec<_> = (0..10_000)
+            .map(|i| RowEntry::new_value(format!("{prefix}{i:08}").as_bytes(), b"", i))
+            .collect();
+        let raw_key_bytes: usize = entries.iter().map(|entry| entry.key.len()).sum();
+        let log = build(&entries);
+        assert!(
+            log.key_bytes() * 4 < raw_key_bytes,
+            "front-coded keys take {} bytes, raw keys {}",
+            log.key_bytes(),
+            raw_key_bytes
+        );
+    }
+}
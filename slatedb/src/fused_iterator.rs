@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+
+use crate::error::SlateDBError;
+use crate::iter::{RowEntryIterator, TrackedRowEntryIterator};
+use crate::types::RowEntry;
+
+/// An iterator adapter that keeps returning `None` once the wrapped iterator
+/// is exhausted.
+///
+/// [`RowEntryIterator`] doesn't require iterators to stay exhausted, and an
+/// iterator over a live memtable can yield entries inserted after it returned
+/// `None`. Once the wrapped iterator returns `Ok(None)`, this adapter stops
+/// polling it: `next` returns `Ok(None)` and `seek` does nothing. Errors are
+/// passed through and don't end the iteration.
+pub(crate) struct FusedIterator<T: RowEntryIterator> {
+    iterator: T,
+    exhausted: bool,
+}
+
+impl<T: RowEntryIterator> FusedIterator<T> {
+    pub(crate) fn new(iterator: T) -> Self {
+        Self {
+            iterator,
+            exhausted: false,
+        }
+    }
+}
+
+#[async_trait]
+impl<T: RowEntryIterator> RowEntryIterator for FusedIterator<T> {
+    async fn init(&mut self) -> Result<(), SlateDBError> {
+        self.iterator.init().await
+    }
+
+    async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        if self.exhausted {
+            return Ok(None);
+        }
+        let entry = self.iterator.next().await?;
+        self.exhausted = entry.is_none();
+        Ok(entry)
+    }
+
+    async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
+        // seeks only move forward, so there's nothing left to seek to
+        if self.exhausted {
+            return Ok(());
+        }
+        self.iterator.seek(next_key).await
+    }
+}
+
+impl<T: TrackedRowEntryIterator> TrackedRowEntryIterator for FusedIterator<T> {
+    fn bytes_processed(&self) -> u64 {
+        self.iterator.bytes_processed()
+    }
+
+    fn entries_processed(&self) -> u64 {
+        self.iterator.entries_processed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes_range::BytesRange;
+    use crate::iter::IterationOrder;
+    use crate::mem_table::KVTable;
+    use crate::test_utils::TestIterator;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(IterationOrder::Ascending)]
+    #[case(IterationOrder::Descending)]
+    #[tokio::test]
+    async fn should_not_yield_entries_inserted_after_exhaustion(#[case] order: IterationOrder) {
+        let table = KVTable::new();
+        table.put(RowEntry::new_value(b"b", b"1", 1));
+        let mut iter = FusedIterator::new(table.range(BytesRange::from(..), order));
+        iter.init().await.unwrap();
+
+        assert_eq!(
+            iter.next().await.unwrap(),
+            Some(RowEntry::new_value(b"b", b"1", 1))
+        );
+        assert_eq!(iter.next().await.unwrap(), None);
+
+        table.put(RowEntry::new_value(b"a", b"2", 2));
+        table.put(RowEntry::new_value(b"c", b"3", 3));
+        assert_eq!(iter.next().await.unwrap(), None);
+        iter.seek(b"a").await.unwrap();
+        assert_eq!(iter.next().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn should_pass_through_until_exhausted() {
+        let iter = TestIterator::new()
+            .with_entry(b"key1", b"value1", 1)
+            .with_entry(b"key2", b"value2", 2)
+            .with_entry(b"key3", b"value3", 3);
+        let mut iter = FusedIterator::new(iter);
+        iter.init().await.unwrap();
+
+        iter.seek(b"key2").await.unwrap();
+        assert_eq!(
+            iter.next().await.unwrap(),
+            Some(RowEntry::new_value(b"key2", b"value2", 2))
+        );
+        assert_eq!(
+            iter.next().await.unwrap(),
+            Some(RowEntry::new_value(b"key3", b"value3", 3))
+        );
+        assert_eq!(iter.next().await.unwrap(), None);
+        assert_eq!(iter.next().await.unwrap(), None);
+    }
+}
@@ -0,0 +1,269 @@
+//! Reporting the gaps between the keys returned by a scan.
+//!
+//! [`GapIterator`] wraps a [`DbIterator`] and returns a [`KeyGap`] between two
+//! consecutive keys of the scan whenever enough keys are missing between
+//! them, e.g. to prefetch around sparse data or to show where a range holds
+//! no data.
+//!
+//! Keys are arbitrary bytes, so there's no general notion of how many keys
+//! fit between two of them. Instead, the caller supplies a successor function
+//! that returns the key that follows a key in their own keyspace, e.g. the
+//! next integer for keys written with [`crate::key_encoding::IntKey`]. Gaps
+//! are measured as follows:
+//!
+//! - The size of the gap between two consecutive keys is the number of times
+//!   the successor function can be applied to the smaller key before reaching
+//!   the larger one, i.e. the number of keys of the keyspace missing between
+//!   them. The function is applied at most `min_gap` times per pair of keys,
+//!   so gaps are found in time proportional to `min_gap`, not to their size.
+//! - Only gaps of at least `min_gap` missing keys are reported.
+//! - Deleted keys count as missing, since the wrapped scan doesn't return
+//!   them. Gaps before the first and after the last key of the scan are not
+//!   reported, since the scanned range may extend past the keyspace.
+
+use std::ops::{Bound, RangeBounds};
+
+use bytes::Bytes;
+
+use crate::db_iter::DbIterator;
+use crate::types::KeyValue;
+
+/// Returns the key that follows a key in the caller's keyspace, or `None` if
+/// it's the last key of the keyspace.
+type SuccessorFn = Box<dyn Fn(&[u8]) -> Option<Bytes> + Send + Sync>;
+
+/// A range of missing keys between two consecutive keys of a scan.
+///
+/// A gap can be passed to [`crate::Db::scan`] as a range, and holds no keys
+/// at the time of the scan that reported it.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyGap {
+    /// The first missing key, i.e. the successor of the smaller of the two
+    /// keys around the gap.
+    pub start: Bytes,
+    /// The larger of the two keys around the gap, which is not part of it.
+    pub end: Bytes,
+}
+
+impl RangeBounds<Bytes> for KeyGap {
+    fn start_bound(&self) -> Bound<&Bytes> {
+        Bound::Included(&self.start)
+    }
+
+    fn end_bound(&self) -> Bound<&Bytes> {
+        Bound::Excluded(&self.end)
+    }
+}
+
+/// An item returned by a [`GapIterator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GapScanItem {
+    /// An entry returned by the wrapped scan.
+    Entry(KeyValue),
+    /// A gap between the previous entry and the next one.
+    Gap(KeyGap),
+}
+
+/// Returns the entries of a [`DbIterator`] along with the gaps between them.
+/// See the module docs for how gaps are measured.
+pub struct GapIterator {
+    iter: DbIterator,
+    successor: SuccessorFn,
+    min_gap: usize,
+    /// The key of the last entry returned, used to measure the next gap.
+    last_key: Option<Bytes>,
+    /// An entry read after a gap, returned after the gap itself.
+    pending: Option<KeyValue>,
+}
+
+impl GapIterator {
+    /// Wraps `iter`, reporting gaps of at least `min_gap` missing keys as
+    /// measured with `successor`. A `min_gap` of 0 is treated as 1, which
+    /// reports every gap.
+    pub fn new<F>(iter: DbIterator, successor: F, min_gap: usize) -> Self
+    where
+        F: Fn(&[u8]) -> Option<Bytes> + Send + Sync + 'static,
+    {
+        Self {
+            iter,
+            successor: Box::new(successor),
+            min_gap: min_gap.max(1),
+            last_key: None,
+            pending: None,
+        }
+    }
+
+    /// Returns the next entry or gap, or `None` once the scan is exhausted.
+    /// A gap is returned between the entries around it, in the scan's order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error`] if the wrapped iterator fails, or with kind
+    /// [`crate::ErrorKind::Invalid`] if the successor function returns a key
+    /// that isn't larger than the key it was given.
+    pub async fn next(&mut self) -> Result<Option<GapScanItem>, crate::Error> {
+        if let Some(entry) = self.pending.take() {
+            return Ok(Some(GapScanItem::Entry(entry)));
+        }
+        let Some(entry) = self.iter.next().await? else {
+            return Ok(None);
+        };
+        let gap = match self.last_key.replace(entry.key.clone()) {
+            Some(last_key) if last_key < entry.key => self.find_gap(&last_key, &entry.key)?,
+            Some(last_key) => self.find_gap(&entry.key, &last_key)?,
+            None => None,
+        };
+        match gap {
+            Some(gap) => {
+                self.pending = Some(entry);
+                Ok(Some(GapScanItem::Gap(gap)))
+            }
+            None => Ok(Some(GapScanItem::Entry(entry))),
+        }
+    }
+
+    /// Returns the gap between `low` and `high` if at least `min_gap` keys
+    /// are missing between them.
+    fn find_gap(&self, low: &Bytes, high: &Bytes) -> Result<Option<KeyGap>, crate::Error> {
+        let mut start = None;
+        let mut key = low.clone();
+        for _ in 0..self.min_gap {
+            let Some(next) = (self.successor)(&key) else {
+                return Ok(None);
+            };
+            if next <= key {
+                return Err(crate::Error::invalid(format!(
+                    "successor {:?} of key {:?} is not larger than the key",
+                    next, key
+                )));
+            }
+            if next >= high {
+                return Ok(None);
+            }
+            start.get_or_insert_with(|| next.clone());
+            key = next;
+        }
+        Ok(start.map(|start| KeyGap {
+            start,
+            end: high.clone(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ScanOptions;
+    use crate::iter::IterationOrder;
+    use crate::key_encoding::IntKey;
+    use crate::{Db, ErrorKind};
+    use object_store::memory::InMemory;
+    use std::sync::Arc;
+
+    fn next_int(key: &[u8]) -> Option<Bytes> {
+        u64::decode(key)?.checked_add(1).map(IntKey::encode)
+    }
+
+    /// Renders entries as their integer key and gaps as `(start, end)`.
+    #[derive(Debug, PartialEq)]
+    enum Item {
+        Entry(u64),
+        Gap(u64, u64),
+    }
+
+    async fn collect_items(db: &Db, order: IterationOrder, min_gap: usize) -> Vec<Item> {
+        let options = ScanOptions {
+            order,
+            ..ScanOptions::default()
+        };
+        let iter = db
+            .scan_with_options::<Bytes, _>(.., &options)
+            .await
+            .unwrap();
+        let mut iter = GapIterator::new(iter, next_int, min_gap);
+        let mut items = Vec::new();
+        while let Some(item) = iter.next().await.unwrap() {
+            let int = |key: &Bytes| u64::decode(key).unwrap();
+            items.push(match item {
+                GapScanItem::Entry(kv) => Item::Entry(int(&kv.key)),
+                GapScanItem::Gap(gap) => Item::Gap(int(&gap.start), int(&gap.end)),
+            });
+        }
+        items
+    }
+
+    #[tokio::test]
+    async fn should_report_gaps_of_at_least_the_minimum_size() {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        let mut batch = crate::WriteBatch::new();
+        for i in [1u64, 2, 3, 10, 11, 12, 50, 51] {
+            batch.put(i.encode(), b"value");
+        }
+        db.write(batch).await.unwrap();
+        // a deleted key leaves a hole, too
+        db.delete(11u64.encode()).await.unwrap();
+
+        use Item::{Entry, Gap};
+        assert_eq!(
+            collect_items(&db, IterationOrder::Ascending, 6).await,
+            vec![
+                Entry(1),
+                Entry(2),
+                Entry(3),
+                Gap(4, 10),
+                Entry(10),
+                Entry(12),
+                Gap(13, 50),
+                Entry(50),
+                Entry(51),
+            ]
+        );
+        // 4..10 holds 6 missing keys, which is one short of 7
+        assert_eq!(
+            collect_items(&db, IterationOrder::Ascending, 7).await,
+            vec![
+                Entry(1),
+                Entry(2),
+                Entry(3),
+                Entry(10),
+                Entry(12),
+                Gap(13, 50),
+                Entry(50),
+                Entry(51),
+            ]
+        );
+        assert_eq!(
+            collect_items(&db, IterationOrder::Descending, 1).await,
+            vec![
+                Entry(51),
+                Entry(50),
+                Gap(13, 50),
+                Entry(12),
+                Gap(11, 12),
+                Entry(10),
+                Gap(4, 10),
+                Entry(3),
+                Entry(2),
+                Entry(1),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_a_successor_that_does_not_advance() {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        db.put(b"a", b"value").await.unwrap();
+        db.put(b"c", b"value").await.unwrap();
+
+        let iter = db.scan::<&[u8], _>(..).await.unwrap();
+        let mut iter = GapIterator::new(iter, |key: &[u8]| Some(Bytes::copy_from_slice(key)), 1);
+        assert!(iter.next().await.unwrap().is_some());
+        let err = iter.next().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Invalid);
+    }
+}
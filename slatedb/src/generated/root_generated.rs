@@ -1129,6 +1129,8 @@ impl<'a> SsTableInfo<'a> {
   pub const VT_STATS_OFFSET: flatbuffers::VOffsetT = 20;
   pub const VT_STATS_LEN: flatbuffers::VOffsetT = 22;
   pub const VT_FILTER_FORMAT: flatbuffers::VOffsetT = 24;
+  pub const VT_NUM_ENTRIES: flatbuffers::VOffsetT = 26;
+  pub const VT_NUM_DELETES: flatbuffers::VOffsetT = 28;
 
   #[inline]
   pub unsafe fn init_from_table(table: flatbuffers::Table<'a>) -> Self {
@@ -1140,6 +1142,8 @@ impl<'a> SsTableInfo<'a> {
     args: &'args SsTableInfoArgs<'args>
   ) -> flatbuffers::WIPOffset<SsTableInfo<'bldr>> {
     let mut builder = SsTableInfoBuilder::new(_fbb);
+    builder.add_num_deletes(args.num_deletes);
+    builder.add_num_entries(args.num_entries);
     builder.add_stats_len(args.stats_len);
     builder.add_stats_offset(args.stats_offset);
     builder.add_filter_len(args.filter_len);
@@ -1232,6 +1236,20 @@ impl<'a> SsTableInfo<'a> {
     // which contains a valid value in this slot
     unsafe { self._tab.get::<FilterFormat>(SsTableInfo::VT_FILTER_FORMAT, Some(FilterFormat::Legacy)).unwrap()}
   }
+  #[inline]
+  pub fn num_entries(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(SsTableInfo::VT_NUM_ENTRIES, Some(0)).unwrap()}
+  }
+  #[inline]
+  pub fn num_deletes(&self) -> u64 {
+    // Safety:
+    // Created from valid Table for this object
+    // which contains a valid value in this slot
+    unsafe { self._tab.get::<u64>(SsTableInfo::VT_NUM_DELETES, Some(0)).unwrap()}
+  }
 }
 
 impl flatbuffers::Verifiable for SsTableInfo<'_> {
@@ -1252,6 +1270,8 @@ impl flatbuffers::Verifiable for SsTableInfo<'_> {
      .visit_field::<u64>("stats_offset", Self::VT_STATS_OFFSET, false)?
      .visit_field::<u64>("stats_len", Self::VT_STATS_LEN, false)?
      .visit_field::<FilterFormat>("filter_format", Self::VT_FILTER_FORMAT, false)?
+     .visit_field::<u64>("num_entries", Self::VT_NUM_ENTRIES, false)?
+     .visit_field::<u64>("num_deletes", Self::VT_NUM_DELETES, false)?
      .finish();
     Ok(())
   }
@@ -1268,6 +1288,8 @@ pub struct SsTableInfoArgs<'a> {
     pub stats_offset: u64,
     pub stats_len: u64,
     pub filter_format: FilterFormat,
+    pub num_entries: u64,
+    pub num_deletes: u64,
 }
 impl<'a> Default for SsTableInfoArgs<'a> {
   #[inline]
@@ -1284,6 +1306,8 @@ impl<'a> Default for SsTableInfoArgs<'a> {
       stats_offset: 0,
       stats_len: 0,
       filter_format: FilterFormat::Legacy,
+      num_entries: 0,
+      num_deletes: 0,
     }
   }
 }
@@ -1338,6 +1362,14 @@ impl<'a: 'b, 'b, A: flatbuffers::Allocator + 'a> SsTableInfoBuilder<'a, 'b, A> {
     self.fbb_.push_slot::<FilterFormat>(SsTableInfo::VT_FILTER_FORMAT, filter_format, FilterFormat::Legacy);
   }
   #[inline]
+  pub fn add_num_entries(&mut self, num_entries: u64) {
+    self.fbb_.push_slot::<u64>(SsTableInfo::VT_NUM_ENTRIES, num_entries, 0);
+  }
+  #[inline]
+  pub fn add_num_deletes(&mut self, num_deletes: u64) {
+    self.fbb_.push_slot::<u64>(SsTableInfo::VT_NUM_DELETES, num_deletes, 0);
+  }
+  #[inline]
   pub fn new(_fbb: &'b mut flatbuffers::FlatBufferBuilder<'a, A>) -> SsTableInfoBuilder<'a, 'b, A> {
     let start = _fbb.start_table();
     SsTableInfoBuilder {
@@ -1366,6 +1398,8 @@ impl core::fmt::Debug for SsTableInfo<'_> {
       ds.field("stats_offset", &self.stats_offset());
       ds.field("stats_len", &self.stats_len());
       ds.field("filter_format", &self.filter_format());
+      ds.field("num_entries", &self.num_entries());
+      ds.field("num_deletes", &self.num_deletes());
       ds.finish()
   }
 }
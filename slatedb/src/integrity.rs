@@ -0,0 +1,464 @@
+//! Online integrity checks of a db's SSTs. See [`Db::verify_integrity`].
+
+use std::collections::HashSet;
+use std::ops::RangeBounds;
+
+use bytes::Bytes;
+use ulid::Ulid;
+
+use crate::block_iterator::DataBlockIterator;
+use crate::bytes_range::BytesRange;
+use crate::config::ScanOptions;
+use crate::db::Db;
+use crate::db_state::{SsTableHandle, SsTableView};
+use crate::error::SlateDBError;
+use crate::iter::IterationOrder;
+use crate::tablestore::TableStore;
+
+/// The number of data blocks read from object storage at a time.
+const BLOCKS_PER_READ: usize = 16;
+
+/// An inconsistency found by [`Db::verify_integrity`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityAnomaly {
+    /// An SST couldn't be read or decoded, e.g. because a block's checksum
+    /// doesn't match its contents.
+    UnreadableSst {
+        /// The id of the SST.
+        sst: Ulid,
+        /// The error the read failed with.
+        error: String,
+    },
+    /// The metadata the manifest holds for an SST differs from the metadata
+    /// in the SST's footer.
+    SstInfoMismatch {
+        /// The id of the SST.
+        sst: Ulid,
+    },
+    /// An entry of an SST isn't sorted after the entry before it, by
+    /// ascending key and then by descending sequence number.
+    OutOfOrderEntry {
+        /// The id of the SST.
+        sst: Ulid,
+        /// The key of the entry.
+        key: Bytes,
+        /// The sequence number of the entry.
+        seq: u64,
+        /// The key of the entry before it.
+        previous_key: Bytes,
+        /// The sequence number of the entry before it.
+        previous_seq: u64,
+    },
+    /// The first key recorded in an SST's metadata isn't the key of its
+    /// first entry.
+    FirstKeyMismatch {
+        /// The id of the SST.
+        sst: Ulid,
+        /// The first key recorded in the metadata.
+        recorded: Option<Bytes>,
+        /// The key of the first entry.
+        actual: Option<Bytes>,
+    },
+    /// The number of rows recorded in an SST's stats block doesn't match the
+    /// number of entries the SST holds.
+    RowCountMismatch {
+        /// The id of the SST.
+        sst: Ulid,
+        /// The number of rows recorded in the stats block.
+        recorded: u64,
+        /// The number of entries read from the SST.
+        actual: u64,
+    },
+    /// The keys visible in an SST of a sorted run don't all come after the
+    /// keys visible in the SST before it.
+    OverlappingSortedRun {
+        /// The id of the sorted run.
+        sorted_run: u32,
+        /// The id of the SST.
+        sst: Ulid,
+        /// The id of the SST before it in the sorted run.
+        previous_sst: Ulid,
+    },
+    /// A scan of the db returned a key that isn't after the key before it,
+    /// e.g. because two versions of a key weren't merged.
+    UnsortedScan {
+        /// The key returned by the scan.
+        key: Bytes,
+        /// The key the scan returned before it.
+        previous_key: Bytes,
+    },
+    /// A scan of the db failed before reaching the end of the keyspace.
+    ScanFailed {
+        /// The error the scan failed with.
+        error: String,
+    },
+}
+
+/// The result of [`Db::verify_integrity`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// The number of distinct SSTs that were read.
+    pub ssts_checked: usize,
+    /// The number of entries read from SSTs, including tombstones, merge
+    /// operands and older versions of keys.
+    pub entries_checked: u64,
+    /// The number of keys returned by the scan of the db.
+    pub keys_scanned: u64,
+    /// The inconsistencies that were found, in the order they were found.
+    pub anomalies: Vec<IntegrityAnomaly>,
+}
+
+impl IntegrityReport {
+    /// Returns true if no inconsistencies were found.
+    pub fn is_ok(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+}
+
+/// The keys of an SST view that are visible through it.
+struct VisibleKeys {
+    first: Option<Bytes>,
+    last: Option<Bytes>,
+}
+
+impl Db {
+    /// Checks the integrity of the db by reading every SST it references and
+    /// scanning the whole keyspace, and returns a report of the
+    /// inconsistencies it finds. For each SST it checks that:
+    ///
+    /// - every block can be read from object storage and its checksum matches,
+    ///   bypassing the block cache;
+    /// - its entries are sorted by key and then by descending sequence number;
+    /// - its first key and row count match its metadata and stats block.
+    ///
+    /// It also checks that the SSTs of each sorted run don't overlap, and that
+    /// a scan of the db returns every key once, in order.
+    ///
+    /// The check runs against the SSTs referenced by the manifest when it is
+    /// called and doesn't block writes, flushes or compactions. An SST that
+    /// compaction replaces and the garbage collector deletes while the check
+    /// runs is reported as unreadable, which the garbage collector's minimum
+    /// age makes unlikely. The check reads every block of the db from object
+    /// storage, so it can take a long time and incur significant request
+    /// costs on a large db.
+    ///
+    /// ## Returns
+    /// - `Ok(IntegrityReport)`: the report, whose `anomalies` are empty if no
+    ///   inconsistencies were found
+    ///
+    /// ## Errors
+    /// - `Error`: if the db has been closed. Failures to read SSTs or to scan
+    ///   are reported as anomalies instead.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     db.put(b"key", b"value").await?;
+    ///     db.flush().await?;
+    ///
+    ///     let report = db.verify_integrity().await?;
+    ///     assert!(report.is_ok(), "{:?}", report.anomalies);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn verify_integrity(&self) -> Result<IntegrityReport, crate::Error> {
+        self.inner.check_closed()?;
+        let trees: Vec<_> = {
+            let state = self.inner.state.read().state();
+            state.core().trees().cloned().collect()
+        };
+        let table_store = self.inner.table_store.without_cache();
+        let mut report = IntegrityReport::default();
+        let mut checked = HashSet::new();
+
+        for tree in &trees {
+            for view in &tree.l0 {
+                verify_sst_view(&table_store, view, &mut checked, &mut report).await;
+            }
+            for sorted_run in &tree.compacted {
+                let mut previous: Option<(Ulid, Bytes)> = None;
+                for view in &sorted_run.sst_views {
+                    let keys = verify_sst_view(&table_store, view, &mut checked, &mut report).await;
+                    let sst = view.sst.id.unwrap_compacted_id();
+                    if let (Some((previous_sst, previous_last)), Some(first)) =
+                        (&previous, &keys.first)
+                    {
+                        if first <= previous_last {
+                            report
+                                .anomalies
+                                .push(IntegrityAnomaly::OverlappingSortedRun {
+                                    sorted_run: sorted_run.id,
+                                    sst,
+                                    previous_sst: *previous_sst,
+                                });
+                        }
+                    }
+                    if let Some(last) = keys.last {
+                        previous = Some((sst, last));
+                    }
+                }
+            }
+        }
+
+        self.verify_scan(&mut report).await;
+        Ok(report)
+    }
+
+    async fn verify_scan(&self, report: &mut IntegrityReport) {
+        let iter = self
+            .inner
+            .scan_with_options(BytesRange::unbounded(), &ScanOptions::default())
+            .await;
+        let mut iter = match iter {
+            Ok(iter) => iter,
+            Err(err) => {
+                report.anomalies.push(IntegrityAnomaly::ScanFailed {
+                    error: err.to_string(),
+                });
+                return;
+            }
+        };
+        let mut previous_key: Option<Bytes> = None;
+        loop {
+            let kv = match iter.next().await {
+                Ok(Some(kv)) => kv,
+                Ok(None) => return,
+                Err(err) => {
+                    report.anomalies.push(IntegrityAnomaly::ScanFailed {
+                        error: err.to_string(),
+                    });
+                    return;
+                }
+            };
+            report.keys_scanned += 1;
+            if let Some(previous_key) = previous_key.take() {
+                if kv.key <= previous_key {
+                    report.anomalies.push(IntegrityAnomaly::UnsortedScan {
+                        key: kv.key.clone(),
+                        previous_key,
+                    });
+                }
+            }
+            previous_key = Some(kv.key);
+        }
+    }
+}
+
+/// Reads the SST of `view`, checking it unless it was already checked
+/// through another view, and returns the first and last keys visible
+/// through `view`.
+async fn verify_sst_view(
+    table_store: &TableStore,
+    view: &SsTableView,
+    checked: &mut HashSet<Ulid>,
+    report: &mut IntegrityReport,
+) -> VisibleKeys {
+    let sst = view.sst.id.unwrap_compacted_id();
+    let first_check = checked.insert(sst);
+    let mut keys = VisibleKeys {
+        first: None,
+        last: None,
+    };
+    let mut anomalies = Vec::new();
+    let result = read_sst(table_store, &view.sst, |entry_key, seq, previous| {
+        if let Some((previous_key, previous_seq)) = previous {
+            let sorted = match entry_key.cmp(previous_key) {
+                std::cmp::Ordering::Greater => true,
+                std::cmp::Ordering::Equal => seq < previous_seq,
+                std::cmp::Ordering::Less => false,
+            };
+            if !sorted && first_check {
+                anomalies.push(IntegrityAnomaly::OutOfOrderEntry {
+                    sst,
+                    key: entry_key.clone(),
+                    seq,
+                    previous_key: previous_key.clone(),
+                    previous_seq,
+                });
+            }
+        }
+        if view.compacted_effective_range().contains(entry_key) {
+            if keys.first.is_none() {
+                keys.first = Some(entry_key.clone());
+            }
+            keys.last = Some(entry_key.clone());
+        }
+    })
+    .await;
+    if !first_check {
+        return keys;
+    }
+    report.ssts_checked += 1;
+    report.anomalies.extend(anomalies);
+    match result {
+        Ok(summary) => {
+            report.entries_checked += summary.entries;
+            if summary.info_mismatch {
+                report
+                    .anomalies
+                    .push(IntegrityAnomaly::SstInfoMismatch { sst });
+            }
+            if summary.recorded_first_key != summary.first_key {
+                report.anomalies.push(IntegrityAnomaly::FirstKeyMismatch {
+                    sst,
+                    recorded: summary.recorded_first_key,
+                    actual: summary.first_key,
+                });
+            }
+            if let Some(recorded) = summary.recorded_rows {
+                if recorded != summary.entries {
+                    report.anomalies.push(IntegrityAnomaly::RowCountMismatch {
+                        sst,
+                        recorded,
+                        actual: summary.entries,
+                    });
+                }
+            }
+        }
+        Err(err) => report.anomalies.push(IntegrityAnomaly::UnreadableSst {
+            sst,
+            error: err.to_string(),
+        }),
+    }
+    keys
+}
+
+/// What was read from an SST by [`read_sst`].
+struct SstSummary {
+    entries: u64,
+    first_key: Option<Bytes>,
+    recorded_first_key: Option<Bytes>,
+    recorded_rows: Option<u64>,
+    info_mismatch: bool,
+}
+
+/// Reads every entry of an SST from object storage, calling `visit` with
+/// each entry's key and sequence number and the key and sequence number of
+/// the entry before it.
+async fn read_sst<F>(
+    table_store: &TableStore,
+    manifest_handle: &SsTableHandle,
+    mut visit: F,
+) -> Result<SstSummary, SlateDBError>
+where
+    F: FnMut(&Bytes, u64, Option<(&Bytes, u64)>),
+{
+    // the footer is read from the file, so that a file that doesn't match
+    // what the manifest recorded for it is still checked against itself
+    let handle = table_store.open_sst(&manifest_handle.id).await?;
+    let index = table_store.read_index(&handle, false).await?;
+    let num_blocks = index.borrow().block_meta().len();
+    let mut summary = SstSummary {
+        entries: 0,
+        first_key: None,
+        recorded_first_key: handle.info.first_entry.clone(),
+        recorded_rows: table_store
+            .read_stats(&handle, false)
+            .await?
+            .map(|stats| stats.num_rows()),
+        info_mismatch: handle.info != manifest_handle.info,
+    };
+    let mut previous: Option<(Bytes, u64)> = None;
+    let mut start = 0;
+    while start < num_blocks {
+        let end = num_blocks.min(start + BLOCKS_PER_READ);
+        let blocks = table_store
+            .read_blocks_using_index(&handle, index.clone(), start..end, false)
+            .await?;
+        for block in blocks {
+            let mut iter =
+                DataBlockIterator::new(block, handle.format_version, IterationOrder::Ascending)?;
+            while let Some(entry) = iter.next().await? {
+                visit(
+                    &entry.key,
+                    entry.seq,
+                    previous.as_ref().map(|(key, seq)| (key, *seq)),
+                );
+                if summary.first_key.is_none() {
+                    summary.first_key = Some(entry.key.clone());
+                }
+                summary.entries += 1;
+                previous = Some((entry.key, entry.seq));
+            }
+        }
+        start = end;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{FlushOptions, FlushType};
+    use crate::types::RowEntry;
+    use object_store::memory::InMemory;
+    use object_store::path::Path;
+    use object_store::ObjectStore;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_verify_integrity_detects_out_of_order_sst() {
+        let object_store = Arc::new(InMemory::new());
+        let path = "/tmp/test_verify_integrity";
+        let db = Db::open(path, object_store.clone()).await.unwrap();
+        for key in [b"a", b"b", b"c"] {
+            db.put(key, b"value").await.unwrap();
+        }
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+
+        let report = db.verify_integrity().await.unwrap();
+        assert!(report.is_ok(), "{:?}", report.anomalies);
+        assert_eq!(report.ssts_checked, 1);
+        assert_eq!(report.entries_checked, 3);
+        assert_eq!(report.keys_scanned, 3);
+
+        // overwrite the L0 SST with one whose keys are out of order
+        let manifest = db.manifest();
+        let sst = manifest.l0()[0].sst.id.unwrap_compacted_id();
+        let mut builder = db.inner.table_store.table_builder();
+        for (key, seq) in [(b"a", 1), (b"c", 3), (b"b", 2)] {
+            builder
+                .add(RowEntry::new_value(key, b"value", seq))
+                .await
+                .unwrap();
+        }
+        let encoded = builder.build().await.unwrap();
+        object_store
+            .put(
+                &Path::from(format!("{}/compacted/{}.sst", path, sst)),
+                encoded.remaining_as_bytes().into(),
+            )
+            .await
+            .unwrap();
+
+        let report = db.verify_integrity().await.unwrap();
+        assert!(
+            report
+                .anomalies
+                .contains(&IntegrityAnomaly::OutOfOrderEntry {
+                    sst,
+                    key: Bytes::from_static(b"b"),
+                    seq: 2,
+                    previous_key: Bytes::from_static(b"c"),
+                    previous_seq: 3,
+                }),
+            "{:?}",
+            report.anomalies
+        );
+        db.close().await.unwrap();
+    }
+}
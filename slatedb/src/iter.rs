@@ -32,6 +32,11 @@ pub(crate) trait RowEntryIterator: Send + Sync {
     /// require the caller to explicitly initialize the iterator. This is in order
     /// to ensure that optimizations which eagerly initialize the iterator are not
     /// lost in a refactor and instead would throw errors.
+    ///
+    /// Iterators are not required to keep returning `None` once exhausted. In
+    /// particular, an iterator over a live memtable may return entries that
+    /// were inserted after it returned `None`. Wrap the iterator in a
+    /// [`crate::fused_iterator::FusedIterator`] if the end must be final.
     async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError>;
 
     /// Seek to the next (inclusive) key
@@ -44,6 +49,34 @@ pub(crate) trait RowEntryIterator: Send + Sync {
     /// to ensure that optimizations which eagerly initialize the iterator are not
     /// lost in a refactor and instead would throw errors.
     async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError>;
+
+    /// Skips `n` entries and returns the one after them, so `nth(0)` is the
+    /// same as [`Self::next`]. Returns `None` if the iterator is exhausted
+    /// first. Iterators that can skip entries without reading them should
+    /// override this.
+    #[allow(dead_code)]
+    async fn nth(&mut self, n: usize) -> Result<Option<RowEntry>, SlateDBError> {
+        for _ in 0..n {
+            if self.next().await?.is_none() {
+                return Ok(None);
+            }
+        }
+        self.next().await
+    }
+
+    /// Drains the iterator and returns its final entry, or `None` if it has
+    /// no entries left.
+    #[allow(dead_code)]
+    async fn last(mut self) -> Result<Option<RowEntry>, SlateDBError>
+    where
+        Self: Sized,
+    {
+        let mut last = None;
+        while let Some(entry) = self.next().await? {
+            last = Some(entry);
+        }
+        Ok(last)
+    }
 }
 
 /// Iterator trait that tracks bytes processed for progress reporting.
@@ -55,6 +88,9 @@ pub(crate) trait RowEntryIterator: Send + Sync {
 pub(crate) trait TrackedRowEntryIterator: RowEntryIterator {
     /// Returns the total bytes processed (key + value length) by this iterator.
     fn bytes_processed(&self) -> u64;
+
+    /// Returns the number of entries processed by this iterator.
+    fn entries_processed(&self) -> u64;
 }
 
 #[async_trait]
@@ -70,6 +106,10 @@ impl<'a> RowEntryIterator for Box<dyn RowEntryIterator + 'a> {
     async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
         self.as_mut().seek(next_key).await
     }
+
+    async fn nth(&mut self, n: usize) -> Result<Option<RowEntry>, SlateDBError> {
+        self.as_mut().nth(n).await
+    }
 }
 
 #[async_trait]
@@ -85,12 +125,20 @@ impl<'a> RowEntryIterator for Box<dyn TrackedRowEntryIterator + 'a> {
     async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
         self.as_mut().seek(next_key).await
     }
+
+    async fn nth(&mut self, n: usize) -> Result<Option<RowEntry>, SlateDBError> {
+        self.as_mut().nth(n).await
+    }
 }
 
 impl<'a> TrackedRowEntryIterator for Box<dyn TrackedRowEntryIterator + 'a> {
     fn bytes_processed(&self) -> u64 {
         self.as_ref().bytes_processed()
     }
+
+    fn entries_processed(&self) -> u64 {
+        self.as_ref().entries_processed()
+    }
 }
 
 pub(crate) struct EmptyIterator;
@@ -115,3 +163,47 @@ impl RowEntryIterator for EmptyIterator {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fused_iterator::FusedIterator;
+    use crate::test_utils::TestIterator;
+
+    fn abc() -> TestIterator {
+        TestIterator::new()
+            .with_entry(b"a", b"1", 1)
+            .with_entry(b"b", b"2", 2)
+            .with_entry(b"c", b"3", 3)
+    }
+
+    #[tokio::test]
+    async fn should_return_nth_entry_or_none_past_the_end() {
+        let mut iter = abc();
+        assert_eq!(iter.nth(1).await.unwrap().unwrap().key, &b"b"[..]);
+        assert_eq!(iter.nth(0).await.unwrap().unwrap().key, &b"c"[..]);
+        assert!(iter.nth(0).await.unwrap().is_none());
+
+        let mut iter = FusedIterator::new(abc());
+        assert_eq!(iter.nth(2).await.unwrap().unwrap().key, &b"c"[..]);
+        let mut iter = FusedIterator::new(abc());
+        assert!(iter.nth(3).await.unwrap().is_none());
+
+        let mut iter: Box<dyn RowEntryIterator> = Box::new(abc());
+        assert!(iter.nth(5).await.unwrap().is_none());
+        assert!(iter.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn should_return_last_entry_or_none_when_empty() {
+        assert_eq!(abc().last().await.unwrap().unwrap().key, &b"c"[..]);
+        let iter = FusedIterator::new(abc());
+        assert_eq!(iter.last().await.unwrap().unwrap().key, &b"c"[..]);
+
+        assert!(TestIterator::new().last().await.unwrap().is_none());
+        assert!(EmptyIterator::new().last().await.unwrap().is_none());
+        let mut iter = abc();
+        iter.nth(2).await.unwrap();
+        assert!(iter.last().await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,165 @@
+//! Order-preserving encodings of integer keys.
+//!
+//! Keys are compared as byte strings, so an integer key only scans in numeric
+//! order if its encoding sorts the same way as the integers. [`IntKey`]
+//! encodes integers as fixed-width big-endian bytes, flipping the sign bit of
+//! signed integers so that negative numbers sort before positive ones. Use
+//! [`IntKey::encode`] to write keys and [`crate::Db::scan_int_range`] to scan
+//! a range of them.
+//!
+//! ```
+//! use slatedb::key_encoding::IntKey;
+//!
+//! assert!((-1i64).encode() < 0i64.encode());
+//! assert!(i64::MIN.encode() < (-1i64).encode());
+//! assert_eq!(i64::decode(&42i64.encode()), Some(42));
+//! ```
+
+use std::ops::{Bound, RangeBounds};
+
+use bytes::Bytes;
+
+use crate::bytes_range::BytesRange;
+use crate::error::SlateDBError;
+
+/// An integer type with an order-preserving byte encoding.
+pub trait IntKey: Copy {
+    /// Encodes the integer as a key that sorts in the same order as the
+    /// integers do.
+    fn encode(self) -> Bytes;
+
+    /// Decodes a key written by [`Self::encode`], or returns `None` if the
+    /// key has the wrong length.
+    fn decode(key: &[u8]) -> Option<Self>;
+}
+
+impl IntKey for u64 {
+    fn encode(self) -> Bytes {
+        Bytes::copy_from_slice(&self.to_be_bytes())
+    }
+
+    fn decode(key: &[u8]) -> Option<Self> {
+        Some(u64::from_be_bytes(key.try_into().ok()?))
+    }
+}
+
+impl IntKey for i64 {
+    fn encode(self) -> Bytes {
+        // flipping the sign bit maps i64::MIN..=i64::MAX onto 0..=u64::MAX
+        ((self as u64) ^ (1 << 63)).encode()
+    }
+
+    fn decode(key: &[u8]) -> Option<Self> {
+        Some((u64::decode(key)? ^ (1 << 63)) as i64)
+    }
+}
+
+/// Returns the range of encoded keys that holds the encodings of the
+/// integers in `range`. Like [`BytesRange::try_from_scan_range`], an empty
+/// range scans nothing and a range whose start is after its end is rejected.
+pub(crate) fn encode_range<I, R>(range: R) -> Result<BytesRange, SlateDBError>
+where
+    I: IntKey,
+    R: RangeBounds<I>,
+{
+    let encode = |bound: Bound<&I>| bound.map(|i| i.encode());
+    BytesRange::try_from_scan_range(&(encode(range.start_bound()), encode(range.end_bound())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Db;
+    use object_store::memory::InMemory;
+    use std::sync::Arc;
+
+    #[test]
+    fn should_preserve_order_across_zero_and_at_the_boundaries() {
+        let signed = [i64::MIN, i64::MIN + 1, -256, -1, 0, 1, 255, 256, i64::MAX];
+        for pair in signed.windows(2) {
+            assert!(pair[0].encode() < pair[1].encode(), "{:?}", pair);
+        }
+        let unsigned = [0, 1, 255, 256, u64::MAX - 1, u64::MAX];
+        for pair in unsigned.windows(2) {
+            assert!(pair[0].encode() < pair[1].encode(), "{:?}", pair);
+        }
+        for i in signed {
+            assert_eq!(i64::decode(&i.encode()), Some(i));
+        }
+        for i in unsigned {
+            assert_eq!(u64::decode(&i.encode()), Some(i));
+        }
+        assert_eq!(i64::decode(b"short"), None);
+    }
+
+    async fn scan_ints<I, R>(db: &Db, range: R) -> Vec<I>
+    where
+        I: IntKey + Send + Sync,
+        R: RangeBounds<I> + Send,
+    {
+        let mut iter = db.scan_int_range(range).await.unwrap();
+        let mut ints = Vec::new();
+        while let Some(kv) = iter.next().await.unwrap() {
+            ints.push(I::decode(&kv.key).unwrap());
+        }
+        ints
+    }
+
+    #[tokio::test]
+    async fn should_scan_signed_ranges_across_zero() {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        let mut batch = crate::WriteBatch::new();
+        for i in [i64::MIN, i64::MIN + 1, -20, -3, -1, 0, 1, 2, 15, i64::MAX] {
+            batch.put(i.encode(), b"value");
+        }
+        db.write(batch).await.unwrap();
+
+        assert_eq!(scan_ints(&db, -3i64..2).await, vec![-3, -1, 0, 1]);
+        assert_eq!(scan_ints(&db, -20i64..=-1).await, vec![-20, -3, -1]);
+        assert_eq!(
+            scan_ints(&db, ..-1i64).await,
+            vec![i64::MIN, i64::MIN + 1, -20, -3]
+        );
+        assert_eq!(scan_ints(&db, 2i64..).await, vec![2, 15, i64::MAX]);
+        assert_eq!(
+            scan_ints(&db, i64::MIN..=i64::MIN + 1).await,
+            vec![i64::MIN, i64::MIN + 1]
+        );
+        assert_eq!(scan_ints(&db, i64::MAX..=i64::MAX).await, vec![i64::MAX]);
+    }
+
+    #[tokio::test]
+    async fn should_scan_unsigned_ranges_up_to_the_maximum() {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        let mut batch = crate::WriteBatch::new();
+        for i in [0u64, 10, 255, 256, 1 << 63, u64::MAX] {
+            batch.put(i.encode(), b"value");
+        }
+        db.write(batch).await.unwrap();
+
+        // a little-endian or variable-width encoding would sort 256 before 255
+        assert_eq!(scan_ints(&db, 10u64..=256).await, vec![10, 255, 256]);
+        assert_eq!(
+            scan_ints(&db, 256u64..=u64::MAX).await,
+            vec![256, 1 << 63, u64::MAX]
+        );
+        assert_eq!(scan_ints(&db, ..10u64).await, vec![0]);
+    }
+
+    #[tokio::test]
+    async fn should_reject_inverted_ranges_and_scan_nothing_for_empty_ones() {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        db.put(5i64.encode(), b"value").await.unwrap();
+
+        assert_eq!(scan_ints(&db, 5i64..5).await, Vec::<i64>::new());
+        #[allow(clippy::reversed_empty_ranges)]
+        let err = db.scan_int_range(9i64..3).await.err().unwrap();
+        assert_eq!(err.kind(), crate::ErrorKind::Invalid);
+    }
+}
@@ -35,6 +35,8 @@ pub use object_store;
 pub use batch::WriteBatch;
 pub use cached_object_store::stats as cached_object_store_stats;
 pub use checkpoint::{Checkpoint, CheckpointCreateResult};
+pub use column_family::{ColumnFamilyExtractor, ColumnFamilyIterator, MAX_COLUMN_FAMILY_NAME_LEN};
+pub use compaction_digest::{CompactionDigests, CompactionObserver, LiveDataDigest};
 #[cfg(feature = "compaction_filters")]
 pub use compaction_filter::{
     CompactionFilter, CompactionFilterDecision, CompactionFilterError, CompactionFilterSupplier,
@@ -43,39 +45,70 @@ pub use compaction_filter::{
 pub use compactor::CompactorBuilder;
 pub use compactor_state::VersionedCompactions;
 pub use config::{Settings, SstBlockSize};
-pub use db::{Db, DbBuilder, DbReaderBuilder, DbStatus, WriteHandle};
+pub use conflict_resolver::{ConflictResolver, ConflictWinner};
+pub use db::{Db, DbBuilder, DbReaderBuilder, DbStatus, PutOutcome, WriteHandle};
 pub use db_cache::stats as db_cache_stats;
 pub use db_cache_manager::CacheTarget;
+pub use db_diff::{Change, DbDiffIterator};
 pub use db_iter::{DbIterator, DbRecencyIterator};
-pub use db_reader::DbReader;
+pub use db_reader::{DbReader, Freshness};
 pub use db_snapshot::DbSnapshot;
 pub use db_transaction::DbTransaction;
+#[cfg(feature = "debug-tools")]
+pub use debug_tools::{MemtableSource, Provenance, RawMemtableEntries};
 pub use error::{CloseReason, Error, ErrorKind};
 pub use filter::BloomFilter;
 pub use filter_policy::{
     BloomFilterPolicy, Filter, FilterBuilder, FilterContext, FilterPolicy, FilterQuery,
 };
+pub use flush_policy::{DefaultFlushPolicy, FlushPolicy, FlushSignals};
 pub use format::sst::BlockTransformer;
+pub use gap_iterator::{GapIterator, GapScanItem, KeyGap};
 pub use garbage_collector::stats as garbage_collector_stats;
 pub use garbage_collector::GarbageCollectorBuilder;
 pub use instrumented_object_store::stats as instrumented_object_store_stats;
+pub use integrity::{IntegrityAnomaly, IntegrityReport};
 pub use iter::IterationOrder;
 pub use manifest::VersionedManifest;
-pub use merge_operator::{MergeOperator, MergeOperatorError};
+#[cfg(feature = "prometheus")]
+pub use memtable_metrics::{
+    MemtableMetricsExporter, MEMTABLE_DURABILITY_LAG_SEQS, MEMTABLE_ENTRIES,
+    MEMTABLE_IMMUTABLE_QUEUE_DEPTH, MEMTABLE_LIVE_ENTRIES, MEMTABLE_SIZE_BYTES,
+};
+pub use merge_join::{JoinType, JoinedEntry, MergeJoinIterator};
+pub use merge_operator::{IntegerAddMergeOperator, MergeOperator, MergeOperatorError};
+pub use merkle::MerkleNode;
 pub use ops::{DbCacheManagerOps, DbMetadataOps, DbReadOps, DbTransactionOps, DbWriteOps};
+pub use oracle::{MonotonicSequenceAllocator, SequenceAllocator};
+pub use pagination::{PageToken, ScanPage};
 pub use prefix_extractor::{PrefixExtractor, PrefixTarget};
+pub use projection::Projector;
 pub use rand::DbRand;
+pub use range_estimate::RangeEstimate;
+pub use rate_limiter::{
+    RateLimitDecision, RateLimitMode, RateLimiter, TenantRateLimit, TokenBucketRateLimiter,
+};
+pub use read_view::ReadView;
+pub use rewrite::RewriteSummary;
+pub use row_filter::RowFilter;
+pub use run_length_iterator::{RunLengthIterator, ValueRun};
 #[cfg(test)]
 pub use sst_builder::BlockFormat;
+pub use sst_export::SstExportStats;
 pub use sst_reader::{SstFile, SstReader};
 pub use sst_stats::{BlockStats, SstStats};
+pub use stats_snapshot::{CompactionStats, DbStats, ImmutableMemtableStats, MemtableStats};
 pub use tablestore::SstFileMetadata;
 pub use transaction_manager::IsolationLevel;
-pub use types::KeyValue;
+pub use types::{KeyValue, PutCondition};
 pub use types::{RowEntry, ValueDeletable};
+pub use union_iterator::{UnionIterator, UnionResolver};
+pub use virtual_source::{KeyValueIterator, SourcePriority};
 pub use wal_reader::{WalFile, WalFileIterator, WalFileMetadata, WalReader};
 
 pub mod admin;
+#[cfg(feature = "arrow")]
+pub mod arrow_export;
 pub mod cached_object_store;
 pub mod clock;
 #[cfg(feature = "bencher")]
@@ -84,6 +117,7 @@ pub mod compactor;
 pub mod config;
 pub mod db_cache;
 pub mod db_stats;
+pub mod key_encoding;
 pub mod manifest;
 pub mod prefix_extractor;
 pub mod seq_tracker;
@@ -101,6 +135,8 @@ mod bytes_generator;
 mod bytes_range;
 mod checkpoint;
 mod clone;
+mod column_family;
+mod compaction_digest;
 #[cfg(feature = "compaction_filters")]
 mod compaction_filter;
 #[cfg(feature = "compaction_filters")]
@@ -111,57 +147,92 @@ mod compactor_state;
 mod compactor_state_protocols;
 #[allow(dead_code)]
 mod comparable_range;
+mod conflict_resolver;
 mod db;
 mod db_cache_manager;
 mod db_common;
+mod db_diff;
 mod db_iter;
 mod db_reader;
 mod db_snapshot;
 mod db_state;
 mod db_status;
 mod db_transaction;
+#[cfg(feature = "debug-tools")]
+mod debug_tools;
 mod dispatcher;
 mod error;
+mod expiry_iterator;
 pub mod filter;
 mod filter_iterator;
 pub mod filter_policy;
 mod flatbuffer_types;
 mod flush;
+mod flush_policy;
 mod format;
+mod front_coding;
+mod fused_iterator;
+mod gap_iterator;
 mod garbage_collector;
 mod instrumented_object_store;
+mod integrity;
 mod iter;
 mod mem_table;
 mod memtable_flusher;
+#[cfg(feature = "prometheus")]
+mod memtable_metrics;
 mod merge_iterator;
+mod merge_join;
 mod merge_operator;
+mod merkle;
+mod multi_get;
 mod object_stores;
 mod ops;
 mod oracle;
+mod pagination;
 mod partitioned_keyspace;
 mod paths;
 mod peeking_iterator;
+mod projection;
 #[cfg(test)]
 mod proptest_util;
 mod rand;
+mod range_estimate;
+mod rate_limiter;
+mod read_ahead_iterator;
+mod read_cache;
+mod read_repair;
+mod read_view;
+#[cfg(feature = "bench-internal")]
+pub use mem_table::benches as mem_table_benches;
+#[cfg(feature = "bench-internal")]
+pub use read_ahead_iterator::benches as read_ahead_iterator_benches;
 mod reader;
 mod retention_iterator;
 mod retrying_object_store;
+mod rewrite;
+mod row_filter;
+mod run_length_iterator;
 mod segment_iterator;
+mod shutdown;
 mod single_flight;
 mod snapshot_manager;
 mod sorted_run_iterator;
 mod sst_builder;
+mod sst_export;
 mod sst_iter;
 mod sst_reader;
 mod sst_stats;
+mod stats_snapshot;
 mod store_provider;
 mod tablestore;
 #[cfg(test)]
 mod test_utils;
 mod transaction_manager;
 mod types;
+mod union_iterator;
 mod utils;
+mod virtual_source;
 
 mod wal;
 mod wal_buffer;
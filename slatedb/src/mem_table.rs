@@ -1,7 +1,7 @@
 use std::cell::Cell;
-use std::ops::{Bound, RangeBounds};
+use std::ops::{Bound, ControlFlow, RangeBounds};
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -12,9 +12,14 @@ use std::sync::atomic::AtomicI64;
 use std::sync::atomic::Ordering::SeqCst;
 
 use chrono::{DateTime, Utc};
+use log::debug;
 use parking_lot::Mutex;
 
+use crate::config::{MemtableType, OutOfOrderWritePolicy, DEFAULT_MEMTABLE_FILTER_BITS};
 use crate::error::SlateDBError;
+use crate::filter::MemtableFilter;
+use crate::filter_iterator::FilterIterator;
+use crate::front_coding::{FrontCodedIterator, FrontCodedLog};
 use crate::iter::{IterationOrder, RowEntryIterator};
 use crate::seq_tracker::{SequenceTracker, TrackedSeq};
 use crate::types::RowEntry;
@@ -88,13 +93,174 @@ impl<T: RangeBounds<Bytes>> From<T> for KVTableInternalKeyRange {
     }
 }
 
+/// The data structure holding a [`KVTable`]'s entries. It is chosen when the
+/// table is created and never changes; a table that needs a different store
+/// is rebuilt, see [`WritableKVTable::prepare_for`] and
+/// [`ImmutableMemtable::front_coded`].
+enum KVTableStore {
+    SkipMap(Arc<SkipMap<SequencedKey, RowEntry>>),
+    AppendOnly(Arc<AppendOnlyLog>),
+    /// A frozen table's entries, re-encoded by [`KVTable::to_front_coded`].
+    FrontCoded(Arc<FrontCodedLog>),
+}
+
+impl KVTableStore {
+    fn new(memtable_type: MemtableType) -> Self {
+        match memtable_type {
+            MemtableType::SkipMap => KVTableStore::SkipMap(Arc::new(SkipMap::new())),
+            MemtableType::AppendOnly { .. } => {
+                KVTableStore::AppendOnly(Arc::new(AppendOnlyLog::new()))
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            KVTableStore::SkipMap(map) => map.len(),
+            KVTableStore::AppendOnly(log) => log.len(),
+            KVTableStore::FrontCoded(log) => log.len(),
+        }
+    }
+}
+
+/// The number of slots in the first chunk of an [`AppendOnlyLog`]. Every chunk
+/// after it is twice as large as the one before.
+const APPEND_ONLY_FIRST_CHUNK: usize = 64;
+
+/// The number of chunks of an [`AppendOnlyLog`], which hold more entries than
+/// any memtable does.
+const APPEND_ONLY_CHUNKS: usize = 32;
+
+/// The entries of an append-only memtable in [`SequencedKey`] order. Keys are
+/// strictly increasing, so each user key has a single version.
+///
+/// Entries are stored in chunks that are allocated as the log grows and never
+/// move, and an entry is never written again once it's below the log's length.
+/// Readers load the length and read the entries below it without locking;
+/// only appends are serialized.
+struct AppendOnlyLog {
+    chunks: [OnceLock<Box<[OnceLock<RowEntry>]>>; APPEND_ONLY_CHUNKS],
+    /// The number of entries appended so far. Stored after the entry it counts
+    /// is written, so every entry below it can be read.
+    len: AtomicUsize,
+    appending: Mutex<()>,
+}
+
+impl AppendOnlyLog {
+    fn new() -> Self {
+        Self {
+            chunks: std::array::from_fn(|_| OnceLock::new()),
+            len: AtomicUsize::new(0),
+            appending: Mutex::new(()),
+        }
+    }
+
+    /// Returns the chunk holding the entry at `index` and its offset in it.
+    fn slot(index: usize) -> (usize, usize) {
+        let chunk = (index / APPEND_ONLY_FIRST_CHUNK + 1).ilog2() as usize;
+        (chunk, index - APPEND_ONLY_FIRST_CHUNK * ((1 << chunk) - 1))
+    }
+
+    fn len(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    /// Returns the entry at `index`, which must be below [`AppendOnlyLog::len`].
+    fn entry(&self, index: usize) -> &RowEntry {
+        let (chunk, offset) = Self::slot(index);
+        self.chunks[chunk]
+            .get()
+            .and_then(|slots| slots[offset].get())
+            .expect("entries below the log's length are written")
+    }
+
+    fn last(&self) -> Option<&RowEntry> {
+        self.len().checked_sub(1).map(|index| self.entry(index))
+    }
+
+    /// Appends `row` if its key sorts after the last entry's. Returns false,
+    /// leaving the log unchanged, if appending it would break the ordering.
+    fn try_append(&self, row: RowEntry) -> bool {
+        let _appending = self.appending.lock();
+        if self.last().is_some_and(|last| last.key >= row.key) {
+            return false;
+        }
+        let index = self.len.load(Ordering::Relaxed);
+        let (chunk, offset) = Self::slot(index);
+        let slots = self.chunks[chunk].get_or_init(|| {
+            (0..APPEND_ONLY_FIRST_CHUNK << chunk)
+                .map(|_| OnceLock::new())
+                .collect()
+        });
+        assert!(
+            slots[offset].set(row).is_ok(),
+            "slots at or above the log's length are empty"
+        );
+        self.len.store(index + 1, Ordering::Release);
+        true
+    }
+
+    /// Returns the index of the first entry below `len` for which `pred` is
+    /// false, given that it is true for the entries before it.
+    fn partition_point(&self, len: usize, pred: impl Fn(&RowEntry) -> bool) -> usize {
+        let (mut low, mut high) = (0, len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if pred(self.entry(mid)) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    /// Returns the range of indexes of the entries whose keys fall in `range`.
+    fn index_range<T: RangeBounds<Bytes>>(&self, range: &T) -> std::ops::Range<usize> {
+        let len = self.len();
+        let start = self.partition_point(len, |entry| match range.start_bound() {
+            Bound::Included(start) => entry.key < start,
+            Bound::Excluded(start) => entry.key <= start,
+            Bound::Unbounded => false,
+        });
+        let end = self.partition_point(len, |entry| match range.end_bound() {
+            Bound::Included(end) => entry.key <= end,
+            Bound::Excluded(end) => entry.key < end,
+            Bound::Unbounded => true,
+        });
+        start..end.max(start)
+    }
+
+    /// Calls `f` with the entries at `indexes` until it breaks.
+    fn visit(
+        &self,
+        indexes: std::ops::Range<usize>,
+        mut f: impl FnMut(&RowEntry) -> ControlFlow<()>,
+    ) {
+        for index in indexes {
+            if f(self.entry(index)).is_break() {
+                return;
+            }
+        }
+    }
+}
+
 pub(crate) struct KVTable {
-    map: Arc<SkipMap<SequencedKey, RowEntry>>,
+    store: KVTableStore,
+    /// the memtable type of the table's store. A table rebuilt as a skip map
+    /// after an out-of-order write is a skip map one, and a front-coded table
+    /// keeps the type of the table it was built from.
+    memtable_type: MemtableType,
+    /// an approximate set of the keys written to this table, including
+    /// deleted ones. See [`KVTable::might_contain`].
+    filter: MemtableFilter,
     durable: WatchableOnceCell<Result<(), SlateDBError>>,
     entries_size_in_bytes: AtomicUsize,
     /// this corresponds to the timestamp of the most recent
     /// modifying operation on this KVTable (insertion or deletion)
     last_tick: AtomicI64,
+    /// the timestamp of the oldest modifying operation on this KVTable
+    first_tick: AtomicI64,
     /// the sequence number of the most recent operation on this KVTable
     last_seq: AtomicU64,
     /// the sequence number of the oldest entry in this KVTable
@@ -114,8 +280,9 @@ pub(crate) struct KVTableMetadata {
     pub(crate) entries_size_in_bytes: usize,
     /// this corresponds to the timestamp of the most recent
     /// modifying operation on this KVTable (insertion or deletion)
-    #[allow(dead_code)]
     pub(crate) last_tick: i64,
+    /// the timestamp of the oldest modifying operation on this KVTable
+    pub(crate) first_tick: i64,
     /// the sequence number of the most recent operation on this KVTable
     pub(crate) last_seq: u64,
     /// the sequence number of the oldest entry in this KVTable
@@ -128,8 +295,12 @@ pub(crate) struct WritableKVTable {
 
 impl WritableKVTable {
     pub(crate) fn new() -> Self {
+        Self::new_with_type(MemtableType::default(), DEFAULT_MEMTABLE_FILTER_BITS)
+    }
+
+    pub(crate) fn new_with_type(memtable_type: MemtableType, filter_bits: usize) -> Self {
         Self {
-            table: Arc::new(KVTable::new()),
+            table: Arc::new(KVTable::new_with_type(memtable_type, filter_bits)),
         }
     }
 
@@ -137,10 +308,26 @@ impl WritableKVTable {
         &self.table
     }
 
+    /// Puts `row` into the table, which must be able to take it, see
+    /// [`WritableKVTable::prepare_for`].
     pub(crate) fn put(&self, row: RowEntry) {
         self.table.put(row);
     }
 
+    /// Makes sure the table can take `entries`, in order. An append-only table
+    /// that can't is replaced with a skip map holding the same entries; readers
+    /// that hold the previous table keep reading it. See [`KVTable::can_append`].
+    pub(crate) fn prepare_for(&mut self, entries: &[RowEntry]) {
+        if self.table.can_append(entries) {
+            return;
+        }
+        debug!(
+            "converting append-only memtable to skip map on out-of-order write [key={:?}]",
+            self.table.out_of_order_key(entries).map(|(key, _)| key)
+        );
+        self.table = Arc::new(self.table.to_skip_map());
+    }
+
     pub(crate) fn metadata(&self) -> KVTableMetadata {
         self.table.metadata()
     }
@@ -193,7 +380,51 @@ pub(crate) struct MemTableIteratorInner<T: RangeBounds<SequencedKey>> {
     /// seq-descending order, which is what the merge iterator needs for dedup.
     descending_stack: Vec<RowEntry>,
 }
-pub(crate) type MemTableIterator = MemTableIteratorInner<KVTableInternalKeyRange>;
+type SkipMapIterator = MemTableIteratorInner<KVTableInternalKeyRange>;
+
+/// Iterator over a range of an append-only memtable.
+pub(crate) struct AppendOnlyIterator {
+    log: Arc<AppendOnlyLog>,
+    ordering: IterationOrder,
+    /// Indexes of the entries that have not been returned yet.
+    remaining: std::ops::Range<usize>,
+}
+
+impl AppendOnlyIterator {
+    fn next_sync(&mut self) -> Option<RowEntry> {
+        let index = match self.ordering {
+            IterationOrder::Ascending => self.remaining.next(),
+            IterationOrder::Descending => self.remaining.next_back(),
+        }?;
+        Some(self.log.entry(index).clone())
+    }
+
+    fn seek_sync(&mut self, next_key: &[u8]) {
+        match self.ordering {
+            IterationOrder::Ascending => {
+                let skipped = self
+                    .log
+                    .partition_point(self.remaining.end, |entry| entry.key.as_ref() < next_key);
+                self.remaining.start = self.remaining.start.max(skipped);
+            }
+            IterationOrder::Descending => {
+                let kept = self
+                    .log
+                    .partition_point(self.remaining.end, |entry| entry.key.as_ref() <= next_key);
+                self.remaining.end = kept.max(self.remaining.start);
+            }
+        }
+    }
+}
+
+/// Iterator over a range of a [`KVTable`], matching the store it was opened on.
+/// The skip map variant is the common case, so it is kept inline.
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum MemTableIterator {
+    SkipMap(SkipMapIterator),
+    AppendOnly(AppendOnlyIterator),
+    FrontCoded(FrontCodedIterator),
+}
 
 #[async_trait]
 impl RowEntryIterator for MemTableIterator {
@@ -206,25 +437,54 @@ impl RowEntryIterator for MemTableIterator {
     }
 
     async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
-        loop {
-            let front = self.borrow_item().clone();
-            if front.is_some_and(|record| record.key < next_key) {
-                self.next_sync();
-            } else {
-                return Ok(());
-            }
+        match self {
+            MemTableIterator::SkipMap(iter) => iter.seek_sync(next_key),
+            MemTableIterator::AppendOnly(iter) => iter.seek_sync(next_key),
+            MemTableIterator::FrontCoded(iter) => iter.seek_sync(next_key),
         }
+        Ok(())
     }
 }
 
 impl MemTableIterator {
     pub(crate) fn next_sync(&mut self) -> Option<RowEntry> {
+        match self {
+            MemTableIterator::SkipMap(iter) => iter.next_sync(),
+            MemTableIterator::AppendOnly(iter) => iter.next_sync(),
+            MemTableIterator::FrontCoded(iter) => iter.next_sync(),
+        }
+    }
+}
+
+impl SkipMapIterator {
+    fn next_sync(&mut self) -> Option<RowEntry> {
         match self.borrow_ordering() {
             IterationOrder::Ascending => self.next_ascending(),
             IterationOrder::Descending => self.next_descending(),
         }
     }
 
+    fn seek_sync(&mut self, next_key: &[u8]) {
+        loop {
+            // In descending order the versions of the current key wait on the
+            // stack, ahead of `item`.
+            let front = self
+                .borrow_descending_stack()
+                .last()
+                .or(self.borrow_item().as_ref())
+                .map(|record| record.key.clone());
+            let skip = front.is_some_and(|key| match self.borrow_ordering() {
+                IterationOrder::Ascending => key < next_key,
+                IterationOrder::Descending => key > next_key,
+            });
+            if skip {
+                self.next_sync();
+            } else {
+                return;
+            }
+        }
+    }
+
     fn next_ascending(&mut self) -> Option<RowEntry> {
         let ans = self.borrow_item().clone();
         let next_entry = self.with_inner_mut(|inner| inner.next());
@@ -323,11 +583,25 @@ impl ImmutableMemtable {
         &self.sequence_tracker
     }
 
+    /// Returns a copy of this memtable with its table front-coded, see
+    /// [`KVTable::to_front_coded`]. The copy shares the upload notification,
+    /// so it can replace this memtable in the db state while it waits to be
+    /// flushed.
+    pub(crate) fn front_coded(&self) -> Self {
+        Self {
+            recent_flushed_wal_id: self.recent_flushed_wal_id,
+            table: Arc::new(self.table.to_front_coded()),
+            uploaded: self.uploaded.clone(),
+            sequence_tracker: self.sequence_tracker.clone(),
+        }
+    }
+
     /// Returns a new [`ImmutableMemtable`] that only contains entries with sequence
     /// number greater than the given `seq`. [`ImmutableMemtable::recent_flushed_wal_id`]
     /// remains the same.
     pub(crate) fn filter_after_seq(&self, seq: u64) -> Self {
-        let new_table = WritableKVTable::new();
+        let new_table =
+            WritableKVTable::new_with_type(self.table.memtable_type, self.table.filter.bits());
         let mut table_iter = self.table.iter();
         while let Some(entry) = table_iter.next_sync() {
             if entry.seq > seq {
@@ -344,13 +618,76 @@ impl ImmutableMemtable {
     }
 }
 
+/// A memtable snapshot read as the input of a flush or compaction.
+///
+/// The snapshot shares the memtable's [`KVTable`] rather than copying it, and
+/// covers the entries up to the table's last sequence number when it's taken.
+/// Reading it takes no lock that writers wait on, so a table that's still
+/// written to can be read while writes continue. The table is kept alive as
+/// long as the input or any iterator over it is, and is reclaimed once the
+/// memtable and every input taken from it have been dropped.
+pub(crate) struct MemtableCompactionInput {
+    table: Arc<KVTable>,
+    max_seq: Option<u64>,
+}
+
+impl MemtableCompactionInput {
+    pub(crate) fn new(table: Arc<KVTable>) -> Self {
+        let max_seq = table.last_seq();
+        Self { table, max_seq }
+    }
+
+    /// Iterates the entries in the snapshot, in key order.
+    pub(crate) fn iter(&self) -> MemtableCompactionInputIterator {
+        let inner = match self.max_seq {
+            Some(max_seq) => FilterIterator::new_with_max_seq(self.table.iter(), Some(max_seq)),
+            // the table was empty when the snapshot was taken
+            None => FilterIterator::new(self.table.iter(), Box::new(|_| false)),
+        };
+        MemtableCompactionInputIterator {
+            _table: Arc::clone(&self.table),
+            inner,
+        }
+    }
+}
+
+/// Iterator over a [`MemtableCompactionInput`]. Holds the input's table.
+pub(crate) struct MemtableCompactionInputIterator {
+    _table: Arc<KVTable>,
+    inner: FilterIterator<MemTableIterator>,
+}
+
+#[async_trait]
+impl RowEntryIterator for MemtableCompactionInputIterator {
+    async fn init(&mut self) -> Result<(), SlateDBError> {
+        self.inner.init().await
+    }
+
+    async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        self.inner.next().await
+    }
+
+    async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
+        self.inner.seek(next_key).await
+    }
+}
+
 impl KVTable {
     pub(crate) fn new() -> Self {
+        Self::new_with_type(MemtableType::default(), DEFAULT_MEMTABLE_FILTER_BITS)
+    }
+
+    /// Creates an empty table backed by `memtable_type`, whose key filter has
+    /// `filter_bits` bits. A filter of 0 bits is disabled.
+    pub(crate) fn new_with_type(memtable_type: MemtableType, filter_bits: usize) -> Self {
         Self {
-            map: Arc::new(SkipMap::new()),
+            store: KVTableStore::new(memtable_type),
+            memtable_type,
+            filter: MemtableFilter::new(filter_bits),
             entries_size_in_bytes: AtomicUsize::new(0),
             durable: WatchableOnceCell::new(),
             last_tick: AtomicI64::new(i64::MIN),
+            first_tick: AtomicI64::new(i64::MAX),
             last_seq: AtomicU64::new(0),
             first_seq: AtomicU64::new(u64::MAX),
             sequence_tracker: Mutex::new(SequenceTracker::new()),
@@ -438,22 +775,37 @@ impl KVTable {
     }
 
     pub(crate) fn metadata(&self) -> KVTableMetadata {
-        let entry_num = self.map.len();
+        let entry_num = self.store.len();
         let entries_size_in_bytes = self.entries_size_in_bytes.load(Ordering::Relaxed);
         let last_tick = self.last_tick.load(SeqCst);
+        let first_tick = self.first_tick.load(SeqCst);
         let last_seq = self.last_seq().unwrap_or(0);
         let first_seq = self.first_seq().unwrap_or(0);
         KVTableMetadata {
             entry_num,
             entries_size_in_bytes,
             last_tick,
+            first_tick,
             last_seq,
             first_seq,
         }
     }
 
     pub(crate) fn is_empty(&self) -> bool {
-        self.map.is_empty()
+        self.store.len() == 0
+    }
+
+    /// Counts the entries that are not tombstones. Walks the whole table.
+    #[cfg_attr(not(feature = "prometheus"), allow(dead_code))]
+    pub(crate) fn live_entry_num(&self) -> usize {
+        let mut iter = self.iter();
+        let mut live = 0;
+        while let Some(row) = iter.next_sync() {
+            if !row.value.is_tombstone() {
+                live += 1;
+            }
+        }
+        live
     }
 
     pub(crate) fn last_tick(&self) -> i64 {
@@ -491,9 +843,26 @@ impl KVTable {
         range: T,
         ordering: IterationOrder,
     ) -> MemTableIterator {
+        let map = match &self.store {
+            KVTableStore::SkipMap(map) => Arc::clone(map),
+            KVTableStore::AppendOnly(log) => {
+                return MemTableIterator::AppendOnly(AppendOnlyIterator {
+                    log: Arc::clone(log),
+                    ordering,
+                    remaining: log.index_range(&range),
+                });
+            }
+            KVTableStore::FrontCoded(log) => {
+                return MemTableIterator::FrontCoded(FrontCodedIterator::new(
+                    Arc::clone(log),
+                    &range,
+                    ordering,
+                ));
+            }
+        };
         let internal_range = KVTableInternalKeyRange::from(range);
         let mut iterator = MemTableIteratorInnerBuilder {
-            map: self.map.clone(),
+            map,
             inner_builder: |map| map.range(internal_range),
             ordering,
             item: None,
@@ -501,35 +870,142 @@ impl KVTable {
         }
         .build();
         iterator.next_sync();
-        iterator
+        MemTableIterator::SkipMap(iterator)
+    }
+
+    /// Returns every version of `key` held by the table, from newest to oldest,
+    /// without resolving tombstones or merge operands.
+    #[cfg_attr(not(feature = "debug-tools"), allow(dead_code))]
+    pub(crate) fn get_raw(&self, key: &[u8]) -> Vec<RowEntry> {
+        let key = Bytes::copy_from_slice(key);
+        let mut entries = Vec::new();
+        self.visit_range(key.clone()..=key, |entry| {
+            entries.push(entry.clone());
+            ControlFlow::Continue(())
+        });
+        entries
     }
 
-    pub(crate) fn put(&self, row: RowEntry) {
-        let internal_key = SequencedKey::new(row.key.clone(), row.seq);
-        let previous_size = Cell::new(None);
+    /// Calls `f` with each entry in `range`, in ascending key order with the
+    /// versions of a key from newest to oldest, until `f` breaks or the range is
+    /// exhausted.
+    ///
+    /// Unlike [`KVTable::range`], entries are lent to `f` instead of cloned, so
+    /// scans that only inspect entries transiently (e.g. to compute an aggregate)
+    /// avoid cloning every key and value. `f` must not write to this table.
+    pub(crate) fn visit_range<T: RangeBounds<Bytes>>(
+        &self,
+        range: T,
+        mut f: impl FnMut(&RowEntry) -> ControlFlow<()>,
+    ) {
+        let map = match &self.store {
+            KVTableStore::SkipMap(map) => map,
+            KVTableStore::AppendOnly(log) => return log.visit(log.index_range(&range), f),
+            KVTableStore::FrontCoded(log) => return log.visit(log.index_range(&range), f),
+        };
+        for entry in map.range(KVTableInternalKeyRange::from(range)) {
+            if f(entry.value()).is_break() {
+                return;
+            }
+        }
+    }
+
+    /// Sums the estimated sizes of the entries in `range`, counting every
+    /// version of a key, as [`KVTableMetadata::entries_size_in_bytes`] does for
+    /// the whole table. Walks the range without cloning its entries.
+    #[cfg_attr(not(feature = "debug-tools"), allow(dead_code))]
+    pub(crate) fn approx_range_bytes<T: RangeBounds<Bytes>>(&self, range: T) -> usize {
+        self.approx_range_entries_and_bytes(range).1
+    }
+
+    /// Like [`KVTable::approx_range_bytes`], but also returns the number of
+    /// entries in `range`.
+    pub(crate) fn approx_range_entries_and_bytes<T: RangeBounds<Bytes>>(
+        &self,
+        range: T,
+    ) -> (usize, usize) {
+        let mut entries = 0;
+        let mut bytes = 0;
+        self.visit_range(range, |entry| {
+            entries += 1;
+            bytes += entry.estimated_size();
+            ControlFlow::Continue(())
+        });
+        (entries, bytes)
+    }
+
+    /// Returns whether `entries` can be put into this table in order. A skip
+    /// map takes entries in any order; an append-only table only takes strictly
+    /// increasing keys that sort after its last one, and a front-coded table
+    /// takes none.
+    pub(crate) fn can_append(&self, entries: &[RowEntry]) -> bool {
+        match &self.store {
+            KVTableStore::SkipMap(_) => true,
+            KVTableStore::AppendOnly(_) => self.out_of_order_key(entries).is_none(),
+            KVTableStore::FrontCoded(_) => entries.is_empty(),
+        }
+    }
 
+    /// Returns the first key of `entries` that doesn't sort after the key
+    /// before it, or after the last key of this append-only table, along with
+    /// that previous key.
+    fn out_of_order_key(&self, entries: &[RowEntry]) -> Option<(Bytes, Bytes)> {
+        let KVTableStore::AppendOnly(log) = &self.store else {
+            return None;
+        };
+        let mut last_key = log.last().map(|entry| entry.key.clone());
+        for entry in entries {
+            match last_key {
+                Some(last_key) if entry.key <= last_key => {
+                    return Some((entry.key.clone(), last_key));
+                }
+                _ => last_key = Some(entry.key.clone()),
+            }
+        }
+        None
+    }
+
+    /// Checks that `entries`, sorted by key as produced by a write batch, can be
+    /// appended to this table. Only append-only tables configured with
+    /// [`OutOfOrderWritePolicy::Reject`] refuse writes; everything else accepts
+    /// keys in any order, see [`WritableKVTable::prepare_for`].
+    pub(crate) fn check_append_order(&self, entries: &[RowEntry]) -> Result<(), SlateDBError> {
+        if self.memtable_type
+            != (MemtableType::AppendOnly {
+                on_out_of_order: OutOfOrderWritePolicy::Reject,
+            })
+        {
+            return Ok(());
+        }
+        match self.out_of_order_key(entries) {
+            Some((key, last_key)) => Err(SlateDBError::OutOfOrderAppend { key, last_key }),
+            None => Ok(()),
+        }
+    }
+
+    /// Puts `row` into the table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table can't take `row`, see [`KVTable::can_append`].
+    pub(crate) fn put(&self, row: RowEntry) {
         // it is safe to use fetch_max here to update the last tick
         // because the monotonicity is enforced when generating the clock tick
         // (see [crate::utils::MonotonicClock::now])
         if let Some(create_ts) = row.create_ts {
             self.last_tick
                 .fetch_max(create_ts, atomic::Ordering::SeqCst);
+            self.first_tick
+                .fetch_min(create_ts, atomic::Ordering::SeqCst);
         }
         // update the last seq number if it is greater than the current last seq
         self.last_seq.fetch_max(row.seq, atomic::Ordering::SeqCst);
         // update the first seq number if it is smaller than the current first seq
         self.first_seq.fetch_min(row.seq, atomic::Ordering::SeqCst);
+        self.filter.add(&row.key);
 
         let row_size = row.estimated_size();
-        self.map.compare_insert(internal_key, row, |previous_row| {
-            // Optimistically calculate the size of the previous value.
-            // `compare_fn` might be called multiple times in case of concurrent
-            // writes to the same key, so we use `Cell` to avoid subtracting
-            // the size multiple times. The last call will set the correct size.
-            previous_size.set(Some(previous_row.estimated_size()));
-            true
-        });
-        if let Some(size) = previous_size.take() {
+        if let Some(size) = self.insert(row) {
             self.entries_size_in_bytes
                 .fetch_sub(size, Ordering::Relaxed);
             self.entries_size_in_bytes
@@ -540,6 +1016,107 @@ impl KVTable {
         }
     }
 
+    /// Returns a copy of the table with its entries' keys front-coded, to save
+    /// memory while a frozen table waits to be flushed. See [`FrontCodedLog`].
+    /// The copy is read-only.
+    ///
+    /// This walks the whole table, so it should not be called on the write
+    /// path.
+    pub(crate) fn to_front_coded(&self) -> KVTable {
+        let log = FrontCodedLog::build(|push| {
+            self.visit_range(.., |entry| {
+                push(entry);
+                ControlFlow::Continue(())
+            })
+        });
+        self.with_store(KVTableStore::FrontCoded(Arc::new(log)), self.memtable_type)
+    }
+
+    /// Returns a copy of the table backed by a skip map.
+    fn to_skip_map(&self) -> KVTable {
+        let map = SkipMap::new();
+        self.visit_range(.., |entry| {
+            map.insert(
+                SequencedKey::new(entry.key.clone(), entry.seq),
+                entry.clone(),
+            );
+            ControlFlow::Continue(())
+        });
+        self.with_store(KVTableStore::SkipMap(Arc::new(map)), MemtableType::SkipMap)
+    }
+
+    /// Returns a table holding `store`, which must hold this table's entries,
+    /// along with the rest of this table's state. The two tables share the
+    /// durable notification.
+    fn with_store(&self, store: KVTableStore, memtable_type: MemtableType) -> KVTable {
+        KVTable {
+            store,
+            memtable_type,
+            filter: self.filter.clone(),
+            durable: self.durable.clone(),
+            entries_size_in_bytes: AtomicUsize::new(
+                self.entries_size_in_bytes.load(Ordering::Relaxed),
+            ),
+            last_tick: AtomicI64::new(self.last_tick.load(SeqCst)),
+            first_tick: AtomicI64::new(self.first_tick.load(SeqCst)),
+            last_seq: AtomicU64::new(self.last_seq.load(SeqCst)),
+            first_seq: AtomicU64::new(self.first_seq.load(SeqCst)),
+            sequence_tracker: Mutex::new(self.sequence_tracker_snapshot()),
+            touched_segments: Mutex::new(self.touched_segments()),
+        }
+    }
+
+    pub(crate) fn is_front_coded(&self) -> bool {
+        matches!(self.store, KVTableStore::FrontCoded(_))
+    }
+
+    /// Returns the number of bytes holding the table's keys if it is
+    /// front-coded, see [`KVTable::to_front_coded`].
+    #[cfg_attr(not(feature = "bench-internal"), allow(dead_code))]
+    pub(crate) fn front_coded_key_bytes(&self) -> Option<usize> {
+        match &self.store {
+            KVTableStore::FrontCoded(log) => Some(log.key_bytes()),
+            _ => None,
+        }
+    }
+
+    /// Inserts `row` into the store and returns the size of the entry it replaced,
+    /// if any.
+    fn insert(&self, row: RowEntry) -> Option<usize> {
+        match &self.store {
+            KVTableStore::SkipMap(map) => Self::insert_into_map(map, row),
+            KVTableStore::AppendOnly(log) => {
+                assert!(
+                    log.try_append(row),
+                    "out-of-order write to an append-only memtable"
+                );
+                None
+            }
+            KVTableStore::FrontCoded(_) => unreachable!("front-coded memtables are read-only"),
+        }
+    }
+
+    fn insert_into_map(map: &SkipMap<SequencedKey, RowEntry>, row: RowEntry) -> Option<usize> {
+        let internal_key = SequencedKey::new(row.key.clone(), row.seq);
+        let previous_size = Cell::new(None);
+        map.compare_insert(internal_key, row, |previous_row| {
+            // Optimistically calculate the size of the previous value.
+            // `compare_fn` might be called multiple times in case of concurrent
+            // writes to the same key, so we use `Cell` to avoid subtracting
+            // the size multiple times. The last call will set the correct size.
+            previous_size.set(Some(previous_row.estimated_size()));
+            true
+        });
+        previous_size.take()
+    }
+
+    /// Returns false if no entry for `key` was ever written to this table, so
+    /// a point lookup can skip it. May return true for keys that were never
+    /// written. A key whose only entry is a tombstone returns true.
+    pub(crate) fn might_contain(&self, key: &[u8]) -> bool {
+        self.filter.might_contain(key)
+    }
+
     pub(crate) fn durable_watcher(&self) -> WatchableOnceCellReader<Result<(), SlateDBError>> {
         self.durable.reader()
     }
@@ -558,6 +1135,142 @@ impl KVTable {
     }
 }
 
+#[cfg(feature = "bench-internal")]
+pub mod benches {
+    use std::ops::ControlFlow;
+
+    use bytes::Bytes;
+
+    use super::KVTable;
+    use crate::config::{MemtableType, DEFAULT_MEMTABLE_FILTER_BITS};
+    use crate::types::{RowEntry, ValueDeletable};
+
+    pub struct MemtableInsertBenchConfig {
+        pub num_entries: usize,
+        pub value_size: usize,
+        pub memtable_type: MemtableType,
+    }
+
+    /// Inserts `num_entries` rows with strictly increasing keys into a fresh
+    /// memtable of the configured type.
+    pub fn memtable_insert_bench<F>(config: MemtableInsertBenchConfig, mut run_bench: F)
+    where
+        F: FnMut(&mut dyn FnMut()),
+    {
+        let value = Bytes::from(vec![0u8; config.value_size]);
+        let keys: Vec<Bytes> = (0..config.num_entries as u64)
+            .map(|i| Bytes::copy_from_slice(&i.to_be_bytes()))
+            .collect();
+
+        run_bench(&mut || {
+            let table = KVTable::new_with_type(config.memtable_type, DEFAULT_MEMTABLE_FILTER_BITS);
+            for (seq, key) in keys.iter().enumerate() {
+                table.put(RowEntry::new(
+                    key.clone(),
+                    ValueDeletable::Value(value.clone()),
+                    seq as u64,
+                    None,
+                    None,
+                ));
+            }
+            std::hint::black_box(table);
+        });
+    }
+
+    pub struct MemtableScanBenchConfig {
+        pub num_entries: usize,
+        pub value_size: usize,
+        pub memtable_type: MemtableType,
+        /// Whether to lend entries with [`KVTable::visit_range`] instead of
+        /// cloning them out of a [`KVTable::iter`] iterator.
+        pub borrowed: bool,
+    }
+
+    /// Sums the value sizes of a memtable holding `num_entries` rows, which is
+    /// an aggregate that doesn't need to retain any entry.
+    pub fn memtable_scan_bench<F>(config: MemtableScanBenchConfig, mut run_bench: F)
+    where
+        F: FnMut(&mut dyn FnMut()),
+    {
+        let value = Bytes::from(vec![0u8; config.value_size]);
+        let table = KVTable::new_with_type(config.memtable_type, DEFAULT_MEMTABLE_FILTER_BITS);
+        for seq in 0..config.num_entries as u64 {
+            table.put(RowEntry::new(
+                Bytes::copy_from_slice(&seq.to_be_bytes()),
+                ValueDeletable::Value(value.clone()),
+                seq,
+                None,
+                None,
+            ));
+        }
+
+        run_bench(&mut || {
+            let mut total = 0;
+            if config.borrowed {
+                table.visit_range(.., |entry| {
+                    total += entry.value.len();
+                    ControlFlow::Continue(())
+                });
+            } else {
+                let mut iter = table.iter();
+                while let Some(entry) = iter.next_sync() {
+                    total += entry.value.len();
+                }
+            }
+            std::hint::black_box(total);
+        });
+    }
+
+    pub struct MemtableFrontCodingBenchConfig {
+        pub num_entries: usize,
+        /// The length of the prefix shared by every key.
+        pub prefix_len: usize,
+        /// Whether to front-code the table with [`KVTable::to_front_coded`] before
+        /// scanning it.
+        pub front_coded: bool,
+    }
+
+    /// Scans a memtable holding `num_entries` rows whose keys share a long
+    /// prefix, and returns the number of bytes holding the table's keys.
+    pub fn memtable_front_coding_bench<F>(
+        config: MemtableFrontCodingBenchConfig,
+        mut run_bench: F,
+    ) -> usize
+    where
+        F: FnMut(&mut dyn FnMut()),
+    {
+        let prefix = "p".repeat(config.prefix_len);
+        let table = KVTable::new_with_type(MemtableType::SkipMap, DEFAULT_MEMTABLE_FILTER_BITS);
+        let mut raw_key_bytes = 0;
+        for seq in 0..config.num_entries as u64 {
+            let key = Bytes::from(format!("{prefix}{seq:016}"));
+            raw_key_bytes += key.len();
+            table.put(RowEntry::new(
+                key,
+                ValueDeletable::Value(Bytes::from_static(b"value")),
+                seq,
+                None,
+                None,
+            ));
+        }
+        let table = if config.front_coded {
+            table.to_front_coded()
+        } else {
+            table
+        };
+
+        run_bench(&mut || {
+            let mut total = 0;
+            let mut iter = table.iter();
+            while let Some(entry) = iter.next_sync() {
+                total += entry.key.len();
+            }
+            std::hint::black_box(total);
+        });
+        table.front_coded_key_bytes().unwrap_or(raw_key_bytes)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::VecDeque;
@@ -571,6 +1284,40 @@ mod tests {
     use rstest::rstest;
     use tokio::runtime::Runtime;
 
+    #[rstest]
+    #[case::sized(4096)]
+    #[case::saturated(64)]
+    fn test_key_filter_has_no_false_negatives(#[case] filter_bits: usize) {
+        let table = KVTable::new_with_type(MemtableType::SkipMap, filter_bits);
+        for i in 0..200u32 {
+            let key = format!("key{:03}", i);
+            table.put(RowEntry::new_value(key.as_bytes(), b"value", i as u64));
+        }
+        // deletes can't remove keys from the filter
+        for i in 0..100u32 {
+            let key = format!("key{:03}", i);
+            table.put(RowEntry::new_tombstone(key.as_bytes(), 200 + i as u64));
+        }
+
+        for i in 0..200u32 {
+            assert!(table.might_contain(format!("key{:03}", i).as_bytes()));
+        }
+        let false_positives = (0..1000u32)
+            .filter(|i| table.might_contain(format!("absent{:04}", i).as_bytes()))
+            .count();
+        if filter_bits >= 4096 {
+            assert!(false_positives < 100, "{} false positives", false_positives);
+        }
+    }
+
+    #[test]
+    fn test_disabled_key_filter_might_contain_every_key() {
+        let table = KVTable::new_with_type(MemtableType::SkipMap, 0);
+        table.put(RowEntry::new_value(b"key", b"value", 1));
+        assert!(table.might_contain(b"key"));
+        assert!(table.might_contain(b"absent"));
+    }
+
     #[tokio::test]
     async fn test_memtable_iter() {
         let table = WritableKVTable::new();
@@ -596,6 +1343,34 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_compaction_input_reads_snapshot_and_releases_table_when_dropped() {
+        let table = WritableKVTable::new();
+        table.put(RowEntry::new_value(b"abc222", b"value2", 1));
+        table.put(RowEntry::new_value(b"abc111", b"value1", 2));
+        let input = MemtableCompactionInput::new(table.table().clone());
+        let weak = Arc::downgrade(table.table());
+
+        // writes after the snapshot is taken aren't read from it
+        table.put(RowEntry::new_value(b"abc333", b"value3", 3));
+        let imm = ImmutableMemtable::new(table, 0);
+        let mut iter = input.iter();
+        drop(imm);
+        drop(input);
+        assert!(weak.upgrade().is_some());
+
+        assert_iterator(
+            &mut iter,
+            vec![
+                RowEntry::new_value(b"abc111", b"value1", 2),
+                RowEntry::new_value(b"abc222", b"value2", 1),
+            ],
+        )
+        .await;
+        drop(iter);
+        assert!(weak.upgrade().is_none());
+    }
+
     #[tokio::test]
     async fn test_memtable_iter_entry_attrs() {
         let table = WritableKVTable::new();
@@ -825,25 +1600,38 @@ mod tests {
         }
     }
 
-    #[test]
-    fn should_iterate_arbitrary_range() {
+    #[rstest]
+    #[case::skip_map(MemtableType::SkipMap, false)]
+    #[case::append_only(MemtableType::AppendOnly {
+        on_out_of_order: OutOfOrderWritePolicy::Reject,
+    }, false)]
+    #[case::front_coded(MemtableType::SkipMap, true)]
+    fn should_iterate_arbitrary_range(
+        #[case] memtable_type: MemtableType,
+        #[case] front_coded: bool,
+    ) {
         let mut runner = proptest_util::runner::new(file!(), None);
         let runtime = Runtime::new().unwrap();
         let sample_table = sample::table(runner.rng(), 500, 10);
 
-        let kv_table = WritableKVTable::new();
+        let kv_table = WritableKVTable::new_with_type(memtable_type, DEFAULT_MEMTABLE_FILTER_BITS);
         let mut seq = 1;
         for (key, value) in &sample_table {
             let row_entry = RowEntry::new_value(key, value, seq);
             kv_table.put(row_entry);
             seq += 1;
         }
+        let table = if front_coded {
+            Arc::new(kv_table.table().to_front_coded())
+        } else {
+            Arc::clone(kv_table.table())
+        };
 
         runner
             .run(
                 &(arbitrary::nonempty_range(10), arbitrary::iteration_order()),
                 |(range, ordering)| {
-                    let mut kv_iter = kv_table.table.range(range.clone(), ordering);
+                    let mut kv_iter = table.range(range.clone(), ordering);
 
                     runtime.block_on(test_utils::assert_ranged_kv_scan(
                         &sample_table,
@@ -975,4 +1763,352 @@ mod tests {
         )
         .await;
     }
+
+    const APPEND_ONLY: MemtableType = MemtableType::AppendOnly {
+        on_out_of_order: OutOfOrderWritePolicy::Fallback,
+    };
+
+    fn collect(mut iter: MemTableIterator) -> Vec<RowEntry> {
+        std::iter::from_fn(|| iter.next_sync()).collect()
+    }
+
+    fn is_append_only(table: &KVTable) -> bool {
+        matches!(&table.store, KVTableStore::AppendOnly(_))
+    }
+
+    /// Puts `entries` the way the write path does, replacing an append-only
+    /// table that can't take them with a skip map first.
+    fn put_all(table: &mut WritableKVTable, entries: &[RowEntry]) {
+        table.prepare_for(entries);
+        for entry in entries {
+            table.put(entry.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_append_only_memtable_matches_skip_map() {
+        let skip_map = WritableKVTable::new();
+        let append_only = WritableKVTable::new_with_type(APPEND_ONLY, DEFAULT_MEMTABLE_FILTER_BITS);
+        for table in [&skip_map, &append_only] {
+            table.put(RowEntry::new_value(b"key01", b"value1", 1));
+            table.put(RowEntry::new_tombstone(b"key02", 2));
+            table.put(RowEntry::new_value(b"key03", b"value3", 3));
+            table.put(RowEntry::new_value(b"key05", b"value5", 4));
+        }
+        assert!(is_append_only(append_only.table()));
+        assert_eq!(append_only.metadata().entry_num, 4);
+        assert_eq!(
+            append_only.metadata().entries_size_in_bytes,
+            skip_map.metadata().entries_size_in_bytes
+        );
+
+        let ranges = [
+            BytesRange::from(..),
+            BytesRange::from(Bytes::from_static(b"key02")..),
+            BytesRange::from(Bytes::from_static(b"key02")..Bytes::from_static(b"key05")),
+            BytesRange::from(..=Bytes::from_static(b"key04")),
+            BytesRange::from(Bytes::from_static(b"key06")..),
+        ];
+        for range in ranges {
+            for ordering in [IterationOrder::Ascending, IterationOrder::Descending] {
+                assert_eq!(
+                    collect(append_only.table().range(range.clone(), ordering)),
+                    collect(skip_map.table().range(range.clone(), ordering)),
+                    "range {range:?} {ordering:?}"
+                );
+            }
+        }
+
+        let mut iter = append_only.table().iter();
+        iter.seek(b"key04").await.unwrap();
+        assert_iterator(&mut iter, vec![RowEntry::new_value(b"key05", b"value5", 4)]).await;
+    }
+
+    #[rstest]
+    #[case::skip_map(MemtableType::SkipMap)]
+    #[case::append_only(APPEND_ONLY)]
+    #[tokio::test]
+    async fn test_descending_seek_skips_keys_after_seek_key(#[case] memtable_type: MemtableType) {
+        let mut table = WritableKVTable::new_with_type(memtable_type, DEFAULT_MEMTABLE_FILTER_BITS);
+        put_all(
+            &mut table,
+            &[
+                RowEntry::new_value(b"key01", b"value1", 1),
+                RowEntry::new_value(b"key03", b"value3", 2),
+                RowEntry::new_value(b"key05", b"value5", 3),
+            ],
+        );
+        let mut iter = table
+            .table()
+            .range(BytesRange::from(..), IterationOrder::Descending);
+
+        iter.seek(b"key04").await.unwrap();
+        assert_eq!(
+            iter.next_sync(),
+            Some(RowEntry::new_value(b"key03", b"value3", 2))
+        );
+        // seeking backwards doesn't rewind
+        iter.seek(b"key05").await.unwrap();
+        assert_eq!(
+            iter.next_sync(),
+            Some(RowEntry::new_value(b"key01", b"value1", 1))
+        );
+        iter.seek(b"key00").await.unwrap();
+        assert_eq!(iter.next_sync(), None);
+    }
+
+    #[rstest]
+    #[case::skip_map(MemtableType::SkipMap)]
+    #[case::append_only(APPEND_ONLY)]
+    fn test_get_raw_returns_unresolved_versions(#[case] memtable_type: MemtableType) {
+        let mut table = WritableKVTable::new_with_type(memtable_type, DEFAULT_MEMTABLE_FILTER_BITS);
+        put_all(
+            &mut table,
+            &[
+                RowEntry::new_value(b"j", b"value", 1),
+                RowEntry::new_value(b"k", b"value", 2),
+                RowEntry::new_merge(b"k", b"operand", 3),
+                RowEntry::new_tombstone(b"k", 4),
+                RowEntry::new_value(b"l", b"value", 5),
+            ],
+        );
+        let table = table.table();
+
+        assert_eq!(
+            table.get_raw(b"k"),
+            vec![
+                RowEntry::new_tombstone(b"k", 4),
+                RowEntry::new_merge(b"k", b"operand", 3),
+                RowEntry::new_value(b"k", b"value", 2),
+            ]
+        );
+        assert!(table.get_raw(b"kk").is_empty());
+    }
+
+    #[rstest]
+    #[case::skip_map(MemtableType::SkipMap)]
+    #[case::append_only(APPEND_ONLY)]
+    fn test_approx_range_bytes_sums_entries_in_range(#[case] memtable_type: MemtableType) {
+        let mut table = WritableKVTable::new_with_type(memtable_type, DEFAULT_MEMTABLE_FILTER_BITS);
+        let entries = vec![
+            RowEntry::new_value(b"a", b"value", 1),
+            RowEntry::new_value(b"b", b"longer value", 2),
+            RowEntry::new_value(b"b", b"v", 3),
+            RowEntry::new_tombstone(b"c", 4),
+            RowEntry::new_merge(b"d", b"operand", 5),
+        ];
+        put_all(&mut table, &entries);
+        let sum_of = |keys: &[&[u8]]| -> usize {
+            entries
+                .iter()
+                .filter(|entry| keys.contains(&entry.key.as_ref()))
+                .map(RowEntry::estimated_size)
+                .sum()
+        };
+
+        assert_eq!(
+            table.table().approx_range_bytes(..),
+            table.metadata().entries_size_in_bytes
+        );
+        assert_eq!(
+            table
+                .table()
+                .approx_range_bytes(Bytes::from_static(b"b")..Bytes::from_static(b"d")),
+            sum_of(&[b"b", b"c"])
+        );
+        assert_eq!(
+            table.table().approx_range_bytes(Bytes::from_static(b"d")..),
+            sum_of(&[b"d"])
+        );
+        assert_eq!(
+            table.table().approx_range_bytes(Bytes::from_static(b"e")..),
+            0
+        );
+        assert_eq!(
+            table
+                .table()
+                .approx_range_entries_and_bytes(Bytes::from_static(b"b")..Bytes::from_static(b"d")),
+            (3, sum_of(&[b"b", b"c"]))
+        );
+    }
+
+    #[rstest]
+    #[case::skip_map(MemtableType::SkipMap)]
+    #[case::append_only(APPEND_ONLY)]
+    fn test_visit_range_lends_same_entries_as_iterator(#[case] memtable_type: MemtableType) {
+        let table = WritableKVTable::new_with_type(memtable_type, DEFAULT_MEMTABLE_FILTER_BITS);
+        // enough entries to fill several chunks of the append-only log
+        for seq in 0..(APPEND_ONLY_FIRST_CHUNK as u64 * 7 + 10) {
+            let key = format!("key{seq:04}");
+            table.put(RowEntry::new_value(key.as_bytes(), b"value", seq));
+        }
+        assert_eq!(is_append_only(table.table()), memtable_type == APPEND_ONLY);
+
+        let ranges = [
+            BytesRange::from(..),
+            BytesRange::from(Bytes::from_static(b"key0100")..Bytes::from_static(b"key0400")),
+            BytesRange::from(Bytes::from_static(b"key9999")..),
+        ];
+        for range in ranges {
+            let mut visited = Vec::new();
+            table.table().visit_range(range.clone(), |entry| {
+                visited.push(entry.clone());
+                ControlFlow::Continue(())
+            });
+            assert_eq!(
+                visited,
+                collect(table.table().range_ascending(range.clone())),
+                "range {range:?}"
+            );
+        }
+
+        let mut visited = Vec::new();
+        table.table().visit_range(.., |entry| {
+            visited.push(entry.key.clone());
+            if visited.len() == 3 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(visited, vec!["key0000", "key0001", "key0002"]);
+    }
+
+    #[tokio::test]
+    async fn test_append_only_memtable_falls_back_to_skip_map_on_out_of_order_write() {
+        let mut table = WritableKVTable::new_with_type(APPEND_ONLY, DEFAULT_MEMTABLE_FILTER_BITS);
+        put_all(
+            &mut table,
+            &[
+                RowEntry::new_value(b"key1", b"value1", 1),
+                RowEntry::new_value(b"key3", b"value3", 2),
+            ],
+        );
+        assert!(is_append_only(table.table()));
+        // readers that hold the table from before the fallback keep reading it
+        let before_fallback = Arc::clone(table.table());
+
+        put_all(
+            &mut table,
+            &[
+                RowEntry::new_value(b"key2", b"value2", 3),
+                RowEntry::new_value(b"key3", b"value3b", 4),
+            ],
+        );
+        assert!(!is_append_only(table.table()));
+        assert_eq!(table.metadata().entry_num, 4);
+        assert_eq!(table.table().first_seq(), Some(1));
+        assert!(table.table().might_contain(b"key1"));
+
+        assert_eq!(
+            collect(before_fallback.iter()),
+            vec![
+                RowEntry::new_value(b"key1", b"value1", 1),
+                RowEntry::new_value(b"key3", b"value3", 2),
+            ]
+        );
+        assert_eq!(
+            collect(table.table().iter()),
+            vec![
+                RowEntry::new_value(b"key1", b"value1", 1),
+                RowEntry::new_value(b"key2", b"value2", 3),
+                RowEntry::new_value(b"key3", b"value3b", 4),
+                RowEntry::new_value(b"key3", b"value3", 2),
+            ]
+        );
+
+        // both tables are notified once the writes are durable
+        let mut watcher = before_fallback.durable_watcher();
+        table.table().notify_durable(Ok(()));
+        watcher.await_value().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_front_coded_memtable_matches_skip_map() {
+        let skip_map = WritableKVTable::new();
+        let front_coded = WritableKVTable::new();
+        for table in [&skip_map, &front_coded] {
+            let mut seq = 0;
+            for i in 0..40u32 {
+                // several versions of some keys, and a restart point every
+                // 16 entries that falls inside a key's versions
+                for version in 0..=(i % 3) {
+                    seq += 1;
+                    let key = format!("tenant/1/user/{i:04}");
+                    if version == 1 {
+                        table.put(RowEntry::new_tombstone(key.as_bytes(), seq));
+                    } else {
+                        table.put(RowEntry::new_value(key.as_bytes(), b"value", seq));
+                    }
+                }
+            }
+        }
+        let front_coded = front_coded.table().to_front_coded();
+        assert!(front_coded.front_coded_key_bytes().is_some());
+        assert_eq!(
+            front_coded.metadata().entry_num,
+            skip_map.metadata().entry_num
+        );
+        assert_eq!(
+            front_coded.metadata().entries_size_in_bytes,
+            skip_map.metadata().entries_size_in_bytes
+        );
+
+        let key = |i: u32| Bytes::from(format!("tenant/1/user/{i:04}"));
+        let ranges = [
+            BytesRange::from(..),
+            BytesRange::from(key(5)..),
+            BytesRange::from(key(5)..key(17)),
+            BytesRange::from(..=key(31)),
+            BytesRange::from(key(50)..),
+        ];
+        for range in ranges {
+            for ordering in [IterationOrder::Ascending, IterationOrder::Descending] {
+                assert_eq!(
+                    collect(front_coded.range(range.clone(), ordering)),
+                    collect(skip_map.table().range(range.clone(), ordering)),
+                    "range {range:?} {ordering:?}"
+                );
+            }
+        }
+        assert_eq!(
+            front_coded.get_raw(&key(14)),
+            skip_map.table().get_raw(&key(14))
+        );
+
+        let mut iter = front_coded.iter();
+        iter.seek(&key(38)).await.unwrap();
+        assert_eq!(
+            collect(iter),
+            collect(skip_map.table().range_ascending(key(38)..))
+        );
+    }
+
+    #[test]
+    fn test_check_append_order() {
+        let rejecting = KVTable::new_with_type(
+            MemtableType::AppendOnly {
+                on_out_of_order: OutOfOrderWritePolicy::Reject,
+            },
+            DEFAULT_MEMTABLE_FILTER_BITS,
+        );
+        let falling_back = KVTable::new_with_type(APPEND_ONLY, DEFAULT_MEMTABLE_FILTER_BITS);
+        for table in [&rejecting, &falling_back] {
+            table.put(RowEntry::new_value(b"key2", b"value2", 1));
+        }
+        let batch = vec![
+            RowEntry::new_value(b"key1", b"value1", 2),
+            RowEntry::new_value(b"key3", b"value3", 2),
+        ];
+
+        match rejecting.check_append_order(&batch).unwrap_err() {
+            SlateDBError::OutOfOrderAppend { key, last_key } => {
+                assert_eq!(key, Bytes::from_static(b"key1"));
+                assert_eq!(last_key, Bytes::from_static(b"key2"));
+            }
+            other => panic!("expected OutOfOrderAppend, got {other:?}"),
+        }
+        rejecting.check_append_order(&batch[1..]).unwrap();
+        falling_back.check_append_order(&batch).unwrap();
+    }
 }
@@ -58,6 +58,7 @@ enum ManifestWriterCommand {
     CreateCheckpoint {
         through_seq: Option<u64>,
         options: CheckpointOptions,
+        unique_name: bool,
         sender: oneshot::Sender<Result<CheckpointCreateResult, SlateDBError>>,
     },
     /// Periodic manifest poll to pick up remote changes (e.g. compaction).
@@ -145,17 +146,21 @@ impl ManifestWriter {
 
     /// Sends a checkpoint request to the manifest_writer. The manifest_writer will write
     /// the checkpoint once all sequences up to and including `through_seq` are
-    /// durable (or immediately if `None`) and respond via `sender`.
+    /// durable (or immediately if `None`) and respond via `sender`. If
+    /// `unique_name` is set, the checkpoint is rejected when the manifest it
+    /// would be written to already has a checkpoint with its name.
     pub(crate) fn send_checkpoint(
         &self,
         through_seq: Option<u64>,
         options: CheckpointOptions,
+        unique_name: bool,
         sender: oneshot::Sender<Result<CheckpointCreateResult, SlateDBError>>,
     ) -> Result<(), SlateDBError> {
         self.commands_tx
             .send(ManifestWriterCommand::CreateCheckpoint {
                 through_seq,
                 options,
+                unique_name,
                 sender,
             })
     }
@@ -226,12 +231,15 @@ impl MessageHandler<ManifestWriterCommand> for ManifestWriterHandler {
             ManifestWriterCommand::CreateCheckpoint {
                 through_seq,
                 options,
+                unique_name,
                 sender,
             } => {
-                self.handle_create_checkpoint(through_seq, options, sender)
+                self.handle_create_checkpoint(through_seq, options, unique_name, sender)
                     .await?;
             }
             ManifestWriterCommand::PollManifest { done } => {
+                // lets a flush policy freeze a memtable that no longer receives writes
+                self.db.maybe_freeze_current_memtable()?;
                 self.refresh_manifest_progress(done).await?;
             }
             ManifestWriterCommand::DurableSeqAdvanced => {}
@@ -330,17 +338,26 @@ impl ManifestWriterHandler {
         &mut self,
         through_seq: Option<u64>,
         options: CheckpointOptions,
+        unique_name: bool,
         sender: oneshot::Sender<Result<CheckpointCreateResult, SlateDBError>>,
     ) -> Result<(), SlateDBError> {
         if self.is_durable(through_seq) {
-            let result = self.write_checkpoint_safely(&options).await;
-            let _ = sender.send(result.clone());
-            return result.map(|_| ());
+            return match self.write_checkpoint_safely(&options, unique_name).await {
+                Ok(result) => {
+                    let _ = sender.send(result);
+                    Ok(())
+                }
+                Err(err) => {
+                    let _ = sender.send(Err(err.clone()));
+                    Err(err)
+                }
+            };
         }
 
         self.pending_checkpoints.push(PendingCheckpoint {
             through_seq,
             options,
+            unique_name,
             sender,
         });
         Ok(())
@@ -435,7 +452,7 @@ impl ManifestWriterHandler {
             .write_manifest_update_safely(
                 &attached_checkpoints
                     .iter()
-                    .map(|c| &c.options)
+                    .map(|c| (&c.options, c.unique_name))
                     .collect::<Vec<_>>(),
             )
             .await
@@ -531,10 +548,10 @@ impl ManifestWriterHandler {
 
     async fn write_manifest_update_safely(
         &mut self,
-        checkpoint_options: &[&CheckpointOptions],
-    ) -> Result<Vec<CheckpointCreateResult>, SlateDBError> {
+        checkpoints: &[(&CheckpointOptions, bool)],
+    ) -> Result<Vec<Result<CheckpointCreateResult, SlateDBError>>, SlateDBError> {
         loop {
-            let result = self.write_manifest_update(checkpoint_options).await;
+            let result = self.write_manifest_update(checkpoints).await;
             if matches!(result.as_ref(), Err(err) if err.is_sequenced_write_conflict()) {
                 self.load_manifest().await?;
             } else {
@@ -543,18 +560,35 @@ impl ManifestWriterHandler {
         }
     }
 
+    /// Writes the local manifest along with `checkpoints`, each paired with
+    /// whether its name must be unique. A checkpoint whose name is taken gets
+    /// an error result and is left out, without failing the other writes.
+    /// The check runs against the manifest being written, so it is repeated
+    /// when a conflict forces a retry.
     async fn write_manifest_update(
         &mut self,
-        checkpoint_options: &[&CheckpointOptions],
-    ) -> Result<Vec<CheckpointCreateResult>, SlateDBError> {
+        checkpoints: &[(&CheckpointOptions, bool)],
+    ) -> Result<Vec<Result<CheckpointCreateResult, SlateDBError>>, SlateDBError> {
         let mut dirty = self.clone_local_manifest_for_write();
         let mut checkpoint_results = Vec::new();
-        for options in checkpoint_options {
+        for (options, unique_name) in checkpoints {
+            if let Some(name) = options.name.as_ref().filter(|_| *unique_name) {
+                let name_taken = dirty
+                    .value
+                    .core
+                    .checkpoints
+                    .iter()
+                    .any(|checkpoint| checkpoint.name.as_ref() == Some(name));
+                if name_taken {
+                    checkpoint_results.push(Err(SlateDBError::CheckpointNameExists(name.clone())));
+                    continue;
+                }
+            }
             let id = self.db.rand.rng().gen_uuid();
             let checkpoint = self.manifest.new_checkpoint(id, options)?;
             let manifest_id = checkpoint.manifest_id;
             dirty.value.core.checkpoints.push(checkpoint);
-            checkpoint_results.push(CheckpointCreateResult { id, manifest_id });
+            checkpoint_results.push(Ok(CheckpointCreateResult { id, manifest_id }));
         }
         self.manifest.update(dirty).await?;
         Ok(checkpoint_results)
@@ -638,9 +672,12 @@ impl ManifestWriterHandler {
     async fn write_checkpoint_safely(
         &mut self,
         options: &CheckpointOptions,
-    ) -> Result<CheckpointCreateResult, SlateDBError> {
+        unique_name: bool,
+    ) -> Result<Result<CheckpointCreateResult, SlateDBError>, SlateDBError> {
         self.load_manifest().await?;
-        let mut results = self.write_manifest_update_safely(&[options]).await?;
+        let mut results = self
+            .write_manifest_update_safely(&[(options, unique_name)])
+            .await?;
         Ok(results
             .pop()
             .expect("checkpoint write should return exactly one result"))
@@ -650,7 +687,7 @@ impl ManifestWriterHandler {
         &mut self,
         staged_batch: Vec<UploadedMemtable>,
         attached_checkpoints: Vec<PendingCheckpoint>,
-        checkpoint_results: Vec<CheckpointCreateResult>,
+        checkpoint_results: Vec<Result<CheckpointCreateResult, SlateDBError>>,
         through_seq: u64,
     ) -> Result<(), SlateDBError> {
         debug!(
@@ -668,8 +705,10 @@ impl ManifestWriterHandler {
             .into_iter()
             .zip(checkpoint_results.into_iter())
         {
-            debug!("checkpoint created [id={}]", result.id);
-            let _ = checkpoint.sender.send(Ok(result));
+            if let Ok(created) = &result {
+                debug!("checkpoint created [id={}]", created.id);
+            }
+            let _ = checkpoint.sender.send(result);
         }
         let _ = self
             .tracker_tx
@@ -742,11 +781,13 @@ impl ManifestWriterHandler {
             ManifestWriterCommand::CreateCheckpoint {
                 through_seq,
                 options,
+                unique_name,
                 sender,
             } => {
                 self.pending_checkpoints.push(PendingCheckpoint {
                     through_seq,
                     options,
+                    unique_name,
                     sender,
                 });
             }
@@ -784,6 +825,7 @@ struct PendingFlush {
 struct PendingCheckpoint {
     through_seq: Option<u64>,
     options: CheckpointOptions,
+    unique_name: bool,
     sender: oneshot::Sender<Result<CheckpointCreateResult, SlateDBError>>,
 }
 
@@ -1284,7 +1326,7 @@ mod tests {
 
         let (tx, rx) = tokio::sync::oneshot::channel();
         started
-            .send_checkpoint(None, CheckpointOptions::default(), tx)
+            .send_checkpoint(None, CheckpointOptions::default(), false, tx)
             .unwrap();
         let checkpoint = timeout(Duration::from_secs(5), rx)
             .await
@@ -1322,7 +1364,7 @@ mod tests {
 
         let (tx, rx) = oneshot::channel();
         started
-            .send_checkpoint(Some(1), CheckpointOptions::default(), tx)
+            .send_checkpoint(Some(1), CheckpointOptions::default(), false, tx)
             .unwrap();
 
         tokio::task::yield_now().await;
@@ -1343,6 +1385,56 @@ mod tests {
         started.shutdown().await;
     }
 
+    #[tokio::test]
+    async fn should_reject_duplicate_checkpoint_name_without_failing_flush_batch() {
+        let harness = setup_harness(
+            "/tmp/test_parallel_l0_flush_manifest_writer_checkpoint_duplicate_name",
+            Arc::new(FailPointRegistry::new()),
+        )
+        .await;
+        let before =
+            latest_manifest_checkpoint_count(&harness.path, Arc::clone(&harness.object_store))
+                .await;
+
+        let inner = Arc::clone(&harness.inner);
+        let started = start_manifest_writer(
+            Arc::clone(&inner),
+            harness.manifest,
+            Duration::from_secs(3600),
+        );
+        let uploaded = next_uploaded_memtable(&inner, b"k1", b"v1").await;
+
+        // both requests wait on the same flush, so neither sees the other in
+        // the manifest before it is written
+        let options = CheckpointOptions {
+            name: Some("backup".to_string()),
+            ..CheckpointOptions::default()
+        };
+        let (tx1, rx1) = oneshot::channel();
+        started
+            .send_checkpoint(Some(1), options.clone(), true, tx1)
+            .unwrap();
+        let (tx2, rx2) = oneshot::channel();
+        started
+            .send_checkpoint(Some(1), options, true, tx2)
+            .unwrap();
+        started.notify_uploaded(uploaded).await.unwrap();
+
+        let through_seq = expect_flushed(&started.tracker_rx).await;
+        assert_eq!(through_seq, 1);
+        assert!(rx1.await.unwrap().is_ok());
+        assert!(matches!(
+            rx2.await.unwrap(),
+            Err(SlateDBError::CheckpointNameExists(name)) if name == "backup"
+        ));
+        let after =
+            latest_manifest_checkpoint_count(&harness.path, Arc::clone(&harness.object_store))
+                .await;
+        assert_eq!(after, before + 1);
+
+        started.shutdown().await;
+    }
+
     #[tokio::test]
     async fn should_emit_fatal_event_when_manifest_writer_is_fenced() {
         let harness = setup_harness(
@@ -1437,7 +1529,7 @@ mod tests {
         // Send a checkpoint request for epoch 1, which hasn't been uploaded yet.
         let (tx, rx) = oneshot::channel();
         started
-            .send_checkpoint(Some(1), CheckpointOptions::default(), tx)
+            .send_checkpoint(Some(1), CheckpointOptions::default(), false, tx)
             .unwrap();
 
         // Fence and trigger a manifest write.
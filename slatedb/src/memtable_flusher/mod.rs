@@ -87,7 +87,7 @@ impl MemtableFlusher {
             self.messages_tx.clone(),
         )?;
 
-        let tracker = FlushTracker::new(inner, uploader, manifest_writer);
+        let tracker = FlushTracker::new(inner, uploader, manifest_writer, self.messages_tx.clone());
         executor.add_handler(
             TRACKER_TASK_NAME.to_string(),
             Box::new(tracker),
@@ -121,17 +121,21 @@ impl MemtableFlusher {
             .send(tracker::TrackerMessage::MemtableFrozen)
     }
 
-    /// Creates a checkpoint using the memtable flusher's flush semantics.
+    /// Creates a checkpoint using the memtable flusher's flush semantics. If
+    /// `unique_name` is set, fails with [`SlateDBError::CheckpointNameExists`]
+    /// when the manifest already has a checkpoint with the same name.
     pub(crate) async fn create_checkpoint(
         &self,
         target: FlushTarget,
         options: CheckpointOptions,
+        unique_name: bool,
     ) -> Result<CheckpointCreateResult, SlateDBError> {
         let (tx, rx) = oneshot::channel();
         self.messages_tx
             .send(tracker::TrackerMessage::CheckpointRequest {
                 target,
                 options,
+                unique_name,
                 sender: tx,
             })?;
         rx.await.map_err(SlateDBError::ReadChannelError)?
@@ -18,13 +18,15 @@ use futures::StreamExt;
 use log::debug;
 
 use crate::checkpoint::CheckpointCreateResult;
-use crate::config::CheckpointOptions;
+use crate::config::{CheckpointOptions, FlushOrder};
 use crate::db::DbInner;
 use crate::dispatcher::MessageHandler;
 use crate::error::SlateDBError;
+use crate::mem_table::ImmutableMemtable;
 use crate::memtable_flusher::manifest_writer::{FlushResult, ManifestWriter};
 use crate::memtable_flusher::uploader::{UploadJob, UploadedMemtable, Uploader};
 use crate::memtable_flusher::FlushTarget;
+use crate::utils::SafeSender;
 use fail_parallel::fail_point;
 use std::collections::VecDeque;
 use std::sync::Arc;
@@ -43,6 +45,7 @@ pub(crate) enum TrackerMessage {
     CheckpointRequest {
         target: FlushTarget,
         options: CheckpointOptions,
+        unique_name: bool,
         sender: oneshot::Sender<Result<CheckpointCreateResult, SlateDBError>>,
     },
     /// An upload worker completed successfully.
@@ -55,6 +58,11 @@ pub(crate) enum TrackerMessage {
     PollManifest {
         sender: oneshot::Sender<Result<(), SlateDBError>>,
     },
+    /// A front-coded copy of a memtable waiting for dispatch has been built.
+    MemtableFrontCoded {
+        imm_memtable: Arc<ImmutableMemtable>,
+        front_coded: Arc<ImmutableMemtable>,
+    },
 }
 
 impl std::fmt::Debug for TrackerMessage {
@@ -75,6 +83,11 @@ impl std::fmt::Debug for TrackerMessage {
             }
             Self::ManifestRefreshed => write!(f, "ManifestRefreshed"),
             Self::PollManifest { .. } => write!(f, "PollManifest"),
+            Self::MemtableFrontCoded { front_coded, .. } => write!(
+                f,
+                "MemtableFrontCoded(last_seq={:?})",
+                front_coded.table().last_seq()
+            ),
         }
     }
 }
@@ -84,6 +97,8 @@ pub(super) struct FlushTracker {
     uploader: Uploader,
     manifest_writer: ManifestWriter,
     frontier: TrackedImmFrontier,
+    /// Sends the tracker's own messages, e.g. once a memtable is front-coded.
+    tracker_tx: SafeSender<TrackerMessage>,
 }
 
 impl FlushTracker {
@@ -91,12 +106,14 @@ impl FlushTracker {
         inner: Arc<DbInner>,
         uploader: Uploader,
         manifest_writer: ManifestWriter,
+        tracker_tx: SafeSender<TrackerMessage>,
     ) -> Self {
         Self {
             inner,
             uploader,
             manifest_writer,
             frontier: TrackedImmFrontier::new(),
+            tracker_tx,
         }
     }
 }
@@ -112,9 +129,10 @@ impl MessageHandler<TrackerMessage> for FlushTracker {
             TrackerMessage::CheckpointRequest {
                 target,
                 options,
+                unique_name,
                 sender,
             } => {
-                self.handle_checkpoint_request(target, options, sender)
+                self.handle_checkpoint_request(target, options, unique_name, sender)
                     .await
             }
             TrackerMessage::UploadComplete(uploaded) => self.handle_uploaded(uploaded).await,
@@ -124,6 +142,13 @@ impl MessageHandler<TrackerMessage> for FlushTracker {
             }
             TrackerMessage::ManifestRefreshed => self.reconcile_and_dispatch().await,
             TrackerMessage::PollManifest { sender } => self.manifest_writer.send_poll(sender),
+            TrackerMessage::MemtableFrontCoded {
+                imm_memtable,
+                front_coded,
+            } => {
+                self.replace_front_coded_memtable(&imm_memtable, front_coded);
+                Ok(())
+            }
         }
     }
 
@@ -161,12 +186,13 @@ impl FlushTracker {
         &mut self,
         target: FlushTarget,
         options: CheckpointOptions,
+        unique_name: bool,
         sender: oneshot::Sender<Result<CheckpointCreateResult, SlateDBError>>,
     ) -> Result<(), SlateDBError> {
         self.reconcile_and_dispatch().await?;
         let through_seq = self.frontier.resolve_target(target);
         self.manifest_writer
-            .send_checkpoint(through_seq, options, sender)?;
+            .send_checkpoint(through_seq, options, unique_name, sender)?;
         self.dispatch_ready_memtables()
     }
 
@@ -197,7 +223,76 @@ impl FlushTracker {
             guard.state().imm_memtable.iter().rev().cloned().collect()
         };
         self.frontier.register(imm_memtables.into_iter());
-        self.dispatch_ready_memtables()
+        self.dispatch_ready_memtables()?;
+        if self.inner.settings.front_code_immutable_memtables {
+            self.front_code_pending_memtables();
+        }
+        Ok(())
+    }
+
+    /// Front-codes the keys of the memtables that couldn't be dispatched, so
+    /// that they take less memory while they wait. Each front-coded copy is
+    /// built off the tracker's event loop, since building it walks the whole
+    /// memtable, and is sent back in a [`TrackerMessage::MemtableFrontCoded`].
+    /// See [`crate::mem_table::ImmutableMemtable::front_coded`].
+    fn front_code_pending_memtables(&mut self) {
+        for tracked in self.frontier.tracked.iter_mut() {
+            if !matches!(tracked.state, TrackedImmState::PendingDispatch)
+                || tracked.front_coding
+                || tracked.imm_memtable.table().is_front_coded()
+            {
+                continue;
+            }
+            tracked.front_coding = true;
+            let imm_memtable = Arc::clone(&tracked.imm_memtable);
+            let tracker_tx = self.tracker_tx.clone();
+            let front_code = move || {
+                let front_coded = Arc::new(imm_memtable.front_coded());
+                // The tracker is gone if the db is closing, and the copy is
+                // no longer needed.
+                let _ = tracker_tx.send(TrackerMessage::MemtableFrontCoded {
+                    imm_memtable,
+                    front_coded,
+                });
+            };
+            // Use tokio::spawn for DST since we need full determinism.
+            #[cfg(not(dst))]
+            #[allow(clippy::disallowed_methods)]
+            tokio::task::spawn_blocking(front_code);
+            #[cfg(dst)]
+            tokio::spawn(async move { front_code() });
+        }
+    }
+
+    /// Replaces `imm_memtable` with its front-coded copy, in the db state and
+    /// here, if it's still waiting for dispatch, so that the copy is the one
+    /// that's uploaded. A memtable dispatched in the meantime is uploaded as
+    /// it is, and the copy is dropped.
+    fn replace_front_coded_memtable(
+        &mut self,
+        imm_memtable: &Arc<ImmutableMemtable>,
+        front_coded: Arc<ImmutableMemtable>,
+    ) {
+        let Some(tracked) = self
+            .frontier
+            .tracked
+            .iter_mut()
+            .find(|tracked| Arc::ptr_eq(&tracked.imm_memtable, imm_memtable))
+        else {
+            return;
+        };
+        tracked.front_coding = false;
+        if !matches!(tracked.state, TrackedImmState::PendingDispatch) {
+            return;
+        }
+        if self
+            .inner
+            .state
+            .write()
+            .replace_imm_memtable(imm_memtable, Arc::clone(&front_coded))
+        {
+            tracked.imm_memtable = front_coded;
+        }
     }
 
     /// RFC-0024 §Backpressure: returns `true` iff every segment the
@@ -264,14 +359,9 @@ impl FlushTracker {
             // saturated segment.
             let next_idx = self
                 .frontier
-                .tracked
-                .iter()
-                .enumerate()
-                .find(|(_, t)| {
-                    matches!(t.state, TrackedImmState::PendingDispatch)
-                        && self.can_dispatch(&t.imm_memtable)
-                })
-                .map(|(idx, _)| idx);
+                .next_pending(self.inner.settings.flush_order, |imm| {
+                    self.can_dispatch(imm)
+                });
             let Some(idx) = next_idx else {
                 return Ok(());
             };
@@ -320,6 +410,8 @@ struct TrackedImm {
     last_seq: u64,
     imm_memtable: Arc<crate::mem_table::ImmutableMemtable>,
     state: TrackedImmState,
+    /// Whether a front-coded copy of the memtable is being built.
+    front_coding: bool,
 }
 
 /// Tracks the frontier of immutable memtables being flushed to L0.
@@ -358,6 +450,7 @@ impl TrackedImmFrontier {
                 last_seq,
                 imm_memtable,
                 state: TrackedImmState::PendingDispatch,
+                front_coding: false,
             });
         }
     }
@@ -409,6 +502,30 @@ impl TrackedImmFrontier {
             .count()
     }
 
+    /// Index of the `PendingDispatch` entry to upload next under `order`,
+    /// considering only entries accepted by `can_dispatch`. Ties go to the
+    /// oldest entry.
+    fn next_pending(
+        &self,
+        order: FlushOrder,
+        can_dispatch: impl Fn(&crate::mem_table::ImmutableMemtable) -> bool,
+    ) -> Option<usize> {
+        let mut candidates = self.tracked.iter().enumerate().filter(|(_, t)| {
+            matches!(t.state, TrackedImmState::PendingDispatch) && can_dispatch(&t.imm_memtable)
+        });
+        match order {
+            FlushOrder::OldestFirst => candidates.next().map(|(idx, _)| idx),
+            FlushOrder::LargestFirst => candidates
+                .max_by_key(|(idx, t)| {
+                    (
+                        t.imm_memtable.table().metadata().entries_size_in_bytes,
+                        std::cmp::Reverse(*idx),
+                    )
+                })
+                .map(|(idx, _)| idx),
+        }
+    }
+
     /// Transition the next `PendingDispatch` entry to `Uploading` and return it.
     #[cfg(test)]
     fn prepare_next_upload(&mut self) -> Option<&TrackedImm> {
@@ -607,6 +724,7 @@ mod tests {
                 stats_offset: 0,
                 stats_len: 0,
                 filter_format: FilterFormat::default(),
+                ..SsTableInfo::default()
             },
         )
     }
@@ -842,7 +960,7 @@ mod tests {
 
         let checkpoint = timeout(
             Duration::from_secs(5),
-            flusher.create_checkpoint(FlushTarget::All, CheckpointOptions::default()),
+            flusher.create_checkpoint(FlushTarget::All, CheckpointOptions::default(), false),
         )
         .await
         .unwrap()
@@ -882,7 +1000,11 @@ mod tests {
         // the flush pipeline to drain.
         let checkpoint = timeout(
             Duration::from_secs(5),
-            flusher.create_checkpoint(FlushTarget::CurrentDurable, CheckpointOptions::default()),
+            flusher.create_checkpoint(
+                FlushTarget::CurrentDurable,
+                CheckpointOptions::default(),
+                false,
+            ),
         )
         .await
         .unwrap()
@@ -1011,6 +1133,69 @@ mod tests {
         flusher.shutdown().await;
     }
 
+    #[tokio::test]
+    async fn should_front_code_memtables_waiting_for_dispatch() {
+        let settings = Settings {
+            l0_max_ssts: 1,
+            manifest_poll_interval: Duration::from_millis(10),
+            front_code_immutable_memtables: true,
+            ..Settings::default()
+        };
+        let harness = setup_harness(
+            "/tmp/test_parallel_l0_flush_flusher_front_coding",
+            settings,
+            Arc::new(FailPointRegistry::new()),
+        )
+        .await;
+        set_local_l0_len(&harness, 1);
+        set_remote_l0_len(&harness.path, Arc::clone(&harness.object_store), 1).await;
+        let path = harness.path.clone();
+        let object_store = Arc::clone(&harness.object_store);
+        let flusher = start_flusher(harness);
+        freeze_value_imm(&flusher.inner, b"k1", b"v1", 41);
+
+        {
+            let flush = flusher.flush(FlushTarget::All);
+            tokio::pin!(flush);
+            assert!(timeout(Duration::from_millis(100), &mut flush)
+                .await
+                .is_err());
+            timeout(Duration::from_secs(5), async {
+                while !flusher.inner.state.read().state().imm_memtable[0]
+                    .table()
+                    .is_front_coded()
+                {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .unwrap();
+            {
+                let state = flusher.inner.state.read().state();
+                let imm = state.imm_memtable.back().unwrap();
+                assert_eq!(
+                    imm.table().get_raw(b"k1"),
+                    vec![RowEntry::new_value(b"k1", b"v1", 1)]
+                );
+            }
+
+            // the front-coded copy is the one that's flushed
+            {
+                let mut guard = flusher.inner.state.write();
+                guard.modify(|modifier| modifier.state.manifest.value.core.tree.l0.clear());
+            }
+            set_remote_l0_len(&path, object_store, 0).await;
+            let result = timeout(Duration::from_secs(5), &mut flush)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(result.durable_seq, 1);
+            assert!(flusher.inner.state.read().state().imm_memtable.is_empty());
+        }
+
+        flusher.shutdown().await;
+    }
+
     #[tokio::test]
     async fn flush_proceeds_when_l0_total_high_but_disjoint_ranges() {
         // Mirrors a post-rescaling (union) manifest: L0 total exceeds the
@@ -1271,6 +1456,7 @@ mod tests {
     }
 
     mod frontier_tests {
+        use crate::config::FlushOrder;
         use crate::mem_table::{ImmutableMemtable, WritableKVTable};
         use crate::memtable_flusher::tracker::{TrackedImmFrontier, TrackedImmState};
         use crate::memtable_flusher::FlushTarget;
@@ -1359,6 +1545,54 @@ mod tests {
             assert_eq!(frontier.tracked[0].last_seq, 3);
         }
 
+        fn make_imm_with_size(seq: u64, value_len: usize) -> Arc<ImmutableMemtable> {
+            let table = WritableKVTable::new();
+            table.put(RowEntry::new_value(
+                &format!("k{seq}").into_bytes(),
+                &vec![b'v'; value_len],
+                seq,
+            ));
+            Arc::new(ImmutableMemtable::new(table, 0))
+        }
+
+        #[test]
+        fn next_pending_follows_flush_order() {
+            let mut frontier = TrackedImmFrontier::new();
+            frontier.register(
+                [
+                    make_imm_with_size(1, 10),
+                    make_imm_with_size(2, 30),
+                    make_imm_with_size(3, 30),
+                    make_imm_with_size(4, 20),
+                ]
+                .into_iter(),
+            );
+            let any = |_: &ImmutableMemtable| true;
+
+            assert_eq!(frontier.next_pending(FlushOrder::OldestFirst, any), Some(0));
+            // ties between equally large memtables go to the older one
+            assert_eq!(
+                frontier.next_pending(FlushOrder::LargestFirst, any),
+                Some(1)
+            );
+
+            // entries already uploading or blocked are skipped
+            frontier.set_state(2, TrackedImmState::Uploading);
+            assert_eq!(
+                frontier.next_pending(FlushOrder::LargestFirst, any),
+                Some(2)
+            );
+            let not_seq_3 = |imm: &ImmutableMemtable| imm.table().last_seq() != Some(3);
+            assert_eq!(
+                frontier.next_pending(FlushOrder::LargestFirst, not_seq_3),
+                Some(3)
+            );
+            assert_eq!(
+                frontier.next_pending(FlushOrder::OldestFirst, not_seq_3),
+                Some(0)
+            );
+        }
+
         #[test]
         fn reserved_l0_slots_counts_in_flight() {
             let mut frontier = TrackedImmFrontier::new();
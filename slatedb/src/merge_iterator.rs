@@ -1,10 +1,78 @@
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::BoxFuture;
 
+use crate::bytes_range::BytesRange;
 use crate::error::SlateDBError;
 use crate::iter::{IterationOrder, RowEntryIterator, TrackedRowEntryIterator};
 use crate::types::{RowEntry, ValueDeletable};
 use std::cmp::{Ordering, Reverse};
 use std::collections::{BinaryHeap, VecDeque};
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::ops::RangeBounds;
+
+type LazyMergeSourceOpener<'a> = Box<
+    dyn FnOnce() -> BoxFuture<'a, Result<Option<Box<dyn RowEntryIterator + 'a>>, SlateDBError>>
+        + Send
+        + Sync
+        + 'a,
+>;
+
+/// A [`MergeIterator`] input that is only materialized once the merge needs
+/// it. Each source carries the key range it may contain, which the merge uses
+/// to decide when the source has to be opened:
+///
+/// - Sources whose range does not overlap the merge's range are dropped
+///   without ever being opened.
+/// - A source is opened once the smallest key the merge is about to emit is
+///   at or past the start of the source's range. Until then, the source can
+///   not contribute an entry that orders before the current one.
+/// - Sources whose range ends before a seek target are dropped unopened.
+///
+/// The opener returns `None` when the source turns out to have nothing in
+/// range. Any iterator it returns is initialized (and seeked to the most
+/// recent seek target) by the merge before it's consulted, so openers do
+/// not need to initialize eagerly.
+///
+/// The range must cover every key the opened iterator can return. A key
+/// outside of the range may be emitted out of order.
+pub(crate) struct LazyMergeSource<'a> {
+    range: BytesRange,
+    open: LazyMergeSourceOpener<'a>,
+}
+
+impl<'a> LazyMergeSource<'a> {
+    pub(crate) fn new<F>(range: BytesRange, open: F) -> Self
+    where
+        F: FnOnce() -> BoxFuture<'a, Result<Option<Box<dyn RowEntryIterator + 'a>>, SlateDBError>>
+            + Send
+            + Sync
+            + 'a,
+    {
+        Self {
+            range,
+            open: Box::new(open),
+        }
+    }
+
+    /// Whether `key` is at or past the start of the source's range.
+    fn starts_at_or_before(&self, key: &[u8]) -> bool {
+        match self.range.start_bound() {
+            Included(start) => start.as_ref() <= key,
+            Excluded(start) => start.as_ref() < key,
+            Unbounded => true,
+        }
+    }
+
+    /// Whether every key in the source's range is strictly before `key`.
+    fn ends_before(&self, key: &[u8]) -> bool {
+        match self.range.end_bound() {
+            Included(end) => end.as_ref() < key,
+            Excluded(end) => end.as_ref() <= key,
+            Unbounded => false,
+        }
+    }
+}
 
 struct MergeIteratorHeapEntry<'a> {
     next_kv: RowEntry,
@@ -19,7 +87,11 @@ impl<'a> MergeIteratorHeapEntry<'a> {
         mut self,
         next_key: &[u8],
     ) -> Result<Option<MergeIteratorHeapEntry<'a>>, SlateDBError> {
-        if self.next_kv.key >= next_key {
+        let reached = match self.order {
+            IterationOrder::Ascending => self.next_kv.key >= next_key,
+            IterationOrder::Descending => self.next_kv.key <= next_key,
+        };
+        if reached {
             Ok(Some(self))
         } else {
             self.iterator.seek(next_key).await?;
@@ -74,6 +146,13 @@ pub(crate) struct MergeIterator<'a> {
     iterators: BinaryHeap<Reverse<MergeIteratorHeapEntry<'a>>>,
     /// Iterators that have not yet been initialized and seeded.
     pending_iterators: Vec<(usize, Box<dyn RowEntryIterator + 'a>)>,
+    /// Sources that have not been opened yet, sorted by the start of their
+    /// key range. See [`LazyMergeSource`].
+    lazy_sources: VecDeque<LazyMergeSource<'a>>,
+    /// Most recent seek target, applied to lazy sources when they're opened.
+    lazy_seek: Option<Bytes>,
+    /// Index assigned to the next opened lazy source.
+    next_index: usize,
     /// Whether to deduplicate entries of multiple versions with the same key. It's enabled by
     /// default, but it is useful to disable when we want to have some merge logics during
     /// compaction.
@@ -82,6 +161,8 @@ pub(crate) struct MergeIterator<'a> {
     initialized: bool,
     /// Counter to track bytes processed (key + value length) for progress reporting.
     bytes_processed: u64,
+    /// Counter to track entries processed, for compaction reports.
+    entries_processed: u64,
     /// The iteration order for key comparison in the merge heap.
     order: IterationOrder,
 }
@@ -97,20 +178,23 @@ impl<'a> MergeIterator<'a> {
         iterators: impl IntoIterator<Item = T>,
         order: IterationOrder,
     ) -> Result<Self, SlateDBError> {
+        let pending_iterators: Vec<_> = iterators
+            .into_iter()
+            .enumerate()
+            .map(|(index, iterator)| (index, Box::new(iterator) as Box<dyn RowEntryIterator + 'a>))
+            .collect();
         Ok(Self {
             current: None,
             iterators: BinaryHeap::new(),
             order,
-            pending_iterators: iterators
-                .into_iter()
-                .enumerate()
-                .map(|(index, iterator)| {
-                    (index, Box::new(iterator) as Box<dyn RowEntryIterator + 'a>)
-                })
-                .collect(),
+            next_index: pending_iterators.len(),
+            pending_iterators,
+            lazy_sources: VecDeque::new(),
+            lazy_seek: None,
             dedup: true,
             initialized: false,
             bytes_processed: 0,
+            entries_processed: 0,
         })
     }
 
@@ -119,6 +203,84 @@ impl<'a> MergeIterator<'a> {
         self
     }
 
+    /// Add sources that are opened on demand as the merge reaches their key
+    /// range. Sources that don't overlap `range` are dropped without being
+    /// opened.
+    ///
+    /// Deferral only applies to ascending merges. Descending merges open all
+    /// overlapping sources when the iterator is initialized.
+    pub(crate) fn with_lazy_sources(
+        mut self,
+        sources: impl IntoIterator<Item = LazyMergeSource<'a>>,
+        range: &BytesRange,
+    ) -> Self {
+        self.lazy_sources.extend(
+            sources
+                .into_iter()
+                .filter(|source| source.range.intersect(range).is_some()),
+        );
+        self.lazy_sources.make_contiguous().sort_by(|a, b| {
+            a.range
+                .comparable_start_bound()
+                .cmp(&b.range.comparable_start_bound())
+        });
+        self
+    }
+
+    /// Number of leading lazy sources that must be opened before the merge
+    /// can safely emit `current`.
+    fn lazy_sources_due(&self) -> usize {
+        match (self.order, self.current.as_ref()) {
+            (IterationOrder::Descending, _) => self.lazy_sources.len(),
+            (IterationOrder::Ascending, None) => self.lazy_sources.len().min(1),
+            (IterationOrder::Ascending, Some(current)) => self
+                .lazy_sources
+                .partition_point(|source| source.starts_at_or_before(&current.next_kv.key)),
+        }
+    }
+
+    /// Open every lazy source that may contain a key ordering at or before
+    /// `current`, so `current` is always the true head of the merge.
+    async fn open_lazy_sources(&mut self) -> Result<(), SlateDBError> {
+        loop {
+            let due = self.lazy_sources_due();
+            if due == 0 {
+                return Ok(());
+            }
+            let opened =
+                futures::future::try_join_all(self.lazy_sources.drain(..due).map(|s| (s.open)()))
+                    .await?;
+            for mut iterator in opened.into_iter().flatten() {
+                iterator.init().await?;
+                let index = self.next_index;
+                self.next_index += 1;
+                let Some(next_kv) = iterator.next().await? else {
+                    continue;
+                };
+                let entry = MergeIteratorHeapEntry {
+                    next_kv,
+                    index,
+                    iterator,
+                    order: self.order,
+                };
+                // The heap entry only seeks sources positioned before the seek
+                // target, since iterators may reject seeks to keys before their
+                // own range.
+                let entry = match self.lazy_seek.as_ref() {
+                    Some(seek_key) => entry.seek(seek_key).await?,
+                    None => Some(entry),
+                };
+                if let Some(entry) = entry {
+                    self.iterators.push(Reverse(entry));
+                }
+            }
+            if let Some(current) = self.current.take() {
+                self.iterators.push(Reverse(current));
+            }
+            self.current = self.iterators.pop().map(|r| r.0);
+        }
+    }
+
     async fn initialize(&mut self) -> Result<(), SlateDBError> {
         if self.initialized {
             return Ok(());
@@ -136,6 +298,7 @@ impl<'a> MergeIterator<'a> {
             }
         }
         self.current = self.iterators.pop().map(|r| r.0);
+        self.open_lazy_sources().await?;
         self.initialized = true;
         Ok(())
     }
@@ -151,6 +314,13 @@ impl<'a> MergeIterator<'a> {
         self.current.as_ref().map(|c| &c.next_kv)
     }
 
+    /// The position, among the iterators the merge was created with, of the
+    /// iterator holding the entry that `next` returns.
+    #[cfg_attr(not(feature = "debug-tools"), allow(dead_code))]
+    pub(crate) fn current_source(&self) -> Option<usize> {
+        self.current.as_ref().map(|c| c.index)
+    }
+
     async fn advance(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
         self.ensure_initialized().await?;
         if let Some(mut iterator_state) = self.current.take() {
@@ -160,10 +330,12 @@ impl<'a> MergeIterator<'a> {
                 self.iterators.push(Reverse(iterator_state));
             }
             self.current = self.iterators.pop().map(|r| r.0);
+            self.open_lazy_sources().await?;
 
             // Track bytes processed for progress reporting
             let entry_bytes = current_kv.key.len() as u64 + current_kv.value.len() as u64;
             self.bytes_processed += entry_bytes;
+            self.entries_processed += 1;
 
             return Ok(Some(current_kv));
         }
@@ -213,6 +385,11 @@ impl RowEntryIterator for MergeIterator<'_> {
             return Err(SlateDBError::IteratorNotInitialized);
         }
         self.ensure_initialized().await?;
+        self.lazy_seek = Some(Bytes::copy_from_slice(next_key));
+        if matches!(self.order, IterationOrder::Ascending) {
+            self.lazy_sources
+                .retain(|source| !source.ends_before(next_key));
+        }
         let mut seek_futures = VecDeque::new();
         if let Some(iterator) = self.current.take() {
             seek_futures.push_back(iterator.seek(next_key))
@@ -229,7 +406,7 @@ impl RowEntryIterator for MergeIterator<'_> {
         }
 
         self.current = self.iterators.pop().map(|r| r.0);
-        Ok(())
+        self.open_lazy_sources().await
     }
 }
 
@@ -237,17 +414,47 @@ impl TrackedRowEntryIterator for MergeIterator<'_> {
     fn bytes_processed(&self) -> u64 {
         self.bytes_processed
     }
+
+    fn entries_processed(&self) -> u64 {
+        self.entries_processed
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::bytes_range::BytesRange;
     use crate::iter::{IterationOrder, RowEntryIterator};
-    use crate::merge_iterator::MergeIterator;
+    use crate::mem_table::KVTable;
+    use crate::merge_iterator::{LazyMergeSource, MergeIterator};
     use crate::test_utils::{assert_iterator, assert_next, TestIterator};
     use crate::types::RowEntry;
+    use bytes::Bytes;
+    use futures::FutureExt;
     use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
     use std::vec;
 
+    /// Builds a lazy source over `entries`, whose range spans the first and
+    /// last entry keys, and counts how often it's opened in `opened`.
+    fn counting_lazy_source(
+        entries: Vec<RowEntry>,
+        opened: Arc<AtomicUsize>,
+    ) -> LazyMergeSource<'static> {
+        let first = entries.first().expect("entries").key.clone();
+        let last = entries.last().expect("entries").key.clone();
+        LazyMergeSource::new(BytesRange::from(first..=last), move || {
+            async move {
+                opened.fetch_add(1, Ordering::SeqCst);
+                let iter = entries
+                    .into_iter()
+                    .fold(TestIterator::new(), |iter, e| iter.with_row_entry(e));
+                Ok(Some(Box::new(iter) as Box<dyn RowEntryIterator>))
+            }
+            .boxed()
+        })
+    }
+
     #[tokio::test]
     async fn test_merge_iterator_should_include_entries_in_order() {
         let mut iters: VecDeque<TestIterator> = VecDeque::new();
@@ -432,6 +639,36 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_seek_descending_merge_iter() {
+        let table1 = KVTable::new();
+        table1.put(RowEntry::new_value(b"aa", b"aa1", 1));
+        table1.put(RowEntry::new_value(b"cc", b"cc1", 2));
+        let table2 = KVTable::new();
+        table2.put(RowEntry::new_value(b"aa", b"aa2", 3));
+        table2.put(RowEntry::new_value(b"bb", b"bb2", 4));
+        table2.put(RowEntry::new_value(b"dd", b"dd2", 5));
+
+        let mut merge_iter = MergeIterator::new_with_order(
+            [table1, table2]
+                .map(|table| table.range(BytesRange::from(..), IterationOrder::Descending)),
+            IterationOrder::Descending,
+        )
+        .unwrap();
+        merge_iter.init().await.unwrap();
+        assert_next(&mut merge_iter, &RowEntry::new_value(b"dd", b"dd2", 5)).await;
+        merge_iter.seek(b"bc".as_ref()).await.unwrap();
+
+        assert_iterator(
+            &mut merge_iter,
+            vec![
+                RowEntry::new_value(b"bb", b"bb2", 4),
+                RowEntry::new_value(b"aa", b"aa2", 3),
+            ],
+        )
+        .await;
+    }
+
     #[tokio::test]
     async fn test_two_merge_seek() {
         let iter1 = TestIterator::new()
@@ -590,4 +827,132 @@ mod tests {
         )
         .await;
     }
+
+    #[tokio::test]
+    async fn test_lazy_sources_merge_in_order() {
+        let opened = Arc::new(AtomicUsize::new(0));
+        let eager = TestIterator::new()
+            .with_entry(b"aa", b"aa0", 1)
+            .with_entry(b"dd", b"dd0", 1);
+        let lazy = vec![
+            counting_lazy_source(
+                vec![
+                    RowEntry::new_value(b"bb", b"bb1", 2),
+                    RowEntry::new_value(b"dd", b"dd1", 2),
+                ],
+                opened.clone(),
+            ),
+            counting_lazy_source(
+                vec![
+                    RowEntry::new_value(b"cc", b"cc2", 3),
+                    RowEntry::new_value(b"ee", b"ee2", 3),
+                ],
+                opened.clone(),
+            ),
+        ];
+
+        let mut merge_iter = MergeIterator::new([eager])
+            .unwrap()
+            .with_lazy_sources(lazy, &BytesRange::unbounded());
+
+        assert_iterator(
+            &mut merge_iter,
+            vec![
+                RowEntry::new_value(b"aa", b"aa0", 1),
+                RowEntry::new_value(b"bb", b"bb1", 2),
+                RowEntry::new_value(b"cc", b"cc2", 3),
+                RowEntry::new_value(b"dd", b"dd1", 2),
+                RowEntry::new_value(b"ee", b"ee2", 3),
+            ],
+        )
+        .await;
+        assert_eq!(opened.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_lazy_sources_only_open_overlapping_sources() {
+        let opened = Arc::new(AtomicUsize::new(0));
+        let sources = [b"a", b"b", b"c", b"d", b"e"].map(|prefix| {
+            let key = |suffix: &[u8]| Bytes::from([prefix.as_slice(), suffix].concat());
+            counting_lazy_source(
+                vec![
+                    RowEntry::new_value(&key(b"1"), b"v", 0),
+                    RowEntry::new_value(&key(b"9"), b"v", 0),
+                ],
+                opened.clone(),
+            )
+        });
+        let range = BytesRange::from(Bytes::from_static(b"b0")..=Bytes::from_static(b"c9"));
+
+        let mut merge_iter = MergeIterator::new(Vec::<TestIterator>::new())
+            .unwrap()
+            .with_lazy_sources(sources, &range);
+
+        merge_iter.init().await.unwrap();
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+        assert_next(&mut merge_iter, &RowEntry::new_value(b"b1", b"v", 0)).await;
+        assert_next(&mut merge_iter, &RowEntry::new_value(b"b9", b"v", 0)).await;
+        assert_next(&mut merge_iter, &RowEntry::new_value(b"c1", b"v", 0)).await;
+        assert_eq!(opened.load(Ordering::SeqCst), 2);
+        assert_next(&mut merge_iter, &RowEntry::new_value(b"c9", b"v", 0)).await;
+        assert!(merge_iter.next().await.unwrap().is_none());
+        assert_eq!(opened.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_lazy_sources_not_opened_when_scan_stops_early() {
+        let opened = Arc::new(AtomicUsize::new(0));
+        let lazy = vec![
+            counting_lazy_source(vec![RowEntry::new_value(b"mm", b"mm1", 1)], opened.clone()),
+            counting_lazy_source(vec![RowEntry::new_value(b"zz", b"zz1", 1)], opened.clone()),
+        ];
+        let eager = TestIterator::new().with_entry(b"aa", b"aa0", 1);
+
+        let mut merge_iter = MergeIterator::new([eager])
+            .unwrap()
+            .with_lazy_sources(lazy, &BytesRange::unbounded());
+
+        assert_next(&mut merge_iter, &RowEntry::new_value(b"aa", b"aa0", 1)).await;
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_lazy_sources_skipped_by_seek_are_not_opened() {
+        let opened = Arc::new(AtomicUsize::new(0));
+        let lazy = vec![
+            counting_lazy_source(
+                vec![
+                    RowEntry::new_value(b"bb", b"bb1", 1),
+                    RowEntry::new_value(b"cc", b"cc1", 1),
+                ],
+                opened.clone(),
+            ),
+            counting_lazy_source(
+                vec![
+                    RowEntry::new_value(b"dd", b"dd1", 1),
+                    RowEntry::new_value(b"ff", b"ff1", 1),
+                ],
+                opened.clone(),
+            ),
+        ];
+        let eager = TestIterator::new()
+            .with_entry(b"aa", b"aa0", 1)
+            .with_entry(b"ee", b"ee0", 1);
+
+        let mut merge_iter = MergeIterator::new([eager])
+            .unwrap()
+            .with_lazy_sources(lazy, &BytesRange::unbounded());
+        merge_iter.init().await.unwrap();
+        merge_iter.seek(b"ee".as_ref()).await.unwrap();
+
+        assert_iterator(
+            &mut merge_iter,
+            vec![
+                RowEntry::new_value(b"ee", b"ee0", 1),
+                RowEntry::new_value(b"ff", b"ff1", 1),
+            ],
+        )
+        .await;
+        assert_eq!(opened.load(Ordering::SeqCst), 1);
+    }
 }
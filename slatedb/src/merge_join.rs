@@ -0,0 +1,323 @@
+//! Sorted-merge joins of two scans.
+//!
+//! [`MergeJoinIterator`] joins the entries of two [`KeyValueIterator`]s, e.g.
+//! an index scan and a scan of the rows it indexes, on a join key extracted
+//! from each entry. Both sides must return their entries in ascending order of
+//! join key, which lets the join run in a single pass over each side:
+//!
+//! - Entries are matched by comparing the join keys of the two sides and
+//!   advancing whichever side is behind.
+//! - Several entries on one side may share a join key. Every left entry is
+//!   paired with every right entry of its join key, in the order the sides
+//!   return them, so many-to-one and many-to-many joins are supported. The
+//!   right entries of a join key are buffered while its left entries are
+//!   joined.
+//! - Left entries without a match are skipped by a [`JoinType::Inner`] join
+//!   and returned without a right entry by a [`JoinType::Left`] join. Right
+//!   entries without a match are always skipped.
+
+use bytes::Bytes;
+
+use crate::virtual_source::KeyValueIterator;
+
+/// Extracts the join key of an entry from its key and value.
+type JoinKeyFn = Box<dyn Fn(&[u8], &[u8]) -> Bytes + Send + Sync>;
+
+/// Which left entries a [`MergeJoinIterator`] returns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JoinType {
+    /// Only left entries with a matching right entry are returned.
+    #[default]
+    Inner,
+    /// Every left entry is returned, without a right entry if none matches.
+    Left,
+}
+
+/// A left entry joined with a matching right entry.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JoinedEntry {
+    /// The join key shared by both entries.
+    pub join_key: Bytes,
+    /// The key and value of the left entry.
+    pub left: (Bytes, Bytes),
+    /// The key and value of the right entry, or `None` for a left entry
+    /// without a match in a [`JoinType::Left`] join.
+    pub right: Option<(Bytes, Bytes)>,
+}
+
+/// Joins two [`KeyValueIterator`]s on the join keys of their entries. See the
+/// module docs for how entries are matched.
+pub struct MergeJoinIterator {
+    left: Box<dyn KeyValueIterator>,
+    right: Box<dyn KeyValueIterator>,
+    left_key: JoinKeyFn,
+    right_key: JoinKeyFn,
+    join_type: JoinType,
+    /// The join key of the last left entry read, used to check the order.
+    last_left_key: Option<Bytes>,
+    /// The join key of the last right entry read, used to check the order.
+    last_right_key: Option<Bytes>,
+    /// A right entry read ahead of the current group, with its join key.
+    right_peeked: Option<(Bytes, (Bytes, Bytes))>,
+    /// The join key whose right entries are held in `group`.
+    group_key: Option<Bytes>,
+    /// The right entries that match `group_key`. Empty if none do.
+    group: Vec<(Bytes, Bytes)>,
+    /// The left entry being joined with `group`, with the index of the next
+    /// right entry to pair it with.
+    current: Option<((Bytes, Bytes), usize)>,
+}
+
+impl MergeJoinIterator {
+    /// Creates an inner join of `left` and `right`, neither of which should
+    /// have been advanced yet. `left_key` and `right_key` extract the join key
+    /// of an entry from its key and value.
+    pub fn new<L, R, FL, FR>(left: L, right: R, left_key: FL, right_key: FR) -> Self
+    where
+        L: KeyValueIterator + 'static,
+        R: KeyValueIterator + 'static,
+        FL: Fn(&[u8], &[u8]) -> Bytes + Send + Sync + 'static,
+        FR: Fn(&[u8], &[u8]) -> Bytes + Send + Sync + 'static,
+    {
+        Self {
+            left: Box::new(left),
+            right: Box::new(right),
+            left_key: Box::new(left_key),
+            right_key: Box::new(right_key),
+            join_type: JoinType::default(),
+            last_left_key: None,
+            last_right_key: None,
+            right_peeked: None,
+            group_key: None,
+            group: Vec::new(),
+            current: None,
+        }
+    }
+
+    /// Sets the type of the join, [`JoinType::Inner`] by default.
+    pub fn with_join_type(mut self, join_type: JoinType) -> Self {
+        self.join_type = join_type;
+        self
+    }
+
+    /// Returns the next joined entry, or `None` once the left side is
+    /// exhausted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error`] if either side fails, or with kind
+    /// [`crate::ErrorKind::Invalid`] if either side returns its join keys out
+    /// of order.
+    pub async fn next(&mut self) -> Result<Option<JoinedEntry>, crate::Error> {
+        loop {
+            if let Some((left, pos)) = &mut self.current {
+                if let Some(right) = self.group.get(*pos) {
+                    *pos += 1;
+                    return Ok(Some(JoinedEntry {
+                        join_key: self.group_key.clone().unwrap_or_default(),
+                        left: left.clone(),
+                        right: Some(right.clone()),
+                    }));
+                }
+                self.current = None;
+            }
+            let Some(left) = self.left.next().await? else {
+                return Ok(None);
+            };
+            let join_key = (self.left_key)(&left.0, &left.1);
+            check_order("left", &mut self.last_left_key, &join_key)?;
+            self.load_group(&join_key).await?;
+            if !self.group.is_empty() {
+                self.current = Some((left, 0));
+            } else if self.join_type == JoinType::Left {
+                return Ok(Some(JoinedEntry {
+                    join_key,
+                    left,
+                    right: None,
+                }));
+            }
+        }
+    }
+
+    /// Fills `group` with the right entries whose join key is `join_key`,
+    /// skipping the right entries with smaller join keys.
+    async fn load_group(&mut self, join_key: &Bytes) -> Result<(), crate::Error> {
+        if self.group_key.as_ref() == Some(join_key) {
+            return Ok(());
+        }
+        self.group.clear();
+        self.group_key = Some(join_key.clone());
+        loop {
+            let (right_key, right) = match self.right_peeked.take() {
+                Some(peeked) => peeked,
+                None => {
+                    let Some(right) = self.right.next().await? else {
+                        return Ok(());
+                    };
+                    let right_key = (self.right_key)(&right.0, &right.1);
+                    check_order("right", &mut self.last_right_key, &right_key)?;
+                    (right_key, right)
+                }
+            };
+            if right_key == join_key {
+                self.group.push(right);
+            } else if right_key > join_key {
+                self.right_peeked = Some((right_key, right));
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn check_order(side: &str, last_key: &mut Option<Bytes>, key: &Bytes) -> Result<(), crate::Error> {
+    if let Some(last_key) = last_key {
+        if key < last_key {
+            return Err(crate::Error::invalid(format!(
+                "{} side of merge join returned join key {:?} out of order after {:?}",
+                side, key, last_key
+            )));
+        }
+    }
+    *last_key = Some(key.clone());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::VecSource;
+    use crate::{Db, ErrorKind};
+    use object_store::memory::InMemory;
+    use std::sync::Arc;
+
+    /// Joins on the part of the key after its last `/`.
+    fn suffix(key: &[u8], _value: &[u8]) -> Bytes {
+        let start = key.iter().rposition(|b| *b == b'/').map_or(0, |i| i + 1);
+        Bytes::copy_from_slice(&key[start..])
+    }
+
+    /// Joins on the value.
+    fn value(_key: &[u8], value: &[u8]) -> Bytes {
+        Bytes::copy_from_slice(value)
+    }
+
+    async fn collect_joined(mut iter: MergeJoinIterator) -> Vec<(String, Option<String>)> {
+        let mut joined = Vec::new();
+        while let Some(entry) = iter.next().await.unwrap() {
+            let key = |kv: &(Bytes, Bytes)| String::from_utf8(kv.0.to_vec()).unwrap();
+            joined.push((key(&entry.left), entry.right.as_ref().map(key)));
+        }
+        joined
+    }
+
+    fn pairs(expected: &[(&str, Option<&str>)]) -> Vec<(String, Option<String>)> {
+        expected
+            .iter()
+            .map(|(left, right)| (left.to_string(), right.map(str::to_string)))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn should_join_index_entries_with_their_rows() {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        let mut batch = crate::WriteBatch::new();
+        for (key, value) in [
+            ("idx/color/blue/2", ""),
+            ("idx/color/red/1", ""),
+            ("idx/color/red/3", ""),
+            ("idx/color/red/5", ""),
+            ("row/1", "apple"),
+            ("row/2", "sky"),
+            ("row/3", "cherry"),
+            ("row/4", "grass"),
+        ] {
+            batch.put(key, value);
+        }
+        db.write(batch).await.unwrap();
+
+        let db = &db;
+        let join = |join_type| async move {
+            let index = db.scan_prefix(b"idx/color/red/").await.unwrap();
+            let rows = db.scan_prefix(b"row/").await.unwrap();
+            let iter =
+                MergeJoinIterator::new(index, rows, suffix, suffix).with_join_type(join_type);
+            collect_joined(iter).await
+        };
+        assert_eq!(
+            join(JoinType::Inner).await,
+            pairs(&[
+                ("idx/color/red/1", Some("row/1")),
+                ("idx/color/red/3", Some("row/3")),
+            ])
+        );
+        // the index entry of a missing row has no match
+        assert_eq!(
+            join(JoinType::Left).await,
+            pairs(&[
+                ("idx/color/red/1", Some("row/1")),
+                ("idx/color/red/3", Some("row/3")),
+                ("idx/color/red/5", None),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn should_pair_every_left_entry_with_every_right_entry_of_its_join_key() {
+        // orders reference their customer by value, customers are keyed by id
+        let orders = || {
+            VecSource::new(&[
+                ("order/10", "a"),
+                ("order/11", "a"),
+                ("order/12", "b"),
+                ("order/13", "c"),
+                ("order/14", "c"),
+            ])
+        };
+        let customers = || {
+            VecSource::new(&[
+                ("cust/a", ""),
+                ("cust/c", ""),
+                ("cust/c", ""),
+                ("cust/d", ""),
+            ])
+        };
+        let iter = MergeJoinIterator::new(orders(), customers(), value, suffix);
+        assert_eq!(
+            collect_joined(iter).await,
+            pairs(&[
+                ("order/10", Some("cust/a")),
+                ("order/11", Some("cust/a")),
+                ("order/13", Some("cust/c")),
+                ("order/13", Some("cust/c")),
+                ("order/14", Some("cust/c")),
+                ("order/14", Some("cust/c")),
+            ])
+        );
+
+        let iter = MergeJoinIterator::new(orders(), customers(), value, suffix)
+            .with_join_type(JoinType::Left);
+        let joined = collect_joined(iter).await;
+        assert_eq!(joined.len(), 7);
+        assert_eq!(joined[2], ("order/12".to_string(), None));
+    }
+
+    #[tokio::test]
+    async fn should_reject_join_keys_out_of_order() {
+        let left = VecSource::new(&[("l/1", "b"), ("l/2", "a")]);
+        let right = VecSource::new(&[("r/a", ""), ("r/b", "")]);
+        let mut iter = MergeJoinIterator::new(left, right, value, suffix);
+        assert!(iter.next().await.unwrap().is_some());
+        let err = iter.next().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Invalid);
+
+        let left = VecSource::new(&[("l/1", "a"), ("l/2", "c")]);
+        let right = VecSource::new(&[("r/a", ""), ("r/c", ""), ("r/b", "")]);
+        let mut iter = MergeJoinIterator::new(left, right, value, suffix);
+        assert!(iter.next().await.unwrap().is_some());
+        let err = iter.next().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Invalid);
+    }
+}
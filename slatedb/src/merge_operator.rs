@@ -116,6 +116,63 @@ pub trait MergeOperator {
 
 pub(crate) type MergeOperatorType = Arc<dyn MergeOperator + Send + Sync>;
 
+/// A built-in merge operator for counters, used by [`crate::Db::increment`].
+///
+/// Counters are stored as 8-byte little-endian `i64`s, and each operand is an
+/// `i64` delta in the same encoding that's added to the counter. Additions
+/// wrap on overflow. A key without a value is a counter of 0, and so is a key
+/// holding a value that isn't a counter, e.g. one written with
+/// [`crate::Db::put`]: the first increment overwrites it rather than failing
+/// every later read of the key.
+///
+/// ## Errors
+///
+/// Merging an operand that isn't an 8-byte delta fails with
+/// [`MergeOperatorError::Callback`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IntegerAddMergeOperator;
+
+impl IntegerAddMergeOperator {
+    /// Decodes a counter or delta, or returns `None` if `value` isn't one.
+    pub fn decode(value: &[u8]) -> Option<i64> {
+        Some(i64::from_le_bytes(value.try_into().ok()?))
+    }
+
+    /// Encodes a counter or delta.
+    pub fn encode(value: i64) -> Bytes {
+        Bytes::copy_from_slice(&value.to_le_bytes())
+    }
+}
+
+impl MergeOperator for IntegerAddMergeOperator {
+    fn merge(
+        &self,
+        key: &Bytes,
+        existing_value: Option<Bytes>,
+        value: Bytes,
+    ) -> Result<Bytes, MergeOperatorError> {
+        self.merge_batch(key, existing_value, &[value])
+    }
+
+    fn merge_batch(
+        &self,
+        _key: &Bytes,
+        existing_value: Option<Bytes>,
+        operands: &[Bytes],
+    ) -> Result<Bytes, MergeOperatorError> {
+        let mut total = existing_value
+            .and_then(|value| Self::decode(&value))
+            .unwrap_or(0);
+        for operand in operands {
+            let delta = Self::decode(operand).ok_or_else(|| MergeOperatorError::Callback {
+                message: format!("counter delta must be 8 bytes, got {} bytes", operand.len()),
+            })?;
+            total = total.wrapping_add(delta);
+        }
+        Ok(Self::encode(total))
+    }
+}
+
 /// Counter for merge operands resolved across all paths.
 ///
 /// Incremented on each successful `merge_batch` call by the number of
@@ -232,6 +289,10 @@ impl<T: TrackedRowEntryIterator> TrackedRowEntryIterator for MergeOperatorRequir
     fn bytes_processed(&self) -> u64 {
         self.delegate.bytes_processed()
     }
+
+    fn entries_processed(&self) -> u64 {
+        self.delegate.entries_processed()
+    }
 }
 
 /// An iterator that merges mergeable entries into a single value.
@@ -486,6 +547,10 @@ impl<T: TrackedRowEntryIterator> TrackedRowEntryIterator for MergeOperatorIterat
     fn bytes_processed(&self) -> u64 {
         self.delegate.bytes_processed()
     }
+
+    fn entries_processed(&self) -> u64 {
+        self.delegate.entries_processed()
+    }
 }
 
 #[cfg(test)]
@@ -495,6 +560,8 @@ mod tests {
     use rstest::rstest;
     use slatedb_common::metrics::{lookup_metric, test_recorder_helper};
 
+    use crate::mem_table::WritableKVTable;
+    use crate::merge_iterator::MergeIterator;
     use crate::test_utils::assert_iterator;
 
     use super::*;
@@ -672,6 +739,46 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_merge_operands_spread_across_three_tables() {
+        let newest = WritableKVTable::new();
+        let middle = WritableKVTable::new();
+        let oldest = WritableKVTable::new();
+
+        // key1: base value in the oldest table, operands in the newer two
+        oldest.put(RowEntry::new_value(b"key1", b"a", 1));
+        middle.put(RowEntry::new_merge(b"key1", b"b", 4));
+        middle.put(RowEntry::new_merge(b"key1", b"c", 5));
+        newest.put(RowEntry::new_merge(b"key1", b"d", 8));
+        // key2: operands only, merged with no existing value
+        oldest.put(RowEntry::new_merge(b"key2", b"x", 2));
+        middle.put(RowEntry::new_merge(b"key2", b"y", 6));
+        newest.put(RowEntry::new_merge(b"key2", b"z", 9));
+        // key3: a tombstone in the middle table hides the older operand
+        oldest.put(RowEntry::new_merge(b"key3", b"p", 3));
+        middle.put(RowEntry::new_tombstone(b"key3", 7));
+        newest.put(RowEntry::new_merge(b"key3", b"q", 10));
+
+        let merge_iter = MergeIterator::new(VecDeque::from(vec![
+            newest.table().iter(),
+            middle.table().iter(),
+            oldest.table().iter(),
+        ]))
+        .unwrap();
+        let mut iterator =
+            MergeOperatorIterator::new(Arc::new(MockMergeOperator {}), merge_iter, true, None);
+
+        assert_iterator(
+            &mut iterator,
+            vec![
+                RowEntry::new_value(b"key1", b"abcd", 8),
+                RowEntry::new_merge(b"key2", b"xyz", 9),
+                RowEntry::new_value(b"key3", b"q", 10),
+            ],
+        )
+        .await;
+    }
+
     #[tokio::test]
     async fn test_seek_clears_buffered_entry() {
         let merge_operator = Arc::new(MockMergeOperator {});
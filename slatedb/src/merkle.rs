@@ -0,0 +1,215 @@
+//! A merkle tree over the key space of a database, used to find the key
+//! ranges in which two replicas diverge. See [`crate::Db::merkle_root`].
+//!
+//! The tree partitions keys by their leading bytes. A node at depth `d` is
+//! identified by a prefix of `d` bytes and has one child for each possible
+//! next byte, so every internal node has 256 children and the leaves, at depth
+//! [`MERKLE_DEPTH`], are identified by the first [`MERKLE_DEPTH`] bytes of a
+//! key. Keys shorter than that are padded with zero bytes, which keeps every
+//! node's keys a contiguous key range: the node with prefix `[0x61, 0x00]`
+//! holds `a`, `a\x00` and everything that sorts between them and `a\x01`.
+//!
+//! A leaf's hash is the [`crate::Db::range_digest`] of its key range, and an
+//! internal node's hash is the SHA-256 digest of its children's hashes, in
+//! order. Only live keys and values contribute, so the tree doesn't depend on
+//! how the data is laid out in memtables and SSTs, and is stable across
+//! flushes and compactions.
+
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use bytes::Bytes;
+use sha2::{Digest, Sha256};
+
+use crate::types::KeyValue;
+
+/// The depth of the leaves of the merkle tree, which is also the number of
+/// leading key bytes that decide which leaf a key belongs to.
+pub(crate) const MERKLE_DEPTH: usize = 2;
+
+/// A node of the merkle tree returned by [`crate::Db::merkle_root`] and
+/// [`crate::Db::merkle_children`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleNode {
+    /// The key prefix that identifies the node. Its length is the node's
+    /// depth: the root has an empty prefix.
+    pub prefix: Bytes,
+    /// The hash of the live data in the node's key range.
+    pub hash: [u8; 32],
+}
+
+impl MerkleNode {
+    /// Returns true if the node is a leaf, so it has no children.
+    pub fn is_leaf(&self) -> bool {
+        self.prefix.len() >= MERKLE_DEPTH
+    }
+
+    /// Returns the range of keys covered by the node, e.g. to scan the keys of
+    /// a divergent leaf.
+    pub fn key_range(&self) -> (Bound<Bytes>, Bound<Bytes>) {
+        key_range(&self.prefix)
+    }
+}
+
+/// Returns the range of keys whose zero-padded leading bytes start with
+/// `prefix`.
+pub(crate) fn key_range(prefix: &[u8]) -> (Bound<Bytes>, Bound<Bytes>) {
+    let start_len = prefix.len() - prefix.iter().rev().take_while(|b| **b == 0).count();
+    let start = if start_len == 0 {
+        Bound::Unbounded
+    } else {
+        Bound::Included(Bytes::copy_from_slice(&prefix[..start_len]))
+    };
+    // the end is the next prefix of the same length, with any carry dropping
+    // the trailing 0xff bytes
+    let end = match prefix.iter().rposition(|b| *b != u8::MAX) {
+        Some(i) => {
+            let mut end = prefix[..=i].to_vec();
+            end[i] += 1;
+            Bound::Excluded(Bytes::from(end))
+        }
+        None => Bound::Unbounded,
+    };
+    (start, end)
+}
+
+/// Accumulates the leaf hashes of the live entries of a key range, visited in
+/// ascending key order.
+pub(crate) struct MerkleLeaves {
+    leaves: BTreeMap<Vec<u8>, [u8; 32]>,
+    current: Option<(Vec<u8>, Sha256)>,
+}
+
+impl MerkleLeaves {
+    pub(crate) fn new() -> Self {
+        Self {
+            leaves: BTreeMap::new(),
+            current: None,
+        }
+    }
+
+    /// Adds a live entry to the hash of its leaf, using the same byte layout
+    /// as [`crate::Db::range_digest`].
+    pub(crate) fn update(&mut self, kv: &KeyValue) {
+        let mut leaf = kv.key[..kv.key.len().min(MERKLE_DEPTH)].to_vec();
+        leaf.resize(MERKLE_DEPTH, 0);
+        if self.current.as_ref().map(|(prefix, _)| prefix) != Some(&leaf) {
+            self.finish_leaf();
+            self.current = Some((leaf, Sha256::new()));
+        }
+        let (_, hasher) = self.current.as_mut().expect("current leaf was just set");
+        hasher.update((kv.key.len() as u32).to_be_bytes());
+        hasher.update(&kv.key);
+        hasher.update((kv.value.len() as u32).to_be_bytes());
+        hasher.update(&kv.value);
+    }
+
+    fn finish_leaf(&mut self) {
+        if let Some((prefix, hasher)) = self.current.take() {
+            self.leaves.insert(prefix, hasher.finalize().into());
+        }
+    }
+
+    /// Returns the node with the given prefix.
+    pub(crate) fn node(&mut self, prefix: &[u8]) -> MerkleNode {
+        self.finish_leaf();
+        self.node_with(prefix.to_vec(), &empty_hashes())
+    }
+
+    /// Returns the children of the node with the given prefix, or no nodes if
+    /// it is a leaf.
+    pub(crate) fn children(&mut self, prefix: &[u8]) -> Vec<MerkleNode> {
+        if prefix.len() >= MERKLE_DEPTH {
+            return Vec::new();
+        }
+        self.finish_leaf();
+        let empty_hashes = empty_hashes();
+        (0..=u8::MAX)
+            .map(|byte| {
+                let mut child = prefix.to_vec();
+                child.push(byte);
+                self.node_with(child, &empty_hashes)
+            })
+            .collect()
+    }
+
+    fn node_with(&self, mut prefix: Vec<u8>, empty_hashes: &[[u8; 32]]) -> MerkleNode {
+        let hash = self.subtree_hash(&mut prefix, empty_hashes);
+        MerkleNode {
+            prefix: Bytes::from(prefix),
+            hash,
+        }
+    }
+
+    fn subtree_hash(&self, path: &mut Vec<u8>, empty_hashes: &[[u8; 32]]) -> [u8; 32] {
+        let height = MERKLE_DEPTH - path.len();
+        if height == 0 {
+            return self
+                .leaves
+                .get(path.as_slice())
+                .copied()
+                .unwrap_or(empty_hashes[0]);
+        }
+        let has_leaves = self
+            .leaves
+            .range(path.clone()..)
+            .next()
+            .is_some_and(|(leaf, _)| leaf.starts_with(path));
+        if !has_leaves {
+            return empty_hashes[height];
+        }
+        let mut hasher = Sha256::new();
+        for byte in 0..=u8::MAX {
+            path.push(byte);
+            hasher.update(self.subtree_hash(path, empty_hashes));
+            path.pop();
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// Returns the hash of an empty subtree of each height, from the leaves up.
+fn empty_hashes() -> Vec<[u8; 32]> {
+    let mut hashes: Vec<[u8; 32]> = vec![Sha256::digest(b"").into()];
+    for height in 1..=MERKLE_DEPTH {
+        let mut hasher = Sha256::new();
+        for _ in 0..=u8::MAX {
+            hasher.update(hashes[height - 1]);
+        }
+        hashes.push(hasher.finalize().into());
+    }
+    hashes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bound(key: &'static [u8]) -> Bound<Bytes> {
+        Bound::Included(Bytes::from_static(key))
+    }
+
+    #[test]
+    fn should_map_prefixes_to_contiguous_key_ranges() {
+        assert_eq!(key_range(b""), (Bound::Unbounded, Bound::Unbounded));
+        assert_eq!(
+            key_range(b"a\x00"),
+            (bound(b"a"), Bound::Excluded(Bytes::from_static(b"a\x01")))
+        );
+        assert_eq!(
+            key_range(b"a\xff"),
+            (bound(b"a\xff"), Bound::Excluded(Bytes::from_static(b"b")))
+        );
+        assert_eq!(
+            key_range(b"\xff\xff"),
+            (bound(b"\xff\xff"), Bound::Unbounded)
+        );
+        assert_eq!(
+            key_range(b"\x00\x00"),
+            (
+                Bound::Unbounded,
+                Bound::Excluded(Bytes::from_static(b"\x00\x01"))
+            )
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! Batched point lookups for [`crate::Db::get_multi`].
+//!
+//! The keys are looked up one source at a time, from the newest to the
+//! oldest: the memtables, then each segment's L0 SSTs, then its sorted runs.
+//! A key is no longer looked up once a source returns a version of it that
+//! isn't a merge operand, since older versions can't change its value.
+//!
+//! Within an SST, the keys are first checked against the SST's filters. The
+//! index is then used to find the blocks that may hold the remaining keys,
+//! and those blocks are fetched together, with runs of adjacent blocks read
+//! in a single range read, so keys that share a block cost one fetch.
+
+use std::collections::HashMap;
+use std::ops::{Bound, Range};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::try_join_all;
+use futures::{StreamExt, TryStreamExt};
+
+use crate::block_iterator::DataBlockIterator;
+use crate::bytes_range::BytesRange;
+use crate::config::ReadOptions;
+use crate::db_state::SsTableView;
+use crate::db_stats::DbStats;
+use crate::error::SlateDBError;
+use crate::filter_policy::FilterQuery;
+use crate::iter::{IterationOrder, RowEntryIterator};
+use crate::partitioned_keyspace;
+use crate::reader::DbStateReader;
+use crate::tablestore::TableStore;
+use crate::types::{RowEntry, ValueDeletable};
+
+/// The number of SSTs of a sorted run that are read at once.
+const MAX_PARALLEL_SST_READS: usize = 4;
+
+/// Shared inputs for the lookups of one [`crate::Db::get_multi`] call.
+pub(crate) struct MultiGetContext<'a> {
+    pub(crate) table_store: &'a TableStore,
+    pub(crate) options: &'a ReadOptions,
+    pub(crate) db_stats: &'a DbStats,
+    pub(crate) max_seq: Option<u64>,
+}
+
+/// Reads the versions of `keys` that are visible at the context's `max_seq`.
+/// `keys` must be sorted and free of duplicates. Returns the versions of each
+/// key, newest first, stopping at the first version that isn't a merge
+/// operand.
+pub(crate) async fn read_versions(
+    keys: &[Bytes],
+    db_state: &(dyn DbStateReader + Sync + Send),
+    ctx: &MultiGetContext<'_>,
+) -> Result<Vec<Vec<RowEntry>>, SlateDBError> {
+    let mut versions = vec![Vec::new(); keys.len()];
+    let mut memtables = vec![db_state.memtable()];
+    memtables.extend(db_state.imm_memtable().iter().map(|imm| imm.table()));
+    for (key, found) in keys.iter().zip(versions.iter_mut()) {
+        for table in memtables.iter() {
+            if is_resolved(found) {
+                break;
+            }
+            if !table.might_contain(key) {
+                continue;
+            }
+            let mut iter = table.range_ascending(key.clone()..=key.clone());
+            while let Some(entry) = iter.next_sync() {
+                push_visible(found, entry, ctx.max_seq);
+            }
+        }
+    }
+
+    let (Some(first), Some(last)) = (keys.first(), keys.last()) else {
+        return Ok(versions);
+    };
+    let range = BytesRange::from(first.clone()..=last.clone());
+    for segment in db_state.core().select_segments(&range) {
+        let start = keys.partition_point(|key| key < &segment.prefix);
+        let end = start
+            + keys[start..]
+                .iter()
+                .take_while(|key| key.starts_with(&segment.prefix))
+                .count();
+        let segment_keys = start..end;
+        for view in segment.tree.l0.iter() {
+            let key_idxs = unresolved(&versions, segment_keys.clone())
+                .filter(|&i| covers(view, &keys[i]))
+                .collect::<Vec<_>>();
+            if !key_idxs.is_empty() {
+                read_sources(vec![(view, key_idxs)], keys, &mut versions, ctx).await?;
+            }
+        }
+        for sr in segment.tree.compacted.iter() {
+            let mut sources: Vec<(&SsTableView, Vec<usize>)> = Vec::new();
+            let mut source_idxs = HashMap::new();
+            for i in unresolved(&versions, segment_keys.clone()).collect::<Vec<_>>() {
+                for view in sr.tables_covering_point_key(&keys[i]) {
+                    if !covers(view, &keys[i]) {
+                        continue;
+                    }
+                    let source = *source_idxs.entry(view.id).or_insert_with(|| {
+                        sources.push((view, Vec::new()));
+                        sources.len() - 1
+                    });
+                    sources[source].1.push(i);
+                }
+            }
+            if !sources.is_empty() {
+                read_sources(sources, keys, &mut versions, ctx).await?;
+            }
+        }
+    }
+    Ok(versions)
+}
+
+/// Reads the given keys from each SST in `sources` and appends their versions,
+/// in the order of `sources`.
+async fn read_sources(
+    sources: Vec<(&SsTableView, Vec<usize>)>,
+    keys: &[Bytes],
+    versions: &mut [Vec<RowEntry>],
+    ctx: &MultiGetContext<'_>,
+) -> Result<(), SlateDBError> {
+    let results = futures::stream::iter(sources.iter())
+        .map(|(view, key_idxs)| {
+            let view_keys = key_idxs.iter().map(|&i| &keys[i]).collect::<Vec<_>>();
+            async move { read_keys_from_sst(view, &view_keys, ctx).await }
+        })
+        .buffered(MAX_PARALLEL_SST_READS)
+        .try_collect::<Vec<_>>()
+        .await?;
+    for ((_, key_idxs), sst_versions) in sources.iter().zip(results) {
+        for (&i, entries) in key_idxs.iter().zip(sst_versions) {
+            for entry in entries {
+                push_visible(&mut versions[i], entry, ctx.max_seq);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads the versions of `keys` from the SST behind `view`. `keys` must be
+/// sorted and within the view's range. Returns every version of each key that
+/// the SST holds, newest first.
+async fn read_keys_from_sst(
+    view: &SsTableView,
+    keys: &[&Bytes],
+    ctx: &MultiGetContext<'_>,
+) -> Result<Vec<Vec<RowEntry>>, SlateDBError> {
+    let sst = &view.sst;
+    let cache_blocks = ctx.options.cache_blocks;
+    let filters = ctx.table_store.read_filters(sst, cache_blocks).await?;
+    let mut candidates = Vec::with_capacity(keys.len());
+    for (i, key) in keys.iter().enumerate() {
+        if filters.is_empty() {
+            candidates.push(i);
+            continue;
+        }
+        let query =
+            FilterQuery::point((*key).clone()).with_context(ctx.options.filter_context.clone());
+        if filters.iter().all(|nf| nf.filter.might_match(&query)) {
+            ctx.db_stats.sst_filter_point_positives.increment(1);
+            candidates.push(i);
+        } else {
+            ctx.db_stats.sst_filter_point_negatives.increment(1);
+        }
+    }
+    let mut versions = vec![Vec::new(); keys.len()];
+    if candidates.is_empty() {
+        return Ok(versions);
+    }
+
+    let index = ctx.table_store.read_index(sst, cache_blocks).await?;
+    let block_ranges = candidates
+        .iter()
+        .map(|&i| {
+            let key = Bound::Included(keys[i].as_ref());
+            partitioned_keyspace::partitions_covering_range(&index.borrow(), key, key)
+        })
+        .collect::<Vec<_>>();
+    // the keys are sorted, so their block ranges are too
+    let mut runs: Vec<Range<usize>> = Vec::new();
+    for blocks in block_ranges.iter().filter(|blocks| !blocks.is_empty()) {
+        match runs.last_mut() {
+            Some(run) if blocks.start <= run.end => run.end = run.end.max(blocks.end),
+            _ => runs.push(blocks.clone()),
+        }
+    }
+    let fetched = try_join_all(runs.iter().map(|run| {
+        ctx.table_store
+            .read_blocks_using_index(sst, index.clone(), run.clone(), cache_blocks)
+    }))
+    .await?;
+    let blocks = runs
+        .into_iter()
+        .zip(fetched)
+        .flat_map(|(run, blocks)| run.zip(blocks))
+        .collect::<HashMap<_, _>>();
+
+    for (&i, block_range) in candidates.iter().zip(block_ranges) {
+        let key = keys[i];
+        'blocks: for block_idx in block_range {
+            let mut iter = DataBlockIterator::new(
+                blocks[&block_idx].clone(),
+                sst.format_version,
+                IterationOrder::Ascending,
+            )?;
+            iter.seek(key).await?;
+            while let Some(entry) = iter.next().await? {
+                if entry.key != key {
+                    break 'blocks;
+                }
+                versions[i].push(entry);
+            }
+        }
+        if versions[i].is_empty() && !filters.is_empty() {
+            ctx.db_stats.sst_filter_point_false_positives.increment(1);
+        }
+    }
+    Ok(versions)
+}
+
+fn covers(view: &SsTableView, key: &Bytes) -> bool {
+    view.calculate_view_range(BytesRange::from(key.clone()..=key.clone()))
+        .is_some()
+}
+
+fn unresolved(versions: &[Vec<RowEntry>], idxs: Range<usize>) -> impl Iterator<Item = usize> + '_ {
+    idxs.filter(|&i| !is_resolved(&versions[i]))
+}
+
+/// Whether the versions found so far determine the key's value, i.e. whether
+/// one of them is a value or a tombstone rather than a merge operand.
+fn is_resolved(versions: &[RowEntry]) -> bool {
+    versions
+        .iter()
+        .any(|entry| !matches!(entry.value, ValueDeletable::Merge(_)))
+}
+
+fn push_visible(versions: &mut Vec<RowEntry>, entry: RowEntry, max_seq: Option<u64>) {
+    if !is_resolved(versions) && max_seq.is_none_or(|max_seq| entry.seq <= max_seq) {
+        versions.push(entry);
+    }
+}
+
+/// Returns the versions of a single key read by [`read_versions`], so that
+/// they can be resolved by a [`crate::DbIterator`] like those of a get.
+pub(crate) struct VersionsIterator {
+    versions: std::vec::IntoIter<RowEntry>,
+}
+
+impl VersionsIterator {
+    pub(crate) fn new(versions: Vec<RowEntry>) -> Self {
+        Self {
+            versions: versions.into_iter(),
+        }
+    }
+}
+
+#[async_trait]
+impl RowEntryIterator for VersionsIterator {
+    async fn init(&mut self) -> Result<(), SlateDBError> {
+        Ok(())
+    }
+
+    async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        Ok(self.versions.next())
+    }
+
+    async fn seek(&mut self, _next_key: &[u8]) -> Result<(), SlateDBError> {
+        // all of the versions are of the same key
+        Ok(())
+    }
+}
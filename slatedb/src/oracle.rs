@@ -3,6 +3,31 @@ use std::sync::atomic::Ordering::SeqCst;
 
 use crate::db_status::DbStatusManager;
 
+/// Assigns the sequence numbers of a [`crate::Db`]'s writes. Set with
+/// [`crate::DbBuilder::with_sequence_allocator`], e.g. to pin the sequence
+/// numbers in a test or to preserve those of the data being imported.
+///
+/// The allocator is consulted once for every write batch, unless the batch's
+/// [`crate::config::WriteOptions::seqnum`] is set. Writes are allocated one at
+/// a time, in the order they are applied.
+pub trait SequenceAllocator: Send + Sync {
+    /// Returns the sequence number of the next write, given the sequence number
+    /// of the last write (or of the last write recovered on open). It must be
+    /// greater than `last_seq` unless the database is in import mode, see
+    /// [`crate::DbBuilder::with_sequence_import_mode`], or the write fails.
+    fn next_seq(&self, last_seq: u64) -> u64;
+}
+
+/// The default [`SequenceAllocator`], which numbers writes consecutively.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MonotonicSequenceAllocator;
+
+impl SequenceAllocator for MonotonicSequenceAllocator {
+    fn next_seq(&self, last_seq: u64) -> u64 {
+        last_seq + 1
+    }
+}
+
 /// Oracle is a trait that centralizes the generation & maintenance of various
 /// sequence numbers. These sequence numbers are mostly related to the lifecycle
 /// of a transaction commit.
@@ -41,6 +66,7 @@ impl DbOracle {
         }
     }
 
+    #[cfg(test)]
     pub(crate) fn next_seq(&self) -> u64 {
         self.last_seq.fetch_add(1, SeqCst) + 1
     }
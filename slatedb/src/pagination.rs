@@ -0,0 +1,327 @@
+//! Paginated scans with continuation tokens. See [`Db::scan_page`].
+
+use std::ops::{Bound, RangeBounds};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::bytes_range::BytesRange;
+use crate::config::ScanOptions;
+use crate::db::Db;
+use crate::db_snapshot::DbSnapshot;
+use crate::error::SlateDBError;
+use crate::oracle::Oracle;
+use crate::types::KeyValue;
+
+/// The version of the [`PageToken`] encoding.
+const PAGE_TOKEN_VERSION: u8 = 1;
+
+/// The most entries a page allocates room for up front. A larger page grows
+/// as it's filled, so that a large `limit` doesn't allocate for entries the
+/// range may not hold.
+const MAX_PREALLOCATED_ENTRIES: usize = 1024;
+
+/// An opaque token that resumes a paginated scan where the previous page
+/// ended. See [`Db::scan_page`].
+///
+/// A token holds the key the next page starts at and the sequence number
+/// watermark the scan reads at, so that every page of a scan sees the same
+/// version of the db. Use [`PageToken::to_bytes`] to send a token to a client
+/// and [`PageToken::from_bytes`] to read it back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageToken {
+    watermark: u64,
+    next_key: Bytes,
+}
+
+impl PageToken {
+    /// Encodes the token.
+    pub fn to_bytes(&self) -> Bytes {
+        let mut buf = BytesMut::with_capacity(1 + 8 + self.next_key.len());
+        buf.put_u8(PAGE_TOKEN_VERSION);
+        buf.put_u64(self.watermark);
+        buf.put_slice(&self.next_key);
+        buf.freeze()
+    }
+
+    /// Decodes a token encoded by [`PageToken::to_bytes`].
+    ///
+    /// ## Errors
+    /// - `Error` with [`crate::ErrorKind::Invalid`]: if `bytes` isn't an
+    ///   encoded token
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, crate::Error> {
+        let invalid = |reason: &str| SlateDBError::InvalidPageToken {
+            reason: reason.to_string(),
+        };
+        let (&version, rest) = bytes.split_first().ok_or_else(|| invalid("empty token"))?;
+        if version != PAGE_TOKEN_VERSION {
+            return Err(invalid(&format!("unknown version {}", version)).into());
+        }
+        let (watermark, next_key) = rest
+            .split_first_chunk::<8>()
+            .ok_or_else(|| invalid("token is truncated"))?;
+        Ok(Self {
+            watermark: u64::from_be_bytes(*watermark),
+            next_key: Bytes::copy_from_slice(next_key),
+        })
+    }
+}
+
+/// A page of entries returned by [`Db::scan_page`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanPage {
+    /// The entries of the page, in ascending key order.
+    pub entries: Vec<KeyValue>,
+    /// The token that resumes the scan after this page, or `None` if this is
+    /// the last page.
+    pub next_token: Option<PageToken>,
+}
+
+impl Db {
+    /// Scans up to `limit` entries of `range` in ascending key order, starting
+    /// where the page that returned `token` ended, or at the start of the
+    /// range if `token` is `None`.
+    ///
+    /// The first page of a scan reads the db as of the latest committed write,
+    /// and records its sequence number as the watermark in the token it
+    /// returns. Later pages read at that watermark, so writes made after the
+    /// first page aren't seen and no key is returned twice or skipped, even if
+    /// memtables are flushed between pages.
+    ///
+    /// No state is held between pages, so a watermark only stays readable
+    /// until newer writes are flushed to L0, which may merge away the versions
+    /// it reads. Resuming after that fails, and the scan must be restarted
+    /// without a token.
+    ///
+    /// ## Arguments
+    /// - `range`: the range of keys to scan, which must be the same for every
+    ///   page of a scan
+    /// - `limit`: the maximum number of entries in the page
+    /// - `token`: the token returned with the previous page, or `None` for the
+    ///   first page
+    ///
+    /// ## Returns
+    /// - `Ok(ScanPage)`: the entries of the page and the token for the next one
+    ///
+    /// ## Errors
+    /// - `Error` with [`crate::ErrorKind::Invalid`]: if `limit` is 0, the range
+    ///   is invalid, the token's key is outside of the range, or the token's
+    ///   watermark is no longer readable
+    /// - `Error`: if there was an error scanning the range
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use slatedb::{Db, Error, PageToken};
+    /// use slatedb::object_store::{ObjectStore, memory::InMemory};
+    /// use std::sync::Arc;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Error> {
+    ///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+    ///     let db = Db::open("test_db", object_store).await?;
+    ///     for key in [b"a", b"b", b"c"] {
+    ///         db.put(key, b"value").await?;
+    ///     }
+    ///
+    ///     let page = db.scan_page::<&[u8], _>(.., 2, None).await?;
+    ///     assert_eq!(page.entries.len(), 2);
+    ///     // the token can be sent to a client and read back later
+    ///     let token = PageToken::from_bytes(&page.next_token.unwrap().to_bytes())?;
+    ///     let page = db.scan_page::<&[u8], _>(.., 2, Some(&token)).await?;
+    ///     assert_eq!(page.entries[0].key.as_ref(), b"c");
+    ///     assert!(page.next_token.is_none());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn scan_page<K, T>(
+        &self,
+        range: T,
+        limit: usize,
+        token: Option<&PageToken>,
+    ) -> Result<ScanPage, crate::Error>
+    where
+        K: AsRef<[u8]> + Send,
+        T: RangeBounds<K> + Send,
+    {
+        self.inner.check_closed()?;
+        if limit == 0 {
+            return Err(crate::Error::invalid(
+                "page limit must be at least 1".to_string(),
+            ));
+        }
+        let range = BytesRange::try_from_scan_range(&range)?;
+        let (snapshot, start) = match token {
+            Some(token) => {
+                if !range.contains(&token.next_key) {
+                    return Err(SlateDBError::InvalidPageToken {
+                        reason: format!("key {:?} is outside of the range", token.next_key),
+                    }
+                    .into());
+                }
+                let snapshot = DbSnapshot::new(self.inner.clone(), Some(token.watermark));
+                (snapshot, Bound::Included(token.next_key.clone()))
+            }
+            None => (
+                DbSnapshot::new(self.inner.clone(), None),
+                range.start_bound().cloned(),
+            ),
+        };
+        let mut iter = snapshot
+            .scan_with_options((start, range.end_bound().cloned()), &ScanOptions::default())
+            .await?;
+        // The snapshot keeps flushes from merging away the versions at the
+        // watermark from now on, and the scan reads the memtables and SSTs
+        // that existed when it was created, so the watermark only needs to be
+        // checked against the SSTs flushed before that.
+        if token.is_some() {
+            self.check_watermark(snapshot.seq())?;
+        }
+
+        let mut entries = Vec::with_capacity(limit.min(MAX_PREALLOCATED_ENTRIES));
+        while entries.len() < limit {
+            match iter.next().await? {
+                Some(entry) => entries.push(entry),
+                None => break,
+            }
+        }
+        let next_token = if entries.len() == limit {
+            iter.next().await?.map(|entry| PageToken {
+                watermark: snapshot.seq(),
+                next_key: entry.key,
+            })
+        } else {
+            None
+        };
+        Ok(ScanPage {
+            entries,
+            next_token,
+        })
+    }
+
+    /// Checks that every version visible at `watermark` is still readable,
+    /// which holds as long as no write newer than it has been flushed to L0.
+    fn check_watermark(&self, watermark: u64) -> Result<(), SlateDBError> {
+        let min_watermark = self.inner.state.read().state().core().last_l0_seq;
+        let max_watermark = self.inner.oracle.last_committed_seq();
+        if watermark < min_watermark || watermark > max_watermark {
+            return Err(SlateDBError::PageTokenExpired {
+                watermark,
+                min_watermark,
+                max_watermark,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{FlushOptions, FlushType};
+    use crate::ErrorKind;
+    use object_store::memory::InMemory;
+    use std::sync::Arc;
+
+    async fn open_db_with_keys(keys: impl IntoIterator<Item = String>) -> Db {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        for key in keys {
+            db.put(key, b"value").await.unwrap();
+        }
+        db
+    }
+
+    async fn flush_memtable(db: &Db) {
+        db.flush_with_options(FlushOptions {
+            flush_type: FlushType::MemTable,
+        })
+        .await
+        .unwrap();
+    }
+
+    fn keys(page: &ScanPage) -> Vec<String> {
+        page.entries
+            .iter()
+            .map(|entry| String::from_utf8(entry.key.to_vec()).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn should_resume_without_duplicates_or_skips_across_flushes() {
+        let expected: Vec<_> = (0..10).map(|i| format!("key{i:02}")).collect();
+        let db = open_db_with_keys(expected.clone()).await;
+        let range = b"key00".to_vec()..b"key99".to_vec();
+
+        let mut scanned = Vec::new();
+        let mut token: Option<PageToken> = None;
+        for page_num in 0.. {
+            let page = db
+                .scan_page(range.clone(), 3, token.as_ref())
+                .await
+                .unwrap();
+            scanned.extend(keys(&page));
+            match page_num {
+                // memtables rotate and flush between pages
+                0 => flush_memtable(&db).await,
+                // writes made after the first page aren't seen
+                1 => {
+                    db.put(b"key055", b"value").await.unwrap();
+                }
+                _ => {}
+            }
+            // the token survives a round trip through a client
+            token = match page.next_token {
+                Some(next) => Some(PageToken::from_bytes(&next.to_bytes()).unwrap()),
+                None => break,
+            };
+        }
+        assert_eq!(scanned, expected);
+    }
+
+    #[tokio::test]
+    async fn should_return_whole_range_when_limit_is_usize_max() {
+        let expected: Vec<_> = (0..4).map(|i| format!("key{i}")).collect();
+        let db = open_db_with_keys(expected.clone()).await;
+
+        let page = db
+            .scan_page::<&[u8], _>(.., usize::MAX, None)
+            .await
+            .unwrap();
+        assert_eq!(keys(&page), expected);
+        assert!(page.next_token.is_none());
+    }
+
+    #[tokio::test]
+    async fn should_reject_tokens_whose_watermark_was_flushed_over() {
+        let db = open_db_with_keys((0..4).map(|i| format!("key{i}"))).await;
+        let page = db.scan_page::<&[u8], _>(.., 2, None).await.unwrap();
+        let token = page.next_token.unwrap();
+
+        db.put(b"key1", b"new value").await.unwrap();
+        flush_memtable(&db).await;
+
+        let result = db.scan_page::<&[u8], _>(.., 2, Some(&token)).await;
+        assert!(matches!(result, Err(err) if err.kind() == ErrorKind::Invalid));
+        // restarting the scan sees the new write
+        let page = db.scan_page::<&[u8], _>(.., 2, None).await.unwrap();
+        assert_eq!(page.entries[1].value.as_ref(), b"new value");
+    }
+
+    #[tokio::test]
+    async fn should_reject_malformed_tokens() {
+        let db = open_db_with_keys((0..4).map(|i| format!("key{i}"))).await;
+        for bytes in [&b""[..], &[2, 0, 0, 0, 0, 0, 0, 0, 1], &[1, 0, 0, 0]] {
+            let err = PageToken::from_bytes(bytes).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Invalid);
+        }
+
+        let page = db.scan_page::<&[u8], _>(.., 1, None).await.unwrap();
+        let token = page.next_token.unwrap();
+        let result = db.scan_page(b"key2".to_vec().., 1, Some(&token)).await;
+        assert!(matches!(result, Err(err) if err.kind() == ErrorKind::Invalid));
+        let result = db.scan_page::<&[u8], _>(.., 0, None).await;
+        assert!(matches!(result, Err(err) if err.kind() == ErrorKind::Invalid));
+    }
+}
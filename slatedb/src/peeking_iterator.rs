@@ -65,6 +65,10 @@ impl<T: TrackedRowEntryIterator> TrackedRowEntryIterator for PeekingIterator<T>
     fn bytes_processed(&self) -> u64 {
         self.iterator.bytes_processed()
     }
+
+    fn entries_processed(&self) -> u64 {
+        self.iterator.entries_processed()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,143 @@
+//! Projecting the values returned by a scan. See
+//! [`crate::DbIterator::with_projector`].
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::error::SlateDBError;
+use crate::iter::RowEntryIterator;
+use crate::types::{RowEntry, ValueDeletable};
+
+/// Trims the values returned by a scan, e.g. to the fields of a structured
+/// value that the caller reads, so that the rest isn't copied out of the
+/// scan.
+///
+/// A projector is called once per value as the scan returns it, after any
+/// merge operands for the key have been merged. It is not called for deleted
+/// keys.
+pub trait Projector: Send + Sync {
+    /// Returns the projection of `value`.
+    fn project(&self, value: &[u8]) -> Bytes;
+}
+
+/// Applies a [`Projector`] to the values returned by an iterator.
+pub(crate) struct ProjectingIterator {
+    inner: Box<dyn RowEntryIterator + 'static>,
+    projector: Arc<dyn Projector>,
+}
+
+impl ProjectingIterator {
+    pub(crate) fn new(
+        inner: Box<dyn RowEntryIterator + 'static>,
+        projector: Arc<dyn Projector>,
+    ) -> Self {
+        Self { inner, projector }
+    }
+}
+
+#[async_trait]
+impl RowEntryIterator for ProjectingIterator {
+    async fn init(&mut self) -> Result<(), SlateDBError> {
+        self.inner.init().await
+    }
+
+    async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        let Some(mut entry) = self.inner.next().await? else {
+            return Ok(None);
+        };
+        if let ValueDeletable::Value(value) = &entry.value {
+            entry.value = ValueDeletable::Value(self.projector.project(value));
+        }
+        Ok(Some(entry))
+    }
+
+    async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
+        self.inner.seek(next_key).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ScanOptions;
+    use crate::iter::IterationOrder;
+    use crate::test_utils::TestIterator;
+    use crate::Db;
+    use object_store::memory::InMemory;
+
+    /// Keeps the first field of a `;`-separated value.
+    struct FirstField;
+
+    impl Projector for FirstField {
+        fn project(&self, value: &[u8]) -> Bytes {
+            let end = value.iter().position(|b| *b == b';').unwrap_or(value.len());
+            Bytes::copy_from_slice(&value[..end])
+        }
+    }
+
+    #[tokio::test]
+    async fn should_project_values_and_preserve_keys_and_order() {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        db.put(b"user/1", b"ada;1815;mathematician").await.unwrap();
+        db.put(b"user/2", b"alan;1912;computer scientist")
+            .await
+            .unwrap();
+        db.put(b"user/3", b"grace;1906;rear admiral").await.unwrap();
+        db.delete(b"user/2").await.unwrap();
+
+        for (order, expected) in [
+            (
+                IterationOrder::Ascending,
+                vec![("user/1", "ada"), ("user/3", "grace")],
+            ),
+            (
+                IterationOrder::Descending,
+                vec![("user/3", "grace"), ("user/1", "ada")],
+            ),
+        ] {
+            let options = ScanOptions {
+                order,
+                ..ScanOptions::default()
+            };
+            let mut iter = db
+                .scan_with_options(..b"user/9".to_vec(), &options)
+                .await
+                .unwrap()
+                .with_projector(Arc::new(FirstField));
+            let mut entries = Vec::new();
+            while let Some(kv) = iter.next().await.unwrap() {
+                entries.push(kv);
+            }
+            let entries: Vec<_> = entries
+                .iter()
+                .map(|kv| (kv.key.as_ref(), kv.value.as_ref()))
+                .collect();
+            let expected: Vec<_> = expected
+                .iter()
+                .map(|(key, value)| (key.as_bytes(), value.as_bytes()))
+                .collect();
+            assert_eq!(entries, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_leave_tombstones_untouched() {
+        let inner = TestIterator::new()
+            .with_entry(b"a", b"x;y", 1)
+            .with_row_entry(RowEntry::new_tombstone(b"b", 2));
+        let mut iter = ProjectingIterator::new(Box::new(inner), Arc::new(FirstField));
+        assert_eq!(
+            iter.next().await.unwrap(),
+            Some(RowEntry::new_value(b"a", b"x", 1))
+        );
+        assert_eq!(
+            iter.next().await.unwrap(),
+            Some(RowEntry::new_tombstone(b"b", 2))
+        );
+        assert_eq!(iter.next().await.unwrap(), None);
+    }
+}
@@ -0,0 +1,87 @@
+//! Approximate size of a key range. See [`crate::Db::estimate_range`].
+
+use std::ops::RangeBounds;
+
+use futures::{StreamExt, TryStreamExt};
+
+use crate::bytes_range::BytesRange;
+use crate::db::DbInner;
+use crate::db_state::SsTableView;
+use crate::error::SlateDBError;
+use crate::partitioned_keyspace;
+
+/// The maximum number of SST indexes read at once.
+const MAX_PARALLEL_INDEX_READS: usize = 4;
+
+/// The approximate number of entries and bytes in a key range, returned by
+/// [`crate::Db::estimate_range`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RangeEstimate {
+    /// The number of entries in the range. Every version of a key and every
+    /// tombstone not yet compacted away is counted.
+    pub keys: u64,
+    /// The size of the entries in the range, in bytes.
+    pub size_bytes: u64,
+}
+
+impl DbInner {
+    /// Estimates the entries and bytes in `range`. Entries in the memtables
+    /// are counted exactly, with their estimated sizes. Each SST overlapping
+    /// the range contributes its entry count and size in proportion to the
+    /// number of its blocks that cover the range, so SSTs are estimated at
+    /// block granularity. At most [`MAX_PARALLEL_INDEX_READS`] SST indexes are
+    /// read at once.
+    pub(crate) async fn estimate_range(
+        &self,
+        range: BytesRange,
+    ) -> Result<RangeEstimate, SlateDBError> {
+        let view = self.state.read().view();
+        let mut estimate = RangeEstimate::default();
+
+        let imm_tables = view.state.imm_memtable.iter().map(|imm| imm.table());
+        for table in std::iter::once(view.memtable.clone()).chain(imm_tables) {
+            let (entries, bytes) = table.approx_range_entries_and_bytes(range.clone());
+            estimate.keys += entries as u64;
+            estimate.size_bytes += bytes as u64;
+        }
+
+        let estimates: Vec<RangeEstimate> = futures::stream::iter(
+            view.state
+                .core()
+                .all_sst_views()
+                .filter_map(|sst| Some((sst, sst.calculate_view_range(range.clone())?))),
+        )
+        .map(|(sst, sst_range)| self.estimate_sst_range(sst, sst_range))
+        .buffered(MAX_PARALLEL_INDEX_READS)
+        .try_collect()
+        .await?;
+        for sst_estimate in estimates {
+            estimate.keys += sst_estimate.keys;
+            estimate.size_bytes += sst_estimate.size_bytes;
+        }
+        Ok(estimate)
+    }
+
+    async fn estimate_sst_range(
+        &self,
+        sst: &SsTableView,
+        range: BytesRange,
+    ) -> Result<RangeEstimate, SlateDBError> {
+        let index = self.table_store.read_index(&sst.sst, true).await?;
+        let num_blocks = index.borrow().block_meta().len() as u64;
+        if num_blocks == 0 {
+            return Ok(RangeEstimate::default());
+        }
+        let covering = partitioned_keyspace::partitions_covering_range(
+            &index.borrow(),
+            range.start_bound().map(|b| b.as_ref()),
+            range.end_bound().map(|b| b.as_ref()),
+        );
+        let covering = covering.len() as u64;
+        Ok(RangeEstimate {
+            keys: sst.sst.info.num_entries * covering / num_blocks,
+            size_bytes: sst.estimate_size() * covering / num_blocks,
+        })
+    }
+}
@@ -0,0 +1,275 @@
+//! Limits the rate of writes per tenant.
+//!
+//! A write names the tenant it is made for in
+//! [`crate::config::WriteOptions::tenant`]. Before the write is admitted, the
+//! [`RateLimiter`] set with [`crate::DbBuilder::with_rate_limiter`] decides
+//! whether it goes ahead, waits, or fails with a rate limited error. Writes
+//! that don't name a tenant aren't limited.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+
+/// What a [`RateLimiter`] decides for a write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    /// The write goes ahead.
+    Admit,
+    /// The write goes ahead once the duration has passed. The limiter has
+    /// already counted it, so it isn't consulted again.
+    Wait(Duration),
+    /// The write fails with an [`crate::ErrorKind::Unavailable`] error.
+    Reject,
+}
+
+/// Decides whether a tenant's write may go ahead. Set with
+/// [`crate::DbBuilder::with_rate_limiter`].
+///
+/// The limiter is consulted once for each write that names a tenant, before
+/// the write is admitted, so a write that waits holds up neither the writes of
+/// other tenants nor the closing of the database.
+pub trait RateLimiter: Send + Sync {
+    /// Decides whether a write for `tenant` made at `now` may go ahead.
+    fn acquire(&self, tenant: &str, now: DateTime<Utc>) -> RateLimitDecision;
+}
+
+/// The rate at which a tenant may write, see [`TokenBucketRateLimiter`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TenantRateLimit {
+    /// The number of writes per second the tenant may make in the long run.
+    pub rate: f64,
+    /// The number of writes the tenant may make at once after being idle.
+    pub burst: u32,
+}
+
+/// What a [`TokenBucketRateLimiter`] does with a write over its tenant's limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RateLimitMode {
+    /// Fail the write.
+    #[default]
+    Reject,
+    /// Hold the write back until the tenant's rate allows it. Waiting writes
+    /// go ahead in the order they were made.
+    Wait,
+}
+
+/// A [`RateLimiter`] that gives each tenant a token bucket.
+///
+/// A tenant's bucket holds up to [`TenantRateLimit::burst`] tokens and refills
+/// at [`TenantRateLimit::rate`] tokens per second. Each write takes a token.
+/// Tenants without a limit aren't limited.
+///
+/// ## Examples
+///
+/// ```
+/// use slatedb::{Db, Error, ErrorKind, RateLimitMode, TenantRateLimit, TokenBucketRateLimiter};
+/// use slatedb::config::{PutOptions, WriteOptions};
+/// use slatedb::object_store::{ObjectStore, memory::InMemory};
+/// use std::sync::Arc;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Error> {
+///     let limiter = TokenBucketRateLimiter::new(RateLimitMode::Reject)
+///         .with_tenant_limit("noisy", TenantRateLimit { rate: 0.001, burst: 1 })?;
+///     let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+///     let db = Db::builder("test_db", object_store)
+///         .with_rate_limiter(Arc::new(limiter))
+///         .build()
+///         .await?;
+///
+///     let options = WriteOptions {
+///         tenant: Some("noisy".to_string()),
+///         ..WriteOptions::default()
+///     };
+///     db.put_with_options(b"k1", b"v1", &PutOptions::default(), &options).await?;
+///     let err = db
+///         .put_with_options(b"k2", b"v2", &PutOptions::default(), &options)
+///         .await
+///         .unwrap_err();
+///     assert_eq!(err.kind(), ErrorKind::Unavailable);
+///     Ok(())
+/// }
+/// ```
+pub struct TokenBucketRateLimiter {
+    mode: RateLimitMode,
+    limits: HashMap<String, TenantRateLimit>,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+struct TokenBucket {
+    /// Negative while writes are waiting for tokens that haven't refilled yet.
+    tokens: f64,
+    refilled_at: DateTime<Utc>,
+}
+
+impl TokenBucketRateLimiter {
+    pub fn new(mode: RateLimitMode) -> Self {
+        Self {
+            mode,
+            limits: HashMap::new(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Limits the writes of `tenant` to `limit`.
+    ///
+    /// ## Errors
+    /// - `Error` with [`crate::ErrorKind::Invalid`]: if the limit's rate isn't a
+    ///   positive finite number or its burst is zero
+    pub fn with_tenant_limit(
+        mut self,
+        tenant: impl Into<String>,
+        limit: TenantRateLimit,
+    ) -> Result<Self, crate::Error> {
+        if !limit.rate.is_finite() || limit.rate <= 0.0 {
+            return Err(crate::Error::invalid(format!(
+                "tenant rate must be positive and finite, got {}",
+                limit.rate
+            )));
+        }
+        if limit.burst == 0 {
+            return Err(crate::Error::invalid(
+                "tenant burst must be at least 1".to_string(),
+            ));
+        }
+        self.limits.insert(tenant.into(), limit);
+        Ok(self)
+    }
+}
+
+impl RateLimiter for TokenBucketRateLimiter {
+    fn acquire(&self, tenant: &str, now: DateTime<Utc>) -> RateLimitDecision {
+        let Some(limit) = self.limits.get(tenant) else {
+            return RateLimitDecision::Admit;
+        };
+        let mut buckets = self.buckets.lock();
+        let bucket = buckets
+            .entry(tenant.to_string())
+            .or_insert_with(|| TokenBucket {
+                tokens: limit.burst as f64,
+                refilled_at: now,
+            });
+        if now > bucket.refilled_at {
+            let elapsed = (now - bucket.refilled_at).to_std().unwrap_or_default();
+            bucket.tokens =
+                (bucket.tokens + elapsed.as_secs_f64() * limit.rate).min(limit.burst as f64);
+            bucket.refilled_at = now;
+        }
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return RateLimitDecision::Admit;
+        }
+        if self.mode == RateLimitMode::Reject {
+            return RateLimitDecision::Reject;
+        }
+        // a rate so small that the wait overflows a duration would hold the
+        // write back forever
+        match Duration::try_from_secs_f64((1.0 - bucket.tokens) / limit.rate) {
+            Ok(wait) => {
+                bucket.tokens -= 1.0;
+                RateLimitDecision::Wait(wait)
+            }
+            Err(_) => RateLimitDecision::Reject,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn at(millis: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(millis).unwrap()
+    }
+
+    fn limiter(mode: RateLimitMode) -> TokenBucketRateLimiter {
+        TokenBucketRateLimiter::new(mode)
+            .with_tenant_limit(
+                "a",
+                TenantRateLimit {
+                    rate: 10.0,
+                    burst: 2,
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_should_reject_writes_over_burst_until_refilled() {
+        let limiter = limiter(RateLimitMode::Reject);
+
+        assert_eq!(limiter.acquire("a", at(0)), RateLimitDecision::Admit);
+        assert_eq!(limiter.acquire("a", at(0)), RateLimitDecision::Admit);
+        assert_eq!(limiter.acquire("a", at(50)), RateLimitDecision::Reject);
+        assert_eq!(limiter.acquire("a", at(100)), RateLimitDecision::Admit);
+        assert_eq!(limiter.acquire("a", at(100)), RateLimitDecision::Reject);
+    }
+
+    #[test]
+    fn test_should_not_refill_past_burst() {
+        let limiter = limiter(RateLimitMode::Reject);
+
+        assert_eq!(limiter.acquire("a", at(0)), RateLimitDecision::Admit);
+        for _ in 0..2 {
+            assert_eq!(limiter.acquire("a", at(60_000)), RateLimitDecision::Admit);
+        }
+        assert_eq!(limiter.acquire("a", at(60_000)), RateLimitDecision::Reject);
+    }
+
+    #[test]
+    fn test_should_queue_waiting_writes_in_order() {
+        let limiter = limiter(RateLimitMode::Wait);
+
+        assert_eq!(limiter.acquire("a", at(0)), RateLimitDecision::Admit);
+        assert_eq!(limiter.acquire("a", at(0)), RateLimitDecision::Admit);
+        assert_eq!(
+            limiter.acquire("a", at(0)),
+            RateLimitDecision::Wait(Duration::from_millis(100))
+        );
+        assert_eq!(
+            limiter.acquire("a", at(0)),
+            RateLimitDecision::Wait(Duration::from_millis(200))
+        );
+    }
+
+    #[rstest]
+    #[case(RateLimitMode::Reject)]
+    #[case(RateLimitMode::Wait)]
+    fn test_should_limit_tenants_independently(#[case] mode: RateLimitMode) {
+        let limiter = limiter(mode)
+            .with_tenant_limit(
+                "b",
+                TenantRateLimit {
+                    rate: 1e-300,
+                    burst: 1,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(limiter.acquire("b", at(0)), RateLimitDecision::Admit);
+        assert_eq!(limiter.acquire("b", at(0)), RateLimitDecision::Reject);
+        assert_eq!(limiter.acquire("a", at(0)), RateLimitDecision::Admit);
+        for _ in 0..10 {
+            assert_eq!(limiter.acquire("c", at(0)), RateLimitDecision::Admit);
+        }
+    }
+
+    #[rstest]
+    #[case(f64::NAN, 1)]
+    #[case(f64::INFINITY, 1)]
+    #[case(0.0, 1)]
+    #[case(-1.0, 1)]
+    #[case(1.0, 0)]
+    fn test_should_reject_invalid_limits(#[case] rate: f64, #[case] burst: u32) {
+        let result = TokenBucketRateLimiter::new(RateLimitMode::Wait)
+            .with_tenant_limit("a", TenantRateLimit { rate, burst });
+
+        assert_eq!(
+            result.err().map(|err| err.kind()),
+            Some(crate::ErrorKind::Invalid)
+        );
+    }
+}
@@ -0,0 +1,464 @@
+//! Read-ahead buffering for [`RowEntryIterator`]s.
+//!
+//! Pulling one entry at a time from an iterator backed by object storage means
+//! the consumer waits on every block fetch in turn. [`ReadAheadIterator`] moves
+//! the wrapped iterator onto a spawned task that keeps up to `depth` entries
+//! buffered ahead of the consumer, so fetches overlap with whatever the consumer
+//! does with the entries it already has. Scans wrap their SST iterators in one
+//! when [`crate::config::ScanOptions::prefetch_depth`] is set.
+//!
+//! Dropping the iterator aborts the prefetch task, cancelling any in-flight
+//! fetch. Seeking stops the task gracefully, keeps the buffered entries that are
+//! still at or after the seek key in the iteration order, and only seeks the
+//! wrapped iterator when none are left.
+
+use std::collections::VecDeque;
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::SlateDBError;
+use crate::iter::{IterationOrder, RowEntryIterator};
+use crate::types::RowEntry;
+
+const READ_AHEAD_TASK_NAME: &str = "read_ahead";
+
+type Prefetched = Result<Option<RowEntry>, SlateDBError>;
+
+struct Prefetcher<T> {
+    rx: mpsc::Receiver<Prefetched>,
+    stop: CancellationToken,
+    handle: JoinHandle<T>,
+}
+
+enum ReadAheadState<T> {
+    /// The wrapped iterator is owned directly and no prefetch task is running.
+    Idle(T),
+    /// The wrapped iterator is owned by a prefetch task.
+    Prefetching(Prefetcher<T>),
+    /// The prefetch task panicked, so the wrapped iterator is gone.
+    Failed,
+}
+
+/// A [`RowEntryIterator`] wrapper that prefetches up to `depth` entries on a
+/// background task. See the module docs for details.
+pub(crate) struct ReadAheadIterator<T: RowEntryIterator + 'static> {
+    depth: usize,
+    /// The order of the wrapped iterator, which decides which buffered entries
+    /// a seek skips.
+    order: IterationOrder,
+    state: ReadAheadState<T>,
+    /// Entries prefetched before a seek that are still at or after the seek key.
+    buffered: VecDeque<RowEntry>,
+}
+
+impl<T: RowEntryIterator + 'static> ReadAheadIterator<T> {
+    /// Wraps `inner`, which iterates in `order`, buffering at most `depth`
+    /// entries ahead of the consumer. A `depth` of zero is treated as one.
+    pub(crate) fn new(inner: T, depth: usize, order: IterationOrder) -> Self {
+        Self {
+            depth: depth.max(1),
+            order,
+            state: ReadAheadState::Idle(inner),
+            buffered: VecDeque::new(),
+        }
+    }
+
+    async fn prefetch(mut inner: T, tx: mpsc::Sender<Prefetched>, stop: CancellationToken) -> T {
+        loop {
+            // Only pull from the inner iterator once there is room in the buffer.
+            let permit = tokio::select! {
+                biased;
+                _ = stop.cancelled() => return inner,
+                permit = tx.reserve() => match permit {
+                    Ok(permit) => permit,
+                    Err(_) => return inner,
+                },
+            };
+            let result = inner.next().await;
+            let done = !matches!(result, Ok(Some(_)));
+            permit.send(result);
+            if done {
+                return inner;
+            }
+        }
+    }
+
+    fn ensure_prefetching(&mut self) -> Result<&mut Prefetcher<T>, SlateDBError> {
+        if let ReadAheadState::Idle(_) = self.state {
+            let ReadAheadState::Idle(inner) =
+                std::mem::replace(&mut self.state, ReadAheadState::Failed)
+            else {
+                unreachable!("state was checked to be idle");
+            };
+            let (tx, rx) = mpsc::channel(self.depth);
+            let stop = CancellationToken::new();
+            let handle = tokio::spawn(Self::prefetch(inner, tx, stop.clone()));
+            self.state = ReadAheadState::Prefetching(Prefetcher { rx, stop, handle });
+        }
+        match &mut self.state {
+            ReadAheadState::Prefetching(prefetcher) => Ok(prefetcher),
+            _ => Err(Self::task_failed()),
+        }
+    }
+
+    /// Stops the prefetch task, if any, and takes back the wrapped iterator.
+    /// Entries the task already buffered are appended to `self.buffered`.
+    async fn stop_prefetching(&mut self) -> Result<T, SlateDBError> {
+        match std::mem::replace(&mut self.state, ReadAheadState::Failed) {
+            ReadAheadState::Idle(inner) => Ok(inner),
+            ReadAheadState::Failed => Err(Self::task_failed()),
+            ReadAheadState::Prefetching(mut prefetcher) => {
+                prefetcher.stop.cancel();
+                let mut first_error = None;
+                // The channel closes once the task returns and drops its sender.
+                while let Some(result) = prefetcher.rx.recv().await {
+                    match result {
+                        Ok(Some(entry)) => self.buffered.push_back(entry),
+                        Ok(None) => {}
+                        Err(e) => first_error = first_error.or(Some(e)),
+                    }
+                }
+                let inner = (&mut prefetcher.handle).await.map_err(|e| {
+                    SlateDBError::BackgroundTaskPanic(format!("{READ_AHEAD_TASK_NAME}: {e}"))
+                })?;
+                match first_error {
+                    Some(e) => {
+                        self.state = ReadAheadState::Idle(inner);
+                        Err(e)
+                    }
+                    None => Ok(inner),
+                }
+            }
+        }
+    }
+
+    fn task_failed() -> SlateDBError {
+        SlateDBError::BackgroundTaskPanic(READ_AHEAD_TASK_NAME.to_string())
+    }
+}
+
+#[async_trait]
+impl<T: RowEntryIterator + 'static> RowEntryIterator for ReadAheadIterator<T> {
+    async fn init(&mut self) -> Result<(), SlateDBError> {
+        match &mut self.state {
+            ReadAheadState::Idle(inner) => inner.init().await,
+            // The task only starts after a call to next, which needs an initialized iterator.
+            ReadAheadState::Prefetching(_) => Ok(()),
+            ReadAheadState::Failed => Err(Self::task_failed()),
+        }
+    }
+
+    async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        if let Some(entry) = self.buffered.pop_front() {
+            return Ok(Some(entry));
+        }
+        let prefetcher = self.ensure_prefetching()?;
+        match prefetcher.rx.recv().await {
+            Some(result) => result,
+            // The task has already sent the end of the iterator (or an error) and exited.
+            None => Ok(None),
+        }
+    }
+
+    async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
+        let mut inner = self.stop_prefetching().await?;
+        let order = self.order;
+        while self.buffered.front().is_some_and(|entry| match order {
+            IterationOrder::Ascending => entry.key.as_ref() < next_key,
+            IterationOrder::Descending => entry.key.as_ref() > next_key,
+        }) {
+            self.buffered.pop_front();
+        }
+        // Buffered entries at or after the key precede anything the inner iterator
+        // still holds, so it only needs seeking when the buffer is exhausted.
+        let result = if self.buffered.is_empty() {
+            inner.seek(next_key).await
+        } else {
+            Ok(())
+        };
+        self.state = ReadAheadState::Idle(inner);
+        result
+    }
+}
+
+impl<T: RowEntryIterator + 'static> Drop for ReadAheadIterator<T> {
+    fn drop(&mut self) {
+        if let ReadAheadState::Prefetching(prefetcher) = &self.state {
+            prefetcher.handle.abort();
+        }
+    }
+}
+
+#[cfg(feature = "bench-internal")]
+pub mod benches {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use slatedb_common::clock::{DefaultSystemClock, SystemClock};
+
+    use super::ReadAheadIterator;
+    use crate::error::SlateDBError;
+    use crate::iter::{IterationOrder, RowEntryIterator};
+    use crate::types::{RowEntry, ValueDeletable};
+
+    pub struct ReadAheadIteratorBenchConfig {
+        pub num_entries: usize,
+        /// Entries returned per simulated block fetch.
+        pub entries_per_block: usize,
+        /// Latency of each simulated block fetch.
+        pub fetch_latency: Duration,
+        /// Time the consumer spends on each block's worth of entries.
+        pub consumer_latency: Duration,
+        /// Read-ahead depth in entries, or `None` to iterate without read-ahead.
+        pub depth: Option<usize>,
+    }
+
+    /// Simulates an SST iterator over a high-latency store by sleeping once
+    /// per block before returning the block's first entry.
+    struct HighLatencyIterator {
+        clock: Arc<dyn SystemClock>,
+        next_index: usize,
+        num_entries: usize,
+        entries_per_block: usize,
+        fetch_latency: Duration,
+    }
+
+    #[async_trait]
+    impl RowEntryIterator for HighLatencyIterator {
+        async fn init(&mut self) -> Result<(), SlateDBError> {
+            Ok(())
+        }
+
+        async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+            if self.next_index >= self.num_entries {
+                return Ok(None);
+            }
+            if self.next_index.is_multiple_of(self.entries_per_block) {
+                self.clock.sleep(self.fetch_latency).await;
+            }
+            let index = self.next_index;
+            self.next_index += 1;
+            Ok(Some(RowEntry::new(
+                Bytes::from(format!("key{index:010}")),
+                ValueDeletable::Value(Bytes::from_static(b"value")),
+                index as u64 + 1,
+                None,
+                None,
+            )))
+        }
+
+        async fn seek(&mut self, _next_key: &[u8]) -> Result<(), SlateDBError> {
+            Ok(())
+        }
+    }
+
+    pub fn read_ahead_iterator_bench<F>(config: ReadAheadIteratorBenchConfig, mut run_bench: F)
+    where
+        F: FnMut(&mut dyn FnMut()),
+    {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_time()
+            .build()
+            .expect("failed to build runtime");
+        let clock: Arc<dyn SystemClock> = Arc::new(DefaultSystemClock::new());
+
+        run_bench(&mut || {
+            runtime.block_on(async {
+                let inner = HighLatencyIterator {
+                    clock: clock.clone(),
+                    next_index: 0,
+                    num_entries: config.num_entries,
+                    entries_per_block: config.entries_per_block,
+                    fetch_latency: config.fetch_latency,
+                };
+                let mut iter: Box<dyn RowEntryIterator> = match config.depth {
+                    Some(depth) => Box::new(ReadAheadIterator::new(
+                        inner,
+                        depth,
+                        IterationOrder::Ascending,
+                    )),
+                    None => Box::new(inner),
+                };
+                iter.init().await.expect("iterator error");
+                let mut count = 0usize;
+                while let Some(entry) = iter.next().await.expect("iterator error") {
+                    std::hint::black_box(entry);
+                    count += 1;
+                    if count.is_multiple_of(config.entries_per_block) {
+                        clock.sleep(config.consumer_latency).await;
+                    }
+                }
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::test_utils::{assert_iterator, TestIterator};
+
+    fn entries(count: usize) -> Vec<RowEntry> {
+        (0..count)
+            .map(|i| {
+                RowEntry::new_value(
+                    format!("key{i:03}").as_bytes(),
+                    format!("value{i}").as_bytes(),
+                    i as u64,
+                )
+            })
+            .collect()
+    }
+
+    fn test_iterator(entries: &[RowEntry]) -> TestIterator {
+        let mut iter = TestIterator::new();
+        for entry in entries {
+            iter = iter.with_row_entry(entry.clone());
+        }
+        iter
+    }
+
+    /// Counts pulls from the wrapped iterator and blocks forever once `block`
+    /// is set, so tests can observe how far ahead the prefetch task runs.
+    struct ObservedIterator {
+        inner: TestIterator,
+        pulled: Arc<AtomicUsize>,
+        block: Arc<AtomicBool>,
+        dropped: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl RowEntryIterator for ObservedIterator {
+        async fn init(&mut self) -> Result<(), SlateDBError> {
+            self.inner.init().await
+        }
+
+        async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+            if self.block.load(Ordering::SeqCst) {
+                futures::future::pending::<()>().await;
+            }
+            self.pulled.fetch_add(1, Ordering::SeqCst);
+            self.inner.next().await
+        }
+
+        async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
+            self.inner.seek(next_key).await
+        }
+    }
+
+    impl Drop for ObservedIterator {
+        fn drop(&mut self) {
+            self.dropped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_ahead_returns_all_entries_in_order() {
+        let expected = entries(20);
+        let mut iter =
+            ReadAheadIterator::new(test_iterator(&expected), 4, IterationOrder::Ascending);
+        iter.init().await.unwrap();
+
+        assert_iterator(&mut iter, expected).await;
+        assert!(iter.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_ahead_prefetches_up_to_depth() {
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let inner = ObservedIterator {
+            inner: test_iterator(&entries(20)),
+            pulled: pulled.clone(),
+            block: Arc::new(AtomicBool::new(false)),
+            dropped: Arc::new(AtomicBool::new(false)),
+        };
+        let mut iter = ReadAheadIterator::new(inner, 3, IterationOrder::Ascending);
+        iter.init().await.unwrap();
+
+        iter.next().await.unwrap().unwrap();
+        for _ in 0..100 {
+            tokio::task::yield_now().await;
+        }
+        // One entry consumed, three buffered, and one pull waiting for room
+        // in the buffer is never started.
+        assert_eq!(pulled.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_read_ahead_seek_uses_buffered_entries() {
+        let expected = entries(10);
+        let mut iter =
+            ReadAheadIterator::new(test_iterator(&expected), 4, IterationOrder::Ascending);
+        iter.init().await.unwrap();
+
+        assert_eq!(iter.next().await.unwrap().unwrap(), expected[0]);
+        // key002 is already buffered, so the seek must not skip past it.
+        iter.seek(b"key002").await.unwrap();
+        assert_iterator(&mut iter, expected[2..].to_vec()).await;
+    }
+
+    #[tokio::test]
+    async fn test_read_ahead_descending_seek_uses_buffered_entries() {
+        let mut expected = entries(10);
+        expected.reverse();
+        let mut iter =
+            ReadAheadIterator::new(test_iterator(&expected), 4, IterationOrder::Descending);
+        iter.init().await.unwrap();
+
+        assert_eq!(iter.next().await.unwrap().unwrap(), expected[0]);
+        // Let the task fill the buffer, so the seek is served from it.
+        for _ in 0..100 {
+            tokio::task::yield_now().await;
+        }
+        // key008 is buffered but comes before key007 in descending order.
+        iter.seek(b"key007").await.unwrap();
+        assert_iterator(&mut iter, expected[2..].to_vec()).await;
+    }
+
+    #[tokio::test]
+    async fn test_read_ahead_seek_past_buffer() {
+        let expected = entries(10);
+        let mut iter =
+            ReadAheadIterator::new(test_iterator(&expected), 2, IterationOrder::Ascending);
+        iter.init().await.unwrap();
+
+        assert_eq!(iter.next().await.unwrap().unwrap(), expected[0]);
+        iter.seek(b"key007").await.unwrap();
+        assert_iterator(&mut iter, expected[7..].to_vec()).await;
+    }
+
+    #[tokio::test]
+    async fn test_read_ahead_drop_cancels_in_flight_prefetch() {
+        let block = Arc::new(AtomicBool::new(false));
+        let dropped = Arc::new(AtomicBool::new(false));
+        let inner = ObservedIterator {
+            inner: test_iterator(&entries(10)),
+            pulled: Arc::new(AtomicUsize::new(0)),
+            block: block.clone(),
+            dropped: dropped.clone(),
+        };
+        let mut iter = ReadAheadIterator::new(inner, 2, IterationOrder::Ascending);
+        iter.init().await.unwrap();
+
+        // Block the next pull so the prefetch task is stuck mid-fetch.
+        block.store(true, Ordering::SeqCst);
+        iter.ensure_prefetching().unwrap();
+        drop(iter);
+
+        for _ in 0..100 {
+            if dropped.load(Ordering::SeqCst) {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,475 @@
+//! A short-lived cache of point-read results, consulted by reads that set
+//! [`ReadOptions::max_cache_staleness`], and a non-evictable tier of keys
+//! pinned with [`crate::Db::pin_keys`].
+
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{fence, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use lru::LruCache;
+use parking_lot::Mutex;
+use slatedb_common::clock::SystemClock;
+
+use crate::config::{DurabilityLevel, ReadOptions};
+use crate::types::{is_expired, KeyValue, RowEntry, ValueDeletable};
+
+/// The maximum number of keys whose read results are cached.
+const READ_CACHE_CAPACITY: usize = 4096;
+
+struct CachedRead {
+    value: Option<KeyValue>,
+    durability_filter: DurabilityLevel,
+    dirty: bool,
+    /// When the read that produced `value` started.
+    read_at: DateTime<Utc>,
+}
+
+/// The state of a pinned key.
+#[derive(Clone)]
+enum PinnedRead {
+    /// The key's latest committed value, or `None` if it doesn't exist.
+    Fresh(Option<KeyValue>),
+    /// The key must be read again before it can be served, e.g. because it was
+    /// just pinned or a merge operand was written to it.
+    Stale,
+}
+
+/// The result of looking up a key in the pinned tier.
+pub(crate) enum PinnedLookup {
+    /// The key is pinned and its latest committed value is known.
+    Hit(Option<KeyValue>),
+    /// The key is pinned but must be read again. The result of the read should
+    /// be passed to [`ReadCache::fill_pinned`].
+    Miss,
+    /// The key isn't pinned, or the read can't be served from the pinned tier.
+    NotPinned,
+}
+
+/// The reads of a key that are in flight and may be cached.
+#[derive(Default)]
+struct InFlightReads {
+    count: usize,
+    /// Whether the key was written while the reads were in flight, in which
+    /// case they may have read the value the write replaced.
+    invalidated: bool,
+}
+
+struct CachedReads {
+    entries: LruCache<Bytes, CachedRead>,
+    in_flight: HashMap<Bytes, InFlightReads>,
+}
+
+impl CachedReads {
+    /// Ends a read of `key`, and returns whether its result may be cached.
+    fn end_read(&mut self, key: &Bytes) -> bool {
+        let Some(in_flight) = self.in_flight.get_mut(key) else {
+            return false;
+        };
+        let valid = !in_flight.invalidated;
+        in_flight.count -= 1;
+        if in_flight.count == 0 {
+            self.in_flight.remove(key);
+        }
+        valid
+    }
+
+    fn tracked_keys(&self) -> usize {
+        self.entries.len() + self.in_flight.len()
+    }
+}
+
+pub(crate) struct ReadCache {
+    reads: Mutex<CachedReads>,
+    /// Keys pinned with `Db::pin_keys`. Unlike cached reads, these are never
+    /// evicted, and writes update them in place instead of dropping them.
+    pinned: Mutex<HashMap<Bytes, PinnedRead>>,
+    /// The number of keys with a cached result or a read in flight, so that
+    /// writes can skip the lock while there are none.
+    tracked_keys: AtomicUsize,
+    /// The number of pinned keys, so that writes can skip the lock while there
+    /// are none.
+    pinned_keys: AtomicUsize,
+    clock: Arc<dyn SystemClock>,
+}
+
+impl ReadCache {
+    pub(crate) fn new(clock: Arc<dyn SystemClock>) -> Self {
+        Self {
+            reads: Mutex::new(CachedReads {
+                entries: LruCache::new(
+                    NonZeroUsize::new(READ_CACHE_CAPACITY).expect("capacity is non-zero"),
+                ),
+                in_flight: HashMap::new(),
+            }),
+            pinned: Mutex::new(HashMap::new()),
+            tracked_keys: AtomicUsize::new(0),
+            pinned_keys: AtomicUsize::new(0),
+            clock,
+        }
+    }
+
+    /// Returns the cached result for `key` if it was read with the same
+    /// visibility options as `options` and is no older than `max_staleness`.
+    pub(crate) fn get(
+        &self,
+        key: &[u8],
+        options: &ReadOptions,
+        max_staleness: Duration,
+    ) -> Option<Option<KeyValue>> {
+        let now = self.clock.now();
+        let mut reads = self.reads.lock();
+        let cached = reads.entries.get(key)?;
+        if cached.durability_filter != options.durability_filter || cached.dirty != options.dirty {
+            return None;
+        }
+        // A clock that moved backwards makes the entry look newer than it is;
+        // treat it as just read rather than failing the lookup.
+        let age = (now - cached.read_at).to_std().unwrap_or_default();
+        if age > max_staleness {
+            return None;
+        }
+        Some(cached.value.clone())
+    }
+
+    /// Starts a read of `key` whose result may be cached. The returned token
+    /// must be passed to [`ReadCache::insert`] or [`ReadCache::fill_pinned`]
+    /// once the read completes. A read that fails just drops it.
+    pub(crate) fn begin_read(&self, key: &[u8]) -> ReadToken<'_> {
+        let key = Bytes::copy_from_slice(key);
+        {
+            let mut reads = self.reads.lock();
+            reads.in_flight.entry(key.clone()).or_default().count += 1;
+            self.tracked_keys
+                .store(reads.tracked_keys(), Ordering::Relaxed);
+        }
+        // Pairs with the fence in `invalidate`: either the write sees this
+        // read in flight, or the read sees the write.
+        fence(Ordering::SeqCst);
+        ReadToken {
+            cache: self,
+            key,
+            read_at: self.clock.now(),
+            ended: false,
+        }
+    }
+
+    /// Caches the result of a read started with `token`, unless its key was
+    /// written after the read started.
+    pub(crate) fn insert(
+        &self,
+        mut token: ReadToken<'_>,
+        options: &ReadOptions,
+        value: Option<KeyValue>,
+    ) {
+        let mut reads = self.reads.lock();
+        token.ended = true;
+        if reads.end_read(&token.key) {
+            reads.entries.put(
+                token.key.clone(),
+                CachedRead {
+                    value,
+                    durability_filter: options.durability_filter,
+                    dirty: options.dirty,
+                    read_at: token.read_at,
+                },
+            );
+        }
+        self.tracked_keys
+            .store(reads.tracked_keys(), Ordering::Relaxed);
+    }
+
+    /// Drops the cached results for `keys`, and keeps the reads of `keys` in
+    /// flight from being cached. Must be called after the write to `keys` is
+    /// visible to readers. Other keys are left alone.
+    pub(crate) fn invalidate<'a>(&self, keys: impl IntoIterator<Item = &'a Bytes>) {
+        // Pairs with the fence in `begin_read`.
+        fence(Ordering::SeqCst);
+        if self.tracked_keys.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let mut reads = self.reads.lock();
+        for key in keys {
+            reads.entries.pop(key);
+            if let Some(in_flight) = reads.in_flight.get_mut(key) {
+                in_flight.invalidated = true;
+            }
+        }
+        self.tracked_keys
+            .store(reads.tracked_keys(), Ordering::Relaxed);
+    }
+
+    /// Drops every cached result. Pinned keys are kept.
+    pub(crate) fn clear(&self) {
+        let mut reads = self.reads.lock();
+        reads.entries.clear();
+        self.tracked_keys
+            .store(reads.tracked_keys(), Ordering::Relaxed);
+    }
+
+    /// Pins `keys`. Keys that weren't already pinned must be read, and the
+    /// result passed to [`ReadCache::fill_pinned`], before they're served.
+    pub(crate) fn pin<'a>(&self, keys: impl IntoIterator<Item = &'a Bytes>) {
+        let mut pinned = self.pinned.lock();
+        for key in keys {
+            pinned.entry(key.clone()).or_insert(PinnedRead::Stale);
+        }
+        self.pinned_keys.store(pinned.len(), Ordering::SeqCst);
+    }
+
+    /// Releases `keys` from the pinned tier.
+    pub(crate) fn unpin<'a>(&self, keys: impl IntoIterator<Item = &'a Bytes>) {
+        let mut pinned = self.pinned.lock();
+        for key in keys {
+            pinned.remove(key);
+        }
+        self.pinned_keys.store(pinned.len(), Ordering::SeqCst);
+    }
+
+    /// Looks up `key` in the pinned tier. Only reads of the latest committed
+    /// data, at [`DurabilityLevel::Memory`], can be served from it.
+    pub(crate) fn get_pinned(&self, key: &[u8], options: &ReadOptions) -> PinnedLookup {
+        if options.durability_filter != DurabilityLevel::Memory || options.dirty {
+            return PinnedLookup::NotPinned;
+        }
+        let pinned = self.pinned.lock();
+        match pinned.get(key) {
+            None => PinnedLookup::NotPinned,
+            Some(PinnedRead::Stale) => PinnedLookup::Miss,
+            Some(PinnedRead::Fresh(Some(kv)))
+                if is_expired(kv.expire_ts, self.clock.now().timestamp_millis()) =>
+            {
+                // let the read path decide how an expired value is served
+                PinnedLookup::Miss
+            }
+            Some(PinnedRead::Fresh(value)) => PinnedLookup::Hit(value.clone()),
+        }
+    }
+
+    /// Records the result of a read of a pinned key started with `token`,
+    /// unless the key was unpinned or written after the read started.
+    pub(crate) fn fill_pinned(&self, mut token: ReadToken<'_>, value: Option<KeyValue>) {
+        let mut pinned = self.pinned.lock();
+        let valid = {
+            let mut reads = self.reads.lock();
+            token.ended = true;
+            let valid = reads.end_read(&token.key);
+            self.tracked_keys
+                .store(reads.tracked_keys(), Ordering::Relaxed);
+            valid
+        };
+        if !valid {
+            return;
+        }
+        if let Some(entry) = pinned.get_mut(&token.key) {
+            *entry = PinnedRead::Fresh(value);
+        }
+    }
+
+    /// Returns the entries of a batch that write to pinned keys, to be passed
+    /// to [`ReadCache::update_pinned`] once the batch is visible.
+    pub(crate) fn pinned_writes(&self, entries: &[RowEntry]) -> Vec<RowEntry> {
+        // A key pinned after this check is read before it's served, and that
+        // read either sees the batch or is invalidated by it.
+        if self.pinned_keys.load(Ordering::SeqCst) == 0 {
+            return Vec::new();
+        }
+        let pinned = self.pinned.lock();
+        if pinned.is_empty() {
+            return Vec::new();
+        }
+        entries
+            .iter()
+            .filter(|entry| pinned.contains_key(&entry.key))
+            .cloned()
+            .collect()
+    }
+
+    /// Updates the pinned keys written by a batch of `entries`. Must be called
+    /// after [`ReadCache::invalidate`] for the same write.
+    pub(crate) fn update_pinned(&self, entries: Vec<RowEntry>) {
+        if entries.is_empty() {
+            return;
+        }
+        let mut pinned = self.pinned.lock();
+        if pinned.is_empty() {
+            return;
+        }
+        let mut written: HashMap<Bytes, Vec<RowEntry>> = HashMap::new();
+        for entry in entries {
+            written.entry(entry.key.clone()).or_default().push(entry);
+        }
+        for (key, mut writes) in written {
+            // a key unpinned since the batch was extracted stays unpinned
+            let Some(state) = pinned.get_mut(&key) else {
+                continue;
+            };
+            *state = match (writes.pop(), writes.is_empty()) {
+                (Some(entry), true) => match entry.value {
+                    ValueDeletable::Value(_) => PinnedRead::Fresh(Some(KeyValue::from(entry))),
+                    ValueDeletable::Tombstone => PinnedRead::Fresh(None),
+                    // the merged value depends on older versions, so it has to
+                    // be read
+                    ValueDeletable::Merge(_) => PinnedRead::Stale,
+                },
+                _ => PinnedRead::Stale,
+            };
+        }
+    }
+}
+
+/// Identifies an in-flight read for [`ReadCache::insert`].
+pub(crate) struct ReadToken<'a> {
+    cache: &'a ReadCache,
+    key: Bytes,
+    read_at: DateTime<Utc>,
+    /// Whether the read was ended by passing the token to the cache.
+    ended: bool,
+}
+
+impl Drop for ReadToken<'_> {
+    fn drop(&mut self) {
+        if self.ended {
+            return;
+        }
+        let mut reads = self.cache.reads.lock();
+        reads.end_read(&self.key);
+        self.cache
+            .tracked_keys
+            .store(reads.tracked_keys(), Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slatedb_common::clock::MockSystemClock;
+
+    fn key_value(key: &'static [u8], value: &'static [u8]) -> Option<KeyValue> {
+        Some(KeyValue {
+            key: Bytes::from_static(key),
+            value: Bytes::from_static(value),
+            seq: 1,
+            create_ts: 0,
+            expire_ts: None,
+        })
+    }
+
+    #[test]
+    fn should_serve_entries_within_staleness_bound() {
+        let clock = Arc::new(MockSystemClock::new());
+        let cache = ReadCache::new(clock.clone());
+        let options = ReadOptions::default();
+        let key = Bytes::from_static(b"key");
+
+        let token = cache.begin_read(&key);
+        cache.insert(token, &options, key_value(b"key", b"value"));
+        clock.set(10);
+
+        let ms = Duration::from_millis;
+        assert_eq!(
+            cache.get(&key, &options, ms(10)),
+            Some(key_value(b"key", b"value"))
+        );
+        assert_eq!(cache.get(&key, &options, ms(9)), None);
+        // a read with different visibility options doesn't share the entry
+        let dirty = ReadOptions::default().with_dirty(true);
+        assert_eq!(cache.get(&key, &dirty, ms(10)), None);
+    }
+
+    #[test]
+    fn should_not_cache_read_that_raced_with_write() {
+        let clock = Arc::new(MockSystemClock::new());
+        let cache = ReadCache::new(clock);
+        let options = ReadOptions::default();
+        let key = Bytes::from_static(b"key");
+
+        let token = cache.begin_read(&key);
+        cache.invalidate([&key]);
+        cache.insert(token, &options, key_value(b"key", b"old"));
+        assert_eq!(cache.get(&key, &options, Duration::MAX), None);
+
+        let token = cache.begin_read(&key);
+        cache.insert(token, &options, None);
+        assert_eq!(cache.get(&key, &options, Duration::MAX), Some(None));
+        cache.invalidate([&key]);
+        assert_eq!(cache.get(&key, &options, Duration::MAX), None);
+    }
+
+    #[test]
+    fn should_only_invalidate_written_keys() {
+        let cache = ReadCache::new(Arc::new(MockSystemClock::new()));
+        let options = ReadOptions::default();
+        let key = Bytes::from_static(b"key");
+        let other = Bytes::from_static(b"other");
+
+        // a write to another key doesn't keep an in-flight read from being cached
+        let token = cache.begin_read(&key);
+        cache.invalidate([&other]);
+        cache.insert(token, &options, key_value(b"key", b"value"));
+        assert_eq!(
+            cache.get(&key, &options, Duration::MAX),
+            Some(key_value(b"key", b"value"))
+        );
+
+        // a failed read stops being tracked once its token is dropped
+        cache.invalidate([&key]);
+        drop(cache.begin_read(&other));
+        assert_eq!(cache.tracked_keys.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn should_update_pinned_keys_in_place() {
+        let cache = ReadCache::new(Arc::new(MockSystemClock::new()));
+        let options = ReadOptions::default();
+        let key = Bytes::from_static(b"key");
+        let hit = |cache: &ReadCache| match cache.get_pinned(&key, &options) {
+            PinnedLookup::Hit(value) => Some(value),
+            PinnedLookup::Miss | PinnedLookup::NotPinned => None,
+        };
+
+        cache.pin([&key]);
+        assert!(matches!(
+            cache.get_pinned(&key, &options),
+            PinnedLookup::Miss
+        ));
+        let token = cache.begin_read(&key);
+        cache.fill_pinned(token, key_value(b"key", b"value"));
+        assert_eq!(hit(&cache), Some(key_value(b"key", b"value")));
+        // pinned keys survive clearing the cache
+        cache.clear();
+        assert_eq!(hit(&cache), Some(key_value(b"key", b"value")));
+
+        let writes = cache.pinned_writes(&[
+            RowEntry::new_tombstone(b"key", 2),
+            RowEntry::new_value(b"other", b"value", 2),
+        ]);
+        cache.invalidate([&key]);
+        cache.update_pinned(writes);
+        assert_eq!(hit(&cache), Some(None));
+
+        // a merge needs the older versions, so the key has to be read again
+        let writes = cache.pinned_writes(&[RowEntry::new_merge(b"key", b"operand", 3)]);
+        cache.invalidate([&key]);
+        cache.update_pinned(writes);
+        assert!(matches!(
+            cache.get_pinned(&key, &options),
+            PinnedLookup::Miss
+        ));
+
+        // a read that raced with a write doesn't fill the pinned entry
+        let token = cache.begin_read(&key);
+        cache.invalidate([&key]);
+        cache.fill_pinned(token, key_value(b"key", b"old"));
+        assert_eq!(hit(&cache), None);
+
+        cache.unpin([&key]);
+        assert!(matches!(
+            cache.get_pinned(&key, &options),
+            PinnedLookup::NotPinned
+        ));
+    }
+}
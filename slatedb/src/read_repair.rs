@@ -0,0 +1,131 @@
+//! Read repair for scans. See [`crate::config::ScanOptions::read_repair`].
+//!
+//! A scan that finds the newest version of a key in an SST can re-insert it
+//! into the active memtable, so that later reads of the key are served from
+//! memory. The copy is written through the write loop like any other write,
+//! with a fresh sequence number, the value that was read, and the same expiry
+//! time. The write loop drops the copy if the key was written again since the
+//! scan read it, so a repair never overwrites a newer value.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::debug;
+use rand::Rng;
+
+use crate::batch::WriteBatch;
+use crate::batch_write::WriteBatchMessage;
+use crate::config::{PutOptions, Ttl, WriteOptions};
+use crate::error::SlateDBError;
+use crate::iter::RowEntryIterator;
+use crate::rand::DbRand;
+use crate::types::{RowEntry, ValueDeletable};
+use crate::utils::SafeSender;
+
+/// The version of a key that a read repair copies, which the write loop checks
+/// is still the newest version before applying the repair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct ReadRepairCheck {
+    /// The sequence number of the version read from an SST.
+    pub(crate) seq: u64,
+    /// The last sequence number flushed to L0 when the version was read.
+    pub(crate) last_l0_seq: u64,
+}
+
+/// Samples the entries a scan returns and sends the repairs of those read from
+/// SSTs to the write loop.
+pub(crate) struct ReadRepairer {
+    write_notifier: SafeSender<WriteBatchMessage>,
+    rand: Arc<DbRand>,
+    sample_rate: f64,
+    /// The last sequence number flushed to L0 when the scan started. Entries
+    /// at or below it were read from an SST, and those above it from a
+    /// memtable.
+    last_l0_seq: u64,
+}
+
+impl ReadRepairer {
+    pub(crate) fn new(
+        write_notifier: SafeSender<WriteBatchMessage>,
+        rand: Arc<DbRand>,
+        sample_rate: f64,
+        last_l0_seq: u64,
+    ) -> Self {
+        Self {
+            write_notifier,
+            rand,
+            sample_rate,
+            last_l0_seq,
+        }
+    }
+
+    /// Called with each entry the scan returns. If the entry was read from an
+    /// SST and is sampled, sends its repair to the write loop without waiting
+    /// for it to be applied. A repair that can't be sent is dropped.
+    pub(crate) fn maybe_repair(&self, entry: &RowEntry) {
+        let ValueDeletable::Value(value) = &entry.value else {
+            return;
+        };
+        if entry.seq > self.last_l0_seq || !self.rand.rng().random_bool(self.sample_rate) {
+            return;
+        }
+        let ttl = match entry.expire_ts {
+            Some(expire_ts) => Ttl::ExpireAt(expire_ts),
+            None => Ttl::NoExpiry,
+        };
+        let mut batch = WriteBatch::new();
+        batch.put_with_options(&entry.key, value, &PutOptions { ttl });
+        batch.read_repair = Some(ReadRepairCheck {
+            seq: entry.seq,
+            last_l0_seq: self.last_l0_seq,
+        });
+        // nobody waits for the repair, so its result is dropped with the receiver
+        let (done, _) = tokio::sync::oneshot::channel();
+        let message = WriteBatchMessage {
+            batch,
+            options: WriteOptions {
+                await_durable: false,
+                ..WriteOptions::default()
+            },
+            done,
+        };
+        if let Err(e) = self.write_notifier.send(message) {
+            debug!("dropping read repair [key={:?}, error={}]", entry.key, e);
+        }
+    }
+}
+
+/// Passes the entries of a scan's merged iterator to a [`ReadRepairer`].
+///
+/// It wraps the iterator before any projector, row filter or virtual source
+/// is applied, so that repairs copy the full values read from SSTs and never
+/// the rows of a virtual source.
+pub(crate) struct ReadRepairIterator {
+    inner: Box<dyn RowEntryIterator + 'static>,
+    repairer: ReadRepairer,
+}
+
+impl ReadRepairIterator {
+    pub(crate) fn new(inner: Box<dyn RowEntryIterator + 'static>, repairer: ReadRepairer) -> Self {
+        Self { inner, repairer }
+    }
+}
+
+#[async_trait]
+impl RowEntryIterator for ReadRepairIterator {
+    async fn init(&mut self) -> Result<(), SlateDBError> {
+        self.inner.init().await
+    }
+
+    async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        let entry = self.inner.next().await?;
+        if let Some(entry) = &entry {
+            self.repairer.maybe_repair(entry);
+        }
+        Ok(entry)
+    }
+
+    async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
+        self.inner.seek(next_key).await
+    }
+}
@@ -0,0 +1,42 @@
+use crate::db_state::DbStateView;
+
+/// A pinned view of the database for deterministic reads, created by
+/// [`crate::Db::read_view`].
+///
+/// A view captures the memtables and the manifest state of the database at the
+/// moment it was created, along with the last committed sequence number. Reads
+/// through [`crate::Db::get_with_view`] and [`crate::Db::scan_with_view`] only
+/// consult the captured tables and only see writes at or below that sequence
+/// number, so they return the same results no matter how many writes, memtable
+/// freezes or flushes happen after the view is created.
+///
+/// Unlike a [`crate::DbSnapshot`], a view does not register itself with the
+/// database, so compaction may drop versions it would read and garbage
+/// collection may then delete SSTs it refers to. Reads through a long-lived
+/// view can therefore fail or miss data; views are meant for short-lived
+/// reads and for tests that need precise control over what a read observes.
+#[derive(Clone)]
+pub struct ReadView {
+    pub(crate) db_state: DbStateView,
+    pub(crate) seq: u64,
+}
+
+impl ReadView {
+    pub(crate) fn new(db_state: DbStateView, seq: u64) -> Self {
+        Self { db_state, seq }
+    }
+
+    /// Returns the highest sequence number visible through this view.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+}
+
+impl std::fmt::Debug for ReadView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReadView")
+            .field("seq", &self.seq)
+            .field("imm_memtables", &self.db_state.state.imm_memtable.len())
+            .finish()
+    }
+}
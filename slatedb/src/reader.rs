@@ -4,20 +4,27 @@ use crate::clock::MonotonicClock;
 use crate::config::{DurabilityLevel, ReadOptions, ScanOptions};
 use crate::db_iter::{apply_filters, DbRecencyIterator};
 use crate::db_stats::DbStats;
-use crate::iter::RowEntryIterator;
+use crate::expiry_iterator::{apply_expiry, ExpiryIterator};
+use crate::filter_iterator::FilterIterator;
+use crate::fused_iterator::FusedIterator;
+use crate::iter::{IterationOrder, RowEntryIterator};
 use crate::manifest::ManifestCore;
 use crate::mem_table::{ImmutableMemtable, KVTable};
+use crate::merge_iterator::MergeIterator;
 use crate::merge_operator::{instrument_merge_operator, MergeOperatorType};
+use crate::multi_get::{self, MultiGetContext, VersionsIterator};
 use crate::oracle::Oracle;
+use crate::read_ahead_iterator::ReadAheadIterator;
 use crate::segment_iterator::{build_segment_iter, SegmentScanContext};
 use crate::sorted_run_iterator::SortedRunIterator;
 use crate::sst_iter::{SstIterator, SstIteratorOptions};
 use crate::tablestore::TableStore;
-use crate::types::KeyValue;
+use crate::types::{KeyValue, RowEntry};
 use crate::{db_iter::DbIteratorRangeTracker, error::SlateDBError, DbIterator};
 
 use bytes::Bytes;
 use std::collections::VecDeque;
+use std::ops::RangeBounds;
 use std::sync::Arc;
 
 pub(crate) trait DbStateReader {
@@ -59,6 +66,15 @@ pub(crate) struct ScanContext<'a> {
     /// filters are probed with a prefix query so SSTs that do not contain the
     /// prefix can be skipped.
     pub(crate) prefix: Option<Bytes>,
+    /// Optional upper bound on the creation time of visible entries. When set,
+    /// entries created after it are filtered out before versions of a key are
+    /// deduplicated, and entries without a `create_ts` are kept. Used by
+    /// time-travel scans.
+    pub(crate) as_of_ts: Option<i64>,
+    /// Whether entries that have expired are returned as they're stored,
+    /// rather than hidden like deleted keys. Used by SST exports that ask for
+    /// expired entries.
+    pub(crate) include_expired: bool,
 }
 
 pub(crate) struct Reader {
@@ -148,11 +164,17 @@ impl Reader {
         for memtable in db_state.imm_memtable() {
             memtables.push_back(memtable.table());
         }
+        // a point lookup doesn't need to search memtables whose key filter
+        // rules the key out
+        if let Some(key) = range.as_point() {
+            memtables.retain(|table| table.might_contain(key));
+        }
         let mem_iters = memtables
             .iter()
             .map(|table| {
-                Box::new(table.range(range.clone(), sst_iter_options.order))
-                    as Box<dyn RowEntryIterator + 'static>
+                Box::new(FusedIterator::new(
+                    table.range(range.clone(), sst_iter_options.order),
+                )) as Box<dyn RowEntryIterator + 'static>
             })
             .collect::<Vec<_>>();
 
@@ -231,12 +253,13 @@ impl Reader {
                 max_seq,
             )
             .await?;
+        let now = self.mono_clock.now().await?;
 
         let mut iterator = DbIterator::new(
             range,
             write_batch_iter,
-            mem_iters,
-            segment_iter,
+            apply_expiry(mem_iters, now),
+            Box::new(ExpiryIterator::new(segment_iter, now)),
             max_seq,
             None,
             self.read_merge_operator.clone(),
@@ -257,12 +280,106 @@ impl Reader {
             .transpose()
     }
 
+    /// Get the values of several keys from one view of the database. `keys`
+    /// must be sorted and free of duplicates. Returns the value of each key, in
+    /// the order of `keys`. See [`crate::multi_get`] for how the keys are read.
+    pub(crate) async fn get_key_values_with_options(
+        &self,
+        keys: &[Bytes],
+        options: &ReadOptions,
+        db_state: &(dyn DbStateReader + Sync + Send),
+    ) -> Result<Vec<Option<KeyValue>>, SlateDBError> {
+        self.db_stats.get_requests.increment(keys.len() as u64);
+        let max_seq = self.prepare_max_seq(None, options.durability_filter, options.dirty);
+        let ctx = MultiGetContext {
+            table_store: &self.table_store,
+            options,
+            db_stats: &self.db_stats,
+            max_seq,
+        };
+        let versions = multi_get::read_versions(keys, db_state, &ctx).await?;
+        let now = self.mono_clock.now().await?;
+
+        let mut values = Vec::with_capacity(keys.len());
+        for (key, versions) in keys.iter().zip(versions) {
+            let mut iterator = DbIterator::new(
+                BytesRange::from(key.clone()..=key.clone()),
+                None,
+                Vec::new(),
+                Box::new(ExpiryIterator::new(VersionsIterator::new(versions), now)),
+                max_seq,
+                None,
+                self.read_merge_operator.clone(),
+                IterationOrder::Ascending,
+            )
+            .await?;
+            let value = iterator
+                .next_entry()
+                .await?
+                .map(|entry| {
+                    if entry.value.is_tombstone() {
+                        Err(SlateDBError::UnexpectedTombstone)
+                    } else {
+                        Ok(KeyValue::from(entry))
+                    }
+                })
+                .transpose()?;
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    /// Get every stored version of `key` whose sequence number falls within
+    /// `seq_range`, ordered from the highest sequence number to the lowest.
+    ///
+    /// Versions are read from the memtables, level-0 SSTs and compacted sorted
+    /// runs and are returned as stored: tombstones, merge operands and expired
+    /// values are all included, and nothing is deduplicated or merged. Versions
+    /// that a memtable flush or compaction has already dropped cannot be
+    /// returned. Writes that are not yet committed are excluded.
+    pub(crate) async fn get_all_versions(
+        &self,
+        key: &[u8],
+        seq_range: impl RangeBounds<u64>,
+        db_state: &(dyn DbStateReader + Sync),
+    ) -> Result<Vec<RowEntry>, SlateDBError> {
+        let max_seq = self.prepare_max_seq(None, DurabilityLevel::Memory, false);
+        let range = BytesRange::from_slice(key..=key);
+        let sst_iter_options = SstIteratorOptions {
+            eager_spawn: true,
+            ..SstIteratorOptions::default()
+        };
+
+        let IteratorSources {
+            mem_iters,
+            segment_iter,
+            ..
+        } = self
+            .build_iterator_sources(&range, db_state, None, &sst_iter_options, None, max_seq)
+            .await?;
+        // The segment iterator applies `max_seq` itself and, like the merge
+        // below, keeps every version of a key.
+        let mut iters = apply_filters(mem_iters, max_seq);
+        iters.push(segment_iter);
+        let mut iter = MergeIterator::new(iters)?.with_dedup(false);
+        iter.init().await?;
+
+        // The merge orders versions of the same key by descending sequence number.
+        let mut versions = Vec::new();
+        while let Some(entry) = iter.next().await? {
+            if seq_range.contains(&entry.seq) {
+                versions.push(entry);
+            }
+        }
+        Ok(versions)
+    }
+
     /// Create an iterator over a key range.
     ///
     /// Produces a merged iterator over the provided `write_batch` (if any),
     /// in-memory memtables, level-0 SSTs, and compacted sorted runs, honoring
     /// the maximum visible sequence number. The iterator yields only non-
-    /// expired, non-tombstone values.
+    /// expired, non-tombstone values, unless `ctx` asks for expired ones.
     ///
     /// Arguments
     /// - `range`: The half-open key range to scan (start inclusive, end
@@ -304,8 +421,31 @@ impl Reader {
                 max_seq,
             )
             .await?;
+        let (mem_iters, segment_iter) = match ctx.as_of_ts {
+            Some(as_of_ts) => (
+                mem_iters
+                    .into_iter()
+                    .map(|iter| filter_created_after(iter, as_of_ts))
+                    .collect(),
+                filter_created_after(segment_iter, as_of_ts),
+            ),
+            None => (mem_iters, segment_iter),
+        };
+        let (mem_iters, segment_iter) = if ctx.include_expired {
+            (mem_iters, segment_iter)
+        } else {
+            let now = self.mono_clock.now().await?;
+            (
+                apply_expiry(mem_iters, now),
+                Box::new(ExpiryIterator::new(segment_iter, now)) as Box<dyn RowEntryIterator>,
+            )
+        };
+        let segment_iter: Box<dyn RowEntryIterator + 'static> = match options.prefetch_depth {
+            Some(depth) => Box::new(ReadAheadIterator::new(segment_iter, depth, options.order)),
+            None => segment_iter,
+        };
 
-        DbIterator::new(
+        let iter = DbIterator::new(
             range,
             write_batch_iter,
             mem_iters,
@@ -315,7 +455,15 @@ impl Reader {
             self.read_merge_operator.clone(),
             options.order,
         )
-        .await
+        .await?;
+        let iter = match options.deadline {
+            Some(deadline) => iter.with_deadline(deadline, self.mono_clock.system_clock()),
+            None => iter,
+        };
+        Ok(match options.max_entries_before_yield {
+            Some(max_entries) => iter.with_max_entries_before_yield(max_entries),
+            None => iter,
+        })
     }
 
     /// See [`crate::Db::scan_prefix_by_recency`].
@@ -370,17 +518,17 @@ impl Reader {
         let mut all_iters: Vec<Box<dyn RowEntryIterator + 'static>> = Vec::new();
 
         // Memtables drain first (newest data, always in memory).
-        all_iters.push(Box::new(
+        all_iters.push(Box::new(FusedIterator::new(
             db_state
                 .memtable()
                 .range(range.clone(), sst_iter_options.order),
-        ));
+        )));
         for memtable in db_state.imm_memtable() {
-            all_iters.push(Box::new(
+            all_iters.push(Box::new(FusedIterator::new(
                 memtable
                     .table()
                     .range(range.clone(), sst_iter_options.order),
-            ));
+            )));
         }
 
         // Single-segment chain: L0 newest-first, then sorted runs newest-first.
@@ -424,6 +572,18 @@ impl Reader {
     }
 }
 
+/// Drops entries created after `as_of_ts`. Entries without a `create_ts` are
+/// kept, since there's no telling when they were written.
+fn filter_created_after(
+    iter: Box<dyn RowEntryIterator + 'static>,
+    as_of_ts: i64,
+) -> Box<dyn RowEntryIterator + 'static> {
+    Box::new(FilterIterator::new(
+        iter,
+        Box::new(move |entry: &RowEntry| entry.create_ts.is_none_or(|ts| ts <= as_of_ts)),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1312,6 +1472,23 @@ mod tests {
             expected.map(|b| String::from_utf8_lossy(b))
         );
 
+        // a batched get reads the same value, outside of a transaction
+        if write_batch.is_none() && test_case.max_seq.is_none() {
+            let values = reader
+                .get_key_values_with_options(
+                    &[Bytes::from_static(test_case.query_key)],
+                    &read_options,
+                    &test_db_state,
+                )
+                .await?;
+            assert_eq!(
+                values[0].as_ref().map(|kv| kv.value.as_ref()),
+                expected,
+                "Failed batched test: {}",
+                test_case.description
+            );
+        }
+
         Ok(())
     }
 
@@ -1735,6 +1912,8 @@ mod tests {
                     max_seq: test_case.max_seq,
                     range_tracker: None,
                     prefix: None,
+                    as_of_ts: None,
+                    include_expired: false,
                 },
             )
             .await?;
@@ -1865,6 +2044,8 @@ mod tests {
                     max_seq: None,
                     range_tracker: None,
                     prefix: None,
+                    as_of_ts: None,
+                    include_expired: false,
                 },
             )
             .await?;
@@ -1903,6 +2084,65 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn should_read_several_keys_from_one_sorted_run_sst() -> Result<(), SlateDBError> {
+        let entries = vec![
+            TestEntry::value(b"key1", b"value1", 50).with_location(LayerLocation::SortedRun(0)),
+            TestEntry::value(b"key3", b"value3", 40).with_location(LayerLocation::SortedRun(0)),
+            TestEntry::value(b"key3", b"newer3", 60).with_location(LayerLocation::L0Sst(0)),
+        ];
+
+        let mut test_db_state = TestDbState::new().await;
+        populate_db_state(&mut test_db_state, entries).await?;
+
+        let recorder = Arc::new(DefaultMetricsRecorder::new());
+        let helper = MetricsRecorderHelper::new(recorder.clone(), MetricLevel::default());
+        let reader = build_reader(&test_db_state, DbStats::new(&helper), false).await;
+
+        let keys = [b"key1", b"key2", b"key3"].map(|key| Bytes::from_static(key));
+        let values = reader
+            .get_key_values_with_options(
+                &keys,
+                &ReadOptions::default().with_dirty(true),
+                &test_db_state,
+            )
+            .await?;
+
+        let values: Vec<_> = values
+            .iter()
+            .map(|kv| kv.as_ref().map(|kv| kv.value.as_ref()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![Some(b"value1".as_ref()), None, Some(b"newer3".as_ref())]
+        );
+        // key3 is found in L0, so only key1 and key2 are looked up in the
+        // sorted run, and its filter rules key2 out. The L0 SST starts at
+        // key3, so only key3 is looked up there.
+        let point_labels = &[(
+            crate::db_stats::FILTER_KIND_LABEL,
+            crate::db_stats::FILTER_KIND_POINT,
+        )];
+        assert_eq!(
+            lookup_metric_with_labels(
+                &recorder,
+                crate::db_stats::SST_FILTER_POSITIVE_COUNT,
+                point_labels,
+            ),
+            Some(2)
+        );
+        assert_eq!(
+            lookup_metric_with_labels(
+                &recorder,
+                crate::db_stats::SST_FILTER_NEGATIVE_COUNT,
+                point_labels,
+            ),
+            Some(1)
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn should_record_bloom_filter_negative_for_sorted_run_point_lookup(
     ) -> Result<(), SlateDBError> {
@@ -2038,6 +2278,8 @@ mod tests {
                     max_seq: None,
                     range_tracker: None,
                     prefix: None,
+                    as_of_ts: None,
+                    include_expired: false,
                 },
             )
             .await?;
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use std::cmp::Reverse;
 use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -27,6 +28,33 @@ pub(crate) struct RetentionMetrics {
     pub(crate) expired_entries_purged_merge: Arc<dyn CounterFn>,
 }
 
+/// The number of entries a [`RetentionIterator`] dropped, by reason. Shared
+/// with the iterator so that a compaction job can read them once it has
+/// written its output.
+#[derive(Debug, Default)]
+pub(crate) struct DroppedEntries {
+    /// Tombstones dropped because nothing older is left for them to shadow.
+    tombstones: AtomicU64,
+    /// Expired merge operands, and expired values whose tombstone was dropped.
+    expired: AtomicU64,
+    /// Versions shadowed by a newer version of their key.
+    duplicates: AtomicU64,
+}
+
+impl DroppedEntries {
+    pub(crate) fn tombstones(&self) -> u64 {
+        self.tombstones.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn expired(&self) -> u64 {
+        self.expired.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn duplicates(&self) -> u64 {
+        self.duplicates.load(Ordering::Relaxed)
+    }
+}
+
 /// A retention iterator that filters entries based on retention time and handles expired/tombstoned keys.
 ///
 /// This iterator implements a retention policy by filtering out entries that are older than a specified
@@ -55,6 +83,8 @@ pub(crate) struct RetentionIterator<T: RowEntryIterator> {
     /// Optional counters for observing expire_ts-driven decisions. Populated on
     /// the compaction path; `None` on the flush path.
     metrics: Option<RetentionMetrics>,
+    /// Optional counts of the entries dropped, by reason.
+    dropped: Option<Arc<DroppedEntries>>,
 }
 
 impl<T: RowEntryIterator> RetentionIterator<T> {
@@ -78,16 +108,24 @@ impl<T: RowEntryIterator> RetentionIterator<T> {
             system_clock,
             sequence_tracker,
             metrics,
+            dropped: None,
             buffer: RetentionBuffer::new(),
         })
     }
 
+    /// Counts the entries the iterator drops in `dropped`.
+    pub(crate) fn with_dropped_entries(mut self, dropped: Arc<DroppedEntries>) -> Self {
+        self.dropped = Some(dropped);
+        self
+    }
+
     /// Applies retention filtering to a collection of versions for the same key
     ///
     /// This function implements the following retention logic:
     ///
     /// - Filters out older versions that exceed the retention period, but keep the latest version (unless tombstone is filtered out)
     /// - Transform expired entries into tombstones, and recycle the tombstones in the tail if filter_tombstone is true.
+    /// - Keep tombstones whose retention period (their `expire_ts`) hasn't passed, even in the tail.
     fn apply_retention_filter(
         versions: BTreeMap<Reverse<u64>, RowEntry>,
         compaction_start_ts: i64,
@@ -97,15 +135,35 @@ impl<T: RowEntryIterator> RetentionIterator<T> {
         filter_tombstone: bool,
         sequence_tracker: Arc<SequenceTracker>,
         metrics: Option<&RetentionMetrics>,
+        dropped: Option<&DroppedEntries>,
     ) -> BTreeMap<Reverse<u64>, RowEntry> {
         let mut filtered_versions = BTreeMap::new();
         let current_system_ts = system_clock.now().timestamp_millis();
-        for (_, entry) in versions.into_iter() {
+        let count_dropped = |counter: fn(&DroppedEntries) -> &AtomicU64, n: u64| {
+            if let Some(dropped) = dropped {
+                counter(dropped).fetch_add(n, Ordering::Relaxed);
+            }
+        };
+        // the tombstones written in place of expired values
+        let mut expired_values = Vec::new();
+        let num_versions = versions.len();
+        for (position, (_, entry)) in versions.into_iter().enumerate() {
             // filter out any expired entries -- eventually we can consider
             // abstracting this away into generic, pluggable compaction filters
             // but for now we do it inline
             let is_merge = matches!(&entry.value, ValueDeletable::Merge(_));
             let entry = match entry.expire_ts.as_ref() {
+                // a tombstone's expiry ends its retention period (see
+                // `WriteBatch::delete_with_retention`), after which it can be
+                // collected like any other tombstone
+                Some(expire_ts)
+                    if *expire_ts <= compaction_start_ts && entry.value.is_tombstone() =>
+                {
+                    RowEntry {
+                        expire_ts: None,
+                        ..entry
+                    }
+                }
                 Some(expire_ts) if *expire_ts <= compaction_start_ts => {
                     if is_merge {
                         // just skip expired merge entries rather than write a tombstone
@@ -113,6 +171,7 @@ impl<T: RowEntryIterator> RetentionIterator<T> {
                         if let Some(m) = metrics {
                             m.expired_entries_purged_merge.increment(1);
                         }
+                        count_dropped(|d| &d.expired, 1);
                         continue;
                     }
                     // for values, insert a tombstone instead of just filtering out the
@@ -122,6 +181,7 @@ impl<T: RowEntryIterator> RetentionIterator<T> {
                     if let Some(m) = metrics {
                         m.expired_entries_purged_value.increment(1);
                     }
+                    expired_values.push(entry.seq);
                     RowEntry {
                         key: entry.key,
                         value: Tombstone,
@@ -185,19 +245,27 @@ impl<T: RowEntryIterator> RetentionIterator<T> {
                 // if we find the first non-merge entry that's neither in retention window by time
                 // nor by seq we should break the loop to filter out the earlier versions of the
                 // same key.
+                count_dropped(|d| &d.duplicates, (num_versions - position - 1) as u64);
                 break;
             }
         }
 
         if filter_tombstone {
-            // remove the tombstones in the tail
+            // remove the tombstones in the tail, except those still within
+            // their retention period
             while filtered_versions
                 .iter()
                 .last()
-                .map(|(_, entry)| entry.value.is_tombstone())
+                .map(|(_, entry)| entry.value.is_tombstone() && entry.expire_ts.is_none())
                 .unwrap_or(false)
             {
-                filtered_versions.pop_last();
+                if let Some((_, tombstone)) = filtered_versions.pop_last() {
+                    if expired_values.contains(&tombstone.seq) {
+                        count_dropped(|d| &d.expired, 1);
+                    } else {
+                        count_dropped(|d| &d.tombstones, 1);
+                    }
+                }
             }
         }
 
@@ -255,6 +323,7 @@ impl<T: RowEntryIterator> RowEntryIterator for RetentionIterator<T> {
                     let retention_min_seq = self.retention_min_seq;
                     let system_clock = self.system_clock.clone();
                     let metrics = self.metrics.clone();
+                    let dropped = self.dropped.clone();
                     self.buffer.process_retention(|versions| {
                         Self::apply_retention_filter(
                             versions,
@@ -265,6 +334,7 @@ impl<T: RowEntryIterator> RowEntryIterator for RetentionIterator<T> {
                             self.filter_tombstone,
                             self.sequence_tracker.clone(),
                             metrics.as_ref(),
+                            dropped.as_deref(),
                         )
                     })?;
                 }
@@ -287,6 +357,10 @@ impl<T: TrackedRowEntryIterator> TrackedRowEntryIterator for RetentionIterator<T
     fn bytes_processed(&self) -> u64 {
         self.inner.bytes_processed()
     }
+
+    fn entries_processed(&self) -> u64 {
+        self.inner.entries_processed()
+    }
 }
 
 /// A buffer that collects and manages multiple versions of the same key from an iterator.
@@ -928,6 +1002,52 @@ mod tests {
         ],
         filter_tombstone: true, // Tombstone at end filtered out
     })]
+    #[case(RetentionIteratorTestCase {
+        name: "tombstone_within_retention_period_preserved",
+        input_entries: vec![
+            RowEntry::new_tombstone(b"key1", 3).with_create_ts(950).with_expire_ts(1100), // Retained until 1100
+            RowEntry::new_value(b"key1", b"value1", 1).with_create_ts(850),
+        ],
+        retention_timeout: None,
+        retention_min_seq: None,
+        system_clock_ts: 1000,
+        compaction_start_ts: 1000,
+        expected_entries: vec![
+            RowEntry::new_tombstone(b"key1", 3).with_create_ts(950).with_expire_ts(1100),
+            // The value it shadows is dropped
+        ],
+        filter_tombstone: true, // Tombstone kept despite filtering tombstones
+    })]
+    #[case(RetentionIteratorTestCase {
+        name: "tombstone_past_retention_period_filtered_out",
+        input_entries: vec![
+            RowEntry::new_tombstone(b"key1", 3).with_create_ts(950).with_expire_ts(1000), // Retained until 1000
+            RowEntry::new_value(b"key1", b"value1", 1).with_create_ts(850),
+        ],
+        retention_timeout: None,
+        retention_min_seq: None,
+        system_clock_ts: 1000,
+        compaction_start_ts: 1000,
+        expected_entries: vec![
+            // Retention period over, so the tombstone is collected
+        ],
+        filter_tombstone: true,
+    })]
+    #[case(RetentionIteratorTestCase {
+        name: "tombstone_past_retention_period_kept_in_upper_run",
+        input_entries: vec![
+            RowEntry::new_tombstone(b"key1", 3).with_create_ts(950).with_expire_ts(1000),
+        ],
+        retention_timeout: None,
+        retention_min_seq: None,
+        system_clock_ts: 1000,
+        compaction_start_ts: 1000,
+        expected_entries: vec![
+            // Still shadows older versions in lower runs; only the expiry is cleared
+            RowEntry::new_tombstone(b"key1", 3).with_create_ts(950),
+        ],
+        filter_tombstone: false,
+    })]
     #[case(RetentionIteratorTestCase {
         name: "all_tombstones_filtered_out",
         input_entries: vec![
@@ -1043,6 +1163,7 @@ mod tests {
             test_case.filter_tombstone,
             Arc::new(SequenceTracker::new()),
             None,
+            None,
         );
 
         // Convert filtered versions back to expected order
@@ -1156,6 +1277,7 @@ mod tests {
             false,
             tracker,
             None,
+            None,
         );
 
         let derived_ts = sorted_points
@@ -1233,6 +1355,7 @@ mod tests {
                 false,
                 Arc::new(SequenceTracker::new()),
                 Some(&metrics),
+                None,
             );
 
             assert_eq!(filtered.len(), 1);
@@ -1266,6 +1389,7 @@ mod tests {
                 false,
                 Arc::new(SequenceTracker::new()),
                 Some(&metrics),
+                None,
             );
 
             assert!(filtered.is_empty(), "expired merge entry should be dropped");
@@ -1289,6 +1413,7 @@ mod tests {
                 false,
                 Arc::new(SequenceTracker::new()),
                 Some(&metrics),
+                None,
             );
 
             assert_eq!(filtered.len(), 1);
@@ -1296,6 +1421,52 @@ mod tests {
             assert_eq!(merge_count(recorder.as_ref()), Some(0));
         }
 
+        #[test]
+        fn apply_retention_filter_counts_dropped_entries_by_reason() {
+            let dropped = DroppedEntries::default();
+            let filter = |entries: Vec<RowEntry>| {
+                let versions: BTreeMap<_, _> = entries
+                    .into_iter()
+                    .map(|entry| (Reverse(entry.seq), entry))
+                    .collect();
+                RetentionIterator::<TestIterator>::apply_retention_filter(
+                    versions,
+                    1_000,
+                    Arc::new(MockSystemClock::with_time(1_000)),
+                    None,
+                    None,
+                    true,
+                    Arc::new(SequenceTracker::new()),
+                    None,
+                    Some(&dropped),
+                )
+            };
+
+            // the tombstone shadows the value, and then has nothing left to shadow
+            let filtered = filter(vec![
+                RowEntry::new_tombstone(b"k1", 2),
+                RowEntry::new_value(b"k1", b"v", 1),
+            ]);
+            assert!(filtered.is_empty());
+            // the expired value's tombstone and the expired merge operand
+            let filtered = filter(vec![
+                RowEntry::new_value(b"k2", b"v", 3).with_expire_ts(500),
+                RowEntry::new_merge(b"k3", b"v", 4).with_expire_ts(500),
+            ]);
+            assert!(filtered.is_empty());
+            // the older versions are shadowed by the newest one
+            let filtered = filter(vec![
+                RowEntry::new_value(b"k4", b"v3", 7),
+                RowEntry::new_value(b"k4", b"v2", 6),
+                RowEntry::new_value(b"k4", b"v1", 5),
+            ]);
+            assert_eq!(filtered.len(), 1);
+
+            assert_eq!(dropped.tombstones(), 1);
+            assert_eq!(dropped.expired(), 2);
+            assert_eq!(dropped.duplicates(), 3);
+        }
+
         #[test]
         fn apply_retention_filter_no_panic_when_metrics_none() {
             let merge_entry = RowEntry::new_merge(b"k1", b"v", 1).with_expire_ts(500);
@@ -1313,6 +1484,7 @@ mod tests {
                 false,
                 Arc::new(SequenceTracker::new()),
                 None,
+                None,
             );
 
             // merge dropped, value kept as tombstone
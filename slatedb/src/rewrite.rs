@@ -0,0 +1,200 @@
+//! Bulk rewrites of the values in a key range. See
+//! [`crate::Db::rewrite_range`].
+
+use std::ops::Bound;
+
+use bytes::Bytes;
+use log::debug;
+
+use crate::config::{PutOptions, Ttl};
+use crate::transaction_manager::IsolationLevel;
+use crate::{Db, ErrorKind};
+
+/// The number of keys read, and committed, by each transaction of a rewrite.
+const REWRITE_CHUNK_SIZE: usize = 256;
+
+/// The number of keys changed by [`crate::Db::rewrite_range`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RewriteSummary {
+    /// The number of keys whose value was replaced.
+    pub rewritten: u64,
+    /// The number of keys that were deleted.
+    pub deleted: u64,
+}
+
+pub(crate) async fn rewrite_range<F>(
+    db: &Db,
+    range: (Bound<Bytes>, Bound<Bytes>),
+    f: F,
+) -> Result<RewriteSummary, crate::Error>
+where
+    F: Fn(&[u8], &[u8]) -> Option<Bytes>,
+{
+    let (mut start, end) = range;
+    let mut summary = RewriteSummary::default();
+    loop {
+        let txn = db.begin(IsolationLevel::Snapshot).await?;
+        let mut iter = txn.scan((start.clone(), end.clone())).await?;
+        let mut chunk = RewriteSummary::default();
+        let mut keys_read = 0;
+        let mut last_key = None;
+        while keys_read < REWRITE_CHUNK_SIZE {
+            let Some(kv) = iter.next().await? else {
+                break;
+            };
+            keys_read += 1;
+            match f(&kv.key, &kv.value) {
+                None => {
+                    txn.delete(&kv.key)?;
+                    chunk.deleted += 1;
+                }
+                Some(value) if value != kv.value => {
+                    // keep the expiry time of the value being replaced
+                    let ttl = match kv.expire_ts {
+                        Some(expire_ts) => Ttl::ExpireAt(expire_ts),
+                        None => Ttl::NoExpiry,
+                    };
+                    txn.put_with_options(&kv.key, value, &PutOptions { ttl })?;
+                    chunk.rewritten += 1;
+                }
+                Some(_) => {}
+            }
+            last_key = Some(kv.key);
+        }
+        match txn.commit().await {
+            Ok(_) => {}
+            Err(err) if err.kind() == ErrorKind::Transaction => {
+                // a concurrent write won the race for one of the keys, so read
+                // the chunk again and rewrite the values it wrote
+                debug!(
+                    "retrying rewrite chunk after a conflict [start={:?}]",
+                    start
+                );
+                continue;
+            }
+            Err(err) => return Err(err),
+        }
+        summary.rewritten += chunk.rewritten;
+        summary.deleted += chunk.deleted;
+        match last_key {
+            Some(last_key) if keys_read == REWRITE_CHUNK_SIZE => {
+                start = Bound::Excluded(last_key);
+            }
+            _ => return Ok(summary),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WriteOptions;
+    use crate::WriteBatch;
+    use object_store::memory::InMemory;
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    fn upper(value: &[u8]) -> Option<Bytes> {
+        Some(Bytes::from(value.to_ascii_uppercase()))
+    }
+
+    #[tokio::test]
+    async fn should_rewrite_and_delete_keys_across_chunks() {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        let keys = REWRITE_CHUNK_SIZE * 2 + 10;
+        let mut batch = WriteBatch::new();
+        for i in 0..keys {
+            batch.put(format!("key{:04}", i), format!("value{}", i));
+        }
+        db.write(batch).await.unwrap();
+        db.put_with_options(
+            b"key0001",
+            b"expiring",
+            &PutOptions {
+                ttl: Ttl::ExpireAt(i64::MAX),
+            },
+            &WriteOptions::default(),
+        )
+        .await
+        .unwrap();
+        db.put(b"other", b"untouched").await.unwrap();
+
+        let summary = db
+            .rewrite_range(b"key".as_slice()..b"kez".as_slice(), |key, value| {
+                if key.ends_with(b"0") {
+                    None
+                } else if key.ends_with(b"5") {
+                    Some(Bytes::copy_from_slice(value))
+                } else {
+                    upper(value)
+                }
+            })
+            .await
+            .unwrap();
+
+        let deleted = keys.div_ceil(10) as u64;
+        let unchanged = (keys as u64 + 5) / 10;
+        assert_eq!(
+            summary,
+            RewriteSummary {
+                rewritten: keys as u64 - deleted - unchanged,
+                deleted,
+            }
+        );
+        assert_eq!(db.get(b"key0000").await.unwrap(), None);
+        assert_eq!(
+            db.get(b"key0005").await.unwrap(),
+            Some(Bytes::from("value5"))
+        );
+        assert_eq!(
+            db.get(b"key0513").await.unwrap(),
+            Some(Bytes::from("VALUE513"))
+        );
+        // the expiry time of a rewritten value is kept
+        let kv = db.get_key_value(b"key0001").await.unwrap().unwrap();
+        assert_eq!(kv.value, Bytes::from("EXPIRING"));
+        assert_eq!(kv.expire_ts, Some(i64::MAX));
+        assert_eq!(
+            db.get(b"other").await.unwrap(),
+            Some(Bytes::from("untouched"))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn should_rewrite_concurrently_written_value() {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        db.put(b"a", b"a").await.unwrap();
+        db.put(b"b", b"b").await.unwrap();
+        let written = AtomicBool::new(false);
+        let calls = AtomicU64::new(0);
+
+        let summary = db
+            .rewrite_range::<&[u8], _, _>(.., |key, value| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                // write to the key while its chunk is being rewritten
+                if key == b"b" && !written.swap(true, Ordering::SeqCst) {
+                    futures::executor::block_on(db.put(b"b", b"concurrent")).unwrap();
+                }
+                upper(value)
+            })
+            .await
+            .unwrap();
+
+        // the chunk conflicted with the write and was rewritten again
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            summary,
+            RewriteSummary {
+                rewritten: 2,
+                deleted: 0
+            }
+        );
+        assert_eq!(db.get(b"a").await.unwrap(), Some(Bytes::from("A")));
+        assert_eq!(db.get(b"b").await.unwrap(), Some(Bytes::from("CONCURRENT")));
+    }
+}
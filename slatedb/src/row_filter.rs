@@ -0,0 +1,147 @@
+//! Filtering the rows returned by a scan. See
+//! [`crate::Db::scan_with_row_filter`].
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::error::SlateDBError;
+use crate::iter::RowEntryIterator;
+use crate::types::{RowEntry, ValueDeletable};
+
+/// Selects the rows a scan returns, e.g. the keys whose values match a query.
+///
+/// A filter is evaluated inside the scan, once per key, after any merge
+/// operands for the key have been merged and before any [`crate::Projector`]
+/// is applied, so rejected rows are dropped without being projected or
+/// returned. It is not called for deleted keys.
+pub trait RowFilter: Send + Sync {
+    /// Returns whether the scan returns `entry`, which holds the key, its
+    /// value and its metadata.
+    fn matches(&self, entry: &RowEntry) -> bool;
+}
+
+/// Drops the rows of an iterator that a [`RowFilter`] rejects.
+pub(crate) struct RowFilterIterator {
+    inner: Box<dyn RowEntryIterator + 'static>,
+    filter: Arc<dyn RowFilter>,
+}
+
+impl RowFilterIterator {
+    pub(crate) fn new(
+        inner: Box<dyn RowEntryIterator + 'static>,
+        filter: Arc<dyn RowFilter>,
+    ) -> Self {
+        Self { inner, filter }
+    }
+}
+
+#[async_trait]
+impl RowEntryIterator for RowFilterIterator {
+    async fn init(&mut self) -> Result<(), SlateDBError> {
+        self.inner.init().await
+    }
+
+    async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        while let Some(entry) = self.inner.next().await? {
+            if matches!(entry.value, ValueDeletable::Tombstone) || self.filter.matches(&entry) {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
+        self.inner.seek(next_key).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ScanOptions;
+    use crate::test_utils::TestIterator;
+    use crate::{Db, Projector, WriteBatch};
+    use bytes::Bytes;
+    use object_store::memory::InMemory;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Matches values that end with `0`.
+    struct EndsWithZero;
+
+    impl RowFilter for EndsWithZero {
+        fn matches(&self, entry: &RowEntry) -> bool {
+            match &entry.value {
+                ValueDeletable::Value(value) => value.ends_with(b"0"),
+                _ => false,
+            }
+        }
+    }
+
+    /// Counts the values it copies.
+    #[derive(Default)]
+    struct CountingProjector {
+        calls: AtomicUsize,
+    }
+
+    impl Projector for CountingProjector {
+        fn project(&self, value: &[u8]) -> Bytes {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Bytes::copy_from_slice(value)
+        }
+    }
+
+    #[tokio::test]
+    async fn should_only_materialize_matching_rows() {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        let mut batch = WriteBatch::new();
+        for i in 0..500 {
+            batch.put(format!("key{i:03}"), format!("value{i}"));
+        }
+        db.write(batch).await.unwrap();
+        db.delete(b"key010").await.unwrap();
+
+        let projector = Arc::new(CountingProjector::default());
+        let mut iter = db
+            .scan_with_row_filter(
+                b"key000".to_vec()..b"key999".to_vec(),
+                &ScanOptions::default(),
+                Arc::new(EndsWithZero),
+            )
+            .await
+            .unwrap()
+            .with_projector(projector.clone());
+        let mut keys = Vec::new();
+        while let Some(kv) = iter.next().await.unwrap() {
+            keys.push(String::from_utf8(kv.key.to_vec()).unwrap());
+        }
+
+        let expected: Vec<_> = (0..500)
+            .filter(|i| i % 10 == 0 && *i != 10)
+            .map(|i| format!("key{i:03}"))
+            .collect();
+        assert_eq!(keys, expected);
+        // only the matching rows reach the projector
+        assert_eq!(projector.calls.load(Ordering::SeqCst), expected.len());
+    }
+
+    #[tokio::test]
+    async fn should_pass_tombstones_through() {
+        let inner = TestIterator::new()
+            .with_entry(b"a", b"1", 1)
+            .with_row_entry(RowEntry::new_tombstone(b"b", 2))
+            .with_entry(b"c", b"20", 3);
+        let mut iter = RowFilterIterator::new(Box::new(inner), Arc::new(EndsWithZero));
+        assert_eq!(
+            iter.next().await.unwrap(),
+            Some(RowEntry::new_tombstone(b"b", 2))
+        );
+        assert_eq!(
+            iter.next().await.unwrap(),
+            Some(RowEntry::new_value(b"c", b"20", 3))
+        );
+        assert_eq!(iter.next().await.unwrap(), None);
+    }
+}
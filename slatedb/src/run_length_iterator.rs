@@ -0,0 +1,197 @@
+//! Run-length grouping of scan results.
+//!
+//! [`RunLengthIterator`] wraps a [`DbIterator`] and collapses adjacent keys that
+//! hold byte-equal values into a single [`ValueRun`], which is handy for
+//! rendering sparse or repetitive data compactly.
+//!
+//! Runs follow these rules:
+//!
+//! - Only keys returned by the wrapped scan are grouped, in the scan's order.
+//!   For a descending scan, [`ValueRun::first_key`] is therefore the largest key
+//!   of the run.
+//! - A deleted key breaks a run, even if the keys on either side of it hold the
+//!   same value. Keys that were never written don't break runs, since the scan
+//!   has no way to observe them.
+//! - A run is never extended past the bounds of the scanned range. A run that
+//!   touches a bound is emitted as it stands, so scanning two adjacent ranges
+//!   separately may split a run that a single scan over both would return whole.
+
+use bytes::Bytes;
+
+use crate::db_iter::DbIterator;
+
+/// A run of adjacent keys that hold the same value.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueRun {
+    /// The first key of the run in iteration order.
+    pub first_key: Bytes,
+    /// The last key of the run in iteration order. Equal to `first_key` for a
+    /// run of one key.
+    pub last_key: Bytes,
+    /// The value shared by every key in the run.
+    pub value: Bytes,
+    /// The number of keys in the run.
+    pub len: usize,
+}
+
+/// Groups the entries of a [`DbIterator`] into [`ValueRun`]s. See the module
+/// docs for how runs are formed.
+pub struct RunLengthIterator {
+    iter: DbIterator,
+    /// The run being built, emitted once an entry that can't extend it is read.
+    pending: Option<ValueRun>,
+}
+
+impl RunLengthIterator {
+    /// Wraps `iter`, which should not have been advanced yet. Entries it
+    /// already returned are not part of any run.
+    pub fn new(iter: DbIterator) -> Self {
+        Self {
+            iter,
+            pending: None,
+        }
+    }
+
+    /// Returns the next run, or `None` once the scan is exhausted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error`] if the wrapped iterator fails. The run being
+    /// built when the error occurred is discarded.
+    pub async fn next(&mut self) -> Result<Option<ValueRun>, crate::Error> {
+        loop {
+            let Some(entry) = self.iter.next_entry_with_tombstones().await? else {
+                return Ok(self.pending.take());
+            };
+            let Some(value) = entry.value.as_bytes() else {
+                // a deleted key ends the current run
+                match self.pending.take() {
+                    Some(run) => return Ok(Some(run)),
+                    None => continue,
+                }
+            };
+            match &mut self.pending {
+                Some(run) if run.value == value => {
+                    run.last_key = entry.key;
+                    run.len += 1;
+                }
+                pending => {
+                    let next = ValueRun {
+                        first_key: entry.key.clone(),
+                        last_key: entry.key,
+                        value,
+                        len: 1,
+                    };
+                    if let Some(run) = pending.replace(next) {
+                        return Ok(Some(run));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytes_range::BytesRange;
+    use crate::iter::{EmptyIterator, IterationOrder, RowEntryIterator};
+    use crate::mem_table::KVTable;
+    use crate::types::RowEntry;
+
+    async fn collect_runs(range: BytesRange, order: IterationOrder) -> Vec<ValueRun> {
+        let table = KVTable::new();
+        for entry in [
+            RowEntry::new_value(b"a", b"x", 1),
+            RowEntry::new_value(b"b", b"x", 2),
+            RowEntry::new_value(b"c", b"x", 3),
+            RowEntry::new_value(b"d", b"y", 4),
+            RowEntry::new_tombstone(b"e", 5),
+            RowEntry::new_value(b"f", b"y", 6),
+            RowEntry::new_value(b"g", b"y", 7),
+            RowEntry::new_value(b"h", b"x", 8),
+            RowEntry::new_tombstone(b"i", 9),
+        ] {
+            table.put(entry);
+        }
+        let iter = DbIterator::new(
+            range.clone(),
+            None,
+            vec![Box::new(table.range(range, order)) as Box<dyn RowEntryIterator + 'static>],
+            Box::new(EmptyIterator::new()),
+            None,
+            None,
+            None,
+            order,
+        )
+        .await
+        .unwrap();
+        let mut iter = RunLengthIterator::new(iter);
+        let mut runs = Vec::new();
+        while let Some(run) = iter.next().await.unwrap() {
+            runs.push(run);
+        }
+        runs
+    }
+
+    fn run(
+        first_key: &'static [u8],
+        last_key: &'static [u8],
+        value: &'static [u8],
+        len: usize,
+    ) -> ValueRun {
+        ValueRun {
+            first_key: Bytes::from_static(first_key),
+            last_key: Bytes::from_static(last_key),
+            value: Bytes::from_static(value),
+            len,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_collapse_adjacent_equal_values() {
+        let runs = collect_runs(BytesRange::from(..), IterationOrder::Ascending).await;
+
+        assert_eq!(
+            runs,
+            vec![
+                run(b"a", b"c", b"x", 3),
+                // the tombstone at "e" splits the "y" run
+                run(b"d", b"d", b"y", 1),
+                run(b"f", b"g", b"y", 2),
+                run(b"h", b"h", b"x", 1),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_follow_descending_order() {
+        let runs = collect_runs(BytesRange::from(..), IterationOrder::Descending).await;
+
+        assert_eq!(
+            runs,
+            vec![
+                run(b"h", b"h", b"x", 1),
+                run(b"g", b"f", b"y", 2),
+                run(b"d", b"d", b"y", 1),
+                run(b"c", b"a", b"x", 3),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_truncate_runs_at_range_bounds() {
+        let range = BytesRange::from(Bytes::from_static(b"b")..Bytes::from_static(b"g"));
+        let runs = collect_runs(range, IterationOrder::Ascending).await;
+
+        assert_eq!(
+            runs,
+            vec![
+                run(b"b", b"c", b"x", 2),
+                run(b"d", b"d", b"y", 1),
+                run(b"f", b"f", b"y", 1),
+            ]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures::future::try_join;
+use futures::FutureExt;
 use std::collections::VecDeque;
 use std::sync::Arc;
 
@@ -11,7 +12,7 @@ use crate::db_stats::DbStats;
 use crate::error::SlateDBError;
 use crate::iter::{EmptyIterator, IterationOrder, RowEntryIterator};
 use crate::manifest::{LsmTreeState, Segment};
-use crate::merge_iterator::MergeIterator;
+use crate::merge_iterator::{LazyMergeSource, MergeIterator};
 use crate::sorted_run_iterator::SortedRunIterator;
 use crate::sst_iter::{SstIterator, SstIteratorOptions};
 use crate::tablestore::TableStore;
@@ -43,20 +44,39 @@ pub(crate) struct SegmentScanContext {
 ///
 /// Descending scans over sorted runs are broken until
 /// [`SortedRunIterator`] supports descending iteration.
+///
+/// For ascending scans the L0 SSTs are not opened up front. They're handed
+/// to the merge as [`LazyMergeSource`]s and only opened once the scan
+/// reaches their key range, so a scan that stops early never fetches blocks
+/// from L0 SSTs past the point it stopped.
 struct RangeTreeIterators {
     l0: VecDeque<Box<dyn RowEntryIterator>>,
+    l0_lazy: Vec<LazyMergeSource<'static>>,
     sr: VecDeque<Box<dyn RowEntryIterator>>,
 }
 
 impl RangeTreeIterators {
     async fn build(tree: LsmTreeState, ctx: &SegmentScanContext) -> Result<Self, SlateDBError> {
+        if matches!(ctx.sst_iter_options.order, IterationOrder::Ascending) {
+            let l0_lazy = build_l0_lazy_sources(tree.l0, ctx);
+            let sr = build_sr_range_iters(tree.compacted, ctx).await?;
+            return Ok(Self {
+                l0: VecDeque::new(),
+                l0_lazy,
+                sr,
+            });
+        }
         // Range scans need both L0 and SR iterators, so build them in parallel
         let (l0, sr) = try_join(
             build_l0_range_iters(tree.l0, ctx),
             build_sr_range_iters(tree.compacted, ctx),
         )
         .await?;
-        Ok(Self { l0, sr })
+        Ok(Self {
+            l0,
+            l0_lazy: Vec::new(),
+            sr,
+        })
     }
 }
 
@@ -207,9 +227,11 @@ impl SegmentMergeIterator {
                 .context
                 .as_ref()
                 .expect("Pending children require a SegmentScanContext");
-            let RangeTreeIterators { l0, sr } = RangeTreeIterators::build(tree, context).await?;
+            let RangeTreeIterators { l0, l0_lazy, sr } =
+                RangeTreeIterators::build(tree, context).await?;
             let iters: VecDeque<Box<dyn RowEntryIterator>> = l0.into_iter().chain(sr).collect();
-            let merge = MergeIterator::new_with_order(iters, context.sst_iter_options.order)?;
+            let merge = MergeIterator::new_with_order(iters, context.sst_iter_options.order)?
+                .with_lazy_sources(l0_lazy, &context.range);
             // Per-segment merge runs with dedup disabled so the outer
             // `max_seq` filter can drop out-of-window entries before any
             // dedup decision is made (see comments in `db_iter::DbIterator::new`).
@@ -373,6 +395,28 @@ async fn build_l0_range_iters(
     .await
 }
 
+fn build_l0_lazy_sources(
+    l0: VecDeque<SsTableView>,
+    ctx: &SegmentScanContext,
+) -> Vec<LazyMergeSource<'static>> {
+    l0.into_iter()
+        .map(|sst| {
+            let sst_range = sst.compacted_effective_range().clone();
+            let table_store = ctx.table_store.clone();
+            let range = ctx.range.clone();
+            let opts = ctx.sst_iter_options.clone();
+            LazyMergeSource::new(sst_range, move || {
+                async move {
+                    SstIterator::new_owned_initialized(range, sst, table_store, opts)
+                        .await
+                        .map(|maybe| maybe.map(|i| Box::new(i) as Box<dyn RowEntryIterator>))
+                }
+                .boxed()
+            })
+        })
+        .collect()
+}
+
 async fn build_sr_range_iters(
     compacted: Vec<SortedRun>,
     ctx: &SegmentScanContext,
@@ -0,0 +1,133 @@
+//! The shutdown protocol for writes. See [`WriteGate`].
+//!
+//! Closing a db moves it through three phases:
+//!
+//! - `Open`: writes are accepted.
+//! - `Closing`: [`crate::Db::close`] has started. New writes are rejected with
+//!   [`SlateDBError::Closing`], while writes accepted before are applied to
+//!   the WAL and the memtable as usual. The flush done by `close` then makes
+//!   them durable, so callers waiting for durability see them succeed.
+//! - `Closed`: every accepted write has been applied, and the db's status is
+//!   closed. New writes are rejected with [`SlateDBError::Closed`].
+
+use tokio::sync::watch;
+
+use crate::error::SlateDBError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ShutdownPhase {
+    Open,
+    Closing,
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GateState {
+    phase: ShutdownPhase,
+    /// The number of accepted writes that haven't been applied yet.
+    pending_writes: usize,
+}
+
+/// Admits writes while the db is open and lets [`crate::Db::close`] wait for
+/// the writes it admitted before closing.
+pub(crate) struct WriteGate {
+    state: watch::Sender<GateState>,
+}
+
+impl WriteGate {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: watch::Sender::new(GateState {
+                phase: ShutdownPhase::Open,
+                pending_writes: 0,
+            }),
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn phase(&self) -> ShutdownPhase {
+        self.state.borrow().phase
+    }
+
+    /// Admits a write, which is pending until the returned permit is dropped.
+    ///
+    /// ## Errors
+    /// - [`SlateDBError::Closing`]: if the db has started closing
+    /// - [`SlateDBError::Closed`]: if the db is closed
+    pub(crate) fn admit(&self) -> Result<WritePermit<'_>, SlateDBError> {
+        let mut result = Ok(());
+        self.state.send_if_modified(|state| match state.phase {
+            ShutdownPhase::Open => {
+                state.pending_writes += 1;
+                true
+            }
+            ShutdownPhase::Closing => {
+                result = Err(SlateDBError::Closing);
+                false
+            }
+            ShutdownPhase::Closed => {
+                result = Err(SlateDBError::Closed);
+                false
+            }
+        });
+        result.map(|_| WritePermit { gate: self })
+    }
+
+    /// Stops admitting writes and waits until every admitted write has been
+    /// applied.
+    pub(crate) async fn close(&self) {
+        self.state.send_if_modified(|state| {
+            if state.phase != ShutdownPhase::Open {
+                return false;
+            }
+            state.phase = ShutdownPhase::Closing;
+            true
+        });
+        // the sender is owned by `self`, so the channel can't close while
+        // waiting
+        let _ = self
+            .state
+            .subscribe()
+            .wait_for(|state| state.pending_writes == 0)
+            .await;
+        self.state
+            .send_modify(|state| state.phase = ShutdownPhase::Closed);
+    }
+}
+
+/// A write admitted by a [`WriteGate`], which stays pending until dropped.
+pub(crate) struct WritePermit<'a> {
+    gate: &'a WriteGate,
+}
+
+impl Drop for WritePermit<'_> {
+    fn drop(&mut self) {
+        self.gate
+            .state
+            .send_modify(|state| state.pending_writes -= 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn should_wait_for_admitted_writes_before_closing() {
+        let gate = Arc::new(WriteGate::new());
+        let permit = gate.admit().unwrap();
+
+        let closing_gate = gate.clone();
+        let close = tokio::spawn(async move { closing_gate.close().await });
+        tokio::task::yield_now().await;
+        assert_eq!(gate.phase(), ShutdownPhase::Closing);
+        assert!(matches!(gate.admit(), Err(SlateDBError::Closing)));
+        assert!(!close.is_finished());
+
+        drop(permit);
+        close.await.unwrap();
+        assert_eq!(gate.phase(), ShutdownPhase::Closed);
+        assert!(matches!(gate.admit(), Err(SlateDBError::Closed)));
+    }
+}
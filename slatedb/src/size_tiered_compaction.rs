@@ -6,7 +6,7 @@ use bytes::Bytes;
 use crate::compactor::{CompactionScheduler, CompactionSchedulerSupplier};
 use crate::compactor_state::{Compaction, CompactionSpec, SourceId};
 use crate::compactor_state_protocols::CompactorStateView;
-use crate::config::{CompactorOptions, SizeTieredCompactionSchedulerOptions};
+use crate::config::{CompactionPriority, CompactorOptions, SizeTieredCompactionSchedulerOptions};
 use crate::error::Error;
 use crate::manifest::{LsmTreeState, ManifestCore};
 use log::warn;
@@ -17,6 +17,10 @@ const DEFAULT_MAX_CONCURRENT_COMPACTIONS: usize = 4;
 struct CompactionSource {
     source: SourceId,
     size: u64,
+    /// The number of entries in the source's SSTs, as recorded when they were written.
+    num_entries: u64,
+    /// The number of delete entries in the source's SSTs.
+    num_deletes: u64,
 }
 
 /// Checks a candidate compaction to make sure that it does not conflict
@@ -160,7 +164,8 @@ impl TreeState {
 ///   run <= options.include_size_threshold * size of the smallest run.
 ///
 /// The scheduler ensures that a compaction has at most options.max_compaction_sources. The scheduler
-/// rejects compactions that violate one of the compaction checkers defined above.
+/// rejects compactions that violate one of the compaction checkers defined above. When several
+/// series of sorted runs are eligible, options.priority decides which one is compacted first.
 pub(crate) struct SizeTieredCompactionScheduler {
     options: SizeTieredCompactionSchedulerOptions,
     max_concurrent_compactions: usize,
@@ -348,6 +353,7 @@ impl SizeTieredCompactionScheduler {
         }
 
         // try to compact the lower levels
+        let mut best: Option<(VecDeque<CompactionSource>, f64)> = None;
         for i in 0..tree.srs.len() {
             let compactable_run = Self::build_compactable_run(
                 self.options.include_size_threshold,
@@ -358,15 +364,25 @@ impl SizeTieredCompactionScheduler {
             let compactable_run = self.clamp_min(compactable_run);
             if let Some(mut compactable_run) = compactable_run {
                 compactable_run = self.clamp_max(compactable_run);
-                let dst = compactable_run
-                    .back()
-                    .expect("expected non-empty compactable run")
-                    .source
-                    .unwrap_sorted_run();
-                return Some(self.create_compaction(&tree.prefix, compactable_run, dst));
+                if self.options.priority != CompactionPriority::DeleteDensity {
+                    best = Some((compactable_run, 0.0));
+                    break;
+                }
+                // ties go to the newer runs, as with the default priority
+                let density = delete_density(&compactable_run);
+                if best.as_ref().is_none_or(|(_, best)| density > *best) {
+                    best = Some((compactable_run, density));
+                }
             }
         }
-        None
+        best.map(|(compactable_run, _)| {
+            let dst = compactable_run
+                .back()
+                .expect("expected non-empty compactable run")
+                .source
+                .unwrap_sorted_run();
+            self.create_compaction(&tree.prefix, compactable_run, dst)
+        })
     }
 
     fn clamp_min(&self, sources: VecDeque<CompactionSource>) -> Option<VecDeque<CompactionSource>> {
@@ -427,6 +443,17 @@ impl SizeTieredCompactionScheduler {
     }
 }
 
+/// Returns the ratio of delete entries to entries across `sources`, or 0 if
+/// they have no recorded entries.
+fn delete_density(sources: &VecDeque<CompactionSource>) -> f64 {
+    let num_entries: u64 = sources.iter().map(|src| src.num_entries).sum();
+    let num_deletes: u64 = sources.iter().map(|src| src.num_deletes).sum();
+    if num_entries == 0 {
+        return 0.0;
+    }
+    num_deletes as f64 / num_entries as f64
+}
+
 /// Collects L0 and sorted-run sources for a single tree (the empty-prefix
 /// segment or one named segment).
 fn compaction_sources(tree: &LsmTreeState) -> (Vec<CompactionSource>, Vec<CompactionSource>) {
@@ -436,6 +463,8 @@ fn compaction_sources(tree: &LsmTreeState) -> (Vec<CompactionSource>, Vec<Compac
         .map(|view| CompactionSource {
             source: SourceId::SstView(view.id),
             size: view.estimate_size(),
+            num_entries: view.sst.info.num_entries,
+            num_deletes: view.sst.info.num_deletes,
         })
         .collect();
     let srs: Vec<CompactionSource> = tree
@@ -444,6 +473,8 @@ fn compaction_sources(tree: &LsmTreeState) -> (Vec<CompactionSource>, Vec<Compac
         .map(|sr| CompactionSource {
             source: SourceId::SortedRun(sr.id),
             size: sr.estimate_size(),
+            num_entries: sr.sst_views.iter().map(|v| v.sst.info.num_entries).sum(),
+            num_deletes: sr.sst_views.iter().map(|v| v.sst.info.num_deletes).sum(),
         })
         .collect();
     (l0, srs)
@@ -503,7 +534,9 @@ mod tests {
     use crate::compactor_state::{
         Compaction, CompactionSpec, Compactions, CompactorState, SourceId,
     };
-    use crate::config::{CompactorOptions, SizeTieredCompactionSchedulerOptions};
+    use crate::config::{
+        CompactionPriority, CompactorOptions, SizeTieredCompactionSchedulerOptions,
+    };
     use crate::db_state::{SortedRun, SsTableHandle, SsTableId, SsTableInfo, SsTableView};
     use crate::format::sst::SST_FORMAT_VERSION_LATEST;
     use crate::manifest::store::test_utils::new_dirty_manifest;
@@ -636,6 +669,39 @@ mod tests {
         assert_eq!(compaction.clone(), expected_compaction,)
     }
 
+    #[test]
+    fn test_should_prioritize_srs_with_highest_delete_density() {
+        // given: two eligible series of runs, the older one mostly deletes
+        let state = &create_compactor_state(create_db_state(
+            VecDeque::new(),
+            vec![
+                with_deletes(create_sr2(3, 2), 100, 0),
+                with_deletes(create_sr2(2, 2), 100, 10),
+                with_deletes(create_sr2(1, 100), 100, 90),
+                with_deletes(create_sr2(0, 100), 100, 80),
+            ],
+        ));
+        let options = SizeTieredCompactionSchedulerOptions {
+            min_compaction_sources: 2,
+            priority: CompactionPriority::DeleteDensity,
+            ..Default::default()
+        };
+
+        // when:
+        let compactions = SizeTieredCompactionScheduler::new(options, 1).propose(&state.into());
+
+        // then:
+        assert_eq!(compactions, vec![create_sr_compaction(vec![1, 0])]);
+
+        // the default priority compacts the newest runs first
+        let options = SizeTieredCompactionSchedulerOptions {
+            min_compaction_sources: 2,
+            ..Default::default()
+        };
+        let compactions = SizeTieredCompactionScheduler::new(options, 1).propose(&state.into());
+        assert_eq!(compactions, vec![create_sr_compaction(vec![3, 2])]);
+    }
+
     #[test]
     fn test_should_only_include_srs_if_with_similar_size() {
         // given:
@@ -978,6 +1044,14 @@ mod tests {
         }
     }
 
+    fn with_deletes(mut sr: SortedRun, num_entries: u64, num_deletes: u64) -> SortedRun {
+        for view in &mut sr.sst_views {
+            view.sst.info.num_entries = num_entries;
+            view.sst.info.num_deletes = num_deletes;
+        }
+        sr
+    }
+
     fn create_db_state(l0: VecDeque<SsTableView>, srs: Vec<SortedRun>) -> ManifestCore {
         ManifestCore {
             initialized: true,
@@ -0,0 +1,63 @@
+//! Exporting a range of the db as a standalone SST file.
+//!
+//! The file is encoded with the same format, compression and block
+//! transformer as the db's own SSTs, so another SlateDB instance configured
+//! the same way can read it. See [`crate::Db::export_sst`].
+
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::config::ExportSstOptions;
+use crate::db_iter::DbIterator;
+use crate::error::SlateDBError;
+use crate::sst_builder::EncodedSsTableBuilder;
+
+/// Describes an SST file written by [`crate::Db::export_sst`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SstExportStats {
+    /// The number of entries written to the file, counting tombstones if they
+    /// were exported.
+    pub num_entries: u64,
+    /// The size of the file in bytes.
+    pub size: u64,
+}
+
+/// Writes the entries `iter` returns to `writer` as an SST, streaming each
+/// block out as soon as it is full. `iter` must return keys in ascending
+/// order. Tombstones are written if `options.include_tombstones` is set.
+pub(crate) async fn write_sst<W>(
+    iter: &mut DbIterator,
+    mut builder: EncodedSsTableBuilder<'_>,
+    writer: &mut W,
+    options: &ExportSstOptions,
+) -> Result<SstExportStats, SlateDBError>
+where
+    W: AsyncWrite + Unpin + Send,
+{
+    let mut stats = SstExportStats::default();
+    loop {
+        let entry = if options.include_tombstones {
+            iter.next_entry_with_tombstones().await?
+        } else {
+            iter.next_entry().await?
+        };
+        let Some(entry) = entry else {
+            break;
+        };
+        builder.add(entry).await?;
+        stats.num_entries += 1;
+        while let Some(block) = builder.next_block() {
+            writer.write_all(&block.encoded_bytes).await?;
+            stats.size += block.encoded_bytes.len() as u64;
+        }
+    }
+    let encoded_sst = builder.build().await?;
+    for block in &encoded_sst.unconsumed_blocks {
+        writer.write_all(&block.encoded_bytes).await?;
+        stats.size += block.encoded_bytes.len() as u64;
+    }
+    writer.write_all(&encoded_sst.footer).await?;
+    stats.size += encoded_sst.footer.len() as u64;
+    writer.flush().await?;
+    Ok(stats)
+}
@@ -10,7 +10,7 @@ use tokio::task::JoinHandle;
 
 use crate::block_iterator::DataBlockIterator;
 use crate::bytes_range::BytesRange;
-use crate::db_state::{SsTableId, SsTableView};
+use crate::db_state::SsTableView;
 use crate::db_stats::DbStats;
 use crate::error::SlateDBError;
 use crate::filter_policy::{FilterContext, FilterQuery, NamedFilter};
@@ -310,10 +310,6 @@ impl<'a> InternalSstIterator<'a> {
         })
     }
 
-    fn table_id(&self) -> SsTableId {
-        self.view.table_as_ref().sst.id
-    }
-
     fn view(&self) -> &SstView<'a> {
         &self.view
     }
@@ -694,6 +690,13 @@ impl RowEntryIterator for InternalSstIterator<'_> {
                         // the normal seek logic.
                     }
                 }
+            } else if self.view.key_precedes(next_key)
+                && matches!(self.options.order, IterationOrder::Descending)
+            {
+                // Seeking before the start of the view range in descending
+                // order means there are no more results.
+                self.stop();
+                return Ok(());
             } else {
                 return Err(SlateDBError::SeekKeyOutOfKeyRange {
                     key: next_key.to_vec(),
@@ -779,10 +782,6 @@ impl<'a> FilterIterator<'a> {
         }
     }
 
-    fn table_id(&self) -> SsTableId {
-        self.inner.table_id()
-    }
-
     fn is_filtered_out(&self) -> bool {
         self.filter.is_filtered_out()
     }
@@ -1027,13 +1026,6 @@ impl<'a> SstIterator<'a> {
             None => Ok(None),
         }
     }
-
-    pub(crate) fn table_id(&self) -> SsTableId {
-        match &self.delegate {
-            SstIteratorDelegate::Direct(inner) => inner.table_id(),
-            SstIteratorDelegate::Filter(inner) => inner.table_id(),
-        }
-    }
 }
 
 #[async_trait]
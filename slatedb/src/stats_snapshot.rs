@@ -0,0 +1,125 @@
+//! Point-in-time statistics of an open database.
+//!
+//! [`DbStats`] gathers the sizes of the memtables and the shape of the LSM
+//! tree into one serializable struct, e.g. for a status handler to return as
+//! JSON. Unlike the metrics in [`crate::db_stats`], it's computed when
+//! [`crate::Db::stats`] is called rather than updated as the database runs.
+
+use serde::Serialize;
+
+use crate::db_state::DbStateView;
+use crate::mem_table::KVTable;
+
+/// A snapshot of a database's memtables and LSM tree. See [`crate::Db::stats`].
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DbStats {
+    /// The memtable that writes are applied to.
+    pub active_memtable: MemtableStats,
+    /// The memtables waiting to be flushed to L0, from the newest to the
+    /// oldest.
+    pub immutable_memtables: Vec<ImmutableMemtableStats>,
+    /// The highest sequence number durable in object storage.
+    pub durable_seq: u64,
+    /// An estimate of the number of keys in the database: the entries in the
+    /// memtables and SSTs added together. Every version of a key and every
+    /// tombstone is counted, so it's an upper bound on the live keys.
+    pub estimated_keys: u64,
+    /// The state of the LSM tree that compaction works on.
+    pub compaction: CompactionStats,
+}
+
+/// The size of a memtable.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct MemtableStats {
+    /// The estimated size of the entries, in bytes.
+    pub size_bytes: usize,
+    /// The number of entries, counting every version of a key.
+    pub entries: usize,
+}
+
+/// The size of an immutable memtable, and the WAL it's recovered from.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct ImmutableMemtableStats {
+    #[serde(flatten)]
+    pub memtable: MemtableStats,
+    /// The ID of the last WAL SST holding entries of the memtable.
+    pub last_wal_id: u64,
+}
+
+/// The shape of the LSM tree, across every segment.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CompactionStats {
+    /// The number of L0 SSTs waiting to be compacted.
+    pub l0_ssts: usize,
+    /// The number of sorted runs.
+    pub sorted_runs: usize,
+    /// The number of SSTs in the sorted runs.
+    pub sorted_run_ssts: usize,
+    /// The ID of the last L0 SST compacted into a sorted run, in the tree of
+    /// keys outside any segment.
+    pub last_compacted_l0_sst_id: Option<ulid::Ulid>,
+}
+
+impl MemtableStats {
+    fn new(table: &KVTable) -> Self {
+        let metadata = table.metadata();
+        Self {
+            size_bytes: metadata.entries_size_in_bytes,
+            entries: metadata.entry_num,
+        }
+    }
+}
+
+impl DbStats {
+    pub(crate) fn new(view: &DbStateView, durable_seq: u64) -> Self {
+        let active_memtable = MemtableStats::new(&view.memtable);
+        let immutable_memtables: Vec<_> = view
+            .state
+            .imm_memtable
+            .iter()
+            .map(|imm| ImmutableMemtableStats {
+                memtable: MemtableStats::new(&imm.table()),
+                last_wal_id: imm.recent_flushed_wal_id(),
+            })
+            .collect();
+
+        let core = view.state.core();
+        let mut compaction = CompactionStats {
+            l0_ssts: 0,
+            sorted_runs: 0,
+            sorted_run_ssts: 0,
+            last_compacted_l0_sst_id: core.tree.last_compacted_l0_sst_id,
+        };
+        for tree in core.trees() {
+            compaction.l0_ssts += tree.l0.len();
+            compaction.sorted_runs += tree.compacted.len();
+            compaction.sorted_run_ssts += tree
+                .compacted
+                .iter()
+                .map(|sr| sr.sst_views.len())
+                .sum::<usize>();
+        }
+
+        let sst_entries: u64 = core
+            .all_sst_views()
+            .map(|view| view.sst.info.num_entries)
+            .sum();
+        let memtable_entries = active_memtable.entries
+            + immutable_memtables
+                .iter()
+                .map(|imm| imm.memtable.entries)
+                .sum::<usize>();
+
+        Self {
+            active_memtable,
+            immutable_memtables,
+            durable_seq,
+            estimated_keys: sst_entries + memtable_entries as u64,
+            compaction,
+        }
+    }
+}
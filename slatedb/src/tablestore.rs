@@ -105,6 +105,18 @@ impl TableStore {
         }
     }
 
+    /// Returns a table store over the same SSTs that reads everything from
+    /// object storage, bypassing the cache.
+    pub(crate) fn without_cache(&self) -> Self {
+        Self {
+            object_stores: self.object_stores.clone(),
+            sst_format: self.sst_format.clone(),
+            path_resolver: self.path_resolver.clone(),
+            fp_registry: self.fp_registry.clone(),
+            cache: None,
+        }
+    }
+
     /// Get the number of blocks for a size specified in bytes.
     /// The returned value will be rounded down to the nearest block.
     pub(crate) fn bytes_to_blocks(&self, bytes: usize) -> usize {
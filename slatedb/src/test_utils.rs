@@ -8,6 +8,7 @@ use crate::format::row::SstRowCodecV0;
 use crate::iter::{IterationOrder, RowEntryIterator};
 use crate::tablestore::TableStore;
 use crate::types::{KeyValue, RowEntry, ValueDeletable};
+use crate::virtual_source::KeyValueIterator;
 use async_trait::async_trait;
 use bytes::{BufMut, Bytes, BytesMut};
 use futures::stream::BoxStream;
@@ -63,6 +64,33 @@ pub(crate) async fn assert_next<T: RowEntryIterator>(iterator: &mut T, expected_
     assert_eq!(actual_entry, expected_entry.clone())
 }
 
+/// Asserts that `actual` matches `expected` entry by entry under
+/// [`RowEntry::logically_eq`], reporting the first position where they diverge.
+pub(crate) fn assert_logically_eq(actual: &[RowEntry], expected: &[RowEntry]) {
+    for (i, (actual_entry, expected_entry)) in actual.iter().zip(expected).enumerate() {
+        assert!(
+            actual_entry.logically_eq(expected_entry),
+            "entries diverge at index {i}\n  actual: {actual_entry:?}\nexpected: {expected_entry:?}"
+        );
+    }
+    if let Some(extra) = actual.get(expected.len()) {
+        panic!(
+            "unexpected entry at index {}: {extra:?} ({} entries expected, got {})",
+            expected.len(),
+            expected.len(),
+            actual.len()
+        );
+    }
+    if let Some(missing) = expected.get(actual.len()) {
+        panic!(
+            "missing entry at index {}: {missing:?} ({} entries expected, got {})",
+            actual.len(),
+            expected.len(),
+            actual.len()
+        );
+    }
+}
+
 pub(crate) fn assert_kv(kv: &KeyValue, key: &[u8], val: &[u8]) {
     assert_eq!(kv.key, key);
     assert_eq!(kv.value, val);
@@ -115,6 +143,42 @@ impl RowEntryIterator for TestIterator {
         }
         Ok(())
     }
+
+    async fn nth(&mut self, n: usize) -> Result<Option<RowEntry>, SlateDBError> {
+        let skipped = n.min(self.entries.len());
+        for entry in self.entries.drain(..skipped) {
+            entry?;
+        }
+        self.next().await
+    }
+
+    async fn last(mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        if let Some(err) = self.entries.iter().find_map(|entry| entry.as_ref().err()) {
+            return Err(err.clone());
+        }
+        self.entries.pop_back().transpose()
+    }
+}
+
+/// A [`KeyValueIterator`] over a fixed list of entries, e.g. to stand in for
+/// a virtual source.
+pub(crate) struct VecSource(std::vec::IntoIter<(Bytes, Bytes)>);
+
+impl VecSource {
+    pub(crate) fn new(entries: &[(&'static str, &'static str)]) -> Self {
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|(key, value)| (Bytes::from(*key), Bytes::from(*value)))
+            .collect();
+        Self(entries.into_iter())
+    }
+}
+
+#[async_trait]
+impl KeyValueIterator for VecSource {
+    async fn next(&mut self) -> Result<Option<(Bytes, Bytes)>, crate::Error> {
+        Ok(self.0.next())
+    }
 }
 
 pub(crate) fn gen_rand_bytes(n: usize) -> Bytes {
@@ -1304,11 +1368,56 @@ pub(crate) fn init_test_infrastructure() {
 
 #[cfg(test)]
 mod tests {
+    use super::assert_logically_eq;
+    use crate::types::RowEntry;
     use parking_lot::Mutex;
     use std::sync::Arc;
     use std::thread;
     use std::time::Duration;
 
+    #[test]
+    fn assert_logically_eq_ignores_seq() {
+        assert_logically_eq(
+            &[
+                RowEntry::new_value(b"a", b"1", 5),
+                RowEntry::new_tombstone(b"b", 6),
+            ],
+            &[
+                RowEntry::new_value(b"a", b"1", 1),
+                RowEntry::new_tombstone(b"b", 2),
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "entries diverge at index 1")]
+    fn assert_logically_eq_reports_first_divergence() {
+        assert_logically_eq(
+            &[
+                RowEntry::new_value(b"a", b"1", 5),
+                RowEntry::new_value(b"b", b"2", 6),
+                RowEntry::new_value(b"c", b"4", 7),
+            ],
+            &[
+                RowEntry::new_value(b"a", b"1", 1),
+                RowEntry::new_tombstone(b"b", 2),
+                RowEntry::new_value(b"c", b"3", 3),
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "missing entry at index 1")]
+    fn assert_logically_eq_reports_missing_entry() {
+        assert_logically_eq(
+            &[RowEntry::new_value(b"a", b"1", 5)],
+            &[
+                RowEntry::new_value(b"a", b"1", 1),
+                RowEntry::new_value(b"b", b"2", 2),
+            ],
+        );
+    }
+
     /// Test to verify the deadlock detector is working.
     /// This test intentionally creates a deadlock and is ignored by default.
     /// Run with: cargo test --package slatedb test_deadlock_detector -- --ignored --nocapture
@@ -11,6 +11,37 @@ pub struct KeyValue {
     pub expire_ts: Option<i64>,
 }
 
+/// Returns whether a row with expiry time `expire_ts` has expired at `now`,
+/// both in milliseconds since the epoch. A row expires at its expiry time.
+pub(crate) fn is_expired(expire_ts: Option<i64>, now: i64) -> bool {
+    expire_ts.is_some_and(|expire_ts| expire_ts <= now)
+}
+
+/// What [`crate::Db::put_if`] requires of a key's current value for the put
+/// to be written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PutCondition {
+    /// The key holds no value: it was never written, or it was deleted or
+    /// has expired.
+    Absent,
+    /// The key holds exactly this value.
+    ValueEquals(Bytes),
+    /// The key's value was written with this sequence number, as returned
+    /// in [`KeyValue::seq`] by [`crate::Db::get_key_value`].
+    VersionEquals(u64),
+}
+
+impl PutCondition {
+    pub(crate) fn is_met_by(&self, current: Option<&KeyValue>) -> bool {
+        match (self, current) {
+            (PutCondition::Absent, current) => current.is_none(),
+            (PutCondition::ValueEquals(value), Some(current)) => current.value == *value,
+            (PutCondition::VersionEquals(seq), Some(current)) => current.seq == *seq,
+            (_, None) => false,
+        }
+    }
+}
+
 /// Represents a key-value pair that may be a tombstone.
 ///
 /// This is the entry type passed to compaction for each key value pair.
@@ -59,6 +90,14 @@ impl RowEntry {
         size
     }
 
+    /// Returns true if `other` holds the same key, value (or tombstone) and
+    /// expiration time as this entry. The sequence number and creation
+    /// timestamp are ignored, since they depend on when an entry was written
+    /// rather than on what it says.
+    pub fn logically_eq(&self, other: &RowEntry) -> bool {
+        self.key == other.key && self.value == other.value && self.expire_ts == other.expire_ts
+    }
+
     /// Returns the encoded size of this entry when written to an SST block.
     /// The `key_prefix_len` is the number of bytes shared with the block's first key.
     pub(crate) fn encoded_size(&self, key_prefix_len: usize) -> usize {
@@ -228,6 +267,21 @@ mod tests {
         assert_eq!(entry.encoded_size(prefix_len), expected);
     }
 
+    #[test]
+    fn logically_eq_ignores_seq_and_create_ts() {
+        let entry = RowEntry::new_value(b"key", b"value", 1).with_create_ts(10);
+
+        assert!(entry.logically_eq(&RowEntry::new_value(b"key", b"value", 7)));
+        assert!(!entry.logically_eq(&RowEntry::new_value(b"key", b"other", 1)));
+        assert!(!entry.logically_eq(&RowEntry::new_value(b"other", b"value", 1)));
+        assert!(!entry.logically_eq(&RowEntry::new_merge(b"key", b"value", 1)));
+        assert!(!entry.logically_eq(&RowEntry::new_tombstone(b"key", 1)));
+        assert!(!entry.logically_eq(&entry.with_expire_ts(20)));
+        assert!(
+            RowEntry::new_tombstone(b"key", 1).logically_eq(&RowEntry::new_tombstone(b"key", 2))
+        );
+    }
+
     #[test]
     fn encoded_size_matches_sst_row_entry() {
         let entry = RowEntry::new_value(b"prefixkey", b"value", 1);
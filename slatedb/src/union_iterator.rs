@@ -0,0 +1,324 @@
+//! Sorted unions of two scans.
+//!
+//! [`UnionIterator`] merges the entries of two [`KeyValueIterator`]s that both
+//! return their keys in ascending order into a single ascending stream. It
+//! pulls from whichever side has the smaller next key, so it runs in a single
+//! pass over each side. A key held by both sides is returned once, with the
+//! value picked by a [`UnionResolver`].
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::error::SlateDBError;
+use crate::merge_operator::MergeOperator;
+use crate::virtual_source::KeyValueIterator;
+
+/// Picks the value of a key held by a custom resolver from the key, the left
+/// value, and the right value.
+type ResolveFn = Box<dyn Fn(&Bytes, Bytes, Bytes) -> Bytes + Send + Sync>;
+
+/// How a [`UnionIterator`] resolves a key that both of its sides hold.
+pub enum UnionResolver {
+    /// The left side's value is returned.
+    PreferLeft,
+    /// The right side's value is returned.
+    PreferRight,
+    /// The value is the merge operator's result of merging the right value
+    /// into the left value, as if the right value were written after it.
+    Merge(Arc<dyn MergeOperator + Send + Sync>),
+    /// The value is the function's result for the key, the left value, and the
+    /// right value. [`KeyValueIterator`] entries don't carry sequence numbers,
+    /// so this is how to prefer the newer entry when the values record their
+    /// own version.
+    Custom(ResolveFn),
+}
+
+impl UnionResolver {
+    /// Creates a [`UnionResolver::Custom`] resolver from `f`.
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(&Bytes, Bytes, Bytes) -> Bytes + Send + Sync + 'static,
+    {
+        UnionResolver::Custom(Box::new(f))
+    }
+
+    fn resolve(&self, key: &Bytes, left: Bytes, right: Bytes) -> Result<Bytes, crate::Error> {
+        match self {
+            UnionResolver::PreferLeft => Ok(left),
+            UnionResolver::PreferRight => Ok(right),
+            UnionResolver::Merge(operator) => operator
+                .merge(key, Some(left), right)
+                .map_err(|err| SlateDBError::from(err).into()),
+            UnionResolver::Custom(f) => Ok(f(key, left, right)),
+        }
+    }
+}
+
+impl std::fmt::Debug for UnionResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnionResolver::PreferLeft => f.write_str("PreferLeft"),
+            UnionResolver::PreferRight => f.write_str("PreferRight"),
+            UnionResolver::Merge(_) => f.write_str("Merge"),
+            UnionResolver::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+/// One side of a [`UnionIterator`], with the entry read ahead from it.
+struct UnionSide {
+    name: &'static str,
+    iter: Box<dyn KeyValueIterator>,
+    peeked: Option<(Bytes, Bytes)>,
+    /// The last key read, used to check the order.
+    last_key: Option<Bytes>,
+    exhausted: bool,
+}
+
+impl UnionSide {
+    fn new(name: &'static str, iter: Box<dyn KeyValueIterator>) -> Self {
+        Self {
+            name,
+            iter,
+            peeked: None,
+            last_key: None,
+            exhausted: false,
+        }
+    }
+
+    /// Reads the next entry into `peeked` if it is empty and the side isn't
+    /// exhausted.
+    async fn fill(&mut self) -> Result<(), crate::Error> {
+        if self.peeked.is_some() || self.exhausted {
+            return Ok(());
+        }
+        let Some((key, value)) = self.iter.next().await? else {
+            self.exhausted = true;
+            return Ok(());
+        };
+        if let Some(last_key) = &self.last_key {
+            if key <= *last_key {
+                return Err(crate::Error::invalid(format!(
+                    "{} side of union returned key {:?} out of order after {:?}",
+                    self.name, key, last_key
+                )));
+            }
+        }
+        self.last_key = Some(key.clone());
+        self.peeked = Some((key, value));
+        Ok(())
+    }
+}
+
+/// Unions two [`KeyValueIterator`]s into one stream in ascending key order.
+/// See the module docs.
+///
+/// Each side must return strictly ascending keys. A `UnionIterator` is itself
+/// a [`KeyValueIterator`], so unions can be nested.
+pub struct UnionIterator {
+    left: UnionSide,
+    right: UnionSide,
+    resolver: UnionResolver,
+}
+
+impl UnionIterator {
+    /// Creates the union of `left` and `right`, neither of which should have
+    /// been advanced yet. `resolver` picks the value of a key both hold.
+    pub fn new<L, R>(left: L, right: R, resolver: UnionResolver) -> Self
+    where
+        L: KeyValueIterator + 'static,
+        R: KeyValueIterator + 'static,
+    {
+        Self {
+            left: UnionSide::new("left", Box::new(left)),
+            right: UnionSide::new("right", Box::new(right)),
+            resolver,
+        }
+    }
+}
+
+#[async_trait]
+impl KeyValueIterator for UnionIterator {
+    /// Returns the entry with the smallest key not returned yet, or `None`
+    /// once both sides are exhausted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::Error`] if either side or the resolver fails, or with
+    /// kind [`crate::ErrorKind::Invalid`] if either side returns its keys out
+    /// of order.
+    async fn next(&mut self) -> Result<Option<(Bytes, Bytes)>, crate::Error> {
+        self.left.fill().await?;
+        self.right.fill().await?;
+        let next = match (&self.left.peeked, &self.right.peeked) {
+            (None, None) => None,
+            (Some(_), None) => self.left.peeked.take(),
+            (None, Some(_)) => self.right.peeked.take(),
+            (Some((left_key, _)), Some((right_key, _))) => match left_key.cmp(right_key) {
+                std::cmp::Ordering::Less => self.left.peeked.take(),
+                std::cmp::Ordering::Greater => self.right.peeked.take(),
+                std::cmp::Ordering::Equal => {
+                    let (key, left) = self.left.peeked.take().expect("left entry was peeked");
+                    let (_, right) = self.right.peeked.take().expect("right entry was peeked");
+                    let value = self.resolver.resolve(&key, left, right)?;
+                    Some((key, value))
+                }
+            },
+        };
+        Ok(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge_operator::MergeOperatorError;
+    use crate::test_utils::VecSource;
+    use crate::{Db, ErrorKind};
+    use object_store::memory::InMemory;
+
+    /// Concatenates the values of a key, separated by `+`.
+    struct ConcatMergeOperator;
+
+    impl MergeOperator for ConcatMergeOperator {
+        fn merge(
+            &self,
+            _key: &Bytes,
+            existing_value: Option<Bytes>,
+            value: Bytes,
+        ) -> Result<Bytes, MergeOperatorError> {
+            let mut merged = existing_value.map(|v| v.to_vec()).unwrap_or_default();
+            if !merged.is_empty() {
+                merged.push(b'+');
+            }
+            merged.extend_from_slice(&value);
+            Ok(Bytes::from(merged))
+        }
+    }
+
+    async fn collect(mut iter: impl KeyValueIterator) -> Vec<(String, String)> {
+        let mut entries = Vec::new();
+        while let Some((key, value)) = iter.next().await.unwrap() {
+            entries.push((
+                String::from_utf8(key.to_vec()).unwrap(),
+                String::from_utf8(value.to_vec()).unwrap(),
+            ));
+        }
+        entries
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn left() -> VecSource {
+        VecSource::new(&[("a", "l1"), ("c", "l3"), ("d", "l4"), ("f", "l6")])
+    }
+
+    fn right() -> VecSource {
+        VecSource::new(&[("b", "r2"), ("c", "r3"), ("f", "r6"), ("g", "r7")])
+    }
+
+    #[tokio::test]
+    async fn should_prefer_left_on_overlapping_keys() {
+        let iter = UnionIterator::new(left(), right(), UnionResolver::PreferLeft);
+        assert_eq!(
+            collect(iter).await,
+            pairs(&[
+                ("a", "l1"),
+                ("b", "r2"),
+                ("c", "l3"),
+                ("d", "l4"),
+                ("f", "l6"),
+                ("g", "r7"),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn should_prefer_right_on_overlapping_keys() {
+        let iter = UnionIterator::new(left(), right(), UnionResolver::PreferRight);
+        assert_eq!(
+            collect(iter).await,
+            pairs(&[
+                ("a", "l1"),
+                ("b", "r2"),
+                ("c", "r3"),
+                ("d", "l4"),
+                ("f", "r6"),
+                ("g", "r7"),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn should_prefer_higher_sequence_on_overlapping_keys() {
+        // each value starts with the sequence number it was written at
+        let left = VecSource::new(&[("a", "5:l"), ("b", "2:l"), ("c", "9:l")]);
+        let right = VecSource::new(&[("b", "7:r"), ("c", "3:r"), ("d", "1:r")]);
+        let seq = |value: &Bytes| -> u64 {
+            let value = std::str::from_utf8(value).unwrap();
+            value.split(':').next().unwrap().parse().unwrap()
+        };
+        let resolver = UnionResolver::custom(move |_key, left, right| {
+            if seq(&right) > seq(&left) {
+                right
+            } else {
+                left
+            }
+        });
+        let iter = UnionIterator::new(left, right, resolver);
+        assert_eq!(
+            collect(iter).await,
+            pairs(&[("a", "5:l"), ("b", "7:r"), ("c", "9:l"), ("d", "1:r")])
+        );
+    }
+
+    #[tokio::test]
+    async fn should_merge_overlapping_keys_with_merge_operator() {
+        let iter = UnionIterator::new(
+            left(),
+            right(),
+            UnionResolver::Merge(Arc::new(ConcatMergeOperator)),
+        );
+        let entries = collect(iter).await;
+        assert_eq!(entries[2], ("c".to_string(), "l3+r3".to_string()));
+        assert_eq!(entries[4], ("f".to_string(), "l6+r6".to_string()));
+    }
+
+    #[tokio::test]
+    async fn should_union_db_scan_with_nested_union() {
+        let db = Db::open("test_db", Arc::new(InMemory::new()))
+            .await
+            .unwrap();
+        db.put(b"k/1", b"db").await.unwrap();
+        db.put(b"k/3", b"db").await.unwrap();
+
+        let scan = db.scan_prefix(b"k/").await.unwrap();
+        let overlay = UnionIterator::new(
+            VecSource::new(&[("k/2", "a")]),
+            VecSource::new(&[("k/3", "b")]),
+            UnionResolver::PreferLeft,
+        );
+        let iter = UnionIterator::new(scan, overlay, UnionResolver::PreferRight);
+        assert_eq!(
+            collect(iter).await,
+            pairs(&[("k/1", "db"), ("k/2", "a"), ("k/3", "b")])
+        );
+    }
+
+    #[tokio::test]
+    async fn should_reject_keys_out_of_order() {
+        let left = VecSource::new(&[("a", ""), ("c", ""), ("b", "")]);
+        let mut iter = UnionIterator::new(left, VecSource::new(&[]), UnionResolver::PreferLeft);
+        assert!(iter.next().await.unwrap().is_some());
+        assert!(iter.next().await.unwrap().is_some());
+        let err = iter.next().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Invalid);
+    }
+}
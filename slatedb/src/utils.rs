@@ -68,6 +68,16 @@ impl<T: Clone> WatchableOnceCellReader<T> {
         self.rx.borrow().clone()
     }
 
+    /// Like [`Self::await_value`], but returns `closed` instead of panicking
+    /// if the cell is dropped without a value being written, e.g. when its
+    /// owner is dropped during shutdown.
+    pub(crate) async fn await_value_or(&mut self, closed: T) -> T {
+        match self.rx.wait_for(|v| v.is_some()).await {
+            Ok(value) => value.clone().unwrap_or(closed),
+            Err(_) => closed,
+        }
+    }
+
     pub(crate) async fn await_value(&mut self) -> T {
         self.rx
             .wait_for(|v| v.is_some())
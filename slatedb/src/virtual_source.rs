@@ -0,0 +1,269 @@
+//! Merging the entries of a user-provided source into a scan. See
+//! [`crate::Db::scan_with_source`].
+
+use std::cmp::Ordering;
+use std::ops::{Bound, RangeBounds};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::bytes_range::BytesRange;
+use crate::error::SlateDBError;
+use crate::iter::{IterationOrder, RowEntryIterator};
+use crate::types::{RowEntry, ValueDeletable};
+
+/// A source of key-value pairs that is merged into a scan by
+/// [`crate::Db::scan_with_source`].
+///
+/// The source must return its keys in the order of the scan, which is
+/// ascending unless [`crate::config::ScanOptions::order`] is descending, and
+/// must not return a key more than once. It may return keys outside the
+/// scanned range, which are skipped.
+#[async_trait]
+pub trait KeyValueIterator: Send + Sync {
+    /// Returns the next key and value, or `None` once the source is exhausted.
+    async fn next(&mut self) -> Result<Option<(Bytes, Bytes)>, crate::Error>;
+}
+
+/// Which entry a scan returns when a [`KeyValueIterator`] and the database
+/// both hold a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourcePriority {
+    /// The source's value shadows the database's value or tombstone.
+    Highest,
+    /// The database's value or tombstone shadows the source's value.
+    Lowest,
+}
+
+/// Adapts a [`KeyValueIterator`] to a [`RowEntryIterator`] over a key range,
+/// checking that the source returns its keys in order.
+pub(crate) struct VirtualSourceIterator {
+    source: Box<dyn KeyValueIterator>,
+    range: BytesRange,
+    order: IterationOrder,
+    /// The sequence number given to the source's entries, so that they read
+    /// as newer or older than every version in the database.
+    seq: u64,
+    /// The last key read from the source, used to check the order.
+    last_key: Option<Bytes>,
+    /// An entry read ahead by a seek, returned by the next call to `next`.
+    peeked: Option<RowEntry>,
+    exhausted: bool,
+}
+
+impl VirtualSourceIterator {
+    pub(crate) fn new(
+        source: Box<dyn KeyValueIterator>,
+        range: BytesRange,
+        order: IterationOrder,
+        priority: SourcePriority,
+    ) -> Self {
+        let seq = match priority {
+            SourcePriority::Highest => u64::MAX,
+            SourcePriority::Lowest => 0,
+        };
+        Self {
+            source,
+            range,
+            order,
+            seq,
+            last_key: None,
+            peeked: None,
+            exhausted: false,
+        }
+    }
+
+    /// Returns true if `key` comes before `other` in the scan order.
+    fn precedes(&self, key: &[u8], other: &[u8]) -> bool {
+        match self.order {
+            IterationOrder::Ascending => key < other,
+            IterationOrder::Descending => key > other,
+        }
+    }
+
+    /// Returns true if `key`, and so every key after it in the scan order, is
+    /// past the end of the range.
+    fn past_range(&self, key: &[u8]) -> bool {
+        match self.order {
+            IterationOrder::Ascending => match self.range.end_bound() {
+                Bound::Included(end) => key > end.as_ref(),
+                Bound::Excluded(end) => key >= end.as_ref(),
+                Bound::Unbounded => false,
+            },
+            IterationOrder::Descending => match self.range.start_bound() {
+                Bound::Included(start) => key < start.as_ref(),
+                Bound::Excluded(start) => key <= start.as_ref(),
+                Bound::Unbounded => false,
+            },
+        }
+    }
+
+    async fn next_from_source(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        while !self.exhausted {
+            let next = self
+                .source
+                .next()
+                .await
+                .map_err(|err| SlateDBError::VirtualSourceError(Arc::new(err)))?;
+            let Some((key, value)) = next else {
+                self.exhausted = true;
+                break;
+            };
+            if let Some(last_key) = &self.last_key {
+                if !self.precedes(last_key, &key) {
+                    return Err(SlateDBError::VirtualSourceError(Arc::new(
+                        crate::Error::invalid(format!(
+                            "virtual source returned key {:?} out of order after {:?}",
+                            key, last_key
+                        )),
+                    )));
+                }
+            }
+            self.last_key = Some(key.clone());
+            if self.range.contains(&key) {
+                return Ok(Some(RowEntry::new(
+                    key,
+                    ValueDeletable::Value(value),
+                    self.seq,
+                    None,
+                    None,
+                )));
+            }
+            if self.past_range(&key) {
+                self.exhausted = true;
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl RowEntryIterator for VirtualSourceIterator {
+    async fn init(&mut self) -> Result<(), SlateDBError> {
+        Ok(())
+    }
+
+    async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        match self.peeked.take() {
+            Some(entry) => Ok(Some(entry)),
+            None => self.next_from_source().await,
+        }
+    }
+
+    async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
+        loop {
+            let entry = match self.peeked.take() {
+                Some(entry) => entry,
+                None => match self.next_from_source().await? {
+                    Some(entry) => entry,
+                    None => return Ok(()),
+                },
+            };
+            if !self.precedes(&entry.key, next_key) {
+                self.peeked = Some(entry);
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Merges the entries of two iterators by key. When both hold a key, only the
+/// entries of `primary` are returned for it.
+pub(crate) struct PrioritizedMergeIterator {
+    primary: Box<dyn RowEntryIterator + 'static>,
+    secondary: Box<dyn RowEntryIterator + 'static>,
+    order: IterationOrder,
+    /// The next entry of each iterator, read ahead to compare keys.
+    primary_next: Option<RowEntry>,
+    secondary_next: Option<RowEntry>,
+    /// Whether `primary_next` and `secondary_next` have been read since the
+    /// last seek.
+    primed: bool,
+}
+
+impl PrioritizedMergeIterator {
+    pub(crate) fn new(
+        primary: Box<dyn RowEntryIterator + 'static>,
+        secondary: Box<dyn RowEntryIterator + 'static>,
+        order: IterationOrder,
+    ) -> Self {
+        Self {
+            primary,
+            secondary,
+            order,
+            primary_next: None,
+            secondary_next: None,
+            primed: false,
+        }
+    }
+
+    async fn prime(&mut self) -> Result<(), SlateDBError> {
+        if !self.primed {
+            self.primary_next = self.primary.next().await?;
+            self.secondary_next = self.secondary.next().await?;
+            self.primed = true;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RowEntryIterator for PrioritizedMergeIterator {
+    async fn init(&mut self) -> Result<(), SlateDBError> {
+        self.primary.init().await?;
+        self.secondary.init().await
+    }
+
+    async fn next(&mut self) -> Result<Option<RowEntry>, SlateDBError> {
+        self.prime().await?;
+        let ordering = match (&self.primary_next, &self.secondary_next) {
+            (None, None) => return Ok(None),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(primary), Some(secondary)) => match self.order {
+                IterationOrder::Ascending => primary.key.cmp(&secondary.key),
+                IterationOrder::Descending => secondary.key.cmp(&primary.key),
+            },
+        };
+        if ordering == Ordering::Equal {
+            // the primary shadows every entry the secondary holds for the key
+            let key = self.primary_next.as_ref().map(|entry| entry.key.clone());
+            while self.secondary_next.is_some()
+                && self.secondary_next.as_ref().map(|entry| &entry.key) == key.as_ref()
+            {
+                self.secondary_next = self.secondary.next().await?;
+            }
+        }
+        if ordering == Ordering::Greater {
+            let next = self.secondary.next().await?;
+            Ok(std::mem::replace(&mut self.secondary_next, next))
+        } else {
+            let next = self.primary.next().await?;
+            Ok(std::mem::replace(&mut self.primary_next, next))
+        }
+    }
+
+    async fn seek(&mut self, next_key: &[u8]) -> Result<(), SlateDBError> {
+        let order = self.order;
+        let primed = self.primed;
+        // an entry already read ahead is kept if the seek doesn't skip it,
+        // since the iterator it came from has moved past it
+        for (iter, next) in [
+            (&mut self.primary, &mut self.primary_next),
+            (&mut self.secondary, &mut self.secondary_next),
+        ] {
+            let keep = primed
+                && next.as_ref().is_none_or(|entry| match order {
+                    IterationOrder::Ascending => entry.key.as_ref() >= next_key,
+                    IterationOrder::Descending => entry.key.as_ref() <= next_key,
+                });
+            if !keep {
+                iter.seek(next_key).await?;
+                *next = iter.next().await?;
+            }
+        }
+        self.primed = true;
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config::{MemtableType, ReplayPolicy, DEFAULT_MEMTABLE_FILTER_BITS};
 use crate::db_state::SsTableId;
 use crate::error::SlateDBError;
 use crate::iter::RowEntryIterator;
@@ -6,7 +7,9 @@ use crate::manifest::SsTableView;
 use crate::mem_table::WritableKVTable;
 use crate::sst_iter::{SstIterator, SstIteratorOptions};
 use crate::tablestore::TableStore;
+use crate::types::RowEntry;
 use crate::utils::panic_string;
+use crate::ErrorKind;
 use log::error;
 use std::collections::VecDeque;
 use std::ops::Range;
@@ -29,6 +32,15 @@ pub(crate) struct WalReplayOptions {
     /// The minimum seq number to replay. If unset, will replay all
     /// entries after `last_l0_seq` in the manifest.
     pub(crate) min_seq: Option<u64>,
+
+    /// The type of memtable to replay entries into.
+    pub(crate) memtable_type: MemtableType,
+
+    /// The size of the key filter of the memtables replayed into.
+    pub(crate) memtable_filter_bits: usize,
+
+    /// What to do with a WAL SST that can't be read.
+    pub(crate) policy: ReplayPolicy,
 }
 
 impl Default for WalReplayOptions {
@@ -38,6 +50,9 @@ impl Default for WalReplayOptions {
             max_memtable_bytes: 64 * 1024 * 1024,
             sst_iter_options: SstIteratorOptions::default(),
             min_seq: None,
+            memtable_type: MemtableType::default(),
+            memtable_filter_bits: DEFAULT_MEMTABLE_FILTER_BITS,
+            policy: ReplayPolicy::default(),
         }
     }
 }
@@ -47,6 +62,10 @@ pub(crate) struct ReplayedMemtable {
     pub(crate) last_tick: i64,
     pub(crate) last_seq: u64,
     pub(crate) last_wal_id: u64,
+    /// The WAL SSTs up to `last_wal_id` that couldn't be read and were skipped,
+    /// or truncated, under [`WalReplayOptions::policy`], with the error that
+    /// made each unreadable. A truncated replay lists only the first.
+    pub(crate) bad_wals: Vec<(u64, SlateDBError)>,
 }
 
 struct IteratorHolder<T> {
@@ -77,12 +96,15 @@ impl<T> IteratorHolder<T> {
     }
 }
 
+type LoadWalHandle<'a> = JoinHandle<Result<Option<SstIterator<'a>>, SlateDBError>>;
+
 pub(crate) struct WalReplayIterator<'a> {
     options: WalReplayOptions,
     wal_id_range: Range<u64>,
     table_store: Arc<TableStore>,
-    current_iter: IteratorHolder<SstIterator<'a>>,
-    next_iters: VecDeque<JoinHandle<Result<Option<SstIterator<'a>>, SlateDBError>>>,
+    current_iter: IteratorHolder<LoadedWal<'a>>,
+    /// The WAL SSTs being opened ahead of replay, by WAL ID.
+    next_iters: VecDeque<(u64, LoadWalHandle<'a>)>,
     last_tick: i64,
     last_seq: u64,
     min_seq: u64,
@@ -172,15 +194,23 @@ impl WalReplayIterator<'_> {
             self.options.sst_iter_options.clone(),
             Arc::clone(&self.table_store),
         ));
-        self.next_iters.push_back(handle);
+        self.next_iters.push_back((next_wal_id, handle));
         true
     }
 
     async fn advance_current_iter(&mut self) -> Result<(), SlateDBError> {
-        let next_iter = if let Some(join_handle) = self.next_iters.pop_front() {
+        let next_iter = if let Some((wal_id, join_handle)) = self.next_iters.pop_front() {
             match join_handle.await {
-                Ok(Ok(sst_iter)) => sst_iter,
-                Ok(Err(slate_err)) => return Err(slate_err),
+                Ok(Ok(sst_iter)) => sst_iter.map(|iter| LoadedWal {
+                    wal_id,
+                    iter: Ok(iter),
+                }),
+                // the policy decides what to do with an SST that can't be opened
+                // once replay gets to it
+                Ok(Err(slate_err)) => Some(LoadedWal {
+                    wal_id,
+                    iter: Err(slate_err),
+                }),
                 Err(join_err) => {
                     let task_name = format!("wal_replay[{:?}]", self.wal_id_range);
                     if let Ok(panic_err) = join_err.try_into_panic() {
@@ -216,27 +246,52 @@ impl WalReplayIterator<'_> {
             return Ok(None);
         }
 
-        let table = WritableKVTable::new();
+        let mut table = WritableKVTable::new_with_type(
+            self.options.memtable_type,
+            self.options.memtable_filter_bits,
+        );
         let mut last_wal_id = 0;
+        let mut bad_wals = Vec::new();
 
         while !self.current_iter.is_finished() {
-            if let Some(sst_iter) = &mut self.current_iter.current_iter {
-                let wal_id = sst_iter.table_id().unwrap_wal_id();
-                while let Some(row_entry) = sst_iter.next().await? {
-                    // skip the entries that are already in the L0 SST.
-                    if row_entry.seq <= self.min_seq {
-                        continue;
-                    }
-
-                    if let Some(ts) = row_entry.create_ts {
-                        self.last_tick = self.last_tick.max(ts);
+            if let Some(loaded) = &mut self.current_iter.current_iter {
+                let wal_id = loaded.wal_id;
+                let entries = match &mut loaded.iter {
+                    Ok(sst_iter) => Self::read_wal(sst_iter, self.min_seq).await,
+                    Err(err) => Err(err.clone()),
+                };
+                match entries {
+                    Ok(entries) => {
+                        // replayed entries are never rejected, see
+                        // `OutOfOrderWritePolicy::Reject`
+                        table.prepare_for(&entries);
+                        for row_entry in entries {
+                            if let Some(ts) = row_entry.create_ts {
+                                self.last_tick = self.last_tick.max(ts);
+                            }
+                            self.last_seq = self.last_seq.max(row_entry.seq);
+                            table.put(row_entry);
+                        }
+                        last_wal_id = wal_id;
                     }
-                    self.last_seq = self.last_seq.max(row_entry.seq);
-                    table.put(row_entry);
+                    Err(err) if !Self::is_bad_wal(&err) => return Err(err),
+                    Err(err) => match self.options.policy {
+                        ReplayPolicy::Strict => return Err(err),
+                        ReplayPolicy::SkipBad => {
+                            bad_wals.push((wal_id, err));
+                            last_wal_id = wal_id;
+                        }
+                        ReplayPolicy::Truncate => {
+                            bad_wals.push((wal_id, err));
+                            // account for every remaining WAL SST as replayed so
+                            // that none of them is replayed after a restart
+                            last_wal_id = self.wal_id_range.end - 1;
+                            self.truncate();
+                            break;
+                        }
+                    },
                 }
 
-                last_wal_id = wal_id;
-
                 let meta = table.metadata();
                 let estimated_bytes = self
                     .table_store
@@ -257,23 +312,64 @@ impl WalReplayIterator<'_> {
                 last_tick: self.last_tick,
                 last_seq: self.last_seq,
                 last_wal_id,
+                bad_wals,
             }))
         } else {
             Ok(None)
         }
     }
+
+    /// Reads the entries of a WAL SST that aren't already in an L0 SST. The
+    /// whole SST is read before any entry is applied, so that an SST that turns
+    /// out to be unreadable part way through can be skipped as a unit.
+    async fn read_wal(
+        sst_iter: &mut SstIterator<'_>,
+        min_seq: u64,
+    ) -> Result<Vec<RowEntry>, SlateDBError> {
+        let mut entries = Vec::new();
+        while let Some(row_entry) = sst_iter.next().await? {
+            // skip the entries that are already in the L0 SST.
+            if row_entry.seq > min_seq {
+                entries.push(row_entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Returns true if `err` means a WAL SST's contents are bad, as opposed to
+    /// a failure to fetch it that may succeed on retry.
+    fn is_bad_wal(err: &SlateDBError) -> bool {
+        crate::Error::from(err.clone()).kind() == ErrorKind::Data
+    }
+
+    /// Stops replay after the current WAL SST.
+    fn truncate(&mut self) {
+        for (_, handle) in self.next_iters.drain(..) {
+            handle.abort();
+        }
+        self.next_wal_id = self.wal_id_range.end;
+        self.current_iter.advance(None);
+    }
+}
+
+/// A WAL SST opened for replay, or the error that kept it from being opened.
+struct LoadedWal<'a> {
+    wal_id: u64,
+    iter: Result<SstIterator<'a>, SlateDBError>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::{WalReplayIterator, WalReplayOptions};
     use crate::bytes_range::BytesRange;
+    use crate::config::ReplayPolicy;
     use crate::db_state::SsTableId;
     use crate::format::sst::SsTableFormat;
     use crate::iter::{IterationOrder, RowEntryIterator};
     use crate::manifest::ManifestCore;
     use crate::mem_table::WritableKVTable;
     use crate::object_stores::ObjectStores;
+    use crate::paths::PathResolver;
     use crate::proptest_util::{rng, sample};
     use crate::tablestore::TableStore;
     use crate::types::RowEntry;
@@ -284,6 +380,7 @@ mod tests {
     use object_store::ObjectStore;
     use proptest::test_runner::TestRng;
     use rand::Rng;
+    use rstest::rstest;
     use std::cmp::min;
     use std::collections::btree_map::Iter;
     use std::collections::BTreeMap;
@@ -678,6 +775,95 @@ mod tests {
         assert_eq!(total, 500);
     }
 
+    #[rstest]
+    #[case::strict_trailing(ReplayPolicy::Strict, 3, None)]
+    #[case::skip_bad_trailing(ReplayPolicy::SkipBad, 3, Some(vec!["a", "b", "c"]))]
+    #[case::truncate_trailing(ReplayPolicy::Truncate, 3, Some(vec!["a", "b", "c"]))]
+    #[case::skip_bad_middle(ReplayPolicy::SkipBad, 2, Some(vec!["a", "b", "d"]))]
+    #[case::truncate_middle(ReplayPolicy::Truncate, 2, Some(vec!["a", "b"]))]
+    #[tokio::test]
+    async fn should_handle_corrupted_wal_per_replay_policy(
+        #[case] policy: ReplayPolicy,
+        #[case] corrupted_wal_id: u64,
+        #[case] expected_keys: Option<Vec<&str>>,
+    ) {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = Path::from("/tmp/test_kv_store");
+        let table_store = Arc::new(TableStore::new(
+            ObjectStores::new(object_store.clone(), None),
+            SsTableFormat::default(),
+            path.clone(),
+            None,
+        ));
+        let wals: [&[&str]; 3] = [&["a", "b"], &["c"], &["d"]];
+        let mut next_seq = 1;
+        for (wal_id, keys) in (1..).zip(wals) {
+            let entries: BTreeMap<Bytes, Bytes> = keys
+                .iter()
+                .map(|key| (Bytes::from(*key), Bytes::from_static(b"value")))
+                .collect();
+            next_seq = write_wal(
+                wal_id,
+                next_seq,
+                &mut entries.iter(),
+                entries.len(),
+                Arc::clone(&table_store),
+            )
+            .await
+            .unwrap();
+        }
+        let corrupted_path = PathResolver::new(path).table_path(&SsTableId::Wal(corrupted_wal_id));
+        let mut corrupted = object_store
+            .get(&corrupted_path)
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap()
+            .to_vec();
+        corrupted[0] ^= 0x01;
+        object_store
+            .put(&corrupted_path, corrupted.into())
+            .await
+            .unwrap();
+
+        let mut replay_iter = WalReplayIterator::new(
+            &ManifestCore::new(),
+            WalReplayOptions {
+                policy,
+                ..WalReplayOptions::default()
+            },
+            Arc::clone(&table_store),
+        )
+        .await
+        .unwrap();
+
+        let Some(expected_keys) = expected_keys else {
+            let Err(err) = replay_iter.next().await else {
+                panic!("expected replay of a corrupted WAL to fail");
+            };
+            assert!(matches!(err, SlateDBError::ChecksumMismatch { .. }));
+            return;
+        };
+        let replayed = replay_iter.next().await.unwrap().unwrap();
+        let mut iter = replayed.table.table().iter();
+        let mut keys = Vec::new();
+        while let Some(entry) = iter.next_sync() {
+            keys.push(entry.key);
+        }
+        assert_eq!(keys, expected_keys);
+        // the corrupted WAL, and with truncation every later one, counts as
+        // replayed so that it isn't replayed again
+        assert_eq!(replayed.last_wal_id, 3);
+        let bad_wal_ids: Vec<u64> = replayed.bad_wals.iter().map(|(id, _)| *id).collect();
+        assert_eq!(bad_wal_ids, vec![corrupted_wal_id]);
+        assert!(matches!(
+            replayed.bad_wals[0].1,
+            SlateDBError::ChecksumMismatch { .. }
+        ));
+        assert!(replay_iter.next().await.unwrap().is_none());
+    }
+
     fn test_table_store() -> Arc<TableStore> {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
         let path = Path::from("/tmp/test_kv_store");
@@ -115,6 +115,7 @@ mod composite_filters {
         let write = WriteOptions {
             await_durable: false,
             seqnum: 0,
+            tenant: None,
         };
         // Write each batch in its own SST so multiple SSTs participate in the
         // read path and the filter has something to actually skip.
@@ -284,6 +285,7 @@ mod prop_test {
         let write_opts = WriteOptions {
             await_durable: false,
             seqnum: 0,
+            tenant: None,
         };
         for (i, key) in keys.iter().enumerate() {
             let value = format!("v{}", i).into_bytes();